@@ -0,0 +1,325 @@
+//! Per-type size analysis of archives.
+//!
+//! [`analyze`] walks an archive from its root and builds a [`TypeSizeReport`]
+//! with the number of values of each archived type and the bytes that they
+//! use. Comparing reports for the same data before and after a code change
+//! shows which types grew, which makes it possible to track the size of
+//! individual components of an archive over time.
+
+use core::{
+    any::type_name,
+    marker::{PhantomData, PhantomPinned},
+    mem::size_of_val,
+    num::{NonZeroI8, NonZeroU8},
+};
+#[cfg(feature = "std")]
+use std::collections::{hash_map, HashSet};
+
+#[cfg(not(feature = "std"))]
+use hashbrown::{hash_map, HashSet};
+#[cfg(feature = "bytecheck")]
+use rancor::{Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    primitive::{
+        ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+        ArchivedI32, ArchivedI64, ArchivedNonZeroI128, ArchivedNonZeroI16,
+        ArchivedNonZeroI32, ArchivedNonZeroI64, ArchivedNonZeroU128,
+        ArchivedNonZeroU16, ArchivedNonZeroU32, ArchivedNonZeroU64,
+        ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64,
+    },
+    Portable,
+};
+
+/// An archived type which can record the values reachable from it in a
+/// [`TypeSizeReport`].
+///
+/// Implementations should [`record`](TypeSizeReport::record) each of their
+/// fields, and [`record_out_of_line`](TypeSizeReport::record_out_of_line) each
+/// of the values that they point to.
+///
+/// This trait can be derived for archived types with
+/// `#[archive_attr(derive(Analyze))]`.
+pub trait Analyze {
+    /// Records the fields of this value and the values that it points to.
+    fn analyze(&self, report: &mut TypeSizeReport);
+}
+
+macro_rules! impl_leaf {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Analyze for $ty {
+                #[inline]
+                fn analyze(&self, _: &mut TypeSizeReport) {}
+            }
+        )*
+    };
+}
+
+impl_leaf!(
+    (),
+    bool,
+    i8,
+    u8,
+    NonZeroI8,
+    NonZeroU8,
+    PhantomPinned,
+    ArchivedI16,
+    ArchivedI32,
+    ArchivedI64,
+    ArchivedI128,
+    ArchivedU16,
+    ArchivedU32,
+    ArchivedU64,
+    ArchivedU128,
+    ArchivedF32,
+    ArchivedF64,
+    ArchivedChar,
+    ArchivedNonZeroI16,
+    ArchivedNonZeroI32,
+    ArchivedNonZeroI64,
+    ArchivedNonZeroI128,
+    ArchivedNonZeroU16,
+    ArchivedNonZeroU32,
+    ArchivedNonZeroU64,
+    ArchivedNonZeroU128,
+    str,
+);
+
+impl<T: ?Sized> Analyze for PhantomData<T> {
+    #[inline]
+    fn analyze(&self, _: &mut TypeSizeReport) {}
+}
+
+impl<T: Analyze, const N: usize> Analyze for [T; N] {
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        for value in self.iter() {
+            report.record(value);
+        }
+    }
+}
+
+impl<T: Analyze> Analyze for [T] {
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        for value in self.iter() {
+            report.record(value);
+        }
+    }
+}
+
+/// The sizes recorded for a single type in a [`TypeSizeReport`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeSize {
+    count: usize,
+    bytes: usize,
+    total_bytes: usize,
+}
+
+impl TypeSize {
+    /// Returns the number of values of the type.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the number of bytes used by the values themselves.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of bytes used by the values and all of the
+    /// out-of-line data reachable from them.
+    #[inline]
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+}
+
+/// A histogram of the bytes used by each type in an archive.
+///
+/// Types are identified by their [`type_name`]. Values which are stored inline
+/// in other values are counted for both types, so the sizes of all of the
+/// types in a report add up to more than the size of the archive. Values which
+/// are reachable through more than one shared pointer are only counted once.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{analyze, rancor::Error, to_bytes, Analyze, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(Analyze))]
+/// struct Mesh {
+///     name: String,
+///     vertices: Vec<[f32; 3]>,
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(Analyze))]
+/// struct Scene {
+///     meshes: Vec<Mesh>,
+/// }
+///
+/// let scene = Scene {
+///     meshes: vec![Mesh {
+///         name: "a mesh with a long name".to_string(),
+///         vertices: vec![[0.0; 3]; 100],
+///     }],
+/// };
+/// let bytes = to_bytes::<Error>(&scene).unwrap();
+/// let report = analyze::<ArchivedScene, Error>(&bytes).unwrap();
+///
+/// let meshes = report.get::<ArchivedMesh>().unwrap();
+/// assert_eq!(meshes.count(), 1);
+/// assert!(meshes.total_bytes() > 100 * 3 * 4);
+/// assert!(report.total_bytes() <= bytes.len());
+/// ```
+#[derive(Debug, Default)]
+pub struct TypeSizeReport {
+    types: hash_map::HashMap<&'static str, TypeSize>,
+    shared: HashSet<usize>,
+    out_of_line: usize,
+    depth: usize,
+    total_bytes: usize,
+}
+
+impl TypeSizeReport {
+    /// Returns a new, empty report.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a report for the given archived value and all of the values
+    /// reachable from it.
+    pub fn from_value<T: Analyze + ?Sized>(value: &T) -> Self {
+        let mut report = Self::new();
+        report.record(value);
+        report
+    }
+
+    /// Records a value which is stored inline in the value being analyzed.
+    pub fn record<T: Analyze + ?Sized>(&mut self, value: &T) {
+        let start = self.out_of_line;
+        self.depth += 1;
+        value.analyze(self);
+        self.depth -= 1;
+
+        let bytes = size_of_val(value);
+        let total_bytes = bytes + (self.out_of_line - start);
+        let entry = self.types.entry(type_name::<T>()).or_default();
+        entry.count += 1;
+        entry.bytes += bytes;
+        entry.total_bytes += total_bytes;
+
+        if self.depth == 0 {
+            self.total_bytes += total_bytes;
+        }
+    }
+
+    /// Records a value which is pointed to by the value being analyzed.
+    #[inline]
+    pub fn record_out_of_line<T: Analyze + ?Sized>(&mut self, value: &T) {
+        self.out_of_line += size_of_val(value);
+        self.record(value);
+    }
+
+    /// Records a value which is pointed to by a shared pointer in the value
+    /// being analyzed.
+    ///
+    /// The value is only recorded the first time that it is encountered.
+    pub fn record_shared<T: Analyze + ?Sized>(&mut self, value: &T) {
+        let address = value as *const T as *const u8 as usize;
+        if self.shared.insert(address) {
+            self.record_out_of_line(value);
+        }
+    }
+
+    /// Records out-of-line bytes used by the value being analyzed which do not
+    /// belong to any type, like the control bytes of a hash table.
+    #[inline]
+    pub fn record_out_of_line_bytes(&mut self, len: usize) {
+        self.out_of_line += len;
+    }
+
+    /// Returns the sizes recorded for the type `T`, if any values of it were
+    /// recorded.
+    #[inline]
+    pub fn get<T: ?Sized>(&self) -> Option<&TypeSize> {
+        self.types.get(type_name::<T>())
+    }
+
+    /// Returns the sizes recorded for the type with the given name, if any
+    /// values of it were recorded.
+    #[inline]
+    pub fn get_by_name(&self, name: &str) -> Option<&TypeSize> {
+        self.types.get(name)
+    }
+
+    /// Returns an iterator over the names of the recorded types and their
+    /// sizes.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &TypeSize)> {
+        self.types.iter().map(|(name, size)| (*name, size))
+    }
+
+    /// Returns the number of recorded types.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns whether no types have been recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Returns the total number of bytes reachable from the recorded roots.
+    ///
+    /// This does not include padding between values, so it may be less than
+    /// the size of the archive.
+    #[inline]
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+}
+
+/// Analyzes the sizes of the types in an archive.
+///
+/// The archive is validated before it is analyzed. See [`TypeSizeReport`] for
+/// an example.
+#[cfg(feature = "bytecheck")]
+pub fn analyze<T, E>(bytes: &[u8]) -> Result<TypeSizeReport, E>
+where
+    T: Analyze
+        + Portable
+        + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    let root = crate::access::<T, E>(bytes)?;
+    Ok(TypeSizeReport::from_value(root))
+}
+
+/// Analyzes the sizes of the types in an archive without validating it.
+///
+/// See [`TypeSizeReport`] for more information.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice.
+pub unsafe fn analyze_unchecked<T>(bytes: &[u8]) -> TypeSizeReport
+where
+    T: Analyze + Portable,
+{
+    // SAFETY: The caller has guaranteed that the bytes contain a `T` at the
+    // root position.
+    let root = unsafe { crate::access_unchecked::<T>(bytes) };
+    TypeSizeReport::from_value(root)
+}
@@ -0,0 +1,296 @@
+//! Read-only sources of archive bytes.
+//!
+//! Archives can live in many kinds of buffers: borrowed slices, aligned
+//! vectors, reference-counted [`Bytes`](https://docs.rs/bytes), memory maps,
+//! and shared memory. The [`Backing`] trait describes what accessing an
+//! archive needs from a buffer, so APIs which own an archive can accept any
+//! of them. [`OwnedArchive`] is the simplest of these: it keeps a backing
+//! together with the validated archive inside it.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use rkyv::{backing::OwnedArchive, rancor::Error, to_bytes, Archived};
+//!
+//! let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+//!
+//! // Owned archives can be backed by an `AlignedVec`...
+//! let archive =
+//!     OwnedArchive::<Archived<Vec<u32>>, _>::new::<Error>(bytes.clone())
+//!         .unwrap();
+//! assert_eq!(archive.len(), 3);
+//!
+//! // ...or by a buffer shared between threads.
+//! let shared = Arc::new(bytes);
+//! let archive =
+//!     OwnedArchive::<Archived<Vec<u32>>, _>::new::<Error>(shared).unwrap();
+//! assert_eq!(archive[2], 3);
+//! ```
+
+use core::{fmt, marker::PhantomData, ops::Deref};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "bytecheck")]
+use rancor::Source;
+#[cfg(feature = "alloc")]
+use rancor::Strategy;
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::Portable;
+#[cfg(feature = "alloc")]
+use crate::{Archive, Serialize};
+
+/// A read-only buffer which can hold an archive.
+///
+/// # Safety
+///
+/// - [`as_bytes`](Backing::as_bytes) must return the same bytes every time it
+///   is called, and the bytes must not be modified while the backing exists.
+/// - The start of the bytes must always be aligned to at least
+///   [`ALIGN`](Backing::ALIGN).
+pub unsafe trait Backing {
+    /// The alignment the start of the bytes is always guaranteed to have.
+    ///
+    /// Backings which make no guarantee use `1`, and archives in them are
+    /// checked for alignment when they are accessed.
+    const ALIGN: usize = 1;
+
+    /// Returns the bytes of the backing.
+    fn as_bytes(&self) -> &[u8];
+
+    /// Returns whether the start of the bytes is aligned to `align`.
+    #[inline]
+    fn is_aligned_to(&self, align: usize) -> bool {
+        Self::ALIGN % align == 0
+            || self.as_bytes().as_ptr() as usize % align == 0
+    }
+}
+
+/// A backing whose bytes stay at the same address when it is moved.
+///
+/// Owned archives require stable backings, because a validated archive must
+/// not move to an address with a different alignment.
+///
+/// # Safety
+///
+/// Moving the backing must not change the address of its bytes.
+pub unsafe trait StableBacking: Backing {}
+
+// SAFETY: A byte slice can't be modified through a shared reference.
+unsafe impl Backing for [u8] {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: Moving a backing behind a reference does not move the backing.
+unsafe impl<B: Backing + ?Sized> Backing for &B {
+    const ALIGN: usize = B::ALIGN;
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        B::as_bytes(self)
+    }
+}
+
+// SAFETY: Moving a reference does not move the backing it points to.
+unsafe impl<B: Backing + ?Sized> StableBacking for &B {}
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    #[cfg(not(feature = "std"))]
+    use ::alloc::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
+    #[cfg(feature = "std")]
+    use ::std::{rc::Rc, sync::Arc};
+
+    use super::{Backing, StableBacking};
+    use crate::util::AlignedVec;
+
+    // SAFETY: The bytes of an `AlignedVec` are aligned to its alignment and
+    // are stored on the heap, and can't be modified through a shared
+    // reference.
+    unsafe impl Backing for AlignedVec {
+        const ALIGN: usize = AlignedVec::ALIGNMENT;
+
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            self.as_slice()
+        }
+    }
+
+    // SAFETY: Moving an `AlignedVec` does not move its heap allocation.
+    unsafe impl StableBacking for AlignedVec {}
+
+    // SAFETY: The bytes of a `Vec` are stored on the heap, and can't be
+    // modified through a shared reference.
+    unsafe impl Backing for Vec<u8> {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            self
+        }
+    }
+
+    // SAFETY: Moving a `Vec` does not move its heap allocation.
+    unsafe impl StableBacking for Vec<u8> {}
+
+    macro_rules! impl_pointer_backing {
+        ($($ptr:ident),* $(,)?) => {
+            $(
+                // SAFETY: The backing is stored on the heap, and can't be
+                // modified through a shared reference.
+                unsafe impl<B: Backing + ?Sized> Backing for $ptr<B> {
+                    const ALIGN: usize = B::ALIGN;
+
+                    #[inline]
+                    fn as_bytes(&self) -> &[u8] {
+                        B::as_bytes(self)
+                    }
+                }
+
+                // SAFETY: Moving the pointer does not move the backing it
+                // points to.
+                unsafe impl<B: Backing + ?Sized> StableBacking for $ptr<B> {}
+            )*
+        };
+    }
+
+    impl_pointer_backing!(Box, Rc, Arc);
+}
+
+// SAFETY: The bytes of a `Bytes` are immutable and reference-counted.
+#[cfg(feature = "bytes")]
+unsafe impl Backing for bytes::Bytes {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: Moving a `Bytes` does not move the buffer it points to.
+#[cfg(feature = "bytes")]
+unsafe impl StableBacking for bytes::Bytes {}
+
+// SAFETY: A read-only memory map can't be modified through the map. Modifying
+// the mapped file while it is mapped is already undefined behavior.
+#[cfg(feature = "memmap2")]
+unsafe impl Backing for memmap2::Mmap {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: Moving a memory map does not move the mapped memory.
+#[cfg(feature = "memmap2")]
+unsafe impl StableBacking for memmap2::Mmap {}
+
+/// An archive which owns the backing it is stored in.
+///
+/// The archive is validated when it is created, and dereferences to its
+/// root.
+pub struct OwnedArchive<T, B> {
+    backing: B,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Portable, B: StableBacking> OwnedArchive<T, B> {
+    /// Validates the archive in the given backing and returns an owned
+    /// archive.
+    #[cfg(feature = "bytecheck")]
+    pub fn new<E>(backing: B) -> Result<Self, E>
+    where
+        T: CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        crate::access::<T, E>(backing.as_bytes())?;
+        Ok(Self {
+            backing,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns an owned archive from the given backing without validating
+    /// it.
+    ///
+    /// # Safety
+    ///
+    /// The backing must contain a valid archive of `T`.
+    #[inline]
+    pub unsafe fn new_unchecked(backing: B) -> Self {
+        Self {
+            backing,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the root of the archive.
+    #[inline]
+    pub fn get(&self) -> &T {
+        // SAFETY: The archive was validated when this was created, and the
+        // backing has not moved or changed since.
+        unsafe { crate::access_unchecked::<T>(self.backing.as_bytes()) }
+    }
+
+    /// Returns a reference to the backing.
+    #[inline]
+    pub fn backing(&self) -> &B {
+        &self.backing
+    }
+
+    /// Consumes the owned archive and returns its backing.
+    #[inline]
+    pub fn into_backing(self) -> B {
+        self.backing
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Portable> OwnedArchive<T, crate::util::AlignedVec> {
+    /// Serializes the given value and returns an owned archive of it.
+    ///
+    /// The archive is not validated, since it was just serialized from a
+    /// valid value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rkyv::{backing::OwnedArchive, rancor::Error};
+    ///
+    /// let archive =
+    ///     OwnedArchive::from_value::<_, Error>(&vec![1u32, 2, 3]).unwrap();
+    /// assert_eq!(archive.len(), 3);
+    /// assert_eq!(archive[2], 3);
+    /// ```
+    pub fn from_value<V, E>(value: &V) -> Result<Self, E>
+    where
+        V: Archive<Archived = T>
+            + Serialize<Strategy<crate::ser::AllocSerializer, E>>,
+    {
+        let bytes = crate::to_bytes::<E>(value)?;
+        // SAFETY: The bytes were just serialized from a `V`, so they contain
+        // a valid archive of `T`.
+        Ok(unsafe { Self::new_unchecked(bytes) })
+    }
+}
+
+impl<T: Portable, B: StableBacking> Deref for OwnedArchive<T, B> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T, B: Backing> fmt::Debug for OwnedArchive<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedArchive")
+            .field("len", &self.backing.as_bytes().len())
+            .finish()
+    }
+}
@@ -5,8 +5,16 @@ use core::{borrow::Borrow, cmp, fmt, hash, ops::Deref, pin::Pin};
 use munge::munge;
 use rancor::Fallible;
 
+#[cfg(feature = "alloc")]
+use crate::analyze::{Analyze, TypeSizeReport};
+#[cfg(feature = "serde")]
+use crate::export::Export;
 use crate::{
-    ArchivePointee, ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
+    deref::AsArchivedRef,
+    redact::Redact,
+    schema::{SchemaHash, SchemaHasher},
+    ArchivePointee, ArchiveUnsized, ArchivedSize, Extract, ExtractUnsized,
+    Place, Portable, RelPtr, SerializeUnsized,
 };
 
 /// An archived [`Box`].
@@ -76,6 +84,76 @@ impl<T: ArchivePointee + ?Sized> ArchivedBox<T> {
     }
 }
 
+impl<T> ArchivedSize for ArchivedBox<T>
+where
+    T: ArchivePointee + ArchivedSize + ?Sized,
+{
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.get().archived_size()
+    }
+}
+
+impl<T> SchemaHash for ArchivedBox<T>
+where
+    T: ArchivePointee + SchemaHash + ?Sized,
+{
+    const SCHEMA_HASH: u64 = SchemaHasher::new()
+        .write_str("Box")
+        .write_layout::<Self>()
+        .write_u64(T::SCHEMA_HASH)
+        .finish();
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Analyze for ArchivedBox<T>
+where
+    T: ArchivePointee + Analyze + ?Sized,
+{
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        report.record_out_of_line(self.get());
+    }
+}
+
+impl<T> Redact for ArchivedBox<T>
+where
+    T: ArchivePointee + Redact + ?Sized,
+{
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        self.get_pin_mut().redact();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Export for ArchivedBox<T>
+where
+    T: ArchivePointee + Export + ?Sized,
+{
+    #[inline]
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.get().export(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for ArchivedBox<T>
+where
+    T: ArchivePointee + Export + ?Sized,
+{
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<T: ArchivePointee + ?Sized> AsRef<T> for ArchivedBox<T> {
     #[inline]
     fn as_ref(&self) -> &T {
@@ -83,6 +161,13 @@ impl<T: ArchivePointee + ?Sized> AsRef<T> for ArchivedBox<T> {
     }
 }
 
+impl<T: ArchivePointee + ?Sized> AsArchivedRef<T> for ArchivedBox<T> {
+    #[inline]
+    fn as_archived_ref(&self) -> &T {
+        self.get()
+    }
+}
+
 impl<T: ArchivePointee + ?Sized> Borrow<T> for ArchivedBox<T> {
     #[inline]
     fn borrow(&self) -> &T {
@@ -120,6 +205,26 @@ impl<T: ArchivePointee + fmt::Display + ?Sized> fmt::Display
 
 impl<T: ArchivePointee + Eq + ?Sized> Eq for ArchivedBox<T> {}
 
+impl<T, S> Extract<S> for ArchivedBox<T>
+where
+    T: ExtractUnsized<S> + ?Sized,
+    S: Fallible + ?Sized,
+{
+    type Resolver = BoxResolver;
+
+    #[inline]
+    fn extract(&self, serializer: &mut S) -> Result<BoxResolver, S::Error> {
+        Ok(BoxResolver {
+            pos: self.get().extract_unsized(serializer)?,
+        })
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, resolver: BoxResolver, out: Place<Self>) {
+        Self::resolve_from_raw_parts(resolver, *self.ptr.metadata(), out);
+    }
+}
+
 impl<T: ArchivePointee + hash::Hash + ?Sized> hash::Hash for ArchivedBox<T> {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
@@ -199,8 +304,10 @@ mod verify {
                 unsafe { context.bounds_check_subtree_rel_ptr(&self.ptr)? };
 
             let range = unsafe { context.push_prefix_subtree(ptr)? };
-            unsafe {
-                T::check_bytes(ptr, context)?;
+            if context.should_check_subtree() {
+                unsafe {
+                    T::check_bytes(ptr, context)?;
+                }
             }
             unsafe {
                 context.pop_subtree_range(range)?;
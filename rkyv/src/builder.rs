@@ -0,0 +1,760 @@
+//! Building blocks for constructing archived values directly in a serializer.
+//!
+//! Types which derive `Archive` with `#[archive(builder)]` get a builder named
+//! `Archived{Type}Builder`. The builder serializes each field as it is set, and
+//! writes the archived value once all of the fields have been set. This allows
+//! producers to write archives without ever constructing the native type.
+//!
+//! Each field is set from a reference to a value of any type which archives
+//! to the same type as the field. The values only need to live until the
+//! builder finishes, so they can be borrowed from a parser's buffers.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked,
+//!     rancor::{Error, Strategy},
+//!     ser::AllocSerializer,
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(builder)]
+//! struct Record {
+//!     id: u32,
+//!     name: String,
+//!     tags: Vec<u16>,
+//! }
+//!
+//! let mut serializer = AllocSerializer::default();
+//! let name = "streamed".to_string();
+//! let tags = vec![1, 2, 3];
+//!
+//! let pos =
+//!     ArchivedRecordBuilder::new(Strategy::<_, Error>::wrap(&mut serializer))
+//!         .id(&7u32)
+//!         .unwrap()
+//!         .name(&name)
+//!         .unwrap()
+//!         .tags(&tags)
+//!         .unwrap()
+//!         .finish()
+//!         .unwrap();
+//!
+//! let bytes = serializer.into_writer();
+//! assert_eq!(pos + core::mem::size_of::<ArchivedRecord>(), bytes.len());
+//! let archived = unsafe { access_unchecked::<ArchivedRecord>(&bytes) };
+//! assert_eq!(archived.id, 7);
+//! assert_eq!(archived.name, "streamed");
+//! assert_eq!(archived.tags.as_slice(), &[1, 2, 3]);
+//! ```
+//!
+//! # Dynamic data
+//!
+//! When the shape of the data is only known at runtime (for example, when
+//! converting JSON to a typed archive), [`ArchivedStringBuilder`],
+//! [`ArchivedVecBuilder`], [`ArchivedHashMapBuilder`], and
+//! [`ArchivedBTreeMapBuilder`] can be used to write collections piece by
+//! piece. Finishing one of these builders returns a handle which archives to
+//! the corresponding archived collection. Handles can be pushed into other
+//! builders, set as fields of derived builders, or written as the root of an
+//! archive with [`serialize_and_resolve`].
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked,
+//!     builder::{ArchivedStringBuilder, ArchivedVecBuilder},
+//!     rancor::{Error, Strategy},
+//!     ser::AllocSerializer,
+//!     string::ArchivedString,
+//!     vec::ArchivedVec,
+//!     Serialize,
+//! };
+//!
+//! let mut serializer = AllocSerializer::default();
+//! let s = Strategy::<_, Error>::wrap(&mut serializer);
+//!
+//! let mut rows = ArchivedVecBuilder::new();
+//! for word in ["a", "runtime", "defined", "list"] {
+//!     let mut string = ArchivedStringBuilder::new();
+//!     string.push_str(word);
+//!     rows.push(string.finish(s).unwrap(), s).unwrap();
+//! }
+//! rows.finish(s).unwrap().serialize_and_resolve(s).unwrap();
+//!
+//! let bytes = serializer.into_writer();
+//! let archived =
+//!     unsafe { access_unchecked::<ArchivedVec<ArchivedString>>(&bytes) };
+//! assert_eq!(archived.len(), 4);
+//! assert_eq!(archived[1], "runtime");
+//! ```
+//!
+//! [`serialize_and_resolve`]: crate::Serialize::serialize_and_resolve
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+#[cfg(all(feature = "alloc", feature = "std"))]
+use std::collections::hash_map;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use hashbrown::hash_map;
+#[cfg(feature = "alloc")]
+use rancor::{Fallible, Source};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    collections::{
+        btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+        swiss_table::map::{ArchivedHashMap, HashMapResolver},
+    },
+    hash::DefaultHashBuilder,
+    ser::{Allocator, Writer, WriterExt as _},
+    string::{ArchivedString, StringResolver},
+    vec::{ArchivedVec, VecResolver},
+    Portable, Serialize,
+};
+use crate::{Archive, Place};
+
+/// A builder field which has not been set yet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unset;
+
+/// A builder field which has been set and serialized.
+///
+/// This holds the value the field was set from and the resolver returned by
+/// serializing it, so that the field can be resolved when the builder
+/// finishes.
+pub struct Set<'a, T: Archive> {
+    value: &'a T,
+    resolver: T::Resolver,
+}
+
+impl<'a, T: Archive> Set<'a, T> {
+    /// Creates a new set field from a value and the resolver returned from
+    /// serializing it.
+    #[inline]
+    pub fn new(value: &'a T, resolver: T::Resolver) -> Self {
+        Self { value, resolver }
+    }
+
+    /// Resolves the field into the given output place.
+    #[inline]
+    pub fn resolve(self, out: Place<T::Archived>) {
+        self.value.resolve(self.resolver, out);
+    }
+}
+
+impl<T: Archive + fmt::Debug> fmt::Debug for Set<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Set").field(self.value).finish()
+    }
+}
+
+/// A builder for an [`ArchivedString`] whose contents are produced at runtime.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default)]
+pub struct ArchivedStringBuilder {
+    value: String,
+}
+
+#[cfg(feature = "alloc")]
+impl ArchivedStringBuilder {
+    /// Creates a new empty string builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new empty string builder with the given capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            value: String::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a string slice to the end of the string.
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.value.push_str(s);
+    }
+
+    /// Appends a character to the end of the string.
+    #[inline]
+    pub fn push(&mut self, c: char) {
+        self.value.push(c);
+    }
+
+    /// Returns the contents of the string built so far.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Writes the contents of the string to the serializer and returns a
+    /// handle which archives to an [`ArchivedString`].
+    pub fn finish<S>(self, serializer: &mut S) -> Result<BuiltString, S::Error>
+    where
+        S: Fallible + Writer + ?Sized,
+    {
+        let resolver = self.value.serialize(serializer)?;
+        Ok(BuiltString {
+            value: self.value,
+            resolver,
+        })
+    }
+}
+
+/// A string which has been written by an [`ArchivedStringBuilder`].
+///
+/// This archives to an [`ArchivedString`] without writing the contents again.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct BuiltString {
+    value: String,
+    resolver: StringResolver,
+}
+
+#[cfg(feature = "alloc")]
+impl BuiltString {
+    /// Returns the contents of the string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for BuiltString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BuiltString").field(&self.value).finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq for BuiltString {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Eq for BuiltString {}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd for BuiltString {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Ord for BuiltString {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Hash for BuiltString {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Archive for BuiltString {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(&self.value, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Fallible + ?Sized> Serialize<S> for BuiltString {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(self.resolver)
+    }
+}
+
+/// A builder for an [`ArchivedVec`] whose length is only known at runtime.
+///
+/// Each element is serialized as it is pushed. The elements themselves are
+/// written when the builder finishes.
+#[cfg(feature = "alloc")]
+pub struct ArchivedVecBuilder<U: Archive> {
+    elements: Vec<(U, U::Resolver)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<U: Archive> ArchivedVecBuilder<U> {
+    /// Creates a new empty vec builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            elements: Vec::new(),
+        }
+    }
+
+    /// Creates a new empty vec builder with the given capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            elements: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of elements pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether no elements have been pushed yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Serializes the dependencies of `value` and appends it to the vec.
+    pub fn push<S>(
+        &mut self,
+        value: U,
+        serializer: &mut S,
+    ) -> Result<(), S::Error>
+    where
+        U: Serialize<S>,
+        S: Fallible + ?Sized,
+    {
+        let resolver = value.serialize(serializer)?;
+        self.elements.push((value, resolver));
+        Ok(())
+    }
+
+    /// Writes the elements of the vec to the serializer and returns a handle
+    /// which archives to an [`ArchivedVec`].
+    pub fn finish<S>(
+        self,
+        serializer: &mut S,
+    ) -> Result<BuiltVec<U::Archived>, S::Error>
+    where
+        S: Fallible + Writer + ?Sized,
+    {
+        let len = self.elements.len();
+        let pos = serializer.align_for::<U::Archived>()?;
+        for (value, resolver) in self.elements {
+            // SAFETY: `resolver` is the result of serializing `value`, and the
+            // serializer was aligned for `U::Archived` above. Archived types
+            // have a size which is a multiple of their alignment, so each
+            // following element is also aligned.
+            unsafe {
+                serializer.resolve_aligned(&value, resolver)?;
+            }
+        }
+
+        Ok(BuiltVec {
+            pos,
+            len,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<U: Archive> Default for ArchivedVecBuilder<U> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<U: Archive + fmt::Debug> fmt::Debug for ArchivedVecBuilder<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.elements.iter().map(|(value, _)| value))
+            .finish()
+    }
+}
+
+/// A vec which has been written by an [`ArchivedVecBuilder`].
+///
+/// This archives to an [`ArchivedVec`] without writing the elements again.
+#[cfg(feature = "alloc")]
+pub struct BuiltVec<T> {
+    pos: usize,
+    len: usize,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BuiltVec<T> {
+    /// Returns the number of elements in the vec.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the vec is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Clone for BuiltVec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Copy for BuiltVec<T> {}
+
+#[cfg(feature = "alloc")]
+impl<T> fmt::Debug for BuiltVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuiltVec")
+            .field("pos", &self.pos)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Portable> Archive for BuiltVec<T> {
+    type Archived = ArchivedVec<T>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_len(self.len, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Portable, S: Fallible + ?Sized> Serialize<S> for BuiltVec<T> {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(VecResolver::from_pos(self.pos))
+    }
+}
+
+/// A builder for an [`ArchivedHashMap`] whose entries are produced at runtime.
+///
+/// Inserting a key which is already present replaces its value.
+#[cfg(feature = "alloc")]
+pub struct ArchivedHashMapBuilder<K, V> {
+    entries: hash_map::HashMap<K, V, DefaultHashBuilder>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Hash + Eq, V> ArchivedHashMapBuilder<K, V> {
+    /// Creates a new empty hash map builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: hash_map::HashMap::with_hasher(
+                DefaultHashBuilder::default(),
+            ),
+        }
+    }
+
+    /// Returns the number of entries inserted so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no entries have been inserted yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts an entry into the map, returning the previous value for the key
+    /// if there was one.
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.entries.insert(key, value)
+    }
+
+    /// Writes the entries of the map to the serializer and returns a handle
+    /// which archives to an [`ArchivedHashMap`].
+    pub fn finish<S>(
+        self,
+        serializer: &mut S,
+    ) -> Result<BuiltHashMap<K::Archived, V::Archived>, S::Error>
+    where
+        K: Serialize<S>,
+        V: Serialize<S>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        let resolver =
+            ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
+                self.entries.iter(),
+                (7, 8),
+                serializer,
+            )?;
+
+        Ok(BuiltHashMap {
+            len: self.entries.len(),
+            resolver,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Hash + Eq, V> Default for ArchivedHashMapBuilder<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ArchivedHashMapBuilder<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries.iter()).finish()
+    }
+}
+
+/// A hash map which has been written by an [`ArchivedHashMapBuilder`].
+///
+/// This archives to an [`ArchivedHashMap`] without writing the entries again.
+#[cfg(feature = "alloc")]
+pub struct BuiltHashMap<K, V> {
+    len: usize,
+    resolver: HashMapResolver,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> BuiltHashMap<K, V> {
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> Clone for BuiltHashMap<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> Copy for BuiltHashMap<K, V> {}
+
+#[cfg(feature = "alloc")]
+impl<K, V> fmt::Debug for BuiltHashMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuiltHashMap")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Portable, V: Portable> Archive for BuiltHashMap<K, V> {
+    type Archived = ArchivedHashMap<K, V>;
+    type Resolver = HashMapResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedHashMap::resolve_from_len(self.len, (7, 8), resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, S> Serialize<S> for BuiltHashMap<K, V>
+where
+    K: Portable,
+    V: Portable,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(self.resolver)
+    }
+}
+
+/// A builder for an [`ArchivedBTreeMap`] whose entries are produced at
+/// runtime.
+///
+/// Inserting a key which is already present replaces its value.
+#[cfg(feature = "alloc")]
+pub struct ArchivedBTreeMapBuilder<K, V> {
+    entries: BTreeMap<K, V>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> ArchivedBTreeMapBuilder<K, V> {
+    /// Creates a new empty B-tree map builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of entries inserted so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no entries have been inserted yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts an entry into the map, returning the previous value for the key
+    /// if there was one.
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.entries.insert(key, value)
+    }
+
+    /// Writes the entries of the map to the serializer and returns a handle
+    /// which archives to an [`ArchivedBTreeMap`].
+    pub fn finish<S>(
+        self,
+        serializer: &mut S,
+    ) -> Result<BuiltBTreeMap<K::Archived, V::Archived>, S::Error>
+    where
+        K: Serialize<S>,
+        V: Serialize<S>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        let resolver = ArchivedBTreeMap::serialize_from_ordered_iter(
+            self.entries.iter(),
+            serializer,
+        )?;
+
+        Ok(BuiltBTreeMap {
+            len: self.entries.len(),
+            resolver,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> Default for ArchivedBTreeMapBuilder<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug
+    for ArchivedBTreeMapBuilder<K, V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries.iter()).finish()
+    }
+}
+
+/// A B-tree map which has been written by an [`ArchivedBTreeMapBuilder`].
+///
+/// This archives to an [`ArchivedBTreeMap`] without writing the entries again.
+#[cfg(feature = "alloc")]
+pub struct BuiltBTreeMap<K, V> {
+    len: usize,
+    resolver: BTreeMapResolver,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> BuiltBTreeMap<K, V> {
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> Clone for BuiltBTreeMap<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> Copy for BuiltBTreeMap<K, V> {}
+
+#[cfg(feature = "alloc")]
+impl<K, V> fmt::Debug for BuiltBTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuiltBTreeMap")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Portable, V: Portable> Archive for BuiltBTreeMap<K, V> {
+    type Archived = ArchivedBTreeMap<K, V>;
+    type Resolver = BTreeMapResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedBTreeMap::resolve_from_len(self.len, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, S> Serialize<S> for BuiltBTreeMap<K, V>
+where
+    K: Portable,
+    V: Portable,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(self.resolver)
+    }
+}
@@ -0,0 +1,36 @@
+//! Archived versions of `cmp` types.
+
+use core::{cmp::Ordering, fmt};
+
+use crate::Portable;
+
+/// An archived [`Reverse`](::core::cmp::Reverse).
+///
+/// Like `Reverse`, it orders values in the opposite order of the value it
+/// wraps.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Portable)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(transparent)]
+#[archive(crate)]
+pub struct ArchivedReverse<T>(pub T);
+
+impl<T: PartialOrd> PartialOrd for ArchivedReverse<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl<T: Ord> Ord for ArchivedReverse<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArchivedReverse<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Reverse").field(&self.0).finish()
+    }
+}
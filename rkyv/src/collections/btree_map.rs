@@ -6,15 +6,23 @@ use core::{
     fmt,
     marker::PhantomData,
     mem::{size_of, MaybeUninit},
-    ops::ControlFlow,
+    ops::{Bound, ControlFlow, RangeBounds},
     slice,
 };
+#[cfg(feature = "alloc")]
+use core::{iter::FusedIterator, pin::Pin};
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
 use munge::munge;
 use rancor::{fail, Fallible, Source};
 
+#[cfg(feature = "alloc")]
+use crate::collections::util::DuplicateKey;
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
 use crate::{
-    collections::util::IteratorLengthMismatch,
+    collections::util::{DuplicateKeys, IteratorLengthMismatch},
     place::Initialized,
     primitive::{ArchivedUsize, FixedUsize},
     ser::{Allocator, Writer, WriterExt as _},
@@ -105,6 +113,20 @@ const fn ll_entries<const E: usize>(height: u32, n: usize) -> usize {
     n - entries_in_full_tree::<E>(height - 1)
 }
 
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct UnsortedKeys;
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for UnsortedKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "iterator yielded keys which were not in ascending order")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsortedKeys {}
+
 #[derive(Portable)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
 #[archive(crate)]
@@ -254,6 +276,87 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         }
     }
 
+    /// Returns the first key-value pair in the B-tree map, or `None` if it is
+    /// empty.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut current = unsafe { self.root.as_ptr().cast::<Node<K, V, E>>() };
+        while let Some(child) = unsafe { Self::child(current, 0) } {
+            current = child;
+        }
+        Some(unsafe { Self::entry(current, 0) })
+    }
+
+    /// Returns the last key-value pair in the B-tree map, or `None` if it is
+    /// empty.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut current = unsafe { self.root.as_ptr().cast::<Node<K, V, E>>() };
+        loop {
+            let len = unsafe { (*current).len.to_native() as usize };
+            match unsafe { Self::child(current, len) } {
+                Some(child) => current = child,
+                None => return Some(unsafe { Self::entry(current, len - 1) }),
+            }
+        }
+    }
+
+    /// Returns the child node which precedes the entry at `index`, or the
+    /// greater node if `index` is the length of the node. Leaf nodes have no
+    /// children.
+    ///
+    /// # Safety
+    ///
+    /// `current` must point to a valid node and `index` must be less than or
+    /// equal to its length.
+    unsafe fn child(
+        current: *const Node<K, V, E>,
+        index: usize,
+    ) -> Option<*const Node<K, V, E>> {
+        let node = unsafe { &*current };
+        match node.kind {
+            NodeKind::Leaf => None,
+            NodeKind::Inner => {
+                let inner = unsafe { &*current.cast::<InnerNode<K, V, E>>() };
+                let ptr = if index < node.len.to_native() as usize {
+                    unsafe { inner.lesser_nodes[index].assume_init_ref() }
+                } else {
+                    &inner.greater_node
+                };
+                if ptr.is_invalid() {
+                    None
+                } else {
+                    Some(unsafe { ptr.as_ptr().cast::<Node<K, V, E>>() })
+                }
+            }
+        }
+    }
+
+    /// Returns the entry at `index` in the given node.
+    ///
+    /// # Safety
+    ///
+    /// `current` must point to a valid node and `index` must be less than its
+    /// length. The returned references must not outlive the B-tree map.
+    unsafe fn entry<'a>(
+        current: *const Node<K, V, E>,
+        index: usize,
+    ) -> (&'a K, &'a V) {
+        let node = unsafe { &*current };
+        unsafe {
+            (
+                node.keys[index].assume_init_ref(),
+                node.values[index].assume_init_ref(),
+            )
+        }
+    }
+
     /// Resolves an `ArchivedBTreeMap` from the given length, resolver, and
     /// output place.
     pub fn resolve_from_len(
@@ -407,6 +510,59 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         )?
     }
 
+    /// Serializes an `ArchivedBTreeMap` from an iterator of owned key-value
+    /// pairs in ascending order of their keys, handling duplicate keys
+    /// according to `duplicates`. Returns the resolver along with the number
+    /// of entries in the map, which should be passed to
+    /// [`resolve_from_len`](ArchivedBTreeMap::resolve_from_len).
+    ///
+    /// Unlike [`serialize_from_ordered_iter`], the iterator doesn't need to
+    /// know its length in advance and its keys don't need to be unique. The
+    /// pairs are buffered in a `Vec` without building a native B-tree map. If
+    /// the keys are not sorted, an error is returned.
+    ///
+    /// [`serialize_from_ordered_iter`]:
+    /// ArchivedBTreeMap::serialize_from_ordered_iter
+    #[cfg(feature = "alloc")]
+    pub fn serialize_from_sorted_iter<I, UK, UV, S>(
+        iter: I,
+        duplicates: DuplicateKeys,
+        serializer: &mut S,
+    ) -> Result<(BTreeMapResolver, usize), S::Error>
+    where
+        I: IntoIterator<Item = (UK, UV)>,
+        UK: Serialize<S, Archived = K> + Ord,
+        UV: Serialize<S, Archived = V>,
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        let iter = iter.into_iter();
+        let mut entries = Vec::<(UK, UV)>::with_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            if let Some(last) = entries.last_mut() {
+                match key.cmp(&last.0) {
+                    Ordering::Less => fail!(UnsortedKeys),
+                    Ordering::Equal => {
+                        match duplicates {
+                            DuplicateKeys::Error => fail!(DuplicateKey),
+                            DuplicateKeys::KeepFirst => (),
+                            DuplicateKeys::KeepLast => *last = (key, value),
+                        }
+                        continue;
+                    }
+                    Ordering::Greater => (),
+                }
+            }
+            entries.push((key, value));
+        }
+
+        let resolver = Self::serialize_from_ordered_iter(
+            entries.iter().map(|(key, value)| (key, value)),
+            serializer,
+        )?;
+        Ok((resolver, entries.len()))
+    }
+
     fn close_leaf<UK, UV, S>(
         items: &[(&UK, &UV)],
         serializer: &mut S,
@@ -588,9 +744,363 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         ControlFlow::Continue(())
     }
 
-    // TODO: add entries iterator if alloc feature is enabled
+    /// Returns an iterator over pinned mutable references to the values in the
+    /// B-tree, in the order of their keys.
+    #[cfg(feature = "alloc")]
+    pub fn values_seal(self: Pin<&mut Self>) -> ValuesSeal<'_, K, V, E> {
+        let len = self.len();
+        let mut iter = ValuesSeal {
+            stack: Vec::new(),
+            remaining: len,
+            _phantom: PhantomData,
+        };
+        if len > 0 {
+            // SAFETY: The B-tree is not empty, so the root pointer is valid.
+            let root =
+                unsafe { self.map_unchecked_mut(|s| &mut s.root).as_mut_ptr() };
+            iter.push_lesser(root.cast());
+        }
+        iter
+    }
+
+    /// Returns a double-ended iterator over the entries of the B-tree, in the
+    /// order of their keys.
+    #[cfg(feature = "alloc")]
+    pub fn iter(&self) -> Range<'_, K, V, E> {
+        Range::new(self, |_| true, |_| true)
+    }
+
+    /// Returns a double-ended iterator over the entries of the B-tree whose
+    /// keys are in the given range, in the order of their keys.
+    ///
+    /// Unlike [`BTreeMap::range`](std::collections::BTreeMap::range), this
+    /// returns an empty iterator instead of panicking if the start of the
+    /// range is greater than its end.
+    #[cfg(feature = "alloc")]
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, E>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q> + Ord,
+        R: RangeBounds<Q>,
+    {
+        self.range_bounds(range.start_bound(), range.end_bound())
+    }
+
+    /// Returns a double-ended iterator over the entries of the B-tree whose
+    /// keys are between the given start and end bounds, in the order of their
+    /// keys.
+    ///
+    /// This is the same as [`range`](Self::range), but takes its bounds
+    /// separately so that they can be borrowed from different places.
+    #[cfg(feature = "alloc")]
+    pub fn range_bounds<Q>(
+        &self,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+    ) -> Range<'_, K, V, E>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q> + Ord,
+    {
+        let mut range = Range::new(
+            self,
+            |key: &K| match start {
+                Bound::Included(start) => key.borrow() >= start,
+                Bound::Excluded(start) => key.borrow() > start,
+                Bound::Unbounded => true,
+            },
+            |key: &K| match end {
+                Bound::Included(end) => key.borrow() <= end,
+                Bound::Excluded(end) => key.borrow() < end,
+                Bound::Unbounded => true,
+            },
+        );
+        // If the bounds cross, the first entry after the start comes after
+        // the last entry before the end.
+        if let (Some(&(front, i)), Some(&(back, j))) =
+            (range.front.last(), range.back.last())
+        {
+            let (first, _) = unsafe { Self::entry(front, i) };
+            let (last, _) = unsafe { Self::entry(back, j) };
+            if first > last {
+                range.clear();
+            }
+        }
+        range
+    }
+}
+
+/// A double-ended iterator over the entries of an [`ArchivedBTreeMap`] in a
+/// range of keys.
+///
+/// This is returned by [`ArchivedBTreeMap::range`],
+/// [`ArchivedBTreeMap::range_bounds`], and [`ArchivedBTreeMap::iter`].
+#[cfg(feature = "alloc")]
+pub struct Range<'a, K, V, const E: usize> {
+    // The nodes containing the next entries to yield from the front and back,
+    // along with the index of the entry. Entries higher on each stack are
+    // yielded before the entries below them.
+    front: Vec<(*const Node<K, V, E>, usize)>,
+    back: Vec<(*const Node<K, V, E>, usize)>,
+    _phantom: PhantomData<&'a ArchivedBTreeMap<K, V, E>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V, const E: usize> Range<'a, K, V, E> {
+    /// Returns a range over the entries in the map whose keys are after the
+    /// start and before the end. Keys must be partitioned by both predicates.
+    fn new(
+        map: &'a ArchivedBTreeMap<K, V, E>,
+        mut after_start: impl FnMut(&K) -> bool,
+        mut before_end: impl FnMut(&K) -> bool,
+    ) -> Self {
+        let mut result = Self {
+            front: Vec::new(),
+            back: Vec::new(),
+            _phantom: PhantomData,
+        };
+        if map.is_empty() {
+            return result;
+        }
+
+        let root = unsafe { map.root.as_ptr().cast::<Node<K, V, E>>() };
+
+        let mut current = root;
+        loop {
+            let node = unsafe { &*current };
+            let len = node.len.to_native() as usize;
+            let i = (0..len)
+                .find(|&i| {
+                    after_start(unsafe { node.keys[i].assume_init_ref() })
+                })
+                .unwrap_or(len);
+            if i < len {
+                result.front.push((current, i));
+            }
+            match unsafe { ArchivedBTreeMap::child(current, i) } {
+                Some(child) => current = child,
+                None => break,
+            }
+        }
+
+        let mut current = root;
+        loop {
+            let node = unsafe { &*current };
+            let len = node.len.to_native() as usize;
+            let i = (0..len)
+                .find(|&i| {
+                    !before_end(unsafe { node.keys[i].assume_init_ref() })
+                })
+                .unwrap_or(len);
+            if i > 0 {
+                result.back.push((current, i - 1));
+            }
+            match unsafe { ArchivedBTreeMap::child(current, i) } {
+                Some(child) => current = child,
+                None => break,
+            }
+        }
+
+        if result.front.is_empty() || result.back.is_empty() {
+            result.clear();
+        }
+        result
+    }
+
+    fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
+
+    /// Moves the front of the range past its next entry.
+    fn advance_front(&mut self) {
+        let (current, index) = self.front.pop().unwrap();
+        let len = unsafe { (*current).len.to_native() as usize };
+        if index + 1 < len {
+            self.front.push((current, index + 1));
+        }
+        // The entries after this one are preceded by the subtree between them.
+        let mut next = unsafe { ArchivedBTreeMap::child(current, index + 1) };
+        while let Some(child) = next {
+            self.front.push((child, 0));
+            next = unsafe { ArchivedBTreeMap::child(child, 0) };
+        }
+    }
+
+    /// Moves the back of the range past its next entry.
+    fn advance_back(&mut self) {
+        let (current, index) = self.back.pop().unwrap();
+        if index > 0 {
+            self.back.push((current, index - 1));
+        }
+        // The entries before this one are followed by the subtree between
+        // them.
+        let mut next = unsafe { ArchivedBTreeMap::child(current, index) };
+        while let Some(child) = next {
+            let len = unsafe { (*child).len.to_native() as usize };
+            self.back.push((child, len - 1));
+            next = unsafe { ArchivedBTreeMap::child(child, len) };
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V, const E: usize> Iterator for Range<'a, K, V, E> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(current, index) = self.front.last()?;
+        if self.back.last() == Some(&(current, index)) {
+            // The front and back have met at the last entry in the range.
+            self.clear();
+        } else {
+            self.advance_front();
+        }
+        Some(unsafe { ArchivedBTreeMap::entry(current, index) })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> DoubleEndedIterator for Range<'_, K, V, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let &(current, index) = self.back.last()?;
+        if self.front.last() == Some(&(current, index)) {
+            // The front and back have met at the first entry in the range.
+            self.clear();
+        } else {
+            self.advance_back();
+        }
+        Some(unsafe { ArchivedBTreeMap::entry(current, index) })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> FusedIterator for Range<'_, K, V, E> {}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> Clone for Range<'_, K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> fmt::Debug for Range<'_, K, V, E>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over pinned mutable references to the values of an
+/// [`ArchivedBTreeMap`].
+///
+/// This is returned by [`ArchivedBTreeMap::values_seal`].
+#[cfg(feature = "alloc")]
+pub struct ValuesSeal<'a, K, V, const E: usize> {
+    // Each node on the path to the next value, along with the index of the
+    // next entry to yield from it.
+    stack: Vec<(*mut Node<K, V, E>, usize)>,
+    remaining: usize,
+    _phantom: PhantomData<&'a mut ArchivedBTreeMap<K, V, E>>,
 }
 
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> ValuesSeal<'_, K, V, E> {
+    /// Pushes `current` and all of the nodes down its lesser edge onto the
+    /// stack.
+    fn push_lesser(&mut self, mut current: *mut Node<K, V, E>) {
+        loop {
+            self.stack.push((current, 0));
+            match unsafe { &(*current).kind } {
+                NodeKind::Leaf => break,
+                NodeKind::Inner => {
+                    match unsafe { Self::child(current.cast(), 0) } {
+                        Some(child) => current = child,
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the child node which precedes the entry at `index`, or the
+    /// greater node if `index` is the length of the node.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must point to a valid inner node and `index` must be less than
+    /// or equal to its length.
+    unsafe fn child(
+        inner: *mut InnerNode<K, V, E>,
+        index: usize,
+    ) -> Option<*mut Node<K, V, E>> {
+        let inner = unsafe { &mut *inner };
+        let ptr = if index < inner.node.len.to_native() as usize {
+            unsafe { inner.lesser_nodes[index].assume_init_mut() }
+        } else {
+            &mut inner.greater_node
+        };
+        if ptr.is_invalid() {
+            None
+        } else {
+            let ptr = unsafe { Pin::new_unchecked(ptr).as_mut_ptr() };
+            Some(ptr.cast())
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V, const E: usize> Iterator for ValuesSeal<'a, K, V, E> {
+    type Item = Pin<&'a mut V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (current, index) = self.stack.last_mut()?;
+            let current = *current;
+            let node = unsafe { &mut *current };
+            if *index < node.len.to_native() as usize {
+                let i = *index;
+                *index += 1;
+
+                let value: *mut V = unsafe { node.values[i].assume_init_mut() };
+                if let NodeKind::Inner = node.kind {
+                    // The entries after this one are preceded by the subtree
+                    // between them.
+                    if let Some(child) =
+                        unsafe { Self::child(current.cast(), i + 1) }
+                    {
+                        self.push_lesser(child);
+                    }
+                }
+
+                self.remaining -= 1;
+                return Some(unsafe { Pin::new_unchecked(&mut *value) });
+            } else {
+                self.stack.pop();
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> ExactSizeIterator for ValuesSeal<'_, K, V, E> {}
+
+#[cfg(feature = "alloc")]
+impl<K, V, const E: usize> FusedIterator for ValuesSeal<'_, K, V, E> {}
+
 impl<K, V, const E: usize> fmt::Debug for ArchivedBTreeMap<K, V, E>
 where
     K: fmt::Debug,
@@ -606,7 +1116,49 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K, V, const E: usize> Export for ArchivedBTreeMap<K, V, E>
+where
+    K: Export,
+    V: Export,
+{
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap as _;
+
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        let result = self.visit(|k, v| {
+            match map.serialize_entry(&Serde(k), &Serde(v)) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(e) => ControlFlow::Break(e),
+            }
+        });
+        match result {
+            Some(e) => Err(e),
+            None => map.end(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, const E: usize> serde::Serialize for ArchivedBTreeMap<K, V, E>
+where
+    K: Export,
+    V: Export,
+{
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 /// The resolver for [`ArchivedBTreeMap`].
+#[derive(Clone, Copy)]
 pub struct BTreeMapResolver {
     root_node_pos: usize,
 }
@@ -5,6 +5,8 @@ use core::{borrow::Borrow, fmt, ops::ControlFlow};
 use munge::munge;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
 use crate::{
     collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
     ser::{Allocator, Writer},
@@ -115,5 +117,36 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K: Export, const E: usize> Export for ArchivedBTreeSet<K, E> {
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq as _;
+
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        let result = self.visit(|k| match seq.serialize_element(&Serde(k)) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        });
+        match result {
+            Some(e) => Err(e),
+            None => seq.end(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: Export, const E: usize> serde::Serialize for ArchivedBTreeSet<K, E> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 /// The resolver for archived B-tree sets.
 pub struct BTreeSetResolver(BTreeMapResolver);
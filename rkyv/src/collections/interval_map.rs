@@ -0,0 +1,275 @@
+//! A map from non-overlapping ranges to values which can be queried in place.
+
+use core::{cmp::Ordering, fmt, ops::Range};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use rancor::{fail, Source};
+
+use crate::{Archive, Deserialize, Serialize};
+
+/// A map from non-overlapping, half-open ranges to values.
+///
+/// The ranges are stored sorted by their start, so both the map and its
+/// archived form can find the range containing a point or the ranges
+/// overlapping another range in `O(log n)` time. The archived map is searched
+/// by comparing archived keys, so the archived key type must be ordered the
+/// same way as the key type.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, collections::interval_map::IntervalMap,
+///     rancor::Error, to_bytes, Archived,
+/// };
+///
+/// let map = IntervalMap::from_ranges::<_, Error>([
+///     (0u32..100, "low".to_string()),
+///     (200..300, "high".to_string()),
+/// ])
+/// .unwrap();
+///
+/// let bytes = to_bytes::<Error>(&map).unwrap();
+/// let archived = unsafe {
+///     access_unchecked::<Archived<IntervalMap<u32, String>>>(&bytes)
+/// };
+///
+/// assert_eq!(archived.get(&50u32).unwrap(), "low");
+/// assert!(archived.get(&150u32).is_none());
+/// assert_eq!(archived.overlapping(&50u32, &250u32).count(), 2);
+/// ```
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct IntervalMap<K, V> {
+    starts: Vec<K>,
+    ends: Vec<K>,
+    values: Vec<V>,
+}
+
+#[derive(Debug)]
+enum IntervalMapError {
+    EmptyRange { index: usize },
+    Overlapping { index: usize },
+}
+
+impl fmt::Display for IntervalMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyRange { index } => {
+                write!(f, "range {} of the interval map is empty", index)
+            }
+            Self::Overlapping { index } => write!(
+                f,
+                "range {} of the interval map overlaps another range",
+                index,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IntervalMapError {}
+
+impl<K, V> IntervalMap<K, V> {
+    /// Returns an empty interval map.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            starts: Vec::new(),
+            ends: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns the number of ranges in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether the map has no ranges.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns an iterator over the ranges and their values, ordered by the
+    /// start of each range.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<&K>, &V)> {
+        self.starts
+            .iter()
+            .zip(self.ends.iter())
+            .zip(self.values.iter())
+            .map(|((start, end), value)| (start..end, value))
+    }
+}
+
+impl<K: Ord, V> IntervalMap<K, V> {
+    /// Builds an interval map from ranges and their values.
+    ///
+    /// The ranges may be given in any order. Returns an error if any range is
+    /// empty or if any two ranges overlap.
+    pub fn from_ranges<I, E>(ranges: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = (Range<K>, V)>,
+        E: Source,
+    {
+        let mut ranges = ranges.into_iter().enumerate().collect::<Vec<_>>();
+        ranges.sort_by(|(_, (a, _)), (_, (b, _))| a.start.cmp(&b.start));
+
+        let mut map = Self::new();
+        for (index, (range, value)) in ranges {
+            if range.start >= range.end {
+                fail!(IntervalMapError::EmptyRange { index });
+            }
+            if matches!(map.ends.last(), Some(end) if *end > range.start) {
+                fail!(IntervalMapError::Overlapping { index });
+            }
+            map.starts.push(range.start);
+            map.ends.push(range.end);
+            map.values.push(value);
+        }
+        Ok(map)
+    }
+
+    /// Returns the value of the range containing the given point.
+    pub fn get<Q>(&self, point: &Q) -> Option<&V>
+    where
+        K: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let index = stab(&self.starts, &self.ends, point)?;
+        Some(&self.values[index])
+    }
+
+    /// Returns an iterator over the ranges which overlap the half-open range
+    /// from `start` to `end`, and their values.
+    pub fn overlapping<'a, Q>(
+        &'a self,
+        start: &Q,
+        end: &Q,
+    ) -> impl Iterator<Item = (Range<&'a K>, &'a V)>
+    where
+        K: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let range = overlapping(&self.starts, &self.ends, start, end);
+        range.map(move |i| (&self.starts[i]..&self.ends[i], &self.values[i]))
+    }
+}
+
+impl<K, V> Default for IntervalMap<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Archive, V: Archive> ArchivedIntervalMap<K, V> {
+    // The starts, ends, and values truncated to the same length, so that
+    // queries never index out of bounds.
+    fn parts(&self) -> (&[K::Archived], &[K::Archived], &[V::Archived]) {
+        let len = self
+            .values
+            .len()
+            .min(self.starts.len())
+            .min(self.ends.len());
+        (&self.starts[..len], &self.ends[..len], &self.values[..len])
+    }
+
+    /// Returns the number of ranges in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.parts().2.len()
+    }
+
+    /// Returns whether the map has no ranges.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the ranges and their values, ordered by the
+    /// start of each range.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (Range<&K::Archived>, &V::Archived)> {
+        let (starts, ends, values) = self.parts();
+        starts
+            .iter()
+            .zip(ends.iter())
+            .zip(values.iter())
+            .map(|((start, end), value)| (start..end, value))
+    }
+
+    /// Returns the value of the range containing the given point.
+    pub fn get<Q>(&self, point: &Q) -> Option<&V::Archived>
+    where
+        K::Archived: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let (starts, ends, values) = self.parts();
+        let index = stab(starts, ends, point)?;
+        Some(&values[index])
+    }
+
+    /// Returns an iterator over the ranges which overlap the half-open range
+    /// from `start` to `end`, and their values.
+    pub fn overlapping<'a, Q>(
+        &'a self,
+        start: &Q,
+        end: &Q,
+    ) -> impl Iterator<Item = (Range<&'a K::Archived>, &'a V::Archived)>
+    where
+        K::Archived: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let (starts, ends, values) = self.parts();
+        let range = overlapping(starts, ends, start, end);
+        range.map(move |i| (&starts[i]..&ends[i], &values[i]))
+    }
+}
+
+// Returns whether `a < b`, treating incomparable values as unordered.
+#[inline]
+fn lt<K: PartialOrd<Q>, Q: ?Sized>(a: &K, b: &Q) -> bool {
+    a.partial_cmp(b) == Some(Ordering::Less)
+}
+
+// Returns whether `a <= b`, treating incomparable values as unordered.
+#[inline]
+fn le<K: PartialOrd<Q>, Q: ?Sized>(a: &K, b: &Q) -> bool {
+    matches!(a.partial_cmp(b), Some(Ordering::Less | Ordering::Equal))
+}
+
+fn stab<K, Q>(starts: &[K], ends: &[K], point: &Q) -> Option<usize>
+where
+    K: PartialOrd<Q>,
+    Q: ?Sized,
+{
+    let index = starts.partition_point(|start| le(start, point));
+    let index = index.checked_sub(1)?;
+    if le(&ends[index], point) {
+        None
+    } else {
+        Some(index)
+    }
+}
+
+fn overlapping<K, Q>(
+    starts: &[K],
+    ends: &[K],
+    start: &Q,
+    end: &Q,
+) -> Range<usize>
+where
+    K: PartialOrd<Q>,
+    Q: ?Sized,
+{
+    // The ranges are sorted and disjoint, so their ends are sorted as well.
+    let first = ends.partition_point(|e| le(e, start));
+    let last = starts.partition_point(|s| lt(s, end));
+    first..last.max(first)
+}
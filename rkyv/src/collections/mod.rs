@@ -2,5 +2,8 @@
 
 pub mod btree_map;
 pub mod btree_set;
+#[cfg(feature = "alloc")]
+pub mod interval_map;
+pub mod sorted_vec;
 pub mod swiss_table;
 pub mod util;
@@ -0,0 +1,325 @@
+//! An archived vec whose elements are guaranteed to be sorted.
+
+use core::{cmp::Ordering, fmt, iter::FusedIterator, slice};
+
+use crate::{vec::ArchivedVec, Portable};
+
+/// An archived vec whose elements are in ascending order.
+///
+/// Sorted vecs are produced by archiving a field with
+/// [`AsSortedSlice`](crate::with::AsSortedSlice). Validating a sorted vec
+/// checks that its elements are sorted, so searches and set operations on it
+/// never give wrong results for validated data.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, to_bytes, with::AsSortedSlice,
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Postings {
+///     #[with(AsSortedSlice)]
+///     apples: Vec<u32>,
+///     #[with(AsSortedSlice)]
+///     pears: Vec<u32>,
+/// }
+///
+/// let value = Postings {
+///     apples: vec![9, 1, 4, 7],
+///     pears: vec![2, 4, 9],
+/// };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedPostings>(&bytes) };
+///
+/// assert!(archived.apples.contains(&7u32));
+/// assert_eq!(archived.apples.binary_search(&4u32), Ok(1));
+/// let both = archived
+///     .apples
+///     .intersection(&archived.pears)
+///     .map(|v| v.to_native())
+///     .collect::<Vec<_>>();
+/// assert_eq!(both, [4, 9]);
+/// ```
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(transparent)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedSortedVec<T> {
+    pub(crate) inner: ArchivedVec<T>,
+}
+
+impl<T> ArchivedSortedVec<T> {
+    /// Returns the elements of the sorted vec as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.inner.as_slice()
+    }
+
+    /// Returns the number of elements in the sorted vec.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns whether the sorted vec has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the element at the given index, or `None` if it is out of
+    /// bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns the smallest element of the sorted vec.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// Returns the largest element of the sorted vec.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.as_slice().last()
+    }
+
+    /// Returns an iterator over the elements of the sorted vec in ascending
+    /// order.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Binary searches the sorted vec for the given element.
+    ///
+    /// Returns the index of a matching element if one is found, or the index
+    /// where the element could be inserted to keep the elements sorted.
+    pub fn binary_search<Q>(&self, value: &Q) -> Result<usize, usize>
+    where
+        T: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        self.as_slice().binary_search_by(|v| {
+            v.partial_cmp(value).unwrap_or(Ordering::Less)
+        })
+    }
+
+    /// Returns whether the sorted vec contains the given element.
+    ///
+    /// Unlike `contains` on slices, this runs in `O(log n)` time.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        self.binary_search(value).is_ok()
+    }
+}
+
+impl<T: Ord> ArchivedSortedVec<T> {
+    /// Returns an iterator over the elements which are in this sorted vec,
+    /// `other`, or both, in ascending order.
+    ///
+    /// Elements which are in both sorted vecs are returned once, from this
+    /// sorted vec.
+    #[inline]
+    pub fn union<'a>(
+        &'a self,
+        other: &'a ArchivedSortedVec<T>,
+    ) -> Union<'a, T> {
+        Union {
+            left: self.as_slice(),
+            right: other.as_slice(),
+        }
+    }
+
+    /// Returns an iterator over the elements which are in both this sorted
+    /// vec and `other`, in ascending order.
+    ///
+    /// Elements are returned from this sorted vec.
+    #[inline]
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a ArchivedSortedVec<T>,
+    ) -> Intersection<'a, T> {
+        Intersection {
+            left: self.as_slice(),
+            right: other.as_slice(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArchivedSortedVec<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArchivedSortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<[U]> for ArchivedSortedVec<T> {
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self.as_slice().eq(other)
+    }
+}
+
+/// An iterator over the union of two archived sorted vecs.
+///
+/// This is returned by [`ArchivedSortedVec::union`].
+pub struct Union<'a, T> {
+    left: &'a [T],
+    right: &'a [T],
+}
+
+impl<'a, T: Ord> Iterator for Union<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let (left, right) = match (self.left.first(), self.right.first()) {
+            (Some(left), Some(right)) => (left, right),
+            (Some(left), None) => {
+                self.left = &self.left[1..];
+                return Some(left);
+            }
+            (None, Some(right)) => {
+                self.right = &self.right[1..];
+                return Some(right);
+            }
+            (None, None) => return None,
+        };
+        match left.cmp(right) {
+            Ordering::Less => {
+                self.left = &self.left[1..];
+                Some(left)
+            }
+            Ordering::Greater => {
+                self.right = &self.right[1..];
+                Some(right)
+            }
+            Ordering::Equal => {
+                self.left = &self.left[1..];
+                self.right = &self.right[1..];
+                Some(left)
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (left, right) = (self.left.len(), self.right.len());
+        (left.max(right), left.checked_add(right))
+    }
+}
+
+impl<T: Ord> FusedIterator for Union<'_, T> {}
+
+/// An iterator over the intersection of two archived sorted vecs.
+///
+/// This is returned by [`ArchivedSortedVec::intersection`].
+pub struct Intersection<'a, T> {
+    left: &'a [T],
+    right: &'a [T],
+}
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (left, right) = (self.left.first()?, self.right.first()?);
+            // Skip past runs of smaller elements with a binary search, which
+            // is much faster than stepping when one side is far larger.
+            match left.cmp(right) {
+                Ordering::Less => {
+                    let skip = self.left.partition_point(|v| v < right);
+                    self.left = &self.left[skip..];
+                }
+                Ordering::Greater => {
+                    let skip = self.right.partition_point(|v| v < left);
+                    self.right = &self.right[skip..];
+                }
+                Ordering::Equal => {
+                    self.left = &self.left[1..];
+                    self.right = &self.right[1..];
+                    return Some(left);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.left.len().min(self.right.len())))
+    }
+}
+
+impl<T: Ord> FusedIterator for Intersection<'_, T> {}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::fmt;
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+    use rancor::fail;
+
+    use super::ArchivedSortedVec;
+
+    #[derive(Debug)]
+    struct UnsortedError {
+        index: usize,
+    }
+
+    impl fmt::Display for UnsortedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "element {} of sorted vec is less than the element before it",
+                self.index,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for UnsortedError {}
+
+    unsafe impl<T, C> Verify<C> for ArchivedSortedVec<T>
+    where
+        T: Ord,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            let slice = self.as_slice();
+            for index in 1..slice.len() {
+                if slice[index] < slice[index - 1] {
+                    fail!(UnsortedError { index });
+                }
+            }
+            Ok(())
+        }
+    }
+}
@@ -14,6 +14,8 @@ use core::{
 use munge::munge;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
 use crate::{
     collections::{
         swiss_table::{ArchivedHashTable, HashTableResolver},
@@ -389,6 +391,35 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K, V, H> Export for ArchivedIndexMap<K, V, H>
+where
+    K: Export,
+    V: Export,
+{
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(k, v)| (Serde(k), Serde(v))))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, H> serde::Serialize for ArchivedIndexMap<K, V, H>
+where
+    K: Export,
+    V: Export,
+{
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<K, V, H> PartialEq for ArchivedIndexMap<K, V, H>
 where
     K: PartialEq,
@@ -551,8 +582,10 @@ mod verify {
             };
 
             let range = unsafe { context.push_prefix_subtree(ptr)? };
-            unsafe {
-                <[Entry<K, V>]>::check_bytes(ptr, context)?;
+            if context.should_check_subtree() {
+                unsafe {
+                    <[Entry<K, V>]>::check_bytes(ptr, context)?;
+                }
             }
             unsafe {
                 context.pop_subtree_range(range)?;
@@ -10,6 +10,8 @@ use core::{
 use munge::munge;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
 use crate::{
     collections::swiss_table::{
         index_map::Keys, ArchivedIndexMap, IndexMapResolver,
@@ -136,6 +138,27 @@ impl<K: fmt::Debug, H> fmt::Debug for ArchivedIndexSet<K, H> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K: Export, H> Export for ArchivedIndexSet<K, H> {
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Serde))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: Export, H> serde::Serialize for ArchivedIndexSet<K, H> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<K: PartialEq, H> PartialEq for ArchivedIndexSet<K, H> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().eq(other.iter())
@@ -10,26 +10,42 @@ use core::{
     pin::Pin,
 };
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
 use munge::munge;
+#[cfg(feature = "alloc")]
+use rancor::fail;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
+#[cfg(feature = "alloc")]
+use crate::{
+    analyze::{Analyze, TypeSizeReport},
+    collections::util::{DuplicateKey, DuplicateKeys},
+};
 use crate::{
     collections::{
         swiss_table::table::{ArchivedHashTable, HashTableResolver, RawIter},
         util::{Entry, EntryAdapter},
     },
-    hash::{hash_value, FxHasher64},
+    hash::{ArchivedHasherConfig, FxHasher64, HasherConfig},
     ser::{Allocator, Writer},
-    Place, Portable, Serialize,
+    ArchivedSize, Place, Portable, Serialize,
 };
 
 /// An archived SwissTable hash map.
+///
+/// The hasher used by the map is chosen during serialization and recorded in
+/// the archived map along with its seed. By default, the hasher `H` is used.
+/// See [`HasherConfig`] for the available alternatives.
 #[derive(Portable)]
 #[archive(crate)]
-#[repr(transparent)]
+#[repr(C)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
 pub struct ArchivedHashMap<K, V, H = FxHasher64> {
     table: ArchivedHashTable<Entry<K, V>>,
+    hasher: ArchivedHasherConfig,
     _phantom: PhantomData<H>,
 }
 
@@ -52,6 +68,12 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
         self.table.capacity()
     }
 
+    /// Returns the configuration of the hasher used by the hash map.
+    #[inline]
+    pub fn hasher(&self) -> &ArchivedHasherConfig {
+        &self.hasher
+    }
+
     /// Returns an iterator over the key-value entries in the hash map.
     #[inline]
     pub fn iter(&self) -> Iter<'_, K, V, H> {
@@ -98,6 +120,16 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
             _phantom: PhantomData,
         }
     }
+
+    /// Returns an iterator over pinned mutable references to the values in
+    /// the hash map.
+    ///
+    /// This is the same as [`values_mut`](Self::values_mut), and is named to
+    /// match [`ArchivedVec::iter_seal`](crate::vec::ArchivedVec::iter_seal).
+    #[inline]
+    pub fn values_seal(self: Pin<&mut Self>) -> ValuesMut<'_, K, V, H> {
+        self.values_mut()
+    }
 }
 
 impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
@@ -109,9 +141,8 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         Q: Hash + Eq + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
-        let entry = self
-            .table
-            .get_with(hash_value::<Q, H>(key), |e| cmp(key, &e.key))?;
+        let hash = self.hasher.hash_value::<Q, H>(key);
+        let entry = self.table.get_with(hash, |e| cmp(key, &e.key))?;
         Some((&entry.key, &entry.value))
     }
 
@@ -159,9 +190,9 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         Q: Hash + Eq + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
+        let hash = self.hasher.hash_value::<Q, H>(key);
         let table = unsafe { Pin::map_unchecked_mut(self, |s| &mut s.table) };
-        let entry = table
-            .get_with_mut(hash_value::<Q, H>(key), |e| cmp(key, &e.key))?;
+        let entry = table.get_with_mut(hash, |e| cmp(key, &e.key))?;
         let entry = unsafe { Pin::into_inner_unchecked(entry) };
         let key = &entry.key;
         let value = unsafe { Pin::new_unchecked(&mut entry.value) };
@@ -219,6 +250,7 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
     }
 
     /// Serializes an iterator of key-value pairs as a hash map.
+    #[inline]
     pub fn serialize_from_iter<'a, I, KU, VU, S>(
         iter: I,
         load_factor: (usize, usize),
@@ -231,13 +263,97 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         S: Fallible + Writer + Allocator + ?Sized,
         S::Error: Source,
     {
-        ArchivedHashTable::<Entry<K, V>>::serialize_from_iter(
-            iter.clone().map(|(key, value)| EntryAdapter { key, value }),
-            iter.map(|(key, _)| hash_value::<KU, H>(key)),
+        Self::serialize_from_iter_with_hasher(
+            iter,
             load_factor,
+            HasherConfig::default(),
             serializer,
         )
-        .map(HashMapResolver)
+    }
+
+    /// Serializes an iterator of key-value pairs as a hash map using the given
+    /// hasher configuration.
+    pub fn serialize_from_iter_with_hasher<'a, I, KU, VU, S>(
+        iter: I,
+        load_factor: (usize, usize),
+        hasher: HasherConfig,
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        I: Clone + ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        VU: 'a + Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        let table = ArchivedHashTable::<Entry<K, V>>::serialize_from_iter(
+            iter.clone().map(|(key, value)| EntryAdapter { key, value }),
+            iter.map(|(key, _)| hasher.hash_value::<KU, H>(key)),
+            load_factor,
+            serializer,
+        )?;
+        Ok(HashMapResolver { table, hasher })
+    }
+
+    /// Serializes an iterator of owned key-value pairs as a hash map,
+    /// handling duplicate keys according to `duplicates`. Returns the resolver
+    /// along with the number of entries in the map, which should be passed to
+    /// [`resolve_from_len`](ArchivedHashMap::resolve_from_len).
+    ///
+    /// Unlike [`serialize_from_iter`](ArchivedHashMap::serialize_from_iter),
+    /// the iterator doesn't need to know its length in advance or be cloned,
+    /// and its keys don't need to be unique. The pairs are buffered in a
+    /// `Vec` and deduplicated without building a native hash map.
+    #[cfg(feature = "alloc")]
+    pub fn serialize_from_pairs<I, KU, VU, S>(
+        iter: I,
+        load_factor: (usize, usize),
+        duplicates: DuplicateKeys,
+        serializer: &mut S,
+    ) -> Result<(HashMapResolver, usize), S::Error>
+    where
+        I: IntoIterator<Item = (KU, VU)>,
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        VU: Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        let hasher = HasherConfig::default();
+        let mut entries = iter
+            .into_iter()
+            .map(|(key, value)| (hasher.hash_value::<KU, H>(&key), key, value))
+            .collect::<Vec<_>>();
+        // Sorting by hash groups equal keys together while keeping them in the
+        // order they were yielded.
+        entries.sort_by_key(|(hash, ..)| *hash);
+
+        let mut len = 0;
+        let mut run_start = 0;
+        for i in 0..entries.len() {
+            if len == 0 || entries[len - 1].0 != entries[i].0 {
+                run_start = len;
+            }
+            match (run_start..len).find(|&j| entries[j].1 == entries[i].1) {
+                None => {
+                    entries.swap(len, i);
+                    len += 1;
+                }
+                Some(j) => match duplicates {
+                    DuplicateKeys::Error => fail!(DuplicateKey),
+                    DuplicateKeys::KeepFirst => (),
+                    DuplicateKeys::KeepLast => entries.swap(j, i),
+                },
+            }
+        }
+        entries.truncate(len);
+
+        let resolver = Self::serialize_from_iter_with_hasher(
+            entries.iter().map(|(_, key, value)| (key, value)),
+            load_factor,
+            hasher,
+            serializer,
+        )?;
+        Ok((resolver, len))
     }
 
     /// Resolves an archived hash map from a given length and parameters.
@@ -247,13 +363,37 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         resolver: HashMapResolver,
         out: Place<Self>,
     ) {
-        munge!(let ArchivedHashMap { table, _phantom: _ } = out);
+        munge!(let ArchivedHashMap { table, hasher, _phantom: _ } = out);
         ArchivedHashTable::<Entry<K, V>>::resolve_from_len(
             len,
             load_factor,
-            resolver.0,
+            resolver.table,
             table,
-        )
+        );
+        ArchivedHasherConfig::resolve_from_config(&resolver.hasher, hasher);
+    }
+}
+
+impl<K, V, H> ArchivedSize for ArchivedHashMap<K, V, H>
+where
+    K: ArchivedSize,
+    V: ArchivedSize,
+{
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.table.out_of_line_size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, H> Analyze for ArchivedHashMap<K, V, H>
+where
+    K: Analyze,
+    V: Analyze,
+{
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        self.table.analyze(report);
     }
 }
 
@@ -268,6 +408,35 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K, V, H> Export for ArchivedHashMap<K, V, H>
+where
+    K: Export,
+    V: Export,
+{
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(k, v)| (Serde(k), Serde(v))))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, H> serde::Serialize for ArchivedHashMap<K, V, H>
+where
+    K: Export,
+    V: Export,
+{
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<K, V, H> Eq for ArchivedHashMap<K, V, H>
 where
     K: Hash + Eq,
@@ -309,7 +478,11 @@ where
 }
 
 /// The resolver for [`ArchivedHashMap`].
-pub struct HashMapResolver(HashTableResolver);
+#[derive(Clone, Copy)]
+pub struct HashMapResolver {
+    table: HashTableResolver,
+    hasher: HasherConfig,
+}
 
 /// An iterator over the key-value pairs of an [`ArchivedHashMap`].
 pub struct Iter<'a, K, V, H> {
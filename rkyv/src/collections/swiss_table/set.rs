@@ -9,16 +9,19 @@ use core::{
 use munge::munge;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
 use crate::{
     collections::swiss_table::map::{ArchivedHashMap, HashMapResolver, Keys},
-    hash::FxHasher64,
+    hash::{ArchivedHasherConfig, FxHasher64, HasherConfig},
     ser::{Allocator, Writer},
-    Place, Portable, Serialize,
+    ArchivedSize, Place, Portable, Serialize,
 };
 
 /// An archived `HashSet`. This is a wrapper around a hash map with the same key
 /// and unit value.
-#[derive(Portable)]
+#[derive(ArchivedSize, Portable)]
+#[cfg_attr(feature = "alloc", derive(crate::Analyze))]
 #[archive(crate)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
 #[repr(transparent)]
@@ -44,6 +47,12 @@ impl<K, H> ArchivedHashSet<K, H> {
     pub fn iter(&self) -> Keys<K, (), H> {
         self.inner.keys()
     }
+
+    /// Returns the configuration of the hasher used by the hash set.
+    #[inline]
+    pub fn hasher(&self) -> &ArchivedHasherConfig {
+        self.inner.hasher()
+    }
 }
 
 impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
@@ -86,6 +95,29 @@ impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
         load_factor: (usize, usize),
         serializer: &mut S,
     ) -> Result<HashSetResolver, S::Error>
+    where
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+        I: Clone + ExactSizeIterator<Item = &'a KU>,
+    {
+        Self::serialize_from_iter_with_hasher(
+            iter,
+            load_factor,
+            HasherConfig::default(),
+            serializer,
+        )
+    }
+
+    /// Serializes an iterator of keys as a hash set using the given hasher
+    /// configuration.
+    #[inline]
+    pub fn serialize_from_iter_with_hasher<'a, KU, S, I>(
+        iter: I,
+        load_factor: (usize, usize),
+        hasher: HasherConfig,
+        serializer: &mut S,
+    ) -> Result<HashSetResolver, S::Error>
     where
         KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
         S: Fallible + Writer + Allocator + ?Sized,
@@ -93,9 +125,10 @@ impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
         I: Clone + ExactSizeIterator<Item = &'a KU>,
     {
         Ok(HashSetResolver(
-            ArchivedHashMap::<K, (), H>::serialize_from_iter(
+            ArchivedHashMap::<K, (), H>::serialize_from_iter_with_hasher(
                 iter.map(|x| (x, &())),
                 load_factor,
+                hasher,
                 serializer,
             )?,
         ))
@@ -108,6 +141,27 @@ impl<K: fmt::Debug, H> fmt::Debug for ArchivedHashSet<K, H> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<K: Export, H> Export for ArchivedHashSet<K, H> {
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Serde))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: Export, H> serde::Serialize for ArchivedHashSet<K, H> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<K: Hash + Eq, H: Hasher + Default> PartialEq for ArchivedHashSet<K, H> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
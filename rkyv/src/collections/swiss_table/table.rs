@@ -29,13 +29,15 @@ use core::{
 use munge::munge;
 use rancor::{fail, Fallible, OptionExt, Panic, ResultExt as _, Source};
 
+#[cfg(feature = "alloc")]
+use crate::analyze::{Analyze, TypeSizeReport};
 use crate::{
     collections::util::IteratorLengthMismatch,
     primitive::ArchivedUsize,
     ser::{Allocator, Writer, WriterExt},
     simd::{Bitmask, Group, MAX_GROUP_WIDTH},
     util::SerVec,
-    Archive as _, Place, Portable, RawRelPtr, Serialize,
+    Archive as _, ArchivedSize, Place, Portable, RawRelPtr, Serialize,
 };
 
 /// A low-level archived SwissTable hash table with explicit hashing.
@@ -77,11 +79,9 @@ impl ProbeSeq {
 
     #[inline]
     fn move_next(&mut self, bucket_mask: usize) {
-        loop {
-            self.pos += self.stride;
-            self.pos &= bucket_mask;
-            self.stride += MAX_GROUP_WIDTH;
-        }
+        self.pos += self.stride;
+        self.pos &= bucket_mask;
+        self.stride += MAX_GROUP_WIDTH;
     }
 }
 
@@ -451,10 +451,50 @@ impl<T> ArchivedHashTable<T> {
 }
 
 /// The resolver for [`ArchivedHashTable`].
+#[derive(Clone, Copy)]
 pub struct HashTableResolver {
     pos: usize,
 }
 
+impl<T: ArchivedSize> ArchivedSize for ArchivedHashTable<T> {
+    fn out_of_line_size(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let capacity = self.capacity();
+        let buckets = capacity * size_of::<T>();
+        let controls = capacity + MAX_GROUP_WIDTH - 1;
+        let entries = self
+            .raw_iter()
+            // SAFETY: The pointers returned by `raw_iter` always point to
+            // initialized entries in the hash table.
+            .map(|entry| unsafe { entry.as_ref() }.out_of_line_size())
+            .sum::<usize>();
+
+        buckets + controls + entries
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Analyze> Analyze for ArchivedHashTable<T> {
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        if self.is_empty() {
+            return;
+        }
+
+        let capacity = self.capacity();
+        let buckets = capacity * size_of::<T>();
+        let controls = capacity + MAX_GROUP_WIDTH - 1;
+        report.record_out_of_line_bytes(buckets + controls);
+        for entry in self.raw_iter() {
+            // SAFETY: The pointers returned by `raw_iter` always point to
+            // initialized entries in the hash table.
+            report.record(unsafe { entry.as_ref() });
+        }
+    }
+}
+
 struct ControlIter {
     current_mask: Bitmask,
     next_group: *const u8,
@@ -617,24 +657,28 @@ mod verify {
             let range = unsafe { context.push_prefix_subtree(ptr)? };
 
             // Check each non-empty bucket
+            if context.should_check_subtree() {
+                // SAFETY: We have checked that `self` is not empty.
+                let mut controls = unsafe { self.control_iter() };
+                let mut base_index = 0;
+                'outer: while base_index < cap {
+                    while let Some(bit) = controls.next_full() {
+                        let index = base_index + bit;
+                        if index >= cap {
+                            break 'outer;
+                        }
 
-            // SAFETY: We have checked that `self` is not empty.
-            let mut controls = unsafe { self.control_iter() };
-            let mut base_index = 0;
-            'outer: while base_index < cap {
-                while let Some(bit) = controls.next_full() {
-                    let index = base_index + bit;
-                    if index >= cap {
-                        break 'outer;
+                        unsafe {
+                            T::check_bytes(
+                                self.bucket(index).as_ptr(),
+                                context,
+                            )?;
+                        }
                     }
 
-                    unsafe {
-                        T::check_bytes(self.bucket(index).as_ptr(), context)?;
-                    }
+                    controls.move_next();
+                    base_index += Group::WIDTH;
                 }
-
-                controls.move_next();
-                base_index += Group::WIDTH;
             }
 
             // Verify that wrapped bytes are set correctly
@@ -5,7 +5,7 @@ use core::fmt;
 use munge::munge;
 use rancor::Fallible;
 
-use crate::{Archive, Place, Portable, Serialize};
+use crate::{Archive, ArchivedSize, Place, Portable, Serialize};
 
 // TODO: can this be replaced with custom resolve functions?
 /// An adapter which serializes and resolves its key and value references.
@@ -53,7 +53,10 @@ where
 }
 
 /// A key-value entry.
-#[derive(Debug, Portable, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    ArchivedSize, Debug, Portable, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[cfg_attr(feature = "alloc", derive(crate::Analyze))]
 #[archive(crate)]
 #[repr(C)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
@@ -87,3 +90,30 @@ impl fmt::Display for IteratorLengthMismatch {
 
 #[cfg(feature = "std")]
 impl std::error::Error for IteratorLengthMismatch {}
+
+/// How to handle duplicate keys when serializing a map from an iterator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Return an error if the iterator yields a key more than once.
+    #[default]
+    Error,
+    /// Keep the first value yielded for each key.
+    KeepFirst,
+    /// Keep the last value yielded for each key.
+    KeepLast,
+}
+
+/// An error describing that an iterator yielded the same key more than once.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub(crate) struct DuplicateKey;
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "iterator yielded the same key more than once")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DuplicateKey {}
@@ -0,0 +1,131 @@
+//! Archived versions of `convert` types.
+
+use core::fmt;
+
+use crate::Portable;
+
+/// An archived [`Infallible`](::core::convert::Infallible).
+///
+/// Like `Infallible`, it has no values. It lets types like
+/// `Result<T, Infallible>` and enums with uninhabited variants be archived,
+/// and validation rejects any archive which claims to contain one.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ArchivedInfallible {}
+
+// SAFETY: `ArchivedInfallible` has no values, so it has no layout to differ
+// between targets.
+unsafe impl Portable for ArchivedInfallible {}
+
+impl ArchivedInfallible {
+    /// Converts the value into any type, since it can never exist.
+    #[inline]
+    pub fn into_any<T>(self) -> T {
+        match self {}
+    }
+}
+
+impl fmt::Debug for ArchivedInfallible {
+    #[inline]
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
+
+impl fmt::Display for ArchivedInfallible {
+    #[inline]
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArchivedInfallible {}
+
+/// The error returned when converting an integer tag into an enum fails.
+///
+/// Fieldless enums which derive `Archive` with `#[archive(try_from_tag)]`
+/// implement `TryFrom<u8>` for both the enum and its archived counterpart.
+/// The conversion fails with this error if no variant has the given tag.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{convert::InvalidTag, Archive};
+///
+/// #[derive(Archive, Debug, PartialEq)]
+/// #[archive(try_from_tag)]
+/// enum Opcode {
+///     Read = 1,
+///     Write = 2,
+/// }
+///
+/// assert_eq!(Opcode::try_from(2), Ok(Opcode::Write));
+/// assert_eq!(Opcode::try_from(3), Err(InvalidTag::new(3)));
+/// assert_eq!(u8::from(Opcode::Read), 1);
+/// assert_eq!(ArchivedOpcode::try_from(1).unwrap().tag(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InvalidTag {
+    tag: u8,
+}
+
+impl InvalidTag {
+    /// Returns a new error for the given tag.
+    #[inline]
+    pub const fn new(tag: u8) -> Self {
+        Self { tag }
+    }
+
+    /// Returns the tag which did not match any variant.
+    #[inline]
+    pub const fn tag(&self) -> u8 {
+        self.tag
+    }
+}
+
+impl fmt::Display for InvalidTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tag {} does not match any enum variant", self.tag)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidTag {}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::fmt;
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        CheckBytes,
+    };
+    use rancor::fail;
+
+    use super::ArchivedInfallible;
+
+    #[derive(Debug)]
+    struct UninhabitedError;
+
+    impl fmt::Display for UninhabitedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "archive contains a value of an uninhabited type")
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for UninhabitedError {}
+
+    unsafe impl<C> CheckBytes<C> for ArchivedInfallible
+    where
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            _: *const Self,
+            _: &mut C,
+        ) -> Result<(), C::Error> {
+            fail!(UninhabitedError);
+        }
+    }
+}
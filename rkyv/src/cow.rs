@@ -0,0 +1,326 @@
+//! Copy-on-write editing of archived data.
+
+use core::{
+    any::TypeId,
+    fmt,
+    mem::{size_of, MaybeUninit},
+};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use rancor::{fail, Failure, Source, Strategy};
+
+use crate::{
+    extract::{extract, Extract, ExtractUnsized as _},
+    ser::AllocSerializer,
+    util::{access_pos_unchecked, access_unchecked, to_bytes, AlignedVec},
+    Place, Portable, Serialize,
+};
+
+#[derive(Debug)]
+struct PatchFailed;
+
+impl fmt::Display for PatchFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to apply a patch to the archive")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PatchFailed {}
+
+type ApplyFn =
+    unsafe fn(&[u8], &mut AllocSerializer, usize) -> Result<(), Failure>;
+
+struct Patch {
+    offset: usize,
+    len: usize,
+    type_id: TypeId,
+    bytes: AlignedVec,
+    apply: ApplyFn,
+}
+
+/// Resolves the root of a patch archive into the given position of the
+/// serializer's buffer.
+///
+/// # Safety
+///
+/// `bytes` must contain a valid `F` at its root position, and `pos` must be
+/// the position of an `F` which has already been written to the serializer.
+unsafe fn apply_patch<F>(
+    bytes: &[u8],
+    serializer: &mut AllocSerializer,
+    pos: usize,
+) -> Result<(), Failure>
+where
+    F: Extract<Strategy<AllocSerializer, Failure>>,
+{
+    // SAFETY: The caller has guaranteed that `bytes` contains a valid `F` at
+    // its root position.
+    let value = unsafe { access_unchecked::<F>(bytes) };
+    let resolver = value.extract(Strategy::wrap(serializer))?;
+
+    let mut resolved = MaybeUninit::<F>::zeroed();
+    // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed `MaybeUninit`,
+    // and so is properly aligned, dereferenceable, and all of its bytes are
+    // initialized.
+    let out = unsafe { Place::new_unchecked(pos, resolved.as_mut_ptr()) };
+    value.resolve_extracted(resolver, out);
+    serializer.writer.as_mut_slice()[pos..pos + size_of::<F>()]
+        .copy_from_slice(out.as_slice());
+
+    Ok(())
+}
+
+/// A mutable overlay of patches on top of a read-only archived value.
+///
+/// Reading a field through a `CowArchive` falls through to the archive unless
+/// the field has been patched. Patches are archived when they are set, so
+/// reads always return archived values. [`freeze`](CowArchive::freeze)
+/// re-serializes the archived value with all of its patches applied into a
+/// new, compact archive without deserializing it.
+///
+/// Fields are selected with projections from the root value, like
+/// `|root| &root.name`. A projection must return a field which is stored
+/// inline in the root value, including fields of fields stored inline. Fields
+/// which are behind pointers (e.g. inside an archived `Box` or `Vec`) cannot be
+/// patched individually; patch the field which contains the pointer instead.
+///
+/// Patches are matched by their location in the root value. Reading a field
+/// which contains a patched field returns the value without that patch applied
+/// until the overlay is frozen.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, cow::CowArchive, rancor::Error, to_bytes, Archive,
+///     Extract, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(Extract))]
+/// struct Asset {
+///     name: String,
+///     version: u32,
+///     data: Vec<u8>,
+/// }
+///
+/// let asset = Asset {
+///     name: "mesh".to_string(),
+///     version: 1,
+///     data: vec![0; 1024],
+/// };
+/// let bytes = to_bytes::<Error>(&asset).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedAsset>(&bytes) };
+///
+/// let mut cow = CowArchive::new(archived);
+/// cow.set::<u32, Error>(|a| &a.version, &2).unwrap();
+/// cow.set::<String, Error>(|a| &a.name, &"a new name".to_string())
+///     .unwrap();
+/// assert_eq!(*cow.get(|a| &a.version), 2);
+/// assert_eq!(archived.version, 1);
+///
+/// let frozen = cow.freeze::<Error>().unwrap();
+/// let edited = unsafe { access_unchecked::<ArchivedAsset>(&frozen) };
+/// assert_eq!(edited.name, "a new name");
+/// assert_eq!(edited.version, 2);
+/// assert_eq!(edited.data.len(), 1024);
+/// ```
+pub struct CowArchive<'a, T> {
+    root: &'a T,
+    patches: Vec<Patch>,
+}
+
+impl<'a, T: Portable> CowArchive<'a, T> {
+    /// Creates a new `CowArchive` with no patches over the given archived
+    /// value.
+    #[inline]
+    pub fn new(root: &'a T) -> Self {
+        Self {
+            root,
+            patches: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying archived value without any patches applied.
+    #[inline]
+    pub fn archived(&self) -> &'a T {
+        self.root
+    }
+
+    /// Returns whether any fields have been patched.
+    #[inline]
+    pub fn is_patched(&self) -> bool {
+        !self.patches.is_empty()
+    }
+
+    /// Returns the number of patched fields.
+    #[inline]
+    pub fn patch_count(&self) -> usize {
+        self.patches.len()
+    }
+
+    fn offset_of<F>(&self, field: impl FnOnce(&T) -> &F) -> usize {
+        let start = self.root as *const T as usize;
+        let addr = field(self.root) as *const F as usize;
+        assert!(
+            addr >= start && addr + size_of::<F>() <= start + size_of::<T>(),
+            "the projected field is not stored inline in the root value",
+        );
+        addr - start
+    }
+
+    fn find_patch<F: 'static>(&self, offset: usize) -> Option<&Patch> {
+        self.patches.iter().rev().find(|patch| {
+            patch.offset == offset && patch.type_id == TypeId::of::<F>()
+        })
+    }
+
+    /// Returns the current value of a field, with its patch applied if it has
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the projected field is not stored inline in the root value.
+    pub fn get<F>(&self, field: impl FnOnce(&T) -> &F) -> &F
+    where
+        F: Portable + 'static,
+    {
+        let offset = self.offset_of(field);
+        match self.find_patch::<F>(offset) {
+            // SAFETY: Patches with the type ID of `F` contain a valid `F` at
+            // their root position.
+            Some(patch) => unsafe { access_unchecked::<F>(&patch.bytes) },
+            // SAFETY: `offset_of` checked that an `F` is located at `offset`
+            // in the root value.
+            None => unsafe {
+                &*(self.root as *const T).cast::<u8>().add(offset).cast::<F>()
+            },
+        }
+    }
+
+    /// Returns whether a field has been patched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the projected field is not stored inline in the root value.
+    pub fn is_field_patched<F>(&self, field: impl FnOnce(&T) -> &F) -> bool
+    where
+        F: Portable + 'static,
+    {
+        let offset = self.offset_of(field);
+        self.find_patch::<F>(offset).is_some()
+    }
+
+    /// Patches a field with a new value.
+    ///
+    /// The value is archived immediately, and replaces any previous patches
+    /// to the same field or to fields contained in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the projected field is not stored inline in the root value.
+    pub fn set<U, E>(
+        &mut self,
+        field: impl FnOnce(&T) -> &U::Archived,
+        value: &U,
+    ) -> Result<(), E>
+    where
+        U: Serialize<Strategy<AllocSerializer, E>>,
+        U::Archived: Extract<Strategy<AllocSerializer, Failure>> + 'static,
+    {
+        let offset = self.offset_of(field);
+        let len = size_of::<U::Archived>();
+        let bytes = to_bytes::<E>(value)?;
+
+        self.patches.retain(|patch| {
+            patch.offset < offset || patch.offset + patch.len > offset + len
+        });
+        self.patches.push(Patch {
+            offset,
+            len,
+            type_id: TypeId::of::<U::Archived>(),
+            bytes,
+            apply: apply_patch::<U::Archived>,
+        });
+
+        Ok(())
+    }
+
+    /// Removes the patch for a field.
+    ///
+    /// Returns whether the field was patched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the projected field is not stored inline in the root value.
+    pub fn revert<F>(&mut self, field: impl FnOnce(&T) -> &F) -> bool
+    where
+        F: Portable + 'static,
+    {
+        let offset = self.offset_of(field);
+        let len = self.patches.len();
+        self.patches.retain(|patch| {
+            patch.offset != offset || patch.type_id != TypeId::of::<F>()
+        });
+        self.patches.len() != len
+    }
+
+    /// Removes all patches.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.patches.clear();
+    }
+
+    /// Re-serializes the archived value with all of its patches applied into
+    /// a new archive.
+    ///
+    /// Data which is only reachable from patched fields is not copied into
+    /// the new archive.
+    pub fn freeze<E>(&self) -> Result<AlignedVec, E>
+    where
+        T: Extract<Strategy<AllocSerializer, E>>,
+        E: Source,
+    {
+        if self.patches.is_empty() {
+            return extract::<T, E>(self.root);
+        }
+
+        let mut serializer = AllocSerializer::default();
+        let root_pos =
+            self.root.extract_unsized(Strategy::wrap(&mut serializer))?;
+        for patch in self.patches.iter() {
+            // SAFETY: The patch bytes contain the type that `apply` was
+            // instantiated with, and the same type is located at the offset
+            // of the patch in the root value.
+            let result = unsafe {
+                (patch.apply)(
+                    &patch.bytes,
+                    &mut serializer,
+                    root_pos + patch.offset,
+                )
+            };
+            if result.is_err() {
+                fail!(PatchFailed);
+            }
+        }
+
+        // The patched fields may still point to data copied from the original
+        // archive, so extract the patched root again to drop it.
+        let bytes = serializer.into_writer();
+        // SAFETY: The root was extracted to `root_pos` and all of the patches
+        // were applied in place, so `root_pos` contains a valid `T`.
+        let root = unsafe { access_pos_unchecked::<T>(&bytes, root_pos) };
+        extract::<T, E>(root)
+    }
+}
+
+impl<T: Portable + fmt::Debug> fmt::Debug for CowArchive<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CowArchive")
+            .field("root", &self.root)
+            .field("patch_count", &self.patches.len())
+            .finish()
+    }
+}
@@ -0,0 +1,210 @@
+//! Zero-copy deserialization from [`Bytes`]-backed buffers.
+//!
+//! When an archive is stored in a reference-counted buffer, byte fields can be
+//! deserialized as [`Bytes`] which share the original allocation instead of
+//! copying out of it. Wrap a deserializer in [`BytesBacked`] and annotate the
+//! fields with [`ShareBytes`](crate::with::ShareBytes) to enable this.
+//!
+//! String fields can be deserialized as `Arc<str>` with the same wrapper. An
+//! `Arc<str>` can't point into another allocation, so each archived string is
+//! copied once and every later request for it returns the same `Arc`.
+
+use core::alloc::Layout;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::{collections::BTreeMap, sync::Arc};
+#[cfg(feature = "std")]
+use ::std::{collections::BTreeMap, sync::Arc};
+use bytes::Bytes;
+use rancor::Strategy;
+
+use crate::de::{ErasedPtr, Metering, Pooling};
+
+/// A deserializer which knows the reference-counted buffer its archive is
+/// backed by.
+pub trait BackingBytes {
+    /// Returns the buffer backing the archive being deserialized.
+    fn backing_bytes(&self) -> &Bytes;
+
+    /// Returns a `Bytes` containing the given slice.
+    ///
+    /// If the slice is located inside the backing buffer, the returned `Bytes`
+    /// shares its allocation. Otherwise, the slice is copied.
+    #[inline]
+    fn share_bytes(&self, slice: &[u8]) -> Bytes {
+        let backing = self.backing_bytes();
+        let start = backing.as_ptr() as usize;
+        let end = start + backing.len();
+        let slice_start = slice.as_ptr() as usize;
+        let slice_end = slice_start + slice.len();
+
+        if slice.is_empty() {
+            Bytes::new()
+        } else if start <= slice_start && slice_end <= end {
+            backing.slice_ref(slice)
+        } else {
+            Bytes::copy_from_slice(slice)
+        }
+    }
+
+    /// Returns an `Arc<str>` containing the given string.
+    ///
+    /// The default implementation copies the string every time. Deserializers
+    /// which cache strings located inside the backing buffer should override
+    /// this to return the cached `Arc`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn share_str(&mut self, s: &str) -> Arc<str> {
+        Arc::from(s)
+    }
+}
+
+impl<T, E> BackingBytes for Strategy<T, E>
+where
+    T: BackingBytes + ?Sized,
+{
+    #[inline]
+    fn backing_bytes(&self) -> &Bytes {
+        T::backing_bytes(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn share_str(&mut self, s: &str) -> Arc<str> {
+        T::share_str(self, s)
+    }
+}
+
+/// A deserializer adapter which is backed by a reference-counted buffer.
+///
+/// `BytesBacked` forwards [`Pooling`] to the wrapped deserializer so that it
+/// can be combined with a shared pointer strategy.
+///
+/// Strings from the backing buffer which are deserialized as `Arc<str>` are
+/// cached by their position in the buffer. Deserializing the same archived
+/// string again, for example when the same archive is deserialized several
+/// times with one `BytesBacked`, returns a clone of the cached `Arc` without
+/// copying.
+///
+/// # Example
+///
+/// ```
+/// use bytes::Bytes;
+/// use rkyv::{
+///     access_unchecked, de::bytes::BytesBacked, deserialize, rancor::Error,
+///     to_bytes, with::ShareBytes, Archive, Deserialize, Serialize,
+/// };
+///
+/// #[derive(Archive, Deserialize, Serialize)]
+/// struct Packet {
+///     id: u32,
+///     #[with(ShareBytes)]
+///     payload: Bytes,
+/// }
+///
+/// let value = Packet {
+///     id: 42,
+///     payload: Bytes::from_static(b"hello world"),
+/// };
+/// let buffer = Bytes::from_owner(to_bytes::<Error>(&value).unwrap());
+///
+/// let archived = unsafe { access_unchecked::<ArchivedPacket>(&buffer) };
+/// let mut deserializer = BytesBacked::new(buffer.clone(), ());
+/// let packet =
+///     deserialize::<Packet, _, Error>(archived, &mut deserializer).unwrap();
+///
+/// assert_eq!(packet.payload, value.payload);
+/// // The payload points into the archive buffer
+/// let range = buffer.as_ptr_range();
+/// assert!(range.contains(&packet.payload.as_ptr()));
+/// ```
+#[derive(Debug)]
+pub struct BytesBacked<D = ()> {
+    buffer: Bytes,
+    #[cfg(feature = "alloc")]
+    strings: BTreeMap<(usize, usize), Arc<str>>,
+    inner: D,
+}
+
+impl<D> BytesBacked<D> {
+    /// Creates a new deserializer backed by the given buffer.
+    #[inline]
+    pub fn new(buffer: Bytes, inner: D) -> Self {
+        Self {
+            buffer,
+            #[cfg(feature = "alloc")]
+            strings: BTreeMap::new(),
+            inner,
+        }
+    }
+
+    /// Creates a new deserializer backed by the given shared slice.
+    ///
+    /// The slice is not copied; the resulting `Bytes` keep the `Arc` alive.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn from_arc(buffer: Arc<[u8]>, inner: D) -> Self {
+        Self::new(Bytes::from_owner(buffer), inner)
+    }
+
+    /// Returns the buffer backing this deserializer.
+    #[inline]
+    pub fn buffer(&self) -> &Bytes {
+        &self.buffer
+    }
+
+    /// Consumes the adapter and returns the wrapped deserializer.
+    #[inline]
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D> BackingBytes for BytesBacked<D> {
+    #[inline]
+    fn backing_bytes(&self) -> &Bytes {
+        &self.buffer
+    }
+
+    #[cfg(feature = "alloc")]
+    fn share_str(&mut self, s: &str) -> Arc<str> {
+        let start = self.buffer.as_ptr() as usize;
+        let end = start + self.buffer.len();
+        let s_start = s.as_ptr() as usize;
+
+        if s.is_empty() || s_start < start || s_start + s.len() > end {
+            return Arc::from(s);
+        }
+
+        self.strings
+            .entry((s_start - start, s.len()))
+            .or_insert_with(|| Arc::from(s))
+            .clone()
+    }
+}
+
+impl<D: Pooling<E>, E> Pooling<E> for BytesBacked<D> {
+    #[inline]
+    fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    unsafe fn add_shared_ptr(
+        &mut self,
+        address: usize,
+        ptr: ErasedPtr,
+        drop: unsafe fn(ErasedPtr),
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `add_shared_ptr` are the same as
+        // the requirements for calling this function.
+        unsafe { self.inner.add_shared_ptr(address, ptr, drop) }
+    }
+}
+
+impl<D: Metering<E>, E> Metering<E> for BytesBacked<D> {
+    #[inline]
+    fn meter(&mut self, layout: Layout) -> Result<(), E> {
+        self.inner.meter(layout)
+    }
+}
@@ -0,0 +1,229 @@
+//! Cooperative deserialization for async runtimes.
+//!
+//! Deserializing a large archive can take long enough to starve other tasks
+//! scheduled on the same executor thread. The drivers in this module
+//! deserialize the top-level elements of large collections one at a time and
+//! periodically yield back to the executor according to a [`Budget`].
+//!
+//! Yielding does not depend on any particular runtime: the driver returns
+//! `Poll::Pending` once and immediately wakes its own task, which works with
+//! any executor.
+
+use core::{
+    future::Future,
+    mem::size_of,
+    num::NonZeroUsize,
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use rancor::Fallible;
+
+#[cfg(feature = "std")]
+use crate::collections::swiss_table::ArchivedHashMap;
+use crate::{vec::ArchivedVec, Archive, Deserialize};
+
+/// How much work a cooperative driver may do before yielding.
+///
+/// A budget can limit the number of elements deserialized, the number of
+/// inline archived bytes consumed, or both. The driver yields as soon as
+/// either limit is reached.
+///
+/// Inline bytes are the size of the archived elements themselves. Data which
+/// the elements point to, like the contents of an archived string, is not
+/// counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    elements: Option<NonZeroUsize>,
+    inline_bytes: Option<NonZeroUsize>,
+}
+
+impl Budget {
+    /// Returns a budget which never yields.
+    #[inline]
+    pub const fn unlimited() -> Self {
+        Self {
+            elements: None,
+            inline_bytes: None,
+        }
+    }
+
+    /// Returns a budget which yields after every `count` elements.
+    #[inline]
+    pub const fn elements(count: NonZeroUsize) -> Self {
+        Self {
+            elements: Some(count),
+            inline_bytes: None,
+        }
+    }
+
+    /// Returns a budget which yields after consuming `count` inline archived
+    /// bytes.
+    #[inline]
+    pub const fn inline_bytes(count: NonZeroUsize) -> Self {
+        Self {
+            elements: None,
+            inline_bytes: Some(count),
+        }
+    }
+
+    /// Sets the element limit of this budget.
+    #[inline]
+    pub const fn with_elements(mut self, count: NonZeroUsize) -> Self {
+        self.elements = Some(count);
+        self
+    }
+
+    /// Sets the inline byte limit of this budget.
+    #[inline]
+    pub const fn with_inline_bytes(mut self, count: NonZeroUsize) -> Self {
+        self.inline_bytes = Some(count);
+        self
+    }
+}
+
+/// Tracks consumption of a [`Budget`] and yields when it is exhausted.
+///
+/// This can be used to write cooperative drivers for collections not covered
+/// by this module.
+#[derive(Debug)]
+pub struct Cooperative {
+    budget: Budget,
+    elements: usize,
+    bytes: usize,
+}
+
+impl Cooperative {
+    /// Creates a new cooperative tracker with the given budget.
+    #[inline]
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            elements: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Records that one element of `bytes` inline archived bytes was
+    /// processed, and yields to the executor if the budget has been exhausted.
+    pub async fn consume(&mut self, bytes: usize) {
+        self.elements += 1;
+        self.bytes = self.bytes.saturating_add(bytes);
+
+        let elements_exhausted = self
+            .budget
+            .elements
+            .is_some_and(|limit| self.elements >= limit.get());
+        let bytes_exhausted = self
+            .budget
+            .inline_bytes
+            .is_some_and(|limit| self.bytes >= limit.get());
+
+        if elements_exhausted || bytes_exhausted {
+            self.elements = 0;
+            self.bytes = 0;
+            yield_now().await;
+        }
+    }
+}
+
+/// Returns a future which yields to the executor once before completing.
+#[inline]
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// A future which yields to the executor once before completing.
+///
+/// This is returned by [`yield_now`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Deserializes an archived slice, yielding to the executor according to the
+/// given budget.
+pub async fn deserialize_slice<T, D>(
+    archived: &[T::Archived],
+    deserializer: &mut D,
+    budget: Budget,
+) -> Result<Vec<T>, D::Error>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    let mut coop = Cooperative::new(budget);
+    let mut result = Vec::with_capacity(archived.len());
+    for item in archived {
+        result.push(item.deserialize(deserializer)?);
+        coop.consume(size_of::<T::Archived>()).await;
+    }
+    Ok(result)
+}
+
+/// Deserializes an archived vec, yielding to the executor according to the
+/// given budget.
+#[inline]
+pub async fn deserialize_vec<T, D>(
+    archived: &ArchivedVec<T::Archived>,
+    deserializer: &mut D,
+    budget: Budget,
+) -> Result<Vec<T>, D::Error>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    deserialize_slice(archived.as_slice(), deserializer, budget).await
+}
+
+/// Deserializes an archived hash map, yielding to the executor according to
+/// the given budget.
+#[cfg(feature = "std")]
+pub async fn deserialize_hash_map<K, V, S, D>(
+    archived: &ArchivedHashMap<K::Archived, V::Archived>,
+    deserializer: &mut D,
+    budget: Budget,
+) -> Result<HashMap<K, V, S>, D::Error>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D> + Hash + Eq,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+    S: Default + BuildHasher,
+{
+    let mut coop = Cooperative::new(budget);
+    let mut result =
+        HashMap::with_capacity_and_hasher(archived.len(), S::default());
+    for (k, v) in archived.iter() {
+        result
+            .insert(k.deserialize(deserializer)?, v.deserialize(deserializer)?);
+        coop.consume(size_of::<K::Archived>() + size_of::<V::Archived>())
+            .await;
+    }
+    Ok(result)
+}
@@ -1,6 +1,13 @@
 //! Deserialization traits, deserializers, and adapters.
 
+#[cfg(feature = "bytes")]
+pub mod bytes;
+#[cfg(feature = "alloc")]
+pub mod coop;
 pub mod pooling;
+pub mod quota;
 
 #[doc(inline)]
 pub use self::pooling::*;
+#[doc(inline)]
+pub use self::quota::Metering;
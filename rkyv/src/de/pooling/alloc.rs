@@ -1,6 +1,6 @@
 //! Adapters wrap deserializers and add support for deserializer traits.
 
-use core::{fmt, mem::size_of};
+use core::{fmt, hash::BuildHasher, mem::size_of};
 #[cfg(feature = "std")]
 use std::collections::hash_map;
 
@@ -9,6 +9,7 @@ use hashbrown::hash_map;
 use rancor::{fail, Source};
 
 use super::{ErasedPtr, Pooling};
+use crate::hash::DefaultHashBuilder;
 
 #[derive(Debug)]
 struct DuplicateSharedPointer {
@@ -45,9 +46,12 @@ impl Drop for SharedPointer {
 
 /// A shared pointer strategy that unifies deserializations of the same shared
 /// pointer.
-#[derive(Default)]
-pub struct Unify {
-    shared_pointers: hash_map::HashMap<usize, SharedPointer>,
+///
+/// The hasher used to look up shared pointers can be customized with `H`. It
+/// is only used while deserializing, so a fast hasher like
+/// [`FxBuildHasher`](crate::hash::FxBuildHasher) can be used safely.
+pub struct Unify<H = DefaultHashBuilder> {
+    shared_pointers: hash_map::HashMap<usize, SharedPointer, H>,
 }
 
 impl Unify {
@@ -60,19 +64,45 @@ impl Unify {
     /// Creates a new shared pointer unifier with initial capacity.
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl Default for Unify {
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<H> Unify<H> {
+    /// Creates a new shared pointer unifier which uses the given hasher.
+    #[inline]
+    pub fn with_hasher(hasher: H) -> Self {
+        Self {
+            shared_pointers: hash_map::HashMap::with_hasher(hasher),
+        }
+    }
+
+    /// Creates a new shared pointer unifier with initial capacity which uses
+    /// the given hasher.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: H) -> Self {
         Self {
-            shared_pointers: hash_map::HashMap::with_capacity(capacity),
+            shared_pointers: hash_map::HashMap::with_capacity_and_hasher(
+                capacity, hasher,
+            ),
         }
     }
 }
 
-impl fmt::Debug for Unify {
+impl<H> fmt::Debug for Unify<H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_map().entries(self.shared_pointers.iter()).finish()
     }
 }
 
-impl<E: Source> Pooling<E> for Unify {
+impl<H: BuildHasher, E: Source> Pooling<E> for Unify<H> {
     fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
         self.shared_pointers.get(&address).map(|p| p.ptr)
     }
@@ -0,0 +1,252 @@
+//! Allocation quotas for deserializing untrusted archives.
+//!
+//! Validation bounds the size of an archive, but deserializing it can still
+//! allocate much more memory than the archive occupies. For example, an
+//! archived `Vec<String>` of empty strings uses far fewer bytes than the
+//! deserialized vector. Wrapping a deserializer in a [`Quota`] limits the
+//! number of bytes and allocations made for fields archived with the
+//! [`Metered`](crate::with::Metered) wrapper.
+//!
+//! Metering is opt-in, so a quota is not a hard cap on the memory used by
+//! deserialization. Allocations for fields which are not metered are neither
+//! counted nor limited.
+
+use core::{alloc::Layout, fmt};
+
+use rancor::{fail, Fallible, Source, Strategy};
+
+use crate::de::{Duplicate, ErasedPtr, Pooling};
+
+/// A deserializer which is notified of allocations before they are made.
+///
+/// Metering is opt-in: only fields archived with the
+/// [`Metered`](crate::with::Metered) wrapper report their allocations, and
+/// only those fields require the deserializer to implement this trait.
+/// Adapters like [`Quota`] check the allocation and then forward it to the
+/// deserializer they wrap.
+pub trait Metering<E = <Self as Fallible>::Error> {
+    /// Records that an allocation with the given layout is about to be made.
+    ///
+    /// Returns an error if the allocation should not be made.
+    fn meter(&mut self, layout: Layout) -> Result<(), E>;
+}
+
+impl<T, E> Metering<E> for Strategy<T, E>
+where
+    T: Metering<E> + ?Sized,
+{
+    #[inline]
+    fn meter(&mut self, layout: Layout) -> Result<(), E> {
+        T::meter(self, layout)
+    }
+}
+
+impl<E> Metering<E> for () {
+    #[inline]
+    fn meter(&mut self, _: Layout) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl<E> Metering<E> for Duplicate {
+    #[inline]
+    fn meter(&mut self, _: Layout) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H, E> Metering<E> for crate::de::Unify<H> {
+    #[inline]
+    fn meter(&mut self, _: Layout) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum QuotaExceeded {
+    Bytes { limit: usize, requested: usize },
+    Allocations { limit: usize },
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes { limit, requested } => write!(
+                f,
+                "deserialization byte quota exceeded: allocating {} bytes \
+                 would exceed the limit of {} bytes",
+                requested, limit,
+            ),
+            Self::Allocations { limit } => write!(
+                f,
+                "deserialization allocation quota exceeded: limit is {} \
+                 allocations",
+                limit,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QuotaExceeded {}
+
+/// A deserializer adapter which limits the number of bytes and allocations
+/// made for metered fields during deserialization.
+///
+/// `Quota` forwards [`Pooling`] and [`Metering`] to the wrapped deserializer
+/// so that it can be combined with a shared pointer strategy or another
+/// quota. Deserialization fails with an error as soon as a metered allocation
+/// would exceed either limit, before the allocation is made.
+///
+/// Only allocations for fields archived with the
+/// [`Metered`](crate::with::Metered) wrapper are counted, and all other
+/// allocations are made without checking the quota. This is not a hard cap: a
+/// quota only bounds the memory used by deserialization if every field which
+/// allocates is metered. The memory used by hash maps and B-tree collections
+/// is estimated from the sizes of their entries, and does not include the
+/// bookkeeping overhead of the collection.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked,
+///     de::quota::Quota,
+///     deserialize,
+///     rancor::Error,
+///     to_bytes,
+///     with::{Map, Metered},
+///     Archive, Deserialize, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+/// struct Example {
+///     #[with(Metered)]
+///     ids: Vec<u32>,
+///     #[with(Map<Metered>)]
+///     names: Vec<String>,
+/// }
+///
+/// let value = Example {
+///     ids: vec![0; 100],
+///     names: vec![String::from("hello"); 100],
+/// };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedExample>(&bytes) };
+///
+/// let mut quota = Quota::new(()).with_max_bytes(512);
+/// let result = deserialize::<Example, _, Error>(archived, &mut quota);
+/// assert!(result.is_err());
+///
+/// let mut quota = Quota::new(()).with_max_allocations(101);
+/// let result = deserialize::<Example, _, Error>(archived, &mut quota);
+/// assert_eq!(result.unwrap(), value);
+/// assert_eq!(quota.allocations(), 101);
+/// ```
+#[derive(Debug)]
+pub struct Quota<D = ()> {
+    inner: D,
+    max_bytes: Option<usize>,
+    max_allocations: Option<usize>,
+    bytes: usize,
+    allocations: usize,
+}
+
+impl<D> Quota<D> {
+    /// Creates a new quota with no limits around the given deserializer.
+    #[inline]
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            max_bytes: None,
+            max_allocations: None,
+            bytes: 0,
+            allocations: 0,
+        }
+    }
+
+    /// Sets the maximum number of bytes that may be allocated for metered
+    /// fields.
+    #[inline]
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sets the maximum number of allocations that may be made for metered
+    /// fields.
+    #[inline]
+    pub fn with_max_allocations(mut self, max_allocations: usize) -> Self {
+        self.max_allocations = Some(max_allocations);
+        self
+    }
+
+    /// Returns the number of bytes allocated so far.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of allocations made so far.
+    #[inline]
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Resets the counters of this quota so that it can be reused.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.bytes = 0;
+        self.allocations = 0;
+    }
+
+    /// Consumes the adapter and returns the wrapped deserializer.
+    #[inline]
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: Metering<E>, E: Source> Metering<E> for Quota<D> {
+    fn meter(&mut self, layout: Layout) -> Result<(), E> {
+        let bytes = self.bytes.saturating_add(layout.size());
+        if let Some(limit) = self.max_bytes {
+            if bytes > limit {
+                fail!(QuotaExceeded::Bytes {
+                    limit,
+                    requested: layout.size(),
+                });
+            }
+        }
+        if let Some(limit) = self.max_allocations {
+            if self.allocations >= limit {
+                fail!(QuotaExceeded::Allocations { limit });
+            }
+        }
+
+        self.inner.meter(layout)?;
+        self.bytes = bytes;
+        self.allocations += 1;
+        Ok(())
+    }
+}
+
+impl<D: Pooling<E>, E> Pooling<E> for Quota<D> {
+    #[inline]
+    fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    unsafe fn add_shared_ptr(
+        &mut self,
+        address: usize,
+        ptr: ErasedPtr,
+        drop: unsafe fn(ErasedPtr),
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `add_shared_ptr` are the same as
+        // the requirements for calling this function.
+        unsafe { self.inner.add_shared_ptr(address, ptr, drop) }
+    }
+}
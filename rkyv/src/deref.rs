@@ -0,0 +1,49 @@
+//! Borrowed views of archived values which abstract over how they are stored.
+
+/// An archived type which can be viewed as a reference to an archived `T`.
+///
+/// Archived values may be stored inline or behind an archived pointer like
+/// `ArchivedBox` or `ArchivedRc`. This trait lets generic code which reads
+/// archived values accept any of these without caring how the value is
+/// wrapped. Every type implements `AsArchivedRef<Self>`, and archived boxes
+/// and shared pointers implement it for the value they point to.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, deref::AsArchivedRef, rancor::Error,
+///     string::ArchivedString, to_bytes, Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Names {
+///     inline: String,
+///     boxed: Box<String>,
+/// }
+///
+/// fn len(name: &impl AsArchivedRef<ArchivedString>) -> usize {
+///     name.as_archived_ref().len()
+/// }
+///
+/// let value = Names {
+///     inline: "inline".to_string(),
+///     boxed: Box::new("boxed".to_string()),
+/// };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedNames>(&bytes) };
+///
+/// assert_eq!(len(&archived.inline), 6);
+/// assert_eq!(len(&archived.boxed), 5);
+/// ```
+pub trait AsArchivedRef<T: ?Sized> {
+    /// Returns a reference to the archived value.
+    fn as_archived_ref(&self) -> &T;
+}
+
+impl<T: ?Sized> AsArchivedRef<T> for T {
+    #[inline]
+    fn as_archived_ref(&self) -> &T {
+        self
+    }
+}
@@ -0,0 +1,47 @@
+//! Shared helpers for code generated by the derive macros.
+//!
+//! Types which derive with `#[archive(compact)]` call these functions instead
+//! of expanding the same code for each of their fields. Nothing in this module
+//! is part of the public API.
+
+use crate::{with::ArchiveWith, Archive, Place};
+
+/// Resolves a field of an archived value.
+///
+/// # Safety
+///
+/// `field` must point to a field of `out`.
+#[inline]
+pub unsafe fn resolve_field<T: Archive, U: ?Sized>(
+    value: &T,
+    resolver: T::Resolver,
+    out: Place<U>,
+    field: *mut T::Archived,
+) {
+    // SAFETY: The caller has guaranteed that `field` points to a field of
+    // `out`.
+    let out_field = unsafe { Place::from_field_unchecked(out, field) };
+    value.resolve(resolver, out_field);
+}
+
+/// Resolves a field of an archived value with a wrapper.
+///
+/// # Safety
+///
+/// `field` must point to a field of `out`.
+#[inline]
+pub unsafe fn resolve_field_with<W, F, U>(
+    value: &F,
+    resolver: W::Resolver,
+    out: Place<U>,
+    field: *mut W::Archived,
+) where
+    W: ArchiveWith<F> + ?Sized,
+    F: ?Sized,
+    U: ?Sized,
+{
+    // SAFETY: The caller has guaranteed that `field` points to a field of
+    // `out`.
+    let out_field = unsafe { Place::from_field_unchecked(out, field) };
+    W::resolve_with(value, resolver, out_field);
+}
@@ -0,0 +1,340 @@
+//! Format descriptors which peers can exchange to check that they agree on the
+//! archived format.
+//!
+//! The bytes of an archive depend on the features rkyv was built with and on
+//! the archived layout of the root type. Two programs can only share archives
+//! if they agree on all of these. A [`FormatDescriptor`] records them in a
+//! small, fixed encoding which does not itself depend on any of those
+//! features, so peers can exchange descriptors during a handshake before
+//! sending any archives.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{descriptor::FormatDescriptor, Archive};
+//!
+//! #[derive(Archive)]
+//! struct Request {
+//!     id: u64,
+//!     body: Vec<u8>,
+//! }
+//!
+//! let local = FormatDescriptor::for_root::<Request>();
+//!
+//! // Send `local.to_bytes()` to the peer and receive theirs.
+//! let remote_bytes = local.to_bytes();
+//! let remote = FormatDescriptor::from_bytes(&remote_bytes).unwrap();
+//!
+//! assert!(local.check_compatible(&remote).is_ok());
+//!
+//! let other = FormatDescriptor::for_root::<u32>();
+//! let mismatch = local.check_compatible(&other).unwrap_err();
+//! assert!(mismatch.root_layout_hash());
+//! assert!(!mismatch.pointer_width());
+//! ```
+
+use core::{fmt, hash::Hasher as _, mem};
+
+use crate::{
+    hash::FxHasher64, primitive::FixedUsize, stability::FORMAT_VERSION, Archive,
+};
+
+/// The byte order of the primitives in an archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// Little-endian.
+    Little,
+    /// Big-endian.
+    Big,
+}
+
+impl Endianness {
+    /// The endianness of archives produced by this build of rkyv.
+    pub const CURRENT: Self = if cfg!(feature = "big_endian") {
+        Self::Big
+    } else {
+        Self::Little
+    };
+
+    const fn id(self) -> u8 {
+        match self {
+            Self::Little => 0,
+            Self::Big => 1,
+        }
+    }
+
+    const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Little),
+            1 => Some(Self::Big),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Little => write!(f, "little-endian"),
+            Self::Big => write!(f, "big-endian"),
+        }
+    }
+}
+
+/// A description of the archived format used by a peer.
+///
+/// Descriptors can be encoded with [`to_bytes`](FormatDescriptor::to_bytes)
+/// and decoded with [`from_bytes`](FormatDescriptor::from_bytes) regardless
+/// of the features either peer was built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FormatDescriptor {
+    /// The version of the archived format of the built-in types. See
+    /// [`FORMAT_VERSION`].
+    pub format_version: u32,
+    /// The width of archived sizes and relative pointer offsets, in bytes.
+    pub pointer_width: u8,
+    /// The byte order of archived primitives.
+    pub endianness: Endianness,
+    /// The features which affect the archived format, as a set of `FLAG_*`
+    /// bits.
+    pub flags: u32,
+    /// A hash of the archived layout of the root type, or zero if the root
+    /// type is not known.
+    pub root_layout_hash: u64,
+}
+
+impl FormatDescriptor {
+    /// The flag which is set when archived primitives are unaligned.
+    pub const FLAG_UNALIGNED: u32 = 1 << 0;
+
+    /// The length of an encoded descriptor in bytes.
+    pub const ENCODED_LEN: usize = 24;
+
+    const MAGIC: [u8; 4] = *b"rkyv";
+
+    /// Returns the descriptor for this build of rkyv, without a root type.
+    pub const fn current() -> Self {
+        let mut flags = 0;
+        if cfg!(feature = "unaligned") {
+            flags |= Self::FLAG_UNALIGNED;
+        }
+
+        Self {
+            format_version: FORMAT_VERSION,
+            pointer_width: mem::size_of::<FixedUsize>() as u8,
+            endianness: Endianness::CURRENT,
+            flags,
+            root_layout_hash: 0,
+        }
+    }
+
+    /// Returns the descriptor for this build of rkyv with `T` as the root
+    /// type.
+    ///
+    /// The layout hash covers the size and alignment of `T::Archived` along
+    /// with its type name. Type names are only guaranteed to match between
+    /// builds with the same compiler, so peers which are built separately
+    /// should provide their own schema hash with
+    /// [`with_root_layout_hash`](FormatDescriptor::with_root_layout_hash).
+    pub fn for_root<T: Archive>() -> Self {
+        Self::current().with_root_layout_hash(layout_hash::<T::Archived>())
+    }
+
+    /// Returns this descriptor with the given root layout hash.
+    pub const fn with_root_layout_hash(mut self, hash: u64) -> Self {
+        self.root_layout_hash = hash;
+        self
+    }
+
+    /// Checks whether archives described by `other` can be read by a peer
+    /// described by `self`.
+    ///
+    /// The root layout hashes are only compared if both are nonzero.
+    pub fn check_compatible(
+        &self,
+        other: &FormatDescriptor,
+    ) -> Result<(), FormatMismatch> {
+        let mismatch = FormatMismatch {
+            local: *self,
+            remote: *other,
+        };
+        if mismatch.format_version()
+            || mismatch.pointer_width()
+            || mismatch.endianness()
+            || mismatch.flags()
+            || mismatch.root_layout_hash()
+        {
+            Err(mismatch)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Encodes the descriptor as bytes.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[0..4].copy_from_slice(&Self::MAGIC);
+        bytes[4..8].copy_from_slice(&self.format_version.to_le_bytes());
+        bytes[8] = self.pointer_width;
+        bytes[9] = self.endianness.id();
+        bytes[12..16].copy_from_slice(&self.flags.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.root_layout_hash.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a descriptor from bytes.
+    ///
+    /// Returns `None` if the bytes are not an encoded descriptor.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; Self::ENCODED_LEN] = bytes.try_into().ok()?;
+        if bytes[0..4] != Self::MAGIC || bytes[10..12] != [0, 0] {
+            return None;
+        }
+
+        let mut u32_bytes = [0; 4];
+        let mut u64_bytes = [0; 8];
+        Some(Self {
+            format_version: {
+                u32_bytes.copy_from_slice(&bytes[4..8]);
+                u32::from_le_bytes(u32_bytes)
+            },
+            pointer_width: bytes[8],
+            endianness: Endianness::from_id(bytes[9])?,
+            flags: {
+                u32_bytes.copy_from_slice(&bytes[12..16]);
+                u32::from_le_bytes(u32_bytes)
+            },
+            root_layout_hash: {
+                u64_bytes.copy_from_slice(&bytes[16..24]);
+                u64::from_le_bytes(u64_bytes)
+            },
+        })
+    }
+}
+
+impl Default for FormatDescriptor {
+    #[inline]
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+fn layout_hash<T>() -> u64 {
+    let mut hasher = FxHasher64::default();
+    hasher.write_u64(mem::size_of::<T>() as u64);
+    hasher.write_u64(mem::align_of::<T>() as u64);
+    hasher.write(core::any::type_name::<T>().as_bytes());
+    // Zero means that the root type is not known.
+    hasher.finish().max(1)
+}
+
+/// The differences between two incompatible [`FormatDescriptor`]s.
+///
+/// Each method returns whether the corresponding part of the descriptors
+/// differs. The `Display` implementation lists every difference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatMismatch {
+    local: FormatDescriptor,
+    remote: FormatDescriptor,
+}
+
+impl FormatMismatch {
+    /// Returns the local descriptor.
+    #[inline]
+    pub fn local(&self) -> &FormatDescriptor {
+        &self.local
+    }
+
+    /// Returns the remote descriptor.
+    #[inline]
+    pub fn remote(&self) -> &FormatDescriptor {
+        &self.remote
+    }
+
+    /// Returns whether the format versions differ.
+    #[inline]
+    pub fn format_version(&self) -> bool {
+        self.local.format_version != self.remote.format_version
+    }
+
+    /// Returns whether the pointer widths differ.
+    #[inline]
+    pub fn pointer_width(&self) -> bool {
+        self.local.pointer_width != self.remote.pointer_width
+    }
+
+    /// Returns whether the endiannesses differ.
+    #[inline]
+    pub fn endianness(&self) -> bool {
+        self.local.endianness != self.remote.endianness
+    }
+
+    /// Returns whether the format flags differ.
+    #[inline]
+    pub fn flags(&self) -> bool {
+        self.local.flags != self.remote.flags
+    }
+
+    /// Returns whether the root layout hashes differ.
+    ///
+    /// This is `false` if either root layout hash is zero.
+    #[inline]
+    pub fn root_layout_hash(&self) -> bool {
+        self.local.root_layout_hash != 0
+            && self.remote.root_layout_hash != 0
+            && self.local.root_layout_hash != self.remote.root_layout_hash
+    }
+}
+
+impl fmt::Display for FormatMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (local, remote) = (&self.local, &self.remote);
+        write!(f, "incompatible archive formats")?;
+
+        let mut separator = ": ";
+        if self.format_version() {
+            write!(
+                f,
+                "{}format version {} (local) vs {} (remote)",
+                separator, local.format_version, remote.format_version,
+            )?;
+            separator = ", ";
+        }
+        if self.pointer_width() {
+            write!(
+                f,
+                "{}{}-byte pointers (local) vs {}-byte pointers (remote)",
+                separator, local.pointer_width, remote.pointer_width,
+            )?;
+            separator = ", ";
+        }
+        if self.endianness() {
+            write!(
+                f,
+                "{}{} (local) vs {} (remote)",
+                separator, local.endianness, remote.endianness,
+            )?;
+            separator = ", ";
+        }
+        if self.flags() {
+            write!(
+                f,
+                "{}flags {:#x} (local) vs {:#x} (remote)",
+                separator, local.flags, remote.flags,
+            )?;
+            separator = ", ";
+        }
+        if self.root_layout_hash() {
+            write!(
+                f,
+                "{}root layout hash {:#018x} (local) vs {:#018x} (remote)",
+                separator, local.root_layout_hash, remote.root_layout_hash,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormatMismatch {}
@@ -0,0 +1,365 @@
+//! Structural digests of values which don't require serializing them.
+//!
+//! A [`StableHash`] feeds a value into a [`Hasher`] as the data its archive
+//! would contain, without producing the archive. Two processes can compare
+//! the digests of their values to check whether they are in sync, even if they
+//! were compiled for different platforms.
+//!
+//! Digests only cover the data in a value, not its layout:
+//!
+//! - Integers are hashed as little-endian bytes, and `usize` and `isize` are
+//!   hashed as 64-bit integers. Floats are hashed as their bits and `char`s as
+//!   `u32`s.
+//! - Structs and tuples hash their fields in order. Padding is not hashed.
+//! - Enums hash the index of their variant as a `usize`, then the fields of the
+//!   variant.
+//! - Strings and slices hash their length, then their elements. Pointers hash
+//!   the value they point to, so the position of out-of-line data never changes
+//!   the digest.
+//! - Hash maps and sets are hashed independently of their iteration order.
+//!
+//! `StableHash` can be derived with `#[derive(StableHash)]`. [`stable_hash`]
+//! returns the digest of a value using [`FxHasher64`].
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{digest::stable_hash, StableHash};
+//!
+//! #[derive(StableHash)]
+//! struct Player {
+//!     name: String,
+//!     scores: Vec<u32>,
+//! }
+//!
+//! let a = Player {
+//!     name: "ferris".to_string(),
+//!     scores: vec![10, 20],
+//! };
+//! let b = Player {
+//!     name: "ferris".to_string(),
+//!     scores: vec![10, 20, 30],
+//! };
+//!
+//! assert_ne!(stable_hash(&a), stable_hash(&b));
+//! ```
+
+use core::{
+    hash::Hasher,
+    marker::{PhantomData, PhantomPinned},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8,
+        NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::BuildHasher,
+    rc::Rc,
+    sync::Arc,
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+
+use crate::hash::FxHasher64;
+
+/// A type which can feed a structural digest of itself into a hasher.
+///
+/// See the [module docs](self) for more information.
+pub trait StableHash {
+    /// Feeds this value into the given hasher.
+    fn stable_hash<H: Hasher>(&self, state: &mut H);
+}
+
+/// Returns the digest of the given value.
+///
+/// The digest is computed with [`FxHasher64`], which is fast and
+/// cross-platform but not collision-resistant. Use [`StableHash::stable_hash`]
+/// directly to compute a digest with a different hasher.
+#[inline]
+pub fn stable_hash<T: StableHash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = FxHasher64::default();
+    value.stable_hash(&mut hasher);
+    hasher.finish()
+}
+
+macro_rules! impl_integer {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl StableHash for $ty {
+                #[inline]
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    state.write(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+macro_rules! impl_as {
+    ($($ty:ty as $as:ty),* $(,)?) => {
+        $(
+            impl StableHash for $ty {
+                #[inline]
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    (*self as $as).stable_hash(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_as!(bool as u8, char as u32, isize as i64, usize as u64,);
+
+macro_rules! impl_get {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl StableHash for $ty {
+                #[inline]
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    self.get().stable_hash(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_get!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+);
+
+impl StableHash for f32 {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().stable_hash(state);
+    }
+}
+
+impl StableHash for f64 {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().stable_hash(state);
+    }
+}
+
+impl StableHash for () {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl StableHash for PhantomPinned {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl<T: ?Sized> StableHash for PhantomData<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl StableHash for Duration {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_secs().stable_hash(state);
+        self.subsec_nanos().stable_hash(state);
+    }
+}
+
+impl<T: StableHash + ?Sized> StableHash for &T {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+impl StableHash for str {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().stable_hash(state);
+        state.write(self.as_bytes());
+    }
+}
+
+impl<T: StableHash, const N: usize> StableHash for [T; N] {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        for value in self.iter() {
+            value.stable_hash(state);
+        }
+    }
+}
+
+impl<T: StableHash> StableHash for [T] {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().stable_hash(state);
+        for value in self.iter() {
+            value.stable_hash(state);
+        }
+    }
+}
+
+impl<T: StableHash> StableHash for Option<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            None => 0usize.stable_hash(state),
+            Some(value) => {
+                1usize.stable_hash(state);
+                value.stable_hash(state);
+            }
+        }
+    }
+}
+
+impl<T: StableHash, E: StableHash> StableHash for Result<T, E> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Ok(value) => {
+                0usize.stable_hash(state);
+                value.stable_hash(state);
+            }
+            Err(error) => {
+                1usize.stable_hash(state);
+                error.stable_hash(state);
+            }
+        }
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($ty:ident $index:tt),*) => {
+        impl<$($ty: StableHash),*> StableHash for ($($ty,)*) {
+            #[inline]
+            fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                $(self.$index.stable_hash(state);)*
+            }
+        }
+    };
+}
+
+impl_tuple!(T0 0);
+impl_tuple!(T0 0, T1 1);
+impl_tuple!(T0 0, T1 1, T2 2);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+
+#[cfg(feature = "alloc")]
+impl StableHash for String {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().stable_hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: StableHash> StableHash for Vec<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().stable_hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: StableHash + ?Sized> StableHash for Box<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: StableHash + ?Sized> StableHash for Rc<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: StableHash + ?Sized> StableHash for Arc<T> {
+    #[inline]
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: StableHash, V: StableHash> StableHash for BTreeMap<K, V> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().stable_hash(state);
+        for (key, value) in self.iter() {
+            key.stable_hash(state);
+            value.stable_hash(state);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: StableHash> StableHash for BTreeSet<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().stable_hash(state);
+        for value in self.iter() {
+            value.stable_hash(state);
+        }
+    }
+}
+
+/// Hashes the length of an unordered collection followed by the sum of the
+/// digests of its elements, which doesn't depend on their order.
+#[cfg(feature = "std")]
+fn hash_unordered<H, T, I>(len: usize, iter: I, state: &mut H)
+where
+    H: Hasher,
+    T: StableHash,
+    I: Iterator<Item = T>,
+{
+    len.stable_hash(state);
+    iter.map(|value| stable_hash(&value))
+        .fold(0u64, u64::wrapping_add)
+        .stable_hash(state);
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> StableHash for HashMap<K, V, S>
+where
+    K: StableHash,
+    V: StableHash,
+    S: BuildHasher,
+{
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.len(), self.iter(), state);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: StableHash, S: BuildHasher> StableHash for HashSet<T, S> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.len(), self.iter(), state);
+    }
+}
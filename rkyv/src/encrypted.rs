@@ -0,0 +1,467 @@
+//! Authenticated encryption of archives at rest.
+//!
+//! Relative pointers in an archive are only meaningful in plaintext, so the
+//! positions seen by the serializer must be positions in the plaintext and not
+//! in the encrypted output. [`EncryptedWriter`] buffers the plaintext archive
+//! and only encrypts and writes it to the wrapped writer when
+//! [`finish`](Writer::finish) is called. [`open_encrypted`] reverses this by
+//! decrypting into an [`AlignedVec`] and validating the result.
+//!
+//! Encrypted archives are laid out as the nonce, followed by the ciphertext,
+//! followed by the authentication tag.
+//!
+//! A nonce must never be used twice with the same key. When encrypting many
+//! archives with one key, take their nonces from a [`NonceCounter`].
+//!
+//! Any [`AeadInPlace`] cipher may be used. The `aes-gcm` and
+//! `chacha20poly1305` features re-export the RustCrypto implementations of
+//! those ciphers from this module.
+
+use core::{fmt, marker::PhantomData};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use aead::{
+    generic_array::typenum::Unsigned as _, AeadCore, AeadInPlace, Key, KeyInit,
+    Nonce, Tag,
+};
+#[cfg(feature = "aes-gcm")]
+pub use aes_gcm::{Aes128Gcm, Aes256Gcm};
+#[cfg(feature = "chacha20poly1305")]
+pub use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+#[cfg(feature = "fallible-alloc")]
+use rancor::ResultExt as _;
+#[cfg(feature = "bytecheck")]
+use rancor::Strategy;
+use rancor::{fail, Source};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    ser::{Positional, Writer},
+    util::AlignedVec,
+    Portable,
+};
+
+#[derive(Debug)]
+struct EncryptionFailed;
+
+impl fmt::Display for EncryptionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to encrypt archive")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncryptionFailed {}
+
+#[derive(Debug)]
+struct DecryptionFailed;
+
+impl fmt::Display for DecryptionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to decrypt or authenticate archive")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecryptionFailed {}
+
+#[derive(Debug)]
+struct EncryptedArchiveTooShort {
+    len: usize,
+    min: usize,
+}
+
+impl fmt::Display for EncryptedArchiveTooShort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "encrypted archive is too short: {} bytes, expected at least {}",
+            self.len, self.min,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncryptedArchiveTooShort {}
+
+#[derive(Debug)]
+struct NoncesExhausted;
+
+impl fmt::Display for NoncesExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nonce counter has no unused nonces left")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NoncesExhausted {}
+
+/// A source of unique nonces for encrypting many archives with the same key.
+///
+/// The nonce is treated as a big-endian integer which is incremented each
+/// time a nonce is taken, so the counter never returns the same nonce twice.
+/// Once every nonce has been returned, taking another one fails.
+///
+/// The counter must not be recreated from a starting nonce which has already
+/// been used with the same key. To resume counting after a restart, persist
+/// the nonce returned by [`peek`](NonceCounter::peek) and pass it to
+/// [`new`](NonceCounter::new) later.
+pub struct NonceCounter<C: AeadCore> {
+    next: Nonce<C>,
+    exhausted: bool,
+}
+
+impl<C: AeadCore> NonceCounter<C> {
+    /// Returns a counter which starts at the given nonce.
+    #[inline]
+    pub fn new(start: Nonce<C>) -> Self {
+        Self {
+            next: start,
+            exhausted: false,
+        }
+    }
+
+    /// Returns the next nonce the counter will return, or `None` if every
+    /// nonce has been returned.
+    #[inline]
+    pub fn peek(&self) -> Option<&Nonce<C>> {
+        (!self.exhausted).then_some(&self.next)
+    }
+
+    /// Returns a nonce which this counter has not returned before.
+    pub fn take<E: Source>(&mut self) -> Result<Nonce<C>, E> {
+        if self.exhausted {
+            fail!(NoncesExhausted);
+        }
+
+        let nonce = self.next.clone();
+        self.exhausted = true;
+        for byte in self.next.iter_mut().rev() {
+            let (incremented, overflowed) = byte.overflowing_add(1);
+            *byte = incremented;
+            if !overflowed {
+                self.exhausted = false;
+                break;
+            }
+        }
+        Ok(nonce)
+    }
+}
+
+impl<C: AeadCore> fmt::Debug for NonceCounter<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NonceCounter")
+            .field("next", &self.peek())
+            .finish()
+    }
+}
+
+/// A writer which encrypts the archive written to it with an AEAD cipher.
+///
+/// Each encrypted archive must use a nonce that is never reused with the same
+/// key. [`with_counter`](EncryptedWriter::with_counter) takes the nonce from a
+/// [`NonceCounter`], which guarantees this. Additional authenticated data can
+/// be bound to the archive with [`with_aad`](EncryptedWriter::with_aad); the
+/// same data must be passed to [`decrypt`] or [`open_encrypted`] to decrypt
+/// it.
+///
+/// `EncryptedWriter` only implements [`Writer`]. To serialize types which
+/// require scratch space or sharing, use it as the writer of a
+/// [`Composite`](crate::ser::Composite) serializer.
+///
+/// # Example
+#[cfg_attr(
+    all(feature = "chacha20poly1305", feature = "bytecheck"),
+    doc = "```"
+)]
+#[cfg_attr(
+    not(all(feature = "chacha20poly1305", feature = "bytecheck")),
+    doc = "```ignore"
+)]
+/// use rkyv::{
+///     encrypted::{
+///         open_encrypted, ChaCha20Poly1305, EncryptedWriter, NonceCounter,
+///     },
+///     rancor::Error,
+///     util::serialize_into,
+///     Archived,
+/// };
+///
+/// let key = [7; 32].into();
+/// let mut nonces = NonceCounter::new([0; 12].into());
+///
+/// let writer = EncryptedWriter::<_, ChaCha20Poly1305>::with_counter(
+///     Vec::new(),
+///     &key,
+///     &mut nonces,
+/// )
+/// .unwrap()
+/// .with_aad(b"header");
+/// let encrypted = serialize_into::<_, Error>(&42u32, writer)
+///     .unwrap()
+///     .into_inner();
+///
+/// let opened = open_encrypted::<Archived<u32>, ChaCha20Poly1305, Error>(
+///     &encrypted, &key, b"header",
+/// )
+/// .unwrap();
+/// assert_eq!(*opened.get(), 42);
+///
+/// // The wrong associated data fails to authenticate
+/// assert!(open_encrypted::<Archived<u32>, ChaCha20Poly1305, Error>(
+///     &encrypted, &key, b"other",
+/// )
+/// .is_err());
+/// ```
+pub struct EncryptedWriter<W, C: AeadCore> {
+    inner: W,
+    cipher: C,
+    nonce: Nonce<C>,
+    aad: Vec<u8>,
+    plaintext: AlignedVec,
+    finished: bool,
+}
+
+impl<W, C: AeadCore + KeyInit> EncryptedWriter<W, C> {
+    /// Creates a new encrypted writer which encrypts with the given key.
+    #[inline]
+    pub fn from_key(inner: W, key: &Key<C>, nonce: Nonce<C>) -> Self {
+        Self::new(inner, C::new(key), nonce)
+    }
+
+    /// Creates a new encrypted writer which encrypts with the given key and
+    /// the next nonce from the given counter.
+    #[inline]
+    pub fn with_counter<E: Source>(
+        inner: W,
+        key: &Key<C>,
+        nonces: &mut NonceCounter<C>,
+    ) -> Result<Self, E> {
+        Ok(Self::from_key(inner, key, nonces.take()?))
+    }
+}
+
+impl<W, C: AeadCore> EncryptedWriter<W, C> {
+    /// Creates a new encrypted writer which encrypts with the given cipher.
+    #[inline]
+    pub fn new(inner: W, cipher: C, nonce: Nonce<C>) -> Self {
+        Self {
+            inner,
+            cipher,
+            nonce,
+            aad: Vec::new(),
+            plaintext: AlignedVec::new(),
+            finished: false,
+        }
+    }
+
+    /// Sets the additional authenticated data for the archive.
+    #[inline]
+    pub fn with_aad(mut self, aad: &[u8]) -> Self {
+        self.aad.clear();
+        self.aad.extend_from_slice(aad);
+        self
+    }
+
+    /// Returns the nonce used to encrypt the archive.
+    #[inline]
+    pub fn nonce(&self) -> &Nonce<C> {
+        &self.nonce
+    }
+
+    /// Returns whether the archive has been encrypted and written.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Consumes the encrypted writer and returns the wrapped writer.
+    ///
+    /// Any plaintext which has not been encrypted by calling
+    /// [`finish`](Writer::finish) is discarded.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, C: AeadCore> Positional for EncryptedWriter<W, C> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.plaintext.len()
+    }
+}
+
+impl<W, C, E> Writer<E> for EncryptedWriter<W, C>
+where
+    W: Writer<E>,
+    C: AeadInPlace,
+    E: Source,
+{
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        #[cfg(feature = "fallible-alloc")]
+        self.plaintext.try_reserve(bytes.len()).into_error()?;
+        self.plaintext.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        // The plaintext is buffered until the writer is finished, so reserve
+        // space there instead of in the inner writer.
+        let _ = self.plaintext.try_reserve_exact(additional);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), E> {
+        if self.finished {
+            return Ok(());
+        }
+
+        let tag = match self.cipher.encrypt_in_place_detached(
+            &self.nonce,
+            &self.aad,
+            self.plaintext.as_mut_slice(),
+        ) {
+            Ok(tag) => tag,
+            Err(_) => fail!(EncryptionFailed),
+        };
+        self.inner.write(&self.nonce)?;
+        self.inner.write(self.plaintext.as_slice())?;
+        self.inner.write(&tag)?;
+        self.plaintext.clear();
+        self.finished = true;
+
+        self.inner.finish()
+    }
+}
+
+/// Decrypts an archive produced by an [`EncryptedWriter`] without validating
+/// it.
+///
+/// The returned buffer is authenticated, but its contents are not checked to
+/// be a valid archive.
+pub fn decrypt<C, E>(
+    bytes: &[u8],
+    key: &Key<C>,
+    aad: &[u8],
+) -> Result<AlignedVec, E>
+where
+    C: AeadInPlace + KeyInit,
+    E: Source,
+{
+    let nonce_size = C::NonceSize::USIZE;
+    let tag_size = C::TagSize::USIZE;
+    if bytes.len() < nonce_size + tag_size {
+        fail!(EncryptedArchiveTooShort {
+            len: bytes.len(),
+            min: nonce_size + tag_size,
+        });
+    }
+
+    let (nonce, rest) = bytes.split_at(nonce_size);
+    let (ciphertext, tag) = rest.split_at(rest.len() - tag_size);
+
+    let mut plaintext = AlignedVec::with_capacity(ciphertext.len());
+    plaintext.extend_from_slice(ciphertext);
+    let result = C::new(key).decrypt_in_place_detached(
+        Nonce::<C>::from_slice(nonce),
+        aad,
+        plaintext.as_mut_slice(),
+        Tag::<C>::from_slice(tag),
+    );
+    if result.is_err() {
+        fail!(DecryptionFailed);
+    }
+
+    Ok(plaintext)
+}
+
+/// Decrypts an archive produced by an [`EncryptedWriter`] without validating
+/// it.
+///
+/// This is available without the `bytecheck` feature. The archive is
+/// authenticated, so it can be trusted as long as the key is only used to
+/// encrypt valid archives of `T`.
+///
+/// # Safety
+///
+/// The decrypted bytes must contain a valid archive of `T` at the root
+/// position.
+pub unsafe fn open_encrypted_unchecked<T, C, E>(
+    bytes: &[u8],
+    key: &Key<C>,
+    aad: &[u8],
+) -> Result<Decrypted<T>, E>
+where
+    T: Portable,
+    C: AeadInPlace + KeyInit,
+    E: Source,
+{
+    Ok(Decrypted {
+        bytes: decrypt::<C, E>(bytes, key, aad)?,
+        _phantom: PhantomData,
+    })
+}
+
+/// Decrypts and validates an archive produced by an [`EncryptedWriter`].
+///
+/// The archive is decrypted into an [`AlignedVec`], which is owned by the
+/// returned [`Decrypted`]. Without the `bytecheck` feature, use
+/// [`open_encrypted_unchecked`] instead.
+#[cfg(feature = "bytecheck")]
+pub fn open_encrypted<T, C, E>(
+    bytes: &[u8],
+    key: &Key<C>,
+    aad: &[u8],
+) -> Result<Decrypted<T>, E>
+where
+    T: Portable + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    C: AeadInPlace + KeyInit,
+    E: Source,
+{
+    let bytes = decrypt::<C, E>(bytes, key, aad)?;
+    crate::access::<T, E>(&bytes)?;
+    Ok(Decrypted {
+        bytes,
+        _phantom: PhantomData,
+    })
+}
+
+/// A decrypted archive which has been validated to contain a `T`.
+pub struct Decrypted<T> {
+    bytes: AlignedVec,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Portable> Decrypted<T> {
+    /// Returns the root of the decrypted archive.
+    #[inline]
+    pub fn get(&self) -> &T {
+        // SAFETY: The decrypted bytes were validated to contain a `T` at the
+        // root position when this `Decrypted` was created, or the caller of
+        // `open_encrypted_unchecked` guaranteed that they do.
+        unsafe { crate::access_unchecked::<T>(&self.bytes) }
+    }
+
+    /// Returns the decrypted bytes of the archive.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes the `Decrypted` and returns the decrypted bytes.
+    #[inline]
+    pub fn into_bytes(self) -> AlignedVec {
+        self.bytes
+    }
+}
+
+impl<T: Portable + fmt::Debug> fmt::Debug for Decrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
@@ -0,0 +1,403 @@
+//! Request and response envelopes for RPC over archives.
+//!
+//! An envelope frames a payload archive with a fixed-size header holding the
+//! kind of message, a method ID, and a correlation ID which matches responses
+//! to their requests. The header has the same encoding regardless of the
+//! features rkyv was built with, so a peer can always read the header and
+//! route the message before it touches the payload.
+//!
+//! Decoding an envelope only checks the header. The payload is validated when
+//! a handler accesses it, so messages for unknown methods or stale requests
+//! can be dropped without paying for validation.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     envelope::{encode_request, Envelope, EnvelopeKind},
+//!     rancor::Error,
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct Add {
+//!     a: u32,
+//!     b: u32,
+//! }
+//!
+//! const ADD: u32 = 1;
+//!
+//! let request = Add { a: 1, b: 2 };
+//! let frame = encode_request::<_, Error>(ADD, 7, &request).unwrap();
+//!
+//! let envelope = Envelope::decode::<Error>(&frame).unwrap();
+//! assert_eq!(envelope.kind(), EnvelopeKind::Request);
+//! assert_eq!(envelope.method(), ADD);
+//! assert_eq!(envelope.correlation(), 7);
+//!
+//! let request = envelope.access::<ArchivedAdd, Error>().unwrap();
+//! assert_eq!(request.a + request.b, 3);
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "alloc")]
+use rancor::Strategy;
+use rancor::{fail, Source};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+#[cfg(feature = "bytecheck")]
+use crate::Portable;
+#[cfg(feature = "alloc")]
+use crate::{ser::AllocSerializer, util::AlignedVec, Serialize};
+
+/// The kind of message carried by an envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EnvelopeKind {
+    /// A request for a method to be called.
+    Request,
+    /// A successful response to a request.
+    Response,
+    /// An error response to a request.
+    Error,
+}
+
+impl EnvelopeKind {
+    const fn id(self) -> u8 {
+        match self {
+            Self::Request => 0,
+            Self::Response => 1,
+            Self::Error => 2,
+        }
+    }
+
+    const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Request),
+            1 => Some(Self::Response),
+            2 => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// The header of an envelope.
+///
+/// Headers are encoded as [`HEADER_LEN`](EnvelopeHeader::HEADER_LEN) bytes.
+/// The header length is a multiple of the archive alignment, so a payload
+/// which follows a header in an aligned buffer is also aligned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EnvelopeHeader {
+    /// The kind of message.
+    pub kind: EnvelopeKind,
+    /// The ID of the method being called or responded to.
+    pub method: u32,
+    /// The ID which matches a response to its request.
+    pub correlation: u64,
+    /// The length of the payload archive in bytes.
+    pub payload_len: u32,
+}
+
+#[derive(Debug)]
+enum EnvelopeError {
+    Truncated { expected: usize, actual: usize },
+    BadVersion(u8),
+    BadKind(u8),
+    NonzeroReserved,
+    PayloadTooLarge(usize),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { expected, actual } => write!(
+                f,
+                "envelope is truncated: expected {} bytes but found {}",
+                expected, actual,
+            ),
+            Self::BadVersion(version) => {
+                write!(f, "unsupported envelope version {}", version)
+            }
+            Self::BadKind(kind) => write!(f, "invalid envelope kind {}", kind),
+            Self::NonzeroReserved => {
+                write!(f, "reserved envelope header bytes are not zero")
+            }
+            Self::PayloadTooLarge(len) => write!(
+                f,
+                "envelope payload of {} bytes is too large to frame",
+                len,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EnvelopeError {}
+
+impl EnvelopeHeader {
+    /// The length of an encoded header in bytes.
+    pub const HEADER_LEN: usize = 32;
+
+    const VERSION: u8 = 1;
+
+    /// Returns the total length of the frame described by this header.
+    #[inline]
+    pub fn frame_len(&self) -> usize {
+        Self::HEADER_LEN + self.payload_len as usize
+    }
+
+    /// Encodes the header as bytes.
+    pub fn to_bytes(&self) -> [u8; Self::HEADER_LEN] {
+        let mut bytes = [0; Self::HEADER_LEN];
+        bytes[0] = Self::VERSION;
+        bytes[1] = self.kind.id();
+        bytes[4..8].copy_from_slice(&self.method.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.correlation.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a header from the start of the given bytes.
+    ///
+    /// Any bytes after the header are ignored.
+    pub fn decode<E: Source>(bytes: &[u8]) -> Result<Self, E> {
+        if bytes.len() < Self::HEADER_LEN {
+            fail!(EnvelopeError::Truncated {
+                expected: Self::HEADER_LEN,
+                actual: bytes.len(),
+            });
+        }
+
+        if bytes[0] != Self::VERSION {
+            fail!(EnvelopeError::BadVersion(bytes[0]));
+        }
+        let kind = match EnvelopeKind::from_id(bytes[1]) {
+            Some(kind) => kind,
+            None => fail!(EnvelopeError::BadKind(bytes[1])),
+        };
+        let reserved = bytes[2..4].iter().chain(&bytes[20..Self::HEADER_LEN]);
+        if reserved.copied().any(|b| b != 0) {
+            fail!(EnvelopeError::NonzeroReserved);
+        }
+
+        let mut u32_bytes = [0; 4];
+        let mut u64_bytes = [0; 8];
+        u32_bytes.copy_from_slice(&bytes[4..8]);
+        let method = u32::from_le_bytes(u32_bytes);
+        u64_bytes.copy_from_slice(&bytes[8..16]);
+        let correlation = u64::from_le_bytes(u64_bytes);
+        u32_bytes.copy_from_slice(&bytes[16..20]);
+        let payload_len = u32::from_le_bytes(u32_bytes);
+
+        Ok(Self {
+            kind,
+            method,
+            correlation,
+            payload_len,
+        })
+    }
+}
+
+/// A decoded envelope which borrows its payload.
+///
+/// The payload has not been validated. It is validated when it is accessed
+/// with `access`.
+#[derive(Clone, Copy, Debug)]
+pub struct Envelope<'a> {
+    header: EnvelopeHeader,
+    payload: &'a [u8],
+}
+
+impl<'a> Envelope<'a> {
+    /// Decodes the envelope at the start of the given bytes.
+    ///
+    /// Any bytes after the end of the frame are ignored; use
+    /// [`frame_len`](Envelope::frame_len) to find where the next frame starts.
+    pub fn decode<E: Source>(bytes: &'a [u8]) -> Result<Self, E> {
+        let header = EnvelopeHeader::decode::<E>(bytes)?;
+        let frame_len = header.frame_len();
+        if bytes.len() < frame_len {
+            fail!(EnvelopeError::Truncated {
+                expected: frame_len,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Self {
+            header,
+            payload: &bytes[EnvelopeHeader::HEADER_LEN..frame_len],
+        })
+    }
+
+    /// Returns the header of the envelope.
+    #[inline]
+    pub fn header(&self) -> &EnvelopeHeader {
+        &self.header
+    }
+
+    /// Returns the kind of message in the envelope.
+    #[inline]
+    pub fn kind(&self) -> EnvelopeKind {
+        self.header.kind
+    }
+
+    /// Returns the method ID of the envelope.
+    #[inline]
+    pub fn method(&self) -> u32 {
+        self.header.method
+    }
+
+    /// Returns the correlation ID of the envelope.
+    #[inline]
+    pub fn correlation(&self) -> u64 {
+        self.header.correlation
+    }
+
+    /// Returns the total length of the envelope's frame in bytes.
+    #[inline]
+    pub fn frame_len(&self) -> usize {
+        self.header.frame_len()
+    }
+
+    /// Returns the unvalidated bytes of the payload archive.
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Validates the payload archive and returns a reference to its root.
+    #[cfg(feature = "bytecheck")]
+    pub fn access<T, E>(&self) -> Result<&'a T, E>
+    where
+        T: Portable + CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        crate::access::<T, E>(self.payload)
+    }
+
+    /// Returns a reference to the root of the payload archive without
+    /// validating it.
+    ///
+    /// # Safety
+    ///
+    /// The payload must contain a valid archive of `T`.
+    #[inline]
+    pub unsafe fn access_unchecked<T: Portable>(&self) -> &'a T {
+        // SAFETY: The caller has guaranteed that the payload contains a valid
+        // archive of `T`.
+        unsafe { crate::access_unchecked::<T>(self.payload) }
+    }
+}
+
+/// Serializes a payload into a new envelope frame with the given kind.
+#[cfg(feature = "alloc")]
+pub fn encode<T, E>(
+    kind: EnvelopeKind,
+    method: u32,
+    correlation: u64,
+    payload: &T,
+) -> Result<AlignedVec, E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    let mut frame = AlignedVec::new();
+    frame.extend_from_slice(&[0; EnvelopeHeader::HEADER_LEN]);
+    let serializer =
+        AllocSerializer::new(frame, Default::default(), Default::default());
+    let mut frame =
+        crate::util::serialize_into(payload, serializer)?.into_writer();
+
+    let payload_len = frame.len() - EnvelopeHeader::HEADER_LEN;
+    let payload_len = match u32::try_from(payload_len) {
+        Ok(len) => len,
+        Err(_) => fail!(EnvelopeError::PayloadTooLarge(payload_len)),
+    };
+    let header = EnvelopeHeader {
+        kind,
+        method,
+        correlation,
+        payload_len,
+    };
+    frame[..EnvelopeHeader::HEADER_LEN].copy_from_slice(&header.to_bytes());
+
+    Ok(frame)
+}
+
+/// Serializes a request payload into a new envelope frame.
+#[cfg(feature = "alloc")]
+pub fn encode_request<T, E>(
+    method: u32,
+    correlation: u64,
+    payload: &T,
+) -> Result<AlignedVec, E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    encode(EnvelopeKind::Request, method, correlation, payload)
+}
+
+/// Serializes a response payload into a new envelope frame which answers the
+/// given request.
+#[cfg(feature = "alloc")]
+pub fn encode_response<T, E>(
+    request: &EnvelopeHeader,
+    payload: &T,
+) -> Result<AlignedVec, E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    encode(
+        EnvelopeKind::Response,
+        request.method,
+        request.correlation,
+        payload,
+    )
+}
+
+/// Serializes an error payload into a new envelope frame which answers the
+/// given request.
+#[cfg(feature = "alloc")]
+pub fn encode_error<T, E>(
+    request: &EnvelopeHeader,
+    payload: &T,
+) -> Result<AlignedVec, E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    encode(
+        EnvelopeKind::Error,
+        request.method,
+        request.correlation,
+        payload,
+    )
+}
+
+/// Reads one envelope frame from a reader into the given buffer.
+///
+/// The buffer is cleared before reading, and holds the whole frame afterward.
+/// Decode it with [`Envelope::decode`].
+#[cfg(feature = "std")]
+pub fn read_frame<R: std::io::Read + ?Sized>(
+    reader: &mut R,
+    buffer: &mut AlignedVec,
+) -> std::io::Result<()> {
+    use std::io;
+
+    let mut header = [0; EnvelopeHeader::HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    let header = EnvelopeHeader::decode::<rancor::Error>(&header)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    buffer.clear();
+    buffer.extend_from_slice(&header.to_bytes());
+    buffer.resize(header.frame_len(), 0);
+    reader.read_exact(&mut buffer[EnvelopeHeader::HEADER_LEN..])
+}
@@ -0,0 +1,345 @@
+//! Archived versions of error types.
+//!
+//! Trait objects can't be archived directly, so errors are archived in a
+//! structured fallback form: the message of the error and the messages of
+//! each error in its [source](std::error::Error::source) chain. This is enough
+//! to report, log, and compare persisted errors. Deserializing an archived
+//! error produces a [`RestoredError`] with the same messages and source chain.
+//!
+//! # Example
+//!
+//! ```
+//! use std::{error::Error as StdError, io};
+//!
+//! use rkyv::{
+//!     access_unchecked, rancor::Error, result::ArchivedResult, to_bytes,
+//!     util::deserialize, Archive, Archived, Deserialize, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize, Deserialize)]
+//! struct JobResult {
+//!     id: u32,
+//!     outcome: Result<u64, Box<dyn StdError + Send + Sync>>,
+//! }
+//!
+//! let result = JobResult {
+//!     id: 7,
+//!     outcome: Err("disk unavailable".into()),
+//! };
+//! let bytes = to_bytes::<Error>(&result).unwrap();
+//! let archived = unsafe { access_unchecked::<ArchivedJobResult>(&bytes) };
+//! match &archived.outcome {
+//!     ArchivedResult::Err(error) => {
+//!         assert_eq!(error.message(), "disk unavailable");
+//!     }
+//!     ArchivedResult::Ok(_) => panic!("expected an error"),
+//! }
+//!
+//! let error = io::Error::new(io::ErrorKind::TimedOut, "no response");
+//! let bytes = to_bytes::<Error>(&error).unwrap();
+//! let archived = unsafe { access_unchecked::<Archived<io::Error>>(&bytes) };
+//! let restored =
+//!     deserialize::<io::Error, _, Error>(archived, &mut ()).unwrap();
+//! assert_eq!(restored.kind(), io::ErrorKind::TimedOut);
+//! assert_eq!(restored.to_string(), "no response");
+//! ```
+
+use core::fmt;
+use std::{error::Error, io};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    ser::{Allocator, Writer},
+    string::{ArchivedString, StringResolver},
+    vec::{ArchivedVec, VecResolver},
+    Place, Portable,
+};
+
+/// An archived error.
+///
+/// This holds the message of an error and the messages of its source chain.
+/// It is the archived form of `Box<dyn Error + Send + Sync>`.
+#[derive(Portable)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(C)]
+pub struct ArchivedError {
+    message: ArchivedString,
+    sources: ArchivedVec<ArchivedString>,
+}
+
+impl ArchivedError {
+    /// Returns the message of the error.
+    #[inline]
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    /// Returns the messages of the error's source chain, starting with its
+    /// direct source.
+    #[inline]
+    pub fn sources(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.sources.iter().map(ArchivedString::as_str)
+    }
+
+    /// Resolves an archived error from a given error.
+    #[inline]
+    pub fn resolve_from_error(
+        error: &dyn Error,
+        resolver: ErrorResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedError { message, sources } = out);
+        ArchivedString::resolve_from_str(
+            &error.to_string(),
+            resolver.message,
+            message,
+        );
+        ArchivedVec::resolve_from_len(
+            source_chain(error).count(),
+            resolver.sources,
+            sources,
+        );
+    }
+
+    /// Serializes an archived error from a given error.
+    pub fn serialize_from_error<S>(
+        error: &dyn Error,
+        serializer: &mut S,
+    ) -> Result<ErrorResolver, S::Error>
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        let sources = source_chain(error)
+            .map(|source| source.to_string())
+            .collect::<Vec<_>>();
+        Ok(ErrorResolver {
+            message: ArchivedString::serialize_from_str(
+                &error.to_string(),
+                serializer,
+            )?,
+            sources: ArchivedVec::<ArchivedString>::serialize_from_slice(
+                &sources, serializer,
+            )?,
+        })
+    }
+
+    /// Returns a [`RestoredError`] with the same messages and source chain.
+    pub fn to_restored(&self) -> RestoredError {
+        let mut source = None;
+        for message in self.sources.iter().rev() {
+            source = Some(Box::new(RestoredError {
+                message: message.as_str().to_string(),
+                source,
+            }));
+        }
+        RestoredError {
+            message: self.message().to_string(),
+            source,
+        }
+    }
+}
+
+impl fmt::Debug for ArchivedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedError")
+            .field("message", &self.message())
+            .field("sources", &self.sources.as_slice())
+            .finish()
+    }
+}
+
+impl fmt::Display for ArchivedError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl Error for ArchivedError {}
+
+/// The resolver for an archived error.
+pub struct ErrorResolver {
+    message: StringResolver,
+    sources: VecResolver,
+}
+
+// Returns an iterator over the source chain of an error, not including the
+// error itself.
+fn source_chain<'a>(
+    error: &'a dyn Error,
+) -> impl Iterator<Item = &'a (dyn Error + 'static)> {
+    let mut next = error.source();
+    core::iter::from_fn(move || {
+        let current = next?;
+        next = current.source();
+        Some(current)
+    })
+}
+
+/// An error restored from an [`ArchivedError`].
+///
+/// It has the same message and source chain as the error that was archived,
+/// but not its type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestoredError {
+    message: String,
+    source: Option<Box<RestoredError>>,
+}
+
+impl RestoredError {
+    /// Returns the message of the error.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RestoredError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for RestoredError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+/// An archived [`ErrorKind`](io::ErrorKind).
+///
+/// Kinds which are not listed here are archived as `Other`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Portable)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(u8)]
+pub enum ArchivedErrorKind {
+    /// An entity was not found.
+    NotFound,
+    /// The operation lacked the necessary privileges to complete.
+    PermissionDenied,
+    /// The connection was refused by the remote server.
+    ConnectionRefused,
+    /// The connection was reset by the remote server.
+    ConnectionReset,
+    /// The connection was aborted by the remote server.
+    ConnectionAborted,
+    /// The network operation failed because it was not connected yet.
+    NotConnected,
+    /// A socket address could not be bound because it is in use.
+    AddrInUse,
+    /// A nonexistent interface was requested or the address was not local.
+    AddrNotAvailable,
+    /// The operation failed because a pipe was closed.
+    BrokenPipe,
+    /// An entity already exists.
+    AlreadyExists,
+    /// The operation needs to block to complete.
+    WouldBlock,
+    /// A parameter was incorrect.
+    InvalidInput,
+    /// Data not valid for the operation were encountered.
+    InvalidData,
+    /// The I/O operation's timeout expired.
+    TimedOut,
+    /// A write returned `Ok(0)`.
+    WriteZero,
+    /// The operation was interrupted.
+    Interrupted,
+    /// The operation is unsupported on this platform.
+    Unsupported,
+    /// An end of file was reached prematurely.
+    UnexpectedEof,
+    /// An operation could not be completed because it failed to allocate
+    /// enough memory.
+    OutOfMemory,
+    /// Any other error.
+    Other,
+}
+
+impl ArchivedErrorKind {
+    /// Returns the archived form of the given error kind.
+    pub fn from_kind(kind: io::ErrorKind) -> Self {
+        use io::ErrorKind as K;
+
+        match kind {
+            K::NotFound => Self::NotFound,
+            K::PermissionDenied => Self::PermissionDenied,
+            K::ConnectionRefused => Self::ConnectionRefused,
+            K::ConnectionReset => Self::ConnectionReset,
+            K::ConnectionAborted => Self::ConnectionAborted,
+            K::NotConnected => Self::NotConnected,
+            K::AddrInUse => Self::AddrInUse,
+            K::AddrNotAvailable => Self::AddrNotAvailable,
+            K::BrokenPipe => Self::BrokenPipe,
+            K::AlreadyExists => Self::AlreadyExists,
+            K::WouldBlock => Self::WouldBlock,
+            K::InvalidInput => Self::InvalidInput,
+            K::InvalidData => Self::InvalidData,
+            K::TimedOut => Self::TimedOut,
+            K::WriteZero => Self::WriteZero,
+            K::Interrupted => Self::Interrupted,
+            K::Unsupported => Self::Unsupported,
+            K::UnexpectedEof => Self::UnexpectedEof,
+            K::OutOfMemory => Self::OutOfMemory,
+            _ => Self::Other,
+        }
+    }
+
+    /// Returns the error kind this archived error kind represents.
+    pub fn to_kind(&self) -> io::ErrorKind {
+        use io::ErrorKind as K;
+
+        match self {
+            Self::NotFound => K::NotFound,
+            Self::PermissionDenied => K::PermissionDenied,
+            Self::ConnectionRefused => K::ConnectionRefused,
+            Self::ConnectionReset => K::ConnectionReset,
+            Self::ConnectionAborted => K::ConnectionAborted,
+            Self::NotConnected => K::NotConnected,
+            Self::AddrInUse => K::AddrInUse,
+            Self::AddrNotAvailable => K::AddrNotAvailable,
+            Self::BrokenPipe => K::BrokenPipe,
+            Self::AlreadyExists => K::AlreadyExists,
+            Self::WouldBlock => K::WouldBlock,
+            Self::InvalidInput => K::InvalidInput,
+            Self::InvalidData => K::InvalidData,
+            Self::TimedOut => K::TimedOut,
+            Self::WriteZero => K::WriteZero,
+            Self::Interrupted => K::Interrupted,
+            Self::Unsupported => K::Unsupported,
+            Self::UnexpectedEof => K::UnexpectedEof,
+            Self::OutOfMemory => K::OutOfMemory,
+            Self::Other => K::Other,
+        }
+    }
+}
+
+/// An archived [`io::Error`].
+///
+/// This holds the kind of the error along with its messages.
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(C)]
+pub struct ArchivedIoError {
+    /// The kind of the error.
+    pub kind: ArchivedErrorKind,
+    /// The message and source chain of the error.
+    pub error: ArchivedError,
+}
+
+impl fmt::Display for ArchivedIoError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for ArchivedIoError {}
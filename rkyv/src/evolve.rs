@@ -0,0 +1,320 @@
+//! Archived structs which can gain fields over time.
+//!
+//! The archived layout of a derived struct depends on all of its fields, so
+//! adding a field makes every existing archive unreadable. Structs which
+//! derive `Archive` with `#[archive(evolve)]` are archived as a
+//! length-prefixed table of relative pointers to their fields instead. Each
+//! field is archived on its own, and the table records which fields the
+//! writer knew about:
+//!
+//! - Newer readers can load archives written by older code. Fields missing from
+//!   the table are deserialized with `Default::default()`.
+//! - Older readers can load archives written by newer code. Fields past the end
+//!   of the struct they know about are skipped.
+//!
+//! Fields are identified by their position in the struct, so fields may only
+//! be added to the end of an evolving struct. Removing, reordering, or
+//! changing the type of a field changes the meaning of existing archives.
+//!
+//! The archived struct has an accessor for each field, which returns `None`
+//! if the field is missing from the archive. When validating, each known field
+//! which is present in the table is checked. Unknown trailing fields are not
+//! checked, and can't be accessed.
+//!
+//! Evolving structs must have named fields, and may not be generic.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{access, deserialize, rancor::Error, to_bytes};
+//!
+//! mod v1 {
+//!     #[derive(rkyv::Archive, rkyv::Serialize)]
+//!     #[archive(evolve, check_bytes)]
+//!     pub struct Config {
+//!         pub name: String,
+//!     }
+//! }
+//!
+//! mod v2 {
+//!     #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+//!     #[archive(evolve, check_bytes)]
+//!     pub struct Config {
+//!         pub name: String,
+//!         pub retries: u32,
+//!     }
+//! }
+//!
+//! let old = v1::Config {
+//!     name: "server".to_string(),
+//! };
+//! let bytes = to_bytes::<Error>(&old).unwrap();
+//!
+//! let archived = access::<v2::ArchivedConfig, Error>(&bytes).unwrap();
+//! assert_eq!(archived.name().unwrap(), "server");
+//! assert!(archived.retries().is_none());
+//!
+//! let new = deserialize::<v2::Config, _, Error>(archived, &mut ()).unwrap();
+//! assert_eq!(new.name, "server");
+//! assert_eq!(new.retries, 0);
+//! ```
+
+use core::{fmt, mem::MaybeUninit, slice};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    primitive::ArchivedUsize,
+    ser::{Allocator, Writer, WriterExt as _},
+    with::{ArchiveWith, SerializeWith},
+    Archive, Place, Portable, RawRelPtr, Serialize, SerializeUnsized as _,
+};
+
+/// The table of fields of an archived evolving struct.
+///
+/// Each entry points to the archived value of the field with the same index.
+/// See the [module docs](self) for more information.
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(C)]
+pub struct ArchivedFieldTable {
+    ptr: RawRelPtr,
+    len: ArchivedUsize,
+}
+
+impl ArchivedFieldTable {
+    /// Returns the number of fields in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.to_native() as usize
+    }
+
+    /// Returns whether the table has no fields.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the entries of the table.
+    #[inline]
+    pub fn entries(&self) -> &[RawRelPtr] {
+        // SAFETY: The table points to `len` entries.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr().cast(), self.len()) }
+    }
+
+    /// Returns the field at the given index, or `None` if the table doesn't
+    /// have that many fields.
+    ///
+    /// # Safety
+    ///
+    /// The field at the given index must have been archived as a `T`.
+    #[inline]
+    pub unsafe fn get<T: Portable>(&self, index: usize) -> Option<&T> {
+        let ptr = self.entries().get(index)?;
+        // SAFETY: The caller has guaranteed that the field at `index` is a
+        // `T`.
+        Some(unsafe { &*ptr.as_ptr().cast::<T>() })
+    }
+
+    /// Resolves a field table with the given number of fields.
+    #[inline]
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: FieldTableResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedFieldTable { ptr, len: out_len } = out);
+        RawRelPtr::emplace(resolver.pos, ptr);
+        usize::resolve(&len, (), out_len);
+    }
+
+    /// Serializes a field table which points to the fields archived at the
+    /// given positions.
+    #[inline]
+    pub fn serialize_from_positions<S>(
+        positions: &[usize],
+        serializer: &mut S,
+    ) -> Result<FieldTableResolver, S::Error>
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        // SAFETY: `FieldPos` is a transparent wrapper around a `usize`.
+        let positions =
+            unsafe { &*(positions as *const [usize] as *const [FieldPos]) };
+        Ok(FieldTableResolver {
+            pos: positions.serialize_unsized(serializer)?,
+        })
+    }
+}
+
+impl fmt::Debug for ArchivedFieldTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedFieldTable")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// The resolver for an [`ArchivedFieldTable`].
+pub struct FieldTableResolver {
+    pos: usize,
+}
+
+// The position of an archived field, which is archived as a pointer to it.
+#[repr(transparent)]
+struct FieldPos(usize);
+
+impl Archive for FieldPos {
+    type Archived = RawRelPtr;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, _: Self::Resolver, out: Place<Self::Archived>) {
+        RawRelPtr::emplace(self.0, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for FieldPos {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+/// Serializes and resolves a field of an evolving struct, and returns the
+/// position of the archived field.
+#[inline]
+pub fn serialize_field<T, S>(
+    value: &T,
+    serializer: &mut S,
+) -> Result<usize, S::Error>
+where
+    T: Serialize<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    value.serialize_and_resolve(serializer)
+}
+
+/// Serializes and resolves a field of an evolving struct with a wrapper, and
+/// returns the position of the archived field.
+pub fn serialize_field_with<W, F, S>(
+    value: &F,
+    serializer: &mut S,
+) -> Result<usize, S::Error>
+where
+    W: SerializeWith<F, S> + ?Sized,
+    F: ?Sized,
+    S: Fallible + Writer + ?Sized,
+{
+    let resolver = W::serialize_with(value, serializer)?;
+    let pos = serializer.align_for::<W::Archived>()?;
+    let mut resolved = MaybeUninit::<W::Archived>::zeroed();
+    // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed `MaybeUninit`,
+    // and so is properly aligned, dereferenceable, and all of its bytes are
+    // initialized.
+    let out = unsafe { Place::new_unchecked(pos, resolved.as_mut_ptr()) };
+    <W as ArchiveWith<F>>::resolve_with(value, resolver, out);
+    serializer.write(out.as_slice())?;
+    Ok(pos)
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        CheckBytes,
+    };
+
+    use super::ArchivedFieldTable;
+    use crate::{
+        validation::{ArchiveContext, ArchiveContextExt},
+        RawRelPtr,
+    };
+
+    /// Checks the field a table entry points to.
+    pub type CheckField<C> =
+        unsafe fn(&RawRelPtr, &mut C) -> Result<(), <C as Fallible>::Error>;
+
+    /// Checks the field that the given table entry points to as a `T`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be an entry of a field table which is being checked.
+    pub unsafe fn check_field<T, C>(
+        ptr: &RawRelPtr,
+        context: &mut C,
+    ) -> Result<(), C::Error>
+    where
+        T: CheckBytes<C>,
+        C: Fallible + ArchiveContext + ?Sized,
+        C::Error: Source,
+    {
+        // SAFETY: The caller has guaranteed that `ptr` is inside the buffer
+        // being checked.
+        let ptr = unsafe {
+            context.bounds_check_subtree_base_offset::<T>(
+                ptr.base(),
+                ptr.offset(),
+                (),
+            )?
+        };
+        let range = unsafe { context.push_prefix_subtree(ptr)? };
+        if context.should_check_subtree() {
+            unsafe {
+                T::check_bytes(ptr, context)?;
+            }
+        }
+        unsafe { context.pop_subtree_range(range) }
+    }
+
+    impl ArchivedFieldTable {
+        /// Checks a field table and the known fields it points to.
+        ///
+        /// Each function in `fields` checks the field with the same index.
+        /// Fields past the end of `fields` are unknown, and are skipped.
+        ///
+        /// # Safety
+        ///
+        /// `value` must be aligned and point to enough bytes for an
+        /// `ArchivedFieldTable`.
+        pub unsafe fn check_bytes_with<C>(
+            value: *const Self,
+            context: &mut C,
+            fields: &[CheckField<C>],
+        ) -> Result<(), C::Error>
+        where
+            C: Fallible + ArchiveContext + ?Sized,
+            C::Error: Source,
+        {
+            // SAFETY: Every bit pattern is a valid field table, though its
+            // pointer may not be.
+            let table = unsafe { &*value };
+            let check_entries = |context: &mut C| unsafe {
+                context.bounds_check_subtree_base_offset::<[RawRelPtr]>(
+                    table.ptr.base(),
+                    table.ptr.offset(),
+                    table.len(),
+                )
+            };
+
+            // Fields are archived before the table that points to them, so
+            // they have to be checked first.
+            let entries = unsafe { &*check_entries(context)? };
+            for (entry, check) in entries.iter().zip(fields) {
+                unsafe {
+                    check(entry, context)?;
+                }
+            }
+
+            // The fields may have claimed the bytes of the table, so it has to
+            // be bounds checked again.
+            let entries = check_entries(context)?;
+            let range = unsafe { context.push_prefix_subtree(entries)? };
+            unsafe { context.pop_subtree_range(range) }
+        }
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+pub use verify::{check_field, CheckField};
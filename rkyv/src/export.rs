@@ -0,0 +1,230 @@
+//! Exporting archived values through serde.
+//!
+//! [`Export`] serializes an archived value with any serde `Serializer`, so an
+//! archive can be converted to JSON (or any other serde format) for debugging
+//! or export without deserializing it into its original types first.
+//!
+//! Archived primitives are defined by `rend`, so they can't implement
+//! `serde::Serialize` directly. [`Serde`] wraps a reference to any exportable
+//! value and implements `serde::Serialize` for it. rkyv's own archived types
+//! (e.g. `ArchivedString`, `ArchivedVec`, and `ArchivedHashMap`) also
+//! implement `serde::Serialize` directly.
+//!
+//! `Export` can be derived for archived types with
+//! `#[archive_attr(derive(Export))]`, which also implements `serde::Serialize`
+//! for the archived type. Archived structs and enums are exported with the
+//! same shape that `#[derive(serde::Serialize)]` would give their unarchived
+//! counterparts.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{access_unchecked, rancor::Error, to_bytes, Archive, Export};
+//!
+//! #[derive(Archive, rkyv::Serialize)]
+//! #[archive_attr(derive(Export))]
+//! struct Player {
+//!     name: String,
+//!     scores: Vec<u32>,
+//!     team: Option<u8>,
+//! }
+//!
+//! let value = Player {
+//!     name: "ferris".to_string(),
+//!     scores: vec![10, 20],
+//!     team: None,
+//! };
+//! let bytes = to_bytes::<Error>(&value).unwrap();
+//! let archived = unsafe { access_unchecked::<ArchivedPlayer>(&bytes) };
+//!
+//! assert_eq!(
+//!     serde_json::to_string(archived).unwrap(),
+//!     r#"{"name":"ferris","scores":[10,20],"team":null}"#,
+//! );
+//! ```
+
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    num::{NonZeroI8, NonZeroU8},
+};
+
+use serde::{ser::SerializeTuple, Serialize, Serializer};
+
+use crate::primitive::{
+    ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+    ArchivedI32, ArchivedI64, ArchivedNonZeroI128, ArchivedNonZeroI16,
+    ArchivedNonZeroI32, ArchivedNonZeroI64, ArchivedNonZeroU128,
+    ArchivedNonZeroU16, ArchivedNonZeroU32, ArchivedNonZeroU64, ArchivedU128,
+    ArchivedU16, ArchivedU32, ArchivedU64,
+};
+
+/// An archived type which can be serialized with serde.
+///
+/// See the [module docs](self) for more information.
+pub trait Export {
+    /// Serializes this value with the given serializer.
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// A wrapper which implements `serde::Serialize` for an exportable value.
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug)]
+pub struct Serde<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized> Clone for Serde<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Serde<'_, T> {}
+
+impl<T: Export + ?Sized> Serialize for Serde<'_, T> {
+    #[inline]
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.export(serializer)
+    }
+}
+
+macro_rules! impl_native {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl Export for $ty {
+                #[inline]
+                fn export<S: Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.$method(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_native!(
+    bool => serialize_bool,
+    i8 => serialize_i8,
+    u8 => serialize_u8,
+);
+
+macro_rules! impl_archived {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl Export for $ty {
+                #[inline]
+                fn export<S: Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.$method(self.to_native())
+                }
+            }
+        )*
+    };
+}
+
+impl_archived!(
+    ArchivedI16 => serialize_i16,
+    ArchivedI32 => serialize_i32,
+    ArchivedI64 => serialize_i64,
+    ArchivedI128 => serialize_i128,
+    ArchivedU16 => serialize_u16,
+    ArchivedU32 => serialize_u32,
+    ArchivedU64 => serialize_u64,
+    ArchivedU128 => serialize_u128,
+    ArchivedF32 => serialize_f32,
+    ArchivedF64 => serialize_f64,
+    ArchivedChar => serialize_char,
+);
+
+macro_rules! impl_nonzero {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl Export for $ty {
+                #[inline]
+                fn export<S: Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    serializer.$method(self.to_native().get())
+                }
+            }
+        )*
+    };
+}
+
+impl_nonzero!(
+    ArchivedNonZeroI16 => serialize_i16,
+    ArchivedNonZeroI32 => serialize_i32,
+    ArchivedNonZeroI64 => serialize_i64,
+    ArchivedNonZeroI128 => serialize_i128,
+    ArchivedNonZeroU16 => serialize_u16,
+    ArchivedNonZeroU32 => serialize_u32,
+    ArchivedNonZeroU64 => serialize_u64,
+    ArchivedNonZeroU128 => serialize_u128,
+);
+
+impl Export for NonZeroI8 {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i8(self.get())
+    }
+}
+
+impl Export for NonZeroU8 {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.get())
+    }
+}
+
+impl Export for () {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl Export for PhantomPinned {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_struct("PhantomPinned")
+    }
+}
+
+impl<T: ?Sized> Export for PhantomData<T> {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_struct("PhantomData")
+    }
+}
+
+impl Export for str {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<T: Export, const N: usize> Export for [T; N] {
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for value in self.iter() {
+            tuple.serialize_element(&Serde(value))?;
+        }
+        tuple.end()
+    }
+}
+
+impl<T: Export> Export for [T] {
+    #[inline]
+    fn export<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Serde))
+    }
+}
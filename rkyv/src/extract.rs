@@ -0,0 +1,439 @@
+//! Copying archived values into new archives without deserializing them.
+//!
+//! [`Extract`] re-serializes an archived value and everything reachable from
+//! it directly from its archived form. This makes it possible to copy a single
+//! record out of a large archive into a new, compact archive without a round
+//! trip through the native type.
+//!
+//! Because extracting only copies the data reachable from a value, it can also
+//! be used to compact archives which have been patched or appended to in place.
+//! [`compact`] copies the live data reachable from the root of an archive into
+//! a new buffer and reports the number of bytes reclaimed.
+//!
+//! `Extract` can be derived for archived types with
+//! `#[archive_attr(derive(Extract))]`.
+
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    num::{NonZeroI8, NonZeroU8},
+};
+
+use rancor::Fallible;
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+use rancor::Source;
+#[cfg(feature = "alloc")]
+use rancor::Strategy;
+
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    primitive::{
+        ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+        ArchivedI32, ArchivedI64, ArchivedNonZeroI128, ArchivedNonZeroI16,
+        ArchivedNonZeroI32, ArchivedNonZeroI64, ArchivedNonZeroU128,
+        ArchivedNonZeroU16, ArchivedNonZeroU32, ArchivedNonZeroU64,
+        ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64,
+    },
+    ser::{Allocator, Writer, WriterExt as _},
+    util::SerVec,
+    ArchivePointee, Place, Portable,
+};
+#[cfg(feature = "alloc")]
+use crate::{ser::AllocSerializer, util::AlignedVec};
+
+/// An archived type which can be serialized into a new archive directly.
+///
+/// This is the counterpart of [`Serialize`](crate::Serialize) for archived
+/// types: extracting a value produces an archived value which is identical to
+/// the original, but located in a different archive.
+pub trait Extract<S: Fallible + ?Sized>: Portable {
+    /// The resolver for the extracted value.
+    type Resolver;
+
+    /// Writes the dependencies of the value to the serializer and returns a
+    /// resolver which can be used to resolve a copy of it.
+    fn extract(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error>;
+
+    /// Writes a copy of the value to the given place using the resolver
+    /// returned by [`extract`](Extract::extract).
+    fn resolve_extracted(&self, resolver: Self::Resolver, out: Place<Self>);
+}
+
+/// An archived type which can be written to a new archive directly, and may
+/// be unsized.
+pub trait ExtractUnsized<S: Fallible + ?Sized>:
+    ArchivePointee + Portable
+{
+    /// Writes a copy of the value and its dependencies to the serializer and
+    /// returns the position of the copy.
+    fn extract_unsized(&self, serializer: &mut S) -> Result<usize, S::Error>;
+}
+
+impl<T, S> ExtractUnsized<S> for T
+where
+    T: Extract<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    #[inline]
+    fn extract_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        let resolver = self.extract(serializer)?;
+        serializer.align_for::<T>()?;
+        resolve_extracted_aligned(self, resolver, serializer)
+    }
+}
+
+/// Resolves a copy of the given value with its resolver and writes it to the
+/// serializer.
+///
+/// Returns the position of the written copy. The serializer must already be
+/// aligned for a `T`.
+pub fn resolve_extracted_aligned<T, S>(
+    value: &T,
+    resolver: T::Resolver,
+    serializer: &mut S,
+) -> Result<usize, S::Error>
+where
+    T: Extract<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    let pos = serializer.pos();
+    debug_assert_eq!(pos & (core::mem::align_of::<T>() - 1), 0);
+
+    let mut resolved = MaybeUninit::<T>::zeroed();
+    // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed `MaybeUninit`,
+    // and so is properly aligned, dereferenceable, and all of its bytes are
+    // initialized.
+    let out = unsafe { Place::new_unchecked(pos, resolved.as_mut_ptr()) };
+    value.resolve_extracted(resolver, out);
+    serializer.write(out.as_slice())?;
+    Ok(pos)
+}
+
+/// Resolves a copy of a field of an archived value.
+///
+/// This is used to implement [`Extract::resolve_extracted`] for archived
+/// types by resolving each of their fields in turn.
+///
+/// # Safety
+///
+/// `field` must be a reference to a field of `parent`, and `out` must be the
+/// place that `parent` is being resolved to.
+#[inline]
+pub unsafe fn resolve_field_unchecked<P, F, S>(
+    parent: &P,
+    field: &F,
+    resolver: F::Resolver,
+    out: Place<P>,
+) where
+    P: ?Sized,
+    F: Extract<S>,
+    S: Fallible + ?Sized,
+{
+    let offset =
+        field as *const F as usize - parent as *const P as *const () as usize;
+    // SAFETY: The caller has guaranteed that `field` is a field of `parent`,
+    // so the same offset from `out` is in bounds of `out` and properly aligned
+    // for an `F`.
+    let field_out = unsafe {
+        let ptr = out.ptr().cast::<u8>().add(offset).cast::<F>();
+        Place::from_field_unchecked(out, ptr)
+    };
+    field.resolve_extracted(resolver, field_out);
+}
+
+macro_rules! impl_extract_copy {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<S: Fallible + ?Sized> Extract<S> for $ty {
+                type Resolver = ();
+
+                #[inline]
+                fn extract(&self, _: &mut S) -> Result<(), S::Error> {
+                    Ok(())
+                }
+
+                #[inline]
+                fn resolve_extracted(&self, _: (), out: Place<Self>) {
+                    out.write(*self);
+                }
+            }
+        )*
+    };
+}
+
+impl_extract_copy!(
+    (),
+    bool,
+    i8,
+    u8,
+    NonZeroI8,
+    NonZeroU8,
+    ArchivedI16,
+    ArchivedI32,
+    ArchivedI64,
+    ArchivedI128,
+    ArchivedU16,
+    ArchivedU32,
+    ArchivedU64,
+    ArchivedU128,
+    ArchivedF32,
+    ArchivedF64,
+    ArchivedChar,
+    ArchivedNonZeroI16,
+    ArchivedNonZeroI32,
+    ArchivedNonZeroI64,
+    ArchivedNonZeroI128,
+    ArchivedNonZeroU16,
+    ArchivedNonZeroU32,
+    ArchivedNonZeroU64,
+    ArchivedNonZeroU128,
+);
+
+impl<T: ?Sized, S: Fallible + ?Sized> Extract<S> for PhantomData<T> {
+    type Resolver = ();
+
+    #[inline]
+    fn extract(&self, _: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, _: (), _: Place<Self>) {}
+}
+
+impl<T, S, const N: usize> Extract<S> for [T; N]
+where
+    T: Extract<S>,
+    S: Fallible + ?Sized,
+{
+    type Resolver = [T::Resolver; N];
+
+    #[inline]
+    fn extract(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let mut result = MaybeUninit::<Self::Resolver>::uninit();
+        let result_ptr = result.as_mut_ptr().cast::<T::Resolver>();
+        for (i, value) in self.iter().enumerate() {
+            unsafe {
+                result_ptr.add(i).write(value.extract(serializer)?);
+            }
+        }
+        unsafe { Ok(result.assume_init()) }
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, resolver: Self::Resolver, out: Place<Self>) {
+        for (i, (value, resolver)) in self.iter().zip(resolver).enumerate() {
+            let out_i = unsafe { out.index(i) };
+            value.resolve_extracted(resolver, out_i);
+        }
+    }
+}
+
+impl<T, S> ExtractUnsized<S> for [T]
+where
+    T: Extract<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn extract_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        SerVec::with_capacity(
+            serializer,
+            self.len(),
+            |resolvers, serializer| {
+                for value in self.iter() {
+                    resolvers.push(value.extract(serializer)?);
+                }
+
+                let result =
+                    serializer.align_for_layout(Layout::for_value(self))?;
+
+                for (value, resolver) in self.iter().zip(resolvers.drain(..)) {
+                    resolve_extracted_aligned(value, resolver, serializer)?;
+                }
+
+                Ok(result)
+            },
+        )?
+    }
+}
+
+impl<S: Fallible + Writer + ?Sized> ExtractUnsized<S> for str {
+    #[inline]
+    fn extract_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        let result =
+            serializer.align_for_layout(Layout::for_value(self.as_bytes()))?;
+        serializer.write(self.as_bytes())?;
+        Ok(result)
+    }
+}
+
+/// Extracts an archived value into the given serializer.
+///
+/// Returns the position of the extracted root.
+#[inline]
+pub fn extract_into<T, S>(
+    value: &T,
+    serializer: &mut S,
+) -> Result<usize, S::Error>
+where
+    T: Extract<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    let pos = value.extract_unsized(serializer)?;
+    serializer.finish()?;
+    Ok(pos)
+}
+
+/// Copies an archived value and everything reachable from it into a new
+/// archive.
+///
+/// The new archive only contains the extracted value, and can be accessed with
+/// the same archived type.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, extract, rancor::Error, to_bytes, Archive, Archived,
+///     Extract, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(Extract))]
+/// struct Record {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let records = (0..100)
+///     .map(|id| Record {
+///         id,
+///         name: format!("record number {id}"),
+///     })
+///     .collect::<Vec<_>>();
+/// let bytes = to_bytes::<Error>(&records).unwrap();
+/// let archived = unsafe { access_unchecked::<Archived<Vec<Record>>>(&bytes) };
+///
+/// let extracted = extract::<_, Error>(&archived[42]).unwrap();
+/// assert!(extracted.len() < bytes.len() / 10);
+///
+/// let record = unsafe { access_unchecked::<ArchivedRecord>(&extracted) };
+/// assert_eq!(record.id, 42);
+/// assert_eq!(record.name, "record number 42");
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn extract<T, E>(value: &T) -> Result<AlignedVec, E>
+where
+    T: Extract<Strategy<AllocSerializer, E>>,
+{
+    let mut serializer = AllocSerializer::default();
+    extract_into(value, Strategy::wrap(&mut serializer))?;
+    Ok(serializer.into_writer())
+}
+
+/// A compacted archive.
+///
+/// This is returned by [`compact`] and [`compact_unchecked`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Compacted {
+    bytes: AlignedVec,
+    reclaimed: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Compacted {
+    /// Returns the bytes of the compacted archive.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes the `Compacted` and returns the bytes of the compacted
+    /// archive.
+    #[inline]
+    pub fn into_bytes(self) -> AlignedVec {
+        self.bytes
+    }
+
+    /// Returns the number of bytes which were reclaimed by compacting the
+    /// archive.
+    #[inline]
+    pub fn reclaimed(&self) -> usize {
+        self.reclaimed
+    }
+}
+
+/// Compacts an archive by copying only the data reachable from its root into
+/// a new buffer.
+///
+/// Archives which are patched or appended to in place accumulate regions which
+/// are no longer reachable from the root. Compacting the archive rewrites the
+/// live data and its relative pointers into a new buffer without any of the
+/// dead regions. The root of the archive must be stored at the end of the
+/// bytes, and the root of the compacted archive is too.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access, extract::compact, rancor::Error, ser::AllocSerializer,
+///     to_bytes, util::serialize_into, Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&vec![0u32; 1024]).unwrap();
+///
+/// // Append a new root to the archive, leaving the old value behind
+/// let serializer = AllocSerializer {
+///     writer: bytes,
+///     ..Default::default()
+/// };
+/// let bytes = serialize_into::<_, Error>(&vec![1u32, 2, 3], serializer)
+///     .unwrap()
+///     .into_writer();
+///
+/// let compacted = compact::<Archived<Vec<u32>>, Error>(&bytes).unwrap();
+/// assert!(compacted.reclaimed() >= 1024 * 4);
+///
+/// let value =
+///     access::<Archived<Vec<u32>>, Error>(compacted.as_bytes()).unwrap();
+/// assert_eq!(value.as_slice(), &[1, 2, 3]);
+/// ```
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+pub fn compact<T, E>(bytes: &[u8]) -> Result<Compacted, E>
+where
+    T: Extract<Strategy<AllocSerializer, E>>
+        + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    crate::access::<T, E>(bytes)?;
+    // SAFETY: We just validated that the bytes contain a `T` at the root
+    // position.
+    unsafe { compact_unchecked::<T, E>(bytes) }
+}
+
+/// Compacts an archive by copying only the data reachable from its root into
+/// a new buffer, without validating it.
+///
+/// See [`compact`] for more information.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice.
+#[cfg(feature = "alloc")]
+pub unsafe fn compact_unchecked<T, E>(bytes: &[u8]) -> Result<Compacted, E>
+where
+    T: Extract<Strategy<AllocSerializer, E>>,
+{
+    // SAFETY: The caller has guaranteed that the bytes contain a `T` at the
+    // root position.
+    let root = unsafe { crate::access_unchecked::<T>(bytes) };
+    let compacted = extract::<T, E>(root)?;
+    Ok(Compacted {
+        reclaimed: bytes.len().saturating_sub(compacted.len()),
+        bytes: compacted,
+    })
+}
@@ -197,8 +197,10 @@ mod verify {
                 unsafe { context.bounds_check_subtree_rel_ptr(&self.ptr)? };
 
             let range = unsafe { context.push_prefix_subtree(ptr)? };
-            unsafe {
-                CStr::check_bytes(ptr, context)?;
+            if context.should_check_subtree() {
+                unsafe {
+                    CStr::check_bytes(ptr, context)?;
+                }
             }
             unsafe {
                 context.pop_subtree_range(range)?;
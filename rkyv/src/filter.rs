@@ -0,0 +1,300 @@
+//! Probabilistic filters which can be archived and queried in place.
+//!
+//! Filters answer whether a key may be in a set without storing the keys
+//! themselves. They never report that a key in the set is missing, but may
+//! report that a missing key is present. They are useful in front of archived
+//! indexes, where most lookups for missing keys can be rejected without
+//! searching the index.
+//!
+//! - [`BloomFilter`] has a configurable false positive rate and supports
+//!   inserting keys after it is built.
+//! - [`XorFilter`] is built from a fixed set of keys, uses less space than a
+//!   bloom filter with the same false positive rate, and always probes three
+//!   bytes. Its false positive rate is about 0.4%.
+//!
+//! Keys are hashed with [`SipHasher13`], so a filter built on one platform
+//! gives the same results on every other platform. Keys which are compared
+//! with each other must hash the same way, as with the keys of a `HashMap`.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked,
+//!     filter::{BloomFilter, XorFilter},
+//!     rancor::Error,
+//!     to_bytes, Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Filters {
+//!     bloom: BloomFilter,
+//!     xor: XorFilter,
+//! }
+//!
+//! let keys = ["apples", "pears", "plums"];
+//! let filters = Filters {
+//!     bloom: BloomFilter::from_keys(keys, 0.01),
+//!     xor: XorFilter::from_keys(keys),
+//! };
+//!
+//! let bytes = to_bytes::<Error>(&filters).unwrap();
+//! let archived = unsafe { access_unchecked::<ArchivedFilters>(&bytes) };
+//! assert!(archived.bloom.contains("pears"));
+//! assert!(archived.xor.contains("pears"));
+//! ```
+
+use core::hash::Hash;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::{vec, vec::Vec};
+
+use crate::{
+    hash::{hash_value, SipHasher13},
+    Archive, Deserialize, Serialize,
+};
+
+#[inline]
+fn key_hash<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    hash_value::<Q, SipHasher13>(key)
+}
+
+/// A bloom filter over a set of keys.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Returns an empty bloom filter sized for `len` keys with `bits_per_key`
+    /// bits for each.
+    ///
+    /// Ten bits per key gives a false positive rate of about 1%.
+    pub fn new(len: usize, bits_per_key: usize) -> Self {
+        let bits = len.max(1).saturating_mul(bits_per_key.max(1));
+        // The optimal number of hashes is `bits_per_key * ln(2)`.
+        let hashes = (bits_per_key as u32 * 69 + 50) / 100;
+        Self {
+            bits: vec![0; (bits + 63) / 64],
+            hashes: hashes.clamp(1, 16),
+        }
+    }
+
+    /// Returns an empty bloom filter sized for `len` keys with approximately
+    /// the given false positive rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not between zero and one.
+    pub fn with_false_positive_rate(len: usize, rate: f64) -> Self {
+        assert!(
+            rate > 0.0 && rate < 1.0,
+            "false positive rate must be between zero and one",
+        );
+
+        // The optimal number of bits per key is `log2(1 / rate) / ln(2)`.
+        let mut log2 = 0;
+        let mut inverse = 1.0 / rate;
+        while inverse > 1.0 {
+            inverse /= 2.0;
+            log2 += 1;
+        }
+        Self::new(len, (log2 * 144 + 99) / 100)
+    }
+
+    /// Builds a bloom filter containing the given keys with approximately the
+    /// given false positive rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not between zero and one.
+    pub fn from_keys<I>(keys: I, rate: f64) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Hash,
+    {
+        let hashes = keys.into_iter().map(|key| key_hash(&key));
+        let hashes = hashes.collect::<Vec<_>>();
+        let mut filter = Self::with_false_positive_rate(hashes.len(), rate);
+        for hash in hashes {
+            filter.insert_hash(hash);
+        }
+        filter
+    }
+
+    /// Adds a key to the bloom filter.
+    pub fn insert<Q: Hash + ?Sized>(&mut self, key: &Q) {
+        self.insert_hash(key_hash(key));
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let len = self.bits.len() as u64 * 64;
+        for bit in bloom_probes(hash, self.hashes, len) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns whether the bloom filter may contain the given key.
+    pub fn contains<Q: Hash + ?Sized>(&self, key: &Q) -> bool {
+        let len = self.bits.len() as u64 * 64;
+        bloom_probes(key_hash(key), self.hashes, len)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+impl ArchivedBloomFilter {
+    /// Returns whether the bloom filter may contain the given key.
+    pub fn contains<Q: Hash + ?Sized>(&self, key: &Q) -> bool {
+        let len = self.bits.len() as u64 * 64;
+        let hashes = self.hashes.to_native();
+        bloom_probes(key_hash(key), hashes, len).all(|bit| {
+            self.bits[(bit / 64) as usize].to_native() & (1 << (bit % 64)) != 0
+        })
+    }
+}
+
+fn bloom_probes(hash: u64, hashes: u32, len: u64) -> impl Iterator<Item = u64> {
+    // Derive each probe from a single hash with double hashing. An empty
+    // filter has no probes, and so contains every key.
+    let step = hash.rotate_left(32) | 1;
+    let hashes = if len == 0 { 0 } else { hashes };
+    (0..hashes as u64)
+        .map(move |i| hash.wrapping_add(i.wrapping_mul(step)) % len)
+}
+
+/// An xor filter with 8-bit fingerprints over a set of keys.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct XorFilter {
+    seed: u64,
+    fingerprints: Vec<u8>,
+}
+
+impl XorFilter {
+    /// Builds an xor filter containing the given keys.
+    pub fn from_keys<I>(keys: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Hash,
+    {
+        let mut hashes = keys
+            .into_iter()
+            .map(|key| key_hash(&key))
+            .collect::<Vec<_>>();
+        // Duplicate keys can never be peeled, so they must be removed first.
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let segment_len = (hashes.len() * 123 / 100 + 32) / 3;
+        let mut seed = 0;
+        loop {
+            if let Some(fingerprints) = xor_build(&hashes, seed, segment_len) {
+                return Self { seed, fingerprints };
+            }
+            seed = mix(seed.wrapping_add(0x9e37_79b9_7f4a_7c15));
+        }
+    }
+
+    /// Returns whether the xor filter may contain the given key.
+    pub fn contains<Q: Hash + ?Sized>(&self, key: &Q) -> bool {
+        xor_contains(&self.fingerprints, self.seed, key_hash(key))
+    }
+}
+
+impl ArchivedXorFilter {
+    /// Returns whether the xor filter may contain the given key.
+    pub fn contains<Q: Hash + ?Sized>(&self, key: &Q) -> bool {
+        let seed = self.seed.to_native();
+        xor_contains(&self.fingerprints, seed, key_hash(key))
+    }
+}
+
+// The finalizer of MurmurHash3.
+#[inline]
+fn mix(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^ (h >> 33)
+}
+
+#[inline]
+fn fingerprint(h: u64) -> u8 {
+    (h ^ (h >> 32)) as u8
+}
+
+// Returns the position of a key in each of the three segments.
+#[inline]
+fn xor_slots(h: u64, segment_len: usize) -> [usize; 3] {
+    let reduce =
+        |x: u64| ((x as u32 as u64 * segment_len as u64) >> 32) as usize;
+    [
+        reduce(h),
+        reduce(h.rotate_left(21)) + segment_len,
+        reduce(h.rotate_left(42)) + 2 * segment_len,
+    ]
+}
+
+fn xor_contains(fingerprints: &[u8], seed: u64, hash: u64) -> bool {
+    let segment_len = fingerprints.len() / 3;
+    if segment_len == 0 {
+        return false;
+    }
+
+    let h = mix(hash ^ seed);
+    let [a, b, c] = xor_slots(h, segment_len);
+    fingerprint(h) == fingerprints[a] ^ fingerprints[b] ^ fingerprints[c]
+}
+
+fn xor_build(hashes: &[u64], seed: u64, segment_len: usize) -> Option<Vec<u8>> {
+    let len = 3 * segment_len;
+    // For each slot, the number of keys mapped to it and the xor of their
+    // hashes. A slot with a single key holds that key's hash.
+    let mut counts = vec![0u32; len];
+    let mut xors = vec![0u64; len];
+    for &hash in hashes {
+        let h = mix(hash ^ seed);
+        for slot in xor_slots(h, segment_len) {
+            counts[slot] += 1;
+            xors[slot] ^= h;
+        }
+    }
+
+    // Peel keys which are alone in a slot until none are left.
+    let mut queue = (0..len).filter(|&i| counts[i] == 1).collect::<Vec<_>>();
+    let mut stack = Vec::with_capacity(hashes.len());
+    while let Some(slot) = queue.pop() {
+        if counts[slot] != 1 {
+            continue;
+        }
+        let h = xors[slot];
+        stack.push((h, slot));
+        for other in xor_slots(h, segment_len) {
+            counts[other] -= 1;
+            xors[other] ^= h;
+            if counts[other] == 1 {
+                queue.push(other);
+            }
+        }
+    }
+    if stack.len() != hashes.len() {
+        return None;
+    }
+
+    // Assign fingerprints in the reverse order that the keys were peeled, so
+    // that each key's slot is assigned after the other slots it maps to.
+    let mut fingerprints = vec![0u8; len];
+    for (h, slot) in stack.into_iter().rev() {
+        let [a, b, c] = xor_slots(h, segment_len);
+        fingerprints[slot] = fingerprint(h)
+            ^ fingerprints[a]
+            ^ fingerprints[b]
+            ^ fingerprints[c];
+    }
+    Some(fingerprints)
+}
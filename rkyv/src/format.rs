@@ -0,0 +1,406 @@
+//! Archive formats selected with type parameters.
+//!
+//! The endianness and pointer width of archives are normally selected for the
+//! whole crate with the `little_endian`, `big_endian`, and `pointer_width_*`
+//! features. A [`Format`] describes the same choices as a type, so that the
+//! archived types which take a format parameter can be archived in several
+//! formats in the same program. The format selected by the enabled features
+//! is [`DefaultFormat`], and format parameters default to it.
+//!
+//! [`ArchivedVec`](crate::vec::ArchivedVec) takes a format parameter which
+//! selects the archived types of its relative pointer and length, and the
+//! [`InFormat`](crate::with::InFormat) wrapper archives integers and vecs in a
+//! given format. The elements of a vec are still archived in their own
+//! archived types, which are selected by the enabled features.
+//!
+//! Whether archived primitives are aligned is still selected with the
+//! `unaligned` feature.
+//!
+//! [`to_bytes_in_format`] serializes a whole value in a given format, and
+//! [`ArchivedIn`] names the type it is archived as. These are only available
+//! for [`FormatAware`] types, whose entire archived layout is selected by the
+//! format, so that they never produce an archive with a mix of formats.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access, deserialize,
+//!     format::{BigEndian, Width32},
+//!     rancor::Error,
+//!     to_bytes,
+//!     with::InFormat,
+//!     Archive, Deserialize, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! #[archive(check_bytes)]
+//! struct Packet {
+//!     #[with(InFormat<BigEndian<Width32>>)]
+//!     sequence: u32,
+//!     #[with(InFormat<BigEndian<Width32>>)]
+//!     payload: Vec<u8>,
+//! }
+//!
+//! let value = Packet {
+//!     sequence: 42,
+//!     payload: vec![1, 2, 3],
+//! };
+//! let bytes = to_bytes::<Error>(&value).unwrap();
+//! let archived = access::<ArchivedPacket, Error>(&bytes).unwrap();
+//! assert_eq!(archived.sequence.to_native(), 42);
+//! assert_eq!(archived.payload.as_slice(), &[1, 2, 3]);
+//!
+//! let deserialized =
+//!     deserialize::<Packet, _, Error>(archived, &mut ()).unwrap();
+//! assert_eq!(deserialized, value);
+//! ```
+
+use core::{fmt, marker::PhantomData};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use rancor::{Fallible, Strategy};
+
+use self::primitives::*;
+use crate::{
+    descriptor::Endianness,
+    place::Initialized,
+    primitive::{ArchivedIsize, ArchivedUsize},
+    rel_ptr::Offset,
+    with::{ArchiveWith, InFormat},
+    Portable,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::AllocSerializer,
+    util::{serialize_into, AlignedVec},
+    with::SerializeWith,
+    Archive, Place, Serialize,
+};
+
+/// The archived primitives of every endianness.
+#[cfg(not(feature = "unaligned"))]
+pub(crate) mod primitives {
+    pub use crate::rend::{
+        i16_be, i16_le, i32_be, i32_le, i64_be, i64_le, u16_be, u16_le, u32_be,
+        u32_le, u64_be, u64_le,
+    };
+}
+
+/// The archived primitives of every endianness.
+#[cfg(feature = "unaligned")]
+pub(crate) mod primitives {
+    pub use crate::rend::unaligned::{
+        i16_ube as i16_be, i16_ule as i16_le, i32_ube as i32_be,
+        i32_ule as i32_le, i64_ube as i64_be, i64_ule as i64_le,
+        u16_ube as u16_be, u16_ule as u16_le, u32_ube as u32_be,
+        u32_ule as u32_le, u64_ube as u64_be, u64_ule as u64_le,
+    };
+}
+
+/// An archived primitive which can be converted to and from a native type.
+pub trait Primitive<T>: Copy + Initialized + Portable {
+    /// Creates an archived primitive from a native value.
+    ///
+    /// Like archiving a `usize` or `isize`, this truncates values which are
+    /// too large for the archived type.
+    fn from_native(value: T) -> Self;
+
+    /// Returns the native value of the archived primitive.
+    fn to_native(&self) -> T;
+}
+
+macro_rules! impl_primitive {
+    ($native:ty as $pointer:ty: $($archived:ty),* $(,)?) => {
+        $(
+            impl Primitive<$native> for $archived {
+                #[inline]
+                fn from_native(value: $native) -> Self {
+                    <$archived>::from_native(value)
+                }
+
+                #[inline]
+                fn to_native(&self) -> $native {
+                    <$archived>::to_native(*self)
+                }
+            }
+
+            impl Primitive<$pointer> for $archived {
+                #[inline]
+                fn from_native(value: $pointer) -> Self {
+                    <$archived>::from_native(value as $native)
+                }
+
+                #[inline]
+                fn to_native(&self) -> $pointer {
+                    <$archived>::to_native(*self) as $pointer
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(i16 as isize: i16_le, i16_be);
+impl_primitive!(i32 as isize: i32_le, i32_be);
+impl_primitive!(i64 as isize: i64_le, i64_be);
+impl_primitive!(u16 as usize: u16_le, u16_be);
+impl_primitive!(u32 as usize: u32_le, u32_be);
+impl_primitive!(u64 as usize: u64_le, u64_be);
+
+/// The endianness and pointer width of an archive.
+///
+/// See the [module docs](self) for more information.
+pub trait Format: 'static {
+    /// The byte order of archived primitives.
+    const ENDIANNESS: Endianness;
+    /// The size of archived `usize`s and `isize`s in bytes.
+    const POINTER_WIDTH: u8;
+
+    /// The archived version of `i16`.
+    type I16: Primitive<i16>;
+    /// The archived version of `i32`.
+    type I32: Primitive<i32>;
+    /// The archived version of `i64`.
+    type I64: Primitive<i64>;
+    /// The archived version of `u16`.
+    type U16: Primitive<u16>;
+    /// The archived version of `u32`.
+    type U32: Primitive<u32>;
+    /// The archived version of `u64`.
+    type U64: Primitive<u64>;
+    /// The archived version of `isize`, which is also used as the offset of
+    /// relative pointers.
+    type Isize: Primitive<isize> + Offset;
+    /// The archived version of `usize`, which is also used for lengths.
+    type Usize: Primitive<usize>;
+}
+
+/// A pointer width of 16 bits.
+#[derive(Debug)]
+pub struct Width16;
+
+/// A pointer width of 32 bits.
+#[derive(Debug)]
+pub struct Width32;
+
+/// A pointer width of 64 bits.
+#[derive(Debug)]
+pub struct Width64;
+
+/// A little-endian format with a pointer width of `W`.
+pub struct LittleEndian<W> {
+    _width: PhantomData<W>,
+}
+
+impl<W> fmt::Debug for LittleEndian<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LittleEndian").finish()
+    }
+}
+
+/// A big-endian format with a pointer width of `W`.
+pub struct BigEndian<W> {
+    _width: PhantomData<W>,
+}
+
+impl<W> fmt::Debug for BigEndian<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BigEndian").finish()
+    }
+}
+
+macro_rules! impl_format {
+    (
+        $format:ident <
+        $width:ident > :
+        $endianness:ident,
+        $pointer_width:literal,
+        $isize:ty,
+        $usize:ty,[$i16:ty, $i32:ty, $i64:ty, $u16:ty, $u32:ty, $u64:ty] $(,)?
+    ) => {
+        impl Format for $format<$width> {
+            const ENDIANNESS: Endianness = Endianness::$endianness;
+            const POINTER_WIDTH: u8 = $pointer_width;
+
+            type I16 = $i16;
+            type I32 = $i32;
+            type I64 = $i64;
+            type U16 = $u16;
+            type U32 = $u32;
+            type U64 = $u64;
+            type Isize = $isize;
+            type Usize = $usize;
+        }
+    };
+}
+
+macro_rules! impl_formats {
+    ($format:ident: $endianness:ident, [
+        $i16:ty, $i32:ty, $i64:ty, $u16:ty, $u32:ty, $u64:ty $(,)?
+    ]) => {
+        impl_format!(
+            $format<Width16>: $endianness, 2, $i16, $u16,
+            [$i16, $i32, $i64, $u16, $u32, $u64],
+        );
+        #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+        impl_format!(
+            $format<Width32>: $endianness, 4, $i32, $u32,
+            [$i16, $i32, $i64, $u16, $u32, $u64],
+        );
+        #[cfg(target_pointer_width = "64")]
+        impl_format!(
+            $format<Width64>: $endianness, 8, $i64, $u64,
+            [$i16, $i32, $i64, $u16, $u32, $u64],
+        );
+    };
+}
+
+impl_formats!(
+    LittleEndian: Little,
+    [i16_le, i32_le, i64_le, u16_le, u32_le, u64_le]
+);
+impl_formats!(
+    BigEndian: Big,
+    [i16_be, i32_be, i64_be, u16_be, u32_be, u64_be]
+);
+
+/// The pointer width selected by the `pointer_width_*` features.
+pub type DefaultWidth = match_pointer_width!(Width16, Width32, Width64);
+
+/// The format selected by the enabled features.
+#[cfg(not(feature = "big_endian"))]
+pub type DefaultFormat = LittleEndian<DefaultWidth>;
+
+/// The format selected by the enabled features.
+#[cfg(feature = "big_endian")]
+pub type DefaultFormat = BigEndian<DefaultWidth>;
+
+// The default format must archive the same types as the enabled features, so
+// that defaulted format parameters don't change any archived layouts.
+const _: fn(ArchivedIsize) -> <DefaultFormat as Format>::Isize = |x| x;
+const _: fn(ArchivedUsize) -> <DefaultFormat as Format>::Usize = |x| x;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type whose entire archived layout is selected by a [`Format`].
+///
+/// Integers are format-aware, and so are vecs of single-byte types like `u8`,
+/// `i8`, and `bool`. Vecs of other types are not, because their elements are
+/// archived in the format selected by the enabled features rather than `F`.
+///
+/// This trait is sealed and can't be implemented outside of rkyv.
+pub trait FormatAware<F: Format>: sealed::Sealed {
+    /// The archived type of `Self` in the format `F`.
+    type Archived: Portable;
+}
+
+macro_rules! impl_format_aware {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl<F: Format> FormatAware<F> for $ty {
+                type Archived = <InFormat<F> as ArchiveWith<$ty>>::Archived;
+            }
+        )*
+    };
+}
+
+impl_format_aware!(i16, i32, i64, isize, u16, u32, u64, usize);
+
+#[cfg(feature = "alloc")]
+impl_format_aware!(Vec<u8>, Vec<i8>, Vec<bool>);
+
+/// The archived type of a `T` archived in the format `F`.
+///
+/// This is the type that [`to_bytes_in_format`] archives a `T` as.
+pub type ArchivedIn<T, F> = <T as FormatAware<F>>::Archived;
+
+/// Serializes the given value in the format `F` and returns the resulting
+/// bytes in an [`AlignedVec`].
+///
+/// The value is archived as if it were a field with the
+/// [`InFormat<F>`](InFormat) wrapper, so its archived type is
+/// [`ArchivedIn<T, F>`](ArchivedIn). Only [`FormatAware`] types can be
+/// serialized this way, since every part of the resulting archive must be in
+/// the format `F`.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked,
+///     format::{to_bytes_in_format, ArchivedIn, BigEndian, Width32},
+///     rancor::Error,
+/// };
+///
+/// type Format = BigEndian<Width32>;
+///
+/// let value = vec![1u8, 2, 3, 4];
+/// let bytes = to_bytes_in_format::<Format, _, Error>(&value).unwrap();
+/// assert_eq!(&bytes[4..], &[0xff, 0xff, 0xff, 0xfc, 0, 0, 0, 4]);
+///
+/// let archived =
+///     unsafe { access_unchecked::<ArchivedIn<Vec<u8>, Format>>(&bytes) };
+/// assert_eq!(archived.as_slice(), &[1, 2, 3, 4]);
+/// ```
+///
+/// Vecs whose elements would be archived in a different format fail to
+/// compile:
+///
+/// ```compile_fail
+/// use rkyv::{
+///     format::{to_bytes_in_format, BigEndian, Width32},
+///     rancor::Error,
+/// };
+///
+/// let value = vec![1u32, 2, 3, 4];
+/// to_bytes_in_format::<BigEndian<Width32>, _, Error>(&value).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_bytes_in_format<F, T, E>(value: &T) -> Result<AlignedVec, E>
+where
+    F: Format,
+    T: FormatAware<F>,
+    InFormat<F>: SerializeWith<T, Strategy<AllocSerializer, E>>,
+{
+    // Archives the wrapped value with `InFormat<F>` so that it can be
+    // serialized as the root of the archive.
+    struct Root<'a, F, T>(&'a T, PhantomData<F>);
+
+    impl<F, T> Archive for Root<'_, F, T>
+    where
+        InFormat<F>: ArchiveWith<T>,
+    {
+        type Archived = ArchivedIn<T, F>;
+        type Resolver = <InFormat<F> as ArchiveWith<T>>::Resolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            InFormat::<F>::resolve_with(self.0, resolver, out)
+        }
+    }
+
+    impl<F, T, S> Serialize<S> for Root<'_, F, T>
+    where
+        InFormat<F>: SerializeWith<T, S>,
+        S: Fallible + ?Sized,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            InFormat::<F>::serialize_with(self.0, serializer)
+        }
+    }
+
+    let root = Root::<F, T>(value, PhantomData);
+    Ok(serialize_into(&root, AllocSerializer::default())?.into_writer())
+}
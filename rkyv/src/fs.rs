@@ -0,0 +1,356 @@
+//! Crash-safe archive files.
+//!
+//! Writing an archive over an existing file in place can leave a mix of old
+//! and new bytes behind if the process or machine crashes part of the way
+//! through. [`write_atomic`] uses a two-phase commit instead:
+//!
+//! 1. The archive is written to a temporary file in the same directory, behind
+//!    a header holding its length and a checksum of its bytes. The temporary
+//!    file is synced to disk.
+//! 2. The temporary file is renamed over the destination, and the directory is
+//!    synced so that the rename is durable.
+//!
+//! Readers see either the old file or the new file, never a partial one.
+//! [`open_checked`] verifies the header and checksum before validating the
+//! archive, so a file that was corrupted some other way is still rejected.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     fs::{open_checked, write_atomic},
+//!     rancor::Error,
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct Settings {
+//!     volume: u8,
+//!     name: String,
+//! }
+//!
+//! let path = std::env::temp_dir()
+//!     .join(format!("rkyv_fs_doc_{}.rkyv", std::process::id()));
+//! let settings = Settings {
+//!     volume: 7,
+//!     name: "living room".to_string(),
+//! };
+//! write_atomic::<_, Error>(&path, &settings).unwrap();
+//!
+//! let file = open_checked::<ArchivedSettings, Error>(&path).unwrap();
+//! assert_eq!(file.volume, 7);
+//! assert_eq!(file.name, "living room");
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+use rancor::{fail, ResultExt as _, Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    hash::{hash_value, FxHasher64},
+    ser::AllocSerializer,
+    util::AlignedVec,
+    Portable, Serialize,
+};
+
+/// The length of the header at the start of an archive file in bytes.
+///
+/// The header length is a multiple of the archive alignment, so the archive
+/// which follows it is aligned when the file is read into an [`AlignedVec`].
+pub const HEADER_LEN: usize = 32;
+
+const MAGIC: [u8; 4] = *b"rkyf";
+const VERSION: u8 = 1;
+
+#[derive(Debug)]
+enum ArchiveFileError {
+    Truncated { expected: usize, actual: usize },
+    BadMagic,
+    BadVersion(u8),
+    NonzeroReserved,
+    LengthMismatch { expected: u64, actual: usize },
+    ChecksumMismatch { expected: u64, actual: u64 },
+    NoFileName(PathBuf),
+}
+
+impl fmt::Display for ArchiveFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { expected, actual } => write!(
+                f,
+                "archive file is truncated: expected at least {} bytes but \
+                 found {}",
+                expected, actual,
+            ),
+            Self::BadMagic => write!(f, "file is not an archive file"),
+            Self::BadVersion(version) => {
+                write!(f, "unsupported archive file version {}", version)
+            }
+            Self::NonzeroReserved => {
+                write!(f, "reserved archive file header bytes are not zero")
+            }
+            Self::LengthMismatch { expected, actual } => write!(
+                f,
+                "archive file length mismatch: header says {} bytes but the \
+                 archive has {}",
+                expected, actual,
+            ),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "archive file checksum mismatch: expected {:#018x} but found \
+                 {:#018x}",
+                expected, actual,
+            ),
+            Self::NoFileName(path) => {
+                write!(f, "path {} does not name a file", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveFileError {}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    hash_value::<[u8], FxHasher64>(bytes)
+}
+
+// Returns a path in the same directory as `path` which no other write is
+// using. Writes from other processes use different IDs, and writes from the
+// same process use different counters.
+fn temp_path<E: Source>(path: &Path) -> Result<PathBuf, E> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => fail!(ArchiveFileError::NoFileName(path.to_path_buf())),
+    };
+    Ok(path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+    )))
+}
+
+// Syncs the directory containing `path` so that a rename into it is durable.
+#[cfg(unix)]
+fn sync_parent<E: Source>(path: &Path) -> Result<(), E> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent).into_error()?.sync_all().into_error()
+}
+
+// Other platforms don't support opening directories, and make renames
+// durable without syncing the directory.
+#[cfg(not(unix))]
+fn sync_parent<E: Source>(_: &Path) -> Result<(), E> {
+    Ok(())
+}
+
+/// Serializes the given value into the bytes of an archive file.
+///
+/// The bytes start with a [`HEADER_LEN`]-byte header holding the length and
+/// checksum of the archive, followed by the archive itself.
+pub fn to_file_bytes<T, E>(value: &T) -> Result<AlignedVec, E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    let mut bytes = AlignedVec::new();
+    bytes.extend_from_slice(&[0; HEADER_LEN]);
+    let serializer =
+        AllocSerializer::new(bytes, Default::default(), Default::default());
+    let mut bytes =
+        crate::util::serialize_into(value, serializer)?.into_writer();
+
+    let len = (bytes.len() - HEADER_LEN) as u64;
+    let checksum = checksum(&bytes[HEADER_LEN..]);
+    bytes[0..4].copy_from_slice(&MAGIC);
+    bytes[4] = VERSION;
+    bytes[8..16].copy_from_slice(&len.to_le_bytes());
+    bytes[16..24].copy_from_slice(&checksum.to_le_bytes());
+
+    Ok(bytes)
+}
+
+/// Verifies the header and checksum of the bytes of an archive file, and
+/// returns the archive they contain.
+///
+/// The archive has not been validated.
+pub fn verify_file_bytes<E: Source>(bytes: &[u8]) -> Result<&[u8], E> {
+    if bytes.len() < HEADER_LEN {
+        fail!(ArchiveFileError::Truncated {
+            expected: HEADER_LEN,
+            actual: bytes.len(),
+        });
+    }
+    if bytes[0..4] != MAGIC {
+        fail!(ArchiveFileError::BadMagic);
+    }
+    if bytes[4] != VERSION {
+        fail!(ArchiveFileError::BadVersion(bytes[4]));
+    }
+    let reserved = bytes[5..8].iter().chain(&bytes[24..HEADER_LEN]);
+    if reserved.copied().any(|b| b != 0) {
+        fail!(ArchiveFileError::NonzeroReserved);
+    }
+
+    let mut u64_bytes = [0; 8];
+    u64_bytes.copy_from_slice(&bytes[8..16]);
+    let expected_len = u64::from_le_bytes(u64_bytes);
+    u64_bytes.copy_from_slice(&bytes[16..24]);
+    let expected_checksum = u64::from_le_bytes(u64_bytes);
+
+    let archive = &bytes[HEADER_LEN..];
+    if expected_len != archive.len() as u64 {
+        fail!(ArchiveFileError::LengthMismatch {
+            expected: expected_len,
+            actual: archive.len(),
+        });
+    }
+    let actual_checksum = checksum(archive);
+    if expected_checksum != actual_checksum {
+        fail!(ArchiveFileError::ChecksumMismatch {
+            expected: expected_checksum,
+            actual: actual_checksum,
+        });
+    }
+
+    Ok(archive)
+}
+
+// Writes and syncs the temporary file, then renames it over the destination.
+fn write_temp<E: Source>(
+    temp: &Path,
+    bytes: &[u8],
+    path: &Path,
+) -> Result<(), E> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp)
+        .into_error()?;
+    file.write_all(bytes).into_error()?;
+    file.sync_all().into_error()?;
+    drop(file);
+    fs::rename(temp, path).into_error()
+}
+
+/// Serializes the given value and atomically replaces the file at the given
+/// path with it.
+///
+/// The archive is written to a temporary file next to the destination, synced
+/// to disk, and then renamed into place. If this returns an error, the file
+/// at the given path is left unchanged. See the [module docs](self) for more
+/// information.
+pub fn write_atomic<T, E>(path: impl AsRef<Path>, value: &T) -> Result<(), E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    let path = path.as_ref();
+    let bytes = to_file_bytes::<T, E>(value)?;
+    let temp = temp_path::<E>(path)?;
+
+    if let Err(e) = write_temp::<E>(&temp, &bytes, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    sync_parent(path)
+}
+
+/// An archive file which has been read into memory.
+///
+/// This is returned by [`open_checked`], and dereferences to the root of the
+/// archive.
+pub struct ArchiveFile<T> {
+    bytes: AlignedVec,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Portable> ArchiveFile<T> {
+    /// Reads the archive file at the given path and verifies its header and
+    /// checksum, without validating the archive.
+    ///
+    /// # Safety
+    ///
+    /// The file must contain a valid archive of `T`.
+    pub unsafe fn open_unchecked<E: Source>(
+        path: impl AsRef<Path>,
+    ) -> Result<Self, E> {
+        let mut file = File::open(path).into_error()?;
+        let mut bytes = AlignedVec::new();
+        bytes.extend_from_reader(&mut file).into_error()?;
+        verify_file_bytes::<E>(&bytes)?;
+        Ok(Self {
+            bytes,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the bytes of the archive, without the file header.
+    #[inline]
+    pub fn archive_bytes(&self) -> &[u8] {
+        &self.bytes[HEADER_LEN..]
+    }
+
+    /// Returns a reference to the root of the archive.
+    #[inline]
+    pub fn get(&self) -> &T {
+        // SAFETY: The archive was validated when the file was opened, or the
+        // caller of `open_unchecked` guaranteed that it is valid.
+        unsafe { crate::access_unchecked::<T>(self.archive_bytes()) }
+    }
+}
+
+impl<T: Portable> Deref for ArchiveFile<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T> fmt::Debug for ArchiveFile<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveFile")
+            .field("len", &(self.bytes.len() - HEADER_LEN))
+            .finish()
+    }
+}
+
+/// Reads the archive file at the given path, verifies its header and
+/// checksum, and validates the archive it contains.
+///
+/// This reads files written by [`write_atomic`].
+#[cfg(feature = "bytecheck")]
+pub fn open_checked<T, E>(path: impl AsRef<Path>) -> Result<ArchiveFile<T>, E>
+where
+    T: Portable + CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    // SAFETY: The archive is validated before the file is returned.
+    let file = unsafe { ArchiveFile::<T>::open_unchecked::<E>(path)? };
+    crate::access::<T, E>(file.archive_bytes())?;
+    Ok(file)
+}
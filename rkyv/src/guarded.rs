@@ -0,0 +1,338 @@
+//! Archived values which one writer can update in place while others read.
+//!
+//! A [`GuardedArchive`] pairs an archived value with an archived generation
+//! counter which works like a seqlock. The generation is even while the value
+//! is stable and odd while a writer is updating it:
+//!
+//! - Writers take a [`WriteGuard`], which makes the generation odd until it is
+//!   dropped. Only one write guard can exist at a time.
+//! - Readers take a [`snapshot`](GuardedArchive::snapshot) of the value by
+//!   copying it out of the archive. If the generation changed while the value
+//!   was being copied, the copy is discarded and the read is retried.
+//!
+//! Because the generation is part of the archive, this coordinates readers
+//! and writers in different processes which map the same buffer. Readers
+//! never block writers, so a guarded archive is best suited to small values
+//! which are read often and written rarely.
+//!
+//! Snapshots copy the value, so only `Copy` values which do not contain
+//! relative pointers can be read from a guarded archive.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked_mut,
+//!     guarded::{Guarded, GuardedArchive},
+//!     primitive::ArchivedU32,
+//!     rancor::Error,
+//!     to_bytes,
+//! };
+//!
+//! let mut bytes = to_bytes::<Error>(&Guarded(1u32)).unwrap();
+//! let guarded = unsafe {
+//!     access_unchecked_mut::<GuardedArchive<ArchivedU32>>(&mut bytes)
+//! }
+//! .get_ref();
+//! assert_eq!(guarded.snapshot().to_native(), 1);
+//!
+//! let mut guard = guarded.write();
+//! guard.get_mut().set(ArchivedU32::from_native(2));
+//! assert!(guarded.try_snapshot().is_none());
+//! drop(guard);
+//!
+//! assert_eq!(guarded.snapshot().to_native(), 2);
+//! assert_eq!(guarded.generation(), 2);
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    fmt, hint,
+    mem::MaybeUninit,
+    ops::Deref,
+    pin::Pin,
+    ptr,
+    sync::atomic::{fence, Ordering},
+};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    primitive::ArchivedAtomicU32, Archive, Deserialize, Place, Portable,
+    Serialize,
+};
+
+/// A value which is archived with a generation counter.
+///
+/// See the [module docs](self) for more information.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Guarded<T>(pub T);
+
+/// An archived `Guarded`.
+///
+/// See the [module docs](self) for more information.
+#[repr(C)]
+pub struct GuardedArchive<T> {
+    generation: ArchivedAtomicU32,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `GuardedArchive<T>` is `repr(C)` and its fields are portable when
+// `T` is portable.
+unsafe impl<T: Portable> Portable for GuardedArchive<T> {}
+
+// SAFETY: The value is only moved between threads by copying it out of the
+// archive or through a write guard, which synchronize on the generation.
+unsafe impl<T: Send> Sync for GuardedArchive<T> {}
+
+impl<T> GuardedArchive<T> {
+    /// Returns the current generation of the value.
+    ///
+    /// The generation is odd while a writer is updating the value, and
+    /// increases by two each time the value is written.
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Returns whether a writer is updating the value.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.generation() % 2 == 1
+    }
+
+    /// Returns a guard for writing the value, or `None` if another writer is
+    /// already updating it.
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        let generation = self.generation.load(Ordering::Relaxed);
+        if generation % 2 == 1 {
+            return None;
+        }
+        self.generation
+            .compare_exchange(
+                generation,
+                generation.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()?;
+        // Writes to the value must not become visible before the generation
+        // is odd.
+        fence(Ordering::Release);
+        Some(WriteGuard { archive: self })
+    }
+
+    /// Returns a guard for writing the value, spinning until any other writer
+    /// is done updating it.
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_write() {
+                return guard;
+            }
+            hint::spin_loop();
+        }
+    }
+}
+
+impl<T: Copy> GuardedArchive<T> {
+    /// Returns a copy of the value, or `None` if a writer was updating it.
+    pub fn try_snapshot(&self) -> Option<T> {
+        let generation = self.generation.load(Ordering::Acquire);
+        if generation % 2 == 1 {
+            return None;
+        }
+
+        // SAFETY: The value is copied with a volatile read so that a
+        // concurrent write can't be assumed away. It is read as a
+        // `MaybeUninit<T>`, which has the same layout as `T` and is valid for
+        // any bytes, so a torn copy is not a valid `T` until it is checked.
+        let value = unsafe {
+            ptr::read_volatile(self.value.get().cast::<MaybeUninit<T>>())
+        };
+        // The copy must be finished before the generation is checked again.
+        fence(Ordering::Acquire);
+        if self.generation.load(Ordering::Relaxed) == generation {
+            // SAFETY: The generation did not change while the value was
+            // copied, so no writer was updating it and the copy is a valid
+            // `T`.
+            Some(unsafe { value.assume_init() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a copy of the value, spinning until no writer is updating it.
+    ///
+    /// This is a seqlock read: the value is copied as uninitialized bytes, and
+    /// the copy is only treated as a `T` once the generation is confirmed to
+    /// be unchanged. A copy torn by a concurrent writer is discarded without
+    /// ever being interpreted as a `T`.
+    pub fn snapshot(&self) -> T {
+        loop {
+            if let Some(value) = self.try_snapshot() {
+                return value;
+            }
+            hint::spin_loop();
+        }
+    }
+}
+
+impl<T> fmt::Debug for GuardedArchive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuardedArchive")
+            .field("generation", &self.generation())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A guard which allows a single writer to update a [`GuardedArchive`].
+///
+/// The generation of the archive is odd while the guard exists, and is
+/// advanced to the next even generation when it is dropped. This is returned
+/// by [`GuardedArchive::write`] and [`GuardedArchive::try_write`].
+pub struct WriteGuard<'a, T> {
+    archive: &'a GuardedArchive<T>,
+}
+
+impl<T> WriteGuard<'_, T> {
+    /// Returns a mutable reference to the value.
+    #[inline]
+    pub fn get_mut(&mut self) -> Pin<&mut T> {
+        // SAFETY: The guard has exclusive access to the value, and archived
+        // values are never moved out of their archive.
+        unsafe { Pin::new_unchecked(&mut *self.archive.value.get()) }
+    }
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The guard has exclusive access to the value.
+        unsafe { &*self.archive.value.get() }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        let generation = self.archive.generation.load(Ordering::Relaxed);
+        self.archive
+            .generation
+            .store(generation.wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for WriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteGuard")
+            .field("archive", self.archive)
+            .finish()
+    }
+}
+
+impl<T: Archive> Archive for Guarded<T> {
+    type Archived = GuardedArchive<T::Archived>;
+    type Resolver = T::Resolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let GuardedArchive { generation, value } = out);
+        generation.write(ArchivedAtomicU32::new(0));
+        // SAFETY: `UnsafeCell<T::Archived>` has the same layout as
+        // `T::Archived`.
+        let value = unsafe { value.cast_unchecked::<T::Archived>() };
+        self.0.resolve(resolver, value);
+    }
+}
+
+impl<T, S> Serialize<S> for Guarded<T>
+where
+    T: Serialize<S>,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T, D> Deserialize<Guarded<T>, D> for GuardedArchive<T::Archived>
+where
+    T: Archive,
+    T::Archived: Copy + Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<Guarded<T>, D::Error> {
+        Ok(Guarded(self.snapshot().deserialize(deserializer)?))
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::{fmt, ptr::addr_of, sync::atomic::Ordering};
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        CheckBytes,
+    };
+    use rancor::fail;
+
+    use super::GuardedArchive;
+    use crate::primitive::ArchivedAtomicU32;
+
+    #[derive(Debug)]
+    struct LockedError {
+        generation: u32,
+    }
+
+    impl fmt::Display for LockedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "guarded archive is locked by a writer at generation {}",
+                self.generation,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for LockedError {}
+
+    unsafe impl<T, C> CheckBytes<C> for GuardedArchive<T>
+    where
+        T: CheckBytes<C>,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: The caller has guaranteed that `value` is aligned and
+            // points to enough bytes for a `GuardedArchive<T>`.
+            let generation = unsafe { addr_of!((*value).generation) };
+            unsafe {
+                ArchivedAtomicU32::check_bytes(generation, context)?;
+            }
+            let generation = unsafe { (*generation).load(Ordering::Acquire) };
+            if generation % 2 == 1 {
+                fail!(LockedError { generation });
+            }
+
+            // SAFETY: `UnsafeCell<T>` has the same layout as `T`.
+            unsafe {
+                T::check_bytes(addr_of!((*value).value).cast::<T>(), context)
+            }
+        }
+    }
+}
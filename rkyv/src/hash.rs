@@ -1,11 +1,16 @@
 //! Hashing support for archived hash maps and sets.
 
 use core::{
-    hash::{Hash, Hasher},
+    hash::{BuildHasherDefault, Hash, Hasher},
     ops::BitXor as _,
 };
 
-use crate::primitive::{FixedIsize, FixedUsize};
+use munge::munge;
+
+use crate::{
+    primitive::{ArchivedU64, FixedIsize, FixedUsize},
+    Place, Portable,
+};
 
 /// A cross-platform 64-bit implementation of fxhash.
 #[derive(Default)]
@@ -13,6 +18,31 @@ pub struct FxHasher64 {
     hash: u64,
 }
 
+impl FxHasher64 {
+    /// Returns a new `FxHasher64` with the given initial state.
+    #[inline]
+    pub const fn with_seed(seed: u64) -> Self {
+        Self { hash: seed }
+    }
+}
+
+/// A [`BuildHasher`](core::hash::BuildHasher) for [`FxHasher64`].
+///
+/// This is much faster than the default hasher for the integer keys used by
+/// in-memory bookkeeping maps like [`ser::Unify`](crate::ser::Unify) and
+/// [`de::Unify`](crate::de::Unify), but is not resistant to HashDoS attacks.
+pub type FxBuildHasher = BuildHasherDefault<FxHasher64>;
+
+/// The hasher used by in-memory bookkeeping maps when no other hasher is
+/// specified.
+#[cfg(feature = "std")]
+pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+
+/// The hasher used by in-memory bookkeeping maps when no other hasher is
+/// specified.
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub type DefaultHashBuilder = hashbrown::hash_map::DefaultHashBuilder;
+
 #[inline]
 fn hash_word(hash: u64, word: u64) -> u64 {
     const ROTATE: u32 = 5;
@@ -123,3 +153,417 @@ where
     value.hash(&mut state);
     state.finish()
 }
+
+/// A cross-platform implementation of SipHash-1-3.
+///
+/// Unlike fxhash, SipHash is keyed and suitable for hash maps which may be
+/// built from untrusted keys.
+#[derive(Clone)]
+pub struct SipHasher13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    tail: u64,
+    ntail: usize,
+    length: usize,
+}
+
+impl Default for SipHasher13 {
+    #[inline]
+    fn default() -> Self {
+        Self::new_with_keys(0, 0)
+    }
+}
+
+#[inline]
+fn load_le(bytes: &[u8]) -> u64 {
+    let mut result = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        result |= (*byte as u64) << (8 * i);
+    }
+    result
+}
+
+impl SipHasher13 {
+    /// Returns a new `SipHasher13` keyed with the two given keys.
+    #[inline]
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v0: k0 ^ 0x73_6f_6d_65_70_73_65_75,
+            v1: k1 ^ 0x64_6f_72_61_6e_64_6f_6d,
+            v2: k0 ^ 0x6c_79_67_65_6e_65_72_61,
+            v3: k1 ^ 0x74_65_64_62_79_74_65_73,
+            tail: 0,
+            ntail: 0,
+            length: 0,
+        }
+    }
+
+    #[inline]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    #[inline]
+    fn compress(&mut self, word: u64) {
+        self.v3 ^= word;
+        self.round();
+        self.v0 ^= word;
+    }
+}
+
+impl Hasher for SipHasher13 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.length += bytes.len();
+
+        let mut needed = 0;
+        if self.ntail != 0 {
+            needed = 8 - self.ntail;
+            let fill = needed.min(bytes.len());
+            self.tail |= load_le(&bytes[..fill]) << (8 * self.ntail);
+            if bytes.len() < needed {
+                self.ntail += bytes.len();
+                return;
+            }
+            self.compress(self.tail);
+            self.tail = 0;
+            self.ntail = 0;
+        }
+
+        let chunks = bytes[needed..].chunks_exact(8);
+        let remainder = chunks.remainder();
+        for chunk in chunks {
+            self.compress(load_le(chunk));
+        }
+        self.tail = load_le(remainder);
+        self.ntail = remainder.len();
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = self.clone();
+        let b = ((self.length as u64 & 0xff) << 56) | self.tail;
+        state.compress(b);
+        state.v2 ^= 0xff;
+        state.round();
+        state.round();
+        state.round();
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write(&(i as FixedUsize).to_le_bytes());
+    }
+
+    #[inline]
+    fn write_isize(&mut self, i: isize) {
+        self.write(&(i as FixedIsize).to_le_bytes());
+    }
+}
+
+/// A hash algorithm which can be selected for an archived hash collection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum HashAlgorithm {
+    /// The hasher selected by the type parameter of the hash collection.
+    #[default]
+    Default = 0,
+    /// fxhash with a seeded initial state. Fast, but not resistant to
+    /// collision attacks.
+    FxHash64 = 1,
+    /// SipHash-1-3 keyed with the seed. Resistant to collision attacks.
+    SipHash13 = 2,
+}
+
+impl HashAlgorithm {
+    /// Returns the algorithm with the given identifier, if any.
+    #[inline]
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Default),
+            1 => Some(Self::FxHash64),
+            2 => Some(Self::SipHash13),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of the algorithm.
+    #[inline]
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Hashes the given value with this algorithm and seed.
+    ///
+    /// `H` is the hasher used for [`HashAlgorithm::Default`].
+    #[inline]
+    pub fn hash_value<Q, H>(self, seed: u64, value: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+        H: Hasher + Default,
+    {
+        match self {
+            Self::Default => hash_value::<Q, H>(value),
+            Self::FxHash64 => {
+                let mut state = FxHasher64::with_seed(seed);
+                value.hash(&mut state);
+                state.finish()
+            }
+            Self::SipHash13 => {
+                let mut state =
+                    SipHasher13::new_with_keys(seed, seed.rotate_left(32));
+                value.hash(&mut state);
+                state.finish()
+            }
+        }
+    }
+}
+
+/// The hasher used to build a hash collection.
+///
+/// The hasher configuration is stored in the archived collection so that
+/// lookups use the same algorithm and seed as serialization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HasherConfig {
+    /// The hash algorithm to use.
+    pub algorithm: HashAlgorithm,
+    /// The seed for the hash algorithm. This is ignored by
+    /// [`HashAlgorithm::Default`].
+    pub seed: u64,
+}
+
+impl HasherConfig {
+    /// Returns a new hasher configuration with the given algorithm and seed.
+    #[inline]
+    pub const fn new(algorithm: HashAlgorithm, seed: u64) -> Self {
+        Self { algorithm, seed }
+    }
+
+    /// Hashes the given value with this configuration.
+    ///
+    /// `H` is the hasher used for [`HashAlgorithm::Default`].
+    #[inline]
+    pub fn hash_value<Q, H>(&self, value: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+        H: Hasher + Default,
+    {
+        self.algorithm.hash_value::<Q, H>(self.seed, value)
+    }
+}
+
+/// A hasher which can be selected for an archived hash collection.
+///
+/// The [`WithHasher`](crate::with::WithHasher) wrapper uses this to archive a
+/// hash map or set with a hasher other than the default.
+pub trait SeededHasher: Hasher {
+    /// The hash algorithm implemented by the hasher.
+    const ALGORITHM: HashAlgorithm;
+}
+
+impl SeededHasher for FxHasher64 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::FxHash64;
+}
+
+impl SeededHasher for SipHasher13 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::SipHash13;
+}
+
+/// An archived [`HasherConfig`].
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedHasherConfig {
+    seed: ArchivedU64,
+    algorithm: u8,
+}
+
+impl ArchivedHasherConfig {
+    /// Returns the hash algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> HashAlgorithm {
+        // Validation guarantees that the algorithm is known, but fall back to
+        // the default hasher for unvalidated archives.
+        HashAlgorithm::from_id(self.algorithm).unwrap_or_default()
+    }
+
+    /// Returns the seed for the hash algorithm.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed.to_native()
+    }
+
+    /// Hashes the given value with this configuration.
+    ///
+    /// `H` is the hasher used for [`HashAlgorithm::Default`].
+    #[inline]
+    pub fn hash_value<Q, H>(&self, value: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+        H: Hasher + Default,
+    {
+        self.algorithm().hash_value::<Q, H>(self.seed(), value)
+    }
+
+    /// Resolves an archived hasher configuration from the given config.
+    #[inline]
+    pub fn resolve_from_config(config: &HasherConfig, out: Place<Self>) {
+        munge!(let ArchivedHasherConfig { seed, algorithm } = out);
+        seed.write(ArchivedU64::from_native(config.seed));
+        algorithm.write(config.algorithm.id());
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::fmt;
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+    use rancor::fail;
+
+    use super::{ArchivedHasherConfig, HashAlgorithm};
+
+    #[derive(Debug)]
+    struct UnknownHashAlgorithm {
+        id: u8,
+    }
+
+    impl fmt::Display for UnknownHashAlgorithm {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "unknown hash algorithm identifier: {}", self.id)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for UnknownHashAlgorithm {}
+
+    unsafe impl<C> Verify<C> for ArchivedHasherConfig
+    where
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        #[inline]
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            if HashAlgorithm::from_id(self.algorithm).is_none() {
+                fail!(UnknownHashAlgorithm { id: self.algorithm });
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use super::{FxHasher64, HashAlgorithm, SipHasher13};
+
+    const DATA: [u8; 64] = {
+        let mut data = [0; 64];
+        let mut i = 0;
+        while i < data.len() {
+            data[i] = i as u8;
+            i += 1;
+        }
+        data
+    };
+
+    #[test]
+    fn siphash13_known_answers() {
+        // Keyed with the bytes 0x00..0x0f and hashing the bytes 0x00..len,
+        // matching the SipHash-1-3 reference implementation.
+        const K0: u64 = 0x07_06_05_04_03_02_01_00;
+        const K1: u64 = 0x0f_0e_0d_0c_0b_0a_09_08;
+        const EXPECTED: [(usize, u64); 6] = [
+            (0, 0xabac0158050fc4dc),
+            (1, 0xc9f49bf37d57ca93),
+            (7, 0xd3927d989bb11140),
+            (8, 0x369095118d299a8e),
+            (15, 0xd320d86d2a519956),
+            (63, 0x9d199062b7bbb3a8),
+        ];
+
+        for (len, expected) in EXPECTED {
+            let mut hasher = SipHasher13::new_with_keys(K0, K1);
+            hasher.write(&DATA[..len]);
+            assert_eq!(hasher.finish(), expected);
+
+            // Splitting the input across writes doesn't change the hash
+            let mut hasher = SipHasher13::new_with_keys(K0, K1);
+            for chunk in DATA[..len].chunks(3) {
+                hasher.write(chunk);
+            }
+            assert_eq!(hasher.finish(), expected);
+        }
+    }
+
+    #[test]
+    fn fxhash64_known_answers() {
+        let mut hasher = FxHasher64::default();
+        hasher.write(b"hello world");
+        assert_eq!(hasher.finish(), 0x824bd397ee3369c5);
+
+        let mut hasher = FxHasher64::default();
+        hasher.write(&DATA[..15]);
+        assert_eq!(hasher.finish(), 0x815796261ab79476);
+
+        let mut hasher = FxHasher64::default();
+        hasher.write_u32(42);
+        assert_eq!(hasher.finish(), 0x5e77c80c6b95bc72);
+
+        let mut hasher = FxHasher64::with_seed(7);
+        hasher.write_u64(0x0102030405060708);
+        assert_eq!(hasher.finish(), 0x0d3c0e628da1aa08);
+    }
+
+    #[test]
+    fn algorithm_ids() {
+        for algorithm in [
+            HashAlgorithm::Default,
+            HashAlgorithm::FxHash64,
+            HashAlgorithm::SipHash13,
+        ] {
+            assert_eq!(HashAlgorithm::from_id(algorithm.id()), Some(algorithm));
+        }
+        assert_eq!(HashAlgorithm::from_id(3), None);
+    }
+}
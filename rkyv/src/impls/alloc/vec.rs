@@ -7,26 +7,31 @@ use ::std::alloc;
 use rancor::{Fallible, ResultExt as _, Source};
 
 use crate::{
+    format::Format,
     ser::{Allocator, Writer},
     vec::{ArchivedVec, VecResolver},
     Archive, Deserialize, DeserializeUnsized, LayoutRaw, Place, Serialize,
 };
 
-impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for ArchivedVec<T> {
+impl<T: PartialEq<U>, U, F: Format> PartialEq<Vec<U>> for ArchivedVec<T, F> {
     #[inline]
     fn eq(&self, other: &Vec<U>) -> bool {
         self.as_slice().eq(other.as_slice())
     }
 }
 
-impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for Vec<T> {
+impl<T: PartialEq<U>, U, F: Format> PartialEq<ArchivedVec<U, F>> for Vec<T> {
     #[inline]
-    fn eq(&self, other: &ArchivedVec<U>) -> bool {
+    fn eq(&self, other: &ArchivedVec<U, F>) -> bool {
         self.as_slice().eq(other.as_slice())
     }
 }
 
-impl<T: PartialOrd<U>, U> PartialOrd<Vec<U>> for ArchivedVec<T> {
+impl<T, U, F> PartialOrd<Vec<U>> for ArchivedVec<T, F>
+where
+    T: PartialOrd<U>,
+    F: Format,
+{
     #[inline]
     fn partial_cmp(&self, other: &Vec<U>) -> Option<cmp::Ordering> {
         let min_len = self.len().min(other.len());
@@ -40,9 +45,9 @@ impl<T: PartialOrd<U>, U> PartialOrd<Vec<U>> for ArchivedVec<T> {
     }
 }
 
-impl<T: PartialOrd> PartialOrd<ArchivedVec<T>> for Vec<T> {
+impl<T: PartialOrd, F: Format> PartialOrd<ArchivedVec<T, F>> for Vec<T> {
     #[inline]
-    fn partial_cmp(&self, other: &ArchivedVec<T>) -> Option<cmp::Ordering> {
+    fn partial_cmp(&self, other: &ArchivedVec<T, F>) -> Option<cmp::Ordering> {
         self.as_slice().partial_cmp(other.as_slice())
     }
 }
@@ -72,9 +77,10 @@ impl<T: Serialize<S>, S: Fallible + Allocator + Writer + ?Sized> Serialize<S>
     }
 }
 
-impl<T, D> Deserialize<Vec<T>, D> for ArchivedVec<T::Archived>
+impl<T, F, D> Deserialize<Vec<T>, D> for ArchivedVec<T::Archived, F>
 where
     T: Archive,
+    F: Format,
     [T::Archived]: DeserializeUnsized<[T], D>,
     D: Fallible + ?Sized,
     D::Error: Source,
@@ -1,11 +1,23 @@
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
 use bytes::{Bytes, BytesMut};
 use rancor::Fallible;
 
 use crate::{
+    de::bytes::BackingBytes,
     ser::{Allocator, Writer},
     vec::{ArchivedVec, VecResolver},
+    with::{ArchiveWith, DeserializeWith, SerializeWith, ShareBytes},
     Archive, Archived, Deserialize, Place, Serialize,
 };
+#[cfg(feature = "alloc")]
+use crate::{
+    string::{ArchivedString, StringResolver},
+    SerializeUnsized,
+};
 
 impl Archive for Bytes {
     type Archived = ArchivedVec<u8>;
@@ -45,6 +57,92 @@ where
     }
 }
 
+// ShareBytes
+
+impl ArchiveWith<Bytes> for ShareBytes {
+    type Archived = ArchivedVec<u8>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &Bytes,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<S> SerializeWith<Bytes, S> for ShareBytes
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &Bytes,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<D> DeserializeWith<ArchivedVec<u8>, Bytes, D> for ShareBytes
+where
+    D: Fallible + BackingBytes + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedVec<u8>,
+        deserializer: &mut D,
+    ) -> Result<Bytes, D::Error> {
+        Ok(deserializer.share_bytes(field.as_slice()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ArchiveWith<Arc<str>> for ShareBytes {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &Arc<str>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedString::resolve_from_str(field, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S> SerializeWith<Arc<str>, S> for ShareBytes
+where
+    S: Fallible + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    #[inline]
+    fn serialize_with(
+        field: &Arc<str>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(field, serializer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> DeserializeWith<ArchivedString, Arc<str>, D> for ShareBytes
+where
+    D: Fallible + BackingBytes + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedString,
+        deserializer: &mut D,
+    ) -> Result<Arc<str>, D::Error> {
+        Ok(deserializer.share_str(field.as_str()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(not(feature = "std"))]
@@ -53,7 +151,10 @@ mod tests {
     use bytes::Bytes;
     use rancor::{Error, Infallible};
 
-    use crate::{access_unchecked, deserialize, to_bytes, vec::ArchivedVec};
+    use crate::{
+        access_unchecked, de::bytes::BytesBacked, deserialize, to_bytes,
+        vec::ArchivedVec, with::ShareBytes, Archive, Deserialize, Serialize,
+    };
 
     #[test]
     fn bytes() {
@@ -67,4 +168,76 @@ mod tests {
             deserialize::<Bytes, _, Infallible>(archived, &mut ()).unwrap();
         assert_eq!(value, deserialized);
     }
+
+    #[test]
+    fn share_bytes() {
+        #[derive(Archive, Deserialize, Serialize)]
+        #[archive(crate)]
+        struct Example {
+            #[with(ShareBytes)]
+            payload: Bytes,
+        }
+
+        let value = Example {
+            payload: Bytes::from(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        };
+        let buffer = Bytes::from_owner(to_bytes::<Error>(&value).unwrap());
+        let archived = unsafe { access_unchecked::<ArchivedExample>(&buffer) };
+
+        let mut deserializer = BytesBacked::new(buffer.clone(), ());
+        let deserialized =
+            deserialize::<Example, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert_eq!(deserialized.payload, value.payload);
+        assert!(buffer
+            .as_ptr_range()
+            .contains(&deserialized.payload.as_ptr()));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn share_arc_str() {
+        #[cfg(not(feature = "std"))]
+        use alloc::sync::Arc;
+        #[cfg(feature = "std")]
+        use std::sync::Arc;
+
+        #[derive(Archive, Deserialize, Serialize)]
+        #[archive(crate)]
+        struct Example {
+            #[with(ShareBytes)]
+            short: Arc<str>,
+            #[with(ShareBytes)]
+            long: Arc<str>,
+        }
+
+        let value = Example {
+            short: Arc::from("hi"),
+            long: Arc::from("a string too long to be inlined"),
+        };
+        let buffer = Bytes::from_owner(to_bytes::<Error>(&value).unwrap());
+        let archived = unsafe { access_unchecked::<ArchivedExample>(&buffer) };
+
+        let mut deserializer = BytesBacked::new(buffer.clone(), ());
+        let first =
+            deserialize::<Example, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert_eq!(first.short, value.short);
+        assert_eq!(first.long, value.long);
+
+        // Deserializing the same strings again reuses the first allocations
+        let second =
+            deserialize::<Example, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert!(Arc::ptr_eq(&first.short, &second.short));
+        assert!(Arc::ptr_eq(&first.long, &second.long));
+
+        // Without a backing buffer, every deserialization copies
+        let mut deserializer = BytesBacked::new(Bytes::new(), ());
+        let third =
+            deserialize::<Example, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert_eq!(third.long, value.long);
+        assert!(!Arc::ptr_eq(&first.long, &third.long));
+    }
 }
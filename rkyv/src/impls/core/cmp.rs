@@ -0,0 +1,49 @@
+use core::cmp::Reverse;
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{cmp::ArchivedReverse, Archive, Deserialize, Place, Serialize};
+
+impl<T: Archive> Archive for Reverse<T> {
+    type Archived = ArchivedReverse<T::Archived>;
+    type Resolver = T::Resolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedReverse(out_value) = out);
+        self.0.resolve(resolver, out_value);
+    }
+}
+
+impl<T: Serialize<S>, S: Fallible + ?Sized> Serialize<S> for Reverse<T> {
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T, D> Deserialize<Reverse<T>, D> for ArchivedReverse<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<Reverse<T>, D::Error> {
+        Ok(Reverse(self.0.deserialize(deserializer)?))
+    }
+}
+
+impl<T, U: PartialEq<T>> PartialEq<Reverse<T>> for ArchivedReverse<U> {
+    #[inline]
+    fn eq(&self, other: &Reverse<T>) -> bool {
+        self.0.eq(&other.0)
+    }
+}
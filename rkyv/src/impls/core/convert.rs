@@ -0,0 +1,65 @@
+use core::convert::Infallible;
+
+use rancor::Fallible;
+
+use crate::{
+    convert::ArchivedInfallible, Archive, Deserialize, Place, Serialize,
+};
+
+impl Archive for Infallible {
+    type Archived = ArchivedInfallible;
+    type Resolver = Infallible;
+
+    #[inline]
+    fn resolve(&self, _: Self::Resolver, _: Place<Self::Archived>) {
+        match *self {}
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for Infallible {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        match *self {}
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<Infallible, D> for ArchivedInfallible {
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<Infallible, D::Error> {
+        match *self {}
+    }
+}
+
+impl PartialEq<Infallible> for ArchivedInfallible {
+    #[inline]
+    fn eq(&self, _: &Infallible) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl Archive for ! {
+    type Archived = ArchivedInfallible;
+    type Resolver = !;
+
+    #[inline]
+    fn resolve(&self, _: Self::Resolver, _: Place<Self::Archived>) {
+        *self
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<S: Fallible + ?Sized> Serialize<S> for ! {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        *self
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<D: Fallible + ?Sized> Deserialize<!, D> for ArchivedInfallible {
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<!, D::Error> {
+        match *self {}
+    }
+}
@@ -13,11 +13,14 @@ use crate::{
     primitive::ArchivedUsize,
     ser::{Allocator, Writer, WriterExt as _},
     tuple::*,
-    Archive, ArchivePointee, ArchiveUnsized, ArchivedMetadata,
+    Archive, ArchivePointee, ArchiveUnsized, ArchivedMetadata, BulkCopy,
     CopyOptimization, Deserialize, DeserializeUnsized, LayoutRaw, Place,
     Portable, Serialize, SerializeUnsized,
 };
 
+mod cmp;
+mod convert;
+mod num;
 mod ops;
 mod option;
 mod primitive;
@@ -214,6 +217,20 @@ impl<T: Archive, const N: usize> Archive for [T; N] {
 
     #[inline]
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        if T::COPY_OPTIMIZATION.is_enabled() {
+            // SAFETY: The copy optimization is only enabled when the bytes of
+            // each `T` are the bytes of its archived form, so the whole array
+            // can be copied at once.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.as_ptr().cast::<u8>(),
+                    out.ptr().cast::<u8>(),
+                    core::mem::size_of::<Self>(),
+                );
+            }
+            return;
+        }
+
         for (i, (value, resolver)) in self.iter().zip(resolver).enumerate() {
             let out_i = unsafe { out.index(i) };
             value.resolve(resolver, out_i);
@@ -221,6 +238,10 @@ impl<T: Archive, const N: usize> Archive for [T; N] {
     }
 }
 
+// SAFETY: Arrays have no padding between their elements, so an array of
+// `BulkCopy` elements has the same bytes as its archived form.
+unsafe impl<T: BulkCopy, const N: usize> BulkCopy for [T; N] {}
+
 impl<T, S, const N: usize> Serialize<S> for [T; N]
 where
     T: Serialize<S>,
@@ -287,7 +308,8 @@ where
 {
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
         if T::COPY_OPTIMIZATION.is_enabled() {
-            let result = serializer.align_for::<T::Archived>()?;
+            let result =
+                serializer.align_for_layout(Layout::for_value(self))?;
             let as_bytes = unsafe {
                 core::slice::from_raw_parts(
                     self.as_ptr().cast::<u8>(),
@@ -308,7 +330,9 @@ where
                         resolvers.push(value.serialize(serializer)?);
                     }
 
-                    let result = serializer.align_for::<T::Archived>()?;
+                    let layout = Layout::array::<T::Archived>(self.len())
+                        .unwrap_or(Layout::new::<T::Archived>());
+                    let result = serializer.align_for_layout(layout)?;
 
                     for (value, resolver) in
                         self.iter().zip(resolvers.drain(..))
@@ -387,7 +411,8 @@ impl ArchivePointee for str {
 impl<S: Fallible + Writer + ?Sized> SerializeUnsized<S> for str {
     #[inline]
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
-        let result = serializer.pos();
+        let result =
+            serializer.align_for_layout(Layout::for_value(self.as_bytes()))?;
         serializer.write(self.as_bytes())?;
         Ok(result)
     }
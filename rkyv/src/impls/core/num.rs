@@ -0,0 +1,49 @@
+use core::num::Saturating;
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{num::ArchivedSaturating, Archive, Deserialize, Place, Serialize};
+
+impl<T: Archive> Archive for Saturating<T> {
+    type Archived = ArchivedSaturating<T::Archived>;
+    type Resolver = T::Resolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedSaturating(out_value) = out);
+        self.0.resolve(resolver, out_value);
+    }
+}
+
+impl<T: Serialize<S>, S: Fallible + ?Sized> Serialize<S> for Saturating<T> {
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T, D> Deserialize<Saturating<T>, D> for ArchivedSaturating<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<Saturating<T>, D::Error> {
+        Ok(Saturating(self.0.deserialize(deserializer)?))
+    }
+}
+
+impl<T, U: PartialEq<T>> PartialEq<Saturating<T>> for ArchivedSaturating<U> {
+    #[inline]
+    fn eq(&self, other: &Saturating<T>) -> bool {
+        self.0.eq(&other.0)
+    }
+}
@@ -19,7 +19,8 @@ use crate::{
         ArchivedNonZeroU32, ArchivedNonZeroU64, ArchivedNonZeroUsize,
         ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64, ArchivedUsize,
     },
-    Archive, CopyOptimization, Deserialize, Place, Portable, Serialize,
+    Archive, BulkCopy, CopyOptimization, Deserialize, NativeLayout, Place,
+    Portable, Serialize,
 };
 
 macro_rules! unsafe_impl_initialized_and_portable {
@@ -109,6 +110,9 @@ macro_rules! impl_archive_self_primitive {
 
         impl_serialize_noop!($type);
 
+        // SAFETY: `$type` is its own archived type and has no padding.
+        unsafe impl BulkCopy for $type {}
+
         impl<D: Fallible + ?Sized> Deserialize<$type, D> for $type {
             #[inline]
             fn deserialize(&self, _: &mut D) -> Result<$type, D::Error> {
@@ -166,6 +170,14 @@ macro_rules! impl_multibyte_primitive {
 
         impl_serialize_noop!($type);
 
+        // SAFETY: Multibyte primitives are archived with the same bytes when
+        // the archived endianness matches the target endianness.
+        #[cfg(any(
+            all(not(feature = "big_endian"), target_endian = "little"),
+            all(feature = "big_endian", target_endian = "big"),
+        ))]
+        unsafe impl BulkCopy for $type {}
+
         impl<D: Fallible + ?Sized> Deserialize<$type, D> for $archived {
             #[inline]
             fn deserialize(&self, _: &mut D) -> Result<$type, D::Error> {
@@ -205,6 +217,39 @@ impl_multibyte_primitives! {
     ArchivedNonZeroU128: NonZeroU128,
 }
 
+macro_rules! impl_native_layout_self {
+    ($($type:ty),* $(,)?) => {
+        $(
+            // SAFETY: `$type` is its own native type.
+            unsafe impl NativeLayout for $type {
+                type Native = $type;
+
+                const LAYOUT_MATCHES: bool = true;
+
+                #[inline]
+                fn to_native(&self) -> $type {
+                    *self
+                }
+            }
+        )*
+    };
+}
+
+impl_native_layout_self!((), bool, i8, u8, NonZeroI8, NonZeroU8);
+
+// SAFETY: Arrays of `T` have the same layout as arrays of `T::Native` if `T`
+// has the same layout as `T::Native`.
+unsafe impl<T: NativeLayout, const N: usize> NativeLayout for [T; N] {
+    type Native = [T::Native; N];
+
+    const LAYOUT_MATCHES: bool = T::LAYOUT_MATCHES;
+
+    #[inline]
+    fn to_native(&self) -> Self::Native {
+        core::array::from_fn(|i| self[i].to_native())
+    }
+}
+
 // PhantomData
 
 unsafe impl<T: ?Sized> Portable for PhantomData<T> {}
@@ -220,6 +265,9 @@ impl<T: ?Sized> Archive for PhantomData<T> {
     fn resolve(&self, _: Self::Resolver, _: Place<Self::Archived>) {}
 }
 
+// SAFETY: `PhantomData` has no bytes.
+unsafe impl<T: ?Sized> BulkCopy for PhantomData<T> {}
+
 impl<T: ?Sized, S: Fallible + ?Sized> Serialize<S> for PhantomData<T> {
     #[inline]
     fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
@@ -251,6 +299,9 @@ impl Archive for PhantomPinned {
     fn resolve(&self, _: Self::Resolver, _: Place<Self::Archived>) {}
 }
 
+// SAFETY: `PhantomPinned` has no bytes.
+unsafe impl BulkCopy for PhantomPinned {}
+
 impl<S: Fallible + ?Sized> Serialize<S> for PhantomPinned {
     #[inline]
     fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
@@ -24,6 +24,10 @@ mod bytes;
 mod hashbrown;
 #[cfg(feature = "indexmap")]
 mod indexmap;
+#[cfg(feature = "ndarray")]
+mod ndarray;
+#[cfg(feature = "roaring")]
+mod roaring;
 #[cfg(feature = "smallvec")]
 mod smallvec;
 #[cfg(feature = "smol_str")]
@@ -0,0 +1,169 @@
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use ndarray::{Array, ArrayViewD, Dimension, IxDyn, ShapeBuilder, ShapeError};
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    ser::{Allocator, Writer},
+    tensor::{row_major_strides, ArchivedNdArray, NdArrayResolver},
+    vec::ArchivedVec,
+    Archive, Deserialize, Place, Serialize,
+};
+
+impl<A: Archive, D: Dimension> Archive for Array<A, D> {
+    type Archived = ArchivedNdArray<A::Archived>;
+    type Resolver = NdArrayResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge::munge!(let ArchivedNdArray { shape, strides, data } = out);
+        ArchivedVec::resolve_from_len(self.ndim(), resolver.shape, shape);
+        ArchivedVec::resolve_from_len(self.ndim(), resolver.strides, strides);
+        ArchivedVec::resolve_from_len(self.len(), resolver.data, data);
+    }
+}
+
+impl<A, D, S> Serialize<S> for Array<A, D>
+where
+    A: Serialize<S>,
+    D: Dimension,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        // Elements are always archived in row-major order, regardless of the
+        // memory layout of the array.
+        let strides = row_major_strides(self.shape());
+        Ok(NdArrayResolver {
+            shape: ArchivedVec::serialize_from_slice(self.shape(), serializer)?,
+            strides: ArchivedVec::serialize_from_slice(&strides, serializer)?,
+            data: match self.as_slice() {
+                Some(slice) => {
+                    ArchivedVec::serialize_from_slice(slice, serializer)?
+                }
+                None => ArchivedVec::serialize_from_iter::<A, _, _>(
+                    self.iter(),
+                    serializer,
+                )?,
+            },
+        })
+    }
+}
+
+#[derive(Debug)]
+enum ArrayError {
+    Dimensions { expected: usize, actual: usize },
+    Shape(ShapeError),
+}
+
+impl fmt::Display for ArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dimensions { expected, actual } => write!(
+                f,
+                "expected an array with {} dimensions but found {}",
+                expected, actual,
+            ),
+            Self::Shape(e) => write!(f, "invalid array shape: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArrayError {}
+
+impl<A, D, De> Deserialize<Array<A, D>, De> for ArchivedNdArray<A::Archived>
+where
+    A: Archive,
+    A::Archived: Deserialize<A, De>,
+    D: Dimension,
+    De: Fallible + ?Sized,
+    De::Error: Source,
+{
+    fn deserialize(
+        &self,
+        deserializer: &mut De,
+    ) -> Result<Array<A, D>, De::Error> {
+        let shape = self
+            .shape()
+            .iter()
+            .map(|d| d.to_native() as usize)
+            .collect::<Vec<_>>();
+        let dim = match D::from_dimension(&IxDyn(&shape)) {
+            Some(dim) => dim,
+            None => fail!(ArrayError::Dimensions {
+                expected: D::NDIM.unwrap_or(shape.len()),
+                actual: shape.len(),
+            }),
+        };
+
+        let mut data = Vec::with_capacity(self.len());
+        for value in self.iter() {
+            data.push(value.deserialize(deserializer)?);
+        }
+        match Array::from_shape_vec(dim, data) {
+            Ok(array) => Ok(array),
+            Err(e) => fail!(ArrayError::Shape(e)),
+        }
+    }
+}
+
+impl<T> ArchivedNdArray<T> {
+    /// Returns a view of the archived array as an `ndarray` array.
+    ///
+    /// Returns an error if the shape and strides of the array are not
+    /// compatible with its elements.
+    pub fn view(&self) -> Result<ArrayViewD<'_, T>, ShapeError> {
+        let shape = self
+            .shape()
+            .iter()
+            .map(|d| d.to_native() as usize)
+            .collect::<Vec<_>>();
+        let strides = self
+            .strides()
+            .iter()
+            .map(|s| s.to_native() as usize)
+            .collect::<Vec<_>>();
+        let shape = IxDyn(&shape).strides(IxDyn(&strides));
+        ArrayViewD::from_shape(shape, self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2, Array3, ArrayD};
+    use rancor::Error;
+
+    use crate::{access_unchecked, deserialize, to_bytes, Archived};
+
+    #[test]
+    fn ndarray() {
+        let value: Array2<u32> = array![[1, 2, 3], [4, 5, 6]];
+        // A transposed array is not in standard layout.
+        let transposed = value.t().to_owned();
+
+        for value in [value, transposed] {
+            let bytes = to_bytes::<Error>(&value).unwrap();
+            let archived =
+                unsafe { access_unchecked::<Archived<Array2<u32>>>(&bytes) };
+
+            let view = archived.view().unwrap();
+            assert_eq!(view.shape(), value.shape());
+            assert!(view.iter().zip(value.iter()).all(|(a, b)| a == b));
+
+            let deserialized =
+                deserialize::<Array2<u32>, _, Error>(archived, &mut ())
+                    .unwrap();
+            assert_eq!(deserialized, value);
+            let dynamic =
+                deserialize::<ArrayD<u32>, _, Error>(archived, &mut ())
+                    .unwrap();
+            assert_eq!(dynamic, value.into_dyn());
+            assert!(deserialize::<Array3<u32>, _, Error>(archived, &mut ())
+                .is_err());
+        }
+    }
+}
@@ -1,7 +1,16 @@
+use core::{
+    mem::{align_of, size_of},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64,
+    },
+};
+
 use rancor::Fallible;
 
 use crate::{
-    rend::*, Archive, CopyOptimization, Deserialize, Place, Serialize,
+    rend::*, Archive, BulkCopy, CopyOptimization, Deserialize, NativeLayout,
+    Place, Serialize,
 };
 
 macro_rules! impl_rend_primitive {
@@ -26,6 +35,9 @@ macro_rules! impl_rend_primitive {
             }
         }
 
+        // SAFETY: `$type` is its own archived type and has no padding.
+        unsafe impl BulkCopy for $type {}
+
         impl<D: Fallible + ?Sized> Deserialize<$type, D> for $type {
             #[inline]
             fn deserialize(&self, _: &mut D) -> Result<$type, D::Error> {
@@ -82,6 +94,75 @@ impl_rend_primitives!(
     NonZeroU128_be,
 );
 
+macro_rules! impl_native_layout {
+    ($endian:tt: $($type:ty => $native:ty),* $(,)?) => {
+        $(
+            // SAFETY: `LAYOUT_MATCHES` is only true when the target has the
+            // same endianness as `$type` and `$type` is the same size and at
+            // least as aligned as `$native`.
+            unsafe impl NativeLayout for $type {
+                type Native = $native;
+
+                const LAYOUT_MATCHES: bool = cfg!(target_endian = $endian)
+                    && size_of::<$type>() == size_of::<$native>()
+                    && align_of::<$type>() >= align_of::<$native>();
+
+                #[inline]
+                fn to_native(&self) -> $native {
+                    let value: $type = *self;
+                    value.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_native_layout!(
+    "little":
+    i16_le => i16,
+    i32_le => i32,
+    i64_le => i64,
+    i128_le => i128,
+    u16_le => u16,
+    u32_le => u32,
+    u64_le => u64,
+    u128_le => u128,
+    f32_le => f32,
+    f64_le => f64,
+    char_le => char,
+    NonZeroI16_le => NonZeroI16,
+    NonZeroI32_le => NonZeroI32,
+    NonZeroI64_le => NonZeroI64,
+    NonZeroI128_le => NonZeroI128,
+    NonZeroU16_le => NonZeroU16,
+    NonZeroU32_le => NonZeroU32,
+    NonZeroU64_le => NonZeroU64,
+    NonZeroU128_le => NonZeroU128,
+);
+
+impl_native_layout!(
+    "big":
+    i16_be => i16,
+    i32_be => i32,
+    i64_be => i64,
+    i128_be => i128,
+    u16_be => u16,
+    u32_be => u32,
+    u64_be => u64,
+    u128_be => u128,
+    f32_be => f32,
+    f64_be => f64,
+    char_be => char,
+    NonZeroI16_be => NonZeroI16,
+    NonZeroI32_be => NonZeroI32,
+    NonZeroI64_be => NonZeroI64,
+    NonZeroI128_be => NonZeroI128,
+    NonZeroU16_be => NonZeroU16,
+    NonZeroU32_be => NonZeroU32,
+    NonZeroU64_be => NonZeroU64,
+    NonZeroU128_be => NonZeroU128,
+);
+
 #[cfg(test)]
 mod tests {
     use core::fmt;
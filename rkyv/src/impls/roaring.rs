@@ -0,0 +1,220 @@
+use munge::munge;
+use rancor::Fallible;
+use roaring::RoaringBitmap;
+
+use crate::{
+    roaring::{
+        ArchivedContainer, ArchivedRoaringBitmap, RoaringBitmapResolver,
+        ARRAY_LIMIT, BITMAP_WORDS,
+    },
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Serialize,
+};
+
+// The values of a `RoaringBitmap` which share their upper 16 bits, in the
+// form they are archived in.
+struct Container {
+    key: u16,
+    len: u32,
+    rank: u64,
+    array: Vec<u16>,
+    bitmap: Vec<u64>,
+}
+
+impl Container {
+    fn new(key: u16, rank: u64, lows: Vec<u16>) -> Self {
+        let len = lows.len() as u32;
+        if lows.len() <= ARRAY_LIMIT {
+            Self {
+                key,
+                len,
+                rank,
+                array: lows,
+                bitmap: Vec::new(),
+            }
+        } else {
+            let mut bitmap = vec![0u64; BITMAP_WORDS];
+            for low in lows {
+                bitmap[low as usize / 64] |= 1 << (low % 64);
+            }
+            Self {
+                key,
+                len,
+                rank,
+                array: Vec::new(),
+                bitmap,
+            }
+        }
+    }
+}
+
+fn containers(bitmap: &RoaringBitmap) -> Vec<Container> {
+    let mut result = Vec::new();
+    let mut rank = 0;
+    let mut current: Option<(u16, Vec<u16>)> = None;
+    for value in bitmap {
+        let (key, low) = ((value >> 16) as u16, value as u16);
+        match &mut current {
+            Some((current_key, lows)) if *current_key == key => lows.push(low),
+            _ => {
+                if let Some((key, lows)) = current.take() {
+                    let len = lows.len() as u64;
+                    result.push(Container::new(key, rank, lows));
+                    rank += len;
+                }
+                current = Some((key, vec![low]));
+            }
+        }
+    }
+    if let Some((key, lows)) = current {
+        result.push(Container::new(key, rank, lows));
+    }
+    result
+}
+
+struct ContainerResolver {
+    array: VecResolver,
+    bitmap: VecResolver,
+}
+
+impl Archive for Container {
+    type Archived = ArchivedContainer;
+    type Resolver = ContainerResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(
+            let ArchivedContainer { key, len, rank, array, bitmap } = out
+        );
+        self.key.resolve((), key);
+        self.len.resolve((), len);
+        self.rank.resolve((), rank);
+        ArchivedVec::resolve_from_slice(&self.array, resolver.array, array);
+        ArchivedVec::resolve_from_slice(&self.bitmap, resolver.bitmap, bitmap);
+    }
+}
+
+impl<S> Serialize<S> for Container
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        Ok(ContainerResolver {
+            array: ArchivedVec::serialize_from_slice(&self.array, serializer)?,
+            bitmap: ArchivedVec::serialize_from_slice(
+                &self.bitmap,
+                serializer,
+            )?,
+        })
+    }
+}
+
+impl Archive for RoaringBitmap {
+    type Archived = ArchivedRoaringBitmap;
+    type Resolver = RoaringBitmapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedRoaringBitmap { containers } = out);
+        ArchivedVec::resolve_from_len(
+            resolver.len,
+            resolver.containers,
+            containers,
+        );
+    }
+}
+
+impl<S> Serialize<S> for RoaringBitmap
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let containers = containers(self);
+        Ok(RoaringBitmapResolver {
+            containers: ArchivedVec::serialize_from_slice(
+                &containers,
+                serializer,
+            )?,
+            len: containers.len(),
+        })
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<RoaringBitmap, D>
+    for ArchivedRoaringBitmap
+{
+    fn deserialize(&self, _: &mut D) -> Result<RoaringBitmap, D::Error> {
+        let mut result = RoaringBitmap::new();
+        result.extend(self.iter());
+        Ok(result)
+    }
+}
+
+impl PartialEq<RoaringBitmap> for ArchivedRoaringBitmap {
+    fn eq(&self, other: &RoaringBitmap) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rancor::{Error, Infallible};
+    use roaring::RoaringBitmap;
+
+    use crate::{
+        access_unchecked, deserialize, roaring::ArchivedRoaringBitmap, to_bytes,
+    };
+
+    #[test]
+    fn roaring_bitmap() {
+        // A sparse container, a dense container, and a single value.
+        let mut value = RoaringBitmap::new();
+        value.extend([1, 5, 9, 1000]);
+        value.extend((1 << 16)..(1 << 16) + 10_000);
+        value.insert(u32::MAX);
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<ArchivedRoaringBitmap>(&bytes) };
+
+        assert_eq!(archived, &value);
+        assert_eq!(archived.len(), value.len());
+        assert_eq!(archived.containers().len(), 3);
+        assert!(archived.contains(9));
+        assert!(!archived.contains(10));
+        assert!(archived.contains((1 << 16) + 5_000));
+        assert!(archived.contains(u32::MAX));
+        assert_eq!(archived.min(), value.min());
+        assert_eq!(archived.max(), value.max());
+        for probe in [0, 1, 8, 9, 1000, 70_000, 80_000, u32::MAX - 1, u32::MAX]
+        {
+            assert_eq!(archived.rank(probe), value.rank(probe));
+        }
+
+        let mut other = RoaringBitmap::new();
+        other.extend([5, 1000, 2000, (1 << 16) + 42, u32::MAX]);
+        let other_bytes = to_bytes::<Error>(&other).unwrap();
+        let other_archived =
+            unsafe { access_unchecked::<ArchivedRoaringBitmap>(&other_bytes) };
+
+        let expected = (&value & &other).iter().collect::<Vec<_>>();
+        assert_eq!(
+            archived.intersection(other_archived).collect::<Vec<_>>(),
+            expected,
+        );
+        assert_eq!(
+            other_archived.intersection_len(archived),
+            expected.len() as u64,
+        );
+
+        let deserialized =
+            deserialize::<RoaringBitmap, _, Infallible>(archived, &mut ())
+                .unwrap();
+        assert_eq!(deserialized, value);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{error::Error, io};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    error::{
+        ArchivedError, ArchivedErrorKind, ArchivedIoError, ErrorResolver,
+        RestoredError,
+    },
+    place::Initialized,
+    ser::{Allocator, Writer},
+    Archive, Deserialize, Place, Serialize,
+};
+
+// Box<dyn Error + Send + Sync>
+
+impl Archive for Box<dyn Error + Send + Sync> {
+    type Archived = ArchivedError;
+    type Resolver = ErrorResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedError::resolve_from_error(self.as_ref(), resolver, out);
+    }
+}
+
+impl<S> Serialize<S> for Box<dyn Error + Send + Sync>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedError::serialize_from_error(self.as_ref(), serializer)
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<Box<dyn Error + Send + Sync>, D>
+    for ArchivedError
+{
+    #[inline]
+    fn deserialize(
+        &self,
+        _: &mut D,
+    ) -> Result<Box<dyn Error + Send + Sync>, D::Error> {
+        Ok(Box::new(self.to_restored()))
+    }
+}
+
+// RestoredError
+
+impl Archive for RestoredError {
+    type Archived = ArchivedError;
+    type Resolver = ErrorResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedError::resolve_from_error(self, resolver, out);
+    }
+}
+
+impl<S> Serialize<S> for RestoredError
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedError::serialize_from_error(self, serializer)
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<RestoredError, D> for ArchivedError {
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<RestoredError, D::Error> {
+        Ok(self.to_restored())
+    }
+}
+
+// ErrorKind
+
+// SAFETY: `ArchivedErrorKind` is a fieldless `repr(u8)` enum and so is always
+// initialized.
+unsafe impl Initialized for ArchivedErrorKind {}
+
+impl Archive for io::ErrorKind {
+    type Archived = ArchivedErrorKind;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, _: Self::Resolver, out: Place<Self::Archived>) {
+        out.write(ArchivedErrorKind::from_kind(*self));
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for io::ErrorKind {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<io::ErrorKind, D> for ArchivedErrorKind {
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<io::ErrorKind, D::Error> {
+        Ok(self.to_kind())
+    }
+}
+
+impl PartialEq<io::ErrorKind> for ArchivedErrorKind {
+    #[inline]
+    fn eq(&self, other: &io::ErrorKind) -> bool {
+        *self == ArchivedErrorKind::from_kind(*other)
+    }
+}
+
+// io::Error
+
+impl Archive for io::Error {
+    type Archived = ArchivedIoError;
+    type Resolver = ErrorResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedIoError { kind, error } = out);
+        self.kind().resolve((), kind);
+        ArchivedError::resolve_from_error(self, resolver, error);
+    }
+}
+
+impl<S> Serialize<S> for io::Error
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedError::serialize_from_error(self, serializer)
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<io::Error, D> for ArchivedIoError {
+    #[inline]
+    fn deserialize(&self, _: &mut D) -> Result<io::Error, D::Error> {
+        Ok(io::Error::new(
+            self.kind.to_kind(),
+            self.error.to_restored(),
+        ))
+    }
+}
@@ -13,7 +13,7 @@ use rancor::{Fallible, ResultExt, Source};
 use crate::{
     ffi::{ArchivedCString, CStringResolver},
     primitive::ArchivedUsize,
-    ser::Writer,
+    ser::{Writer, WriterExt as _},
     Archive, ArchivePointee, ArchiveUnsized, ArchivedMetadata, Deserialize,
     DeserializeUnsized, LayoutRaw, Place, Portable, Serialize,
     SerializeUnsized,
@@ -55,8 +55,9 @@ impl ArchivePointee for CStr {
 impl<S: Fallible + Writer + ?Sized> SerializeUnsized<S> for CStr {
     #[inline]
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
-        let result = serializer.pos();
-        serializer.write(self.to_bytes_with_nul())?;
+        let bytes = self.to_bytes_with_nul();
+        let result = serializer.align_for_layout(Layout::for_value(bytes))?;
+        serializer.write(bytes)?;
         Ok(result)
     }
 }
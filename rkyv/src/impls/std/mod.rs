@@ -1,4 +1,5 @@
 mod collections;
+mod error;
 mod ffi;
 mod net;
 mod time;
@@ -1,6 +1,9 @@
-use std::time::Duration;
+use core::fmt;
+use std::time::{Duration, SystemTime, SystemTimeError};
 
-use crate::time::ArchivedDuration;
+use rancor::{fail, Fallible, Source};
+
+use crate::{time::ArchivedDuration, Archive, Deserialize, Place, Serialize};
 
 impl PartialEq<Duration> for ArchivedDuration {
     #[inline]
@@ -15,3 +18,61 @@ impl PartialEq<ArchivedDuration> for Duration {
         other.eq(self)
     }
 }
+
+// SystemTimeError
+
+impl Archive for SystemTimeError {
+    type Archived = ArchivedDuration;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        self.duration().resolve(resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for SystemTimeError {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct InvalidSystemTimeError {
+    duration: Duration,
+}
+
+impl fmt::Display for InvalidSystemTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a system time error can't be created with a duration of {:?}",
+            self.duration,
+        )
+    }
+}
+
+impl std::error::Error for InvalidSystemTimeError {}
+
+impl<D> Deserialize<SystemTimeError, D> for ArchivedDuration
+where
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, _: &mut D) -> Result<SystemTimeError, D::Error> {
+        // `SystemTimeError` can't be constructed directly, so one is made by
+        // measuring from a later time to an earlier one.
+        let duration = Duration::new(self.as_secs(), self.subsec_nanos());
+        let error =
+            SystemTime::UNIX_EPOCH
+                .checked_add(duration)
+                .and_then(|later| {
+                    SystemTime::UNIX_EPOCH.duration_since(later).err()
+                });
+        match error {
+            Some(error) if error.duration() == duration => Ok(error),
+            _ => fail!(InvalidSystemTimeError { duration }),
+        }
+    }
+}
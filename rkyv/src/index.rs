@@ -0,0 +1,207 @@
+//! Sorted secondary indexes which are serialized alongside collections.
+//!
+//! Finding the elements of an archived vector with a particular field value
+//! normally requires scanning the whole vector. A [`SortedIndex`] is built
+//! from the elements before serialization and archived next to them. It maps
+//! each key to the positions of the elements with that key, so the archived
+//! index can find them with a binary search.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked, index::SortedIndex, rancor::Error, to_bytes, Archive,
+//!     Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Record {
+//!     name: String,
+//!     score: u32,
+//! }
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Table {
+//!     records: Vec<Record>,
+//!     by_name: SortedIndex<String>,
+//! }
+//!
+//! let records = vec![
+//!     Record {
+//!         name: "bob".to_string(),
+//!         score: 3,
+//!     },
+//!     Record {
+//!         name: "alice".to_string(),
+//!         score: 7,
+//!     },
+//!     Record {
+//!         name: "bob".to_string(),
+//!         score: 5,
+//!     },
+//! ];
+//! let by_name = SortedIndex::build(&records, |r| r.name.clone());
+//! let table = Table { records, by_name };
+//!
+//! let bytes = to_bytes::<Error>(&table).unwrap();
+//! let archived = unsafe { access_unchecked::<ArchivedTable>(&bytes) };
+//!
+//! let scores = archived
+//!     .by_name
+//!     .get(&archived.records, "bob")
+//!     .map(|r| r.score.to_native())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(scores, [3, 5]);
+//! ```
+
+use core::{cmp::Ordering, ops::Range};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+
+use crate::{Archive, Deserialize, Serialize};
+
+/// A sorted index mapping keys to the positions of elements in a collection.
+///
+/// Positions with the same key are kept in ascending order. The archived
+/// index is searched by comparing archived keys, so the archived key type must
+/// be ordered the same way as the key type. This holds for the built-in
+/// integer and string types.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct SortedIndex<K> {
+    keys: Vec<K>,
+    positions: Vec<usize>,
+}
+
+impl<K: Ord> SortedIndex<K> {
+    /// Builds an index over the given elements using the key returned by
+    /// `key` for each element.
+    pub fn build<T>(items: &[T], mut key: impl FnMut(&T) -> K) -> Self {
+        let mut entries = items
+            .iter()
+            .enumerate()
+            .map(|(position, item)| (key(item), position))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let (keys, positions) = entries.into_iter().unzip();
+        Self { keys, positions }
+    }
+
+    /// Returns the number of entries in the index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the index has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the positions of the elements with the given key.
+    pub fn positions<Q>(&self, key: &Q) -> &[usize]
+    where
+        K: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let range = equal_range(&self.keys, |k| compare(k, key));
+        &self.positions[range]
+    }
+}
+
+impl<K: Archive> ArchivedSortedIndex<K> {
+    /// Returns the number of entries in the index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the index has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the positions of the elements for which `f` returns
+    /// `Ordering::Equal`.
+    ///
+    /// `f` compares an archived key to the key being searched for, as with
+    /// `slice::binary_search_by`.
+    pub fn positions_by<F>(&self, f: F) -> impl Iterator<Item = usize> + '_
+    where
+        F: FnMut(&K::Archived) -> Ordering,
+    {
+        let range = equal_range(&self.keys, f);
+        self.positions[range].iter().map(|p| p.to_native() as usize)
+    }
+
+    /// Returns the positions of the elements with the given key.
+    pub fn positions<'a, Q>(
+        &'a self,
+        key: &'a Q,
+    ) -> impl Iterator<Item = usize> + 'a
+    where
+        K::Archived: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        self.positions_by(move |k| compare(k, key))
+    }
+
+    /// Returns the elements of `items` for which `f` returns
+    /// `Ordering::Equal`.
+    ///
+    /// `items` must be the archived collection that the index was built from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position in the index is out of bounds of `items`.
+    pub fn get_by<'a, T, F>(
+        &'a self,
+        items: &'a [T],
+        f: F,
+    ) -> impl Iterator<Item = &'a T> + 'a
+    where
+        F: FnMut(&K::Archived) -> Ordering + 'a,
+    {
+        self.positions_by(f).map(move |p| &items[p])
+    }
+
+    /// Returns the elements of `items` with the given key.
+    ///
+    /// `items` must be the archived collection that the index was built from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position in the index is out of bounds of `items`.
+    pub fn get<'a, T, Q>(
+        &'a self,
+        items: &'a [T],
+        key: &'a Q,
+    ) -> impl Iterator<Item = &'a T> + 'a
+    where
+        K::Archived: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        self.get_by(items, move |k| compare(k, key))
+    }
+}
+
+// Keys which are incomparable with the search key sort before it, so they are
+// never considered equal.
+#[inline]
+fn compare<K: PartialOrd<Q>, Q: ?Sized>(k: &K, key: &Q) -> Ordering {
+    k.partial_cmp(key).unwrap_or(Ordering::Less)
+}
+
+fn equal_range<K>(
+    keys: &[K],
+    mut f: impl FnMut(&K) -> Ordering,
+) -> Range<usize> {
+    let start = keys.partition_point(|k| f(k) == Ordering::Less);
+    let len = keys[start..].partition_point(|k| f(k) == Ordering::Equal);
+    start..start + len
+}
@@ -75,8 +75,30 @@
 //! - `size_64`: Archives integral `*size` types as 64-bit integers. This is
 //!   intended to be used only for very large archives and may cause unnecessary
 //!   data bloat.
+//!
+//!   The endianness and size features select the
+//!   [`DefaultFormat`](format::DefaultFormat) of the crate. Types which take a
+//!   [`Format`](format::Format) parameter can be archived in other formats in
+//!   the same program; see [`format`](mod@format) for more information.
 //! - `std`: Enables standard library support. Enabled by default.
 //! - `bytecheck`: Enables validation support through `bytecheck`.
+//! - `fallible-alloc`: Makes the allocating serializer components return an
+//!   error instead of aborting when an allocation fails, and adds
+//!   [`FallibleWriter`](ser::writer::FallibleWriter) for `AlignedVec` and
+//!   `Vec<u8>` writers.
+//! - `static-errors`: Enables [`static_error`], an error type which is built
+//!   from static error codes and numeric context and never allocates.
+//!   Validation also traces the position of the value being validated.
+//! - `test-util`: Enables [`test_util`], which round-trips values through every
+//!   serializer, validation mode, and deserializer for testing.
+//! - `madvise`: Passes the memory advice from the helpers in `util` to the
+//!   operating system on Unix platforms. Without it, the advice helpers do
+//!   nothing.
+//! - `format-stability`: Checks at compile time that the archived layouts of
+//!   the built-in types match those frozen for the current format version. See
+//!   [`stability`] for more information.
+//! - `nightly`: Enables support for types which require a nightly compiler,
+//!   like the never type `!`.
 //!
 //! ## Crate support
 //!
@@ -89,9 +111,21 @@
 //! Crates supported by rkyv:
 //!
 //! - [`indexmap`](https://docs.rs/indexmap)
+//! - [`memmap2`](https://docs.rs/memmap2) *Read-only maps can back owned
+//!   archives, and `util::mmap` opens archive files with aligned maps.*
+//! - [`ndarray`](https://docs.rs/ndarray) *Archives arrays as
+//!   `tensor::ArchivedNdArray`s, which can be viewed as `ndarray` arrays.*
 //! - [`rend`](https://docs.rs/rend) *Enabled automatically when using
 //!   endian-specific archive features.*
+//! - [`roaring`](https://docs.rs/roaring) *Archives bitmaps in a form which can
+//!   be queried in place.*
+//! - [`serde`](https://docs.rs/serde) *Exports archived values to any serde
+//!   format with `export::Export`.*
+//! - [`serde_json`](https://docs.rs/serde_json) *Converts JSON values into
+//!   untyped `value::Value`s.*
 //! - [`tinyvec`](https://docs.rs/tinyvec)
+//! - [`tokio`](https://docs.rs/tokio) *Async writers can stream serialized
+//!   archives with `ser::stream::TokioWriter`.*
 //! - [`uuid`](https://docs.rs/uuid)
 //!
 //! Support for each of these crates can be enabled with a feature of the same
@@ -130,6 +164,7 @@
     13.512-13.512-2.702 2.703-2.702-8.107-8.107z"/%3E%3C/svg%3E
 "#)]
 #![cfg_attr(miri, feature(alloc_layout_extra))]
+#![cfg_attr(feature = "nightly", feature(never_type))]
 
 // Extern crates
 
@@ -145,50 +180,139 @@ pub use ::munge;
 pub use ::ptr_meta;
 pub use ::rancor;
 pub use ::rend;
-pub use ::rkyv_derive::{Archive, Deserialize, Portable, Serialize};
+pub use ::rkyv_derive::{
+    archive_naming, archived_module, Analyze, Archive, ArchivedSize,
+    Deserialize, Export, Extract, Portable, Redact, SchemaHash, Serialize,
+    StableHash,
+};
+#[cfg(feature = "serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+pub use ::serde;
 
 // Modules
 
 mod alias;
 #[macro_use]
 mod _macros;
+#[cfg(feature = "alloc")]
+pub mod analyze;
+pub mod backing;
 #[cfg(feature = "bitvec")]
 pub mod bitvec;
 pub mod boxed;
+pub mod builder;
+pub mod cmp;
 pub mod collections;
+pub mod convert;
+#[cfg(feature = "alloc")]
+pub mod cow;
 pub mod de;
+pub mod deref;
+#[doc(hidden)]
+pub mod derive;
+pub mod descriptor;
+pub mod digest;
+#[cfg(feature = "aead")]
+pub mod encrypted;
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod error;
+pub mod evolve;
+#[cfg(feature = "serde")]
+pub mod export;
+pub mod extract;
+#[cfg(feature = "alloc")]
+pub mod filter;
 mod fmt;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod fs;
 // This is pretty unfortunate. CStr doesn't rely on the rest of std, but it's
 // not in core. If CStr ever gets moved into `core` then this module will no
 // longer need cfg(feature = "std")
 #[cfg(feature = "std")]
 pub mod ffi;
+#[cfg(target_has_atomic = "32")]
+pub mod guarded;
 pub mod hash;
 mod impls;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "alloc")]
+pub mod merkle;
+pub mod nested;
 pub mod net;
 pub mod niche;
+pub mod num;
 pub mod ops;
 pub mod option;
+#[cfg(feature = "alloc")]
+pub mod pack;
 pub mod place;
 mod polyfill;
 pub mod primitive;
 pub mod rc;
+pub mod redact;
 pub mod rel_ptr;
 pub mod result;
+#[cfg(all(feature = "alloc", target_has_atomic = "32"))]
+pub mod ring;
+#[cfg(feature = "roaring")]
+pub mod roaring;
+pub mod schema;
+pub mod search;
+pub mod segment;
 pub mod ser;
 mod simd;
+pub mod size;
+pub mod stability;
+#[cfg(feature = "static-errors")]
+pub mod static_error;
+#[cfg(feature = "std")]
+pub mod store;
 pub mod string;
+#[cfg(feature = "alloc")]
+pub mod tensor;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod time;
 pub mod traits;
 pub mod tuple;
 pub mod util;
 #[cfg(feature = "bytecheck")]
 pub mod validation;
+#[cfg(feature = "alloc")]
+pub mod value;
 pub mod vec;
 pub mod with;
 
 // Exports
 
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+#[cfg_attr(
+    doc_cfg,
+    doc(cfg(all(feature = "alloc", feature = "bytecheck")))
+)]
+#[doc(inline)]
+pub use analyze::analyze;
+#[cfg(feature = "alloc")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+#[doc(inline)]
+pub use analyze::Analyze;
+#[cfg(feature = "serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+#[doc(inline)]
+pub use export::Export;
+#[cfg(feature = "alloc")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+#[doc(inline)]
+pub use extract::extract;
+#[cfg(feature = "bytecheck")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "bytecheck")))]
+#[doc(inline)]
+pub use redact::redact;
+#[doc(inline)]
+pub use redact::Redact;
 #[cfg(feature = "alloc")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
 #[doc(inline)]
@@ -208,7 +332,11 @@ pub use validation::util::{access, access_mut};
 #[doc(inline)]
 pub use crate::{
     alias::*,
+    digest::StableHash,
+    extract::{Extract, ExtractUnsized},
     place::Place,
+    schema::SchemaHash,
+    size::ArchivedSize,
     traits::*,
     util::{access_unchecked, access_unchecked_mut, deserialize, serialize},
 };
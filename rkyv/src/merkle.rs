@@ -0,0 +1,605 @@
+//! Chunked Merkle hashing of archives for content addressing.
+//!
+//! A [`MerkleWriter`] hashes the bytes written to it in fixed-size chunks as
+//! they are produced, so the digest tree of an archive is available as soon as
+//! serialization finishes without scanning the buffer again. The resulting
+//! [`MerkleTree`] can be stored alongside the archive and used to verify
+//! either the whole archive or individual chunks of it.
+//!
+//! The hash function is pluggable through the [`MerkleHasher`] trait.
+//! [`SipMerkleHasher`] is provided as a keyed default; systems which need
+//! collision resistance against adversaries should implement `MerkleHasher`
+//! with a cryptographic hash.
+
+use core::{alloc::Layout, fmt, ptr::NonNull};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+#[cfg(feature = "bytecheck")]
+use rancor::Strategy;
+use rancor::{fail, Source};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    hash::SipHasher13,
+    ser::{Allocator, Positional, Sharing, Writer},
+    Archive, Deserialize, Serialize,
+};
+
+/// A hash function used to build a [`MerkleTree`].
+///
+/// Leaf and node hashes must be domain-separated so that a leaf can never be
+/// mistaken for an interior node.
+pub trait MerkleHasher {
+    /// The digest produced by the hash function.
+    type Digest: Copy + Eq + fmt::Debug;
+
+    /// Hashes a chunk of archive bytes.
+    fn hash_leaf(&self, chunk: &[u8]) -> Self::Digest;
+
+    /// Hashes two child digests into their parent digest.
+    fn hash_node(
+        &self,
+        left: &Self::Digest,
+        right: &Self::Digest,
+    ) -> Self::Digest;
+}
+
+/// A [`MerkleHasher`] which uses keyed SipHash-1-3.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SipMerkleHasher {
+    k0: u64,
+    k1: u64,
+}
+
+impl SipMerkleHasher {
+    const LEAF_TAG: u8 = 0;
+    const NODE_TAG: u8 = 1;
+
+    /// Returns a new `SipMerkleHasher` keyed with the two given keys.
+    #[inline]
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self { k0, k1 }
+    }
+}
+
+impl MerkleHasher for SipMerkleHasher {
+    type Digest = u64;
+
+    #[inline]
+    fn hash_leaf(&self, chunk: &[u8]) -> u64 {
+        use core::hash::Hasher as _;
+
+        let mut hasher = SipHasher13::new_with_keys(self.k0, self.k1);
+        hasher.write_u8(Self::LEAF_TAG);
+        hasher.write(chunk);
+        hasher.finish()
+    }
+
+    #[inline]
+    fn hash_node(&self, left: &u64, right: &u64) -> u64 {
+        use core::hash::Hasher as _;
+
+        let mut hasher = SipHasher13::new_with_keys(self.k0, self.k1);
+        hasher.write_u8(Self::NODE_TAG);
+        hasher.write_u64(*left);
+        hasher.write_u64(*right);
+        hasher.finish()
+    }
+}
+
+/// A digest tree over the fixed-size chunks of an archive.
+///
+/// The leaves are the digests of the chunks in order. Interior nodes hash
+/// pairs of children, and an unpaired node at the end of a level is promoted
+/// to the next level unchanged. The root of an empty buffer is the digest of
+/// an empty leaf.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+#[cfg_attr(feature = "bytecheck", archive_attr(check_bytes(verify)))]
+pub struct MerkleTree<D> {
+    chunk_size: usize,
+    len: usize,
+    leaves: Vec<D>,
+    root: D,
+}
+
+impl<D: Copy + Eq + fmt::Debug> MerkleTree<D> {
+    /// Builds a digest tree from the chunk digests of a buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn from_leaves<H>(
+        chunk_size: usize,
+        len: usize,
+        leaves: Vec<D>,
+        hasher: &H,
+    ) -> Self
+    where
+        H: MerkleHasher<Digest = D> + ?Sized,
+    {
+        assert!(chunk_size > 0, "chunk size must be nonzero");
+
+        let root = compute_root(&leaves, hasher);
+        Self {
+            chunk_size,
+            len,
+            leaves,
+            root,
+        }
+    }
+
+    /// Hashes the given bytes in chunks and builds a digest tree from them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn from_bytes<H>(bytes: &[u8], chunk_size: usize, hasher: &H) -> Self
+    where
+        H: MerkleHasher<Digest = D> + ?Sized,
+    {
+        assert!(chunk_size > 0, "chunk size must be nonzero");
+
+        let leaves = bytes
+            .chunks(chunk_size)
+            .map(|chunk| hasher.hash_leaf(chunk))
+            .collect();
+        Self::from_leaves(chunk_size, bytes.len(), leaves, hasher)
+    }
+
+    /// Returns the size of the chunks that were hashed.
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the length of the hashed buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the hashed buffer was empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the digests of the chunks.
+    #[inline]
+    pub fn leaves(&self) -> &[D] {
+        &self.leaves
+    }
+
+    /// Returns the root digest of the tree.
+    #[inline]
+    pub fn root(&self) -> D {
+        self.root
+    }
+
+    /// Returns the byte range of the chunk at the given index.
+    ///
+    /// Returns `None` if the index is out of bounds or the chunk would start
+    /// past the end of the hashed buffer.
+    #[inline]
+    pub fn chunk_range(&self, index: usize) -> Option<core::ops::Range<usize>> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+
+        let start = index.checked_mul(self.chunk_size)?;
+        if start >= self.len {
+            return None;
+        }
+        Some(start..self.len.min(start.saturating_add(self.chunk_size)))
+    }
+
+    /// Verifies a single chunk against its digest.
+    ///
+    /// This allows a buffer which is transferred in pieces to be checked
+    /// before all of it has arrived.
+    pub fn verify_chunk<H, E>(
+        &self,
+        index: usize,
+        chunk: &[u8],
+        hasher: &H,
+    ) -> Result<(), E>
+    where
+        H: MerkleHasher<Digest = D> + ?Sized,
+        E: Source,
+    {
+        match self.chunk_range(index) {
+            Some(range) if range.len() == chunk.len() => {
+                if hasher.hash_leaf(chunk) == self.leaves[index] {
+                    Ok(())
+                } else {
+                    fail!(ChunkMismatch { index })
+                }
+            }
+            _ => fail!(ChunkBoundsMismatch {
+                index,
+                len: chunk.len(),
+            }),
+        }
+    }
+
+    /// Verifies a buffer against the tree.
+    ///
+    /// Every chunk is checked against its digest, and the root is recomputed
+    /// from the leaves so that a tampered tree is also detected. Trees whose
+    /// chunk size is zero or whose leaf count does not match their length are
+    /// rejected.
+    pub fn verify<H, E>(&self, bytes: &[u8], hasher: &H) -> Result<(), E>
+    where
+        H: MerkleHasher<Digest = D> + ?Sized,
+        E: Source,
+    {
+        check_shape(self.chunk_size, self.len, self.leaves.len())?;
+        if bytes.len() != self.len {
+            fail!(LengthMismatch {
+                expected: self.len,
+                found: bytes.len(),
+            });
+        }
+        for (index, chunk) in bytes.chunks(self.chunk_size).enumerate() {
+            self.verify_chunk(index, chunk, hasher)?;
+        }
+        if compute_root(&self.leaves, hasher) != self.root {
+            fail!(RootMismatch);
+        }
+        Ok(())
+    }
+}
+
+fn check_shape<E: Source>(
+    chunk_size: usize,
+    len: usize,
+    leaves: usize,
+) -> Result<(), E> {
+    if chunk_size == 0 || leaves != len.div_ceil(chunk_size) {
+        fail!(ShapeMismatch {
+            chunk_size,
+            len,
+            leaves,
+        });
+    }
+    Ok(())
+}
+
+fn compute_root<H>(leaves: &[H::Digest], hasher: &H) -> H::Digest
+where
+    H: MerkleHasher + ?Sized,
+{
+    if leaves.is_empty() {
+        return hasher.hash_leaf(&[]);
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity((level.len() + 1) / 2);
+        for pair in level.chunks(2) {
+            match pair {
+                [left, right] => next.push(hasher.hash_node(left, right)),
+                [single] => next.push(*single),
+                _ => unreachable!(),
+            }
+        }
+        level = next;
+    }
+    level[0]
+}
+
+/// Validates an archive against its digest tree and then accesses it.
+///
+/// This is [`access`](crate::access) preceded by
+/// [`MerkleTree::verify`].
+#[cfg(feature = "bytecheck")]
+pub fn access_verified<'a, T, H, E>(
+    bytes: &'a [u8],
+    tree: &MerkleTree<H::Digest>,
+    hasher: &H,
+) -> Result<&'a T, E>
+where
+    T: crate::Portable + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    H: MerkleHasher + ?Sized,
+    E: Source,
+{
+    tree.verify(bytes, hasher)?;
+    crate::access::<T, E>(bytes)
+}
+
+#[derive(Debug)]
+struct ChunkMismatch {
+    index: usize,
+}
+
+impl fmt::Display for ChunkMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "digest mismatch for chunk {}", self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkMismatch {}
+
+#[derive(Debug)]
+struct ChunkBoundsMismatch {
+    index: usize,
+    len: usize,
+}
+
+impl fmt::Display for ChunkBoundsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "chunk {} with length {} does not match the digest tree",
+            self.index, self.len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkBoundsMismatch {}
+
+#[derive(Debug)]
+struct LengthMismatch {
+    expected: usize,
+    found: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer length mismatch: expected {} bytes, found {}",
+            self.expected, self.found,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthMismatch {}
+
+#[derive(Debug)]
+struct ShapeMismatch {
+    chunk_size: usize,
+    len: usize,
+    leaves: usize,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "digest tree with chunk size {} cannot have {} leaves for {} bytes",
+            self.chunk_size, self.leaves, self.len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShapeMismatch {}
+
+#[derive(Debug)]
+struct RootMismatch;
+
+impl fmt::Display for RootMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "root digest does not match the chunk digests")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RootMismatch {}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+
+    use super::{check_shape, ArchivedMerkleTree};
+    use crate::Archive;
+
+    unsafe impl<D, C> Verify<C> for ArchivedMerkleTree<D>
+    where
+        D: Archive,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            check_shape(
+                self.chunk_size.to_native() as usize,
+                self.len.to_native() as usize,
+                self.leaves.len(),
+            )
+        }
+    }
+}
+
+/// A writer adapter which hashes its output in fixed-size chunks.
+///
+/// `MerkleWriter` wraps a writer or a complete serializer and forwards
+/// [`Allocator`] and [`Sharing`] to it. Chunk boundaries are relative to the
+/// position of the wrapped writer when the adapter was created.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     merkle::{access_verified, MerkleWriter, SipMerkleHasher},
+///     rancor::Error,
+///     ser::AllocSerializer,
+///     util::serialize,
+///     Archived,
+/// };
+///
+/// let value = (0..100u32).collect::<Vec<_>>();
+/// let hasher = SipMerkleHasher::new_with_keys(1, 2);
+///
+/// let mut writer = MerkleWriter::new(AllocSerializer::default(), hasher, 64);
+/// serialize::<_, Error>(&value, &mut writer).unwrap();
+/// let (serializer, tree) = writer.into_raw_parts();
+/// let bytes = serializer.into_writer();
+///
+/// assert_eq!(tree.leaves().len(), (bytes.len() + 63) / 64);
+/// let archived =
+///     access_verified::<Archived<Vec<u32>>, _, Error>(&bytes, &tree, &hasher)
+///         .unwrap();
+/// assert_eq!(archived, &value);
+/// ```
+#[derive(Debug)]
+pub struct MerkleWriter<W, H: MerkleHasher> {
+    inner: W,
+    hasher: H,
+    chunk_size: usize,
+    len: usize,
+    chunk: Vec<u8>,
+    leaves: Vec<H::Digest>,
+}
+
+impl<W, H: MerkleHasher> MerkleWriter<W, H> {
+    /// Wraps the given writer, hashing its output in chunks of `chunk_size`
+    /// bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    #[inline]
+    pub fn new(inner: W, hasher: H, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be nonzero");
+
+        Self {
+            inner,
+            hasher,
+            chunk_size,
+            len: 0,
+            chunk: Vec::with_capacity(chunk_size),
+            leaves: Vec::new(),
+        }
+    }
+
+    /// Returns the digests of the chunks completed so far.
+    #[inline]
+    pub fn leaves(&self) -> &[H::Digest] {
+        &self.leaves
+    }
+
+    /// Returns a reference to the wrapped writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped writer and the digest tree
+    /// of everything written to it.
+    pub fn into_raw_parts(mut self) -> (W, MerkleTree<H::Digest>) {
+        self.flush_chunk();
+        let tree = MerkleTree::from_leaves(
+            self.chunk_size,
+            self.len,
+            self.leaves,
+            &self.hasher,
+        );
+        (self.inner, tree)
+    }
+
+    fn flush_chunk(&mut self) {
+        if !self.chunk.is_empty() {
+            self.leaves.push(self.hasher.hash_leaf(&self.chunk));
+            self.chunk.clear();
+        }
+    }
+
+    fn hash_bytes(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len();
+
+        if !self.chunk.is_empty() {
+            let needed = self.chunk_size - self.chunk.len();
+            let fill = needed.min(bytes.len());
+            self.chunk.extend_from_slice(&bytes[..fill]);
+            bytes = &bytes[fill..];
+            if self.chunk.len() < self.chunk_size {
+                return;
+            }
+            self.flush_chunk();
+        }
+
+        let chunks = bytes.chunks_exact(self.chunk_size);
+        self.chunk.extend_from_slice(chunks.remainder());
+        for chunk in chunks {
+            self.leaves.push(self.hasher.hash_leaf(chunk));
+        }
+    }
+}
+
+impl<W: Positional, H: MerkleHasher> Positional for MerkleWriter<W, H> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, H: MerkleHasher, E> Writer<E> for MerkleWriter<W, H> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)?;
+        self.hash_bytes(bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.flush_chunk();
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.inner.position_for(layout)
+    }
+}
+
+impl<W: Allocator<E>, H: MerkleHasher, E> Allocator<E> for MerkleWriter<W, H> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+impl<W: Sharing<E>, H: MerkleHasher, E> Sharing<E> for MerkleWriter<W, H> {
+    #[inline]
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+}
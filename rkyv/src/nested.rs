@@ -0,0 +1,116 @@
+//! Archives which are nested inside of other archives.
+
+use core::{fmt, marker::PhantomData};
+
+use munge::munge;
+use rancor::Fallible;
+#[cfg(feature = "bytecheck")]
+use rancor::{Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    ser::{Writer, WriterExt as _},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Place, Portable,
+};
+
+/// An independent archive stored inside of another archive.
+///
+/// The nested archive has its own root position and is validated separately
+/// from the archive it is contained in. This is useful for container formats
+/// where inner archives are produced by different writers, or where the inner
+/// archive should only be validated when it is accessed.
+///
+/// Use the [`Nested`](crate::with::Nested) wrapper to archive a field as a
+/// nested archive.
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+pub struct ArchivedNested<T> {
+    bytes: ArchivedVec<u8>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ArchivedNested<T> {
+    /// The alignment that nested archives are written with.
+    pub const ALIGNMENT: usize = 16;
+
+    /// Returns the bytes of the nested archive.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Resolves an archived nested archive from a resolver.
+    #[inline]
+    pub fn resolve_from_resolver(resolver: NestedResolver, out: Place<Self>) {
+        munge!(let ArchivedNested { bytes, _phantom: _ } = out);
+        ArchivedVec::resolve_from_len(
+            resolver.len,
+            VecResolver::from_pos(resolver.pos),
+            bytes,
+        );
+    }
+
+    /// Writes the bytes of an independent archive to the serializer as a nested
+    /// archive.
+    ///
+    /// The bytes must have been written starting at an address aligned to
+    /// [`ALIGNMENT`](Self::ALIGNMENT).
+    pub fn serialize_from_bytes<S>(
+        bytes: &[u8],
+        serializer: &mut S,
+    ) -> Result<NestedResolver, S::Error>
+    where
+        S: Fallible + Writer + ?Sized,
+    {
+        let pos = serializer.align(Self::ALIGNMENT)?;
+        serializer.write(bytes)?;
+        Ok(NestedResolver {
+            pos,
+            len: bytes.len(),
+        })
+    }
+}
+
+impl<T: Archive> ArchivedNested<T> {
+    /// Returns the root of the nested archive without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of the nested archive must contain a valid `T::Archived` at
+    /// the default root position.
+    #[inline]
+    pub unsafe fn access_inner_unchecked(&self) -> &T::Archived {
+        // SAFETY: The caller has guaranteed that the nested bytes contain a
+        // valid archived `T` at the root position.
+        unsafe { crate::access_unchecked::<T::Archived>(self.as_bytes()) }
+    }
+
+    /// Validates the nested archive and returns its root.
+    #[cfg(feature = "bytecheck")]
+    #[inline]
+    pub fn access_inner<E>(&self) -> Result<&T::Archived, E>
+    where
+        T::Archived: bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        crate::access::<T::Archived, E>(self.as_bytes())
+    }
+}
+
+impl<T> fmt::Debug for ArchivedNested<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedNested")
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+/// The resolver for [`ArchivedNested`].
+pub struct NestedResolver {
+    pos: usize,
+    len: usize,
+}
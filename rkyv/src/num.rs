@@ -0,0 +1,28 @@
+//! Archived versions of `num` types.
+
+use core::fmt;
+
+use crate::Portable;
+
+/// An archived [`Saturating`](::core::num::Saturating).
+#[derive(
+    Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Portable,
+)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(transparent)]
+#[archive(crate)]
+pub struct ArchivedSaturating<T>(pub T);
+
+impl<T: fmt::Debug> fmt::Debug for ArchivedSaturating<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for ArchivedSaturating<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
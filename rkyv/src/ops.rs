@@ -280,3 +280,49 @@ impl<T> ArchivedBound<T> {
         }
     }
 }
+
+/// An archived [`ControlFlow`](::core::ops::ControlFlow).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Portable)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(u8)]
+#[archive(crate)]
+pub enum ArchivedControlFlow<B, C> {
+    /// Move on to the next phase of the operation as normal.
+    Continue(C),
+    /// Exit the operation without running subsequent phases.
+    Break(B),
+}
+
+impl<B, C> ArchivedControlFlow<B, C> {
+    /// Returns `true` if this is a `Break` variant.
+    #[inline]
+    pub fn is_break(&self) -> bool {
+        matches!(self, ArchivedControlFlow::Break(_))
+    }
+
+    /// Returns `true` if this is a `Continue` variant.
+    #[inline]
+    pub fn is_continue(&self) -> bool {
+        matches!(self, ArchivedControlFlow::Continue(_))
+    }
+
+    /// Returns the value in the `Break` variant, or `None` if this is a
+    /// `Continue` variant.
+    #[inline]
+    pub fn break_value(&self) -> Option<&B> {
+        match self {
+            ArchivedControlFlow::Continue(_) => None,
+            ArchivedControlFlow::Break(value) => Some(value),
+        }
+    }
+
+    /// Returns the value in the `Continue` variant, or `None` if this is a
+    /// `Break` variant.
+    #[inline]
+    pub fn continue_value(&self) -> Option<&C> {
+        match self {
+            ArchivedControlFlow::Continue(value) => Some(value),
+            ArchivedControlFlow::Break(_) => None,
+        }
+    }
+}
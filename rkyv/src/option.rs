@@ -6,13 +6,16 @@ use core::{
     pin::Pin,
 };
 
-use crate::Portable;
+#[cfg(feature = "serde")]
+use crate::export::{Export, Serde};
+use crate::{redact::Redact, ArchivedSize, Extract, Portable, SchemaHash};
 
 /// An archived [`Option`].
 ///
 /// It functions identically to [`Option`] but has a different internal
 /// representation to allow for archiving.
-#[derive(Clone, Copy, Debug, Portable)]
+#[derive(ArchivedSize, Clone, Copy, Debug, Extract, Portable, SchemaHash)]
+#[cfg_attr(feature = "alloc", derive(crate::Analyze))]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
 #[repr(u8)]
 #[archive(crate)]
@@ -166,6 +169,41 @@ impl<T: DerefMut> ArchivedOption<T> {
     }
 }
 
+impl<T: Redact> Redact for ArchivedOption<T> {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        if let Some(value) = self.as_pin_mut() {
+            value.redact();
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Export> Export for ArchivedOption<T> {
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            ArchivedOption::None => serializer.serialize_none(),
+            ArchivedOption::Some(value) => {
+                serializer.serialize_some(&Serde(value))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Export> serde::Serialize for ArchivedOption<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
 impl<T: Eq> Eq for ArchivedOption<T> {}
 
 impl<T: hash::Hash> hash::Hash for ArchivedOption<T> {
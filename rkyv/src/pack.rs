@@ -0,0 +1,300 @@
+//! Relocation and concatenation of archives.
+//!
+//! Archives only contain relative pointers, so an archive can be moved to a
+//! different position in a buffer without rewriting any of its bytes as long
+//! as its alignment is preserved. This module uses that property to build
+//! multi-root pack files out of individually produced archives without
+//! re-serializing them.
+
+use core::mem::MaybeUninit;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use munge::munge;
+#[cfg(feature = "bytecheck")]
+use rancor::{Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    ser::{Writer, WriterExt as _},
+    vec::{ArchivedVec, VecResolver},
+    Place, Portable,
+};
+
+/// The alignment that archives are written with when they are relocated or
+/// packed.
+///
+/// This matches the alignment of [`AlignedVec`](crate::util::AlignedVec), so
+/// any archive produced in an `AlignedVec` can be relocated.
+pub const ARCHIVE_ALIGNMENT: usize = 16;
+
+/// Writes the bytes of an archive to a writer, preserving its alignment.
+///
+/// Returns the position the archive was written at. Since archives only
+/// contain relative pointers, the archive is valid at its new position.
+///
+/// The archive bytes must have been written starting at an address aligned to
+/// [`ARCHIVE_ALIGNMENT`].
+pub fn relocate_unchecked<W, E>(
+    bytes: &[u8],
+    writer: &mut W,
+) -> Result<usize, E>
+where
+    W: Writer<E> + ?Sized,
+{
+    relocate_unchecked_with_align(bytes, writer, ARCHIVE_ALIGNMENT)
+}
+
+/// Writes the bytes of an archive to a writer, aligning it to `align`.
+///
+/// Returns the position the archive was written at. This can be used with
+/// archives written with a smaller maximum alignment than
+/// [`ARCHIVE_ALIGNMENT`], such as those produced by
+/// [`to_bytes_with_max_align`](crate::util::to_bytes_with_max_align).
+///
+/// The archive bytes must have been written starting at an address aligned to
+/// `align`, and no object in the archive may have a larger alignment than
+/// `align`. `align` must be a power of two.
+pub fn relocate_unchecked_with_align<W, E>(
+    bytes: &[u8],
+    writer: &mut W,
+    align: usize,
+) -> Result<usize, E>
+where
+    W: Writer<E> + ?Sized,
+{
+    let pos = writer.align(align)?;
+    writer.write(bytes)?;
+    Ok(pos)
+}
+
+/// Validates an archive and then writes it to a writer, preserving its
+/// alignment.
+///
+/// Returns the position of the root object of the relocated archive.
+#[cfg(feature = "bytecheck")]
+pub fn relocate<T, W, E>(bytes: &[u8], writer: &mut W) -> Result<usize, E>
+where
+    T: Portable + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    W: Writer<E> + ?Sized,
+    E: Source,
+{
+    crate::access::<T, E>(bytes)?;
+    let pos = relocate_unchecked(bytes, writer)?;
+    Ok(pos + bytes.len() - core::mem::size_of::<T>())
+}
+
+/// An archived multi-root pack of archives.
+///
+/// Packs are built with a [`Packer`], and each archive in the pack can be
+/// accessed and validated independently.
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[repr(transparent)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+pub struct ArchivedPack {
+    archives: ArchivedVec<ArchivedVec<u8>>,
+}
+
+impl ArchivedPack {
+    /// Returns the number of archives in the pack.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.archives.len()
+    }
+
+    /// Returns whether the pack is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.archives.is_empty()
+    }
+
+    /// Returns the bytes of the archive at the given index.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.archives.get(index).map(|archive| archive.as_slice())
+    }
+
+    /// Returns an iterator over the bytes of the archives in the pack.
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        self.archives.iter().map(|archive| archive.as_slice())
+    }
+
+    /// Returns the root of the archive at the given index without validating
+    /// it.
+    ///
+    /// # Safety
+    ///
+    /// The archive at the given index must contain a valid `T` at the default
+    /// root position.
+    #[inline]
+    pub unsafe fn access_unchecked<T: Portable>(
+        &self,
+        index: usize,
+    ) -> Option<&T> {
+        // SAFETY: The caller has guaranteed that the archive at `index`
+        // contains a valid `T` at the root position.
+        self.get(index)
+            .map(|bytes| unsafe { crate::access_unchecked::<T>(bytes) })
+    }
+
+    /// Validates the archive at the given index and returns its root.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    #[cfg(feature = "bytecheck")]
+    #[inline]
+    pub fn access<T, E>(&self, index: usize) -> Option<Result<&T, E>>
+    where
+        T: Portable + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        self.get(index).map(crate::access::<T, E>)
+    }
+}
+
+/// Concatenates archives into a multi-root pack.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked,
+///     pack::{ArchivedPack, Packer},
+///     rancor::Error,
+///     util::AlignedVec,
+///     Archived,
+/// };
+///
+/// let a = rkyv::to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+/// let b = rkyv::to_bytes::<Error>(&"hello world".to_string()).unwrap();
+///
+/// let mut packer = Packer::new(AlignedVec::new());
+/// packer.push::<Error>(&a).unwrap();
+/// packer.push::<Error>(&b).unwrap();
+/// let bytes = packer.finish::<Error>().unwrap();
+///
+/// let pack = unsafe { access_unchecked::<ArchivedPack>(&bytes) };
+/// assert_eq!(pack.len(), 2);
+/// let first = pack.access::<Archived<Vec<u32>>, Error>(0).unwrap();
+/// assert_eq!(first.unwrap().len(), 3);
+/// let second = pack.access::<Archived<String>, Error>(1).unwrap();
+/// assert_eq!(second.unwrap().as_str(), "hello world");
+/// ```
+///
+/// Archives are aligned to [`ARCHIVE_ALIGNMENT`] by default. Packs of archives
+/// with a smaller maximum alignment can be built with
+/// [`with_max_align`](Packer::with_max_align) to reduce the padding between
+/// them.
+#[derive(Debug)]
+pub struct Packer<W, const MAX_ALIGN: usize = ARCHIVE_ALIGNMENT> {
+    writer: W,
+    entries: Vec<(usize, usize)>,
+}
+
+impl<W> Packer<W> {
+    /// Creates a new packer which writes to the given writer.
+    ///
+    /// The writer must be positioned at an address aligned to
+    /// [`ARCHIVE_ALIGNMENT`].
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self::with_max_align(writer)
+    }
+}
+
+impl<W, const MAX_ALIGN: usize> Packer<W, MAX_ALIGN> {
+    /// Creates a new packer which writes to the given writer and aligns each
+    /// archive to `MAX_ALIGN`.
+    ///
+    /// The writer must be positioned at an address aligned to `MAX_ALIGN`,
+    /// and no archive pushed to the packer may contain an object with a larger
+    /// alignment than `MAX_ALIGN`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `MAX_ALIGN` is not a power of two, or is smaller than the
+    /// alignment of the pack index.
+    #[inline]
+    pub fn with_max_align(writer: W) -> Self {
+        assert!(
+            MAX_ALIGN.is_power_of_two(),
+            "MAX_ALIGN must be a power of 2"
+        );
+        assert!(
+            MAX_ALIGN >= core::mem::align_of::<ArchivedPack>(),
+            "MAX_ALIGN must be at least the alignment of ArchivedPack"
+        );
+        Self {
+            writer,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the number of archives pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no archives have been pushed yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Appends an archive to the pack and returns its index.
+    ///
+    /// The archive bytes must have been written starting at an address
+    /// aligned to `MAX_ALIGN`.
+    pub fn push<E>(&mut self, bytes: &[u8]) -> Result<usize, E>
+    where
+        W: Writer<E>,
+    {
+        let pos =
+            relocate_unchecked_with_align(bytes, &mut self.writer, MAX_ALIGN)?;
+        self.entries.push((pos, bytes.len()));
+        Ok(self.entries.len() - 1)
+    }
+
+    /// Writes the pack index and returns the writer.
+    ///
+    /// The root of the written buffer is an [`ArchivedPack`].
+    pub fn finish<E>(mut self) -> Result<W, E>
+    where
+        W: Writer<E>,
+    {
+        let entries_pos = self.writer.align_for::<ArchivedVec<u8>>()?;
+        for &(pos, len) in self.entries.iter() {
+            let entry_pos = self.writer.pos();
+            let mut resolved = MaybeUninit::<ArchivedVec<u8>>::zeroed();
+            // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+            // `MaybeUninit`, and so is properly aligned, dereferenceable, and
+            // all of its bytes are initialized.
+            let out = unsafe {
+                Place::new_unchecked(entry_pos, resolved.as_mut_ptr())
+            };
+            ArchivedVec::resolve_from_len(len, VecResolver::from_pos(pos), out);
+            self.writer.write(out.as_slice())?;
+        }
+
+        let root_pos = self.writer.align_for::<ArchivedPack>()?;
+        let mut resolved = MaybeUninit::<ArchivedPack>::zeroed();
+        // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+        // `MaybeUninit`, and so is properly aligned, dereferenceable, and all
+        // of its bytes are initialized.
+        let out =
+            unsafe { Place::new_unchecked(root_pos, resolved.as_mut_ptr()) };
+        munge!(let ArchivedPack { archives } = out);
+        ArchivedVec::resolve_from_len(
+            self.entries.len(),
+            VecResolver::from_pos(entries_pos),
+            archives,
+        );
+        self.writer.write(out.as_slice())?;
+        self.writer.finish()?;
+
+        Ok(self.writer)
+    }
+}
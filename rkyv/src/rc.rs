@@ -7,10 +7,16 @@ use core::{
 use munge::munge;
 use rancor::Fallible;
 
+#[cfg(feature = "alloc")]
+use crate::analyze::{Analyze, TypeSizeReport};
+#[cfg(feature = "serde")]
+use crate::export::Export;
 use crate::{
+    deref::AsArchivedRef,
     place::Initialized,
     ser::{Sharing, SharingExt, Writer, WriterExt as _},
-    ArchivePointee, ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
+    ArchivePointee, ArchiveUnsized, ArchivedSize, Extract, ExtractUnsized,
+    Place, Portable, RelPtr, SerializeUnsized,
 };
 
 /// The flavor type for [`Rc`](std::rc::Rc).
@@ -91,6 +97,27 @@ impl<T: ArchivePointee + ?Sized, F> ArchivedRc<T, F> {
     }
 }
 
+impl<T, F> ArchivedSize for ArchivedRc<T, F>
+where
+    T: ArchivePointee + ArchivedSize + ?Sized,
+{
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.get().archived_size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F> Analyze for ArchivedRc<T, F>
+where
+    T: ArchivePointee + Analyze + ?Sized,
+{
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        report.record_shared(self.get());
+    }
+}
+
 impl<T: ArchivePointee + ?Sized, F> AsRef<T> for ArchivedRc<T, F> {
     #[inline]
     fn as_ref(&self) -> &T {
@@ -98,6 +125,13 @@ impl<T: ArchivePointee + ?Sized, F> AsRef<T> for ArchivedRc<T, F> {
     }
 }
 
+impl<T: ArchivePointee + ?Sized, F> AsArchivedRef<T> for ArchivedRc<T, F> {
+    #[inline]
+    fn as_archived_ref(&self) -> &T {
+        self.get()
+    }
+}
+
 impl<T: ArchivePointee + ?Sized, F> Borrow<T> for ArchivedRc<T, F> {
     #[inline]
     fn borrow(&self) -> &T {
@@ -134,6 +168,64 @@ impl<T: ArchivePointee + fmt::Display + ?Sized, F> fmt::Display
 
 impl<T: ArchivePointee + Eq + ?Sized, F> Eq for ArchivedRc<T, F> {}
 
+#[cfg(feature = "serde")]
+impl<T: ArchivePointee + Export + ?Sized, F> Export for ArchivedRc<T, F> {
+    #[inline]
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.get().export(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ArchivePointee + Export + ?Sized, F> serde::Serialize
+    for ArchivedRc<T, F>
+{
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
+impl<T, F, S> Extract<S> for ArchivedRc<T, F>
+where
+    T: ExtractUnsized<S> + ?Sized,
+    S: Fallible + Writer + Sharing + ?Sized,
+{
+    type Resolver = RcResolver;
+
+    fn extract(&self, serializer: &mut S) -> Result<RcResolver, S::Error> {
+        let value = self.get();
+        let pos = if let Some(pos) = serializer.get_shared(value) {
+            pos
+        } else {
+            let pos = value.extract_unsized(serializer)?;
+            serializer.add_shared(value, pos)?;
+            pos
+        };
+
+        // As when serializing, the positions of extracted `Rc` values must be
+        // unique.
+        if serializer.pos() == pos {
+            serializer.pad(1)?;
+        }
+
+        Ok(RcResolver { pos })
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, resolver: RcResolver, out: Place<Self>) {
+        let metadata = *self.ptr.metadata();
+        munge!(let ArchivedRc { ptr, .. } = out);
+        RelPtr::emplace_unsized(resolver.pos, metadata, ptr);
+    }
+}
+
 impl<T: ArchivePointee + hash::Hash + ?Sized, F> hash::Hash
     for ArchivedRc<T, F>
 {
@@ -182,7 +274,8 @@ pub struct RcResolver {
 /// An archived `rc::Weak`.
 ///
 /// This is essentially just an optional [`ArchivedRc`].
-#[derive(Portable)]
+#[derive(ArchivedSize, Extract, Portable)]
+#[cfg_attr(feature = "alloc", derive(crate::Analyze))]
 #[archive(crate)]
 #[repr(u8)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
@@ -335,8 +428,10 @@ mod verify {
                 }
 
                 let range = unsafe { context.push_prefix_subtree(ptr)? };
-                unsafe {
-                    T::check_bytes(ptr, context)?;
+                if context.should_check_subtree() {
+                    unsafe {
+                        T::check_bytes(ptr, context)?;
+                    }
                 }
                 unsafe {
                     context.pop_subtree_range(range)?;
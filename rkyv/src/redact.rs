@@ -0,0 +1,177 @@
+//! In-place redaction of archived values.
+//!
+//! [`redact`] overwrites the fields of an archive which are marked as
+//! sensitive without deserializing it. Strings and byte buffers are zeroed and
+//! numbers are set to zero, but the layout of the archive is unchanged so it
+//! can still be accessed and validated afterwards.
+
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+};
+
+#[cfg(feature = "bytecheck")]
+use rancor::{Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    primitive::{
+        ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+        ArchivedI32, ArchivedI64, ArchivedU128, ArchivedU16, ArchivedU32,
+        ArchivedU64,
+    },
+    Portable,
+};
+
+/// An archived type which can overwrite its sensitive data in place.
+///
+/// Primitives, strings, and byte buffers overwrite their entire value when
+/// redacted. Containers like archived `Vec`s, `Box`es, and `Option`s redact
+/// each of the values they contain.
+///
+/// This trait can be derived for archived types with
+/// `#[archive_attr(derive(Redact))]`. Derived implementations only redact the
+/// fields which are marked with `#[archive_attr(redact)]`, so a marked field
+/// of a type which also derives `Redact` has its own marked fields redacted.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, redact, to_bytes, Archive, Redact,
+///     Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(Redact))]
+/// struct Report {
+///     id: u32,
+///     #[archive_attr(redact)]
+///     email: String,
+///     #[archive_attr(redact)]
+///     phone: u64,
+/// }
+///
+/// let value = Report {
+///     id: 42,
+///     email: "someone@example.com".to_string(),
+///     phone: 5550100,
+/// };
+/// let mut bytes = to_bytes::<Error>(&value).unwrap();
+/// redact::<ArchivedReport, Error>(&mut bytes).unwrap();
+///
+/// let archived = unsafe { access_unchecked::<ArchivedReport>(&bytes) };
+/// assert_eq!(archived.id, 42);
+/// assert!(archived.email.bytes().all(|b| b == 0));
+/// assert_eq!(archived.phone, 0);
+/// ```
+pub trait Redact {
+    /// Overwrites the sensitive data in this value.
+    fn redact(self: Pin<&mut Self>);
+}
+
+macro_rules! impl_zeroed {
+    ($($ty:ty = $zero:expr),* $(,)?) => {
+        $(
+            impl Redact for $ty {
+                #[inline]
+                fn redact(self: Pin<&mut Self>) {
+                    *self.get_mut() = $zero;
+                }
+            }
+        )*
+    };
+}
+
+impl_zeroed!(
+    bool = false,
+    i8 = 0,
+    u8 = 0,
+    ArchivedI16 = ArchivedI16::from_native(0),
+    ArchivedI32 = ArchivedI32::from_native(0),
+    ArchivedI64 = ArchivedI64::from_native(0),
+    ArchivedI128 = ArchivedI128::from_native(0),
+    ArchivedU16 = ArchivedU16::from_native(0),
+    ArchivedU32 = ArchivedU32::from_native(0),
+    ArchivedU64 = ArchivedU64::from_native(0),
+    ArchivedU128 = ArchivedU128::from_native(0),
+    ArchivedF32 = ArchivedF32::from_native(0.0),
+    ArchivedF64 = ArchivedF64::from_native(0.0),
+    ArchivedChar = ArchivedChar::from_native('\0'),
+);
+
+impl Redact for () {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {}
+}
+
+impl Redact for PhantomPinned {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {}
+}
+
+impl<T: ?Sized> Redact for PhantomData<T> {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {}
+}
+
+impl Redact for str {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        // SAFETY: The bytes are not moved, and a string of zero bytes is valid
+        // UTF-8.
+        unsafe { self.get_unchecked_mut().as_bytes_mut().fill(0) }
+    }
+}
+
+impl<T: Redact, const N: usize> Redact for [T; N] {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        let slice: Pin<&mut [T]> = self;
+        slice.redact();
+    }
+}
+
+impl<T: Redact> Redact for [T] {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        // SAFETY: The elements are not moved out of the slice, and are only
+        // redacted through pinned references.
+        for value in unsafe { self.get_unchecked_mut().iter_mut() } {
+            unsafe { Pin::new_unchecked(value) }.redact();
+        }
+    }
+}
+
+/// Redacts the archived value in the given bytes in place.
+///
+/// The archive is validated before it is redacted. See [`Redact`] for an
+/// example.
+#[cfg(feature = "bytecheck")]
+pub fn redact<T, E>(bytes: &mut [u8]) -> Result<(), E>
+where
+    T: Redact + Portable + bytecheck::CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    crate::validation::util::access_mut::<T, E>(bytes)?.redact();
+    Ok(())
+}
+
+/// Redacts the archived value in the given bytes in place without validating
+/// it.
+///
+/// See [`Redact`] for more information.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice.
+pub unsafe fn redact_unchecked<T>(bytes: &mut [u8])
+where
+    T: Redact + Portable,
+{
+    // SAFETY: The caller has guaranteed that the bytes contain a `T` at the
+    // root position.
+    unsafe { crate::access_unchecked_mut::<T>(bytes) }.redact();
+}
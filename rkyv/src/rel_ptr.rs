@@ -10,6 +10,10 @@ use munge::munge;
 use rancor::{fail, Panic, ResultExt as _, Source};
 
 use crate::{
+    format::primitives::{
+        i16_be, i16_le, i32_be, i32_le, i64_be, i64_le, u16_be, u16_le, u32_be,
+        u32_le, u64_be, u64_le,
+    },
     place::Initialized,
     primitive::{
         ArchivedI16, ArchivedI32, ArchivedI64, ArchivedU16, ArchivedU32,
@@ -88,17 +92,30 @@ macro_rules! impl_offset_multi_byte {
     };
 }
 
-impl_offset_multi_byte!(i16, ArchivedI16);
+// Offsets are implemented for both endiannesses so that relative pointers can
+// be used in any `Format`.
+
+impl_offset_multi_byte!(i16, i16_le);
+impl_offset_multi_byte!(i16, i16_be);
+#[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+impl_offset_multi_byte!(i32, i32_le);
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
-impl_offset_multi_byte!(i32, ArchivedI32);
+impl_offset_multi_byte!(i32, i32_be);
 #[cfg(target_pointer_width = "64")]
-impl_offset_multi_byte!(i64, ArchivedI64);
+impl_offset_multi_byte!(i64, i64_le);
+#[cfg(target_pointer_width = "64")]
+impl_offset_multi_byte!(i64, i64_be);
 
-impl_offset_multi_byte!(u16, ArchivedU16);
+impl_offset_multi_byte!(u16, u16_le);
+impl_offset_multi_byte!(u16, u16_be);
+#[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+impl_offset_multi_byte!(u32, u32_le);
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
-impl_offset_multi_byte!(u32, ArchivedU32);
+impl_offset_multi_byte!(u32, u32_be);
+#[cfg(target_pointer_width = "64")]
+impl_offset_multi_byte!(u64, u64_le);
 #[cfg(target_pointer_width = "64")]
-impl_offset_multi_byte!(u64, ArchivedU64);
+impl_offset_multi_byte!(u64, u64_be);
 
 /// An untyped pointer which resolves relative to its position in memory.
 ///
@@ -0,0 +1,465 @@
+//! A fixed-capacity ring buffer which can be used as a queue in place.
+//!
+//! An archived ring buffer keeps its head and tail as archived atomics, so a
+//! single producer and a single consumer can push and pop values directly in
+//! the archive. When the archive is placed in shared memory, this makes it a
+//! bounded queue between two processes which never copies the buffer.
+//!
+//! Validation checks the capacity and the head and tail of the buffer along
+//! with the values in its slots. Because the slots are read during
+//! validation, a buffer must be validated before its producer starts pushing
+//! values.
+//!
+//! Values are copied into and out of the slots, so only `Copy` values which
+//! do not contain relative pointers can be pushed and popped.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked_mut,
+//!     primitive::ArchivedU32,
+//!     rancor::Error,
+//!     ring::{ArchivedRingBuffer, RingBuffer},
+//!     to_bytes,
+//! };
+//!
+//! let mut bytes =
+//!     to_bytes::<Error>(&RingBuffer::<u32>::with_capacity(2)).unwrap();
+//! let ring = unsafe {
+//!     access_unchecked_mut::<ArchivedRingBuffer<ArchivedU32>>(&mut bytes)
+//! }
+//! .get_ref();
+//!
+//! // SAFETY: This is the only producer and the only consumer of the buffer.
+//! let (mut producer, mut consumer) =
+//!     unsafe { (ring.producer(), ring.consumer()) };
+//! producer.push(ArchivedU32::from_native(1)).unwrap();
+//! producer.push(ArchivedU32::from_native(2)).unwrap();
+//! assert!(producer.push(ArchivedU32::from_native(3)).is_err());
+//! assert_eq!(consumer.pop().map(|v| v.to_native()), Some(1));
+//! assert_eq!(consumer.pop().map(|v| v.to_native()), Some(2));
+//! assert!(consumer.pop().is_none());
+//! ```
+
+use core::{cell::UnsafeCell, fmt, mem, sync::atomic::Ordering};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    primitive::ArchivedAtomicU32,
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Place, Portable, Serialize,
+};
+
+/// The largest capacity of a ring buffer.
+pub const MAX_CAPACITY: usize = (u32::MAX / 2) as usize;
+
+/// A fixed-capacity ring buffer.
+///
+/// Ring buffers are archived with all of their slots, so values can be pushed
+/// to the archived buffer until it reaches the capacity it was created with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RingBuffer<T> {
+    slots: Vec<T>,
+    head: usize,
+    len: usize,
+}
+
+impl<T: Default> RingBuffer<T> {
+    /// Returns an empty ring buffer with the given capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or greater than [`MAX_CAPACITY`].
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(
+            capacity > 0 && capacity <= MAX_CAPACITY,
+            "ring buffer capacity must be between one and `MAX_CAPACITY`",
+        );
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize_with(capacity, T::default);
+        Self {
+            slots,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Removes the value at the front of the buffer and returns it, or
+    /// returns `None` if the buffer is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = mem::take(&mut self.slots[self.head]);
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Returns the number of values the buffer can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of values in the buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the buffer has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the buffer is at capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Adds a value to the back of the buffer.
+    ///
+    /// Returns the value back if the buffer is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        let index = (self.head + self.len) % self.capacity();
+        self.slots[index] = value;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+/// A slot of an archived ring buffer.
+///
+/// Slots are written by the producer and read by the consumer of the buffer
+/// while it is shared between them.
+#[repr(transparent)]
+pub struct RingSlot<T> {
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `RingSlot<T>` is a transparent wrapper around an `UnsafeCell<T>`,
+// which is portable when `T` is portable.
+unsafe impl<T: Portable> Portable for RingSlot<T> {}
+
+/// An archived `RingBuffer`.
+///
+/// The head and tail of the buffer count up to twice its capacity before
+/// wrapping around, so that a full buffer can be told apart from an empty
+/// one.
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedRingBuffer<T> {
+    head: ArchivedAtomicU32,
+    tail: ArchivedAtomicU32,
+    slots: ArchivedVec<RingSlot<T>>,
+}
+
+// SAFETY: Values are only moved between threads by the producer and consumer
+// of the buffer, which synchronize on its head and tail.
+unsafe impl<T: Send> Sync for ArchivedRingBuffer<T> {}
+
+impl<T> ArchivedRingBuffer<T> {
+    /// Returns the number of values the buffer can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of values in the buffer.
+    ///
+    /// The producer and consumer may change the length of the buffer at any
+    /// time, so the returned length may already be out of date.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        distance(head, tail, self.capacity()) as usize
+    }
+
+    /// Returns whether the buffer has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the buffer is at capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// Returns the producer of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// There must be at most one producer for the buffer at a time, including
+    /// producers in other processes sharing the buffer.
+    #[inline]
+    pub unsafe fn producer(&self) -> Producer<'_, T> {
+        Producer { buffer: self }
+    }
+
+    /// Returns the consumer of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// There must be at most one consumer for the buffer at a time, including
+    /// consumers in other processes sharing the buffer.
+    #[inline]
+    pub unsafe fn consumer(&self) -> Consumer<'_, T> {
+        Consumer { buffer: self }
+    }
+
+    fn slot(&self, index: u32) -> *mut T {
+        let slot = &self.slots[index as usize % self.capacity()];
+        slot.value.get()
+    }
+}
+
+impl<T> fmt::Debug for ArchivedRingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedRingBuffer")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+// Returns the number of values between the head and tail of a buffer.
+#[inline]
+fn distance(head: u32, tail: u32, capacity: usize) -> u64 {
+    let wrap = 2 * capacity as u64;
+    if wrap == 0 {
+        0
+    } else {
+        (tail as u64 + wrap - head as u64 % wrap) % wrap
+    }
+}
+
+// Returns the head or tail of a buffer after it is advanced by one slot.
+#[inline]
+fn advance(index: u32, capacity: usize) -> u32 {
+    ((index as u64 + 1) % (2 * capacity as u64)) as u32
+}
+
+/// The producer of an archived ring buffer.
+///
+/// This is returned by [`ArchivedRingBuffer::producer`].
+pub struct Producer<'a, T> {
+    buffer: &'a ArchivedRingBuffer<T>,
+}
+
+impl<T: Copy> Producer<'_, T> {
+    /// Adds a value to the back of the buffer.
+    ///
+    /// Returns the value back if the buffer is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        let buffer = self.buffer;
+        let capacity = buffer.capacity();
+        // Only the producer changes the tail.
+        let tail = buffer.tail.load(Ordering::Relaxed);
+        let head = buffer.head.load(Ordering::Acquire);
+        if distance(head, tail, capacity) >= capacity as u64 {
+            return Err(value);
+        }
+
+        // SAFETY: The slot at the tail is not visible to the consumer until
+        // the tail is advanced past it.
+        unsafe {
+            buffer.slot(tail).write(value);
+        }
+        buffer
+            .tail
+            .store(advance(tail, capacity), Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T> fmt::Debug for Producer<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer")
+            .field("buffer", self.buffer)
+            .finish()
+    }
+}
+
+/// The consumer of an archived ring buffer.
+///
+/// This is returned by [`ArchivedRingBuffer::consumer`].
+pub struct Consumer<'a, T> {
+    buffer: &'a ArchivedRingBuffer<T>,
+}
+
+impl<T: Copy> Consumer<'_, T> {
+    /// Removes the value at the front of the buffer and returns it, or
+    /// returns `None` if the buffer is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let buffer = self.buffer;
+        let capacity = buffer.capacity();
+        // Only the consumer changes the head.
+        let head = buffer.head.load(Ordering::Relaxed);
+        let tail = buffer.tail.load(Ordering::Acquire);
+        if distance(head, tail, capacity) == 0 {
+            return None;
+        }
+
+        // SAFETY: The producer does not write to the slot at the head until
+        // the head is advanced past it.
+        let value = unsafe { buffer.slot(head).read() };
+        buffer
+            .head
+            .store(advance(head, capacity), Ordering::Release);
+        Some(value)
+    }
+}
+
+impl<T> fmt::Debug for Consumer<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer")
+            .field("buffer", self.buffer)
+            .finish()
+    }
+}
+
+impl<T: Archive> Archive for RingBuffer<T> {
+    type Archived = ArchivedRingBuffer<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedRingBuffer { head, tail, slots } = out);
+        head.write(ArchivedAtomicU32::new(self.head as u32));
+        tail.write(ArchivedAtomicU32::new((self.head + self.len) as u32));
+        ArchivedVec::resolve_from_len(self.slots.len(), resolver, slots);
+    }
+}
+
+impl<T, S> Serialize<S> for RingBuffer<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        // `RingSlot<T::Archived>` has the same layout as `T::Archived`, so
+        // the slots can be serialized as a plain slice.
+        ArchivedVec::<T::Archived>::serialize_from_slice(
+            &self.slots,
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::{fmt, sync::atomic::Ordering};
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        CheckBytes, Verify,
+    };
+    use rancor::fail;
+
+    use super::{distance, ArchivedRingBuffer, RingSlot, MAX_CAPACITY};
+
+    unsafe impl<T, C> CheckBytes<C> for RingSlot<T>
+    where
+        T: CheckBytes<C>,
+        C: Fallible + ?Sized,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: `RingSlot<T>` is a transparent wrapper around an
+            // `UnsafeCell<T>`, which has the same layout as `T`.
+            unsafe { T::check_bytes(value.cast::<T>(), context) }
+        }
+    }
+
+    #[derive(Debug)]
+    enum RingBufferError {
+        InvalidCapacity {
+            capacity: usize,
+        },
+        IndexOutOfRange {
+            head: u32,
+            tail: u32,
+            capacity: usize,
+        },
+    }
+
+    impl fmt::Display for RingBufferError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::InvalidCapacity { capacity } => write!(
+                    f,
+                    "ring buffer capacity {} is not between 1 and {}",
+                    capacity, MAX_CAPACITY,
+                ),
+                Self::IndexOutOfRange {
+                    head,
+                    tail,
+                    capacity,
+                } => write!(
+                    f,
+                    "ring buffer head {} and tail {} are out of range for \
+                     capacity {}",
+                    head, tail, capacity,
+                ),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for RingBufferError {}
+
+    unsafe impl<T, C> Verify<C> for ArchivedRingBuffer<T>
+    where
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            let capacity = self.capacity();
+            if capacity == 0 || capacity > MAX_CAPACITY {
+                fail!(RingBufferError::InvalidCapacity { capacity });
+            }
+
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            let wrap = 2 * capacity as u64;
+            if head as u64 >= wrap
+                || tail as u64 >= wrap
+                || distance(head, tail, capacity) > capacity as u64
+            {
+                fail!(RingBufferError::IndexOutOfRange {
+                    head,
+                    tail,
+                    capacity,
+                });
+            }
+
+            Ok(())
+        }
+    }
+}
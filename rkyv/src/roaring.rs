@@ -0,0 +1,313 @@
+//! An archived version of `RoaringBitmap`.
+//!
+//! Archived roaring bitmaps can be queried in place. Values are split into
+//! containers by their upper 16 bits, and each container stores the lower 16
+//! bits of its values either as a sorted array or as a dense bitmap.
+
+use core::{cmp::Ordering, iter::FusedIterator};
+
+use crate::{
+    primitive::{ArchivedU16, ArchivedU32, ArchivedU64},
+    vec::{ArchivedVec, VecResolver},
+    Portable,
+};
+
+/// The number of values above which a container is stored as a bitmap
+/// instead of a sorted array.
+pub(crate) const ARRAY_LIMIT: usize = 4096;
+
+/// The number of words in the bitmap of a dense container.
+pub(crate) const BITMAP_WORDS: usize = 1024;
+
+/// An archived `RoaringBitmap`.
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(transparent)]
+pub struct ArchivedRoaringBitmap {
+    pub(crate) containers: ArchivedVec<ArchivedContainer>,
+}
+
+/// The values of an archived roaring bitmap which share their upper 16 bits.
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(C)]
+pub struct ArchivedContainer {
+    pub(crate) key: ArchivedU16,
+    pub(crate) len: ArchivedU32,
+    // The number of values in all of the preceding containers.
+    pub(crate) rank: ArchivedU64,
+    // The sorted lower bits of each value if the container is sparse.
+    pub(crate) array: ArchivedVec<ArchivedU16>,
+    // A bitmap of the lower bits of the values if the container is dense.
+    pub(crate) bitmap: ArchivedVec<ArchivedU64>,
+}
+
+impl ArchivedContainer {
+    /// Returns the upper 16 bits of the values in the container.
+    #[inline]
+    pub fn key(&self) -> u16 {
+        self.key.to_native()
+    }
+
+    /// Returns the number of values in the container.
+    #[inline]
+    pub fn len(&self) -> u32 {
+        self.len.to_native()
+    }
+
+    /// Returns whether the container has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the container contains the given lower 16 bits.
+    pub fn contains(&self, low: u16) -> bool {
+        if self.bitmap.is_empty() {
+            self.array
+                .binary_search_by(|v| v.to_native().cmp(&low))
+                .is_ok()
+        } else {
+            match self.bitmap.get(low as usize / 64) {
+                Some(word) => word.to_native() & (1 << (low % 64)) != 0,
+                None => false,
+            }
+        }
+    }
+
+    /// Returns the number of values in the container whose lower 16 bits are
+    /// less than or equal to `low`.
+    pub fn rank(&self, low: u16) -> u64 {
+        if self.bitmap.is_empty() {
+            self.array.partition_point(|v| v.to_native() <= low) as u64
+        } else {
+            let index = low as usize / 64;
+            let words = &self.bitmap[..index.min(self.bitmap.len())];
+            let before = words
+                .iter()
+                .map(|w| w.to_native().count_ones() as u64)
+                .sum();
+            let mask = u64::MAX >> (63 - low % 64);
+            match self.bitmap.get(index) {
+                Some(word) => {
+                    before + (word.to_native() & mask).count_ones() as u64
+                }
+                None => before,
+            }
+        }
+    }
+
+    /// Returns an iterator over the lower 16 bits of the values in the
+    /// container, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        ContainerValues::new(self)
+    }
+}
+
+impl ArchivedRoaringBitmap {
+    /// Returns the number of values in the bitmap.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        match self.containers.last() {
+            Some(last) => last.rank.to_native() + last.len() as u64,
+            None => 0,
+        }
+    }
+
+    /// Returns whether the bitmap has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the containers of the bitmap, ordered by key.
+    #[inline]
+    pub fn containers(&self) -> &[ArchivedContainer] {
+        &self.containers
+    }
+
+    fn find(&self, key: u16) -> Result<usize, usize> {
+        self.containers.binary_search_by(|c| c.key().cmp(&key))
+    }
+
+    /// Returns whether the bitmap contains the given value.
+    pub fn contains(&self, value: u32) -> bool {
+        let (key, low) = split(value);
+        match self.find(key) {
+            Ok(index) => self.containers[index].contains(low),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the number of values in the bitmap which are less than or
+    /// equal to the given value.
+    pub fn rank(&self, value: u32) -> u64 {
+        let (key, low) = split(value);
+        match self.find(key) {
+            Ok(index) => {
+                let container = &self.containers[index];
+                container.rank.to_native() + container.rank(low)
+            }
+            Err(index) => match self.containers.get(index) {
+                Some(next) => next.rank.to_native(),
+                None => self.len(),
+            },
+        }
+    }
+
+    /// Returns the smallest value in the bitmap.
+    pub fn min(&self) -> Option<u32> {
+        let first = self.containers.first()?;
+        first.iter().next().map(|low| join(first.key(), low))
+    }
+
+    /// Returns the largest value in the bitmap.
+    pub fn max(&self) -> Option<u32> {
+        let last = self.containers.last()?;
+        last.iter().last().map(|low| join(last.key(), low))
+    }
+
+    /// Returns an iterator over the values in the bitmap, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.containers.iter().flat_map(|container| {
+            let key = container.key();
+            container.iter().map(move |low| join(key, low))
+        })
+    }
+
+    /// Returns an iterator over the values which are in both this bitmap and
+    /// `other`, in ascending order.
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a ArchivedRoaringBitmap,
+    ) -> Intersection<'a> {
+        Intersection {
+            left: &self.containers,
+            right: &other.containers,
+            current: None,
+        }
+    }
+
+    /// Returns the number of values which are in both this bitmap and
+    /// `other`.
+    pub fn intersection_len(&self, other: &ArchivedRoaringBitmap) -> u64 {
+        self.intersection(other).count() as u64
+    }
+}
+
+/// The resolver for an archived `RoaringBitmap`.
+pub struct RoaringBitmapResolver {
+    pub(crate) containers: VecResolver,
+    pub(crate) len: usize,
+}
+
+/// An iterator over the values which are in both of two archived roaring
+/// bitmaps.
+///
+/// This is returned by [`ArchivedRoaringBitmap::intersection`].
+pub struct Intersection<'a> {
+    left: &'a [ArchivedContainer],
+    right: &'a [ArchivedContainer],
+    current: Option<(u16, &'a ArchivedContainer, ContainerValues<'a>)>,
+}
+
+struct ContainerValues<'a> {
+    array: core::slice::Iter<'a, ArchivedU16>,
+    bitmap: BitmapIter<'a>,
+}
+
+impl Iterator for ContainerValues<'_> {
+    type Item = u16;
+
+    #[inline]
+    fn next(&mut self) -> Option<u16> {
+        match self.array.next() {
+            Some(value) => Some(value.to_native()),
+            None => self.bitmap.next(),
+        }
+    }
+}
+
+struct BitmapIter<'a> {
+    words: core::iter::Enumerate<core::slice::Iter<'a, ArchivedU64>>,
+    base: usize,
+    word: u64,
+}
+
+impl Iterator for BitmapIter<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        while self.word == 0 {
+            let (index, word) = self.words.next()?;
+            self.base = index * 64;
+            self.word = word.to_native();
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some((self.base + bit) as u16)
+    }
+}
+
+impl<'a> ContainerValues<'a> {
+    fn new(container: &'a ArchivedContainer) -> Self {
+        Self {
+            array: container.array.iter(),
+            bitmap: BitmapIter {
+                words: container.bitmap.iter().enumerate(),
+                base: 0,
+                word: 0,
+            },
+        }
+    }
+}
+
+impl Iterator for Intersection<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some((key, other, values)) = &mut self.current {
+                for low in values.by_ref() {
+                    if other.contains(low) {
+                        return Some(join(*key, low));
+                    }
+                }
+                self.current = None;
+            }
+
+            let (left, right) = (self.left.first()?, self.right.first()?);
+            match left.key().cmp(&right.key()) {
+                Ordering::Less => self.left = &self.left[1..],
+                Ordering::Greater => self.right = &self.right[1..],
+                Ordering::Equal => {
+                    self.left = &self.left[1..];
+                    self.right = &self.right[1..];
+                    // Iterate over the smaller container and probe the larger
+                    // one.
+                    let (small, large) = if left.len() <= right.len() {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                    self.current =
+                        Some((small.key(), large, ContainerValues::new(small)));
+                }
+            }
+        }
+    }
+}
+
+impl FusedIterator for Intersection<'_> {}
+
+#[inline]
+fn split(value: u32) -> (u16, u16) {
+    ((value >> 16) as u16, value as u16)
+}
+
+#[inline]
+fn join(key: u16, low: u16) -> u32 {
+    (key as u32) << 16 | low as u32
+}
@@ -0,0 +1,226 @@
+//! Compile-time fingerprints of archived layouts.
+//!
+//! Archives don't describe their own layout, so reading an archive written
+//! with a different layout silently misreads its bytes. A [`SchemaHash`] is a
+//! constant fingerprint of an archived type's layout which applications can
+//! embed in file headers, and compare before accessing the archive.
+//!
+//! Schema hashes cover the size, alignment, field order, field names, and
+//! field offsets of archived types, along with the endianness of archived
+//! primitives. They are computed with [`SchemaHasher`], which is stable across
+//! compiler versions and platforms. Two builds produce the same schema hash
+//! for a type exactly when they agree on its archived layout.
+//!
+//! `SchemaHash` can be derived for archived types with
+//! `#[archive_attr(derive(SchemaHash))]`. The schema hash of a root type can
+//! also be exchanged as the root layout hash of a
+//! [`FormatDescriptor`](crate::descriptor::FormatDescriptor).
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{schema::SchemaHash, Archived};
+//!
+//! mod v1 {
+//!     #[derive(rkyv::Archive)]
+//!     #[archive_attr(derive(rkyv::SchemaHash))]
+//!     pub struct Header {
+//!         pub version: u32,
+//!         pub len: u64,
+//!     }
+//! }
+//!
+//! mod v2 {
+//!     #[derive(rkyv::Archive)]
+//!     #[archive_attr(derive(rkyv::SchemaHash))]
+//!     pub struct Header {
+//!         pub len: u64,
+//!         pub version: u32,
+//!     }
+//! }
+//!
+//! // Reordering fields changes the schema hash.
+//! assert_ne!(
+//!     Archived::<v1::Header>::SCHEMA_HASH,
+//!     Archived::<v2::Header>::SCHEMA_HASH,
+//! );
+//! ```
+
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    num::{NonZeroI8, NonZeroU8},
+};
+
+use crate::{
+    descriptor::Endianness,
+    format::Format,
+    primitive::{
+        ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+        ArchivedI32, ArchivedI64, ArchivedNonZeroI128, ArchivedNonZeroI16,
+        ArchivedNonZeroI32, ArchivedNonZeroI64, ArchivedNonZeroU128,
+        ArchivedNonZeroU16, ArchivedNonZeroU32, ArchivedNonZeroU64,
+        ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64,
+    },
+};
+
+/// An archived type with a constant fingerprint of its layout.
+///
+/// See the [module docs](self) for more information.
+pub trait SchemaHash {
+    /// The fingerprint of this type's archived layout.
+    const SCHEMA_HASH: u64;
+}
+
+/// A hasher which computes schema hashes in constant contexts.
+///
+/// This is a 64-bit FNV-1a hasher. Integers are hashed as little-endian bytes
+/// and strings are prefixed with their length, so the same inputs always
+/// produce the same hash.
+#[derive(Clone, Copy, Debug)]
+pub struct SchemaHasher {
+    state: u64,
+}
+
+impl SchemaHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Returns a new hasher.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    /// Hashes the given bytes.
+    #[inline]
+    pub const fn write_bytes(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self.state ^= bytes[i] as u64;
+            self.state = self.state.wrapping_mul(Self::PRIME);
+            i += 1;
+        }
+        self
+    }
+
+    /// Hashes the given `u64`.
+    #[inline]
+    pub const fn write_u64(self, value: u64) -> Self {
+        self.write_bytes(&value.to_le_bytes())
+    }
+
+    /// Hashes the given `usize`.
+    #[inline]
+    pub const fn write_usize(self, value: usize) -> Self {
+        self.write_u64(value as u64)
+    }
+
+    /// Hashes the given string.
+    #[inline]
+    pub const fn write_str(self, value: &str) -> Self {
+        self.write_usize(value.len()).write_bytes(value.as_bytes())
+    }
+
+    /// Hashes the size and alignment of `T`.
+    #[inline]
+    pub const fn write_layout<T>(self) -> Self {
+        self.write_usize(mem::size_of::<T>())
+            .write_usize(mem::align_of::<T>())
+    }
+
+    /// Hashes the endianness of archived primitives.
+    #[inline]
+    pub const fn write_endianness(self) -> Self {
+        self.write_bytes(&[cfg!(feature = "big_endian") as u8])
+    }
+
+    /// Hashes the endianness and pointer width of the format `F`.
+    #[inline]
+    pub const fn write_format<F: Format>(self) -> Self {
+        let big_endian = matches!(F::ENDIANNESS, Endianness::Big);
+        self.write_bytes(&[big_endian as u8, F::POINTER_WIDTH])
+    }
+
+    /// Returns the hash of the values written so far.
+    #[inline]
+    pub const fn finish(self) -> u64 {
+        self.state
+    }
+}
+
+impl Default for SchemaHasher {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl SchemaHash for $ty {
+                const SCHEMA_HASH: u64 = SchemaHasher::new()
+                    .write_str($name)
+                    .write_layout::<$ty>()
+                    .write_endianness()
+                    .finish();
+            }
+        )*
+    };
+}
+
+impl_primitive!(
+    () => "()",
+    bool => "bool",
+    i8 => "i8",
+    u8 => "u8",
+    NonZeroI8 => "NonZeroI8",
+    NonZeroU8 => "NonZeroU8",
+    PhantomPinned => "PhantomPinned",
+    ArchivedI16 => "i16",
+    ArchivedI32 => "i32",
+    ArchivedI64 => "i64",
+    ArchivedI128 => "i128",
+    ArchivedU16 => "u16",
+    ArchivedU32 => "u32",
+    ArchivedU64 => "u64",
+    ArchivedU128 => "u128",
+    ArchivedF32 => "f32",
+    ArchivedF64 => "f64",
+    ArchivedChar => "char",
+    ArchivedNonZeroI16 => "NonZeroI16",
+    ArchivedNonZeroI32 => "NonZeroI32",
+    ArchivedNonZeroI64 => "NonZeroI64",
+    ArchivedNonZeroI128 => "NonZeroI128",
+    ArchivedNonZeroU16 => "NonZeroU16",
+    ArchivedNonZeroU32 => "NonZeroU32",
+    ArchivedNonZeroU64 => "NonZeroU64",
+    ArchivedNonZeroU128 => "NonZeroU128",
+);
+
+impl SchemaHash for str {
+    const SCHEMA_HASH: u64 = SchemaHasher::new().write_str("str").finish();
+}
+
+impl<T: ?Sized> SchemaHash for PhantomData<T> {
+    const SCHEMA_HASH: u64 =
+        SchemaHasher::new().write_str("PhantomData").finish();
+}
+
+impl<T: SchemaHash, const N: usize> SchemaHash for [T; N] {
+    const SCHEMA_HASH: u64 = SchemaHasher::new()
+        .write_str("[T; N]")
+        .write_u64(T::SCHEMA_HASH)
+        .write_usize(N)
+        .finish();
+}
+
+impl<T: SchemaHash> SchemaHash for [T] {
+    const SCHEMA_HASH: u64 = SchemaHasher::new()
+        .write_str("[T]")
+        .write_u64(T::SCHEMA_HASH)
+        .finish();
+}
@@ -0,0 +1,238 @@
+//! Substring search over archived strings and byte vectors.
+//!
+//! [`ArchivedString`](crate::string::ArchivedString) and
+//! [`ArchivedVec<u8>`](crate::vec::ArchivedVec) have `contains`, `find`,
+//! `split`, and `starts_with` methods which search their bytes in place. With
+//! the `memchr` feature enabled, searches are accelerated with SIMD.
+
+use core::{fmt, str};
+
+/// A pattern which can be searched for in bytes.
+///
+/// This is implemented for string and byte slices, as well as single
+/// characters and bytes.
+pub trait Needle<'n> {
+    /// Returns the bytes to search for.
+    fn into_needle(self) -> NeedleBytes<'n>;
+}
+
+/// A [`Needle`] which is valid UTF-8, and so can be searched for in strings.
+pub trait StrNeedle<'n>: Needle<'n> {}
+
+/// The bytes of a [`Needle`].
+#[derive(Clone, Copy)]
+pub struct NeedleBytes<'n> {
+    repr: NeedleRepr<'n>,
+}
+
+#[derive(Clone, Copy)]
+enum NeedleRepr<'n> {
+    Borrowed(&'n [u8]),
+    Inline { bytes: [u8; 4], len: u8 },
+}
+
+impl NeedleBytes<'_> {
+    /// Returns the bytes to search for.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.repr {
+            NeedleRepr::Borrowed(bytes) => bytes,
+            NeedleRepr::Inline { bytes, len } => &bytes[..*len as usize],
+        }
+    }
+}
+
+impl fmt::Debug for NeedleBytes<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_bytes(), f)
+    }
+}
+
+impl<'n> Needle<'n> for &'n [u8] {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        NeedleBytes {
+            repr: NeedleRepr::Borrowed(self),
+        }
+    }
+}
+
+impl<'n, const N: usize> Needle<'n> for &'n [u8; N] {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        self.as_slice().into_needle()
+    }
+}
+
+impl<'n> Needle<'n> for &'n str {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        self.as_bytes().into_needle()
+    }
+}
+
+impl<'n> StrNeedle<'n> for &'n str {}
+
+impl<'n, 'a: 'n> Needle<'n> for &'n &'a str {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        self.as_bytes().into_needle()
+    }
+}
+
+impl<'n, 'a: 'n> StrNeedle<'n> for &'n &'a str {}
+
+impl<'n> Needle<'n> for u8 {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        NeedleBytes {
+            repr: NeedleRepr::Inline {
+                bytes: [self, 0, 0, 0],
+                len: 1,
+            },
+        }
+    }
+}
+
+impl<'n> Needle<'n> for &u8 {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        (*self).into_needle()
+    }
+}
+
+impl<'n> Needle<'n> for char {
+    #[inline]
+    fn into_needle(self) -> NeedleBytes<'n> {
+        let mut bytes = [0; 4];
+        let len = self.encode_utf8(&mut bytes).len() as u8;
+        NeedleBytes {
+            repr: NeedleRepr::Inline { bytes, len },
+        }
+    }
+}
+
+impl<'n> StrNeedle<'n> for char {}
+
+/// Returns the index of the first occurrence of `needle` in `haystack`.
+///
+/// An empty needle is found at index 0.
+#[inline]
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    match needle {
+        [] => Some(0),
+        [byte] => find_byte(haystack, *byte),
+        _ => find_bytes(haystack, needle),
+    }
+}
+
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    memchr::memchr(byte, haystack)
+}
+
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_byte(haystack: &[u8], byte: u8) -> Option<usize> {
+    haystack.iter().position(|b| *b == byte)
+}
+
+#[cfg(feature = "memchr")]
+#[inline]
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    memchr::memmem::find(haystack, needle)
+}
+
+#[cfg(not(feature = "memchr"))]
+#[inline]
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// An iterator over the subslices of some bytes which are separated by a
+/// needle.
+///
+/// This is returned by
+/// [`ArchivedVec::split`](crate::vec::ArchivedVec::split). If the needle is
+/// empty, the whole haystack is returned as the only subslice.
+#[derive(Clone, Debug)]
+pub struct Split<'a, 'n> {
+    haystack: Option<&'a [u8]>,
+    needle: NeedleBytes<'n>,
+}
+
+impl<'a, 'n> Split<'a, 'n> {
+    /// Returns an iterator over the subslices of `haystack` which are
+    /// separated by `needle`.
+    #[inline]
+    pub fn new(haystack: &'a [u8], needle: impl Needle<'n>) -> Self {
+        Self {
+            haystack: Some(haystack),
+            needle: needle.into_needle(),
+        }
+    }
+}
+
+impl<'a> Iterator for Split<'a, '_> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let haystack = self.haystack?;
+        let needle = self.needle.as_bytes();
+        if needle.is_empty() {
+            self.haystack = None;
+            return Some(haystack);
+        }
+
+        match find(haystack, needle) {
+            Some(index) => {
+                self.haystack = Some(&haystack[index + needle.len()..]);
+                Some(&haystack[..index])
+            }
+            None => {
+                self.haystack = None;
+                Some(haystack)
+            }
+        }
+    }
+}
+
+/// An iterator over the substrings of a string which are separated by a
+/// needle.
+///
+/// This is returned by
+/// [`ArchivedString::split`](crate::string::ArchivedString::split). If the
+/// needle is empty, the whole string is returned as the only substring.
+#[derive(Clone, Debug)]
+pub struct SplitStr<'a, 'n> {
+    inner: Split<'a, 'n>,
+}
+
+impl<'a, 'n> SplitStr<'a, 'n> {
+    /// Returns an iterator over the substrings of `haystack` which are
+    /// separated by `needle`.
+    #[inline]
+    pub fn new(haystack: &'a str, needle: impl StrNeedle<'n>) -> Self {
+        Self {
+            inner: Split::new(haystack.as_bytes(), needle),
+        }
+    }
+}
+
+impl<'a> Iterator for SplitStr<'a, '_> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.inner.next()?;
+        // SAFETY: The haystack and needle are both valid UTF-8, so matches of
+        // the needle always start and end on character boundaries. Every
+        // subslice returned by `Split` is delimited by matches or by the ends
+        // of the haystack, and so is also valid UTF-8.
+        Some(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+}
@@ -0,0 +1,484 @@
+//! Archives which are split across multiple segments.
+//!
+//! A single archive must be stored in one contiguous buffer. Large datasets can
+//! instead be split into segments which are written, stored, and loaded
+//! independently. A [`FarPtr`] points to a value in another segment by its
+//! segment ID and its offset in that segment, rather than by a relative
+//! offset.
+//!
+//! Fields are written to their own segments with the [`Far`](crate::with::Far)
+//! wrapper. The serializer must be wrapped in a [`Segmented`] adapter, which
+//! collects the segments in a table as they are written. Segment IDs are
+//! indices into that table; the archive containing the far pointers is not
+//! part of the table.
+
+#[cfg(feature = "alloc")]
+use core::{alloc::Layout, ptr::NonNull};
+use core::{fmt, marker::PhantomData, mem::size_of};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
+use munge::munge;
+#[cfg(feature = "alloc")]
+use rancor::Fallible;
+use rancor::{fail, Source, Strategy};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    de::{ErasedPtr, Metering, Pooling},
+    ser::{Allocator, Positional, Sharing, Writer},
+    util::AlignedVec,
+};
+use crate::{
+    primitive::{ArchivedU32, ArchivedUsize, FixedUsize},
+    util::access_pos_unchecked,
+    Place, Portable,
+};
+
+#[derive(Debug)]
+struct MissingSegment {
+    id: u32,
+}
+
+impl fmt::Display for MissingSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "segment {} is not loaded", self.id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingSegment {}
+
+#[derive(Debug)]
+struct FarPtrOutOfBounds {
+    id: u32,
+    offset: usize,
+    size: usize,
+    len: usize,
+}
+
+impl fmt::Display for FarPtrOutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "far pointer to {} bytes at offset {} is out of bounds for \
+             segment {} of length {}",
+            self.size, self.offset, self.id, self.len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FarPtrOutOfBounds {}
+
+/// A set of loaded segments that far pointers can be resolved against.
+///
+/// Segments only need to be available when they are accessed, so large
+/// segmented archives can be loaded or memory-mapped piecewise.
+pub trait Segments<'a> {
+    /// Returns the bytes of the segment with the given ID, or `None` if it is
+    /// not loaded.
+    fn segment(&self, id: u32) -> Option<&'a [u8]>;
+}
+
+impl<'a, B: AsRef<[u8]>> Segments<'a> for &'a [B] {
+    #[inline]
+    fn segment(&self, id: u32) -> Option<&'a [u8]> {
+        self.get(id as usize).map(AsRef::as_ref)
+    }
+}
+
+impl<'a, T, E> Segments<'a> for Strategy<T, E>
+where
+    T: Segments<'a> + ?Sized,
+{
+    #[inline]
+    fn segment(&self, id: u32) -> Option<&'a [u8]> {
+        T::segment(self, id)
+    }
+}
+
+/// A pointer to a value located in a different segment.
+///
+/// Unlike a relative pointer, a far pointer does not depend on where the
+/// segments are located in memory. The value it points to can only be accessed
+/// with the [`Segments`] that the archive was written with.
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+pub struct FarPtr<T> {
+    segment: ArchivedU32,
+    offset: ArchivedUsize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> FarPtr<T> {
+    /// Returns the ID of the segment that the value is located in.
+    #[inline]
+    pub fn segment(&self) -> u32 {
+        self.segment.to_native()
+    }
+
+    /// Returns the offset of the value in its segment.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset.to_native() as usize
+    }
+
+    /// Resolves a far pointer from a resolver.
+    #[inline]
+    pub fn resolve_from_resolver(resolver: FarResolver, out: Place<Self>) {
+        munge!(let FarPtr { segment, offset, _phantom: _ } = out);
+        segment.write(ArchivedU32::from_native(resolver.segment));
+        offset.write(ArchivedUsize::from_native(resolver.pos as FixedUsize));
+    }
+
+    fn locate<'a, S, E>(&self, segments: &S) -> Result<&'a [u8], E>
+    where
+        S: Segments<'a> + ?Sized,
+        E: Source,
+    {
+        let id = self.segment();
+        let bytes = match segments.segment(id) {
+            Some(bytes) => bytes,
+            None => fail!(MissingSegment { id }),
+        };
+
+        let offset = self.offset();
+        let size = size_of::<T>();
+        if offset
+            .checked_add(size)
+            .map_or(true, |end| end > bytes.len())
+        {
+            fail!(FarPtrOutOfBounds {
+                id,
+                offset,
+                size,
+                len: bytes.len(),
+            });
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl<T: Portable> FarPtr<T> {
+    /// Returns the value this far pointer points to without validating it.
+    ///
+    /// Returns an error if the segment is not loaded or the value is out of
+    /// bounds of the segment.
+    ///
+    /// # Safety
+    ///
+    /// The segment must contain a valid `T` at the offset of this far pointer.
+    #[inline]
+    pub unsafe fn get_unchecked<'a, S, E>(
+        &self,
+        segments: &S,
+    ) -> Result<&'a T, E>
+    where
+        S: Segments<'a> + ?Sized,
+        E: Source,
+    {
+        let bytes = self.locate::<S, E>(segments)?;
+        // SAFETY: The caller has guaranteed that the segment contains a valid
+        // `T` at the offset of this far pointer.
+        Ok(unsafe { access_pos_unchecked::<T>(bytes, self.offset()) })
+    }
+
+    /// Validates and returns the value this far pointer points to.
+    ///
+    /// The segment is validated as an independent archive whose root is the
+    /// value this far pointer points to.
+    #[cfg(feature = "bytecheck")]
+    #[inline]
+    pub fn get<'a, S, E>(&self, segments: &S) -> Result<&'a T, E>
+    where
+        S: Segments<'a> + ?Sized,
+        T: bytecheck::CheckBytes<
+            Strategy<crate::validation::validators::DefaultValidator, E>,
+        >,
+        E: Source,
+    {
+        let bytes = self.locate::<S, E>(segments)?;
+        crate::validation::util::access_pos::<T, E>(bytes, self.offset())
+    }
+}
+
+impl<T> fmt::Debug for FarPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FarPtr")
+            .field("segment", &self.segment())
+            .field("offset", &self.offset())
+            .finish()
+    }
+}
+
+/// The resolver for [`FarPtr`].
+pub struct FarResolver {
+    segment: u32,
+    pos: usize,
+}
+
+impl FarResolver {
+    /// Creates a new far resolver from the ID of a segment and the position of
+    /// the value in that segment.
+    #[inline]
+    pub fn new(segment: u32, pos: usize) -> Self {
+        Self { segment, pos }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct TooManySegments;
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for TooManySegments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the segment table is full")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooManySegments {}
+
+/// A serializer which can write values to separate segments.
+///
+/// This trait is required to serialize fields with the
+/// [`Far`](crate::with::Far) wrapper.
+#[cfg(feature = "alloc")]
+pub trait Segmenting<E = <Self as Fallible>::Error> {
+    /// Adds a segment to the segment table and returns its ID.
+    fn push_segment(&mut self, bytes: AlignedVec) -> Result<u32, E>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, E> Segmenting<E> for Strategy<T, E>
+where
+    T: Segmenting<E> + ?Sized,
+{
+    #[inline]
+    fn push_segment(&mut self, bytes: AlignedVec) -> Result<u32, E> {
+        T::push_segment(self, bytes)
+    }
+}
+
+/// A serializer adapter which collects segments in a segment table.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, segment::Segmented,
+///     ser::AllocSerializer, util::serialize, with::Far, Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Dataset {
+///     name: String,
+///     #[with(Far)]
+///     samples: Vec<u32>,
+/// }
+///
+/// let value = Dataset {
+///     name: "samples".to_string(),
+///     samples: (0..1000).collect(),
+/// };
+///
+/// let mut serializer = Segmented::new(AllocSerializer::default());
+/// serialize::<_, Error>(&value, &mut serializer).unwrap();
+/// let (serializer, segments) = serializer.into_raw_parts();
+/// let bytes = serializer.into_writer();
+/// assert_eq!(segments.len(), 1);
+///
+/// let archived = unsafe { access_unchecked::<ArchivedDataset>(&bytes) };
+/// let samples = unsafe {
+///     archived
+///         .samples
+///         .get_unchecked::<_, Error>(&segments.as_slice())
+///         .unwrap()
+/// };
+/// assert_eq!(samples.len(), 1000);
+/// assert_eq!(samples[999], 999);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default)]
+pub struct Segmented<S> {
+    inner: S,
+    segments: Vec<AlignedVec>,
+}
+
+#[cfg(feature = "alloc")]
+impl<S> Segmented<S> {
+    /// Wraps the given serializer with an empty segment table.
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Returns the segments written so far.
+    #[inline]
+    pub fn segments(&self) -> &[AlignedVec] {
+        &self.segments
+    }
+
+    /// Returns a reference to the wrapped serializer.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped serializer and the segment
+    /// table.
+    #[inline]
+    pub fn into_raw_parts(self) -> (S, Vec<AlignedVec>) {
+        (self.inner, self.segments)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S, E: Source> Segmenting<E> for Segmented<S> {
+    fn push_segment(&mut self, bytes: AlignedVec) -> Result<u32, E> {
+        let id = match u32::try_from(self.segments.len()) {
+            Ok(id) => id,
+            Err(_) => fail!(TooManySegments),
+        };
+        self.segments.push(bytes);
+        Ok(id)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Positional> Positional for Segmented<S> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Writer<E>, E> Writer<E> for Segmented<S> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.inner.position_for(layout)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Allocator<E>, E> Allocator<E> for Segmented<S> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Sharing<E>, E> Sharing<E> for Segmented<S> {
+    #[inline]
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+}
+
+/// A deserializer adapter which can load values from segments.
+///
+/// `WithSegments` forwards [`Pooling`] and [`Metering`] to the wrapped
+/// deserializer.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct WithSegments<'a, B, D> {
+    segments: &'a [B],
+    inner: D,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, B, D> WithSegments<'a, B, D> {
+    /// Wraps the given deserializer with a set of loaded segments.
+    #[inline]
+    pub fn new(segments: &'a [B], inner: D) -> Self {
+        Self { segments, inner }
+    }
+
+    /// Consumes the adapter and returns the wrapped deserializer.
+    #[inline]
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, B: AsRef<[u8]>, D> Segments<'a> for WithSegments<'a, B, D> {
+    #[inline]
+    fn segment(&self, id: u32) -> Option<&'a [u8]> {
+        self.segments.segment(id)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B, D: Pooling<E>, E> Pooling<E> for WithSegments<'_, B, D> {
+    #[inline]
+    fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    unsafe fn add_shared_ptr(
+        &mut self,
+        address: usize,
+        ptr: ErasedPtr,
+        drop: unsafe fn(ErasedPtr),
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `add_shared_ptr` are the same as
+        // the requirements for calling this function.
+        unsafe { self.inner.add_shared_ptr(address, ptr, drop) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B, D: Metering<E>, E> Metering<E> for WithSegments<'_, B, D> {
+    #[inline]
+    fn meter(&mut self, layout: Layout) -> Result<(), E> {
+        self.inner.meter(layout)
+    }
+}
@@ -134,6 +134,27 @@ impl fmt::Display for NoAllocationsToPop {
 #[cfg(feature = "std")]
 impl std::error::Error for NoAllocationsToPop {}
 
+#[cfg(feature = "fallible-alloc")]
+#[derive(Debug)]
+struct AllocationFailed {
+    layout: Layout,
+}
+
+#[cfg(feature = "fallible-alloc")]
+impl fmt::Display for AllocationFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to allocate scratch space with size {} and align {}",
+            self.layout.size(),
+            self.layout.align(),
+        )
+    }
+}
+
+#[cfg(all(feature = "fallible-alloc", feature = "std"))]
+impl std::error::Error for AllocationFailed {}
+
 /// Scratch space that always uses the global allocator.
 ///
 /// This allocator will panic if scratch is popped that it did not allocate. For
@@ -193,8 +214,17 @@ impl<E: Source> Allocator<E> for GlobalAllocator {
                 });
             }
         }
+        #[cfg(feature = "fallible-alloc")]
+        if self.allocations.try_reserve(1).is_err() {
+            fail!(AllocationFailed { layout });
+        }
         // SAFETY: The caller has guaranteed that `layout` has non-zero size.
         let result_ptr = unsafe { alloc(layout) };
+        #[cfg(feature = "fallible-alloc")]
+        if result_ptr.is_null() {
+            fail!(AllocationFailed { layout });
+        }
+        #[cfg(not(feature = "fallible-alloc"))]
         assert!(!result_ptr.is_null());
         self.allocations.push((result_ptr, layout));
         let result_slice =
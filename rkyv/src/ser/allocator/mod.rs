@@ -4,8 +4,8 @@
 mod alloc;
 mod core;
 
-use ::core::{alloc::Layout, ptr::NonNull};
-use rancor::{Fallible, Strategy};
+use ::core::{alloc::Layout, fmt, ptr::NonNull};
+use rancor::{fail, Fallible, Source, Strategy};
 
 #[cfg(feature = "alloc")]
 pub use self::alloc::*;
@@ -106,12 +106,211 @@ where
     }
 }
 
+#[derive(Debug)]
+struct ScratchExhausted {
+    allocated: usize,
+    layout: Layout,
+}
+
+impl fmt::Display for ScratchExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scratch space exhausted: {} bytes were allocated when a request \
+             of size {} and align {} was made, so at least {} bytes are needed",
+            self.allocated,
+            self.layout.size(),
+            self.layout.align(),
+            self.allocated + self.layout.size(),
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScratchExhausted {}
+
+/// An allocator which falls back to a secondary allocator when its primary
+/// allocator runs out of space.
+///
+/// Once an allocation is made from the secondary allocator, all allocations
+/// are made from it until they have all been popped. Because allocations are
+/// popped in reverse order, this always returns allocations to the allocator
+/// they came from.
+///
+/// If the secondary allocator also fails, the error reports how much scratch
+/// space would have been needed. Use [`Unavailable`] as the secondary
+/// allocator to get this report without falling back.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{
+///         allocator::{BufferAllocator, Fallback},
+///         FallbackCoreSerializer,
+///     },
+///     util::{serialize_into, AlignedBytes},
+/// };
+///
+/// let value = vec![vec![1u32, 2], vec![3, 4], vec![5, 6]];
+///
+/// // The primary region is too small, so the secondary region is used.
+/// let serializer = FallbackCoreSerializer::<256, 8, _>::new(
+///     Default::default(),
+///     Fallback::new(
+///         Default::default(),
+///         BufferAllocator::new(AlignedBytes::<64>::default()),
+///     ),
+///     Default::default(),
+/// );
+/// serialize_into::<_, Error>(&value, serializer).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct Fallback<A, B> {
+    primary: A,
+    secondary: B,
+    primary_allocated: usize,
+    secondary_allocations: usize,
+}
+
+impl<A, B> Fallback<A, B> {
+    /// Creates a new fallback allocator from primary and secondary allocators.
+    pub fn new(primary: A, secondary: B) -> Self {
+        Self {
+            primary,
+            secondary,
+            primary_allocated: 0,
+            secondary_allocations: 0,
+        }
+    }
+
+    /// Returns a reference to the primary allocator.
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Returns a reference to the secondary allocator.
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    /// Consumes the fallback allocator and returns the primary and secondary
+    /// allocators.
+    pub fn into_inner(self) -> (A, B) {
+        (self.primary, self.secondary)
+    }
+}
+
+impl<A, B, E> Allocator<E> for Fallback<A, B>
+where
+    A: Allocator<E>,
+    B: Allocator<E>,
+    E: Source,
+{
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        if self.secondary_allocations == 0 {
+            // SAFETY: The safety requirements for `push_alloc` are the same as
+            // the requirements for calling this function.
+            if let Ok(result) = unsafe { self.primary.push_alloc(layout) } {
+                self.primary_allocated += layout.size();
+                return Ok(result);
+            }
+        }
+
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        match unsafe { self.secondary.push_alloc(layout) } {
+            Ok(result) => {
+                self.secondary_allocations += 1;
+                Ok(result)
+            }
+            Err(_) => fail!(ScratchExhausted {
+                allocated: self.primary_allocated,
+                layout,
+            }),
+        }
+    }
+
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        if self.secondary_allocations > 0 {
+            // SAFETY: Allocations are popped in reverse order, so the last
+            // allocation was made from the secondary allocator.
+            unsafe { self.secondary.pop_alloc(ptr, layout)? };
+            self.secondary_allocations -= 1;
+        } else {
+            // SAFETY: Allocations are popped in reverse order, and no live
+            // allocations were made from the secondary allocator.
+            unsafe { self.primary.pop_alloc(ptr, layout)? };
+            self.primary_allocated -= layout.size();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct AllocatorUnavailable;
+
+impl fmt::Display for AllocatorUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no scratch space is available")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AllocatorUnavailable {}
+
+/// An allocator which has no space and fails every allocation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unavailable;
+
+impl<E: Source> Allocator<E> for Unavailable {
+    #[inline]
+    unsafe fn push_alloc(&mut self, _: Layout) -> Result<NonNull<[u8]>, E> {
+        fail!(AllocatorUnavailable);
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(&mut self, _: NonNull<u8>, _: Layout) -> Result<(), E> {
+        fail!(AllocatorUnavailable);
+    }
+}
+
 /// A passthrough allocator that tracks usage.
+///
+/// Serializing representative data with a tracked allocator shows how much
+/// scratch space a [`CoreSerializer`](crate::ser::CoreSerializer) needs, so its
+/// scratch space can be sized without guessing.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{rancor::Error, ser::CoreSerializer, util::serialize_into};
+///
+/// let value = vec![vec![1u32, 2], vec![3, 4]];
+///
+/// let serializer =
+///     CoreSerializer::<256, 256>::default().with_allocation_tracker();
+/// let serializer = serialize_into::<_, Error>(&value, serializer).unwrap();
+///
+/// let tracker = serializer.allocation_tracker();
+/// assert_eq!(tracker.bytes_allocated(), 0);
+/// assert_eq!(tracker.total_allocations(), 1);
+/// assert!(tracker.min_buffer_size() <= 256);
+/// ```
 #[derive(Debug, Default)]
 pub struct AllocationTracker<T> {
     inner: T,
     bytes_allocated: usize,
     allocations: usize,
+    total_allocations: usize,
     max_bytes_allocated: usize,
     max_allocations: usize,
     max_alignment: usize,
@@ -124,12 +323,41 @@ impl<T> AllocationTracker<T> {
             inner,
             bytes_allocated: 0,
             allocations: 0,
+            total_allocations: 0,
             max_bytes_allocated: 0,
             max_allocations: 0,
             max_alignment: 1,
         }
     }
 
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Consumes the allocation tracker and returns the wrapped allocator.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the number of bytes that are currently allocated.
+    pub fn bytes_allocated(&self) -> usize {
+        self.bytes_allocated
+    }
+
+    /// Returns the number of allocations that are currently live.
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Returns the total number of allocations that were made.
+    ///
+    /// Every allocation is paired with a deallocation, so this is also the
+    /// number of push and pop pairs once serialization has finished.
+    pub fn total_allocations(&self) -> usize {
+        self.total_allocations
+    }
+
     /// Returns the maximum number of bytes that were concurrently allocated.
     pub fn max_bytes_allocated(&self) -> usize {
         self.max_bytes_allocated
@@ -173,6 +401,7 @@ impl<T: Allocator<E>, E> Allocator<E> for AllocationTracker<T> {
 
         self.bytes_allocated += layout.size();
         self.allocations += 1;
+        self.total_allocations += 1;
         self.max_bytes_allocated =
             usize::max(self.bytes_allocated, self.max_bytes_allocated);
         self.max_allocations =
@@ -0,0 +1,388 @@
+//! Serialization which places shared pointers in a canonical order.
+//!
+//! Shared pointers like `Arc` and `Rc` are written the first time they are
+//! encountered while serializing. Their positions depend on the order values
+//! are traversed in, so moving an unrelated field can change where every
+//! shared value is written. [`to_bytes_canonical`] instead serializes a value
+//! twice:
+//!
+//! 1. The first pass records the bytes written for each shared value, aligned
+//!    to [`SUBTREE_ALIGN`](crate::ser::reuse::SUBTREE_ALIGN).
+//! 2. The second pass writes those shared values first, ordered by a hash of
+//!    their contents, and then serializes the value normally. Shared pointers
+//!    resolve to the values that were placed up front.
+//!
+//! Shared values which point to other shared values serialized outside of
+//! them can't be moved, and are written in traversal order as usual. Archived
+//! types must not require an alignment greater than `SUBTREE_ALIGN`.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use rkyv::{
+//!     rancor::Error, ser::canonical::to_bytes_canonical, Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Forward {
+//!     a: Arc<String>,
+//!     b: Arc<String>,
+//! }
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Backward {
+//!     b: Arc<String>,
+//!     a: Arc<String>,
+//! }
+//!
+//! let a = Arc::new("apple".to_string());
+//! let b = Arc::new("banana".to_string());
+//!
+//! let forward = Forward {
+//!     a: a.clone(),
+//!     b: b.clone(),
+//! };
+//! let backward = Backward { b, a };
+//!
+//! let forward = to_bytes_canonical::<Error>(&forward).unwrap();
+//! let backward = to_bytes_canonical::<Error>(&backward).unwrap();
+//!
+//! // The shared strings are written in the same order, so only the roots
+//! // differ.
+//! let roots = core::mem::size_of::<ArchivedForward>();
+//! let len = forward.len() - roots;
+//! assert_eq!(forward[..len], backward[..len]);
+//! ```
+
+use core::{alloc::Layout, cell::RefCell, ptr::NonNull};
+#[cfg(feature = "std")]
+use std::collections::hash_map;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::{vec, vec::Vec};
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map;
+use rancor::{Source, Strategy};
+
+use crate::{
+    hash::{hash_value, FxHasher64},
+    ser::{
+        reuse::SUBTREE_ALIGN, AllocSerializer, Allocator, Positional, Sharing,
+        Writer, WriterExt as _,
+    },
+    util::{serialize_into, AlignedVec},
+    Serialize,
+};
+
+// A shared value which has been looked up but not yet added.
+#[derive(Debug)]
+struct Open {
+    address: usize,
+    start: usize,
+    escapes: bool,
+}
+
+// The bytes written for a shared value during the first pass.
+#[derive(Debug)]
+struct Recorded {
+    address: usize,
+    start: usize,
+    root: usize,
+    end: usize,
+    escapes: bool,
+}
+
+#[derive(Debug, Default)]
+struct Recorder {
+    open: Vec<Open>,
+    recorded: Vec<Recorded>,
+    // Whether the last opened value has not written anything yet. Its start
+    // is aligned to `SUBTREE_ALIGN` when it does.
+    pending: bool,
+}
+
+impl Recorder {
+    fn miss(&mut self, address: usize, pos: usize) {
+        if !matches!(self.open.last(), Some(open) if open.address == address) {
+            self.open.push(Open {
+                address,
+                start: pos,
+                escapes: false,
+            });
+            self.pending = true;
+        }
+    }
+
+    fn start(&mut self, pos: usize) {
+        if let Some(open) = self.open.last_mut() {
+            open.start = pos;
+        }
+        self.pending = false;
+    }
+
+    fn hit(&mut self, pos: usize) {
+        // Every open value which started after the hit points outside of its
+        // own bytes.
+        for open in self.open.iter_mut().rev() {
+            if open.start <= pos {
+                break;
+            }
+            open.escapes = true;
+        }
+    }
+
+    fn add(&mut self, address: usize, root: usize, end: usize) {
+        self.pending = false;
+        // Values which were looked up but never added are discarded.
+        while let Some(open) = self.open.pop() {
+            if open.address == address {
+                self.recorded.push(Recorded {
+                    address,
+                    start: open.start,
+                    root,
+                    end,
+                    escapes: open.escapes,
+                });
+                return;
+            }
+        }
+    }
+
+    fn into_blocks(mut self, bytes: &[u8]) -> Vec<Block> {
+        self.recorded
+            .sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut blocks = Vec::<Block>::new();
+        for recorded in self.recorded {
+            let member = (recorded.address, recorded.root);
+            match blocks.last_mut() {
+                Some(block)
+                    if recorded.start < block.end
+                        && recorded.end <= block.end =>
+                {
+                    block.members.push(member);
+                }
+                _ => blocks.push(Block {
+                    start: recorded.start,
+                    end: recorded.end,
+                    escapes: recorded.escapes,
+                    hash: 0,
+                    members: vec![member],
+                }),
+            }
+        }
+
+        // Empty blocks are skipped because shared values must be written at
+        // unique positions.
+        blocks.retain(|block| !block.escapes && block.start < block.end);
+        for block in blocks.iter_mut() {
+            block.hash =
+                hash_value::<[u8], FxHasher64>(&bytes[block.start..block.end]);
+        }
+        blocks.sort_by(|a, b| {
+            a.hash
+                .cmp(&b.hash)
+                .then_with(|| bytes[a.start..a.end].cmp(&bytes[b.start..b.end]))
+        });
+        blocks
+    }
+}
+
+// A contiguous range of the first pass which holds one or more shared values
+// and can be copied as-is.
+#[derive(Debug)]
+struct Block {
+    start: usize,
+    end: usize,
+    escapes: bool,
+    hash: u64,
+    members: Vec<(usize, usize)>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record(RefCell<Recorder>),
+    Place(hash_map::HashMap<usize, usize>),
+}
+
+/// A serializer adapter which places shared pointers in a canonical order.
+///
+/// This is used by [`to_bytes_canonical`], which drives both of its passes.
+#[derive(Debug)]
+pub struct Canonical<S> {
+    inner: S,
+    mode: Mode,
+}
+
+impl<S> Canonical<S> {
+    fn record(inner: S) -> Self {
+        Self {
+            inner,
+            mode: Mode::Record(RefCell::new(Recorder::default())),
+        }
+    }
+
+    fn place(inner: S) -> Self {
+        Self {
+            inner,
+            mode: Mode::Place(hash_map::HashMap::new()),
+        }
+    }
+
+    fn place_blocks<E>(
+        &mut self,
+        blocks: &[Block],
+        bytes: &[u8],
+    ) -> Result<(), E>
+    where
+        S: Writer<E>,
+    {
+        let positions = match &mut self.mode {
+            Mode::Place(positions) => positions,
+            Mode::Record(_) => return Ok(()),
+        };
+        for block in blocks {
+            let start = self.inner.align(SUBTREE_ALIGN)?;
+            self.inner.write(&bytes[block.start..block.end])?;
+            for &(address, root) in block.members.iter() {
+                positions.insert(address, start + (root - block.start));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the wrapped serializer.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped serializer.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Positional> Positional for Canonical<S> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<S: Writer<E>, E> Writer<E> for Canonical<S> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        if let Mode::Record(recorder) = &mut self.mode {
+            let recorder = recorder.get_mut();
+            if recorder.pending {
+                recorder.start(self.inner.align(SUBTREE_ALIGN)?);
+            }
+        }
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    fn position_for(&mut self, layout: Layout) -> usize {
+        if let Mode::Record(recorder) = &mut self.mode {
+            let recorder = recorder.get_mut();
+            if recorder.pending {
+                let layout = match layout.align_to(SUBTREE_ALIGN) {
+                    Ok(layout) => layout,
+                    Err(_) => layout,
+                };
+                let pos = self.inner.position_for(layout);
+                recorder.start(pos);
+                return pos;
+            }
+        }
+        self.inner.position_for(layout)
+    }
+}
+
+impl<S: Allocator<E>, E> Allocator<E> for Canonical<S> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+impl<S: Sharing<E> + Positional, E> Sharing<E> for Canonical<S> {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        match &self.mode {
+            Mode::Record(recorder) => {
+                let result = self.inner.get_shared_ptr(address);
+                let mut recorder = recorder.borrow_mut();
+                match result {
+                    Some(pos) => recorder.hit(pos),
+                    None => recorder.miss(address, self.inner.pos()),
+                }
+                result
+            }
+            Mode::Place(positions) => match positions.get(&address) {
+                Some(pos) => Some(*pos),
+                None => self.inner.get_shared_ptr(address),
+            },
+        }
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        if let Mode::Record(recorder) = &mut self.mode {
+            recorder.get_mut().add(address, pos, self.inner.pos());
+        }
+        self.inner.add_shared_ptr(address, pos)
+    }
+}
+
+/// Serializes the given value with its shared pointers placed in a canonical
+/// order, and returns the resulting bytes in an [`AlignedVec`].
+///
+/// The value is serialized twice, so this takes about twice as long as
+/// [`to_bytes`](crate::to_bytes). See the [module docs](self) for more
+/// information.
+pub fn to_bytes_canonical<E: Source>(
+    value: &impl Serialize<Strategy<Canonical<AllocSerializer>, E>>,
+) -> Result<AlignedVec, E> {
+    let recorded =
+        serialize_into(value, Canonical::record(AllocSerializer::default()))?;
+    let (scratch, recorder) = match recorded.mode {
+        Mode::Record(recorder) => {
+            (recorded.inner.into_writer(), recorder.into_inner())
+        }
+        Mode::Place(_) => unreachable!(),
+    };
+    let blocks = recorder.into_blocks(&scratch);
+
+    let mut serializer = Canonical::place(AllocSerializer::default());
+    serializer.place_blocks::<E>(&blocks, &scratch)?;
+    Ok(serialize_into(value, serializer)?
+        .into_inner()
+        .into_writer())
+}
@@ -0,0 +1,111 @@
+//! Custom serializer capabilities which can be looked up by type.
+//!
+//! [`Composite`](crate::ser::Composite) serializers have an extension slot
+//! which can hold any additional components, like an interner or a tracer.
+//! `Serialize` impls can require a component with a [`GetComponent`] bound
+//! and look it up by type, without needing a serializer newtype which
+//! forwards every serializer trait.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     rancor::{Error, Fallible},
+//!     ser::{component::GetComponent, AllocSerializer, Writer},
+//!     string::{ArchivedString, StringResolver},
+//!     util::serialize_into,
+//!     Archive, Place, Serialize,
+//! };
+//!
+//! #[derive(Default)]
+//! struct Counter {
+//!     strings: usize,
+//! }
+//!
+//! struct Name(String);
+//!
+//! impl Archive for Name {
+//!     type Archived = ArchivedString;
+//!     type Resolver = StringResolver;
+//!
+//!     fn resolve(
+//!         &self,
+//!         resolver: Self::Resolver,
+//!         out: Place<Self::Archived>,
+//!     ) {
+//!         ArchivedString::resolve_from_str(&self.0, resolver, out);
+//!     }
+//! }
+//!
+//! impl<S> Serialize<S> for Name
+//! where
+//!     S: Fallible + Writer + GetComponent<Counter> + ?Sized,
+//! {
+//!     fn serialize(
+//!         &self,
+//!         serializer: &mut S,
+//!     ) -> Result<Self::Resolver, S::Error> {
+//!         serializer.component_mut().strings += 1;
+//!         ArchivedString::serialize_from_str(&self.0, serializer)
+//!     }
+//! }
+//!
+//! let serializer =
+//!     AllocSerializer::default().with_extension(Counter::default());
+//! let names = vec![Name("alice".to_string()), Name("bob".to_string())];
+//! let serializer = serialize_into::<_, Error>(&names, serializer).unwrap();
+//! assert_eq!(serializer.extension.strings, 2);
+//! ```
+
+use rancor::Strategy;
+
+/// A serializer which has a component of type `T`.
+pub trait GetComponent<T: ?Sized> {
+    /// Returns a reference to the component.
+    fn component(&self) -> &T;
+
+    /// Returns a mutable reference to the component.
+    fn component_mut(&mut self) -> &mut T;
+}
+
+impl<S, T, E> GetComponent<T> for Strategy<S, E>
+where
+    S: GetComponent<T> + ?Sized,
+    T: ?Sized,
+{
+    #[inline]
+    fn component(&self) -> &T {
+        S::component(self)
+    }
+
+    #[inline]
+    fn component_mut(&mut self) -> &mut T {
+        S::component_mut(self)
+    }
+}
+
+/// A set of serializer components which contains a component of type `T`.
+///
+/// Every type is a component pack containing only itself. To put several
+/// components in the extension slot of a [`Composite`](crate::ser::Composite)
+/// serializer, implement `ComponentPack` on a struct for each of its
+/// components.
+pub trait ComponentPack<T: ?Sized> {
+    /// Returns a reference to the component.
+    fn get(&self) -> &T;
+
+    /// Returns a mutable reference to the component.
+    fn get_mut(&mut self) -> &mut T;
+}
+
+impl<T> ComponentPack<T> for T {
+    #[inline]
+    fn get(&self) -> &T {
+        self
+    }
+
+    #[inline]
+    fn get_mut(&mut self) -> &mut T {
+        self
+    }
+}
@@ -0,0 +1,180 @@
+//! Incremental serialization which can be driven in steps.
+//!
+//! Archiving a large value in one call may take longer than a soft-real-time
+//! loop can afford. [`IncrementalVec`] splits the serialization of a slice
+//! into steps, each of which writes roughly a configurable number of bytes
+//! before returning [`Poll::Pending`]. Calling
+//! [`poll_serialize`](IncrementalVec::poll_serialize) again resumes where the
+//! previous call left off.
+//!
+//! The granularity of a step is a single element: an element is never split
+//! across calls, so a step may write more than the requested budget if an
+//! individual element is larger than it.
+
+use core::{mem::MaybeUninit, task::Poll};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use rancor::Fallible;
+
+use crate::{
+    ser::{Writer, WriterExt as _},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Place, Serialize,
+};
+
+enum State {
+    Serializing,
+    Resolving { pos: usize, index: usize },
+    Root { pos: usize },
+    Done,
+}
+
+/// A slice which is serialized in steps as an archived `Vec`.
+///
+/// The serializer must not be used for anything else between the first call
+/// to [`poll_serialize`](IncrementalVec::poll_serialize) and the call which
+/// returns [`Poll::Ready`].
+///
+/// # Example
+///
+/// ```
+/// use core::{mem::size_of, task::Poll};
+///
+/// use rkyv::{
+///     access_unchecked,
+///     rancor::{Error, Strategy},
+///     ser::incremental::IncrementalVec,
+///     util::AlignedVec,
+///     Archived,
+/// };
+///
+/// let values = (0..1000u32).collect::<Vec<_>>();
+///
+/// let mut writer = AlignedVec::new();
+/// let mut incremental = IncrementalVec::new(&values);
+/// let mut steps = 0;
+/// let pos = loop {
+///     steps += 1;
+///     let serializer = Strategy::<_, Error>::wrap(&mut writer);
+///     match incremental.poll_serialize(serializer, 256) {
+///         Poll::Ready(result) => break result.unwrap(),
+///         Poll::Pending => continue,
+///     }
+/// };
+/// assert!(steps > 1);
+/// assert_eq!(pos + size_of::<Archived<Vec<u32>>>(), writer.len());
+///
+/// let archived =
+///     unsafe { access_unchecked::<Archived<Vec<u32>>>(writer.as_slice()) };
+/// assert_eq!(archived, &values);
+/// ```
+pub struct IncrementalVec<'a, T: Archive> {
+    items: &'a [T],
+    resolvers: Vec<T::Resolver>,
+    state: State,
+}
+
+impl<'a, T: Archive> IncrementalVec<'a, T> {
+    /// Creates a new incremental serializer for the given slice.
+    #[inline]
+    pub fn new(items: &'a [T]) -> Self {
+        Self {
+            items,
+            resolvers: Vec::with_capacity(items.len()),
+            state: State::Serializing,
+        }
+    }
+
+    /// Returns whether serialization has completed.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Performs serialization steps until at least `budget` bytes have been
+    /// written or serialization completes.
+    ///
+    /// Returns `Poll::Ready` with the position of the archived root `Vec` once
+    /// serialization has completed, and `Poll::Pending` if more steps are
+    /// required.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called again after it has returned `Poll::Ready`.
+    pub fn poll_serialize<S>(
+        &mut self,
+        serializer: &mut S,
+        budget: usize,
+    ) -> Poll<Result<usize, S::Error>>
+    where
+        T: Serialize<S>,
+        S: Fallible + Writer + ?Sized,
+    {
+        let start = serializer.pos();
+        loop {
+            match self.step(serializer) {
+                Ok(Some(pos)) => return Poll::Ready(Ok(pos)),
+                Ok(None) => (),
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+            if serializer.pos() - start >= budget {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    fn step<S>(&mut self, serializer: &mut S) -> Result<Option<usize>, S::Error>
+    where
+        T: Serialize<S>,
+        S: Fallible + Writer + ?Sized,
+    {
+        match self.state {
+            State::Serializing => {
+                if let Some(item) = self.items.get(self.resolvers.len()) {
+                    self.resolvers.push(item.serialize(serializer)?);
+                } else {
+                    let pos = serializer.align_for::<T::Archived>()?;
+                    self.resolvers.reverse();
+                    self.state = State::Resolving { pos, index: 0 };
+                }
+            }
+            State::Resolving { pos, ref mut index } => {
+                if let Some(resolver) = self.resolvers.pop() {
+                    unsafe {
+                        serializer
+                            .resolve_aligned(&self.items[*index], resolver)?;
+                    }
+                    *index += 1;
+                } else {
+                    self.state = State::Root { pos };
+                }
+            }
+            State::Root { pos } => {
+                let root_pos =
+                    serializer.align_for::<ArchivedVec<T::Archived>>()?;
+                let mut resolved =
+                    MaybeUninit::<ArchivedVec<T::Archived>>::zeroed();
+                // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+                // `MaybeUninit`, and so is properly aligned, dereferenceable,
+                // and all of its bytes are initialized.
+                let out = unsafe {
+                    Place::new_unchecked(root_pos, resolved.as_mut_ptr())
+                };
+                ArchivedVec::resolve_from_len(
+                    self.items.len(),
+                    VecResolver::from_pos(pos),
+                    out,
+                );
+                serializer.write(out.as_slice())?;
+                serializer.finish()?;
+                self.state = State::Done;
+                return Ok(Some(root_pos));
+            }
+            State::Done => {
+                panic!("`poll_serialize` called after serialization completed")
+            }
+        }
+        Ok(None)
+    }
+}
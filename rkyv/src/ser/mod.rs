@@ -1,38 +1,121 @@
 //! Serialization traits and adapters.
 
 pub mod allocator;
+#[cfg(feature = "alloc")]
+pub mod canonical;
+pub mod component;
+#[cfg(feature = "alloc")]
+pub mod incremental;
+#[cfg(feature = "alloc")]
+pub mod paged;
+pub mod reuse;
 pub mod sharing;
+#[cfg(feature = "alloc")]
+pub mod stats;
+#[cfg(feature = "alloc")]
+pub mod stream;
 pub mod writer;
 
 use ::core::{alloc::Layout, ptr::NonNull};
+use rancor::Fallible;
 
 #[doc(inline)]
 pub use self::{
     allocator::Allocator,
+    component::GetComponent,
     sharing::{Sharing, SharingExt},
     writer::{Positional, Writer, WriterExt},
 };
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{allocator::GlobalAllocator, sharing::Unify, writer::MaxAlign},
+    util::AlignedVec,
+};
 use crate::{
     ser::{
-        allocator::BufferAllocator, sharing::Duplicate, writer::BufferWriter,
+        allocator::{AllocationTracker, BufferAllocator, Fallback},
+        component::ComponentPack,
+        sharing::Duplicate,
+        writer::BufferWriter,
     },
     util::AlignedBytes,
 };
-#[cfg(feature = "alloc")]
-use crate::{
-    ser::{allocator::GlobalAllocator, sharing::Unify},
-    util::AlignedVec,
-};
+
+/// An object-safe serializer.
+///
+/// Generic bounds like `S: Writer<E> + Allocator<E> + Sharing<E>` can't cross a
+/// `dyn` boundary, such as the interface of a plugin. `DynSerializer` combines
+/// all of the serializer capabilities into a single object-safe trait, and
+/// `&mut dyn DynSerializer<E>` is itself a serializer with error type `E`.
+///
+/// Any type which implements [`Writer`], [`Allocator`], and [`Sharing`]
+/// implements `DynSerializer` automatically.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::{Error, Strategy},
+///     ser::{AllocSerializer, AsDynSerializer, DynSerializer},
+///     Serialize,
+/// };
+///
+/// fn serialize_plugin_data(
+///     serializer: &mut dyn DynSerializer<Error>,
+/// ) -> Result<usize, Error> {
+///     vec![1u32, 2, 3].serialize_and_resolve(serializer)
+/// }
+///
+/// let mut serializer = AllocSerializer::default();
+/// let pos = serialize_plugin_data(
+///     Strategy::<_, Error>::wrap(&mut serializer).as_dyn_serializer(),
+/// )
+/// .unwrap();
+/// assert!(pos > 0);
+/// ```
+pub trait DynSerializer<E>: Writer<E> + Allocator<E> + Sharing<E> {}
+
+impl<E> Fallible for dyn DynSerializer<E> + '_ {
+    type Error = E;
+}
+
+impl<S: Writer<E> + Allocator<E> + Sharing<E>, E> DynSerializer<E> for S {}
+
+/// Converts a serializer into a [`DynSerializer`] trait object.
+pub trait AsDynSerializer<E> {
+    /// Returns this serializer as a `DynSerializer` trait object.
+    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E>;
+}
+
+impl<S: DynSerializer<E>, E> AsDynSerializer<E> for S {
+    #[inline]
+    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E> {
+        self as &mut dyn DynSerializer<E>
+    }
+}
+
+impl<E> AsDynSerializer<E> for dyn DynSerializer<E> + '_ {
+    #[inline]
+    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E> {
+        self
+    }
+}
 
 /// A serializer built from composeable pieces.
+///
+/// The extension slot `X` can hold additional components, which `Serialize`
+/// impls can look up by type with [`GetComponent`]. See the
+/// [`component`] module for more information.
 #[derive(Debug, Default)]
-pub struct Composite<W = (), A = (), S = ()> {
+pub struct Composite<W = (), A = (), S = (), X = ()> {
     /// The writer of the `Composite` serializer.
     pub writer: W,
     /// The allocator of the `Composite` serializer.
     pub allocator: A,
     /// The shared pointer strategy of the `Composite` serializer.
     pub share: S,
+    /// The extension components of the `Composite` serializer.
+    pub extension: X,
 }
 
 impl<W, A, S> Composite<W, A, S> {
@@ -44,10 +127,40 @@ impl<W, A, S> Composite<W, A, S> {
             writer,
             allocator,
             share,
+            extension: (),
+        }
+    }
+}
+
+impl<W, A, S, X> Composite<W, A, S, X> {
+    /// Replaces the extension components of the composite serializer.
+    #[inline]
+    pub fn with_extension<Y>(self, extension: Y) -> Composite<W, A, S, Y> {
+        Composite {
+            writer: self.writer,
+            allocator: self.allocator,
+            share: self.share,
+            extension,
+        }
+    }
+
+    /// Wraps the allocator of the composite serializer with an
+    /// [`AllocationTracker`] to collect scratch space statistics.
+    #[inline]
+    pub fn with_allocation_tracker(
+        self,
+    ) -> Composite<W, AllocationTracker<A>, S, X> {
+        Composite {
+            writer: self.writer,
+            allocator: AllocationTracker::new(self.allocator),
+            share: self.share,
+            extension: self.extension,
         }
     }
 
     /// Consumes the composite serializer and returns the components.
+    ///
+    /// The extension components are discarded.
     #[inline]
     pub fn into_raw_parts(self) -> (W, A, S) {
         (self.writer, self.allocator, self.share)
@@ -62,21 +175,44 @@ impl<W, A, S> Composite<W, A, S> {
     }
 }
 
-impl<W: Positional, A, S> Positional for Composite<W, A, S> {
+impl<W, A, S, X> Composite<W, AllocationTracker<A>, S, X> {
+    /// Returns the allocation tracker of the composite serializer.
+    #[inline]
+    pub fn allocation_tracker(&self) -> &AllocationTracker<A> {
+        &self.allocator
+    }
+}
+
+impl<W: Positional, A, S, X> Positional for Composite<W, A, S, X> {
     #[inline]
     fn pos(&self) -> usize {
         self.writer.pos()
     }
 }
 
-impl<W: Writer<E>, A, S, E> Writer<E> for Composite<W, A, S> {
+impl<W: Writer<E>, A, S, X, E> Writer<E> for Composite<W, A, S, X> {
     #[inline]
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         self.writer.write(bytes)
     }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.writer.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.writer.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.writer.position_for(layout)
+    }
 }
 
-impl<W, A: Allocator<E>, S, E> Allocator<E> for Composite<W, A, S> {
+impl<W, A: Allocator<E>, S, X, E> Allocator<E> for Composite<W, A, S, X> {
     #[inline]
     unsafe fn push_alloc(
         &mut self,
@@ -99,7 +235,7 @@ impl<W, A: Allocator<E>, S, E> Allocator<E> for Composite<W, A, S> {
     }
 }
 
-impl<W, A, S: Sharing<E>, E> Sharing<E> for Composite<W, A, S> {
+impl<W, A, S: Sharing<E>, X, E> Sharing<E> for Composite<W, A, S, X> {
     #[inline]
     fn get_shared_ptr(&self, address: usize) -> Option<usize> {
         self.share.get_shared_ptr(address)
@@ -111,6 +247,22 @@ impl<W, A, S: Sharing<E>, E> Sharing<E> for Composite<W, A, S> {
     }
 }
 
+impl<W, A, S, X, T> GetComponent<T> for Composite<W, A, S, X>
+where
+    X: ComponentPack<T>,
+    T: ?Sized,
+{
+    #[inline]
+    fn component(&self) -> &T {
+        self.extension.get()
+    }
+
+    #[inline]
+    fn component_mut(&mut self) -> &mut T {
+        self.extension.get_mut()
+    }
+}
+
 /// A serializer suitable for environments where allocations cannot be made.
 ///
 /// `CoreSerializer` takes two arguments: the amount of serialization memory to
@@ -122,6 +274,17 @@ pub type CoreSerializer<const W: usize, const A: usize> = Composite<
     Duplicate,
 >;
 
+/// A serializer suitable for environments where allocations cannot be made,
+/// which falls back to a secondary allocator when its scratch space runs out.
+///
+/// Use [`Unavailable`](allocator::Unavailable) as the secondary allocator to
+/// report how much scratch space was needed when it runs out.
+pub type FallbackCoreSerializer<const W: usize, const A: usize, B> = Composite<
+    BufferWriter<AlignedBytes<W>>,
+    Fallback<BufferAllocator<AlignedBytes<A>>, B>,
+    Duplicate,
+>;
+
 /// A general-purpose serializer suitable for environments where allocations can
 /// be made.
 #[cfg(feature = "alloc")]
@@ -131,3 +294,11 @@ pub type AllocSerializer = Composite<
     GlobalAllocator,
     Unify,
 >;
+
+/// A general-purpose serializer which writes archives with a maximum alignment
+/// of `N`.
+///
+/// See [`MaxAlign`] for more information.
+#[cfg(feature = "alloc")]
+pub type MaxAlignSerializer<const N: usize> =
+    Composite<MaxAlign<AlignedVec<N>, N>, GlobalAllocator, Unify>;
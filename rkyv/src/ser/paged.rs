@@ -0,0 +1,277 @@
+//! Page-aligned archive layouts.
+//!
+//! Archives which are read through a page cache or memory-mapped from disk
+//! touch every page that an object overlaps. [`PagedWriter`] pads the archive
+//! so that objects no larger than a page never straddle a page boundary, and
+//! records which pages each object was written to.
+
+use core::{alloc::Layout, ptr::NonNull};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+
+use crate::ser::{Allocator, Positional, Sharing, Writer};
+
+/// The placement of an object written by a [`PagedWriter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PagedObject {
+    pos: usize,
+    len: usize,
+    first_page: usize,
+    last_page: usize,
+}
+
+impl PagedObject {
+    /// Returns the position of the object in the archive.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the size of the object in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the object is zero-sized.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the index of the first page that the object is written to.
+    #[inline]
+    pub fn first_page(&self) -> usize {
+        self.first_page
+    }
+
+    /// Returns the index of the last page that the object is written to.
+    ///
+    /// This is only different from the first page for objects larger than a
+    /// page.
+    #[inline]
+    pub fn last_page(&self) -> usize {
+        self.last_page
+    }
+}
+
+/// A writer adapter which lays an archive out in fixed-size pages.
+///
+/// Before an object is written, `PagedWriter` checks whether it would cross
+/// a page boundary. If it would and the object fits in a page, the writer is
+/// padded with zeroes up to the start of the next page first. Objects larger
+/// than a page are written at their normal position and span as many pages as
+/// they need to.
+///
+/// Objects are placed with [`Writer::position_for`], which is used by all of
+/// the archived types in this crate when they align themselves. Bytes written
+/// directly with [`Writer::write`] without being placed first are not moved
+/// and are not recorded. Every placed object is recorded along with the pages
+/// it was written to, and can be looked up with
+/// [`objects`](PagedWriter::objects) and
+/// [`objects_on_page`](PagedWriter::objects_on_page).
+///
+/// The archive is not padded to a whole number of pages when it is finished,
+/// since that would move the root object away from the end of the buffer.
+///
+/// `PagedWriter` forwards [`Allocator`] and [`Sharing`] to the wrapped
+/// serializer, so it can wrap a complete serializer like
+/// [`AllocSerializer`](crate::ser::AllocSerializer).
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked,
+///     rancor::Error,
+///     ser::{paged::PagedWriter, AllocSerializer},
+///     util::serialize,
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Record {
+///     id: u64,
+///     tags: Vec<String>,
+/// }
+///
+/// let records = (0..20)
+///     .map(|id| Record {
+///         id,
+///         tags: vec![format!("a tag which is not inlined #{}", id)],
+///     })
+///     .collect::<Vec<_>>();
+///
+/// let mut serializer = PagedWriter::new(AllocSerializer::default(), 256);
+/// serialize::<_, Error>(&records, &mut serializer).unwrap();
+/// let (serializer, objects) = serializer.into_raw_parts();
+///
+/// for object in objects.iter() {
+///     if object.len() <= 256 {
+///         assert_eq!(object.first_page(), object.last_page());
+///     }
+/// }
+///
+/// let bytes = serializer.into_writer();
+/// let archived =
+///     unsafe { access_unchecked::<rkyv::Archived<Vec<Record>>>(&bytes) };
+/// assert_eq!(archived[19].id, 19);
+/// assert_eq!(archived[19].tags[0], "a tag which is not inlined #19");
+/// ```
+#[derive(Debug)]
+pub struct PagedWriter<W> {
+    inner: W,
+    page_size: usize,
+    objects: Vec<PagedObject>,
+}
+
+impl<W> PagedWriter<W> {
+    /// Wraps the given writer with pages of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is not a power of two.
+    #[inline]
+    pub fn new(inner: W, page_size: usize) -> Self {
+        assert!(
+            page_size.is_power_of_two(),
+            "page size must be a power of two",
+        );
+
+        Self {
+            inner,
+            page_size,
+            objects: Vec::new(),
+        }
+    }
+
+    /// Returns the size of the pages in bytes.
+    #[inline]
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Returns the objects placed so far, in the order that they were placed.
+    #[inline]
+    pub fn objects(&self) -> &[PagedObject] {
+        &self.objects
+    }
+
+    /// Returns an iterator over the objects which are written to the given
+    /// page.
+    #[inline]
+    pub fn objects_on_page(
+        &self,
+        page: usize,
+    ) -> impl Iterator<Item = &PagedObject> {
+        self.objects.iter().filter(move |object| {
+            object.first_page <= page && page <= object.last_page
+        })
+    }
+
+    /// Returns a reference to the wrapped writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped writer and the placements
+    /// of the objects written to it.
+    #[inline]
+    pub fn into_raw_parts(self) -> (W, Vec<PagedObject>) {
+        (self.inner, self.objects)
+    }
+}
+
+impl<W: Positional> PagedWriter<W> {
+    /// Returns the number of pages written to so far, including the last
+    /// partially-filled page.
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        (self.inner.pos() + self.page_size - 1) / self.page_size
+    }
+}
+
+impl<W: Positional> Positional for PagedWriter<W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, E> Writer<E> for PagedWriter<W> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    fn position_for(&mut self, layout: Layout) -> usize {
+        let mut pos = self.inner.position_for(layout);
+        let len = layout.size();
+        if len == 0 {
+            return pos;
+        }
+
+        let offset = pos & (self.page_size - 1);
+        if len <= self.page_size && offset + len > self.page_size {
+            // The object fits in a page but would cross a page boundary, so
+            // move it to the start of the next page. Page boundaries are
+            // aligned for any object which fits in a page.
+            pos += self.page_size - offset;
+        }
+
+        self.objects.push(PagedObject {
+            pos,
+            len,
+            first_page: pos / self.page_size,
+            last_page: (pos + len - 1) / self.page_size,
+        });
+        pos
+    }
+}
+
+impl<W: Allocator<E>, E> Allocator<E> for PagedWriter<W> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+impl<W: Sharing<E>, E> Sharing<E> for PagedWriter<W> {
+    #[inline]
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+}
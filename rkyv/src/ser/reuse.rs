@@ -0,0 +1,381 @@
+//! Re-serialization which copies unchanged values from a previous archive.
+//!
+//! **This API is experimental.**
+//!
+//! When only a small part of a large value changes between serializations,
+//! most of the new archive is the same as the previous one. A [`Tracked`]
+//! value remembers where it was written in the last archive it was serialized
+//! into, and whether it has been modified since. When it is serialized again
+//! with a [`Reuse`] adapter over that archive, an unmodified value is copied
+//! from the previous archive byte-for-byte instead of being serialized.
+//!
+//! Tracked values are archived out-of-line, like a `Box`. Copying a value only
+//! works if everything it points to was written along with it, so a tracked
+//! value must not contain shared pointers which are also serialized outside of
+//! it. Its archived form must also not require an alignment greater than
+//! [`SUBTREE_ALIGN`].
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked,
+//!     rancor::Error,
+//!     ser::{
+//!         reuse::{Reuse, Tracked},
+//!         AllocSerializer,
+//!     },
+//!     util::{serialize, AlignedVec},
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! struct World {
+//!     terrain: Tracked<Vec<u8>>,
+//!     players: Tracked<Vec<String>>,
+//! }
+//!
+//! fn save(world: &World, previous: &[u8]) -> (AlignedVec, usize) {
+//!     let mut serializer = Reuse::new(AllocSerializer::default(), previous);
+//!     serialize::<_, Error>(world, &mut serializer).unwrap();
+//!     let reused = serializer.reused();
+//!     (serializer.into_inner().into_writer(), reused)
+//! }
+//!
+//! let mut world = World {
+//!     terrain: Tracked::new(vec![0; 4096]),
+//!     players: Tracked::new(vec!["alice".to_string()]),
+//! };
+//! let (bytes, reused) = save(&world, &[]);
+//! assert_eq!(reused, 0);
+//!
+//! // Only the players are serialized again; the terrain is copied.
+//! world.players.get_mut().push("bob".to_string());
+//! let (bytes, reused) = save(&world, &bytes);
+//! assert_eq!(reused, 1);
+//!
+//! let archived = unsafe { access_unchecked::<ArchivedWorld>(&bytes) };
+//! assert_eq!(archived.terrain.len(), 4096);
+//! assert_eq!(archived.players[1], "bob");
+//! ```
+
+use core::{alloc::Layout, cell::Cell, fmt, ops::Deref, ptr::NonNull};
+
+use rancor::{Fallible, Strategy};
+
+use crate::{
+    boxed::{ArchivedBox, BoxResolver},
+    ser::{Allocator, Positional, Sharing, Writer, WriterExt as _},
+    Archive, Deserialize, Place, Serialize,
+};
+
+/// The alignment that tracked values are written at.
+///
+/// Copied values keep their alignment as long as none of the types inside
+/// them require a greater alignment than this.
+pub const SUBTREE_ALIGN: usize = 16;
+
+/// A serializer which can copy values from a previous archive.
+///
+/// This trait is required to serialize [`Tracked`] values.
+pub trait Reusing<E = <Self as Fallible>::Error> {
+    /// Copies the given range of bytes from the previous archive, aligned to
+    /// [`SUBTREE_ALIGN`].
+    ///
+    /// Returns the position the bytes were copied to, or `None` if the range
+    /// is not in the previous archive.
+    fn reuse(&mut self, start: usize, end: usize) -> Result<Option<usize>, E>;
+}
+
+impl<T, E> Reusing<E> for Strategy<T, E>
+where
+    T: Reusing<E> + ?Sized,
+{
+    #[inline]
+    fn reuse(&mut self, start: usize, end: usize) -> Result<Option<usize>, E> {
+        T::reuse(self, start, end)
+    }
+}
+
+/// A serializer adapter which copies unmodified [`Tracked`] values from a
+/// previous archive.
+///
+/// The previous archive must be the complete output of the last serialization
+/// of the tracked values. Pass an empty slice when there is no previous
+/// archive.
+#[derive(Debug)]
+pub struct Reuse<'a, S> {
+    inner: S,
+    previous: &'a [u8],
+    reused: usize,
+    reused_bytes: usize,
+}
+
+impl<'a, S> Reuse<'a, S> {
+    /// Wraps the given serializer with a previous archive.
+    #[inline]
+    pub fn new(inner: S, previous: &'a [u8]) -> Self {
+        Self {
+            inner,
+            previous,
+            reused: 0,
+            reused_bytes: 0,
+        }
+    }
+
+    /// Returns the number of values copied from the previous archive.
+    #[inline]
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Returns the number of bytes copied from the previous archive.
+    #[inline]
+    pub fn reused_bytes(&self) -> usize {
+        self.reused_bytes
+    }
+
+    /// Returns a reference to the wrapped serializer.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped serializer.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Writer<E>, E> Reusing<E> for Reuse<'_, S> {
+    fn reuse(&mut self, start: usize, end: usize) -> Result<Option<usize>, E> {
+        let bytes = match self.previous.get(start..end) {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let pos = self.inner.align(SUBTREE_ALIGN)?;
+        self.inner.write(bytes)?;
+        self.reused += 1;
+        self.reused_bytes += bytes.len();
+        Ok(Some(pos))
+    }
+}
+
+impl<S: Positional> Positional for Reuse<'_, S> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<S: Writer<E>, E> Writer<E> for Reuse<'_, S> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.inner.position_for(layout)
+    }
+}
+
+impl<S: Allocator<E>, E> Allocator<E> for Reuse<'_, S> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+impl<S: Sharing<E>, E> Sharing<E> for Reuse<'_, S> {
+    #[inline]
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    #[inline]
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+}
+
+// Where a tracked value was written in the last archive it was serialized
+// into.
+#[derive(Clone, Copy, Debug)]
+struct Span {
+    start: usize,
+    end: usize,
+    root: usize,
+}
+
+/// A value which can be copied from a previous archive if it has not been
+/// modified.
+///
+/// A tracked value is modified through [`get_mut`](Tracked::get_mut), which
+/// marks it as dirty. Dirty values are serialized normally, and clean values
+/// are copied from the previous archive when serialized with a [`Reuse`]
+/// adapter.
+///
+/// If serializing a tracked value fails, the archive it was being written to
+/// must not be used as a previous archive.
+pub struct Tracked<T> {
+    value: T,
+    span: Cell<Option<Span>>,
+}
+
+impl<T> Tracked<T> {
+    /// Returns a new dirty tracked value.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            span: Cell::new(None),
+        }
+    }
+
+    /// Returns a reference to the value.
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the value and marks it as dirty.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.mark_dirty();
+        &mut self.value
+    }
+
+    /// Marks the value as dirty, so that it will be serialized again instead
+    /// of being copied.
+    #[inline]
+    pub fn mark_dirty(&mut self) {
+        self.span.set(None);
+    }
+
+    /// Returns whether the value has been modified since it was last
+    /// serialized.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.span.get().is_none()
+    }
+
+    /// Consumes the tracked value and returns the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> From<T> for Tracked<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for Tracked<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracked")
+            .field("value", &self.value)
+            .field("dirty", &self.is_dirty())
+            .finish()
+    }
+}
+
+impl<T: Archive> Archive for Tracked<T> {
+    type Archived = ArchivedBox<T::Archived>;
+    type Resolver = BoxResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedBox::resolve_from_ref(&self.value, resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for Tracked<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Writer + Reusing + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        if let Some(span) = self.span.get() {
+            if let Some(start) = serializer.reuse(span.start, span.end)? {
+                let root = start + (span.root - span.start);
+                self.span.set(Some(Span {
+                    start,
+                    end: start + (span.end - span.start),
+                    root,
+                }));
+                return Ok(BoxResolver::from_pos(root));
+            }
+        }
+
+        let start = serializer.align(SUBTREE_ALIGN)?;
+        let root = self.value.serialize_and_resolve(serializer)?;
+        let end = serializer.pos();
+        self.span.set(Some(Span { start, end, root }));
+        Ok(BoxResolver::from_pos(root))
+    }
+}
+
+impl<T, D> Deserialize<Tracked<T>, D> for ArchivedBox<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<Tracked<T>, D::Error> {
+        Ok(Tracked::new(self.get().deserialize(deserializer)?))
+    }
+}
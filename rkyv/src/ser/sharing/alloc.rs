@@ -1,4 +1,4 @@
-use core::{fmt, mem::size_of};
+use core::{fmt, hash::BuildHasher, mem::size_of};
 #[cfg(feature = "std")]
 use std::collections::hash_map;
 
@@ -6,7 +6,7 @@ use std::collections::hash_map;
 use hashbrown::hash_map;
 use rancor::{fail, Source};
 
-use crate::ser::Sharing;
+use crate::{hash::DefaultHashBuilder, ser::Sharing};
 
 #[derive(Debug)]
 struct DuplicateSharedPointer {
@@ -27,11 +27,30 @@ impl fmt::Display for DuplicateSharedPointer {
 #[cfg(feature = "std")]
 impl std::error::Error for DuplicateSharedPointer {}
 
+#[cfg(feature = "fallible-alloc")]
+#[derive(Debug)]
+struct SharedPointerAllocationFailed;
+
+#[cfg(feature = "fallible-alloc")]
+impl fmt::Display for SharedPointerAllocationFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to allocate space for a shared pointer")
+    }
+}
+
+#[cfg(all(feature = "fallible-alloc", feature = "std"))]
+impl std::error::Error for SharedPointerAllocationFailed {}
+
 /// A shared pointer strategy that unifies serializations of the same shared
 /// pointer.
-#[derive(Debug, Default)]
-pub struct Unify {
-    shared_address_to_pos: hash_map::HashMap<usize, usize>,
+///
+/// The hasher used to look up shared pointers can be customized with `H`. It
+/// is only used while serializing and does not affect the archive, so a fast
+/// hasher like [`FxBuildHasher`](crate::hash::FxBuildHasher) can be used
+/// safely.
+#[derive(Debug)]
+pub struct Unify<H = DefaultHashBuilder> {
+    shared_address_to_pos: hash_map::HashMap<usize, usize, H>,
 }
 
 impl Unify {
@@ -39,25 +58,56 @@ impl Unify {
     #[inline]
     pub fn new() -> Self {
         Self {
-            shared_address_to_pos: hash_map::HashMap::new(),
+            shared_address_to_pos: hash_map::HashMap::default(),
         }
     }
 
     /// Creates a new shared pointer unifier with initial capacity.
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl Default for Unify {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> Unify<H> {
+    /// Creates a new shared pointer unifier which uses the given hasher.
+    #[inline]
+    pub fn with_hasher(hasher: H) -> Self {
         Self {
-            shared_address_to_pos: hash_map::HashMap::with_capacity(capacity),
+            shared_address_to_pos: hash_map::HashMap::with_hasher(hasher),
+        }
+    }
+
+    /// Creates a new shared pointer unifier with initial capacity which uses
+    /// the given hasher.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: H) -> Self {
+        Self {
+            shared_address_to_pos: hash_map::HashMap::with_capacity_and_hasher(
+                capacity, hasher,
+            ),
         }
     }
 }
 
-impl<E: Source> Sharing<E> for Unify {
+impl<H: BuildHasher, E: Source> Sharing<E> for Unify<H> {
     fn get_shared_ptr(&self, address: usize) -> Option<usize> {
         self.shared_address_to_pos.get(&address).copied()
     }
 
     fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        #[cfg(feature = "fallible-alloc")]
+        if self.shared_address_to_pos.try_reserve(1).is_err() {
+            fail!(SharedPointerAllocationFailed);
+        }
+
         match self.shared_address_to_pos.entry(address) {
             hash_map::Entry::Occupied(_) => {
                 fail!(DuplicateSharedPointer { address });
@@ -0,0 +1,284 @@
+//! Statistics collection for serializers.
+
+use core::{
+    alloc::Layout,
+    any::type_name,
+    cell::{Cell, RefCell},
+    ptr::NonNull,
+};
+#[cfg(feature = "std")]
+use std::collections::hash_map;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map;
+use rancor::Strategy;
+
+use crate::{
+    ser::{Allocator, Positional, Sharing, Writer},
+    Serialize,
+};
+
+/// Statistics collected by a [`Stats`] serializer.
+#[derive(Debug, Default)]
+pub struct SerializationStats {
+    bytes_written: usize,
+    bytes_by_type: hash_map::HashMap<&'static str, usize>,
+    shared_hits: usize,
+    shared_bytes_saved: usize,
+    scratch_current: usize,
+    scratch_high_water: usize,
+}
+
+impl SerializationStats {
+    /// Returns the total number of bytes written.
+    #[inline]
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Returns the number of bytes written by values serialized with
+    /// [`serialize_tracked`], keyed by type name.
+    #[inline]
+    pub fn bytes_by_type(&self) -> &hash_map::HashMap<&'static str, usize> {
+        &self.bytes_by_type
+    }
+
+    /// Returns the number of bytes written by values of type `T` serialized
+    /// with [`serialize_tracked`].
+    #[inline]
+    pub fn bytes_for_type<T: ?Sized>(&self) -> usize {
+        self.bytes_by_type
+            .get(type_name::<T>())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of times a shared pointer was found to be already
+    /// serialized.
+    #[inline]
+    pub fn shared_hits(&self) -> usize {
+        self.shared_hits
+    }
+
+    /// Returns the number of bytes that the sharing strategy avoided writing
+    /// by reusing previously-serialized shared pointers.
+    #[inline]
+    pub fn shared_bytes_saved(&self) -> usize {
+        self.shared_bytes_saved
+    }
+
+    /// Returns the maximum number of scratch bytes allocated at once.
+    #[inline]
+    pub fn scratch_high_water(&self) -> usize {
+        self.scratch_high_water
+    }
+}
+
+/// A serializer adapter which collects statistics about serialization.
+///
+/// `Stats` wraps a complete serializer (such as a
+/// [`Composite`](crate::ser::Composite)) and records the number of bytes
+/// written, the bytes saved by the sharing strategy, and the high-water mark
+/// of scratch space. Use [`serialize_tracked`] to also record the bytes written
+/// for individual values by type.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::{Error, Strategy},
+///     ser::{
+///         stats::{serialize_tracked, Stats},
+///         AllocSerializer,
+///     },
+/// };
+///
+/// let value = vec![1u32, 2, 3, 4];
+///
+/// let mut serializer = Stats::new(AllocSerializer::default());
+/// serialize_tracked(&value, Strategy::<_, Error>::wrap(&mut serializer))
+///     .unwrap();
+///
+/// let (serializer, stats) = serializer.into_raw_parts();
+/// assert_eq!(stats.bytes_written(), serializer.writer.len());
+/// assert!(stats.bytes_for_type::<Vec<u32>>() >= 16);
+/// ```
+#[derive(Debug, Default)]
+pub struct Stats<S> {
+    inner: S,
+    stats: SerializationStats,
+    shared_hits: Cell<usize>,
+    shared_bytes_saved: Cell<usize>,
+    shared_sizes: hash_map::HashMap<usize, usize>,
+    pending_shared: RefCell<Vec<usize>>,
+}
+
+impl<S> Stats<S> {
+    /// Wraps the given serializer.
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            stats: SerializationStats::default(),
+            shared_hits: Cell::new(0),
+            shared_bytes_saved: Cell::new(0),
+            shared_sizes: hash_map::HashMap::new(),
+            pending_shared: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the statistics collected so far.
+    #[inline]
+    pub fn stats(&self) -> SerializationStats {
+        SerializationStats {
+            bytes_written: self.stats.bytes_written,
+            bytes_by_type: self.stats.bytes_by_type.clone(),
+            shared_hits: self.shared_hits.get(),
+            shared_bytes_saved: self.shared_bytes_saved.get(),
+            scratch_current: self.stats.scratch_current,
+            scratch_high_water: self.stats.scratch_high_water,
+        }
+    }
+
+    /// Returns a reference to the wrapped serializer.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped serializer.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Records that `bytes` bytes were written for a value of type `T`.
+    #[inline]
+    pub fn record_type<T: ?Sized>(&mut self, bytes: usize) {
+        *self
+            .stats
+            .bytes_by_type
+            .entry(type_name::<T>())
+            .or_insert(0) += bytes;
+    }
+
+    /// Consumes the adapter and returns the wrapped serializer and the
+    /// collected statistics.
+    #[inline]
+    pub fn into_raw_parts(self) -> (S, SerializationStats) {
+        let mut stats = self.stats;
+        stats.shared_hits = self.shared_hits.get();
+        stats.shared_bytes_saved = self.shared_bytes_saved.get();
+        (self.inner, stats)
+    }
+}
+
+impl<S: Positional> Positional for Stats<S> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<S: Writer<E>, E> Writer<E> for Stats<S> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)?;
+        self.stats.bytes_written += bytes.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.inner.position_for(layout)
+    }
+}
+
+impl<S: Allocator<E>, E> Allocator<E> for Stats<S> {
+    #[inline]
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for calling this function.
+        let result = unsafe { self.inner.push_alloc(layout)? };
+        self.stats.scratch_current += layout.size();
+        self.stats.scratch_high_water = self
+            .stats
+            .scratch_high_water
+            .max(self.stats.scratch_current);
+        Ok(result)
+    }
+
+    #[inline]
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for calling this function.
+        unsafe { self.inner.pop_alloc(ptr, layout)? };
+        self.stats.scratch_current -= layout.size();
+        Ok(())
+    }
+}
+
+impl<S: Sharing<E> + Positional, E> Sharing<E> for Stats<S> {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        let result = self.inner.get_shared_ptr(address);
+        if result.is_some() {
+            self.shared_hits.set(self.shared_hits.get() + 1);
+            if let Some(size) = self.shared_sizes.get(&address) {
+                self.shared_bytes_saved
+                    .set(self.shared_bytes_saved.get() + size);
+            }
+        } else {
+            // The shared value is about to be serialized, remember where it
+            // started so its size can be recorded when it is added.
+            self.pending_shared.borrow_mut().push(self.inner.pos());
+        }
+        result
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)?;
+        if let Some(start) = self.pending_shared.get_mut().pop() {
+            let size = self.inner.pos().saturating_sub(start);
+            self.shared_sizes.insert(address, size);
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a value and records the number of bytes it wrote under its type
+/// name.
+///
+/// Returns the position of the serialized value.
+pub fn serialize_tracked<T, S, E>(
+    value: &T,
+    serializer: &mut Strategy<Stats<S>, E>,
+) -> Result<usize, E>
+where
+    T: Serialize<Strategy<Stats<S>, E>>,
+    Stats<S>: Writer<E>,
+{
+    let start = serializer.pos();
+    let pos = value.serialize_and_resolve(serializer)?;
+    let end = serializer.pos();
+    serializer.record_type::<T>(end - start);
+    Ok(pos)
+}
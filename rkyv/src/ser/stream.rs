@@ -0,0 +1,364 @@
+//! Serialization which streams archives to async writers.
+//!
+//! Serializing is synchronous, but the bytes it produces don't have to be
+//! collected into a single buffer before they are sent. An
+//! [`AsyncSerializer`] serializes into a small spool buffer, and drains the
+//! spool into an [`AsyncWriter`] whenever a value has been written. Only the
+//! bytes of the value currently being serialized are held in memory.
+//!
+//! Large slices can be streamed in steps with
+//! [`serialize_slice`](AsyncSerializer::serialize_slice), which drains the
+//! spool every time roughly a budget of bytes has been written.
+//!
+//! With the `tokio` feature enabled, any `tokio::io::AsyncWrite` can be used
+//! as an async writer by wrapping it in a `TokioWriter`.
+
+use core::{
+    fmt,
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use rancor::{fail, Fallible, Source, Strategy};
+
+use crate::{
+    ser::{
+        allocator::GlobalAllocator, incremental::IncrementalVec,
+        sharing::Unify, Composite, Positional, Writer,
+    },
+    Archive, Serialize, SerializeUnsized,
+};
+
+/// A writer which accepts bytes asynchronously.
+///
+/// This is the async counterpart to [`Writer`]. Unlike a `Writer`, an async
+/// writer may accept only part of the bytes it is given, and may not be ready
+/// to accept any bytes at all.
+pub trait AsyncWriter<E = <Self as Fallible>::Error> {
+    /// Attempts to write some of the given bytes, and returns how many bytes
+    /// were written.
+    ///
+    /// Returning `Ok(0)` when given a non-empty slice indicates that the
+    /// writer can't accept any more bytes.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bytes: &[u8],
+    ) -> Poll<Result<usize, E>>;
+
+    /// Attempts to flush any buffered bytes to their destination.
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), E>>;
+}
+
+impl<W, E> AsyncWriter<E> for &mut W
+where
+    W: AsyncWriter<E> + Unpin + ?Sized,
+{
+    #[inline]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bytes: &[u8],
+    ) -> Poll<Result<usize, E>> {
+        Pin::new(&mut **self).poll_write(cx, bytes)
+    }
+
+    #[inline]
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), E>> {
+        Pin::new(&mut **self).poll_flush(cx)
+    }
+}
+
+#[derive(Debug)]
+struct WriteZero {
+    remaining: usize,
+}
+
+impl fmt::Display for WriteZero {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "async writer stopped accepting bytes with {} bytes left to write",
+            self.remaining,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WriteZero {}
+
+async fn write_all<W, E>(writer: &mut W, mut bytes: &[u8]) -> Result<(), E>
+where
+    W: AsyncWriter<E> + Unpin + ?Sized,
+    E: Source,
+{
+    while !bytes.is_empty() {
+        let written =
+            poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, bytes)).await?;
+        if written == 0 {
+            fail!(WriteZero {
+                remaining: bytes.len(),
+            });
+        }
+        bytes = &bytes[written..];
+    }
+    Ok(())
+}
+
+/// A writer which holds the bytes written since it was last drained.
+///
+/// Archives are only ever appended to, so the bytes before the spool can be
+/// sent on as soon as they are written. The position of the spool includes
+/// the bytes which have already been drained.
+#[derive(Debug, Default)]
+pub struct Spool {
+    bytes: Vec<u8>,
+    drained: usize,
+}
+
+impl Spool {
+    /// Returns a new, empty spool.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bytes which have not been drained yet.
+    #[inline]
+    pub fn pending(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Marks the pending bytes as drained and removes them from the spool.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.drained += self.bytes.len();
+        self.bytes.clear();
+    }
+}
+
+impl Positional for Spool {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.drained + self.bytes.len()
+    }
+}
+
+impl<E> Writer<E> for Spool {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// A serializer which streams archives to an [`AsyncWriter`].
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug)]
+pub struct AsyncSerializer<W, A = GlobalAllocator, S = Unify> {
+    writer: W,
+    inner: Composite<Spool, A, S>,
+}
+
+impl<W> AsyncSerializer<W> {
+    /// Returns a new async serializer which writes to the given writer.
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self::with_parts(writer, GlobalAllocator::default(), Unify::default())
+    }
+}
+
+impl<W, A, S> AsyncSerializer<W, A, S> {
+    /// Returns a new async serializer which writes to the given writer with
+    /// the given allocator and shared pointer strategy.
+    #[inline]
+    pub fn with_parts(writer: W, allocator: A, share: S) -> Self {
+        Self {
+            writer,
+            inner: Composite::new(Spool::new(), allocator, share),
+        }
+    }
+
+    /// Returns the number of bytes serialized so far.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.inner.writer.pos()
+    }
+
+    /// Returns a reference to the async writer.
+    #[inline]
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consumes the serializer and returns the async writer.
+    ///
+    /// Any bytes which have not been drained are discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    async fn drain<E>(&mut self) -> Result<(), E>
+    where
+        W: AsyncWriter<E> + Unpin,
+        E: Source,
+    {
+        let spool = &mut self.inner.writer;
+        write_all(&mut self.writer, spool.pending()).await?;
+        spool.clear();
+        Ok(())
+    }
+
+    /// Serializes the given value and writes it to the async writer.
+    ///
+    /// Returns the position of the archived value.
+    pub async fn serialize<T, E>(&mut self, value: &T) -> Result<usize, E>
+    where
+        T: Serialize<Strategy<Composite<Spool, A, S>, E>>,
+        W: AsyncWriter<E> + Unpin,
+        E: Source,
+    {
+        let pos =
+            value.serialize_and_resolve(Strategy::wrap(&mut self.inner))?;
+        self.drain().await?;
+        Ok(pos)
+    }
+
+    /// Serializes the given unsized value and writes it to the async writer.
+    ///
+    /// Returns the position of the archived value.
+    pub async fn serialize_unsized<T, E>(
+        &mut self,
+        value: &T,
+    ) -> Result<usize, E>
+    where
+        T: SerializeUnsized<Strategy<Composite<Spool, A, S>, E>> + ?Sized,
+        W: AsyncWriter<E> + Unpin,
+        E: Source,
+    {
+        let pos = value.serialize_unsized(Strategy::wrap(&mut self.inner))?;
+        self.drain().await?;
+        Ok(pos)
+    }
+
+    /// Serializes the given slice as an archived `Vec` and writes it to the
+    /// async writer.
+    ///
+    /// The bytes are written whenever at least `budget` bytes have been
+    /// serialized, so only about `budget` bytes are held in memory at a time.
+    /// Returns the position of the archived `Vec`.
+    pub async fn serialize_slice<T, E>(
+        &mut self,
+        items: &[T],
+        budget: usize,
+    ) -> Result<usize, E>
+    where
+        T: Archive + Serialize<Strategy<Composite<Spool, A, S>, E>>,
+        W: AsyncWriter<E> + Unpin,
+        E: Source,
+    {
+        let mut incremental = IncrementalVec::new(items);
+        loop {
+            let serializer = Strategy::wrap(&mut self.inner);
+            match incremental.poll_serialize(serializer, budget) {
+                Poll::Ready(result) => {
+                    let pos = result?;
+                    self.drain().await?;
+                    return Ok(pos);
+                }
+                Poll::Pending => self.drain().await?,
+            }
+        }
+    }
+
+    /// Flushes the async writer.
+    pub async fn flush<E>(&mut self) -> Result<(), E>
+    where
+        W: AsyncWriter<E> + Unpin,
+        E: Source,
+    {
+        self.drain().await?;
+        poll_fn(|cx| Pin::new(&mut self.writer).poll_flush(cx)).await
+    }
+}
+
+/// Serializes the given value and streams it to the given async writer.
+///
+/// Returns the number of bytes written. The writer is flushed once the
+/// archive has been written.
+pub async fn to_async_writer<T, W, E>(value: &T, writer: W) -> Result<usize, E>
+where
+    T: Serialize<Strategy<Composite<Spool, GlobalAllocator, Unify>, E>>,
+    W: AsyncWriter<E> + Unpin,
+    E: Source,
+{
+    let mut serializer = AsyncSerializer::new(writer);
+    serializer.serialize(value).await?;
+    serializer.flush().await?;
+    Ok(serializer.pos())
+}
+
+/// Adapts a [`tokio::io::AsyncWrite`] into an [`AsyncWriter`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioWriter<W> {
+    inner: W,
+}
+
+#[cfg(feature = "tokio")]
+impl<W> TokioWriter<W> {
+    /// Wraps the given tokio writer.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the tokio writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the tokio writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W, E> AsyncWriter<E> for TokioWriter<W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    E: Source,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bytes: &[u8],
+    ) -> Poll<Result<usize, E>> {
+        Pin::new(&mut self.inner)
+            .poll_write(cx, bytes)
+            .map(|result| result.map_err(E::new))
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), E>> {
+        Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map(|result| result.map_err(E::new))
+    }
+}
@@ -1,84 +1,280 @@
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
+#[cfg(feature = "fallible-alloc")]
+use rancor::{ResultExt as _, Source};
+
 use crate::{
     ser::{Positional, Writer},
-    util::AlignedVec,
+    util::{AlignedVec, RawAllocator},
 };
 
-impl Positional for Vec<u8> {
+macro_rules! impl_writer {
+    ($ty:ty $(, $($generics:tt)*)?) => {
+        impl<$($($generics)*)?> Positional for $ty {
+            #[inline]
+            fn pos(&self) -> usize {
+                self.len()
+            }
+        }
+
+        impl<E, $($($generics)*)?> Writer<E> for $ty {
+            #[inline]
+            fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+                self.extend_from_slice(bytes);
+                Ok(())
+            }
+
+            #[inline]
+            fn reserve(&mut self, additional: usize) -> Result<(), E> {
+                let _ = self.try_reserve_exact(additional);
+                Ok(())
+            }
+        }
+
+        #[cfg(feature = "fallible-alloc")]
+        impl<E: Source, $($($generics)*)?> Writer<E> for FallibleWriter<$ty> {
+            #[inline]
+            fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+                self.inner.try_reserve(bytes.len()).into_error()?;
+                self.inner.extend_from_slice(bytes);
+                Ok(())
+            }
+
+            #[inline]
+            fn reserve(&mut self, additional: usize) -> Result<(), E> {
+                let _ = self.inner.try_reserve_exact(additional);
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_writer!(Vec<u8>);
+impl_writer!(AlignedVec<A, B>, const A: usize, B: RawAllocator);
+
+/// A writer which returns an error instead of aborting when it fails to grow.
+///
+/// Like the standard library collections, `Vec<u8>` and [`AlignedVec`] abort
+/// when they fail to allocate. Wrapping them in a `FallibleWriter` makes
+/// writes reserve space with `try_reserve` first, so serialization fails with
+/// an error and services with memory limits can recover.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::writer::FallibleWriter,
+///     util::{serialize_into, AlignedVec},
+/// };
+///
+/// let value = [1u32, 2, 3, 4];
+/// let writer = FallibleWriter::new(AlignedVec::<16>::new());
+/// let writer = serialize_into::<_, Error>(&value, writer).unwrap();
+/// assert_eq!(writer.into_inner().len(), 16);
+/// ```
+#[cfg(feature = "fallible-alloc")]
+#[derive(Debug, Default)]
+pub struct FallibleWriter<W> {
+    inner: W,
+}
+
+#[cfg(feature = "fallible-alloc")]
+impl<W> FallibleWriter<W> {
+    /// Wraps the given writer.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(feature = "fallible-alloc")]
+impl<W: Positional> Positional for FallibleWriter<W> {
     #[inline]
     fn pos(&self) -> usize {
-        self.len()
+        self.inner.pos()
     }
 }
 
-impl<E> Writer<E> for Vec<u8> {
+/// A writer which batches small writes into a buffer before passing them to
+/// the inner writer.
+///
+/// Serializers write many small pieces, so writers which make a system call
+/// for each write are slow to serialize to directly. `BufferedWriter` collects
+/// writes in a buffer, and only writes to the inner writer when the buffer is
+/// full. Writes which are at least as large as the buffer bypass it. The
+/// buffer is flushed when serialization finishes.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::writer::{BufferedWriter, IoWriter},
+///     util::serialize_into,
+/// };
+///
+/// let value = [(1u8, 2u32); 64];
+/// let writer = BufferedWriter::with_capacity(IoWriter::new(Vec::new()), 256);
+/// let writer = serialize_into::<_, Error>(&value, writer).unwrap();
+/// let bytes = writer.into_inner::<Error>().unwrap().into_inner();
+/// assert_eq!(bytes.len(), 64 * 8);
+/// ```
+#[derive(Debug)]
+pub struct BufferedWriter<W> {
+    inner: W,
+    buffer: AlignedVec,
+    capacity: usize,
+}
+
+impl<W> BufferedWriter<W> {
+    /// The default capacity of the buffer.
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    /// Wraps the given writer with a buffer of the default capacity.
     #[inline]
-    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
-        self.extend_from_slice(bytes);
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(inner, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Wraps the given writer with a buffer which holds up to `capacity`
+    /// bytes.
+    #[inline]
+    pub fn with_capacity(inner: W, capacity: usize) -> Self {
+        Self {
+            inner,
+            buffer: AlignedVec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the bytes which have been written but not yet passed to the
+    /// inner writer.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.as_slice()
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Passes any buffered bytes to the inner writer.
+    #[inline]
+    pub fn flush<E>(&mut self) -> Result<(), E>
+    where
+        W: Writer<E>,
+    {
+        if !self.buffer.is_empty() {
+            self.inner.write(self.buffer.as_slice())?;
+            self.buffer.clear();
+        }
         Ok(())
     }
+
+    /// Flushes the buffer and returns the underlying writer.
+    #[inline]
+    pub fn into_inner<E>(mut self) -> Result<W, E>
+    where
+        W: Writer<E>,
+    {
+        self.flush()?;
+        Ok(self.inner)
+    }
 }
 
-impl Positional for AlignedVec {
+impl<W: Positional> Positional for BufferedWriter<W> {
     #[inline]
     fn pos(&self) -> usize {
-        self.len()
+        self.inner.pos() + self.buffer.len()
     }
 }
 
-impl<E> Writer<E> for AlignedVec {
+impl<W: Writer<E>, E> Writer<E> for BufferedWriter<W> {
     #[inline]
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
-        self.extend_from_slice(bytes);
-        Ok(())
+        if self.buffer.len() + bytes.len() > self.capacity {
+            self.flush()?;
+        }
+        if bytes.len() >= self.capacity {
+            self.inner.write(bytes)
+        } else {
+            self.buffer.extend_from_slice(bytes);
+            Ok(())
+        }
     }
 
-    // TODO: check whether moving this into an extension trait resulted in a
-    // benchmark regression from additional memory copying.
-
-    // #[inline]
-    // unsafe fn resolve_aligned<T: Archive + ?Sized>(
-    //     &mut self,
-    //     value: &T,
-    //     resolver: T::Resolver,
-    // ) -> Result<usize, E> {
-    //     let pos = Serializer::<E>::pos(self);
-    //     debug_assert_eq!(pos & (mem::align_of::<T::Archived>() - 1), 0);
-    //     let vec = self.inner.borrow_mut();
-    //     let additional = mem::size_of::<T::Archived>();
-    //     vec.reserve(additional);
-    //     vec.set_len(vec.len() + additional);
-
-    //     let ptr = vec.as_mut_ptr().add(pos).cast::<T::Archived>();
-    //     ptr.write_bytes(0, 1);
-    //     value.resolve(pos, resolver, ptr);
-
-    //     Ok(pos)
-    // }
-
-    // #[inline]
-    // unsafe fn resolve_unsized_aligned<T: ArchiveUnsized + ?Sized>(
-    //     &mut self,
-    //     value: &T,
-    //     to: usize,
-    //     metadata_resolver: T::MetadataResolver,
-    // ) -> Result<usize, E> {
-    //     let from = Serializer::<E>::pos(self);
-    //     debug_assert_eq!(
-    //         from & (mem::align_of::<RelPtr<T::Archived>>() - 1),
-    //         0
-    //     );
-    //     let vec = self.inner.borrow_mut();
-    //     let additional = mem::size_of::<RelPtr<T::Archived>>();
-    //     vec.reserve(additional);
-    //     vec.set_len(vec.len() + additional);
-
-    //     let ptr = vec.as_mut_ptr().add(from).cast::<RelPtr<T::Archived>>();
-    //     ptr.write_bytes(0, 1);
-
-    //     value.resolve_unsized(from, to, metadata_resolver, ptr);
-    //     Ok(from)
-    // }
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.flush()?;
+        self.inner.finish()
+    }
 }
+
+// TODO: check whether moving this into an extension trait resulted in a
+// benchmark regression from additional memory copying.
+
+// #[inline]
+// unsafe fn resolve_aligned<T: Archive + ?Sized>(
+//     &mut self,
+//     value: &T,
+//     resolver: T::Resolver,
+// ) -> Result<usize, E> {
+//     let pos = Serializer::<E>::pos(self);
+//     debug_assert_eq!(pos & (mem::align_of::<T::Archived>() - 1), 0);
+//     let vec = self.inner.borrow_mut();
+//     let additional = mem::size_of::<T::Archived>();
+//     vec.reserve(additional);
+//     vec.set_len(vec.len() + additional);
+
+//     let ptr = vec.as_mut_ptr().add(pos).cast::<T::Archived>();
+//     ptr.write_bytes(0, 1);
+//     value.resolve(pos, resolver, ptr);
+
+//     Ok(pos)
+// }
+
+// #[inline]
+// unsafe fn resolve_unsized_aligned<T: ArchiveUnsized + ?Sized>(
+//     &mut self,
+//     value: &T,
+//     to: usize,
+//     metadata_resolver: T::MetadataResolver,
+// ) -> Result<usize, E> {
+//     let from = Serializer::<E>::pos(self);
+//     debug_assert_eq!(
+//         from & (mem::align_of::<RelPtr<T::Archived>>() - 1),
+//         0
+//     );
+//     let vec = self.inner.borrow_mut();
+//     let additional = mem::size_of::<RelPtr<T::Archived>>();
+//     vec.reserve(additional);
+//     vec.set_len(vec.len() + additional);
+
+//     let ptr = vec.as_mut_ptr().add(from).cast::<RelPtr<T::Archived>>();
+//     ptr.write_bytes(0, 1);
+
+//     value.resolve_unsized(from, to, metadata_resolver, ptr);
+//     Ok(from)
+// }
@@ -1,4 +1,7 @@
-use core::{fmt, ptr::copy_nonoverlapping};
+use core::{
+    alloc::Layout, fmt, marker::PhantomData, mem::align_of,
+    ptr::copy_nonoverlapping,
+};
 
 use rancor::{fail, Source};
 
@@ -135,3 +138,246 @@ impl<T: AsMut<[u8]>, E: Source> Writer<E> for BufferWriter<T> {
         }
     }
 }
+
+/// Wraps a writer and declares the maximum alignment of the archived types
+/// written to it.
+///
+/// Archives are normally buffered and relocated with an alignment of 16 bytes
+/// so that they can hold any archived type. When every type in an archive has
+/// a smaller alignment, declaring it lets the archive be stored in a buffer
+/// with that alignment (like an [`AlignedVec<N>`]) and packed next to other
+/// archives with less padding between them.
+///
+/// The root type can be checked against the maximum alignment at compile time
+/// with [`assert_fits`](MaxAlign::assert_fits). Any object which is written
+/// with a larger alignment causes a panic.
+///
+/// [`AlignedVec<N>`]: crate::util::AlignedVec
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     access_unchecked,
+///     rancor::Error,
+///     ser::writer::MaxAlign,
+///     util::{serialize, AlignedVec},
+///     Archived,
+/// };
+///
+/// let value = "a string which is stored out of line".to_string();
+/// MaxAlign::<AlignedVec<4>, 4>::assert_fits::<Archived<String>>();
+///
+/// let mut writer = MaxAlign::<_, 4>::new(AlignedVec::<4>::new());
+/// serialize::<_, Error>(&value, &mut writer).unwrap();
+/// let bytes = writer.into_inner();
+///
+/// let archived = unsafe { access_unchecked::<Archived<String>>(&bytes) };
+/// assert_eq!(archived.as_str(), value);
+/// ```
+#[derive(Debug, Default)]
+pub struct MaxAlign<W, const N: usize> {
+    inner: W,
+}
+
+impl<W, const N: usize> MaxAlign<W, N> {
+    /// Wraps the given writer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is not a power of two.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        assert!(N.is_power_of_two(), "N must be a power of 2");
+        Self { inner }
+    }
+
+    /// Fails to compile if the alignment of `T` is greater than `N`.
+    #[inline]
+    pub fn assert_fits<T>() {
+        #[allow(clippy::let_unit_value)]
+        let () = AlignCeiling::<T, N>::CHECK;
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+struct AlignCeiling<T, const N: usize>(PhantomData<T>);
+
+impl<T, const N: usize> AlignCeiling<T, N> {
+    const CHECK: () = assert!(
+        align_of::<T>() <= N,
+        "the alignment of the archived type exceeds the maximum alignment",
+    );
+}
+
+impl<W: Positional, const N: usize> Positional for MaxAlign<W, N> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, E, const N: usize> Writer<E> for MaxAlign<W, N> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        self.inner.reserve(additional)
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        assert!(
+            layout.align() <= N,
+            "wrote an object with alignment {} to a writer with a maximum \
+             alignment of {}",
+            layout.align(),
+            N,
+        );
+        self.inner.position_for(layout)
+    }
+}
+
+#[derive(Debug)]
+struct LimitExceeded {
+    needed: usize,
+    limit: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "archive exceeds limit (needed {} bytes, limit is {} bytes)",
+            self.needed, self.limit,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl Error for LimitExceeded {}
+};
+
+/// Wraps a writer and enforces a maximum size for the archive written to it.
+///
+/// Serialization fails as soon as a write would move the position of the
+/// writer past the limit, before any of the bytes are passed to the inner
+/// writer. Reservations are capped at the limit, so an oversized estimate
+/// doesn't allocate more than the limit allows.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::writer::LimitedWriter,
+///     util::{serialize, AlignedVec},
+/// };
+///
+/// let mut writer = LimitedWriter::new(AlignedVec::<16>::new(), 64);
+/// serialize::<_, Error>(&"a".repeat(16), &mut writer).unwrap();
+///
+/// let mut writer = LimitedWriter::new(AlignedVec::<16>::new(), 64);
+/// let result = serialize::<_, Error>(&"a".repeat(128), &mut writer);
+/// assert!(result.unwrap_err().to_string().contains("exceeds limit"));
+/// ```
+#[derive(Debug, Default)]
+pub struct LimitedWriter<W> {
+    inner: W,
+    limit: usize,
+}
+
+impl<W> LimitedWriter<W> {
+    /// Wraps the given writer with a maximum archive size of `limit` bytes.
+    #[inline]
+    pub fn new(inner: W, limit: usize) -> Self {
+        Self { inner, limit }
+    }
+
+    /// Returns the maximum archive size in bytes.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Positional> Positional for LimitedWriter<W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, E: Source> Writer<E> for LimitedWriter<W> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        let needed = self.inner.pos().saturating_add(bytes.len());
+        if needed > self.limit {
+            fail!(LimitExceeded {
+                needed,
+                limit: self.limit,
+            });
+        }
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.finish()
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        let remaining = self.limit.saturating_sub(self.inner.pos());
+        self.inner.reserve(additional.min(remaining))
+    }
+
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        self.inner.position_for(layout)
+    }
+}
@@ -6,9 +6,11 @@ mod core;
 #[cfg(feature = "std")]
 mod std;
 
-use ::core::mem;
+use ::core::{alloc::Layout, mem};
 use rancor::{Fallible, Strategy};
 
+#[cfg(feature = "alloc")]
+pub use self::alloc::*;
 pub use self::core::*;
 #[cfg(feature = "std")]
 pub use self::std::*;
@@ -38,9 +40,60 @@ where
 /// before attempting to read objects out of it; use an
 /// [`AlignedVec`](crate::util::AlignedVec) or the
 /// [`AlignedBytes`](crate::util::AlignedBytes) wrappers as appropriate.
+///
+/// Every byte passed to a writer is initialized. Padding between archived
+/// objects is written as zeroes by [`pad`](WriterExt::pad), and archived
+/// objects are resolved into zeroed memory before being written, so padding
+/// inside of them is zeroed as well. Archives never contain stale memory from
+/// the buffer they are written into, even if that buffer is reused.
 pub trait Writer<E = <Self as Fallible>::Error>: Positional {
     /// Attempts to write the given bytes to the serializer.
     fn write(&mut self, bytes: &[u8]) -> Result<(), E>;
+
+    /// Finishes writing, flushing any buffered or trailing state.
+    ///
+    /// This is called once after the root object has been written by helpers
+    /// like [`serialize`](crate::util::serialize) and
+    /// [`to_bytes`](crate::to_bytes). Writers that wrap another writer should
+    /// finish themselves and then finish the inner writer.
+    ///
+    /// The default implementation does nothing.
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Reserves space for at least `additional` more bytes to be written.
+    ///
+    /// This is a hint which lets writers that buffer their output allocate
+    /// space ahead of time, for example from an estimate of the size of the
+    /// serialized value. Writers may ignore it. Failing to reserve space is not
+    /// an error, since the estimate may be larger than the data which is
+    /// actually written.
+    ///
+    /// The default implementation does nothing.
+    #[inline]
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        let _ = additional;
+        Ok(())
+    }
+
+    /// Returns the position that an object with the given layout should be
+    /// written at.
+    ///
+    /// [`align_for_layout`](WriterExt::align_for_layout) pads the writer up to
+    /// the returned position before an object is written. Writers can override
+    /// this to control where objects are placed, for example to keep objects
+    /// from spanning page boundaries. The returned position must be aligned to
+    /// `layout.align()` and must not be less than the current position.
+    ///
+    /// The default implementation rounds the current position up to
+    /// `layout.align()`.
+    #[inline]
+    fn position_for(&mut self, layout: Layout) -> usize {
+        let mask = layout.align() - 1;
+        (self.pos() + mask) & !mask
+    }
 }
 
 impl<T, E> Writer<E> for Strategy<T, E>
@@ -50,17 +103,32 @@ where
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         T::write(self, bytes)
     }
+
+    fn finish(&mut self) -> Result<(), E> {
+        T::finish(self)
+    }
+
+    fn reserve(&mut self, additional: usize) -> Result<(), E> {
+        T::reserve(self, additional)
+    }
+
+    fn position_for(&mut self, layout: Layout) -> usize {
+        T::position_for(self, layout)
+    }
 }
 
 /// TODO: Document
 pub trait WriterExt<E>: Writer<E> {
     /// Advances the given number of bytes as padding.
     #[inline]
-    fn pad(&mut self, padding: usize) -> Result<(), E> {
+    fn pad(&mut self, mut padding: usize) -> Result<(), E> {
         const MAX_ZEROES: usize = 32;
         const ZEROES: [u8; MAX_ZEROES] = [0; MAX_ZEROES];
-        debug_assert!(padding < MAX_ZEROES);
 
+        while padding > MAX_ZEROES {
+            self.write(&ZEROES)?;
+            padding -= MAX_ZEROES;
+        }
         self.write(&ZEROES[0..padding])
     }
 
@@ -74,11 +142,25 @@ pub trait WriterExt<E>: Writer<E> {
         Ok(self.pos())
     }
 
+    /// Pads the serializer up to the position that an object with the given
+    /// layout should be written at, and returns that position.
+    ///
+    /// The position is chosen by [`Writer::position_for`].
+    #[inline]
+    fn align_for_layout(&mut self, layout: Layout) -> Result<usize, E> {
+        let pos = self.position_for(layout);
+        debug_assert!(pos >= self.pos());
+        debug_assert_eq!(pos & (layout.align() - 1), 0);
+
+        self.pad(pos - self.pos())?;
+        Ok(pos)
+    }
+
     /// Aligns the position of the serializer to be suitable to write the given
     /// type.
     #[inline]
     fn align_for<T>(&mut self) -> Result<usize, E> {
-        self.align(mem::align_of::<T>())
+        self.align_for_layout(Layout::new::<T>())
     }
 
     /// Resolves the given value with its resolver and writes the archived type.
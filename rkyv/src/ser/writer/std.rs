@@ -7,6 +7,13 @@ use crate::ser::{Positional, Writer};
 /// Wraps a type that implements [`io::Write`](std::io::Write) and equips it
 /// with [`Writer`].
 ///
+/// Bytes are passed to the inner writer as soon as they are written, so the
+/// archive never has to be held in memory all at once. Serializers write many
+/// small pieces, so wrap unbuffered writers like files and sockets in a
+/// [`BufWriter`](std::io::BufWriter) first, or wrap the `IoWriter` in a
+/// [`BufferedWriter`](crate::ser::writer::BufferedWriter). The buffer is
+/// flushed when serialization finishes.
+///
 /// # Examples
 /// ```
 /// # use rkyv::ser::{Writer, Positional, writer::IoWriter};
@@ -64,4 +71,186 @@ impl<W: io::Write, E: Source> Writer<E> for IoWriter<W> {
         self.pos += bytes.len();
         Ok(())
     }
+
+    #[inline]
+    fn finish(&mut self) -> Result<(), E> {
+        self.inner.flush().into_error()
+    }
+}
+
+/// A writer which accumulates its output as a list of owned chunks instead of
+/// a single contiguous buffer.
+///
+/// Growing a contiguous buffer copies everything written so far each time it
+/// reallocates. `ChunkedWriter` instead allocates a new chunk whenever the
+/// current one fills up, and exposes the chunks as [`IoSlice`]s so that they
+/// can be sent with a single vectored write.
+///
+/// The chunks are not contiguous in memory, so the archive must be written out
+/// (or copied into a single buffer) before it can be accessed.
+///
+/// [`IoSlice`]: std::io::IoSlice
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, ser::writer::ChunkedWriter,
+///     util::serialize_into, Archived,
+/// };
+///
+/// let value = (0..1000u32).collect::<Vec<_>>();
+/// let writer =
+///     serialize_into::<_, Error>(&value, ChunkedWriter::new(256)).unwrap();
+/// assert!(writer.chunks().count() > 1);
+///
+/// // Send the chunks to a socket with vectored writes
+/// let mut socket = Vec::new();
+/// writer.write_to(&mut socket).unwrap();
+///
+/// let archived = unsafe { access_unchecked::<Archived<Vec<u32>>>(&socket) };
+/// assert_eq!(archived.len(), 1000);
+/// ```
+#[derive(Debug)]
+pub struct ChunkedWriter {
+    chunks: Vec<Vec<u8>>,
+    chunk_size: usize,
+    pos: usize,
+}
+
+impl ChunkedWriter {
+    /// The default size of each chunk.
+    pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+    /// Creates a new chunked writer which allocates chunks of `chunk_size`
+    /// bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    #[inline]
+    pub fn new(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be greater than zero");
+        Self {
+            chunks: Vec::new(),
+            chunk_size,
+            pos: 0,
+        }
+    }
+
+    /// Returns the size of each chunk.
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the total number of bytes written.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns whether no bytes have been written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// Returns an iterator over the written chunks in order.
+    #[inline]
+    pub fn chunks(&self) -> impl ExactSizeIterator<Item = &[u8]> + '_ {
+        self.chunks.iter().map(|chunk| chunk.as_slice())
+    }
+
+    /// Returns the written chunks as [`IoSlice`]s, suitable for passing to
+    /// [`write_vectored`](io::Write::write_vectored).
+    ///
+    /// [`IoSlice`]: std::io::IoSlice
+    #[inline]
+    pub fn io_slices(&self) -> Vec<io::IoSlice<'_>> {
+        self.chunks().map(io::IoSlice::new).collect()
+    }
+
+    /// Writes all of the chunks to the given writer with vectored writes.
+    pub fn write_to<W: io::Write + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let mut slices = self.io_slices();
+        let mut first = 0;
+        while first < slices.len() {
+            let mut written = match writer.write_vectored(&slices[first..]) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole archive",
+                    ))
+                }
+                Ok(written) => written,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Skip the slices which were written completely.
+            while first < slices.len() && written >= slices[first].len() {
+                written -= slices[first].len();
+                first += 1;
+            }
+
+            // Advance past the part of the next slice which was written.
+            if written > 0 {
+                let chunk = &self.chunks[first];
+                let start = chunk.len() - slices[first].len() + written;
+                slices[first] = io::IoSlice::new(&chunk[start..]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the chunks into a single contiguous buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.pos);
+        for chunk in self.chunks() {
+            result.extend_from_slice(chunk);
+        }
+        result
+    }
+
+    /// Consumes the writer and returns the written chunks.
+    #[inline]
+    pub fn into_chunks(self) -> Vec<Vec<u8>> {
+        self.chunks
+    }
+}
+
+impl Default for ChunkedWriter {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl Positional for ChunkedWriter {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<E> Writer<E> for ChunkedWriter {
+    fn write(&mut self, mut bytes: &[u8]) -> Result<(), E> {
+        self.pos += bytes.len();
+        while !bytes.is_empty() {
+            let chunk = match self.chunks.last_mut() {
+                Some(chunk) if chunk.len() < chunk.capacity() => chunk,
+                _ => {
+                    self.chunks.push(Vec::with_capacity(self.chunk_size));
+                    self.chunks.last_mut().unwrap()
+                }
+            };
+            let len = bytes.len().min(chunk.capacity() - chunk.len());
+            chunk.extend_from_slice(&bytes[..len]);
+            bytes = &bytes[len..];
+        }
+        Ok(())
+    }
 }
@@ -1,8 +1,26 @@
+#[cfg(all(
+    target_feature = "sse2",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(miri),
+))]
+#[path = "sse2.rs"]
+mod group;
+
+#[cfg(not(all(
+    target_feature = "sse2",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(miri),
+)))]
 #[path = "generic.rs"]
 mod group;
 
-// TODO: add optimized SIMD implementations for sse2 and neon
+// TODO: add an optimized SIMD implementation for neon
 
 pub use group::*;
 
+/// The number of control bytes read at each step of a probe sequence.
+///
+/// Groups narrower than this are read several times per step, so the probe
+/// sequence (and therefore the archived layout) doesn't depend on which group
+/// implementation is used.
 pub const MAX_GROUP_WIDTH: usize = 16;
@@ -0,0 +1,78 @@
+#[cfg(target_arch = "x86")]
+use core::arch::x86;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as x86;
+use core::{mem::size_of, num::NonZeroU16};
+
+#[derive(Clone, Copy)]
+pub struct Bitmask(u16);
+
+impl Bitmask {
+    pub const EMPTY: Self = Bitmask(0);
+
+    #[inline]
+    pub fn any_bit_set(self) -> bool {
+        self.0 != 0
+    }
+
+    #[inline]
+    pub fn remove_lowest_bit(self) -> Self {
+        Self(self.0 & (self.0 - 1))
+    }
+
+    #[inline]
+    pub fn lowest_set_bit(self) -> Option<usize> {
+        let nonzero = NonZeroU16::new(self.0)?;
+        Some(nonzero.trailing_zeros() as usize)
+    }
+}
+
+impl Iterator for Bitmask {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = self.lowest_set_bit()?;
+        *self = self.remove_lowest_bit();
+        Some(bit)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Group(x86::__m128i);
+
+impl Group {
+    pub const WIDTH: usize = size_of::<Self>();
+
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and point to enough bytes for a `Group`.
+    #[inline]
+    pub unsafe fn read(ptr: *const u8) -> Self {
+        // SAFETY: The caller has guaranteed that `ptr` is valid for reads and
+        // points to enough bytes for a `Group`. `_mm_loadu_si128` does not
+        // require `ptr` to be aligned.
+        unsafe { Self(x86::_mm_loadu_si128(ptr.cast())) }
+    }
+
+    #[inline]
+    pub fn match_byte(self, byte: u8) -> Bitmask {
+        // SAFETY: SSE2 is enabled for the target.
+        unsafe {
+            let cmp =
+                x86::_mm_cmpeq_epi8(self.0, x86::_mm_set1_epi8(byte as i8));
+            Bitmask(x86::_mm_movemask_epi8(cmp) as u16)
+        }
+    }
+
+    #[inline]
+    pub fn match_empty(self) -> Bitmask {
+        // Empty control bytes are the only ones with their high bit set.
+        // SAFETY: SSE2 is enabled for the target.
+        unsafe { Bitmask(x86::_mm_movemask_epi8(self.0) as u16) }
+    }
+
+    #[inline]
+    pub fn match_full(self) -> Bitmask {
+        Bitmask(!self.match_empty().0)
+    }
+}
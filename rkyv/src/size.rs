@@ -0,0 +1,135 @@
+//! Memory accounting for archived values.
+
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem::size_of_val,
+    num::{NonZeroI8, NonZeroU8},
+};
+
+use crate::primitive::{
+    ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+    ArchivedI32, ArchivedI64, ArchivedNonZeroI128, ArchivedNonZeroI16,
+    ArchivedNonZeroI32, ArchivedNonZeroI64, ArchivedNonZeroU128,
+    ArchivedNonZeroU16, ArchivedNonZeroU32, ArchivedNonZeroU64, ArchivedU128,
+    ArchivedU16, ArchivedU32, ArchivedU64,
+};
+
+/// An archived type which can report the number of bytes reachable from it.
+///
+/// The total size of an archived value is the size of the value itself plus
+/// the size of all of the out-of-line data that it points to, such as the
+/// elements of an archived `Vec` or the contents of an archived `Box`. This is
+/// the real footprint of a zero-copy value, and is useful for caches which need
+/// to account for the memory used by their entries.
+///
+/// Values which are reachable through more than one shared pointer (e.g. an
+/// archived `Rc`) are counted once per pointer, so the total size of a value
+/// with shared pointers is an upper bound.
+///
+/// This trait can be derived for archived types with
+/// `#[archive_attr(derive(ArchivedSize))]`.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, to_bytes, Archive, ArchivedSize,
+///     Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive_attr(derive(ArchivedSize))]
+/// struct Entry {
+///     id: u32,
+///     tags: Vec<u16>,
+/// }
+///
+/// let value = Entry {
+///     id: 1,
+///     tags: vec![1, 2, 3, 4],
+/// };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedEntry>(&bytes) };
+///
+/// assert_eq!(archived.out_of_line_size(), 4 * 2);
+/// assert_eq!(
+///     archived.archived_size(),
+///     core::mem::size_of::<ArchivedEntry>() + 4 * 2,
+/// );
+/// ```
+pub trait ArchivedSize {
+    /// Returns the number of bytes reachable from this value which are not
+    /// part of the value itself.
+    fn out_of_line_size(&self) -> usize;
+
+    /// Returns the total number of bytes used by this value, including its
+    /// out-of-line data.
+    #[inline]
+    fn archived_size(&self) -> usize {
+        size_of_val(self) + self.out_of_line_size()
+    }
+}
+
+macro_rules! impl_inline {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ArchivedSize for $ty {
+                #[inline]
+                fn out_of_line_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_inline!(
+    (),
+    bool,
+    i8,
+    u8,
+    NonZeroI8,
+    NonZeroU8,
+    PhantomPinned,
+    ArchivedI16,
+    ArchivedI32,
+    ArchivedI64,
+    ArchivedI128,
+    ArchivedU16,
+    ArchivedU32,
+    ArchivedU64,
+    ArchivedU128,
+    ArchivedF32,
+    ArchivedF64,
+    ArchivedChar,
+    ArchivedNonZeroI16,
+    ArchivedNonZeroI32,
+    ArchivedNonZeroI64,
+    ArchivedNonZeroI128,
+    ArchivedNonZeroU16,
+    ArchivedNonZeroU32,
+    ArchivedNonZeroU64,
+    ArchivedNonZeroU128,
+    str,
+);
+
+impl<T: ?Sized> ArchivedSize for PhantomData<T> {
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: ArchivedSize, const N: usize> ArchivedSize for [T; N] {
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.iter().map(ArchivedSize::out_of_line_size).sum()
+    }
+}
+
+impl<T: ArchivedSize> ArchivedSize for [T] {
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.iter().map(ArchivedSize::out_of_line_size).sum()
+    }
+}
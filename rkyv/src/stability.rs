@@ -0,0 +1,477 @@
+//! Checks which guard the archived format against unintended changes.
+//!
+//! The archived layouts of types are part of the wire format: an archive
+//! written by one version of a program can only be read by another if the
+//! layouts of the types in it are the same. Layouts can change when a field is
+//! added or reordered, or when a dependency changes the archived type it uses.
+//!
+//! [`freeze_layout!`](crate::freeze_layout) records the size and alignment of
+//! archived types and fails to compile if they change. [`check_golden`]
+//! compares the serialized bytes of a value against a golden fixture, which
+//! also catches changes that keep the size and alignment the same.
+//!
+//! Enabling the `format-stability` feature freezes the archived layouts of the
+//! built-in types for the current [`FORMAT_VERSION`]. The crate's golden
+//! fixtures additionally pin the bytes produced for built-in types.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     freeze_layout, rancor::Error, stability::check_golden, Archive,
+//! };
+//!
+//! #[derive(Archive, rkyv::Serialize)]
+//! struct Point {
+//!     x: u16,
+//!     y: u16,
+//! }
+//!
+//! freeze_layout! {
+//!     Point => { size: 4, align: 2 },
+//! }
+//!
+//! # #[cfg(not(any(feature = "big_endian", feature = "unaligned")))]
+//! check_golden::<_, Error>(&Point { x: 1, y: 2 }, &[1, 0, 2, 0]).unwrap();
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use rancor::{fail, Source, Strategy};
+
+#[cfg(feature = "alloc")]
+use crate::{ser::AllocSerializer, Serialize};
+
+/// The version of the archived format of the built-in types.
+///
+/// This is incremented whenever the archived layout or bytes of any built-in
+/// type change.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Records the size and alignment of archived types, failing to compile if
+/// they change.
+///
+/// Each entry names a type and the expected `size` and `align` of its
+/// archived type. The expected values may be any constant expressions.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{freeze_layout, Archive};
+///
+/// #[derive(Archive)]
+/// struct Header {
+///     version: u32,
+///     flags: u8,
+/// }
+///
+/// freeze_layout! {
+///     Header => { size: 8, align: 4 },
+///     u64 => { size: 8, align: 8 },
+/// }
+/// ```
+///
+/// Changing the archived layout of a frozen type fails to compile:
+///
+/// ```compile_fail
+/// use rkyv::{freeze_layout, Archive};
+///
+/// #[derive(Archive)]
+/// struct Header {
+///     version: u32,
+///     flags: u8,
+///     extra: u64,
+/// }
+///
+/// freeze_layout! {
+///     Header => { size: 8, align: 4 },
+/// }
+/// ```
+#[macro_export]
+macro_rules! freeze_layout {
+    ($($ty:ty => { size: $size:expr, align: $align:expr $(,)? }),* $(,)?) => {
+        const _: () = {
+            $(
+                $crate::stability::assert_layout::<$crate::Archived<$ty>>(
+                    $size,
+                    $align,
+                );
+            )*
+        };
+    };
+}
+
+#[doc(hidden)]
+pub const fn assert_layout<T>(size: usize, align: usize) {
+    if core::mem::size_of::<T>() != size {
+        panic!("the size of a frozen archived type changed");
+    }
+    if core::mem::align_of::<T>() != align {
+        panic!("the alignment of a frozen archived type changed");
+    }
+}
+
+#[derive(Debug)]
+struct FormatChanged {
+    offset: usize,
+    expected_len: usize,
+    actual_len: usize,
+}
+
+impl fmt::Display for FormatChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "serialized bytes differ from the golden fixture at offset {} \
+             (expected {} bytes, found {} bytes)",
+            self.offset, self.expected_len, self.actual_len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormatChanged {}
+
+/// Serializes a value and checks that the bytes match a golden fixture.
+///
+/// Returns an error naming the first offset at which the bytes differ. Golden
+/// fixtures depend on the endianness, pointer width, and alignment features,
+/// so each fixture should only be checked with the features it was recorded
+/// with.
+#[cfg(feature = "alloc")]
+pub fn check_golden<T, E>(value: &T, expected: &[u8]) -> Result<(), E>
+where
+    T: Serialize<Strategy<AllocSerializer, E>>,
+    E: Source,
+{
+    let bytes = crate::to_bytes::<E>(value)?;
+    let actual = bytes.as_slice();
+    if actual == expected {
+        return Ok(());
+    }
+
+    let offset = actual
+        .iter()
+        .zip(expected.iter())
+        .position(|(a, e)| a != e)
+        .unwrap_or(actual.len().min(expected.len()));
+    fail!(FormatChanged {
+        offset,
+        expected_len: expected.len(),
+        actual_len: actual.len(),
+    });
+}
+
+#[cfg(feature = "format-stability")]
+mod frozen {
+    use core::mem::size_of;
+
+    use crate::primitive::FixedUsize;
+
+    // The width of archived sizes and relative pointer offsets.
+    const W: usize = size_of::<FixedUsize>();
+
+    const fn align(align: usize) -> usize {
+        if cfg!(feature = "unaligned") {
+            1
+        } else {
+            align
+        }
+    }
+
+    const fn round_up(size: usize, align: usize) -> usize {
+        (size + align - 1) / align * align
+    }
+
+    // The size of a one-byte field followed by a field with the given size and
+    // alignment, as in `(u8, T)` or a `repr(u8)` enum with a single field.
+    const fn tagged(size: usize, field_align: usize) -> usize {
+        round_up(round_up(1, field_align) + size, field_align)
+    }
+
+    freeze_layout! {
+        () => { size: 0, align: 1 },
+        bool => { size: 1, align: 1 },
+        u8 => { size: 1, align: 1 },
+        i8 => { size: 1, align: 1 },
+        u16 => { size: 2, align: align(2) },
+        i16 => { size: 2, align: align(2) },
+        u32 => { size: 4, align: align(4) },
+        i32 => { size: 4, align: align(4) },
+        u64 => { size: 8, align: align(8) },
+        i64 => { size: 8, align: align(8) },
+        u128 => { size: 16, align: align(16) },
+        i128 => { size: 16, align: align(16) },
+        f32 => { size: 4, align: align(4) },
+        f64 => { size: 8, align: align(8) },
+        char => { size: 4, align: align(4) },
+        usize => { size: W, align: align(W) },
+        isize => { size: W, align: align(W) },
+        [u32; 4] => { size: 16, align: align(4) },
+        (u8, u32) => { size: tagged(4, align(4)), align: align(4) },
+        Option<u32> => { size: tagged(4, align(4)), align: align(4) },
+        Option<u64> => { size: tagged(8, align(8)), align: align(8) },
+        Result<u32, u32> => { size: tagged(4, align(4)), align: align(4) },
+    }
+
+    #[cfg(feature = "alloc")]
+    mod alloc_types {
+        #[cfg(feature = "std")]
+        use std::{collections::BTreeMap, rc::Rc};
+
+        #[cfg(not(feature = "std"))]
+        use ::alloc::{
+            boxed::Box, collections::BTreeMap, rc::Rc, string::String, vec::Vec,
+        };
+
+        use super::{align, W};
+
+        freeze_layout! {
+            Box<u32> => { size: W, align: align(W) },
+            Box<[u32]> => { size: 2 * W, align: align(W) },
+            Box<str> => { size: 2 * W, align: align(W) },
+            Rc<u32> => { size: W, align: align(W) },
+            String => { size: 2 * W, align: align(W) },
+            Vec<u8> => { size: 2 * W, align: align(W) },
+            Vec<u64> => { size: 2 * W, align: align(W) },
+            BTreeMap<u32, u32> => { size: 2 * W, align: align(W) },
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod std_types {
+        use std::collections::HashMap;
+
+        use super::{align, round_up, W};
+
+        // Three words for the table followed by the 64-bit seed and the
+        // algorithm ID of the hasher configuration.
+        const HASH_MAP_SIZE: usize = if cfg!(feature = "unaligned") {
+            3 * W + 9
+        } else {
+            round_up(3 * W, 8) + 16
+        };
+
+        freeze_layout! {
+            HashMap<u32, u32> => {
+                size: HASH_MAP_SIZE,
+                align: align(8),
+            },
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "alloc",
+    not(feature = "big_endian"),
+    not(feature = "unaligned"),
+    not(any(feature = "pointer_width_16", feature = "pointer_width_64")),
+))]
+mod tests {
+    use core::num::NonZeroU32;
+
+    #[cfg(not(feature = "std"))]
+    use ::alloc::{boxed::Box, string::ToString, vec};
+    use rancor::Error;
+
+    use super::check_golden;
+    use crate::{with::Niche, Archive, Serialize};
+
+    // Golden fixtures for little-endian, 32-bit, aligned archives.
+
+    #[test]
+    fn golden_primitives() {
+        check_golden::<_, Error>(&(), &[]).unwrap();
+        check_golden::<_, Error>(&true, &[1]).unwrap();
+        check_golden::<_, Error>(&0x2au32, &[0x2a, 0, 0, 0]).unwrap();
+        check_golden::<_, Error>(&-2i16, &[0xfe, 0xff]).unwrap();
+        check_golden::<_, Error>(&1.5f64, &[0, 0, 0, 0, 0, 0, 0xf8, 0x3f])
+            .unwrap();
+        check_golden::<_, Error>(&'A', &[0x41, 0, 0, 0]).unwrap();
+        check_golden::<_, Error>(&[1u8, 2, 3], &[1, 2, 3]).unwrap();
+        check_golden::<_, Error>(&(1u8, 0x0203u16), &[1, 0, 3, 2]).unwrap();
+    }
+
+    #[test]
+    fn golden_enums() {
+        check_golden::<_, Error>(&Some(7u16), &[1, 0, 7, 0]).unwrap();
+        check_golden::<_, Error>(&None::<u16>, &[0, 0, 0, 0]).unwrap();
+        check_golden::<_, Error>(&Ok::<u8, u16>(5), &[0, 5, 0, 0]).unwrap();
+        check_golden::<_, Error>(&Err::<u8, u16>(6), &[1, 0, 6, 0]).unwrap();
+    }
+
+    #[test]
+    fn golden_derived_enums() {
+        #[derive(Archive, Serialize)]
+        #[archive(crate)]
+        enum Shape {
+            Empty,
+            Circle(u32),
+            Rect { w: u16, h: u8 },
+        }
+
+        // A one-byte tag followed by the fields of the variant, padded to the
+        // size of the largest variant
+        check_golden::<_, Error>(&Shape::Empty, &[0, 0, 0, 0, 0, 0, 0, 0])
+            .unwrap();
+        check_golden::<_, Error>(
+            &Shape::Circle(0x01020304),
+            &[1, 0, 0, 0, 4, 3, 2, 1],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &Shape::Rect { w: 0x0506, h: 7 },
+            &[2, 0, 6, 5, 7, 0, 0, 0],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn golden_niches() {
+        #[derive(Archive, Serialize)]
+        #[archive(crate)]
+        struct NicheBox {
+            #[with(Niche)]
+            value: Option<Box<u32>>,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(crate)]
+        struct NicheInt {
+            #[with(Niche)]
+            value: Option<NonZeroU32>,
+        }
+
+        // `None` is an invalid relative pointer with an offset of 1
+        check_golden::<_, Error>(&NicheBox { value: None }, &[1, 0, 0, 0])
+            .unwrap();
+        check_golden::<_, Error>(
+            &NicheBox {
+                value: Some(Box::new(42)),
+            },
+            &[42, 0, 0, 0, 0xfc, 0xff, 0xff, 0xff],
+        )
+        .unwrap();
+
+        // `None` is zero
+        check_golden::<_, Error>(&NicheInt { value: None }, &[0, 0, 0, 0])
+            .unwrap();
+        check_golden::<_, Error>(
+            &NicheInt {
+                value: NonZeroU32::new(5),
+            },
+            &[5, 0, 0, 0],
+        )
+        .unwrap();
+
+        // Options without a niche are tagged
+        check_golden::<_, Error>(
+            &Some(Box::new(9u8)),
+            &[9, 0, 0, 0, 1, 0, 0, 0, 0xf8, 0xff, 0xff, 0xff],
+        )
+        .unwrap();
+        check_golden::<_, Error>(&None::<Box<u8>>, &[0, 0, 0, 0, 0, 0, 0, 0])
+            .unwrap();
+    }
+
+    #[test]
+    fn golden_tuples() {
+        check_golden::<_, Error>(
+            &(0x0102u16, 3u8, 0x04050607u32),
+            &[2, 1, 3, 0, 7, 6, 5, 4],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &(1u8, vec![1u16, 2]),
+            &[1, 0, 2, 0, 1, 0, 0, 0, 0xf8, 0xff, 0xff, 0xff, 2, 0, 0, 0],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn golden_rel_ptrs() {
+        // Relative pointers are signed offsets from the pointer itself, and
+        // pointers to unsized types are followed by their metadata
+        check_golden::<_, Error>(
+            &Box::new(42u32),
+            &[42, 0, 0, 0, 0xfc, 0xff, 0xff, 0xff],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &vec![1u16, 2, 3].into_boxed_slice(),
+            &[1, 0, 2, 0, 3, 0, 0, 0, 0xf8, 0xff, 0xff, 0xff, 3, 0, 0, 0],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &Box::<str>::from("abc"),
+            &[b'a', b'b', b'c', 0, 0xfc, 0xff, 0xff, 0xff, 3, 0, 0, 0],
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn golden_hash_map() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(1u32, 2u32);
+
+        #[rustfmt::skip]
+        let expected = [
+            // Buckets, stored in reverse order before the control bytes. The
+            // key hashes to bucket 1.
+            1, 0, 0, 0, 2, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            // Control bytes for both buckets followed by the wraparound group
+            0xff, 0x28, 0xff, 0x28, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff,
+            // Padding
+            0, 0, 0, 0, 0, 0, 0,
+            // Pointer to the control bytes, length, and capacity
+            0xe8, 0xff, 0xff, 0xff, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0,
+            // Hasher seed and algorithm
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        check_golden::<_, Error>(&map, &expected).unwrap();
+    }
+
+    #[test]
+    fn golden_collections() {
+        check_golden::<_, Error>(
+            &vec![1u8, 2, 3],
+            &[1, 2, 3, 0, 0xfc, 0xff, 0xff, 0xff, 3, 0, 0, 0],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &"hi".to_string(),
+            &[b'h', b'i', 0, 0, 0, 0, 0, 2],
+        )
+        .unwrap();
+        check_golden::<_, Error>(
+            &"hello, world".to_string(),
+            &[
+                b'h', b'e', b'l', b'l', b'o', b',', b' ', b'w', b'o', b'r',
+                b'l', b'd', 12, 0, 0, 0, 0xf4, 0xff, 0xff, 0xff,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn golden_mismatch() {
+        let error = check_golden::<_, Error>(&0x2au32, &[0x2a, 0, 1, 0])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("offset 2"));
+
+        let error = check_golden::<_, Error>(&0x2au32, &[0x2a, 0])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("offset 2"));
+        assert!(error.contains("found 4 bytes"));
+    }
+}
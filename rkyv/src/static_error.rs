@@ -0,0 +1,146 @@
+//! Error reporting which never allocates.
+//!
+//! Composing errors with `rancor::Error` boxes the original error and every
+//! trace added to it. On targets without an allocator, or where error paths
+//! must not allocate, [`StaticError`] can be used as the error type instead.
+//! It records a static error code for the type of the original error along
+//! with numeric context, and discards everything else.
+//!
+//! With the `static-errors` feature enabled, validation traces the position
+//! of the value being validated with an [`ErrorOffset`], which a
+//! `StaticError` keeps as its offset.
+//!
+//! # Example
+//!
+//! ```
+//! use core::mem::size_of;
+//!
+//! use rkyv::{access, static_error::StaticError, to_bytes, Archived};
+//!
+//! let bytes = to_bytes::<StaticError>(&vec![1u32, 2, 3]).unwrap();
+//!
+//! // Only keep the root, which now points outside of the buffer.
+//! let root = bytes.len() - size_of::<Archived<Vec<u32>>>();
+//! let truncated = &bytes[root..];
+//! let error =
+//!     access::<Archived<Vec<u32>>, StaticError>(truncated).unwrap_err();
+//! assert_eq!(error.offset(), Some(0));
+//! assert_ne!(error.code(), 0);
+//! ```
+
+use core::{
+    any::{type_name, Any},
+    error::Error,
+    fmt,
+};
+
+use rancor::{Source, Trace};
+
+/// The position of the value which caused an error.
+///
+/// This is added as a trace by rkyv's error paths when the `static-errors`
+/// feature is enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorOffset(pub usize);
+
+impl fmt::Display for ErrorOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset {}", self.0)
+    }
+}
+
+/// An error which is constructed without allocating.
+///
+/// A static error holds the type name of the original error, a code hashed
+/// from that name, and the innermost [`ErrorOffset`] traced onto it. Other
+/// traces are counted but not kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaticError {
+    name: &'static str,
+    code: u32,
+    offset: Option<usize>,
+    traces: usize,
+}
+
+impl StaticError {
+    /// Returns the type name of the original error.
+    ///
+    /// Type names are meant for diagnostics, and may change between compiler
+    /// versions.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the code of the original error.
+    ///
+    /// The code is a hash of the type name of the original error, so errors
+    /// of the same type have the same code.
+    #[inline]
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the innermost offset traced onto the error, if any.
+    #[inline]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the number of traces which were discarded.
+    #[inline]
+    pub fn discarded_traces(&self) -> usize {
+        self.traces
+    }
+}
+
+// A 32-bit FNV-1a hash.
+fn hash_name(name: &str) -> u32 {
+    let mut hash = 0x811c_9dc5_u32;
+    for byte in name.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+impl fmt::Display for StaticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error {:#010x} ({})", self.code, self.name)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+// Unlike most error types in rkyv, this is implemented without `std` so that
+// static errors can be used on `no_std` targets.
+impl Error for StaticError {}
+
+impl Trace for StaticError {
+    fn trace<R>(mut self, trace: R) -> Self
+    where
+        R: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    {
+        match (&trace as &dyn Any).downcast_ref::<ErrorOffset>() {
+            Some(offset) if self.offset.is_none() => {
+                self.offset = Some(offset.0);
+            }
+            _ => self.traces += 1,
+        }
+        self
+    }
+}
+
+impl Source for StaticError {
+    fn new<T: Error + Send + Sync + 'static>(_: T) -> Self {
+        let name = type_name::<T>();
+        Self {
+            name,
+            code: hash_name(name),
+            offset: None,
+            traces: 0,
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! A read-only key-value store backed by a single archive file.
+//!
+//! A [`StoreBuilder`] collects entries and writes them to a file as an
+//! archived B-tree map, optionally alongside a [`BloomFilter`] over the keys.
+//! A [`Store`] loads the file back, validates it once, and then looks up
+//! values without deserializing anything. When the file has a bloom filter,
+//! lookups for missing keys can usually be rejected without searching the
+//! map.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     rancor::Error,
+//!     store::{Store, StoreBuilder},
+//! };
+//!
+//! let mut builder = StoreBuilder::<String, u32>::new();
+//! builder.insert("apples".to_string(), 3);
+//! builder.insert("pears".to_string(), 5);
+//! let bytes = builder.with_bloom_filter(10).to_bytes::<Error>().unwrap();
+//!
+//! let store = Store::<String, u32>::from_bytes::<Error>(bytes).unwrap();
+//! assert_eq!(store.get("apples").map(|v| v.to_native()), Some(3));
+//! assert!(store.get("plums").is_none());
+//! ```
+
+use core::{borrow::Borrow, fmt, hash::Hash, marker::PhantomData};
+use std::{collections::BTreeMap, path::Path};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+use rancor::{ResultExt as _, Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    backing::StableBacking,
+    collections::btree_map::ArchivedBTreeMap,
+    filter::{ArchivedBloomFilter, BloomFilter},
+    ser::AllocSerializer,
+    util::AlignedVec,
+    Archive, Deserialize, Serialize,
+};
+
+/// The root of a store file.
+///
+/// Store files are written by [`StoreBuilder`] and read by [`Store`].
+#[derive(Archive, Debug, Deserialize, Serialize)]
+#[archive(crate)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct StoreContents<K, V> {
+    map: BTreeMap<K, V>,
+    bloom: Option<BloomFilter>,
+}
+
+/// A builder for store files.
+///
+/// Later entries replace earlier entries with the same key.
+pub struct StoreBuilder<K, V> {
+    map: BTreeMap<K, V>,
+    bits_per_key: Option<usize>,
+}
+
+impl<K: Ord, V> StoreBuilder<K, V> {
+    /// Returns a new, empty builder.
+    pub fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+            bits_per_key: None,
+        }
+    }
+
+    /// Inserts an entry into the store, returning the previous value for the
+    /// key if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert(key, value)
+    }
+
+    /// Returns the number of entries in the store.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the store has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Writes a bloom filter over the keys with `bits_per_key` bits for each
+    /// key.
+    pub fn with_bloom_filter(mut self, bits_per_key: usize) -> Self {
+        self.bits_per_key = Some(bits_per_key);
+        self
+    }
+
+    /// Returns the contents of the store file.
+    pub fn finish(self) -> StoreContents<K, V>
+    where
+        K: Hash,
+    {
+        let bloom = self.bits_per_key.map(|bits_per_key| {
+            let mut bloom = BloomFilter::new(self.map.len(), bits_per_key);
+            for key in self.map.keys() {
+                bloom.insert(key);
+            }
+            bloom
+        });
+        StoreContents {
+            map: self.map,
+            bloom,
+        }
+    }
+
+    /// Serializes the store file to bytes.
+    pub fn to_bytes<E>(self) -> Result<AlignedVec, E>
+    where
+        K: Hash,
+        StoreContents<K, V>: Serialize<Strategy<AllocSerializer, E>>,
+        E: Source,
+    {
+        crate::to_bytes(&self.finish())
+    }
+
+    /// Serializes the store file and writes it to the file at the given path.
+    pub fn write_to_file<E>(self, path: impl AsRef<Path>) -> Result<(), E>
+    where
+        K: Hash,
+        StoreContents<K, V>: Serialize<Strategy<AllocSerializer, E>>,
+        E: Source,
+    {
+        let bytes = self.to_bytes::<E>()?;
+        std::fs::write(path, bytes.as_slice()).into_error()
+    }
+}
+
+impl<K: Ord, V> Default for StoreBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for StoreBuilder<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreBuilder")
+            .field("map", &self.map)
+            .field("bits_per_key", &self.bits_per_key)
+            .finish()
+    }
+}
+
+/// A read-only key-value store loaded from a store file.
+///
+/// The store file is validated when the store is created. Lookups search the
+/// archived map in place. Stores opened from a path read the file into an
+/// [`AlignedVec`], and stores can be created from any other
+/// [`StableBacking`] (like a memory map) with
+/// [`from_bytes`](Store::from_bytes).
+pub struct Store<K, V, B = AlignedVec> {
+    bytes: B,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Store<K, V>
+where
+    K: Archive + Ord,
+    K::Archived: Ord,
+    V: Archive,
+{
+    /// Reads and validates the store file at the given path.
+    #[cfg(feature = "bytecheck")]
+    pub fn open<E>(path: impl AsRef<Path>) -> Result<Self, E>
+    where
+        ArchivedStoreContents<K, V>: CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        let mut file = std::fs::File::open(path).into_error()?;
+        let mut bytes = AlignedVec::new();
+        bytes.extend_from_reader(&mut file).into_error()?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<K, V, B> Store<K, V, B>
+where
+    K: Archive + Ord,
+    K::Archived: Ord,
+    V: Archive,
+    B: StableBacking,
+{
+    /// Validates the bytes of a store file.
+    #[cfg(feature = "bytecheck")]
+    pub fn from_bytes<E>(bytes: B) -> Result<Self, E>
+    where
+        ArchivedStoreContents<K, V>: CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        crate::access::<ArchivedStoreContents<K, V>, E>(bytes.as_bytes())?;
+        // SAFETY: The bytes were just checked to contain valid store
+        // contents.
+        Ok(unsafe { Self::from_bytes_unchecked(bytes) })
+    }
+
+    /// Returns a store from the bytes of a store file without validating
+    /// them.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must contain a valid archived `StoreContents<K, V>`.
+    pub unsafe fn from_bytes_unchecked(bytes: B) -> Self {
+        Self {
+            bytes,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the bytes of the store file.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_bytes()
+    }
+
+    /// Returns the archived map of the store.
+    pub fn map(&self) -> &ArchivedBTreeMap<K::Archived, V::Archived> {
+        &self.contents().map
+    }
+
+    /// Returns the archived bloom filter of the store, if it has one.
+    pub fn bloom_filter(&self) -> Option<&ArchivedBloomFilter> {
+        self.contents().bloom.as_ref()
+    }
+
+    /// Returns the number of entries in the store.
+    pub fn len(&self) -> usize {
+        self.map().len()
+    }
+
+    /// Returns whether the store has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map().is_empty()
+    }
+
+    /// Returns the value associated with the given key, or `None` if the key
+    /// is not present in the store.
+    ///
+    /// If the store has a bloom filter, it is checked before searching the
+    /// map. The key must hash the same way as the keys the store was built
+    /// with.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V::Archived>
+    where
+        Q: Hash + Ord + ?Sized,
+        K::Archived: Borrow<Q>,
+    {
+        if let Some(bloom) = self.bloom_filter() {
+            if !bloom.contains(key) {
+                return None;
+            }
+        }
+        self.map().get(key)
+    }
+
+    /// Returns whether the store contains the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Ord + ?Sized,
+        K::Archived: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    fn contents(&self) -> &ArchivedStoreContents<K, V> {
+        // SAFETY: The bytes were checked to contain valid store contents when
+        // the store was created.
+        unsafe {
+            crate::access_unchecked::<ArchivedStoreContents<K, V>>(
+                self.as_bytes(),
+            )
+        }
+    }
+}
+
+impl<K, V, B> fmt::Debug for Store<K, V, B>
+where
+    K: Archive + Ord,
+    K::Archived: Ord,
+    V: Archive,
+    B: StableBacking,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Store")
+            .field("len", &self.len())
+            .field("bloom_filter", &self.bloom_filter().is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,489 @@
+//! Archived strings with pluggable encodings.
+//!
+//! [`ArchivedString`](crate::string::ArchivedString) always holds UTF-8, and
+//! validating it rejects anything else. Some strings can't be represented as
+//! UTF-8 without losing information, like platform strings on Windows, and
+//! some strings don't need to be checked at all. An [`ArchivedEncodedString`]
+//! is parameterized over an [`Encoding`], which decides which bytes are valid
+//! when the string is serialized and when it is validated:
+//!
+//! - [`Utf8`] accepts valid UTF-8, like `ArchivedString`.
+//! - [`Wtf8`] accepts [WTF-8], which is UTF-8 that may also contain unpaired
+//!   surrogates. Any sequence of UTF-16 code units, and so any Windows
+//!   `OsString`, can be encoded as WTF-8 without loss.
+//! - [`Latin1`] accepts any bytes, and maps each byte to the code point with
+//!   the same value (ISO 8859-1).
+//! - [`Bytes`] accepts any bytes, and gives them no meaning as text.
+//!
+//! Strings are archived with an encoding using the
+//! [`Encoded`](crate::with::Encoded) wrapper.
+//!
+//! [WTF-8]: https://simonsapin.github.io/wtf-8/
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access, rancor::Error, string::encoded::Latin1, to_bytes,
+//!     with::Encoded, Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct Record {
+//!     #[with(Encoded<Latin1>)]
+//!     name: Vec<u8>,
+//! }
+//!
+//! let record = Record {
+//!     name: b"Caf\xe9".to_vec(),
+//! };
+//! let bytes = to_bytes::<Error>(&record).unwrap();
+//! let archived = access::<ArchivedRecord, Error>(&bytes).unwrap();
+//! assert_eq!(archived.name.chars().collect::<String>(), "Café");
+//! ```
+
+use core::{fmt, hash, marker::PhantomData, str};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::{string::String, vec::Vec};
+use munge::munge;
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Place, Portable,
+};
+
+/// A policy which decides which bytes an [`ArchivedEncodedString`] may hold.
+pub trait Encoding {
+    /// The name of the encoding, used in error messages.
+    const NAME: &'static str;
+
+    /// Checks whether the given bytes are valid in this encoding.
+    ///
+    /// Returns the index of the first invalid byte if they are not.
+    fn validate(bytes: &[u8]) -> Result<(), usize>;
+}
+
+/// The UTF-8 encoding.
+///
+/// Strings must be valid UTF-8, which is the same as `ArchivedString`.
+#[derive(Debug)]
+pub struct Utf8;
+
+impl Encoding for Utf8 {
+    const NAME: &'static str = "UTF-8";
+
+    #[inline]
+    fn validate(bytes: &[u8]) -> Result<(), usize> {
+        str::from_utf8(bytes)
+            .map(|_| ())
+            .map_err(|error| error.valid_up_to())
+    }
+}
+
+/// The WTF-8 encoding.
+///
+/// WTF-8 is UTF-8 which may also encode surrogate code points, as long as no
+/// lead surrogate is directly followed by a trail surrogate. Those pairs must
+/// be encoded as the supplementary code point they represent instead, so each
+/// sequence of UTF-16 code units has exactly one WTF-8 encoding.
+#[derive(Debug)]
+pub struct Wtf8;
+
+impl Encoding for Wtf8 {
+    const NAME: &'static str = "WTF-8";
+
+    fn validate(bytes: &[u8]) -> Result<(), usize> {
+        let mut i = 0;
+        while i < bytes.len() {
+            let width = match bytes[i] {
+                0x00..=0x7f => 1,
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => return Err(i),
+            };
+            if i + width > bytes.len() {
+                return Err(i);
+            }
+            if bytes[i + 1..i + width].iter().any(|b| b & 0xc0 != 0x80) {
+                return Err(i);
+            }
+            if width > 1 {
+                // Reject overlong encodings and code points above U+10FFFF.
+                let second = bytes[i + 1];
+                match bytes[i] {
+                    0xe0 if second < 0xa0 => return Err(i),
+                    0xf0 if second < 0x90 => return Err(i),
+                    0xf4 if second > 0x8f => return Err(i),
+                    _ => (),
+                }
+            }
+            if is_lead_surrogate(&bytes[i..])
+                && is_trail_surrogate(&bytes[i + width..])
+            {
+                return Err(i + width);
+            }
+            i += width;
+        }
+        Ok(())
+    }
+}
+
+fn is_lead_surrogate(bytes: &[u8]) -> bool {
+    matches!(bytes, [0xed, 0xa0..=0xaf, ..])
+}
+
+fn is_trail_surrogate(bytes: &[u8]) -> bool {
+    matches!(bytes, [0xed, 0xb0..=0xbf, ..])
+}
+
+/// The Latin-1 (ISO 8859-1) encoding.
+///
+/// Every byte is valid, and represents the code point with the same value.
+#[derive(Debug)]
+pub struct Latin1;
+
+impl Encoding for Latin1 {
+    const NAME: &'static str = "Latin-1";
+
+    #[inline]
+    fn validate(_: &[u8]) -> Result<(), usize> {
+        Ok(())
+    }
+}
+
+/// Raw bytes with no encoding.
+///
+/// Every byte is valid, and the bytes have no meaning as text.
+#[derive(Debug)]
+pub struct Bytes;
+
+impl Encoding for Bytes {
+    const NAME: &'static str = "bytes";
+
+    #[inline]
+    fn validate(_: &[u8]) -> Result<(), usize> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct InvalidEncoding {
+    encoding: &'static str,
+    index: usize,
+}
+
+impl fmt::Display for InvalidEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} at byte {} of encoded string",
+            self.encoding, self.index,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidEncoding {}
+
+/// An archived string whose bytes are valid in the encoding `E`.
+///
+/// See the [module docs](self) for more information.
+#[derive(Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedEncodedString<E> {
+    bytes: ArchivedVec<u8>,
+    _encoding: PhantomData<E>,
+}
+
+impl<E> ArchivedEncodedString<E> {
+    /// Returns the encoded bytes of the string.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Returns the length of the string in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns whether the string is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl<E: Encoding> ArchivedEncodedString<E> {
+    /// Resolves an archived encoded string from the given bytes.
+    #[inline]
+    pub fn resolve_from_bytes(
+        bytes: &[u8],
+        resolver: VecResolver,
+        out: Place<Self>,
+    ) {
+        Self::resolve_from_len(bytes.len(), resolver, out);
+    }
+
+    /// Resolves an archived encoded string from the length of its bytes.
+    #[inline]
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: VecResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedEncodedString { bytes, .. } = out);
+        ArchivedVec::resolve_from_len(len, resolver, bytes);
+    }
+
+    /// Serializes an archived encoded string from the given bytes.
+    ///
+    /// Fails if the bytes are not valid in the encoding `E`.
+    #[inline]
+    pub fn serialize_from_bytes<S>(
+        bytes: &[u8],
+        serializer: &mut S,
+    ) -> Result<VecResolver, S::Error>
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        if let Err(index) = E::validate(bytes) {
+            fail!(InvalidEncoding {
+                encoding: E::NAME,
+                index,
+            });
+        }
+        ArchivedVec::serialize_from_slice(bytes, serializer)
+    }
+}
+
+impl ArchivedEncodedString<Utf8> {
+    /// Returns the string as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The bytes of a UTF-8 encoded string are valid UTF-8.
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+}
+
+impl ArchivedEncodedString<Wtf8> {
+    /// Returns an iterator over the code points of the string.
+    ///
+    /// Code points are returned as `u32`s because they may be surrogates,
+    /// which are not valid `char`s.
+    #[inline]
+    pub fn code_points(&self) -> CodePoints<'_> {
+        CodePoints {
+            bytes: self.as_bytes(),
+        }
+    }
+
+    /// Returns an iterator over the string encoded as UTF-16.
+    ///
+    /// Unpaired surrogates are returned as-is, so the code units are the same
+    /// as those the string was encoded from. On Windows, they can be turned
+    /// back into an `OsString` with `OsStringExt::from_wide`.
+    #[inline]
+    pub fn encode_wide(&self) -> EncodeWide<'_> {
+        EncodeWide {
+            code_points: self.code_points(),
+            trail: None,
+        }
+    }
+
+    /// Returns the string as a `str` if it holds no surrogates.
+    #[inline]
+    pub fn to_str(&self) -> Option<&str> {
+        str::from_utf8(self.as_bytes()).ok()
+    }
+
+    /// Converts the string to a `String`, replacing each surrogate with
+    /// U+FFFD REPLACEMENT CHARACTER.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> String {
+        self.code_points()
+            .map(|c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+}
+
+impl ArchivedEncodedString<Latin1> {
+    /// Returns an iterator over the characters of the string.
+    #[inline]
+    pub fn chars(&self) -> Latin1Chars<'_> {
+        Latin1Chars {
+            bytes: self.as_bytes().iter(),
+        }
+    }
+}
+
+impl<E: Encoding> fmt::Debug for ArchivedEncodedString<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedEncodedString")
+            .field("encoding", &E::NAME)
+            .field("bytes", &self.as_bytes())
+            .finish()
+    }
+}
+
+impl<E> PartialEq for ArchivedEncodedString<E> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<E> Eq for ArchivedEncodedString<E> {}
+
+impl<E> hash::Hash for ArchivedEncodedString<E> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+/// An iterator over the code points of a WTF-8 string.
+///
+/// Returned by [`ArchivedEncodedString::code_points`].
+#[derive(Clone, Debug)]
+pub struct CodePoints<'a> {
+    bytes: &'a [u8],
+}
+
+impl Iterator for CodePoints<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let (&first, rest) = self.bytes.split_first()?;
+        let (width, mut code_point) = match first {
+            0x00..=0x7f => (1, first as u32),
+            0xc0..=0xdf => (2, first as u32 & 0x1f),
+            0xe0..=0xef => (3, first as u32 & 0x0f),
+            _ => (4, first as u32 & 0x07),
+        };
+        let width = width.min(self.bytes.len());
+        for &byte in &rest[..width - 1] {
+            code_point = (code_point << 6) | (byte as u32 & 0x3f);
+        }
+        self.bytes = &self.bytes[width..];
+        Some(code_point)
+    }
+}
+
+/// An iterator over the UTF-16 code units of a WTF-8 string.
+///
+/// Returned by [`ArchivedEncodedString::encode_wide`].
+#[derive(Clone, Debug)]
+pub struct EncodeWide<'a> {
+    code_points: CodePoints<'a>,
+    trail: Option<u16>,
+}
+
+impl Iterator for EncodeWide<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        if let Some(trail) = self.trail.take() {
+            return Some(trail);
+        }
+        let code_point = self.code_points.next()?;
+        if code_point > 0xffff {
+            let offset = code_point - 0x1_0000;
+            self.trail = Some(0xdc00 | (offset & 0x3ff) as u16);
+            Some(0xd800 | (offset >> 10) as u16)
+        } else {
+            Some(code_point as u16)
+        }
+    }
+}
+
+/// An iterator over the characters of a Latin-1 string.
+///
+/// Returned by [`ArchivedEncodedString::chars`].
+#[derive(Clone, Debug)]
+pub struct Latin1Chars<'a> {
+    bytes: core::slice::Iter<'a, u8>,
+}
+
+impl Iterator for Latin1Chars<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.bytes.next().map(|&b| b as char)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bytes.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Latin1Chars<'_> {}
+
+/// Encodes the given UTF-16 code units as WTF-8.
+///
+/// Unpaired surrogates are encoded as-is, so the result can be decoded back
+/// into the same code units with [`ArchivedEncodedString::encode_wide`].
+#[cfg(feature = "alloc")]
+pub fn wtf8_from_wide(wide: impl IntoIterator<Item = u16>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for result in char::decode_utf16(wide) {
+        match result {
+            Ok(c) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            Err(error) => {
+                let unit = error.unpaired_surrogate();
+                bytes.extend_from_slice(&[
+                    0xe0 | (unit >> 12) as u8,
+                    0x80 | ((unit >> 6) & 0x3f) as u8,
+                    0x80 | (unit & 0x3f) as u8,
+                ]);
+            }
+        }
+    }
+    bytes
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+    use rancor::fail;
+
+    use super::{ArchivedEncodedString, Encoding, InvalidEncoding};
+
+    unsafe impl<E, C> Verify<C> for ArchivedEncodedString<E>
+    where
+        E: Encoding,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            if let Err(index) = E::validate(self.as_bytes()) {
+                fail!(InvalidEncoding {
+                    encoding: E::NAME,
+                    index,
+                });
+            }
+            Ok(())
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Archived versions of string types.
 
+pub mod encoded;
 pub mod repr;
 
 use core::{
@@ -17,7 +18,18 @@ use munge::munge;
 use rancor::Fallible;
 use repr::{ArchivedStringRepr, INLINE_CAPACITY};
 
-use crate::{Place, Portable, SerializeUnsized};
+#[cfg(feature = "alloc")]
+use crate::analyze::{Analyze, TypeSizeReport};
+#[cfg(feature = "serde")]
+use crate::export::Export;
+use crate::{
+    redact::Redact,
+    schema::{SchemaHash, SchemaHasher},
+    search::{self, Needle as _, SplitStr, StrNeedle},
+    ser::Writer,
+    ArchivedSize, Extract, ExtractUnsized as _, Place, Portable,
+    SerializeUnsized,
+};
 
 /// An archived [`String`].
 ///
@@ -43,6 +55,44 @@ impl ArchivedString {
         self.repr.as_str()
     }
 
+    /// Returns whether the string contains the given pattern.
+    ///
+    /// With the `memchr` feature enabled, this search is accelerated with
+    /// SIMD.
+    #[inline]
+    pub fn contains<'n>(&self, pattern: impl StrNeedle<'n>) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    /// Returns the byte index of the first occurrence of the given pattern in
+    /// the string.
+    ///
+    /// With the `memchr` feature enabled, this search is accelerated with
+    /// SIMD.
+    #[inline]
+    pub fn find<'n>(&self, pattern: impl StrNeedle<'n>) -> Option<usize> {
+        search::find(self.as_bytes(), pattern.into_needle().as_bytes())
+    }
+
+    /// Returns an iterator over the substrings of the string which are
+    /// separated by the given pattern.
+    ///
+    /// See [`SplitStr`] for more information.
+    #[inline]
+    pub fn split<'a, 'n>(
+        &'a self,
+        pattern: impl StrNeedle<'n>,
+    ) -> SplitStr<'a, 'n> {
+        SplitStr::new(self.as_str(), pattern)
+    }
+
+    /// Returns whether the string starts with the given pattern.
+    #[inline]
+    pub fn starts_with<'n>(&self, pattern: impl StrNeedle<'n>) -> bool {
+        self.as_bytes()
+            .starts_with(pattern.into_needle().as_bytes())
+    }
+
     /// Extracts a pinned mutable string slice containing the entire
     /// `ArchivedString`.
     #[inline]
@@ -92,6 +142,35 @@ impl ArchivedString {
     }
 }
 
+impl ArchivedSize for ArchivedString {
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        if self.repr.is_inline() {
+            0
+        } else {
+            self.repr.len()
+        }
+    }
+}
+
+impl SchemaHash for ArchivedString {
+    const SCHEMA_HASH: u64 = SchemaHasher::new()
+        .write_str("String")
+        .write_layout::<Self>()
+        .write_endianness()
+        .finish();
+}
+
+#[cfg(feature = "alloc")]
+impl Analyze for ArchivedString {
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        if !self.repr.is_inline() {
+            report.record_out_of_line(self.as_str());
+        }
+    }
+}
+
 impl AsRef<str> for ArchivedString {
     #[inline]
     fn as_ref(&self) -> &str {
@@ -131,6 +210,58 @@ impl fmt::Display for ArchivedString {
 
 impl Eq for ArchivedString {}
 
+impl Redact for ArchivedString {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        self.pin_mut_str().redact();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Export for ArchivedString {
+    #[inline]
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.as_str().export(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ArchivedString {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
+impl<S> Extract<S> for ArchivedString
+where
+    S: Fallible + Writer + ?Sized,
+{
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn extract(&self, serializer: &mut S) -> Result<StringResolver, S::Error> {
+        if self.len() <= INLINE_CAPACITY {
+            Ok(StringResolver { pos: 0 })
+        } else {
+            Ok(StringResolver {
+                pos: self.as_str().extract_unsized(serializer)?,
+            })
+        }
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, resolver: StringResolver, out: Place<Self>) {
+        Self::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
 impl hash::Hash for ArchivedString {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
@@ -236,6 +367,7 @@ impl PartialOrd<ArchivedString> for str {
 }
 
 /// The resolver for `String`.
+#[derive(Clone, Copy)]
 pub struct StringResolver {
     pos: usize,
 }
@@ -275,8 +407,10 @@ mod verify {
                 };
 
                 let range = unsafe { context.push_prefix_subtree(ptr)? };
-                unsafe {
-                    str::check_bytes(ptr, context)?;
+                if context.should_check_subtree() {
+                    unsafe {
+                        str::check_bytes(ptr, context)?;
+                    }
                 }
                 unsafe {
                     context.pop_subtree_range(range)?;
@@ -0,0 +1,438 @@
+//! N-dimensional arrays which can be indexed in place.
+//!
+//! An [`NdArray`] stores the shape and strides of an array alongside its
+//! elements, and validating an archived array checks that every index within
+//! its shape lands inside its elements. Archived arrays can be indexed and
+//! iterated without copying, and with the `ndarray` feature they can be viewed
+//! as an `ndarray::ArrayView`.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access_unchecked, rancor::Error, tensor::NdArray, to_bytes, Archived,
+//! };
+//!
+//! let value =
+//!     NdArray::from_shape_vec::<Error>(vec![2, 3], vec![1u32, 2, 3, 4, 5, 6])
+//!         .unwrap();
+//!
+//! let bytes = to_bytes::<Error>(&value).unwrap();
+//! let archived =
+//!     unsafe { access_unchecked::<Archived<NdArray<u32>>>(&bytes) };
+//! assert_eq!(archived.ndim(), 2);
+//! assert_eq!(archived.get(&[1, 2]).map(|v| v.to_native()), Some(6));
+//! assert!(archived.get(&[2, 0]).is_none());
+//! ```
+
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+use munge::munge;
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    primitive::ArchivedUsize,
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Portable, Serialize,
+};
+
+/// An n-dimensional array.
+///
+/// The element at an index is found by multiplying each coordinate of the
+/// index by the stride of its axis and summing the results. Arrays built
+/// with [`from_shape_vec`](NdArray::from_shape_vec) are laid out in row-major
+/// order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NdArray<T> {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    data: Vec<T>,
+}
+
+#[derive(Debug)]
+enum NdArrayError {
+    DimensionMismatch { shape: usize, strides: usize },
+    Overflow,
+    LengthMismatch { expected: usize, actual: usize },
+    OutOfBounds { offset: usize, len: usize },
+}
+
+impl fmt::Display for NdArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DimensionMismatch { shape, strides } => write!(
+                f,
+                "array shape has {} dimensions but strides have {}",
+                shape, strides,
+            ),
+            Self::Overflow => write!(f, "array shape overflows `usize`"),
+            Self::LengthMismatch { expected, actual } => write!(
+                f,
+                "array shape requires {} elements but {} were provided",
+                expected, actual,
+            ),
+            Self::OutOfBounds { offset, len } => write!(
+                f,
+                "array strides reach element {} but there are only {} elements",
+                offset, len,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NdArrayError {}
+
+impl<T> NdArray<T> {
+    /// Builds an array with the given shape from elements in row-major order.
+    ///
+    /// Returns an error if the number of elements does not match the shape.
+    pub fn from_shape_vec<E: Source>(
+        shape: Vec<usize>,
+        data: Vec<T>,
+    ) -> Result<Self, E> {
+        let expected = match element_count(&shape) {
+            Some(expected) => expected,
+            None => fail!(NdArrayError::Overflow),
+        };
+        if expected != data.len() {
+            fail!(NdArrayError::LengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+        let strides = row_major_strides(&shape);
+        Ok(Self {
+            shape,
+            strides,
+            data,
+        })
+    }
+
+    /// Builds an array with the given shape and strides.
+    ///
+    /// Strides may be zero to repeat elements along an axis. Returns an error
+    /// if the shape and strides have different numbers of dimensions, or if
+    /// any index within the shape is out of bounds of the elements.
+    pub fn from_shape_strides_vec<E: Source>(
+        shape: Vec<usize>,
+        strides: Vec<usize>,
+        data: Vec<T>,
+    ) -> Result<Self, E> {
+        check_layout(&shape, &strides, data.len())?;
+        Ok(Self {
+            shape,
+            strides,
+            data,
+        })
+    }
+
+    /// Returns the length of each axis of the array.
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Returns the stride of each axis of the array.
+    #[inline]
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    /// Returns the number of dimensions of the array.
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Returns the number of elements in the array.
+    ///
+    /// This may differ from the length of the underlying elements if the
+    /// array has zero strides.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Returns whether the array has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the underlying elements of the array.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the element at the given index, or `None` if the index is out
+    /// of bounds.
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        let offset = offset(&self.shape, &self.strides, index)?;
+        self.data.get(offset)
+    }
+
+    /// Returns an iterator over the elements of the array in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len()).map(move |i| {
+            &self.data[linear_offset(&self.shape, &self.strides, i)]
+        })
+    }
+}
+
+/// An archived `NdArray`.
+#[derive(Debug, Portable)]
+#[archive(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedNdArray<T> {
+    pub(crate) shape: ArchivedVec<ArchivedUsize>,
+    pub(crate) strides: ArchivedVec<ArchivedUsize>,
+    pub(crate) data: ArchivedVec<T>,
+}
+
+impl<T> ArchivedNdArray<T> {
+    /// Returns the length of each axis of the array.
+    #[inline]
+    pub fn shape(&self) -> &[ArchivedUsize] {
+        &self.shape
+    }
+
+    /// Returns the stride of each axis of the array.
+    #[inline]
+    pub fn strides(&self) -> &[ArchivedUsize] {
+        &self.strides
+    }
+
+    /// Returns the number of dimensions of the array.
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Returns the number of elements in the array.
+    ///
+    /// This may differ from the length of the underlying elements if the
+    /// array has zero strides.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shape.iter().map(|d| d.to_native() as usize).product()
+    }
+
+    /// Returns whether the array has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the underlying elements of the array.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the element at the given index, or `None` if the index is out
+    /// of bounds.
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        let offset = offset(&self.shape, &self.strides, index)?;
+        self.data.get(offset)
+    }
+
+    /// Returns an iterator over the elements of the array in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len()).map(move |i| {
+            &self.data[linear_offset(&self.shape, &self.strides, i)]
+        })
+    }
+}
+
+// The length or stride of an axis.
+trait Extent {
+    fn extent(&self) -> usize;
+}
+
+impl Extent for usize {
+    #[inline]
+    fn extent(&self) -> usize {
+        *self
+    }
+}
+
+impl Extent for ArchivedUsize {
+    #[inline]
+    fn extent(&self) -> usize {
+        self.to_native() as usize
+    }
+}
+
+fn element_count<S: Extent>(shape: &[S]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |count, d| count.checked_mul(d.extent()))
+}
+
+pub(crate) fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = Vec::with_capacity(shape.len());
+    let mut stride = 1usize;
+    for d in shape.iter().rev() {
+        strides.push(stride);
+        stride = stride.wrapping_mul(*d);
+    }
+    strides.reverse();
+    strides
+}
+
+fn check_layout<S: Extent, E: Source>(
+    shape: &[S],
+    strides: &[S],
+    len: usize,
+) -> Result<(), E> {
+    if shape.len() != strides.len() {
+        fail!(NdArrayError::DimensionMismatch {
+            shape: shape.len(),
+            strides: strides.len(),
+        });
+    }
+    if element_count(shape).is_none() {
+        fail!(NdArrayError::Overflow);
+    }
+    if shape.iter().any(|d| d.extent() == 0) {
+        return Ok(());
+    }
+
+    // The last element of the array is the furthest from the first one.
+    let mut last = 0usize;
+    for (d, s) in shape.iter().zip(strides.iter()) {
+        let reach = (d.extent() - 1).checked_mul(s.extent());
+        last = match reach.and_then(|reach| last.checked_add(reach)) {
+            Some(last) => last,
+            None => fail!(NdArrayError::Overflow),
+        };
+    }
+    if last >= len {
+        fail!(NdArrayError::OutOfBounds { offset: last, len });
+    }
+    Ok(())
+}
+
+fn offset<S: Extent>(
+    shape: &[S],
+    strides: &[S],
+    index: &[usize],
+) -> Option<usize> {
+    if index.len() != shape.len() || index.len() != strides.len() {
+        return None;
+    }
+    let mut offset = 0usize;
+    for ((i, d), s) in index.iter().zip(shape).zip(strides) {
+        if *i >= d.extent() {
+            return None;
+        }
+        offset = offset.checked_add(i.checked_mul(s.extent())?)?;
+    }
+    Some(offset)
+}
+
+// Returns the offset of the element at position `i` in row-major order.
+fn linear_offset<S: Extent>(shape: &[S], strides: &[S], mut i: usize) -> usize {
+    let mut offset = 0;
+    for (d, s) in shape.iter().zip(strides).rev() {
+        let d = d.extent();
+        offset += i % d * s.extent();
+        i /= d;
+    }
+    offset
+}
+
+/// The resolver for an archived `NdArray`.
+pub struct NdArrayResolver {
+    pub(crate) shape: VecResolver,
+    pub(crate) strides: VecResolver,
+    pub(crate) data: VecResolver,
+}
+
+impl<T: Archive> Archive for NdArray<T> {
+    type Archived = ArchivedNdArray<T::Archived>;
+    type Resolver = NdArrayResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedNdArray { shape, strides, data } = out);
+        ArchivedVec::resolve_from_slice(&self.shape, resolver.shape, shape);
+        ArchivedVec::resolve_from_slice(
+            &self.strides,
+            resolver.strides,
+            strides,
+        );
+        ArchivedVec::resolve_from_slice(&self.data, resolver.data, data);
+    }
+}
+
+impl<T, S> Serialize<S> for NdArray<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        Ok(NdArrayResolver {
+            shape: ArchivedVec::serialize_from_slice(&self.shape, serializer)?,
+            strides: ArchivedVec::serialize_from_slice(
+                &self.strides,
+                serializer,
+            )?,
+            data: ArchivedVec::serialize_from_slice(&self.data, serializer)?,
+        })
+    }
+}
+
+impl<T, D> Deserialize<NdArray<T>, D> for ArchivedNdArray<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(
+        &self,
+        deserializer: &mut D,
+    ) -> Result<NdArray<T>, D::Error> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for value in self.data.iter() {
+            data.push(value.deserialize(deserializer)?);
+        }
+        Ok(NdArray {
+            shape: self.shape.iter().map(|d| d.extent()).collect(),
+            strides: self.strides.iter().map(|s| s.extent()).collect(),
+            data,
+        })
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+
+    use super::{check_layout, ArchivedNdArray};
+
+    unsafe impl<T, C> Verify<C> for ArchivedNdArray<T>
+    where
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            check_layout(&self.shape, &self.strides, self.data.len())
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Helpers for testing archive implementations.
+//!
+//! Custom `Archive`, `Serialize`, and `Deserialize` impls are usually tested
+//! with a single serializer and deserializer, and can break with the others.
+//! [`round_trip`] serializes a value with every serializer, accesses it with
+//! and without validation, and deserializes it with every shared pointer
+//! strategy. Each combination must deserialize to a value equal to the
+//! original.
+//!
+//! Endianness and pointer width are chosen with features when rkyv is
+//! compiled, so they can't be varied at runtime. The [`RoundTripReport`]
+//! records which ones were used, and the same tests can be run again with
+//! different features (e.g. `--features rkyv/big_endian`) to cover them.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{test_util::round_trip, Archive, Deserialize, Serialize};
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! #[archive(check_bytes)]
+//! struct Example {
+//!     id: u32,
+//!     name: String,
+//! }
+//!
+//! let report = round_trip(&Example {
+//!     id: 42,
+//!     name: "hello world".to_string(),
+//! });
+//! assert_eq!(report.runs().len(), 8);
+//! ```
+
+use core::fmt::Debug;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::vec::Vec;
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+use rancor::{Error, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::{
+    de::pooling::{Duplicate, Unify},
+    ser::{AllocSerializer, CoreSerializer, Positional as _},
+    util::{deserialize, serialize_into},
+    Archive, Deserialize, Serialize,
+};
+
+/// The size of the buffer and scratch space of the core serializer used by
+/// [`round_trip`].
+pub const CORE_BUFFER_SIZE: usize = 4096;
+
+/// The core serializer used by [`round_trip`].
+pub type TestCoreSerializer =
+    CoreSerializer<CORE_BUFFER_SIZE, CORE_BUFFER_SIZE>;
+
+/// A serializer used by a round trip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializerKind {
+    /// A [`CoreSerializer`] which doesn't allocate.
+    Core,
+    /// An [`AllocSerializer`].
+    Alloc,
+}
+
+/// A shared pointer strategy used to deserialize a round trip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolingKind {
+    /// The [`Unify`] strategy, which deserializes shared pointers once.
+    Unify,
+    /// The [`Duplicate`] strategy, which deserializes shared pointers each
+    /// time they are encountered.
+    Duplicate,
+}
+
+/// A single combination of serializer, validation, and deserializer which was
+/// round-tripped successfully.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Run {
+    /// The serializer used.
+    pub serializer: SerializerKind,
+    /// Whether the archive was validated before it was accessed.
+    pub validated: bool,
+    /// The shared pointer strategy used to deserialize.
+    pub pooling: PoolingKind,
+    /// The length of the serialized archive in bytes.
+    pub len: usize,
+}
+
+/// The combinations exercised by [`round_trip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundTripReport {
+    runs: Vec<Run>,
+}
+
+impl RoundTripReport {
+    /// Returns each combination which was round-tripped.
+    #[inline]
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    /// Returns whether archives were little-endian.
+    #[inline]
+    pub fn is_little_endian(&self) -> bool {
+        cfg!(not(feature = "big_endian"))
+    }
+
+    /// Returns the width of archived pointers in bits.
+    #[inline]
+    pub fn pointer_width(&self) -> usize {
+        if cfg!(feature = "pointer_width_16") {
+            16
+        } else if cfg!(feature = "pointer_width_64") {
+            64
+        } else {
+            32
+        }
+    }
+}
+
+/// Round-trips the given value through every combination of serializer,
+/// validation, and deserializer.
+///
+/// Validation is only exercised when the `bytecheck` feature is enabled. See
+/// the [module docs](self) for more information.
+///
+/// # Panics
+///
+/// Panics if the value fails to serialize, validate, or deserialize, or if any
+/// deserialized value is not equal to the original.
+#[cfg(not(feature = "bytecheck"))]
+pub fn round_trip<T>(value: &T) -> RoundTripReport
+where
+    T: Debug
+        + PartialEq
+        + Serialize<Strategy<TestCoreSerializer, Error>>
+        + Serialize<Strategy<AllocSerializer, Error>>,
+    T::Archived: Deserialize<T, Strategy<Unify, Error>>
+        + Deserialize<T, Strategy<Duplicate, Error>>,
+{
+    round_trip_with(value, |_, _| ())
+}
+
+/// Round-trips the given value through every combination of serializer,
+/// validation, and deserializer.
+///
+/// Validation is only exercised when the `bytecheck` feature is enabled. See
+/// the [module docs](self) for more information.
+///
+/// # Panics
+///
+/// Panics if the value fails to serialize, validate, or deserialize, or if any
+/// deserialized value is not equal to the original.
+#[cfg(feature = "bytecheck")]
+pub fn round_trip<T>(value: &T) -> RoundTripReport
+where
+    T: Debug
+        + PartialEq
+        + Serialize<Strategy<TestCoreSerializer, Error>>
+        + Serialize<Strategy<AllocSerializer, Error>>,
+    T::Archived: Deserialize<T, Strategy<Unify, Error>>
+        + Deserialize<T, Strategy<Duplicate, Error>>
+        + CheckBytes<Strategy<DefaultValidator, Error>>,
+{
+    round_trip_with(value, |bytes, run| {
+        if let Err(error) = crate::access::<T::Archived, Error>(bytes) {
+            panic!("failed to validate archive for {:?}: {}", run, error);
+        }
+    })
+}
+
+fn round_trip_with<T>(
+    value: &T,
+    validate: impl Fn(&[u8], &Run),
+) -> RoundTripReport
+where
+    T: Debug
+        + PartialEq
+        + Serialize<Strategy<TestCoreSerializer, Error>>
+        + Serialize<Strategy<AllocSerializer, Error>>,
+    T::Archived: Deserialize<T, Strategy<Unify, Error>>
+        + Deserialize<T, Strategy<Duplicate, Error>>,
+{
+    let mut runs = Vec::new();
+
+    let serializer = match serialize_into(value, TestCoreSerializer::default())
+    {
+        Ok(serializer) => serializer,
+        Err(error) => panic!("failed to serialize with core: {}", error),
+    };
+    let len = serializer.pos();
+    check(
+        value,
+        &serializer.writer.inner()[..len],
+        SerializerKind::Core,
+        &validate,
+        &mut runs,
+    );
+
+    let serializer = match serialize_into(value, AllocSerializer::default()) {
+        Ok(serializer) => serializer,
+        Err(error) => panic!("failed to serialize with alloc: {}", error),
+    };
+    check(
+        value,
+        &serializer.writer,
+        SerializerKind::Alloc,
+        &validate,
+        &mut runs,
+    );
+
+    RoundTripReport { runs }
+}
+
+fn check<T>(
+    value: &T,
+    bytes: &[u8],
+    serializer: SerializerKind,
+    validate: &impl Fn(&[u8], &Run),
+    runs: &mut Vec<Run>,
+) where
+    T: Archive + Debug + PartialEq,
+    T::Archived: Deserialize<T, Strategy<Unify, Error>>
+        + Deserialize<T, Strategy<Duplicate, Error>>,
+{
+    for validated in [false, true] {
+        if validated && cfg!(not(feature = "bytecheck")) {
+            continue;
+        }
+        for pooling in [PoolingKind::Unify, PoolingKind::Duplicate] {
+            let run = Run {
+                serializer,
+                validated,
+                pooling,
+                len: bytes.len(),
+            };
+            if validated {
+                validate(bytes, &run);
+            }
+
+            // SAFETY: The bytes were just serialized from a `T`, and were
+            // validated if `validated` is true.
+            let archived =
+                unsafe { crate::access_unchecked::<T::Archived>(bytes) };
+            let result = match pooling {
+                PoolingKind::Unify => {
+                    deserialize::<T, _, Error>(archived, &mut Unify::default())
+                }
+                PoolingKind::Duplicate => {
+                    deserialize::<T, _, Error>(archived, &mut Duplicate)
+                }
+            };
+            match result {
+                Ok(deserialized) => assert_eq!(
+                    &deserialized, value,
+                    "round trip mismatch for {:?}",
+                    run,
+                ),
+                Err(error) => {
+                    panic!("failed to deserialize for {:?}: {}", run, error)
+                }
+            }
+
+            runs.push(run);
+        }
+    }
+}
@@ -73,6 +73,90 @@ impl<T: ?Sized> CopyOptimization<T> {
     }
 }
 
+impl<T: BulkCopy> CopyOptimization<T> {
+    /// Returns a `TriviallyCopyable` hint with the optimization enabled for
+    /// `T`, which has been marked as [`BulkCopy`].
+    ///
+    /// Types which derive `Archive` with `#[archive(bulk_copy)]` use this as
+    /// their [`COPY_OPTIMIZATION`](Archive::COPY_OPTIMIZATION).
+    pub const fn bulk_copy() -> Self {
+        Self(true, PhantomData)
+    }
+}
+
+/// A type whose bytes can be copied directly into an archive.
+///
+/// Slices and arrays of types with the copy optimization enabled are
+/// serialized with a single copy instead of serializing and resolving each
+/// element. Implementing `BulkCopy` marks a type as safe to copy this way,
+/// and [`CopyOptimization::bulk_copy`] enables the optimization for it.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{rancor::Error, to_bytes, Archive, BulkCopy, Serialize};
+///
+/// #[derive(Archive, Serialize, Clone)]
+/// #[archive(bulk_copy)]
+/// #[repr(C)]
+/// struct Rgb {
+///     r: u8,
+///     g: u8,
+///     b: u8,
+/// }
+///
+/// // SAFETY: `Rgb` is `repr(C)` with only `u8` fields, so it has no padding
+/// // and its archived form has the same bytes.
+/// unsafe impl BulkCopy for Rgb {}
+///
+/// assert!(<Rgb as Archive>::COPY_OPTIMIZATION.is_enabled());
+///
+/// let pixels = vec![
+///     Rgb {
+///         r: 255,
+///         g: 128,
+///         b: 0
+///     };
+///     1024
+/// ];
+/// let bytes = to_bytes::<Error>(&pixels).unwrap();
+/// ```
+///
+/// # Safety
+///
+/// - `Self` must not have any uninit bytes (e.g. padding).
+/// - `Self::Archived` must have the same size as `Self`, and archiving any
+///   value of `Self` must produce the same bytes as the value itself.
+/// - Serializing `Self` must not write anything, since it is skipped when the
+///   bytes are copied.
+pub unsafe trait BulkCopy: Archive {}
+
+/// An archived type which may have the same layout as its native type.
+///
+/// When [`LAYOUT_MATCHES`](NativeLayout::LAYOUT_MATCHES) is `true`, archived
+/// values can be used as native values without converting them, for example
+/// with
+/// [`ArchivedVec::as_native_slice`](crate::vec::ArchivedVec::as_native_slice).
+/// Whether the layouts match is determined at compile time from the endianness
+/// of the target and the enabled features.
+///
+/// # Safety
+///
+/// If `LAYOUT_MATCHES` is `true`, then `Self` and `Self::Native` must have the
+/// same size, `Self` must be at least as aligned as `Self::Native`, and every
+/// valid `Self` must have the same bytes as a valid `Self::Native` with the
+/// same value.
+pub unsafe trait NativeLayout: Portable {
+    /// The native type that this archived type represents.
+    type Native;
+
+    /// Whether `Self` has the same layout as `Self::Native`.
+    const LAYOUT_MATCHES: bool;
+
+    /// Converts this archived value to its native type.
+    fn to_native(&self) -> Self::Native;
+}
+
 /// A type that can be used without deserializing.
 ///
 /// `Archive` is one of three basic traits used to work with zero-copy data and
@@ -0,0 +1,143 @@
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::io;
+
+/// The page size used when the operating system's page size is not available.
+const FALLBACK_PAGE_SIZE: usize = 4096;
+
+fn page_size() -> usize {
+    #[cfg(all(unix, feature = "madvise"))]
+    {
+        // SAFETY: `sysconf` has no preconditions.
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            return size as usize;
+        }
+    }
+
+    FALLBACK_PAGE_SIZE
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(not(all(unix, feature = "madvise")), allow(unused_variables))]
+fn advise(bytes: &[u8], advice: Advice) -> io::Result<()> {
+    #[cfg(all(unix, feature = "madvise"))]
+    {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        // `madvise` requires the start address to be page-aligned, so extend
+        // the range down to the start of the first page.
+        let page_size = page_size();
+        let start = bytes.as_ptr() as usize;
+        let aligned_start = start & !(page_size - 1);
+        let len = bytes.len() + (start - aligned_start);
+        let advice = match advice {
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+        };
+
+        // SAFETY: These advice values only affect paging behavior and never
+        // change the contents of the advised memory.
+        let result = unsafe {
+            libc::madvise(aligned_start as *mut libc::c_void, len, advice)
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+enum Advice {
+    Sequential,
+    Random,
+    WillNeed,
+}
+
+/// Advises the operating system that the given bytes will be read
+/// sequentially.
+///
+/// This allows the operating system to read ahead aggressively and drop pages
+/// soon after they are read. It is most useful when validating or
+/// deserializing an entire memory-mapped archive.
+///
+/// The advice is only passed along on Unix platforms when the `madvise`
+/// feature is enabled. Otherwise, this function does nothing.
+#[cfg(feature = "std")]
+#[inline]
+pub fn advise_sequential(bytes: &[u8]) -> io::Result<()> {
+    advise(bytes, Advice::Sequential)
+}
+
+/// Advises the operating system that the given bytes will be read in a random
+/// order.
+///
+/// This disables read-ahead, which avoids reading pages which will not be
+/// accessed when only a few values are looked up in a large memory-mapped
+/// archive.
+///
+/// The advice is only passed along on Unix platforms when the `madvise`
+/// feature is enabled. Otherwise, this function does nothing.
+#[cfg(feature = "std")]
+#[inline]
+pub fn advise_random(bytes: &[u8]) -> io::Result<()> {
+    advise(bytes, Advice::Random)
+}
+
+/// Advises the operating system that the given range of bytes will be read
+/// soon.
+///
+/// The operating system may start reading the pages in the background so that
+/// accessing them later does not block on I/O.
+///
+/// The advice is only passed along on Unix platforms when the `madvise`
+/// feature is enabled. Otherwise, this function does nothing.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of `bytes`.
+#[cfg(feature = "std")]
+#[inline]
+pub fn prefetch(bytes: &[u8], range: Range<usize>) -> io::Result<()> {
+    advise(&bytes[range], Advice::WillNeed)
+}
+
+/// Touches every page in the given range of bytes so that later reads do not
+/// page fault.
+///
+/// Unlike [`prefetch`], this blocks until every page has been loaded. Calling
+/// it before validating a large memory-mapped archive moves the cost of page
+/// faults out of validation. See [`access_prefaulted`] for a helper which does
+/// both.
+///
+/// [`prefetch`]: crate::util::prefetch
+/// [`access_prefaulted`]: crate::validation::util::access_prefaulted
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of `bytes`.
+pub fn prefault(bytes: &[u8], range: Range<usize>) {
+    let bytes = &bytes[range];
+    let page_size = page_size();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        // SAFETY: `pos` is in bounds of `bytes`. The read is volatile so that
+        // it can't be optimized out.
+        unsafe {
+            core::ptr::read_volatile(bytes.as_ptr().add(pos));
+        }
+        pos += page_size;
+    }
+    if let Some(last) = bytes.last() {
+        // SAFETY: `last` is a valid reference.
+        unsafe {
+            core::ptr::read_volatile(last);
+        }
+    }
+}
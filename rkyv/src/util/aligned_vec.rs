@@ -25,34 +25,160 @@ use ::alloc::{alloc, boxed::Box, vec::Vec};
 /// let bytes = AlignedVec::<4096>::with_capacity(1);
 /// assert_eq!(bytes.as_ptr() as usize % 4096, 0);
 /// ```
-pub struct AlignedVec<const ALIGNMENT: usize = 16> {
+///
+/// The memory of an `AlignedVec` can be provided by a custom
+/// [`RawAllocator`], for example one which allocates huge pages or pins
+/// memory to a NUMA node. See [`new_in`](AlignedVec::new_in) and
+/// [`with_capacity_in`](AlignedVec::with_capacity_in).
+pub struct AlignedVec<const ALIGNMENT: usize = 16, B: RawAllocator = Global> {
     ptr: NonNull<u8>,
     cap: usize,
     len: usize,
+    allocator: B,
 }
 
-impl<const A: usize> Drop for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> Drop for AlignedVec<A, B> {
     #[inline]
     fn drop(&mut self) {
         if self.cap != 0 {
             unsafe {
-                alloc::dealloc(self.ptr.as_ptr(), self.layout());
+                self.allocator.deallocate(self.ptr.as_ptr(), self.layout());
             }
         }
     }
 }
 
-impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
-    /// The alignment of the vector
-    pub const ALIGNMENT: usize = ALIGNMENT;
+/// An allocator which provides the memory for an [`AlignedVec`].
+///
+/// # Safety
+///
+/// `allocate` and `reallocate` must return either a null pointer or a pointer
+/// to a block of memory which fits the requested layout. The block must remain
+/// valid until it is passed to `deallocate` or `reallocate`.
+pub unsafe trait RawAllocator {
+    /// Allocates a block of memory with the given layout.
+    ///
+    /// Returns a null pointer if the allocation fails.
+    ///
+    /// # Safety
+    ///
+    /// `layout` must have a non-zero size.
+    unsafe fn allocate(&self, layout: alloc::Layout) -> *mut u8;
 
-    /// Maximum capacity of the vector.
+    /// Deallocates a block of memory.
     ///
-    /// Dictated by the requirements of [`alloc::Layout`]. "`size`, when rounded
-    /// up to the nearest multiple of `align`, must not overflow `isize` (i.e.
-    /// the rounded value must be less than or equal to `isize::MAX`)".
-    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator.
+    /// - `layout` must be the same layout that was used to allocate `ptr`.
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: alloc::Layout);
+
+    /// Resizes a block of memory to `new_size` bytes, keeping its alignment.
+    ///
+    /// On success, the contents of the block are preserved up to the lesser of
+    /// the old and new sizes and `ptr` must no longer be used. Returns a null
+    /// pointer if the allocation fails, in which case `ptr` is left unchanged.
+    ///
+    /// The default implementation allocates a new block, copies the contents
+    /// of the old block into it, and deallocates the old block.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator.
+    /// - `layout` must be the same layout that was used to allocate `ptr`.
+    /// - `new_size` must be greater than zero and must not overflow `isize`
+    ///   when rounded up to the nearest multiple of `layout.align()`.
+    unsafe fn reallocate(
+        &self,
+        ptr: *mut u8,
+        layout: alloc::Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        // SAFETY: The caller has guaranteed that `new_size` is a valid size for
+        // the alignment of `layout`.
+        let new_layout = unsafe {
+            alloc::Layout::from_size_align_unchecked(new_size, layout.align())
+        };
+        // SAFETY: The caller has guaranteed that `new_size` is non-zero.
+        let new_ptr = unsafe { self.allocate(new_layout) };
+        if !new_ptr.is_null() {
+            // SAFETY: Both blocks are valid for the lesser of their sizes, and
+            // are distinct allocations.
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    ptr,
+                    new_ptr,
+                    core::cmp::min(layout.size(), new_size),
+                );
+                self.deallocate(ptr, layout);
+            }
+        }
+        new_ptr
+    }
+}
 
+// SAFETY: `allocate`, `deallocate`, and `reallocate` forward to an allocator
+// which upholds the same guarantees.
+unsafe impl<B: RawAllocator + ?Sized> RawAllocator for &B {
+    #[inline]
+    unsafe fn allocate(&self, layout: alloc::Layout) -> *mut u8 {
+        // SAFETY: The caller has upheld the safety requirements.
+        unsafe { B::allocate(self, layout) }
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: alloc::Layout) {
+        // SAFETY: The caller has upheld the safety requirements.
+        unsafe { B::deallocate(self, ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: *mut u8,
+        layout: alloc::Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        // SAFETY: The caller has upheld the safety requirements.
+        unsafe { B::reallocate(self, ptr, layout, new_size) }
+    }
+}
+
+/// The global memory allocator.
+///
+/// This is the default [`RawAllocator`] for [`AlignedVec`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Global;
+
+// SAFETY: The global allocator returns either null or a block of memory which
+// fits the requested layout.
+unsafe impl RawAllocator for Global {
+    #[inline]
+    unsafe fn allocate(&self, layout: alloc::Layout) -> *mut u8 {
+        // SAFETY: The caller has guaranteed that `layout` has a non-zero size.
+        unsafe { alloc::alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: alloc::Layout) {
+        // SAFETY: The caller has guaranteed that `ptr` was allocated by the
+        // global allocator with `layout`.
+        unsafe { alloc::dealloc(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn reallocate(
+        &self,
+        ptr: *mut u8,
+        layout: alloc::Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        // SAFETY: The caller has upheld the safety requirements of `realloc`.
+        unsafe { alloc::realloc(ptr, layout, new_size) }
+    }
+}
+
+impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     /// Constructs a new, empty `AlignedVec`.
     ///
     /// The vector will not allocate until elements are pushed into it.
@@ -64,7 +190,7 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     /// ```
     #[inline]
     pub fn new() -> Self {
-        Self::with_capacity(0)
+        Self::new_in(Global)
     }
 
     /// Constructs a new, empty `AlignedVec` with the specified capacity.
@@ -95,6 +221,52 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     /// ```
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<const ALIGNMENT: usize, B: RawAllocator> AlignedVec<ALIGNMENT, B> {
+    /// The alignment of the vector
+    pub const ALIGNMENT: usize = ALIGNMENT;
+
+    /// Maximum capacity of the vector.
+    ///
+    /// Dictated by the requirements of [`alloc::Layout`]. "`size`, when rounded
+    /// up to the nearest multiple of `align`, must not overflow `isize` (i.e.
+    /// the rounded value must be less than or equal to `isize::MAX`)".
+    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
+
+    /// Constructs a new, empty `AlignedVec` which allocates its memory from
+    /// the given allocator.
+    ///
+    /// The vector will not allocate until elements are pushed into it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::{AlignedVec, Global};
+    /// let mut vec = AlignedVec::<16, _>::new_in(Global);
+    /// vec.extend_from_slice(&[1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn new_in(allocator: B) -> Self {
+        Self::with_capacity_in(0, allocator)
+    }
+
+    /// Constructs a new, empty `AlignedVec` with the specified capacity which
+    /// allocates its memory from the given allocator.
+    ///
+    /// The vector will be able to hold exactly `capacity` bytes without
+    /// reallocating. If `capacity` is 0, the vector will not allocate.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::{AlignedVec, Global};
+    /// let vec = AlignedVec::<4096, _>::with_capacity_in(10, Global);
+    /// assert_eq!(vec.capacity(), 10);
+    /// assert_eq!(vec.as_ptr() as usize % 4096, 0);
+    /// ```
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, allocator: B) -> Self {
         assert!(ALIGNMENT > 0, "ALIGNMENT must be 1 or more");
         assert!(
             ALIGNMENT.is_power_of_two(),
@@ -112,6 +284,7 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
                 ptr: NonNull::dangling(),
                 cap: 0,
                 len: 0,
+                allocator,
             }
         } else {
             assert!(
@@ -124,7 +297,7 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
                     capacity,
                     Self::ALIGNMENT,
                 );
-                let ptr = alloc::alloc(layout);
+                let ptr = allocator.allocate(layout);
                 if ptr.is_null() {
                     alloc::handle_alloc_error(layout);
                 }
@@ -135,10 +308,17 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
                 ptr,
                 cap: capacity,
                 len: 0,
+                allocator,
             }
         }
     }
 
+    /// Returns a reference to the allocator backing the vector.
+    #[inline]
+    pub fn allocator(&self) -> &B {
+        &self.allocator
+    }
+
     #[inline]
     fn layout(&self) -> alloc::Layout {
         unsafe {
@@ -185,68 +365,79 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     /// - `new_cap` must be greater than or equal to [`len()`](AlignedVec::len)
     #[inline]
     pub unsafe fn change_capacity(&mut self, new_cap: usize) {
+        // SAFETY: The caller has guaranteed that `new_cap` is less than or
+        // equal to `MAX_CAPACITY` and greater than or equal to `len`.
+        let result = unsafe { self.try_change_capacity(new_cap) };
+        if let Err(TryReserveError {
+            kind: TryReserveErrorKind::AllocError { layout },
+        }) = result
+        {
+            alloc::handle_alloc_error(layout);
+        }
+    }
+
+    /// Changes the capacity of the vector to exactly `new_cap`, returning an
+    /// error instead of aborting if the allocation fails.
+    ///
+    /// # Safety
+    ///
+    /// - `new_cap` must be less than or equal to
+    ///   [`MAX_CAPACITY`](AlignedVec::MAX_CAPACITY)
+    /// - `new_cap` must be greater than or equal to [`len()`](AlignedVec::len)
+    unsafe fn try_change_capacity(
+        &mut self,
+        new_cap: usize,
+    ) -> Result<(), TryReserveError> {
         debug_assert!(new_cap <= Self::MAX_CAPACITY);
         debug_assert!(new_cap >= self.len);
 
         if new_cap > 0 {
+            // SAFETY:
+            // - `ALIGNMENT` is always guaranteed to be a nonzero power of two.
+            // - The caller has guaranteed that `new_cap` doesn't overflow
+            //   `isize` when rounded up to the nearest multiple of `ALIGNMENT`.
+            let layout = unsafe {
+                alloc::Layout::from_size_align_unchecked(
+                    new_cap,
+                    Self::ALIGNMENT,
+                )
+            };
             let new_ptr = if self.cap > 0 {
-                // TODO: also verify that `new_cap` does not overflow `isize`
-                // when rounded up to the nearest multiple of `ALIGNMENT`.
                 // SAFETY:
                 // - `self.ptr` is currently allocated because `self.cap` is
                 //   greater than zero.
                 // - `self.layout()` always matches the layout used to allocate
                 //   the current block of memory.
                 // - We checked that `new_cap` is greater than zero.
-                let new_ptr = unsafe {
-                    alloc::realloc(self.ptr.as_ptr(), self.layout(), new_cap)
-                };
-                if new_ptr.is_null() {
-                    // SAFETY:
-                    // - `ALIGNMENT` is always guaranteed to be a nonzero power
-                    //   of two.
-                    // - We checked that `new_cap` doesn't overflow `isize` when
-                    //   rounded up to the nearest power of two.
-                    let layout = unsafe {
-                        alloc::Layout::from_size_align_unchecked(
-                            new_cap,
-                            Self::ALIGNMENT,
-                        )
-                    };
-                    alloc::handle_alloc_error(layout);
-                }
-                new_ptr
-            } else {
-                // SAFETY:
-                // - `ALIGNMENT` is always guaranteed to be a nonzero power of
-                //   two.
-                // - We checked that `new_cap` doesn't overflow `isize` when
-                //   rounded up to the nearest power of two.
-                let layout = unsafe {
-                    alloc::Layout::from_size_align_unchecked(
+                unsafe {
+                    self.allocator.reallocate(
+                        self.ptr.as_ptr(),
+                        self.layout(),
                         new_cap,
-                        Self::ALIGNMENT,
                     )
-                };
+                }
+            } else {
                 // SAFETY: We checked that `new_cap` has non-zero size.
-                let new_ptr = unsafe { alloc::alloc(layout) };
-                if new_ptr.is_null() {
-                    alloc::handle_alloc_error(layout);
+                unsafe { self.allocator.allocate(layout) }
+            };
+            self.ptr = match NonNull::new(new_ptr) {
+                Some(ptr) => ptr,
+                None => {
+                    return Err(TryReserveError {
+                        kind: TryReserveErrorKind::AllocError { layout },
+                    })
                 }
-                new_ptr
             };
-            // SAFETY: We checked that `new_ptr` is non-null in each of the
-            // branches.
-            self.ptr = unsafe { NonNull::new_unchecked(new_ptr) };
             self.cap = new_cap;
         } else if self.cap > 0 {
-            //
             unsafe {
-                alloc::dealloc(self.ptr.as_ptr(), self.layout());
+                self.allocator.deallocate(self.ptr.as_ptr(), self.layout());
             }
             self.ptr = NonNull::dangling();
             self.cap = 0;
         }
+
+        Ok(())
     }
 
     /// Shrinks the capacity of the vector as much as possible.
@@ -705,6 +896,97 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
         }
     }
 
+    /// Tries to reserve capacity for at least `additional` more bytes to be
+    /// inserted into the given `AlignedVec`. The collection may reserve more
+    /// space to avoid frequent reallocations, using the same growth strategy
+    /// as [`reserve`](AlignedVec::reserve). After calling `try_reserve`,
+    /// capacity will be greater than or equal to `self.len() + additional` if
+    /// it returns `Ok(())`. Does nothing if capacity is already sufficient.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned and the vector is left unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::AlignedVec;
+    ///
+    /// let mut vec = AlignedVec::<16>::new();
+    /// vec.push(1);
+    /// vec.try_reserve(10).unwrap();
+    /// assert!(vec.capacity() >= 11);
+    ///
+    /// assert!(vec.try_reserve(usize::MAX).is_err());
+    /// ```
+    #[inline]
+    pub fn try_reserve(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        let remaining = self.cap.wrapping_sub(self.len);
+        if additional <= remaining {
+            return Ok(());
+        }
+
+        let new_cap = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(TryReserveError::capacity_overflow)?;
+        let new_cap = if new_cap > (isize::MAX as usize + 1) >> 1 {
+            if new_cap > Self::MAX_CAPACITY {
+                return Err(TryReserveError::capacity_overflow());
+            }
+            Self::MAX_CAPACITY
+        } else {
+            new_cap.next_power_of_two()
+        };
+        // SAFETY: We just checked that `new_cap` is greater than `len` and
+        // less than or equal to `MAX_CAPACITY`.
+        unsafe { self.try_change_capacity(new_cap) }
+    }
+
+    /// Tries to reserve the minimum capacity for exactly `additional` more
+    /// bytes to be inserted in the given `AlignedVec`. After calling
+    /// `try_reserve_exact`, capacity will be greater than or equal to
+    /// `self.len() + additional` if it returns `Ok(())`. Does nothing if the
+    /// capacity is already sufficient.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned and the vector is left unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::AlignedVec;
+    ///
+    /// let mut vec = AlignedVec::<16>::new();
+    /// vec.push(1);
+    /// vec.try_reserve_exact(10).unwrap();
+    /// assert_eq!(vec.capacity(), 11);
+    /// ```
+    #[inline]
+    pub fn try_reserve_exact(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        let new_cap = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(TryReserveError::capacity_overflow)?;
+        if new_cap > self.cap {
+            if new_cap > Self::MAX_CAPACITY {
+                return Err(TryReserveError::capacity_overflow());
+            }
+            // SAFETY: We just checked that `new_cap` is greater than `len` and
+            // less than or equal to `MAX_CAPACITY`.
+            unsafe { self.try_change_capacity(new_cap) }
+        } else {
+            Ok(())
+        }
+    }
+
     /// Forces the length of the vector to `new_len`.
     ///
     /// This is a low-level operation that maintains none of the normal
@@ -785,13 +1067,41 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     pub fn into_vec(self) -> Vec<u8> {
         Vec::from(self.as_ref())
     }
+
+    /// Consumes and leaks the vector, returning a mutable reference to its
+    /// contents.
+    ///
+    /// The memory of the vector is never freed, so the returned reference may
+    /// live for the rest of the program. Any excess capacity is leaked along
+    /// with the contents.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::AlignedVec;
+    /// let mut v = AlignedVec::<16>::new();
+    /// v.extend_from_slice(&[1, 2, 3]);
+    ///
+    /// let slice: &'static mut [u8] = v.leak();
+    /// assert_eq!(slice, &[1, 2, 3]);
+    /// assert_eq!(slice.as_ptr() as usize % 16, 0);
+    /// ```
+    #[inline]
+    pub fn leak<'a>(self) -> &'a mut [u8]
+    where
+        B: 'a,
+    {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: The vector is never dropped, so its first `len` bytes remain
+        // initialized and allocated for the rest of the program.
+        unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), this.len) }
+    }
 }
 
 #[cfg(feature = "std")]
 const _: () = {
     use std::io::{ErrorKind, Read};
 
-    impl<const A: usize> AlignedVec<A> {
+    impl<const A: usize, B: RawAllocator> AlignedVec<A, B> {
         /// Reads all bytes until EOF from `r` and appends them to this
         /// `AlignedVec`.
         ///
@@ -897,46 +1207,47 @@ const _: () = {
     }
 };
 
-impl<const A: usize> From<AlignedVec<A>> for Vec<u8> {
+impl<const A: usize, B: RawAllocator> From<AlignedVec<A, B>> for Vec<u8> {
     #[inline]
-    fn from(aligned: AlignedVec<A>) -> Self {
+    fn from(aligned: AlignedVec<A, B>) -> Self {
         aligned.to_vec()
     }
 }
 
-impl<const A: usize> AsMut<[u8]> for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> AsMut<[u8]> for AlignedVec<A, B> {
     #[inline]
     fn as_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize> AsRef<[u8]> for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> AsRef<[u8]> for AlignedVec<A, B> {
     #[inline]
     fn as_ref(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl<const A: usize> Borrow<[u8]> for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> Borrow<[u8]> for AlignedVec<A, B> {
     #[inline]
     fn borrow(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl<const A: usize> BorrowMut<[u8]> for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> BorrowMut<[u8]> for AlignedVec<A, B> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize> Clone for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator + Clone> Clone for AlignedVec<A, B> {
     #[inline]
     fn clone(&self) -> Self {
         unsafe {
-            let mut result = Self::with_capacity(self.len);
+            let mut result =
+                Self::with_capacity_in(self.len, self.allocator.clone());
             result.len = self.len;
             core::ptr::copy_nonoverlapping(
                 self.as_ptr(),
@@ -948,7 +1259,7 @@ impl<const A: usize> Clone for AlignedVec<A> {
     }
 }
 
-impl<const A: usize> fmt::Debug for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> fmt::Debug for AlignedVec<A, B> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.as_slice().fmt(f)
@@ -962,7 +1273,7 @@ impl<const A: usize> Default for AlignedVec<A> {
     }
 }
 
-impl<const A: usize> Deref for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> Deref for AlignedVec<A, B> {
     type Target = [u8];
 
     #[inline]
@@ -971,14 +1282,18 @@ impl<const A: usize> Deref for AlignedVec<A> {
     }
 }
 
-impl<const A: usize> DerefMut for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> DerefMut for AlignedVec<A, B> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize, I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec<A> {
+impl<const A: usize, B, I> Index<I> for AlignedVec<A, B>
+where
+    B: RawAllocator,
+    I: slice::SliceIndex<[u8]>,
+{
     type Output = <I as slice::SliceIndex<[u8]>>::Output;
 
     #[inline]
@@ -987,7 +1302,11 @@ impl<const A: usize, I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec<A> {
     }
 }
 
-impl<const A: usize, I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec<A> {
+impl<const A: usize, B, I> IndexMut<I> for AlignedVec<A, B>
+where
+    B: RawAllocator,
+    I: slice::SliceIndex<[u8]>,
+{
     #[inline]
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
         &mut self.as_mut_slice()[index]
@@ -995,7 +1314,7 @@ impl<const A: usize, I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec<A> {
 }
 
 #[cfg(feature = "std")]
-impl<const A: usize> io::Write for AlignedVec<A> {
+impl<const A: usize, B: RawAllocator> io::Write for AlignedVec<A, B> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.extend_from_slice(buf);
@@ -1026,10 +1345,63 @@ impl<const A: usize> io::Write for AlignedVec<A> {
     }
 }
 
-// SAFETY: AlignedVec is safe to send to another thread
-unsafe impl<const A: usize> Send for AlignedVec<A> {}
+// SAFETY: AlignedVec is safe to send to another thread if its allocator is
+unsafe impl<const A: usize, B: RawAllocator + Send> Send for AlignedVec<A, B> {}
+
+// SAFETY: AlignedVec is safe to share between threads if its allocator is
+unsafe impl<const A: usize, B: RawAllocator + Sync> Sync for AlignedVec<A, B> {}
+
+impl<const A: usize, B: RawAllocator> Unpin for AlignedVec<A, B> {}
+
+/// The error type for [`AlignedVec::try_reserve`] and
+/// [`AlignedVec::try_reserve_exact`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TryReserveError {
+    kind: TryReserveErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TryReserveErrorKind {
+    CapacityOverflow,
+    AllocError { layout: alloc::Layout },
+}
+
+impl TryReserveError {
+    #[inline]
+    fn capacity_overflow() -> Self {
+        Self {
+            kind: TryReserveErrorKind::CapacityOverflow,
+        }
+    }
+
+    /// Returns the layout of the allocation that failed, if the allocator
+    /// reported a failure.
+    ///
+    /// Returns `None` if the requested capacity exceeded
+    /// [`MAX_CAPACITY`](AlignedVec::MAX_CAPACITY).
+    #[inline]
+    pub fn layout(&self) -> Option<alloc::Layout> {
+        match self.kind {
+            TryReserveErrorKind::CapacityOverflow => None,
+            TryReserveErrorKind::AllocError { layout } => Some(layout),
+        }
+    }
+}
 
-// SAFETY: AlignedVec is safe to share between threads
-unsafe impl<const A: usize> Sync for AlignedVec<A> {}
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            TryReserveErrorKind::CapacityOverflow => write!(
+                f,
+                "memory allocation failed because the computed capacity \
+                 exceeded the maximum capacity of AlignedVec"
+            ),
+            TryReserveErrorKind::AllocError { layout } => {
+                write!(f, "memory allocation of {} bytes failed", layout.size())
+            }
+        }
+    }
+}
 
-impl<const A: usize> Unpin for AlignedVec<A> {}
+#[cfg(feature = "std")]
+impl std::error::Error for TryReserveError {}
@@ -0,0 +1,160 @@
+//! Memory-mapped archive files.
+//!
+//! Mapping an archive file avoids reading all of it up front, but the mapped
+//! bytes must still be aligned for the archive to be accessed. Maps start on
+//! a page boundary, which is always aligned enough. When a map can't be used
+//! (for example, when the file is empty or the map is not aligned) the file is
+//! read into an [`AlignedVec`] instead. Either way, the result is a
+//! [`MappedBytes`] which is always aligned to [`AlignedVec::ALIGNMENT`].
+//!
+//! [`open`] maps a file and validates the archive in it once, returning an
+//! [`OwnedArchive`](crate::backing::OwnedArchive) which can be accessed
+//! without validating it again.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{rancor::Error, to_bytes, util::mmap, Archived};
+//!
+//! let path = std::env::temp_dir()
+//!     .join(format!("rkyv_mmap_doc_{}.rkyv", std::process::id()));
+//! let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+//! std::fs::write(&path, &bytes).unwrap();
+//!
+//! // SAFETY: The file is not modified while it is mapped.
+//! let archive =
+//!     unsafe { mmap::open::<Archived<Vec<u32>>, Error>(&path).unwrap() };
+//! assert_eq!(archive.len(), 3);
+//! assert_eq!(archive[2], 3);
+//! # drop(archive);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use core::fmt;
+use std::{fs::File, io, path::Path};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+use memmap2::Mmap;
+#[cfg(feature = "bytecheck")]
+use rancor::Strategy;
+use rancor::{ResultExt as _, Source};
+
+#[cfg(feature = "bytecheck")]
+use crate::{
+    backing::OwnedArchive, validation::validators::DefaultValidator, Portable,
+};
+use crate::{
+    backing::{Backing, StableBacking},
+    util::AlignedVec,
+};
+
+enum Inner {
+    Mapped(Mmap),
+    Copied(AlignedVec),
+}
+
+/// The bytes of a file, either memory-mapped or copied into an
+/// [`AlignedVec`].
+///
+/// The bytes are always aligned to [`AlignedVec::ALIGNMENT`].
+pub struct MappedBytes {
+    inner: Inner,
+}
+
+impl MappedBytes {
+    /// Returns whether the bytes are memory-mapped, rather than copied.
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.inner, Inner::Mapped(_))
+    }
+
+    /// Returns the bytes of the file.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.inner {
+            Inner::Mapped(map) => map,
+            Inner::Copied(vec) => vec,
+        }
+    }
+}
+
+impl fmt::Debug for MappedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedBytes")
+            .field("mapped", &self.is_mapped())
+            .field("len", &self.as_slice().len())
+            .finish()
+    }
+}
+
+// SAFETY: Maps are only kept when they are aligned to `ALIGN`, and
+// `AlignedVec`s are always aligned to it. Neither can be modified through a
+// shared reference, and modifying a mapped file while it is mapped is already
+// undefined behavior.
+unsafe impl Backing for MappedBytes {
+    const ALIGN: usize = AlignedVec::ALIGNMENT;
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+// SAFETY: Moving a map or an `AlignedVec` does not move the bytes they hold.
+unsafe impl StableBacking for MappedBytes {}
+
+/// Maps the file at the given path into memory.
+///
+/// If the file is empty or the map is not aligned to
+/// [`AlignedVec::ALIGNMENT`], the file is read into an `AlignedVec` instead.
+///
+/// # Safety
+///
+/// The file must not be modified while it is mapped.
+pub unsafe fn map_file<E: Source>(
+    path: impl AsRef<Path>,
+) -> Result<MappedBytes, E> {
+    let mut file = File::open(path).into_error()?;
+    let len = file.metadata().into_error()?.len();
+
+    if len != 0 {
+        // SAFETY: The caller has guaranteed that the file will not be
+        // modified while it is mapped.
+        let map = unsafe { Mmap::map(&file).into_error()? };
+        if map.as_ptr() as usize % AlignedVec::ALIGNMENT == 0 {
+            return Ok(MappedBytes {
+                inner: Inner::Mapped(map),
+            });
+        }
+    }
+
+    let mut vec = AlignedVec::with_capacity(len as usize);
+    io::copy(&mut file, &mut vec).into_error()?;
+    Ok(MappedBytes {
+        inner: Inner::Copied(vec),
+    })
+}
+
+/// Maps the file at the given path into memory and validates the archive in
+/// it.
+///
+/// The returned archive can be accessed without validating it again. See the
+/// [module docs](self) for more information.
+///
+/// # Safety
+///
+/// The file must not be modified while it is mapped.
+#[cfg(feature = "bytecheck")]
+pub unsafe fn open<T, E>(
+    path: impl AsRef<Path>,
+) -> Result<OwnedArchive<T, MappedBytes>, E>
+where
+    T: Portable + CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    // SAFETY: The caller has guaranteed that the file will not be modified
+    // while it is mapped.
+    let bytes = unsafe { map_file::<E>(path)? };
+    OwnedArchive::new(bytes)
+}
@@ -9,11 +9,33 @@
 //!
 //! Alignment helpers ensure that byte buffers are properly aligned when
 //! accessing and deserializing data.
+//!
+//! ## Static archives
+//!
+//! [`StaticArchive`] wraps an archive embedded in the program with
+//! [`include_bytes_aligned`](crate::include_bytes_aligned) and validates it
+//! the first time it is accessed.
+//!
+//! ## Memory advice
+//!
+//! Advice helpers tell the operating system how a large (typically
+//! memory-mapped) archive is going to be read, and prefault helpers touch its
+//! pages ahead of time to keep page faults off of the hot path.
+//!
+//! ## Memory-mapped files
+//!
+//! With the `memmap2` feature, [`mmap`] maps archive files into memory and
+//! falls back to reading them into an [`AlignedVec`] when a map can't be
+//! aligned.
 
+mod advice;
 #[cfg(feature = "alloc")]
 mod aligned_vec;
 mod inline_vec;
+#[cfg(feature = "memmap2")]
+pub mod mmap;
 mod ser_vec;
+mod static_archive;
 
 use core::{
     mem,
@@ -22,14 +44,24 @@ use core::{
 };
 
 use rancor::Strategy;
+#[cfg(feature = "alloc")]
+use rancor::{ResultExt as _, Source};
 
 #[doc(inline)]
+pub use self::advice::prefault;
+#[cfg(feature = "std")]
+pub use self::advice::{advise_random, advise_sequential, prefetch};
 #[cfg(feature = "alloc")]
 pub use self::aligned_vec::*;
 #[doc(inline)]
-pub use self::{inline_vec::InlineVec, ser_vec::SerVec};
+pub use self::{
+    inline_vec::InlineVec, ser_vec::SerVec, static_archive::StaticArchive,
+};
 #[cfg(feature = "alloc")]
-use crate::{de::pooling::Unify, ser::AllocSerializer};
+use crate::{
+    de::pooling::Unify,
+    ser::{writer::MaxAlign, AllocSerializer, MaxAlignSerializer},
+};
 use crate::{ser::Writer, Archive, Deserialize, Portable, Serialize};
 
 #[cfg(debug_assertions)]
@@ -75,6 +107,81 @@ pub unsafe fn access_pos_unchecked<T: Portable>(
     unsafe { &*bytes.as_ptr().add(pos).cast() }
 }
 
+/// Reads a copy of an archived value out of the given byte slice at the given
+/// position in a const context.
+///
+/// Unlike [`access_pos_unchecked`], this can be called in a `const` or
+/// `static` initializer, so archived configuration blobs which are embedded in
+/// the binary (e.g. with `include_bytes!`) can be decoded at compile time. The
+/// value is read without any alignment requirements.
+///
+/// Because the value is copied out of the byte slice, this should only be used
+/// with fixed-size archived types which do not point to any out-of-line data.
+/// Relative pointers like those in archived strings, vectors, and boxes are no
+/// longer valid after being copied.
+///
+/// # Panics
+///
+/// Panics if the value would extend past the end of the byte slice.
+///
+/// # Safety
+///
+/// - A valid `T` must be located at the given position in the byte slice.
+/// - `T` must not contain any relative pointers.
+///
+/// # Examples
+/// ```
+/// use rkyv::{primitive::ArchivedU32, util::read_pos_unchecked};
+///
+/// const BLOB: [u8; 8] = [0, 0, 0, 0, 7, 7, 7, 7];
+/// const VALUE: ArchivedU32 =
+///     unsafe { read_pos_unchecked::<ArchivedU32>(&BLOB, 4) };
+///
+/// assert_eq!(VALUE.to_native(), 0x07070707);
+/// ```
+#[inline]
+pub const unsafe fn read_pos_unchecked<T: Portable>(
+    bytes: &[u8],
+    pos: usize,
+) -> T {
+    assert!(
+        pos <= bytes.len() && mem::size_of::<T>() <= bytes.len() - pos,
+        "archived value extends past the end of the byte slice",
+    );
+
+    // SAFETY: We checked that the value lies within the byte slice, and the
+    // caller has guaranteed that a valid `T` is located at `pos` in it.
+    unsafe { bytes.as_ptr().add(pos).cast::<T>().read_unaligned() }
+}
+
+/// Reads a copy of the root archived value out of the given byte slice in a
+/// const context.
+///
+/// This is a wrapper for [`read_pos_unchecked`] that calculates the position
+/// of the root object using the length of the byte slice.
+///
+/// # Panics
+///
+/// Panics if the byte slice is shorter than a `T`.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice (this is the
+///   default behavior).
+/// - `T` must not contain any relative pointers.
+#[inline]
+pub const unsafe fn read_unchecked<T: Portable>(bytes: &[u8]) -> T {
+    assert!(
+        mem::size_of::<T>() <= bytes.len(),
+        "byte slice is too short to contain the root archived value",
+    );
+
+    // SAFETY: The caller has guaranteed that a valid `T` is located at the root
+    // position in the byte slice.
+    unsafe { read_pos_unchecked::<T>(bytes, bytes.len() - mem::size_of::<T>()) }
+}
+
 /// Accesses a mutable archived value from the given byte slice at the given
 /// position.
 ///
@@ -237,6 +344,96 @@ pub fn to_bytes<E>(
     Ok(serialize_into(value, Default::default())?.into_writer())
 }
 
+/// Serializes the given value and returns the resulting bytes in an
+/// [`AlignedVec`] with an alignment of `N`.
+///
+/// This fails to compile if the archived type is aligned to more than `N`
+/// bytes, and panics if any object in the archive is. Archives of types with
+/// small alignments can be stored and concatenated with less padding than the
+/// default alignment of 16 bytes. See [`MaxAlign`] for more information.
+///
+/// [`MaxAlign`]: crate::ser::writer::MaxAlign
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, util::to_bytes_with_max_align,
+///     Archived,
+/// };
+///
+/// let value = vec![1u32, 2, 3, 4];
+/// let bytes = to_bytes_with_max_align::<4, _, Error>(&value).unwrap();
+///
+/// let archived = unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+/// assert_eq!(archived.as_slice(), &[1, 2, 3, 4]);
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn to_bytes_with_max_align<const N: usize, T, E>(
+    value: &T,
+) -> Result<AlignedVec<N>, E>
+where
+    T: Archive + Serialize<Strategy<MaxAlignSerializer<N>, E>>,
+{
+    MaxAlign::<AlignedVec<N>, N>::assert_fits::<T::Archived>();
+    let serializer = MaxAlignSerializer::<N>::new(
+        MaxAlign::new(AlignedVec::new()),
+        Default::default(),
+        Default::default(),
+    );
+    Ok(serialize_into(value, serializer)?
+        .into_writer()
+        .into_inner())
+}
+
+/// Serializes the given value and returns the resulting bytes in an
+/// [`AlignedVec`], reserving `capacity` bytes up front.
+///
+/// `capacity` is an estimate of the size of the serialized value, for example
+/// the length of a previous serialization of similar data. Reserving it up
+/// front avoids repeatedly reallocating the buffer as it grows. If the buffer
+/// can't be allocated, an error is returned instead of aborting.
+///
+/// # Examples
+/// ```
+/// use rkyv::{rancor::Error, util::to_bytes_with_capacity};
+///
+/// let value = vec![1u32; 256];
+/// let bytes = to_bytes_with_capacity::<Error>(&value, 1024 + 8).unwrap();
+/// assert!(bytes.capacity() >= bytes.len());
+///
+/// assert!(to_bytes_with_capacity::<Error>(&value, usize::MAX).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn to_bytes_with_capacity<E: Source>(
+    value: &impl Serialize<Strategy<AllocSerializer, E>>,
+    capacity: usize,
+) -> Result<AlignedVec, E> {
+    let mut writer = AlignedVec::new();
+    writer.try_reserve_exact(capacity).into_error()?;
+    let serializer =
+        AllocSerializer::new(writer, Default::default(), Default::default());
+    Ok(serialize_into(value, serializer)?.into_writer())
+}
+
+/// Serializes the given value into the given serializer after reserving
+/// `size_hint` bytes in its writer.
+///
+/// See [`Writer::reserve`] for more information.
+#[inline]
+pub fn serialize_with_size_hint<S, E>(
+    value: &impl Serialize<Strategy<S, E>>,
+    serializer: &mut S,
+    size_hint: usize,
+) -> Result<(), E>
+where
+    S: Writer<E> + ?Sized,
+{
+    serializer.reserve(size_hint)?;
+    serialize(value, serializer)
+}
+
 /// Serializes the given value into the given serializer and then returns the
 /// serializer.
 #[inline]
@@ -260,8 +457,9 @@ pub fn serialize<S, E>(
 where
     S: Writer<E> + ?Sized,
 {
-    value.serialize_and_resolve(Strategy::wrap(serializer))?;
-    Ok(())
+    let serializer = Strategy::wrap(serializer);
+    value.serialize_and_resolve(serializer)?;
+    serializer.finish()
 }
 
 /// Deserializes a value from the given bytes.
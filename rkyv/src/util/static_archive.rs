@@ -0,0 +1,166 @@
+#[cfg(feature = "bytecheck")]
+use core::ops::Deref;
+use core::{
+    fmt,
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+#[cfg(feature = "bytecheck")]
+use rancor::{Panic, ResultExt as _, Source, Strategy};
+
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
+use crate::Archive;
+
+/// Includes a file as a byte slice with a guaranteed alignment.
+///
+/// Byte slices from `include_bytes!` are only guaranteed to be aligned to one
+/// byte, which is not enough to access most archives. By default, the bytes
+/// included by this macro are aligned to 16 bytes, the same alignment as an
+/// `AlignedVec`, so they can hold any archive produced by `to_bytes`. A
+/// different alignment may be given as an integer literal before the path.
+///
+/// The path is resolved relative to the current file, as with
+/// `include_bytes!`.
+///
+/// # Example
+///
+/// ```ignore
+/// use rkyv::{include_bytes_aligned, util::StaticArchive};
+///
+/// static CONFIG: StaticArchive<Config> =
+///     StaticArchive::new(include_bytes_aligned!("config.rkyv"));
+///
+/// // Page-aligned bytes
+/// static PAGES: &[u8] = include_bytes_aligned!(4096, "pages.rkyv");
+/// ```
+#[macro_export]
+macro_rules! include_bytes_aligned {
+    ($align:literal, $path:expr $(,)?) => {{
+        #[repr(C, align($align))]
+        struct __Aligned<T: ?::core::marker::Sized>(T);
+
+        const __ALIGNED: &__Aligned<[u8]> =
+            &__Aligned(*::core::include_bytes!($path));
+
+        &__ALIGNED.0
+    }};
+    ($path:expr $(,)?) => {
+        $crate::include_bytes_aligned!(16, $path)
+    };
+}
+
+/// An archive embedded in the program which is validated the first time it is
+/// accessed.
+///
+/// Static archives are typically created from bytes included with
+/// [`include_bytes_aligned`](crate::include_bytes_aligned). They dereference
+/// to the archived value, validating it first if it has not been validated
+/// yet. Dereferencing a static archive which is invalid panics. Use
+/// [`get`](StaticArchive::get) to handle validation errors instead.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::util::StaticArchive;
+///
+/// #[repr(C, align(16))]
+/// struct Aligned([u8; 4]);
+///
+/// static BYTES: Aligned = Aligned(*b"rkyv");
+/// static ARCHIVE: StaticArchive<[u8; 4]> = StaticArchive::new(&BYTES.0);
+///
+/// assert_eq!(*ARCHIVE, *b"rkyv");
+/// ```
+pub struct StaticArchive<T> {
+    bytes: &'static [u8],
+    validated: AtomicBool,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StaticArchive<T> {
+    /// Creates a new static archive from the given bytes.
+    ///
+    /// The bytes are not validated until the archive is first accessed.
+    #[inline]
+    pub const fn new(bytes: &'static [u8]) -> Self {
+        Self {
+            bytes,
+            validated: AtomicBool::new(false),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the bytes of the archive.
+    #[inline]
+    pub const fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Returns whether the archive has been validated.
+    #[inline]
+    pub fn is_validated(&self) -> bool {
+        self.validated.load(Ordering::Acquire)
+    }
+}
+
+impl<T: Archive> StaticArchive<T> {
+    /// Returns the archived value, validating it first if it has not been
+    /// validated yet.
+    ///
+    /// Validation only succeeds once. Later calls return the archived value
+    /// without validating it again.
+    #[cfg(feature = "bytecheck")]
+    pub fn get<E>(&self) -> Result<&T::Archived, E>
+    where
+        T::Archived: CheckBytes<Strategy<DefaultValidator, E>>,
+        E: Source,
+    {
+        if self.is_validated() {
+            // SAFETY: The bytes were already checked to contain a valid
+            // archived `T`.
+            return Ok(unsafe { self.get_unchecked() });
+        }
+
+        let archived = crate::access::<T::Archived, E>(self.bytes)?;
+        self.validated.store(true, Ordering::Release);
+        Ok(archived)
+    }
+
+    /// Returns the archived value without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of the archive must contain a valid archived `T`.
+    #[inline]
+    pub unsafe fn get_unchecked(&self) -> &T::Archived {
+        // SAFETY: The caller has guaranteed that the bytes contain a valid
+        // archived `T`.
+        unsafe { crate::access_unchecked::<T::Archived>(self.bytes) }
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+impl<T: Archive> Deref for StaticArchive<T>
+where
+    T::Archived: CheckBytes<Strategy<DefaultValidator, Panic>>,
+{
+    type Target = T::Archived;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get::<Panic>().always_ok()
+    }
+}
+
+impl<T> fmt::Debug for StaticArchive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticArchive")
+            .field("len", &self.bytes.len())
+            .field("validated", &self.is_validated())
+            .finish()
+    }
+}
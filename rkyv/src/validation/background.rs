@@ -0,0 +1,378 @@
+//! Validation which runs in the background and can be cancelled.
+//!
+//! Validating a very large archive can take several seconds. A
+//! [`CancelToken`] lets another thread stop a validation part of the way
+//! through: the [`Cancellable`] validator checks the token each time it
+//! enters a new subtree, and fails with an error once it has been cancelled.
+//!
+//! [`validate_in_background`] runs a cancellable validation on its own thread
+//! and returns a [`ValidationHandle`]. The handle can be joined, polled as a
+//! [`Future`], or cancelled, and it produces a [`Validated`] archive which
+//! can be accessed without validating it again.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     rancor::Error, to_bytes,
+//!     validation::background::validate_in_background, Archived,
+//! };
+//!
+//! let value = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+//! let bytes = to_bytes::<Error>(&value).unwrap();
+//!
+//! let handle =
+//!     validate_in_background::<_, Archived<Vec<String>>, Error>(bytes);
+//! // The application stays responsive here. Calling `handle.cancel()` would
+//! // stop the validation early.
+//! let validated = handle.join().unwrap();
+//! assert_eq!(validated.len(), 1000);
+//! assert_eq!(validated[999], "999");
+//! ```
+
+use core::{
+    any::TypeId,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    ops::{Deref, Range},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use bytecheck::CheckBytes;
+use rancor::{fail, Source, Strategy};
+
+use crate::{
+    backing::StableBacking,
+    validation::{
+        util::access_with_context, validators::DefaultValidator,
+        ArchiveContext, SharedContext,
+    },
+    Portable,
+};
+
+/// A token which can cancel a validation from another thread.
+///
+/// Clones of a token share the same cancellation state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Returns a new token which has not been cancelled.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every validation using this token.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether this token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "validation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// A validator adapter which stops validating once its [`CancelToken`] is
+/// cancelled.
+///
+/// The token is checked each time the validator enters a subtree, so a
+/// cancelled validation stops after the subtree it is currently checking.
+#[derive(Debug)]
+pub struct Cancellable<C> {
+    inner: C,
+    token: CancelToken,
+}
+
+impl<C> Cancellable<C> {
+    /// Wraps the given validator with a cancel token.
+    #[inline]
+    pub fn new(inner: C, token: CancelToken) -> Self {
+        Self { inner, token }
+    }
+
+    /// Returns a reference to the wrapped validator.
+    #[inline]
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped validator.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+unsafe impl<C, E> ArchiveContext<E> for Cancellable<C>
+where
+    C: ArchiveContext<E>,
+    E: Source,
+{
+    #[inline]
+    fn check_subtree_ptr(
+        &mut self,
+        ptr: *const u8,
+        layout: &core::alloc::Layout,
+    ) -> Result<(), E> {
+        self.inner.check_subtree_ptr(ptr, layout)
+    }
+
+    #[inline]
+    unsafe fn push_subtree_range(
+        &mut self,
+        root: *const u8,
+        end: *const u8,
+    ) -> Result<Range<usize>, E> {
+        if self.token.is_cancelled() {
+            fail!(Cancelled);
+        }
+        // SAFETY: This just forwards the call to the underlying validator,
+        // which has the same safety requirements.
+        unsafe { self.inner.push_subtree_range(root, end) }
+    }
+
+    #[inline]
+    unsafe fn pop_subtree_range(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<(), E> {
+        // SAFETY: This just forwards the call to the underlying validator,
+        // which has the same safety requirements.
+        unsafe { self.inner.pop_subtree_range(range) }
+    }
+
+    #[inline]
+    fn should_check_subtree(&mut self) -> bool {
+        self.inner.should_check_subtree()
+    }
+}
+
+impl<C, E> SharedContext<E> for Cancellable<C>
+where
+    C: SharedContext<E>,
+{
+    #[inline]
+    fn register_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Result<bool, E> {
+        self.inner.register_shared_ptr(address, type_id)
+    }
+}
+
+/// The validator used by cancellable validations.
+pub type CancellableValidator = Cancellable<DefaultValidator>;
+
+/// Accesses an archived value from the given byte slice after checking its
+/// validity, stopping early if the given token is cancelled.
+pub fn access_cancellable<'a, T, E>(
+    bytes: &'a [u8],
+    token: &CancelToken,
+) -> Result<&'a T, E>
+where
+    T: Portable + CheckBytes<Strategy<CancellableValidator, E>>,
+    E: Source,
+{
+    let mut validator =
+        Cancellable::new(DefaultValidator::new(bytes), token.clone());
+    access_with_context::<T, CancellableValidator, E>(bytes, &mut validator)
+}
+
+/// Bytes which have been validated to contain an archived `T`.
+///
+/// The archived value can be accessed without validating it again.
+pub struct Validated<B, T> {
+    bytes: B,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<B: StableBacking, T: Portable> Validated<B, T> {
+    /// Returns a reference to the archived value.
+    #[inline]
+    pub fn get(&self) -> &T {
+        // SAFETY: The bytes were validated to contain a `T` when this was
+        // created.
+        unsafe { crate::access_unchecked::<T>(self.bytes.as_bytes()) }
+    }
+
+    /// Returns the validated bytes.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        self.bytes.as_bytes()
+    }
+
+    /// Consumes the validated archive and returns its bytes.
+    #[inline]
+    pub fn into_bytes(self) -> B {
+        self.bytes
+    }
+}
+
+impl<B: StableBacking, T: Portable> Deref for Validated<B, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<B, T> fmt::Debug for Validated<B, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validated").finish_non_exhaustive()
+    }
+}
+
+struct State<R> {
+    result: Option<R>,
+    waker: Option<Waker>,
+}
+
+/// A handle to a validation running on a background thread.
+///
+/// This is returned by [`validate_in_background`]. The result can be waited
+/// for with [`join`](ValidationHandle::join) or by awaiting the handle.
+pub struct ValidationHandle<B, T, E> {
+    state: Arc<Mutex<State<Result<Validated<B, T>, E>>>>,
+    token: CancelToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<B, T, E> ValidationHandle<B, T, E> {
+    /// Cancels the validation.
+    ///
+    /// The validation stops the next time it enters a subtree, and the handle
+    /// resolves to an error.
+    #[inline]
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns the cancel token for the validation.
+    #[inline]
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    /// Returns whether the validation has finished.
+    pub fn is_finished(&self) -> bool {
+        lock(&self.state).result.is_some()
+    }
+
+    /// Waits for the validation to finish and returns its result.
+    pub fn join(mut self) -> Result<Validated<B, T>, E> {
+        if let Some(thread) = self.thread.take() {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        match lock(&self.state).result.take() {
+            Some(result) => result,
+            None => panic!("validation result was already taken"),
+        }
+    }
+}
+
+impl<B, T, E> Future for ValidationHandle<B, T, E> {
+    type Output = Result<Validated<B, T>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = lock(&self.state);
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<B, T, E> fmt::Debug for ValidationHandle<B, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationHandle")
+            .field("finished", &self.is_finished())
+            .field("cancelled", &self.token.is_cancelled())
+            .finish()
+    }
+}
+
+// The state is only locked briefly and never while running user code, so a
+// poisoned lock still holds a consistent state.
+fn lock<R>(state: &Mutex<State<R>>) -> std::sync::MutexGuard<'_, State<R>> {
+    match state.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Validates the given bytes on a background thread.
+///
+/// The returned handle can be used to cancel the validation or wait for its
+/// result. See the [module docs](self) for more information.
+pub fn validate_in_background<B, T, E>(bytes: B) -> ValidationHandle<B, T, E>
+where
+    B: StableBacking + Send + 'static,
+    T: Portable + CheckBytes<Strategy<CancellableValidator, E>> + 'static,
+    E: Source + Send + 'static,
+{
+    let token = CancelToken::new();
+    let state = Arc::new(Mutex::new(State {
+        result: None,
+        waker: None,
+    }));
+
+    let thread = {
+        let token = token.clone();
+        let state = state.clone();
+        thread::spawn(move || {
+            let result = access_cancellable::<T, E>(bytes.as_bytes(), &token)
+                .map(|_| ());
+            let result = result.map(|()| Validated {
+                bytes,
+                _phantom: PhantomData,
+            });
+            let waker = {
+                let mut state = lock(&state);
+                state.result = Some(result);
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        })
+    };
+
+    ValidationHandle {
+        state,
+        token,
+        thread: Some(thread),
+    }
+}
@@ -0,0 +1,237 @@
+//! Caches which remember the archived values that have been validated.
+//!
+//! Servers often keep a long-lived buffer and access many different types out
+//! of it on every request. Validating the same value again for each request is
+//! wasted work. A [`ValidationCache`] records each buffer, archived type, and
+//! position that has been validated, so later accesses of the same value skip
+//! validation.
+//!
+//! Buffers are identified by their address and length, along with a
+//! generation chosen by the caller. The generation must change whenever the
+//! bytes of the buffer change, which discards everything cached for the
+//! previous generation.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     rancor::Error, to_bytes, validation::cache::ValidationCache, Archived,
+//! };
+//!
+//! let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+//! let cache = ValidationCache::new();
+//!
+//! for _ in 0..3 {
+//!     // SAFETY: `bytes` is not modified while generation 0 is in use.
+//!     let archived = unsafe {
+//!         cache
+//!             .access::<Archived<Vec<u32>>, Error>(&bytes, 0)
+//!             .unwrap()
+//!     };
+//!     assert_eq!(archived[2], 3);
+//! }
+//!
+//! // Only the first access validated the archive.
+//! assert_eq!(cache.len(), 1);
+//! ```
+
+use core::{any::TypeId, fmt, mem::size_of};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use bytecheck::CheckBytes;
+use rancor::{Source, Strategy};
+
+use crate::{
+    util::access_pos_unchecked,
+    validation::{util::check_pos_with_context, validators::DefaultValidator},
+    Portable,
+};
+
+#[derive(Default)]
+struct Buffer {
+    generation: u64,
+    validated: HashSet<(TypeId, usize)>,
+}
+
+/// A cache of the archived values which have been validated in each buffer.
+///
+/// The cache can be shared between threads. See the [module docs](self) for
+/// more information.
+#[derive(Default)]
+pub struct ValidationCache {
+    buffers: RwLock<HashMap<(usize, usize), Buffer>>,
+}
+
+impl ValidationCache {
+    /// Returns a new, empty validation cache.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The cache is only locked briefly and never while validating, so a
+    // poisoned lock still holds a consistent cache.
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<(usize, usize), Buffer>> {
+        match self.buffers.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<(usize, usize), Buffer>> {
+        match self.buffers.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Returns whether a `T` at the given position of the given buffer
+    /// generation has been validated.
+    pub fn contains<T: 'static>(
+        &self,
+        bytes: &[u8],
+        generation: u64,
+        pos: usize,
+    ) -> bool {
+        match self.read().get(&key(bytes)) {
+            Some(buffer) => {
+                buffer.generation == generation
+                    && buffer.validated.contains(&(TypeId::of::<T>(), pos))
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the given buffer for a valid `T` at the given position, unless
+    /// it has already been validated in this generation of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of the buffer must not have changed since they were last
+    /// checked with the same address, length, and generation.
+    pub unsafe fn check_pos<T, E>(
+        &self,
+        bytes: &[u8],
+        generation: u64,
+        pos: usize,
+    ) -> Result<(), E>
+    where
+        T: CheckBytes<Strategy<DefaultValidator, E>> + 'static,
+        E: Source,
+    {
+        if self.contains::<T>(bytes, generation, pos) {
+            return Ok(());
+        }
+
+        let mut validator = DefaultValidator::new(bytes);
+        check_pos_with_context::<T, DefaultValidator, E>(
+            bytes,
+            pos,
+            &mut validator,
+        )?;
+
+        let mut buffers = self.write();
+        let buffer = buffers.entry(key(bytes)).or_default();
+        if buffer.generation != generation {
+            buffer.generation = generation;
+            buffer.validated.clear();
+        }
+        buffer.validated.insert((TypeId::of::<T>(), pos));
+
+        Ok(())
+    }
+
+    /// Accesses an archived value from the given buffer at the given
+    /// position, validating it only if it has not been validated in this
+    /// generation of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of the buffer must not have changed since they were last
+    /// checked with the same address, length, and generation.
+    #[inline]
+    pub unsafe fn access_pos<'a, T, E>(
+        &self,
+        bytes: &'a [u8],
+        generation: u64,
+        pos: usize,
+    ) -> Result<&'a T, E>
+    where
+        T: Portable + CheckBytes<Strategy<DefaultValidator, E>> + 'static,
+        E: Source,
+    {
+        // SAFETY: The caller has guaranteed that the bytes have not changed
+        // within this generation.
+        unsafe { self.check_pos::<T, E>(bytes, generation, pos)? };
+        // SAFETY: The value at `pos` was validated, either just now or
+        // earlier in this generation.
+        Ok(unsafe { access_pos_unchecked::<T>(bytes, pos) })
+    }
+
+    /// Accesses the root of the archive in the given buffer, validating it
+    /// only if it has not been validated in this generation of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The bytes of the buffer must not have changed since they were last
+    /// checked with the same address, length, and generation.
+    #[inline]
+    pub unsafe fn access<'a, T, E>(
+        &self,
+        bytes: &'a [u8],
+        generation: u64,
+    ) -> Result<&'a T, E>
+    where
+        T: Portable + CheckBytes<Strategy<DefaultValidator, E>> + 'static,
+        E: Source,
+    {
+        let pos = bytes.len().saturating_sub(size_of::<T>());
+        // SAFETY: The caller has guaranteed that the bytes have not changed
+        // within this generation.
+        unsafe { self.access_pos::<T, E>(bytes, generation, pos) }
+    }
+
+    /// Discards everything cached for the given buffer.
+    pub fn invalidate(&self, bytes: &[u8]) {
+        self.write().remove(&key(bytes));
+    }
+
+    /// Discards everything in the cache.
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+
+    /// Returns the number of validated values in the cache.
+    pub fn len(&self) -> usize {
+        self.read()
+            .values()
+            .map(|buffer| buffer.validated.len())
+            .sum()
+    }
+
+    /// Returns whether the cache has no validated values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for ValidationCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let buffers = self.read();
+        let len = buffers
+            .values()
+            .map(|buffer| buffer.validated.len())
+            .sum::<usize>();
+        f.debug_struct("ValidationCache")
+            .field("buffers", &buffers.len())
+            .field("len", &len)
+            .finish()
+    }
+}
+
+fn key(bytes: &[u8]) -> (usize, usize) {
+    (bytes.as_ptr() as usize, bytes.len())
+}
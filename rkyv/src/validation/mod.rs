@@ -1,5 +1,10 @@
 //! Validation implementations and helper types.
 
+#[cfg(feature = "std")]
+pub mod background;
+#[cfg(feature = "std")]
+pub mod cache;
+pub mod path;
 pub mod util;
 pub mod validators;
 
@@ -53,6 +58,19 @@ pub unsafe trait ArchiveContext<E = <Self as Fallible>::Error> {
         &mut self,
         range: Range<usize>,
     ) -> Result<(), E>;
+
+    /// Returns whether the contents of the current subtree range should be
+    /// checked.
+    ///
+    /// Validators performing a shallow check return `false` once they have
+    /// descended past their depth cutoff. The pointers to skipped subtrees are
+    /// still bounds checked, but their contents are not. A context which skips
+    /// any subtrees must return an error when the outermost subtree range is
+    /// popped so that partially-checked archives are never accessed.
+    #[inline]
+    fn should_check_subtree(&mut self) -> bool {
+        true
+    }
 }
 
 unsafe impl<T, E> ArchiveContext<E> for Strategy<T, E>
@@ -85,6 +103,10 @@ where
         // has the same safety requirements.
         unsafe { T::pop_subtree_range(self, range) }
     }
+
+    fn should_check_subtree(&mut self) -> bool {
+        T::should_check_subtree(self)
+    }
 }
 
 /// Helper methods for `ArchiveContext`s.
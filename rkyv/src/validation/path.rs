@@ -0,0 +1,264 @@
+//! Locating invalid data in archives.
+//!
+//! When validation fails, the error is traced with the location of the
+//! invalid data as it unwinds. Derived `CheckBytes` implementations trace the
+//! fields they were checking, archived vecs trace a [`PathSegment`] for the
+//! element they were checking, and the
+//! [`ArchiveValidator`](crate::validation::validators::ArchiveValidator)
+//! traces the [`ArchiveOffset`] of pointers which fail their bounds checks.
+//!
+//! These traces are included when an error like `rancor::Error` is displayed.
+//! [`PathError`] collects them into a path from the root of the archive to
+//! the invalid data, like `root.users[3].name`, along with its byte offset.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     access, rancor::Error, to_bytes, validation::path::PathError, Archive,
+//!     Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct User {
+//!     name: String,
+//!     active: bool,
+//! }
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct Users {
+//!     users: Vec<User>,
+//! }
+//!
+//! let value = Users {
+//!     users: vec![
+//!         User {
+//!             name: "ferris".to_string(),
+//!             active: true,
+//!         },
+//!         User {
+//!             name: "crab".to_string(),
+//!             active: false,
+//!         },
+//!     ],
+//! };
+//! let mut bytes = to_bytes::<Error>(&value).unwrap();
+//!
+//! // Corrupt the `active` field of the second user.
+//! let users = unsafe { rkyv::access_unchecked::<ArchivedUsers>(&bytes) };
+//! let active = &users.users[1].active as *const bool as usize
+//!     - bytes.as_ptr() as usize;
+//! bytes[active] = 2;
+//!
+//! let error = access::<ArchivedUsers, PathError>(&bytes).unwrap_err();
+//! assert_eq!(error.path().to_string(), "root.users[1].active");
+//! ```
+
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::{any::Any, error::Error};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use rancor::{Source, Trace};
+
+/// A step in the path from the root of an archive to some data in it.
+///
+/// Archived containers trace path segments onto validation errors to record
+/// which of their elements was invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A named field of a struct or enum variant.
+    Field(&'static str),
+    /// An unnamed field of a tuple struct or enum variant.
+    TupleField(usize),
+    /// A variant of an enum.
+    Variant(&'static str),
+    /// An element of a sequence.
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, "while checking field `{}`", name),
+            Self::TupleField(index) => {
+                write!(f, "while checking field {}", index)
+            }
+            Self::Variant(name) => {
+                write!(f, "while checking variant `{}`", name)
+            }
+            Self::Index(index) => {
+                write!(f, "while checking element {}", index)
+            }
+        }
+    }
+}
+
+/// The offset from the start of an archive of some invalid data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ArchiveOffset(pub usize);
+
+impl fmt::Display for ArchiveOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at archive offset {}", self.0)
+    }
+}
+
+/// The path from the root of an archive to some data in it.
+///
+/// This is displayed as a path expression starting at `root`, like
+/// `root.users[3].name`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorPath<'a> {
+    segments: &'a [PathSegment],
+}
+
+impl<'a> ErrorPath<'a> {
+    /// Returns the segments of the path, starting from the root.
+    #[inline]
+    pub fn segments(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &'a PathSegment> + 'a {
+        // Segments are traced from the innermost value outward.
+        self.segments.iter().rev()
+    }
+}
+
+impl fmt::Display for ErrorPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "root")?;
+        for segment in self.segments() {
+            match segment {
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::TupleField(index) => write!(f, ".{}", index)?,
+                PathSegment::Variant(name) => write!(f, "::{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error which records the path to and offset of the invalid data that
+/// caused it.
+///
+/// A path error wraps another error type `E`, and passes every trace along to
+/// it. See the [module docs](self) for more information.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct PathError<E = rancor::Error> {
+    inner: E,
+    segments: Vec<PathSegment>,
+    offset: Option<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl<E> PathError<E> {
+    /// Returns the path from the root of the archive to the invalid data.
+    #[inline]
+    pub fn path(&self) -> ErrorPath<'_> {
+        ErrorPath {
+            segments: &self.segments,
+        }
+    }
+
+    /// Returns the offset of the innermost pointer which failed its bounds
+    /// check, if any.
+    #[inline]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns a reference to the wrapped error.
+    #[inline]
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Consumes the path error and returns the wrapped error.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    fn push_trace(&mut self, trace: &dyn Any) {
+        // The contexts traced by derived `CheckBytes` implementations are
+        // translated into path segments as well.
+        if let Some(segment) = trace.downcast_ref::<PathSegment>() {
+            self.segments.push(*segment);
+        } else if let Some(offset) = trace.downcast_ref::<ArchiveOffset>() {
+            self.offset.get_or_insert(offset.0);
+        } else if let Some(context) =
+            trace.downcast_ref::<bytecheck::StructCheckContext>()
+        {
+            self.segments.push(PathSegment::Field(context.field_name));
+        } else if let Some(context) =
+            trace.downcast_ref::<bytecheck::TupleStructCheckContext>()
+        {
+            self.segments
+                .push(PathSegment::TupleField(context.field_index));
+        } else if let Some(context) =
+            trace.downcast_ref::<bytecheck::NamedEnumVariantCheckContext>()
+        {
+            self.segments.push(PathSegment::Field(context.field_name));
+            self.segments
+                .push(PathSegment::Variant(context.variant_name));
+        } else if let Some(context) =
+            trace.downcast_ref::<bytecheck::UnnamedEnumVariantCheckContext>()
+        {
+            self.segments
+                .push(PathSegment::TupleField(context.field_index));
+            self.segments
+                .push(PathSegment::Variant(context.variant_name));
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: fmt::Display> fmt::Display for PathError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid data at `{}`", self.path())?;
+        if let Some(offset) = self.offset {
+            write!(f, " (archive offset {})", offset)?;
+        }
+        write!(f, ": {}", self.inner)
+    }
+}
+
+// Like `StaticError`, this is implemented without `std` so that path errors
+// can be used on `no_std` targets.
+#[cfg(feature = "alloc")]
+impl<E: Error + 'static> Error for PathError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Trace> Trace for PathError<E> {
+    fn trace<R>(mut self, trace: R) -> Self
+    where
+        R: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    {
+        self.push_trace(&trace);
+        Self {
+            inner: self.inner.trace(trace),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Source> Source for PathError<E> {
+    fn new<T: Error + Send + Sync + 'static>(source: T) -> Self {
+        Self {
+            inner: E::new(source),
+            segments: Vec::new(),
+            offset: None,
+        }
+    }
+}
@@ -4,8 +4,12 @@ use core::{mem::size_of, pin::Pin};
 
 use bytecheck::CheckBytes;
 use ptr_meta::Pointee;
+#[cfg(feature = "static-errors")]
+use rancor::Trace as _;
 use rancor::{ResultExt as _, Source, Strategy};
 
+#[cfg(feature = "alloc")]
+use crate::util::AlignedVec;
 use crate::{
     de::pooling::Unify,
     deserialize,
@@ -23,6 +27,19 @@ pub fn check_pos_with_context<T, C, E>(
     pos: usize,
     context: &mut C,
 ) -> Result<(), E>
+where
+    T: CheckBytes<Strategy<C, E>> + Pointee<Metadata = ()>,
+    C: ArchiveContext<E> + ?Sized,
+    E: Source,
+{
+    trace_offset(check_pos_untraced::<T, C, E>(bytes, pos, context), pos)
+}
+
+fn check_pos_untraced<T, C, E>(
+    bytes: &[u8],
+    pos: usize,
+    context: &mut C,
+) -> Result<(), E>
 where
     T: CheckBytes<Strategy<C, E>> + Pointee<Metadata = ()>,
     C: ArchiveContext<E> + ?Sized,
@@ -45,6 +62,18 @@ where
     }
 }
 
+// Static errors keep the position of the value which failed to validate.
+#[cfg(feature = "static-errors")]
+fn trace_offset<E: Source>(result: Result<(), E>, pos: usize) -> Result<(), E> {
+    result.map_err(|error| error.trace(crate::static_error::ErrorOffset(pos)))
+}
+
+#[cfg(not(feature = "static-errors"))]
+#[inline]
+fn trace_offset<E>(result: Result<(), E>, _: usize) -> Result<(), E> {
+    result
+}
+
 // TODO: Either this should be unsafe or there must be some invariant that
 // `check_pos_with_context` verifies that the position is dereferenceable
 // regardless of what context was used to verify it.
@@ -151,6 +180,182 @@ where
     access_with_context::<T, DefaultValidator, E>(bytes, &mut validator)
 }
 
+/// Accesses an archived value from the given byte slice after prefaulting all
+/// of its pages and checking its validity.
+///
+/// Validating a large memory-mapped archive touches most of its pages in an
+/// unpredictable order. Prefaulting the archive first reads the pages in order,
+/// which lets the operating system read ahead and keeps page faults out of
+/// validation. See [`prefault`](crate::util::prefault) for more information.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, validation::util::access_prefaulted, Archived,
+/// };
+///
+/// let value = vec![1u32, 2, 3, 4];
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+///
+/// let archived =
+///     access_prefaulted::<Archived<Vec<u32>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.as_slice(), &[1, 2, 3, 4]);
+/// ```
+#[inline]
+pub fn access_prefaulted<T, E>(bytes: &[u8]) -> Result<&T, E>
+where
+    T: Portable + CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    crate::util::prefault(bytes, 0..bytes.len());
+    access::<T, E>(bytes)
+}
+
+/// Accesses an archived `T` from a byte slice which lives for the rest of the
+/// program after checking its validity.
+///
+/// This is useful for data which is loaded once and then used until the
+/// program exits, such as configuration. See [`access_leaked_vec`] to leak an
+/// owned buffer instead.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, validation::util::access_leaked, Archived,
+/// };
+///
+/// let bytes: &'static [u8] = to_bytes::<Error>(&42u32).unwrap().leak();
+///
+/// let archived: &'static Archived<u32> =
+///     access_leaked::<u32, Error>(bytes).unwrap();
+/// assert_eq!(*archived, 42);
+/// ```
+#[inline]
+pub fn access_leaked<T, E>(
+    bytes: &'static [u8],
+) -> Result<&'static T::Archived, E>
+where
+    T: Archive,
+    T::Archived: CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    access::<T::Archived, E>(bytes)
+}
+
+/// Checks the validity of an archived `T` in an owned buffer, then leaks the
+/// buffer and accesses the archived value.
+///
+/// The buffer is only leaked if it is valid. Its memory is never freed, so the
+/// returned reference may live for the rest of the program.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, validation::util::access_leaked_vec, Archived,
+/// };
+///
+/// fn load_config() -> &'static Archived<Vec<String>> {
+///     let bytes = to_bytes::<Error>(&vec!["a".to_string()]).unwrap();
+///     access_leaked_vec::<Vec<String>, Error>(bytes).unwrap()
+/// }
+///
+/// let config = load_config();
+/// assert_eq!(config[0], "a");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn access_leaked_vec<T, E>(
+    bytes: AlignedVec,
+) -> Result<&'static T::Archived, E>
+where
+    T: Archive,
+    T::Archived: CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    access::<T::Archived, E>(&bytes)?;
+    let bytes: &'static [u8] = bytes.leak();
+    // SAFETY: The bytes were checked to contain a valid archived `T` before
+    // they were leaked.
+    unsafe { Ok(crate::access_unchecked::<T::Archived>(bytes)) }
+}
+
+/// The result of a shallow check.
+#[derive(Debug)]
+pub enum ShallowCheck<E> {
+    /// The archive was fully checked and is valid.
+    Valid,
+    /// No errors were found, but some subtrees were deeper than the depth
+    /// cutoff and were not checked.
+    Incomplete,
+    /// The archive is invalid.
+    Invalid(E),
+}
+
+impl<E> ShallowCheck<E> {
+    /// Returns whether the archive was fully checked and is valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+
+    /// Returns whether the archive was rejected.
+    #[inline]
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, Self::Invalid(_))
+    }
+}
+
+/// Performs a cheap check of the root of an archive and the subtrees up to
+/// `depth` levels below it.
+///
+/// This can be used to quickly reject large untrusted archives before
+/// committing to full validation. A depth of zero only checks the root object
+/// and the bounds of its top-level containers. Archives which pass a shallow
+/// check must still be fully validated before being accessed.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     validation::util::{check_shallow, ShallowCheck},
+///     Archived,
+/// };
+///
+/// let value = vec![vec![1u32, 2], vec![3, 4]];
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+///
+/// let result = check_shallow::<Archived<Vec<Vec<u32>>>, Error>(&bytes, 0);
+/// assert!(matches!(result, ShallowCheck::Incomplete));
+///
+/// let result = check_shallow::<Archived<Vec<Vec<u32>>>, Error>(&bytes, 2);
+/// assert!(result.is_valid());
+///
+/// // Truncating the archive moves the root, so the check fails immediately
+/// let result = check_shallow::<Archived<Vec<Vec<u32>>>, Error>(
+///     &bytes[..bytes.len() - 4],
+///     0,
+/// );
+/// assert!(result.is_invalid());
+/// ```
+pub fn check_shallow<T, E>(bytes: &[u8], depth: usize) -> ShallowCheck<E>
+where
+    T: Portable + CheckBytes<Strategy<DefaultValidator, E>>,
+    E: Source,
+{
+    let mut validator = DefaultValidator::with_shallow_check(bytes, depth);
+    let result = check_pos_with_context::<T, DefaultValidator, E>(
+        bytes,
+        bytes.len().saturating_sub(size_of::<T>()),
+        &mut validator,
+    );
+    match result {
+        Ok(()) => ShallowCheck::Valid,
+        Err(_) if validator.is_incomplete() => ShallowCheck::Incomplete,
+        Err(e) => ShallowCheck::Invalid(e),
+    }
+}
+
 // TODO: `Pin` is not technically correct for the return type. `Pin` requires
 // the pinned value to be dropped before its memory can be reused, but archived
 // types explicitly do not require that. It just wants immovable types.
@@ -2,9 +2,13 @@
 
 use core::{alloc::Layout, fmt, num::NonZeroUsize, ops::Range};
 
-use rancor::{fail, OptionExt, Source};
+use rancor::{fail, OptionExt, Source, Trace as _};
 
-use crate::{fmt::Pointer, validation::ArchiveContext};
+use super::limits::{LimitExceeded, ValidationLimits};
+use crate::{
+    fmt::Pointer,
+    validation::{path::ArchiveOffset, ArchiveContext},
+};
 
 #[derive(Debug)]
 struct UnalignedPointer {
@@ -88,11 +92,35 @@ impl fmt::Display for RangePoppedOutOfOrder {
 #[cfg(feature = "std")]
 impl std::error::Error for RangePoppedOutOfOrder {}
 
-/// A validator that can verify archives with nonlocal memory.
 #[derive(Debug)]
+struct ShallowCheckIncomplete {
+    depth: usize,
+}
+
+impl fmt::Display for ShallowCheckIncomplete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shallow check skipped subtrees deeper than the depth cutoff of {}",
+            self.depth,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShallowCheckIncomplete {}
+
+/// A validator that can verify archives with nonlocal memory.
+#[derive(Clone, Debug)]
 pub struct ArchiveValidator {
+    archive_start: usize,
     subtree_range: Range<usize>,
     max_subtree_depth: Option<NonZeroUsize>,
+    depth: usize,
+    shallow_depth: Option<usize>,
+    incomplete: bool,
+    max_claimed_bytes: Option<usize>,
+    claimed_bytes: usize,
 }
 
 // SAFETY: `ArchiveValidator` is safe to send between threads because the
@@ -121,19 +149,105 @@ impl ArchiveValidator {
     ) -> Self {
         let Range { start, end } = bytes.as_ptr_range();
         Self {
+            archive_start: start as usize,
             subtree_range: Range {
                 start: start as usize,
                 end: end as usize,
             },
             max_subtree_depth,
+            depth: 0,
+            shallow_depth: None,
+            incomplete: false,
+            max_claimed_bytes: None,
+            claimed_bytes: 0,
         }
     }
-}
 
-unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator {
+    /// Creates a new bounds validator for the given bytes which fails once it
+    /// exceeds the given depth or claimed bytes limits.
+    ///
+    /// See [`ValidationLimits`] for more information.
     #[inline]
-    fn check_subtree_ptr(
+    pub fn with_limits(bytes: &[u8], limits: ValidationLimits) -> Self {
+        // The maximum subtree depth fails when it reaches zero, so it has to
+        // start one higher than the number of subtrees that may be nested.
+        let max_subtree_depth = limits
+            .max_depth()
+            .map(|depth| NonZeroUsize::MIN.saturating_add(depth));
+        Self {
+            max_claimed_bytes: limits.max_claimed_bytes(),
+            ..Self::with_max_depth(bytes, max_subtree_depth)
+        }
+    }
+
+    /// Creates a new bounds validator for the given bytes which performs a
+    /// shallow check.
+    ///
+    /// A shallow check only checks the contents of subtrees up to `depth`
+    /// levels below the root object. Pointers to deeper subtrees are still
+    /// bounds checked, but their contents are skipped. A depth of zero only
+    /// checks the root object and the bounds of the subtrees it points to,
+    /// such as the sizes and offsets of its top-level containers.
+    ///
+    /// Because the archive is not fully checked, the check fails after
+    /// completing if any subtrees were skipped. Use
+    /// [`is_incomplete`](ArchiveValidator::is_incomplete) to distinguish
+    /// incomplete checks from invalid archives, or use
+    /// [`check_shallow`](crate::validation::util::check_shallow).
+    #[inline]
+    pub fn with_shallow_check(bytes: &[u8], depth: usize) -> Self {
+        Self {
+            shallow_depth: Some(depth),
+            ..Self::new(bytes)
+        }
+    }
+
+    /// Returns whether a shallow check finished without finding any errors,
+    /// but skipped some subtrees.
+    #[inline]
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete && self.depth == 0
+    }
+
+    /// Returns the total size of the subtrees claimed so far.
+    #[inline]
+    pub fn claimed_bytes(&self) -> usize {
+        self.claimed_bytes
+    }
+
+    /// Returns the start of the current subtree range.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn subtree_start(&self) -> usize {
+        self.subtree_range.start
+    }
+
+    /// Moves the start of the current subtree range forward to `start` and
+    /// takes on any skipped subtrees and claimed bytes from the validator
+    /// `other`, which was cloned when `claimed_bytes` had been claimed.
+    ///
+    /// This is used to join the state of validators which checked disjoint
+    /// parts of the current subtree range.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn join(
         &mut self,
+        start: usize,
+        other: &Self,
+        claimed_bytes: usize,
+    ) {
+        self.subtree_range.start = start;
+        self.incomplete |= other.incomplete;
+        self.claimed_bytes = self
+            .claimed_bytes
+            .saturating_add(other.claimed_bytes - claimed_bytes);
+    }
+}
+
+impl ArchiveValidator {
+    #[inline]
+    fn check_subtree_ptr_untraced<E: Source>(
+        &self,
         ptr: *const u8,
         layout: &Layout,
     ) -> Result<(), E> {
@@ -154,6 +268,22 @@ unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator {
             Ok(())
         }
     }
+}
+
+unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator {
+    #[inline]
+    fn check_subtree_ptr(
+        &mut self,
+        ptr: *const u8,
+        layout: &Layout,
+    ) -> Result<(), E> {
+        self.check_subtree_ptr_untraced(ptr, layout)
+            .map_err(|e: E| {
+                e.trace(ArchiveOffset(
+                    (ptr as usize).wrapping_sub(self.archive_start),
+                ))
+            })
+    }
 
     #[inline]
     unsafe fn push_subtree_range(
@@ -166,11 +296,25 @@ unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator {
                 .into_trace(ExceededMaximumSubtreeDepth)?;
         }
 
+        let claimed_bytes = self
+            .claimed_bytes
+            .saturating_add((end as usize).saturating_sub(root as usize));
+        if let Some(limit) = self.max_claimed_bytes {
+            if claimed_bytes > limit {
+                fail!(LimitExceeded::ClaimedBytes {
+                    limit,
+                    claimed: claimed_bytes,
+                });
+            }
+        }
+        self.claimed_bytes = claimed_bytes;
+
         let result = Range {
             start: end as usize,
             end: self.subtree_range.end,
         };
         self.subtree_range.end = root as usize;
+        self.depth += 1;
         Ok(result)
     }
 
@@ -188,6 +332,25 @@ unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator {
                 .checked_add(1)
                 .into_trace(RangePoppedTooManyTimes)?;
         }
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 && self.incomplete {
+            fail!(ShallowCheckIncomplete {
+                depth: self.shallow_depth.unwrap_or(0),
+            });
+        }
         Ok(())
     }
+
+    #[inline]
+    fn should_check_subtree(&mut self) -> bool {
+        match self.shallow_depth {
+            // The root object is the first subtree range pushed, so subtrees
+            // `depth` levels below it are pushed at `depth + 1`.
+            Some(depth) if self.depth > depth + 1 => {
+                self.incomplete = true;
+                false
+            }
+            _ => true,
+        }
+    }
 }
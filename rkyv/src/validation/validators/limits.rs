@@ -0,0 +1,144 @@
+//! Resource limits for validating untrusted archives.
+
+use core::fmt;
+
+/// Limits on the resources that validating an archive may use.
+///
+/// Validation only checks that an archive is well-formed, so an archive from
+/// an untrusted source can still nest subtrees deeply enough to overflow the
+/// stack or register millions of shared pointers. A validator created with
+/// [`DefaultValidator::with_limits`](super::DefaultValidator::with_limits)
+/// fails with an error as soon as any of these limits is exceeded.
+///
+/// By default, no limits are set.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     validation::{
+///         util::access_with_context,
+///         validators::{DefaultValidator, ValidationLimits},
+///     },
+///     Archived,
+/// };
+///
+/// let value = vec![vec![1u32, 2], vec![3, 4]];
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+///
+/// // The outer vec, its elements, and the root object are three levels deep
+/// let limits = ValidationLimits::new().with_max_depth(2);
+/// let mut validator = DefaultValidator::with_limits(&bytes, limits);
+/// let result = access_with_context::<Archived<Vec<Vec<u32>>>, _, Error>(
+///     &bytes,
+///     &mut validator,
+/// );
+/// assert!(result.is_err());
+///
+/// let limits = ValidationLimits::new()
+///     .with_max_depth(3)
+///     .with_max_claimed_bytes(bytes.len());
+/// let mut validator = DefaultValidator::with_limits(&bytes, limits);
+/// let result = access_with_context::<Archived<Vec<Vec<u32>>>, _, Error>(
+///     &bytes,
+///     &mut validator,
+/// );
+/// assert!(result.is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidationLimits {
+    max_depth: Option<usize>,
+    max_claimed_bytes: Option<usize>,
+    max_shared_pointers: Option<usize>,
+}
+
+impl ValidationLimits {
+    /// Returns limits which don't restrict validation.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            max_depth: None,
+            max_claimed_bytes: None,
+            max_shared_pointers: None,
+        }
+    }
+
+    /// Sets the maximum number of nested subtrees, including the root object.
+    #[inline]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum total size of the subtrees that may be claimed.
+    ///
+    /// Every object behind a pointer claims the bytes it occupies. Nested
+    /// subtrees are counted in addition to the subtrees that contain them.
+    #[inline]
+    pub const fn with_max_claimed_bytes(
+        mut self,
+        max_claimed_bytes: usize,
+    ) -> Self {
+        self.max_claimed_bytes = Some(max_claimed_bytes);
+        self
+    }
+
+    /// Sets the maximum number of distinct shared pointers that may be
+    /// registered.
+    #[inline]
+    pub const fn with_max_shared_pointers(
+        mut self,
+        max_shared_pointers: usize,
+    ) -> Self {
+        self.max_shared_pointers = Some(max_shared_pointers);
+        self
+    }
+
+    /// Returns the maximum number of nested subtrees, if any.
+    #[inline]
+    pub const fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Returns the maximum total size of the claimed subtrees, if any.
+    #[inline]
+    pub const fn max_claimed_bytes(&self) -> Option<usize> {
+        self.max_claimed_bytes
+    }
+
+    /// Returns the maximum number of shared pointers, if any.
+    #[inline]
+    pub const fn max_shared_pointers(&self) -> Option<usize> {
+        self.max_shared_pointers
+    }
+}
+
+#[derive(Debug)]
+pub(super) enum LimitExceeded {
+    ClaimedBytes { limit: usize, claimed: usize },
+    SharedPointers { limit: usize },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClaimedBytes { limit, claimed } => write!(
+                f,
+                "validation claimed bytes limit exceeded: claiming {} bytes \
+                 would exceed the limit of {} bytes",
+                claimed, limit,
+            ),
+            Self::SharedPointers { limit } => write!(
+                f,
+                "validation shared pointer limit exceeded: limit is {} shared \
+                 pointers",
+                limit,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitExceeded {}
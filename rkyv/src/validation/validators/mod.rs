@@ -1,20 +1,31 @@
 //! Validators that can check archived types.
 
 mod archive;
+mod limits;
+#[cfg(feature = "std")]
+mod parallel;
 mod shared;
 
 use core::{any::TypeId, ops::Range};
 
 pub use archive::*;
+pub use limits::*;
+#[cfg(feature = "std")]
+pub use parallel::*;
 pub use shared::*;
 
-use crate::validation::{ArchiveContext, SharedContext};
+use crate::{
+    hash::DefaultHashBuilder,
+    validation::{ArchiveContext, SharedContext},
+};
 
 /// The default validator.
+///
+/// The hasher used by the [`SharedValidator`] can be customized with `H`.
 #[derive(Debug)]
-pub struct DefaultValidator {
+pub struct DefaultValidator<H = DefaultHashBuilder> {
     archive: ArchiveValidator,
-    shared: SharedValidator,
+    shared: SharedValidator<H>,
 }
 
 impl DefaultValidator {
@@ -35,9 +46,60 @@ impl DefaultValidator {
             shared: SharedValidator::with_capacity(capacity),
         }
     }
+
+    /// Creates a new validator from a byte range which fails once it exceeds
+    /// any of the given limits.
+    ///
+    /// See [`ValidationLimits`] for more information.
+    #[inline]
+    pub fn with_limits(bytes: &[u8], limits: ValidationLimits) -> Self {
+        let mut shared = SharedValidator::new();
+        shared.set_limits(limits);
+        Self {
+            archive: ArchiveValidator::with_limits(bytes, limits),
+            shared,
+        }
+    }
+
+    /// Creates a new validator from a byte range which performs a shallow
+    /// check with the given depth cutoff.
+    ///
+    /// See [`ArchiveValidator::with_shallow_check`] for more information.
+    #[inline]
+    pub fn with_shallow_check(bytes: &[u8], depth: usize) -> Self {
+        Self {
+            archive: ArchiveValidator::with_shallow_check(bytes, depth),
+            shared: SharedValidator::new(),
+        }
+    }
 }
 
-unsafe impl<E> ArchiveContext<E> for DefaultValidator
+impl<H> DefaultValidator<H> {
+    /// Creates a new validator from a byte range which uses the given hasher
+    /// to track shared pointers.
+    #[inline]
+    pub fn with_hasher(bytes: &[u8], hasher: H) -> Self {
+        Self {
+            archive: ArchiveValidator::new(bytes),
+            shared: SharedValidator::with_hasher(hasher),
+        }
+    }
+
+    /// Returns whether a shallow check finished without finding any errors,
+    /// but skipped some subtrees.
+    #[inline]
+    pub fn is_incomplete(&self) -> bool {
+        self.archive.is_incomplete()
+    }
+
+    /// Returns the total size of the subtrees claimed so far.
+    #[inline]
+    pub fn claimed_bytes(&self) -> usize {
+        self.archive.claimed_bytes()
+    }
+}
+
+unsafe impl<H, E> ArchiveContext<E> for DefaultValidator<H>
 where
     ArchiveValidator: ArchiveContext<E>,
 {
@@ -70,11 +132,16 @@ where
         // `ArchiveValidator`, which has the same safety requirements.
         unsafe { self.archive.pop_subtree_range(range) }
     }
+
+    #[inline]
+    fn should_check_subtree(&mut self) -> bool {
+        self.archive.should_check_subtree()
+    }
 }
 
-impl<E> SharedContext<E> for DefaultValidator
+impl<H, E> SharedContext<E> for DefaultValidator<H>
 where
-    SharedValidator: SharedContext<E>,
+    SharedValidator<H>: SharedContext<E>,
 {
     #[inline]
     fn register_shared_ptr(
@@ -0,0 +1,251 @@
+//! A validator which checks independent subtrees on multiple threads.
+
+use core::{alloc::Layout, fmt, mem::size_of, num::NonZeroUsize, ops::Range};
+use std::thread;
+
+use bytecheck::CheckBytes;
+use rancor::{fail, ResultExt as _, Source, Strategy};
+
+use crate::{
+    fmt::Pointer,
+    validation::{
+        validators::ArchiveValidator, ArchiveContext, ArchiveContextExt as _,
+    },
+    vec::ArchivedVec,
+};
+
+#[derive(Debug)]
+struct ChunkOutOfOrder {
+    address: usize,
+    claimed: usize,
+}
+
+impl fmt::Display for ChunkOutOfOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "parallel chunk claimed memory at {} before the end of the \
+             subtrees claimed by earlier chunks at {}",
+            Pointer(self.address),
+            Pointer(self.claimed),
+        )
+    }
+}
+
+impl std::error::Error for ChunkOutOfOrder {}
+
+/// A validator which can check the elements of a slice on multiple threads.
+///
+/// [`access`](crate::access) checks an archive on a single thread. The
+/// elements of an archived vec are independent subtrees, so a large vec can
+/// be split into chunks which are checked on separate threads.
+/// [`access_vec_parallel`] does this for an archive with an `ArchivedVec` at
+/// its root.
+///
+/// Each chunk is checked with its own `ParallelValidator` over the same
+/// subtree range. While checking, a validator records the lowest address that
+/// its chunk claimed. After all of the chunks finish, their claimed ranges are
+/// joined in order: each chunk must only claim memory after the end of the
+/// subtrees claimed by the chunks before it. This is the same rule that a
+/// single-threaded validator enforces, so an archive is accepted by a parallel
+/// check exactly when it is accepted by a sequential one.
+///
+/// Shared pointers may be validated by whichever chunk reaches them first,
+/// which would break the ordering of claimed ranges. `ParallelValidator` does
+/// not implement [`SharedContext`](crate::validation::SharedContext), so
+/// archives containing shared pointers must be checked with the
+/// [`DefaultValidator`](crate::validation::validators::DefaultValidator).
+///
+/// # Example
+///
+/// ```
+/// use core::num::NonZeroUsize;
+///
+/// use rkyv::{
+///     rancor::Error, string::ArchivedString, to_bytes,
+///     validation::validators::access_vec_parallel,
+/// };
+///
+/// let value = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+///
+/// let threads = NonZeroUsize::new(4).unwrap();
+/// let archived =
+///     access_vec_parallel::<ArchivedString, Error>(&bytes, threads).unwrap();
+/// assert_eq!(archived.len(), 1000);
+/// assert_eq!(archived[999], "999");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParallelValidator {
+    archive: ArchiveValidator,
+    threads: NonZeroUsize,
+    lowest: usize,
+}
+
+impl ParallelValidator {
+    /// Creates a new validator for the given bytes which checks slices with
+    /// up to `threads` threads.
+    #[inline]
+    pub fn new(bytes: &[u8], threads: NonZeroUsize) -> Self {
+        Self {
+            archive: ArchiveValidator::new(bytes),
+            threads,
+            lowest: usize::MAX,
+        }
+    }
+
+    /// Returns the number of threads used to check slices.
+    #[inline]
+    pub fn threads(&self) -> NonZeroUsize {
+        self.threads
+    }
+
+    /// Checks the elements of the given slice, splitting them into chunks
+    /// which are checked on separate threads.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be bounds checked and its subtree range must have been
+    /// pushed onto this validator.
+    pub unsafe fn check_slice<T, E>(&mut self, ptr: *const [T]) -> Result<(), E>
+    where
+        T: CheckBytes<Strategy<Self, E>>,
+        E: Source + Send,
+    {
+        let len = ptr_meta::metadata(ptr);
+        let chunk_len = len.div_ceil(self.threads.get()).max(1);
+        // Raw pointers can't be sent between threads, so the workers receive
+        // the address of the first element instead.
+        let base = ptr as *const T as usize;
+
+        let workers = thread::scope(|scope| {
+            let handles = (0..len)
+                .step_by(chunk_len)
+                .map(|start| {
+                    let mut worker = Self {
+                        lowest: usize::MAX,
+                        ..self.clone()
+                    };
+                    let end = usize::min(start + chunk_len, len);
+                    scope.spawn(move || {
+                        for i in start..end {
+                            let element = (base as *const T).wrapping_add(i);
+                            // SAFETY: The caller has guaranteed that `ptr` was
+                            // bounds checked, so each of its elements is
+                            // located inside the archive.
+                            unsafe {
+                                T::check_bytes(
+                                    element,
+                                    Strategy::wrap(&mut worker),
+                                )?;
+                            }
+                        }
+                        Ok::<_, E>(worker)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| match handle.join() {
+                    Ok(result) => result,
+                    Err(panic) => std::panic::resume_unwind(panic),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let claimed_bytes = self.archive.claimed_bytes();
+        let mut claimed = self.archive.subtree_start();
+        for worker in workers {
+            let worker = worker?;
+            if worker.lowest < claimed {
+                fail!(ChunkOutOfOrder {
+                    address: worker.lowest,
+                    claimed,
+                });
+            }
+            claimed = usize::max(claimed, worker.archive.subtree_start());
+            self.archive.join(claimed, &worker.archive, claimed_bytes);
+            self.lowest = usize::min(self.lowest, worker.lowest);
+        }
+
+        Ok(())
+    }
+}
+
+unsafe impl<E: Source> ArchiveContext<E> for ParallelValidator {
+    #[inline]
+    fn check_subtree_ptr(
+        &mut self,
+        ptr: *const u8,
+        layout: &Layout,
+    ) -> Result<(), E> {
+        self.lowest = usize::min(self.lowest, ptr as usize);
+        self.archive.check_subtree_ptr(ptr, layout)
+    }
+
+    #[inline]
+    unsafe fn push_subtree_range(
+        &mut self,
+        root: *const u8,
+        end: *const u8,
+    ) -> Result<Range<usize>, E> {
+        // SAFETY: This just forwards the call to the underlying validator,
+        // which has the same safety requirements.
+        unsafe { self.archive.push_subtree_range(root, end) }
+    }
+
+    #[inline]
+    unsafe fn pop_subtree_range(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<(), E> {
+        // SAFETY: This just forwards the call to the underlying validator,
+        // which has the same safety requirements.
+        unsafe { self.archive.pop_subtree_range(range) }
+    }
+
+    #[inline]
+    fn should_check_subtree(&mut self) -> bool {
+        ArchiveContext::<E>::should_check_subtree(&mut self.archive)
+    }
+}
+
+/// Accesses an archived vec at the root of the given byte slice after checking
+/// its validity, checking its elements on up to `threads` threads.
+///
+/// See [`ParallelValidator`] for more information.
+pub fn access_vec_parallel<T, E>(
+    bytes: &[u8],
+    threads: NonZeroUsize,
+) -> Result<&ArchivedVec<T>, E>
+where
+    T: CheckBytes<Strategy<ParallelValidator, E>>,
+    E: Source + Send,
+{
+    let mut validator = ParallelValidator::new(bytes, threads);
+    let pos = bytes.len().saturating_sub(size_of::<ArchivedVec<T>>());
+
+    unsafe {
+        let ptr = validator
+            .bounds_check_subtree_base_offset::<ArchivedVec<T>>(
+                bytes.as_ptr(),
+                pos.try_into().into_error()?,
+                (),
+            )?;
+        let root_range = validator.push_prefix_subtree(ptr)?;
+
+        // SAFETY: `ptr` was bounds checked, and every bit pattern is a valid
+        // relative pointer and length, so the fields of the vec are valid.
+        let vec = &*ptr;
+        let elements = vec.bounds_check_elements(&mut validator)?;
+        let range = validator.push_prefix_subtree(elements)?;
+        if ArchiveContext::<E>::should_check_subtree(&mut validator) {
+            validator.check_slice::<T, E>(elements)?;
+        }
+        validator.pop_subtree_range(range)?;
+        validator.pop_subtree_range(root_range)?;
+
+        Ok(vec)
+    }
+}
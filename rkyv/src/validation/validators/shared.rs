@@ -1,7 +1,7 @@
 //! Validators add validation capabilities by wrapping and extending basic
 //! validators.
 
-use core::{any::TypeId, fmt};
+use core::{any::TypeId, fmt, hash::BuildHasher};
 #[cfg(feature = "std")]
 use std::collections::HashMap;
 
@@ -9,7 +9,8 @@ use std::collections::HashMap;
 use hashbrown::HashMap;
 use rancor::{fail, Source};
 
-use crate::validation::SharedContext;
+use super::limits::{LimitExceeded, ValidationLimits};
+use crate::{hash::DefaultHashBuilder, validation::SharedContext};
 
 /// Errors that can occur when checking shared memory.
 #[derive(Debug)]
@@ -47,9 +48,12 @@ impl std::error::Error for SharedError {
 }
 
 /// A validator that can verify shared memory.
-#[derive(Debug, Default)]
-pub struct SharedValidator {
-    shared: HashMap<usize, TypeId>,
+///
+/// The hasher used to look up shared pointers can be customized with `H`.
+#[derive(Debug)]
+pub struct SharedValidator<H = DefaultHashBuilder> {
+    shared: HashMap<usize, TypeId, H>,
+    max_shared_pointers: Option<usize>,
 }
 
 impl SharedValidator {
@@ -57,20 +61,56 @@ impl SharedValidator {
     #[inline]
     pub fn new() -> Self {
         Self {
-            shared: HashMap::new(),
+            shared: HashMap::default(),
+            max_shared_pointers: None,
         }
     }
 
     /// Shared memory validator with specific capacity.
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl Default for SharedValidator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> SharedValidator<H> {
+    /// Shared memory validator which uses the given hasher.
+    #[inline]
+    pub fn with_hasher(hasher: H) -> Self {
         Self {
-            shared: HashMap::with_capacity(capacity),
+            shared: HashMap::with_hasher(hasher),
+            max_shared_pointers: None,
         }
     }
+
+    /// Shared memory validator with specific capacity which uses the given
+    /// hasher.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: H) -> Self {
+        Self {
+            shared: HashMap::with_capacity_and_hasher(capacity, hasher),
+            max_shared_pointers: None,
+        }
+    }
+
+    /// Sets the maximum number of shared pointers that may be registered
+    /// from the given limits.
+    ///
+    /// See [`ValidationLimits`] for more information.
+    #[inline]
+    pub fn set_limits(&mut self, limits: ValidationLimits) {
+        self.max_shared_pointers = limits.max_shared_pointers();
+    }
 }
 
-impl<E: Source> SharedContext<E> for SharedValidator {
+impl<H: BuildHasher, E: Source> SharedContext<E> for SharedValidator<H> {
     #[inline]
     fn register_shared_ptr(
         &mut self,
@@ -83,6 +123,7 @@ impl<E: Source> SharedContext<E> for SharedValidator {
         #[cfg(not(feature = "std"))]
         use hashbrown::hash_map::Entry;
 
+        let registered = self.shared.len();
         match self.shared.entry(address) {
             Entry::Occupied(previous_type_entry) => {
                 let previous_type_id = previous_type_entry.get();
@@ -96,6 +137,11 @@ impl<E: Source> SharedContext<E> for SharedValidator {
                 }
             }
             Entry::Vacant(ent) => {
+                if let Some(limit) = self.max_shared_pointers {
+                    if registered >= limit {
+                        fail!(LimitExceeded::SharedPointers { limit });
+                    }
+                }
                 ent.insert(type_id);
                 Ok(true)
             }
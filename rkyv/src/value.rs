@@ -0,0 +1,394 @@
+//! An untyped, JSON-like value which can be archived and navigated zero-copy.
+//!
+//! [`Value`] is useful for carrying a small amount of schemaless data alongside
+//! typed structures without falling back to embedding JSON strings. Its
+//! archived form, [`ArchivedValue`], can be navigated in place with accessors
+//! like [`get`](ArchivedValue::get) and [`pointer`](ArchivedValue::pointer).
+//!
+//! # Example
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use rkyv::{
+//!     access_unchecked,
+//!     rancor::Error,
+//!     to_bytes,
+//!     value::{ArchivedValue, Value},
+//! };
+//!
+//! let mut map = BTreeMap::new();
+//! map.insert("name".to_string(), Value::from("ferris"));
+//! map.insert("age".to_string(), Value::from(10));
+//! map.insert(
+//!     "tags".to_string(),
+//!     Value::from(vec![Value::from("crab"), Value::Null]),
+//! );
+//! let value = Value::from(map);
+//!
+//! let bytes = to_bytes::<Error>(&value).unwrap();
+//! let archived = unsafe { access_unchecked::<ArchivedValue>(&bytes) };
+//! assert_eq!(archived.get("name").unwrap().as_str(), Some("ferris"));
+//! assert_eq!(archived.get("age").unwrap().as_i64(), Some(10));
+//! assert_eq!(archived.pointer("/tags/0").unwrap().as_str(), Some("crab"));
+//! assert!(archived.pointer("/tags/1").unwrap().is_null());
+//! ```
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(not(feature = "std"))]
+use ::alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use crate::{
+    collections::btree_map::ArchivedBTreeMap, string::ArchivedString, Archive,
+    Deserialize, Serialize,
+};
+
+/// An untyped value.
+///
+/// The variants mirror the data model of JSON, with the addition of raw byte
+/// strings. Maps are ordered by key so that archived maps can be searched.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[archive(crate)]
+#[archive(
+    serialize_bounds(
+        __S: crate::ser::Writer + crate::ser::Allocator,
+        __S::Error: rancor::Source,
+    ),
+    deserialize_bounds(
+        __D::Error: rancor::Source,
+    ),
+)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+#[cfg_attr(
+    feature = "bytecheck",
+    archive_attr(check_bytes(bounds(
+        __C: crate::validation::ArchiveContext,
+    )))
+)]
+#[archive_attr(derive(Debug))]
+pub enum Value {
+    /// The absence of a value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// A floating-point number.
+    Float(f64),
+    /// A UTF-8 string.
+    String(String),
+    /// A byte string.
+    Bytes(Vec<u8>),
+    /// An ordered sequence of values.
+    Array(
+        #[omit_bounds]
+        #[cfg_attr(feature = "bytecheck", archive_attr(omit_bounds))]
+        Vec<Value>,
+    ),
+    /// A map from string keys to values.
+    Map(
+        #[omit_bounds]
+        #[cfg_attr(feature = "bytecheck", archive_attr(omit_bounds))]
+        BTreeMap<String, Value>,
+    ),
+}
+
+impl Value {
+    /// Returns whether the value is null.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the value as a boolean, if it is one.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an integer, if it is one.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a floating-point number, if it is a number.
+    ///
+    /// Integers are converted to the nearest floating-point number.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string, if it is one.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a byte string, if it is one.
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an array, if it is one.
+    #[inline]
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a map, if it is one.
+    #[inline]
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Self::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the given key if this value is a map containing
+    /// it.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_map()?.get(key)
+    }
+
+    /// Returns the element at the given index if this value is an array long
+    /// enough to contain it.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        self.as_array()?.get(index)
+    }
+}
+
+impl Default for Value {
+    #[inline]
+    fn default() -> Self {
+        Self::Null
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    bool => Bool,
+    i8 => Int,
+    i16 => Int,
+    i32 => Int,
+    i64 => Int,
+    u8 => Int,
+    u16 => Int,
+    u32 => Int,
+    f32 => Float,
+    f64 => Float,
+    String => String,
+    &str => String,
+    Vec<Value> => Array,
+    BTreeMap<String, Value> => Map,
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for Value {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Value> for Value {
+    /// Converts a JSON value into a `Value`.
+    ///
+    /// Numbers which fit in an `i64` are converted to integers, and all other
+    /// numbers are converted to floating-point numbers.
+    fn from(value: serde_json::Value) -> Self {
+        use serde_json::Value as Json;
+
+        match value {
+            Json::Null => Self::Null,
+            Json::Bool(b) => Self::Bool(b),
+            Json::Number(n) => match n.as_i64() {
+                Some(i) => Self::Int(i),
+                None => Self::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Json::String(s) => Self::String(s),
+            Json::Array(a) => {
+                Self::Array(a.into_iter().map(Self::from).collect())
+            }
+            Json::Object(o) => Self::Map(
+                o.into_iter().map(|(k, v)| (k, Self::from(v))).collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<&serde_json::Value> for Value {
+    #[inline]
+    fn from(value: &serde_json::Value) -> Self {
+        Self::from(value.clone())
+    }
+}
+
+impl ArchivedValue {
+    /// Returns whether the value is null.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the value as a boolean, if it is one.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an integer, if it is one.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(i) => Some(i.to_native()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a floating-point number, if it is a number.
+    ///
+    /// Integers are converted to the nearest floating-point number.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(i) => Some(i.to_native() as f64),
+            Self::Float(f) => Some(f.to_native()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string, if it is one.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a byte string, if it is one.
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(b) => Some(b.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an array, if it is one.
+    #[inline]
+    pub fn as_array(&self) -> Option<&[ArchivedValue]> {
+        match self {
+            Self::Array(a) => Some(a.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a map, if it is one.
+    #[inline]
+    pub fn as_map(&self) -> Option<&ArchivedBTreeMap<ArchivedString, Self>> {
+        match self {
+            Self::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the given key if this value is a map containing
+    /// it.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&ArchivedValue> {
+        self.as_map()?.get(key)
+    }
+
+    /// Returns the element at the given index if this value is an array long
+    /// enough to contain it.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&ArchivedValue> {
+        self.as_array()?.get(index)
+    }
+
+    /// Looks up a value by a JSON pointer as defined in [RFC 6901].
+    ///
+    /// An empty pointer refers to this value. Otherwise, each `/`-separated
+    /// token of the pointer selects a map entry by key or an array element by
+    /// index. Returns `None` if any token does not refer to a value.
+    ///
+    /// [RFC 6901]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn pointer(&self, pointer: &str) -> Option<&ArchivedValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match value {
+                Self::Array(items) => items.as_slice().get(parse_index(token)?),
+                Self::Map(entries) => {
+                    if token.contains('~') {
+                        let key = token.replace("~1", "/").replace("~0", "~");
+                        entries.get(key.as_str())
+                    } else {
+                        entries.get(token)
+                    }
+                }
+                _ => None,
+            })
+    }
+}
+
+fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -1,20 +1,33 @@
 //! An archived version of `Vec`.
 
 use core::{
+    alloc::Layout,
     borrow::Borrow,
     cmp, fmt, hash,
+    iter::FusedIterator,
     ops::{Deref, Index, IndexMut},
     pin::Pin,
-    slice::SliceIndex,
+    slice::{self, SliceIndex},
 };
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use ::alloc::vec::Vec;
 use munge::munge;
 use rancor::Fallible;
 
+#[cfg(feature = "alloc")]
+use crate::analyze::{Analyze, TypeSizeReport};
+#[cfg(feature = "serde")]
+use crate::export::Export;
 use crate::{
-    primitive::ArchivedUsize,
+    format::{DefaultFormat, Format, Primitive as _},
+    redact::Redact,
+    rel_ptr::RelPtr,
+    schema::{SchemaHash, SchemaHasher},
+    search::{self, Needle, Split},
     ser::{Allocator, Writer, WriterExt as _},
-    Archive, Place, Portable, RelPtr, Serialize, SerializeUnsized,
+    Archive, ArchivedSize, Extract, ExtractUnsized as _, NativeLayout, Place,
+    Portable, Serialize, SerializeUnsized,
 };
 
 // pub use self::raw::*;
@@ -24,6 +37,9 @@ use crate::{
 /// This uses a [`RelPtr`] to a `[T]` under the hood. Unlike
 /// [`ArchivedString`](crate::string::ArchivedString), it does not have an
 /// inline representation.
+///
+/// The [`Format`] parameter selects the archived types of the relative pointer
+/// and length. See [`format`](mod@crate::format) for more information.
 #[derive(Portable)]
 #[archive(crate)]
 #[repr(C)]
@@ -32,12 +48,12 @@ use crate::{
     derive(bytecheck::CheckBytes),
     check_bytes(verify)
 )]
-pub struct ArchivedVec<T> {
-    ptr: RelPtr<T>,
-    len: ArchivedUsize,
+pub struct ArchivedVec<T, F: Format = DefaultFormat> {
+    ptr: RelPtr<T, F::Isize>,
+    len: F::Usize,
 }
 
-impl<T> ArchivedVec<T> {
+impl<T, F: Format> ArchivedVec<T, F> {
     /// Returns a pointer to the first element of the archived vec.
     #[inline]
     pub fn as_ptr(&self) -> *const T {
@@ -47,7 +63,7 @@ impl<T> ArchivedVec<T> {
     /// Returns the number of elements in the archived vec.
     #[inline]
     pub fn len(&self) -> usize {
-        self.len.to_native() as usize
+        self.len.to_native()
     }
 
     /// Returns whether the archived vec is empty.
@@ -75,6 +91,35 @@ impl<T> ArchivedVec<T> {
         }
     }
 
+    /// Returns an iterator over pinned mutable references to the elements of
+    /// the archived vec.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rkyv::{access_unchecked_mut, rancor::Error, to_bytes, Archived};
+    ///
+    /// let mut bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+    /// let archived =
+    ///     unsafe { access_unchecked_mut::<Archived<Vec<u32>>>(&mut bytes) };
+    /// for mut value in archived.iter_seal() {
+    ///     *value = 0.into();
+    /// }
+    ///
+    /// let archived =
+    ///     unsafe { rkyv::access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+    /// assert_eq!(archived.as_slice(), &[0, 0, 0]);
+    /// ```
+    #[inline]
+    pub fn iter_seal(self: Pin<&mut Self>) -> IterSeal<'_, T> {
+        // SAFETY: The elements are never moved out of the slice, and are only
+        // handed out as pinned references.
+        let slice = unsafe { self.pin_mut_slice().get_unchecked_mut() };
+        IterSeal {
+            inner: slice.iter_mut(),
+        }
+    }
+
     // This method can go away once pinned slices have indexing support
     // https://github.com/rust-lang/rust/pull/78370
 
@@ -110,9 +155,14 @@ impl<T> ArchivedVec<T> {
     ) {
         munge!(let ArchivedVec { ptr, len: out_len } = out);
         RelPtr::emplace(resolver.pos, ptr);
-        usize::resolve(&len, (), out_len);
+        out_len.write(F::Usize::from_native(len));
     }
+}
 
+// Serializing the elements of a vec doesn't depend on its format, so these are
+// only provided for the default format. Their resolvers can be used to resolve
+// a vec in any format.
+impl<T> ArchivedVec<T> {
     /// Serializes an archived `Vec` from a given slice.
     #[inline]
     pub fn serialize_from_slice<
@@ -155,7 +205,9 @@ impl<T> ArchivedVec<T> {
                     resolvers.push(resolver);
                 }
 
-                let pos = serializer.align_for::<T>()?;
+                let layout = Layout::array::<T>(iter.len())
+                    .unwrap_or(Layout::new::<T>());
+                let pos = serializer.align_for_layout(layout)?;
                 for (value, resolver) in iter.zip(resolvers.drain(..)) {
                     unsafe {
                         serializer.resolve_aligned(value.borrow(), resolver)?;
@@ -197,29 +249,303 @@ impl<T> ArchivedVec<T> {
             Ok(VecResolver { pos })
         }
     }
+
+    /// Serializes an archived `Vec` from a given iterator, buffering its items
+    /// in scratch space. Returns the resolver along with the number of items
+    /// serialized, which should be passed to
+    /// [`resolve_from_len`](ArchivedVec::resolve_from_len).
+    ///
+    /// Unlike [`serialize_from_iter`](ArchivedVec::serialize_from_iter), the
+    /// iterator doesn't need to know its length in advance or be cloned, and
+    /// each item is only produced once. Unlike
+    /// [`serialize_from_unknown_length_iter`], items may write out-of-line
+    /// data when they are serialized.
+    ///
+    /// Items and their resolvers are buffered in chunks of scratch space which
+    /// double in size, starting from the lower bound of the iterator's size
+    /// hint.
+    ///
+    /// [`serialize_from_unknown_length_iter`]:
+    /// ArchivedVec::serialize_from_unknown_length_iter
+    #[inline]
+    pub fn serialize_from_buffered_iter<B, I, S>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<(VecResolver, usize), S::Error>
+    where
+        B: Serialize<S, Archived = T>,
+        I: IntoIterator<Item = B>,
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        const MIN_CHUNK_LEN: usize = 8;
+
+        let mut iter = iter.into_iter();
+        let chunk_len = cmp::max(iter.size_hint().0, MIN_CHUNK_LEN);
+        Self::serialize_chunks(
+            &mut iter,
+            chunk_len,
+            0,
+            &mut |_| Ok(()),
+            serializer,
+        )
+    }
+
+    /// Serializes items from the iterator into a chunk of scratch space with
+    /// the given length, continuing into a larger chunk if the iterator has
+    /// more items. `resolve_prev` resolves the items from the previous chunks.
+    fn serialize_chunks<B, I, S>(
+        iter: &mut I,
+        chunk_len: usize,
+        prev_len: usize,
+        resolve_prev: &mut dyn FnMut(&mut S) -> Result<(), S::Error>,
+        serializer: &mut S,
+    ) -> Result<(VecResolver, usize), S::Error>
+    where
+        B: Serialize<S, Archived = T>,
+        I: Iterator<Item = B>,
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        use crate::util::SerVec;
+
+        SerVec::<(B, B::Resolver)>::with_capacity(
+            serializer,
+            chunk_len,
+            |chunk, serializer| {
+                while chunk.len() < chunk.capacity() {
+                    match iter.next() {
+                        Some(value) => {
+                            let resolver = value.serialize(serializer)?;
+                            chunk.push((value, resolver));
+                        }
+                        None => {
+                            // Every item has been serialized, so the items can
+                            // be written out.
+                            let len = prev_len + chunk.len();
+                            let pos = serializer.align_for::<T>()?;
+                            resolve_prev(serializer)?;
+                            for (value, resolver) in chunk.drain(..) {
+                                unsafe {
+                                    serializer
+                                        .resolve_aligned(&value, resolver)?;
+                                }
+                            }
+                            return Ok((VecResolver { pos }, len));
+                        }
+                    }
+                }
+
+                Self::serialize_chunks(
+                    iter,
+                    chunk_len * 2,
+                    prev_len + chunk.len(),
+                    &mut |serializer| {
+                        resolve_prev(serializer)?;
+                        for (value, resolver) in chunk.drain(..) {
+                            unsafe {
+                                serializer.resolve_aligned(&value, resolver)?;
+                            }
+                        }
+                        Ok(())
+                    },
+                    serializer,
+                )
+            },
+        )?
+    }
+}
+
+impl<T: ArchivedSize, F: Format> ArchivedSize for ArchivedVec<T, F> {
+    #[inline]
+    fn out_of_line_size(&self) -> usize {
+        self.as_slice().archived_size()
+    }
+}
+
+impl<T: SchemaHash, F: Format> SchemaHash for ArchivedVec<T, F> {
+    const SCHEMA_HASH: u64 = SchemaHasher::new()
+        .write_str("Vec")
+        .write_layout::<Self>()
+        .write_format::<F>()
+        .write_u64(T::SCHEMA_HASH)
+        .finish();
 }
 
-impl<T> AsRef<[T]> for ArchivedVec<T> {
+impl<T: NativeLayout, F: Format> ArchivedVec<T, F> {
+    /// Gets the elements of the archived vec as a slice of native values.
+    ///
+    /// Returns `None` if the archived elements do not have the same layout as
+    /// their native type, for example if the archive uses a different
+    /// endianness than the target. This is determined at compile time, so the
+    /// check has no runtime cost.
+    #[inline]
+    pub fn as_native_slice(&self) -> Option<&[T::Native]> {
+        if T::LAYOUT_MATCHES {
+            // SAFETY: `T` has the same layout as `T::Native`, and every valid
+            // `T` is a valid `T::Native`. `T` is at least as aligned as
+            // `T::Native`, so the pointer is properly aligned.
+            Some(unsafe {
+                core::slice::from_raw_parts(
+                    self.as_ptr().cast::<T::Native>(),
+                    self.len(),
+                )
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Copies the elements of the archived vec into a slice of native values.
+    ///
+    /// If the archived elements have the same layout as their native type,
+    /// they are copied directly. Otherwise, they are converted in fixed-size
+    /// chunks so that the conversion (e.g. swapping bytes for archives with a
+    /// different endianness) can be vectorized. This is much faster than
+    /// converting each element with a separate call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` does not have the same length as the archived vec.
+    pub fn copy_to_native(&self, out: &mut [T::Native])
+    where
+        T::Native: Copy,
+    {
+        const CHUNK_LEN: usize = 16;
+
+        assert_eq!(
+            out.len(),
+            self.len(),
+            "destination and archived vec have different lengths",
+        );
+
+        if let Some(native) = self.as_native_slice() {
+            out.copy_from_slice(native);
+            return;
+        }
+
+        let mut src = self.as_slice().chunks_exact(CHUNK_LEN);
+        let mut dst = out.chunks_exact_mut(CHUNK_LEN);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            for (from, to) in src.iter().zip(dst.iter_mut()) {
+                *to = from.to_native();
+            }
+        }
+        for (from, to) in src.remainder().iter().zip(dst.into_remainder()) {
+            *to = from.to_native();
+        }
+    }
+
+    /// Converts the elements of the archived vec into a `Vec` of native
+    /// values.
+    ///
+    /// See [`copy_to_native`](ArchivedVec::copy_to_native) for more
+    /// information.
+    #[cfg(feature = "alloc")]
+    pub fn to_native_vec(&self) -> Vec<T::Native>
+    where
+        T::Native: Copy,
+    {
+        match self.as_native_slice() {
+            Some(native) => native.to_vec(),
+            None => self.iter().map(NativeLayout::to_native).collect(),
+        }
+    }
+}
+
+impl<F: Format> ArchivedVec<u8, F> {
+    /// Returns whether the bytes contain the given pattern.
+    ///
+    /// With the `memchr` feature enabled, this search is accelerated with
+    /// SIMD.
+    #[inline]
+    pub fn contains<'n>(&self, pattern: impl Needle<'n>) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    /// Returns the index of the first occurrence of the given pattern in the
+    /// bytes.
+    ///
+    /// With the `memchr` feature enabled, this search is accelerated with
+    /// SIMD.
+    #[inline]
+    pub fn find<'n>(&self, pattern: impl Needle<'n>) -> Option<usize> {
+        search::find(self.as_slice(), pattern.into_needle().as_bytes())
+    }
+
+    /// Returns an iterator over the subslices of the bytes which are
+    /// separated by the given pattern.
+    ///
+    /// See [`Split`] for more information.
+    #[inline]
+    pub fn split<'a, 'n>(&'a self, pattern: impl Needle<'n>) -> Split<'a, 'n> {
+        Split::new(self.as_slice(), pattern)
+    }
+
+    /// Returns whether the bytes start with the given pattern.
+    #[inline]
+    pub fn starts_with<'n>(&self, pattern: impl Needle<'n>) -> bool {
+        self.as_slice()
+            .starts_with(pattern.into_needle().as_bytes())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Analyze, F: Format> Analyze for ArchivedVec<T, F> {
+    #[inline]
+    fn analyze(&self, report: &mut TypeSizeReport) {
+        report.record_out_of_line(self.as_slice());
+    }
+}
+
+impl<T: Redact, F: Format> Redact for ArchivedVec<T, F> {
+    #[inline]
+    fn redact(self: Pin<&mut Self>) {
+        self.pin_mut_slice().redact();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Export, F: Format> Export for ArchivedVec<T, F> {
+    #[inline]
+    fn export<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.as_slice().export(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Export, F: Format> serde::Serialize for ArchivedVec<T, F> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.export(serializer)
+    }
+}
+
+impl<T, F: Format> AsRef<[T]> for ArchivedVec<T, F> {
     #[inline]
     fn as_ref(&self) -> &[T] {
         self.as_slice()
     }
 }
 
-impl<T> Borrow<[T]> for ArchivedVec<T> {
+impl<T, F: Format> Borrow<[T]> for ArchivedVec<T, F> {
     #[inline]
     fn borrow(&self) -> &[T] {
         self.as_slice()
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for ArchivedVec<T> {
+impl<T: fmt::Debug, F: Format> fmt::Debug for ArchivedVec<T, F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.as_slice()).finish()
     }
 }
 
-impl<T> Deref for ArchivedVec<T> {
+impl<T, F: Format> Deref for ArchivedVec<T, F> {
     type Target = [T];
 
     #[inline]
@@ -228,16 +554,37 @@ impl<T> Deref for ArchivedVec<T> {
     }
 }
 
-impl<T: Eq> Eq for ArchivedVec<T> {}
+impl<T: Eq, F: Format> Eq for ArchivedVec<T, F> {}
+
+impl<T, F, S> Extract<S> for ArchivedVec<T, F>
+where
+    T: Extract<S>,
+    F: Format,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn extract(&self, serializer: &mut S) -> Result<VecResolver, S::Error> {
+        Ok(VecResolver {
+            pos: self.as_slice().extract_unsized(serializer)?,
+        })
+    }
+
+    #[inline]
+    fn resolve_extracted(&self, resolver: VecResolver, out: Place<Self>) {
+        Self::resolve_from_len(self.len(), resolver, out);
+    }
+}
 
-impl<T: hash::Hash> hash::Hash for ArchivedVec<T> {
+impl<T: hash::Hash, F: Format> hash::Hash for ArchivedVec<T, F> {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
         self.as_slice().hash(state)
     }
 }
 
-impl<T, I: SliceIndex<[T]>> Index<I> for ArchivedVec<T> {
+impl<T, F: Format, I: SliceIndex<[T]>> Index<I> for ArchivedVec<T, F> {
     type Output = <[T] as Index<I>>::Output;
 
     #[inline]
@@ -246,69 +593,124 @@ impl<T, I: SliceIndex<[T]>> Index<I> for ArchivedVec<T> {
     }
 }
 
-impl<T: Ord> Ord for ArchivedVec<T> {
+impl<T: Ord, F: Format> Ord for ArchivedVec<T, F> {
     #[inline]
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         self.as_slice().cmp(other.as_slice())
     }
 }
 
-impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for ArchivedVec<T> {
+impl<T, U, F, G> PartialEq<ArchivedVec<U, G>> for ArchivedVec<T, F>
+where
+    T: PartialEq<U>,
+    F: Format,
+    G: Format,
+{
     #[inline]
-    fn eq(&self, other: &ArchivedVec<U>) -> bool {
+    fn eq(&self, other: &ArchivedVec<U, G>) -> bool {
         self.as_slice().eq(other.as_slice())
     }
 }
 
-impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for ArchivedVec<T> {
+impl<T, U, F, const N: usize> PartialEq<[U; N]> for ArchivedVec<T, F>
+where
+    T: PartialEq<U>,
+    F: Format,
+{
     #[inline]
     fn eq(&self, other: &[U; N]) -> bool {
         self.as_slice().eq(&other[..])
     }
 }
 
-impl<T: PartialEq<U>, U, const N: usize> PartialEq<ArchivedVec<T>> for [U; N] {
+impl<T, U, F, const N: usize> PartialEq<ArchivedVec<T, F>> for [U; N]
+where
+    T: PartialEq<U>,
+    F: Format,
+{
     #[inline]
-    fn eq(&self, other: &ArchivedVec<T>) -> bool {
+    fn eq(&self, other: &ArchivedVec<T, F>) -> bool {
         other.eq(self)
     }
 }
 
-impl<T: PartialEq<U>, U> PartialEq<[U]> for ArchivedVec<T> {
+impl<T: PartialEq<U>, U, F: Format> PartialEq<[U]> for ArchivedVec<T, F> {
     #[inline]
     fn eq(&self, other: &[U]) -> bool {
         self.as_slice().eq(other)
     }
 }
 
-impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for [T] {
+impl<T: PartialEq<U>, U, F: Format> PartialEq<ArchivedVec<U, F>> for [T] {
     #[inline]
-    fn eq(&self, other: &ArchivedVec<U>) -> bool {
+    fn eq(&self, other: &ArchivedVec<U, F>) -> bool {
         self.eq(other.as_slice())
     }
 }
 
-impl<T: PartialOrd> PartialOrd<ArchivedVec<T>> for ArchivedVec<T> {
+impl<T, F, G> PartialOrd<ArchivedVec<T, G>> for ArchivedVec<T, F>
+where
+    T: PartialOrd,
+    F: Format,
+    G: Format,
+{
     #[inline]
-    fn partial_cmp(&self, other: &ArchivedVec<T>) -> Option<cmp::Ordering> {
+    fn partial_cmp(&self, other: &ArchivedVec<T, G>) -> Option<cmp::Ordering> {
         self.as_slice().partial_cmp(other.as_slice())
     }
 }
 
-impl<T: PartialOrd> PartialOrd<[T]> for ArchivedVec<T> {
+impl<T: PartialOrd, F: Format> PartialOrd<[T]> for ArchivedVec<T, F> {
     #[inline]
     fn partial_cmp(&self, other: &[T]) -> Option<cmp::Ordering> {
         self.as_slice().partial_cmp(other)
     }
 }
 
-impl<T: PartialOrd> PartialOrd<ArchivedVec<T>> for [T] {
+impl<T: PartialOrd, F: Format> PartialOrd<ArchivedVec<T, F>> for [T] {
     #[inline]
-    fn partial_cmp(&self, other: &ArchivedVec<T>) -> Option<cmp::Ordering> {
+    fn partial_cmp(&self, other: &ArchivedVec<T, F>) -> Option<cmp::Ordering> {
         self.partial_cmp(other.as_slice())
     }
 }
 
+/// An iterator over pinned mutable references to the elements of an
+/// [`ArchivedVec`].
+///
+/// This is returned by [`ArchivedVec::iter_seal`].
+pub struct IterSeal<'a, T> {
+    inner: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterSeal<'a, T> {
+    type Item = Pin<&'a mut T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|value| unsafe { Pin::new_unchecked(value) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterSeal<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|value| unsafe { Pin::new_unchecked(value) })
+    }
+}
+
+impl<T> ExactSizeIterator for IterSeal<'_, T> {}
+
+impl<T> FusedIterator for IterSeal<'_, T> {}
+
 /// The resolver for [`ArchivedVec`].
 pub struct VecResolver {
     pos: usize,
@@ -325,33 +727,62 @@ impl VecResolver {
 #[cfg(feature = "bytecheck")]
 mod verify {
     use bytecheck::{
-        rancor::{Fallible, Source},
+        rancor::{Fallible, Source, Trace as _},
         CheckBytes, Verify,
     };
 
     use crate::{
-        validation::{ArchiveContext, ArchiveContextExt},
+        format::{Format, Primitive as _},
+        validation::{path::PathSegment, ArchiveContext, ArchiveContextExt},
         vec::ArchivedVec,
     };
 
-    unsafe impl<T, C> Verify<C> for ArchivedVec<T>
+    impl<T, F: Format> ArchivedVec<T, F> {
+        /// Checks that the elements of the vec are located within the subtree
+        /// range of the context and returns a pointer to them.
+        ///
+        /// # Safety
+        ///
+        /// This vec must be located inside the archive that the context was
+        /// created for.
+        pub(crate) unsafe fn bounds_check_elements<C, E>(
+            &self,
+            context: &mut C,
+        ) -> Result<*const [T], E>
+        where
+            C: ArchiveContext<E> + ?Sized,
+            E: Source,
+        {
+            unsafe {
+                context.bounds_check_subtree_base_offset::<[T]>(
+                    self.ptr.base(),
+                    self.ptr.offset(),
+                    self.len.to_native(),
+                )
+            }
+        }
+    }
+
+    unsafe impl<T, F, C> Verify<C> for ArchivedVec<T, F>
     where
         T: CheckBytes<C>,
+        F: Format,
         C: Fallible + ArchiveContext + ?Sized,
         C::Error: Source,
     {
         fn verify(&self, context: &mut C) -> Result<(), C::Error> {
-            let ptr = unsafe {
-                context.bounds_check_subtree_base_offset::<[T]>(
-                    self.ptr.base(),
-                    self.ptr.offset(),
-                    self.len.to_native() as usize,
-                )?
-            };
+            let ptr = unsafe { self.bounds_check_elements(context)? };
 
             let range = unsafe { context.push_prefix_subtree(ptr)? };
-            unsafe {
-                <[T]>::check_bytes(ptr, context)?;
+            if context.should_check_subtree() {
+                let first = ptr as *const T;
+                for i in 0..self.len() {
+                    // SAFETY: The elements were bounds checked above.
+                    unsafe {
+                        T::check_bytes(first.add(i), context)
+                            .map_err(|e| e.trace(PathSegment::Index(i)))?;
+                    }
+                }
             }
             unsafe {
                 context.pop_subtree_range(range)?;
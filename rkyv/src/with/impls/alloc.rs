@@ -4,10 +4,11 @@ use alloc::{
     boxed::Box,
     collections::{BTreeMap, BTreeSet},
     rc::Rc,
+    string::String,
     sync::Arc,
     vec::Vec,
 };
-use core::marker::PhantomData;
+use core::{alloc::Layout, marker::PhantomData, mem::size_of};
 #[cfg(feature = "std")]
 use std::{
     borrow::Cow,
@@ -16,18 +17,34 @@ use std::{
     sync::Arc,
 };
 
+use munge::munge;
 use ptr_meta::Pointee;
-use rancor::{Fallible, Source};
+use rancor::{Fallible, ResultExt as _, Source, Strategy};
 
+#[cfg(feature = "bytecheck")]
+use crate::validation::validators::DefaultValidator;
 use crate::{
-    collections::util::{Entry, EntryAdapter},
+    boxed::ArchivedBox,
+    collections::{
+        btree_map::ArchivedBTreeMap,
+        btree_set::ArchivedBTreeSet,
+        sorted_vec::ArchivedSortedVec,
+        util::{Entry, EntryAdapter},
+    },
+    de::Metering,
+    format::Format,
+    nested::{ArchivedNested, NestedResolver},
     niche::option_box::{ArchivedOptionBox, OptionBoxResolver},
-    ser::{Allocator, Writer},
-    string::{ArchivedString, StringResolver},
+    segment::{FarPtr, FarResolver, Segmenting, Segments},
+    ser::{AllocSerializer, Allocator, Writer},
+    string::{
+        encoded::{ArchivedEncodedString, Encoding},
+        ArchivedString, StringResolver,
+    },
     vec::{ArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsOwned, AsVec, Cloned, DeserializeWith, Map, Niche,
-        SerializeWith,
+        ArchiveWith, AsOwned, AsSortedSlice, AsVec, Cloned, DeserializeWith,
+        Encoded, Far, InFormat, Map, Metered, Nested, Niche, SerializeWith,
     },
     Archive, ArchiveUnsized, ArchivedMetadata, Deserialize, DeserializeUnsized,
     LayoutRaw, Place, Serialize, SerializeUnsized,
@@ -351,6 +368,54 @@ where
     }
 }
 
+// InFormat
+
+impl<T: Archive, F: Format> ArchiveWith<Vec<T>> for InFormat<F> {
+    type Archived = ArchivedVec<T::Archived, F>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &Vec<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedVec::resolve_from_slice(field.as_slice(), resolver, out);
+    }
+}
+
+impl<T, F, S> SerializeWith<Vec<T>, S> for InFormat<F>
+where
+    T: Serialize<S>,
+    F: Format,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(
+            field.as_slice(),
+            serializer,
+        )
+    }
+}
+
+impl<T, F, D> DeserializeWith<ArchivedVec<T::Archived, F>, Vec<T>, D>
+    for InFormat<F>
+where
+    T: Archive,
+    F: Format,
+    ArchivedVec<T::Archived, F>: Deserialize<Vec<T>, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<T::Archived, F>,
+        deserializer: &mut D,
+    ) -> Result<Vec<T>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
 // Niche
 
 impl<T: ArchiveUnsized + ?Sized> ArchiveWith<Option<Box<T>>> for Niche
@@ -466,3 +531,376 @@ impl<A: Deserialize<T, D>, T, D: Fallible + ?Sized> DeserializeWith<A, Rc<T>, D>
         Ok(Rc::new(A::deserialize(x, d)?))
     }
 }
+
+// Nested
+
+impl<T: Archive> ArchiveWith<T> for Nested {
+    type Archived = ArchivedNested<T>;
+    type Resolver = NestedResolver;
+
+    #[inline]
+    fn resolve_with(
+        _: &T,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedNested::resolve_from_resolver(resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<T, S> for Nested
+where
+    T: Archive + Serialize<Strategy<AllocSerializer, S::Error>>,
+    S: Fallible + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &T,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let bytes = crate::to_bytes::<S::Error>(field)?;
+        ArchivedNested::<T>::serialize_from_bytes(&bytes, serializer)
+    }
+}
+
+// Validating the outer archive does not validate the nested archive, so it has
+// to be validated before it can be deserialized.
+#[cfg(feature = "bytecheck")]
+impl<T, D> DeserializeWith<ArchivedNested<T>, T, D> for Nested
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>
+        + bytecheck::CheckBytes<Strategy<DefaultValidator, D::Error>>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedNested<T>,
+        deserializer: &mut D,
+    ) -> Result<T, D::Error> {
+        let archived = field.access_inner::<D::Error>()?;
+        archived.deserialize(deserializer)
+    }
+}
+
+// AsSortedSlice
+
+impl<T: Archive> ArchiveWith<Vec<T>> for AsSortedSlice {
+    type Archived = ArchivedSortedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &Vec<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        munge!(let ArchivedSortedVec { inner } = out);
+        ArchivedVec::resolve_from_len(field.len(), resolver, inner);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<T>, S> for AsSortedSlice
+where
+    T: Ord + Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        if field.windows(2).all(|w| w[0] <= w[1]) {
+            ArchivedVec::serialize_from_slice(field.as_slice(), serializer)
+        } else {
+            let mut sorted = field.iter().collect::<Vec<_>>();
+            sorted.sort();
+            ArchivedVec::<T::Archived>::serialize_from_iter::<T, _, _>(
+                sorted.iter().copied(),
+                serializer,
+            )
+        }
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedSortedVec<T::Archived>, Vec<T>, D>
+    for AsSortedSlice
+where
+    T: Archive,
+    ArchivedVec<T::Archived>: Deserialize<Vec<T>, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Vec<T>, D::Error> {
+        field.inner.deserialize(deserializer)
+    }
+}
+
+impl<T: Archive> ArchiveWith<BTreeSet<T>> for AsSortedSlice {
+    type Archived = ArchivedSortedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &BTreeSet<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        munge!(let ArchivedSortedVec { inner } = out);
+        ArchivedVec::resolve_from_len(field.len(), resolver, inner);
+    }
+}
+
+impl<T, S> SerializeWith<BTreeSet<T>, S> for AsSortedSlice
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &BTreeSet<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        AsVec::serialize_with(field, serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedSortedVec<T::Archived>, BTreeSet<T>, D>
+    for AsSortedSlice
+where
+    T: Archive + Ord,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedSortedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeSet<T>, D::Error> {
+        AsVec::deserialize_with(&field.inner, deserializer)
+    }
+}
+
+// Encoded
+
+impl<E: Encoding> ArchiveWith<Vec<u8>> for Encoded<E> {
+    type Archived = ArchivedEncodedString<E>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &Vec<u8>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedEncodedString::resolve_from_bytes(field, resolver, out);
+    }
+}
+
+impl<E, S> SerializeWith<Vec<u8>, S> for Encoded<E>
+where
+    E: Encoding,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    #[inline]
+    fn serialize_with(
+        field: &Vec<u8>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedEncodedString::<E>::serialize_from_bytes(field, serializer)
+    }
+}
+
+impl<E, D> DeserializeWith<ArchivedEncodedString<E>, Vec<u8>, D> for Encoded<E>
+where
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedEncodedString<E>,
+        _: &mut D,
+    ) -> Result<Vec<u8>, D::Error> {
+        Ok(field.as_bytes().to_vec())
+    }
+}
+
+// Far
+
+impl<T: Archive> ArchiveWith<T> for Far {
+    type Archived = FarPtr<T::Archived>;
+    type Resolver = FarResolver;
+
+    #[inline]
+    fn resolve_with(
+        _: &T,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        FarPtr::resolve_from_resolver(resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<T, S> for Far
+where
+    T: Archive + Serialize<Strategy<AllocSerializer, S::Error>>,
+    S: Fallible + Segmenting + ?Sized,
+{
+    fn serialize_with(
+        field: &T,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let bytes = crate::to_bytes::<S::Error>(field)?;
+        let pos = bytes.len().saturating_sub(size_of::<T::Archived>());
+        let segment = serializer.push_segment(bytes)?;
+        Ok(FarResolver::new(segment, pos))
+    }
+}
+
+// Segments are loaded independently of the archive that points into them, so
+// they have to be validated before they can be deserialized.
+#[cfg(feature = "bytecheck")]
+impl<'a, T, D> DeserializeWith<FarPtr<T::Archived>, T, D> for Far
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>
+        + bytecheck::CheckBytes<Strategy<DefaultValidator, D::Error>>,
+    D: Fallible + Segments<'a> + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &FarPtr<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<T, D::Error> {
+        let archived = field.get::<D, D::Error>(deserializer)?;
+        archived.deserialize(deserializer)
+    }
+}
+
+// Metered
+
+impl<T: Archive> ArchiveWith<T> for Metered {
+    type Archived = T::Archived;
+    type Resolver = T::Resolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &T,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<T: Serialize<S>, S: Fallible + ?Sized> SerializeWith<T, S> for Metered {
+    #[inline]
+    fn serialize_with(
+        field: &T,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+/// Meters an allocation for `len` values of `T`. Empty allocations are not
+/// made, so they are not metered.
+pub(super) fn meter_array<T, D>(
+    len: usize,
+    deserializer: &mut D,
+) -> Result<(), D::Error>
+where
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    let layout = Layout::array::<T>(len).into_error()?;
+    if layout.size() > 0 {
+        deserializer.meter(layout)?;
+    }
+    Ok(())
+}
+
+impl<T, D> DeserializeWith<ArchivedVec<T::Archived>, Vec<T>, D> for Metered
+where
+    T: Archive,
+    ArchivedVec<T::Archived>: Deserialize<Vec<T>, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Vec<T>, D::Error> {
+        meter_array::<T, D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
+
+impl<D> DeserializeWith<ArchivedString, String, D> for Metered
+where
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedString,
+        deserializer: &mut D,
+    ) -> Result<String, D::Error> {
+        meter_array::<u8, D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedBox<T::Archived>, Box<T>, D> for Metered
+where
+    T: ArchiveUnsized + LayoutRaw + ?Sized,
+    T::Archived: DeserializeUnsized<T, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedBox<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Box<T>, D::Error> {
+        let metadata = field.get().deserialize_metadata(deserializer)?;
+        let layout = T::layout_raw(metadata).into_error()?;
+        if layout.size() > 0 {
+            deserializer.meter(layout)?;
+        }
+        field.deserialize(deserializer)
+    }
+}
+
+impl<K, V, D>
+    DeserializeWith<
+        ArchivedBTreeMap<K::Archived, V::Archived>,
+        BTreeMap<K, V>,
+        D,
+    > for Metered
+where
+    K: Archive,
+    V: Archive,
+    ArchivedBTreeMap<K::Archived, V::Archived>: Deserialize<BTreeMap<K, V>, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedBTreeMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeMap<K, V>, D::Error> {
+        meter_array::<(K, V), D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
+
+impl<K, D> DeserializeWith<ArchivedBTreeSet<K::Archived>, BTreeSet<K>, D>
+    for Metered
+where
+    K: Archive,
+    ArchivedBTreeSet<K::Archived>: Deserialize<BTreeSet<K>, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedBTreeSet<K::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeSet<K>, D::Error> {
+        meter_array::<K, D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
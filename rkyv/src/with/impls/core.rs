@@ -9,6 +9,7 @@ use rancor::Fallible;
 
 use crate::{
     boxed::{ArchivedBox, BoxResolver},
+    format::{Format, Primitive},
     niche::option_nonzero::{
         ArchivedOptionNonZeroIsize, ArchivedOptionNonZeroUsize,
     },
@@ -16,8 +17,9 @@ use crate::{
     place::Initialized,
     primitive::{FixedNonZeroIsize, FixedNonZeroUsize},
     with::{
-        ArchiveWith, Boxed, BoxedInline, DeserializeWith, Inline, Map, Niche,
-        SerializeWith, Skip, Unsafe,
+        ArchiveFrom, ArchiveWith, Boxed, BoxedInline, DeserializeWith,
+        InFormat, Inline, Map, Niche, SerializeFrom, SerializeWith, Skip,
+        Substitute, Unsafe,
     },
     Archive, ArchiveUnsized, Deserialize, Place, Serialize, SerializeUnsized,
 };
@@ -122,6 +124,48 @@ struct ArchivedOptionVariantNone(ArchivedOptionTag);
 #[repr(C)]
 struct ArchivedOptionVariantSome<T>(ArchivedOptionTag, T);
 
+// Substitute
+
+impl<F, A, R> ArchiveWith<F> for Substitute<A, R>
+where
+    F: ?Sized,
+    A: ArchiveFrom<F, Resolver = R>,
+{
+    type Archived = A;
+    type Resolver = R;
+
+    #[inline]
+    fn resolve_with(field: &F, resolver: R, out: Place<A>) {
+        A::resolve_from(field, resolver, out);
+    }
+}
+
+impl<F, A, R, S> SerializeWith<F, S> for Substitute<A, R>
+where
+    F: ?Sized,
+    A: SerializeFrom<F, S, Resolver = R>,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize_with(field: &F, serializer: &mut S) -> Result<R, S::Error> {
+        A::serialize_from(field, serializer)
+    }
+}
+
+impl<F, A, R, D> DeserializeWith<A, F, D> for Substitute<A, R>
+where
+    A: Deserialize<F, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &A,
+        deserializer: &mut D,
+    ) -> Result<F, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
 // Inline
 
 impl<F: Archive> ArchiveWith<&F> for Inline {
@@ -304,6 +348,61 @@ impl<D: Fallible + ?Sized>
     }
 }
 
+// InFormat
+
+macro_rules! impl_in_format {
+    ($($ty:ty => $archived:ident),* $(,)?) => {
+        $(
+            impl<F: Format> ArchiveWith<$ty> for InFormat<F> {
+                type Archived = F::$archived;
+                type Resolver = ();
+
+                fn resolve_with(
+                    field: &$ty,
+                    _: Self::Resolver,
+                    out: Place<Self::Archived>,
+                ) {
+                    out.write(Primitive::<$ty>::from_native(*field));
+                }
+            }
+
+            impl<F, S> SerializeWith<$ty, S> for InFormat<F>
+            where
+                F: Format,
+                S: Fallible + ?Sized,
+            {
+                fn serialize_with(_: &$ty, _: &mut S) -> Result<(), S::Error> {
+                    Ok(())
+                }
+            }
+
+            impl<F, D> DeserializeWith<F::$archived, $ty, D> for InFormat<F>
+            where
+                F: Format,
+                D: Fallible + ?Sized,
+            {
+                fn deserialize_with(
+                    field: &F::$archived,
+                    _: &mut D,
+                ) -> Result<$ty, D::Error> {
+                    Ok(Primitive::<$ty>::to_native(field))
+                }
+            }
+        )*
+    };
+}
+
+impl_in_format! {
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    isize => Isize,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    usize => Usize,
+}
+
 // Unsafe
 
 impl<F: Archive> ArchiveWith<UnsafeCell<F>> for Unsafe {
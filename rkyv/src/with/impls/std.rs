@@ -1,3 +1,7 @@
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt as _, OsStringExt as _};
+#[cfg(windows)]
+use std::os::windows::ffi::{OsStrExt as _, OsStringExt as _};
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
@@ -11,16 +15,29 @@ use std::{
 
 use rancor::{Fallible, OptionExt, ResultExt, Source};
 
+#[cfg(unix)]
+use crate::string::encoded::Bytes;
+#[cfg(windows)]
+use crate::string::encoded::{wtf8_from_wide, Wtf8};
 use crate::{
-    collections::util::{Entry, EntryAdapter},
+    collections::{
+        swiss_table::{
+            map::{ArchivedHashMap, HashMapResolver},
+            set::{ArchivedHashSet, HashSetResolver},
+        },
+        util::{Entry, EntryAdapter},
+    },
+    de::Metering,
     ffi::{ArchivedCString, CStringResolver},
+    hash::{HasherConfig, SeededHasher},
     ser::{Allocator, Writer},
-    string::{ArchivedString, StringResolver},
+    string::{encoded::ArchivedEncodedString, ArchivedString, StringResolver},
     time::ArchivedDuration,
     vec::{ArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsOwned, AsString, AsVec, DeserializeWith, Immutable,
-        InvalidStr, Lock, Poisoned, SerializeWith, UnixTimestamp,
+        ArchiveWith, AsOwned, AsString, AsVec, DeserializeWith, Encoded,
+        Immutable, InvalidStr, Lock, Metered, Poisoned, SerializeWith,
+        UnixTimestamp, WithHasher,
     },
     Archive, Deserialize, Place, Serialize, SerializeUnsized,
 };
@@ -76,6 +93,110 @@ impl<D: Fallible + ?Sized> DeserializeWith<ArchivedString, OsString, D>
     }
 }
 
+// Encoded
+
+#[cfg(unix)]
+impl ArchiveWith<OsString> for Encoded<Bytes> {
+    type Archived = ArchivedEncodedString<Bytes>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &OsString,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedEncodedString::resolve_from_bytes(
+            field.as_bytes(),
+            resolver,
+            out,
+        );
+    }
+}
+
+#[cfg(unix)]
+impl<S> SerializeWith<OsString, S> for Encoded<Bytes>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    #[inline]
+    fn serialize_with(
+        field: &OsString,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedEncodedString::<Bytes>::serialize_from_bytes(
+            field.as_bytes(),
+            serializer,
+        )
+    }
+}
+
+#[cfg(unix)]
+impl<D: Fallible + ?Sized>
+    DeserializeWith<ArchivedEncodedString<Bytes>, OsString, D>
+    for Encoded<Bytes>
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedEncodedString<Bytes>,
+        _: &mut D,
+    ) -> Result<OsString, D::Error> {
+        Ok(OsString::from_vec(field.as_bytes().to_vec()))
+    }
+}
+
+#[cfg(windows)]
+impl ArchiveWith<OsString> for Encoded<Wtf8> {
+    type Archived = ArchivedEncodedString<Wtf8>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &OsString,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        let len = char::decode_utf16(field.encode_wide())
+            .map(|c| c.map_or(3, char::len_utf8))
+            .sum();
+        ArchivedEncodedString::resolve_from_len(len, resolver, out);
+    }
+}
+
+#[cfg(windows)]
+impl<S> SerializeWith<OsString, S> for Encoded<Wtf8>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    #[inline]
+    fn serialize_with(
+        field: &OsString,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedEncodedString::<Wtf8>::serialize_from_bytes(
+            &wtf8_from_wide(field.encode_wide()),
+            serializer,
+        )
+    }
+}
+
+#[cfg(windows)]
+impl<D: Fallible + ?Sized>
+    DeserializeWith<ArchivedEncodedString<Wtf8>, OsString, D>
+    for Encoded<Wtf8>
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedEncodedString<Wtf8>,
+        _: &mut D,
+    ) -> Result<OsString, D::Error> {
+        let wide = field.encode_wide().collect::<Vec<_>>();
+        Ok(OsString::from_wide(&wide))
+    }
+}
+
 impl ArchiveWith<PathBuf> for AsString {
     type Archived = ArchivedString;
     type Resolver = StringResolver;
@@ -350,6 +471,133 @@ where
     }
 }
 
+// WithHasher
+
+impl<K, V, RS, H, const SEED: u64> ArchiveWith<HashMap<K, V, RS>>
+    for WithHasher<H, SEED>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedHashMap::resolve_from_len(field.len(), (7, 8), resolver, out);
+    }
+}
+
+impl<K, V, RS, H, S, const SEED: u64> SerializeWith<HashMap<K, V, RS>, S>
+    for WithHasher<H, SEED>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    V: Serialize<S>,
+    H: SeededHasher,
+    S: Fallible + Writer + Allocator + ?Sized,
+    S::Error: Source,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashMap::<_, _>::serialize_from_iter_with_hasher(
+            field.iter(),
+            (7, 8),
+            HasherConfig::new(H::ALGORITHM, SEED),
+            serializer,
+        )
+    }
+}
+
+impl<K, V, RS, H, D, const SEED: u64>
+    DeserializeWith<
+        ArchivedHashMap<K::Archived, V::Archived>,
+        HashMap<K, V, RS>,
+        D,
+    > for WithHasher<H, SEED>
+where
+    K: Archive,
+    V: Archive,
+    ArchivedHashMap<K::Archived, V::Archived>:
+        Deserialize<HashMap<K, V, RS>, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+impl<K, RS, H, const SEED: u64> ArchiveWith<HashSet<K, RS>>
+    for WithHasher<H, SEED>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Hash + Eq,
+{
+    type Archived = ArchivedHashSet<K::Archived>;
+    type Resolver = HashSetResolver;
+
+    #[inline]
+    fn resolve_with(
+        field: &HashSet<K, RS>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedHashSet::resolve_from_len(field.len(), (7, 8), resolver, out);
+    }
+}
+
+impl<K, RS, H, S, const SEED: u64> SerializeWith<HashSet<K, RS>, S>
+    for WithHasher<H, SEED>
+where
+    K: Serialize<S> + Hash + Eq,
+    K::Archived: Hash + Eq,
+    H: SeededHasher,
+    S: Fallible + Writer + Allocator + ?Sized,
+    S::Error: Source,
+{
+    #[inline]
+    fn serialize_with(
+        field: &HashSet<K, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashSet::<K::Archived>::serialize_from_iter_with_hasher(
+            field.iter(),
+            (7, 8),
+            HasherConfig::new(H::ALGORITHM, SEED),
+            serializer,
+        )
+    }
+}
+
+impl<K, RS, H, D, const SEED: u64>
+    DeserializeWith<ArchivedHashSet<K::Archived>, HashSet<K, RS>, D>
+    for WithHasher<H, SEED>
+where
+    K: Archive,
+    ArchivedHashSet<K::Archived>: Deserialize<HashSet<K, RS>, D>,
+    D: Fallible + ?Sized,
+{
+    #[inline]
+    fn deserialize_with(
+        field: &ArchivedHashSet<K::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashSet<K, RS>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
 // UnixTimestamp
 
 impl ArchiveWith<SystemTime> for UnixTimestamp {
@@ -434,3 +682,44 @@ where
         Ok(Cow::Owned(field.deserialize(deserializer)?))
     }
 }
+
+// Metered
+
+impl<K, V, S, D>
+    DeserializeWith<
+        ArchivedHashMap<K::Archived, V::Archived>,
+        HashMap<K, V, S>,
+        D,
+    > for Metered
+where
+    K: Archive,
+    V: Archive,
+    ArchivedHashMap<K::Archived, V::Archived>: Deserialize<HashMap<K, V, S>, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, S>, D::Error> {
+        super::alloc::meter_array::<(K, V), D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
+
+impl<K, S, D> DeserializeWith<ArchivedHashSet<K::Archived>, HashSet<K, S>, D>
+    for Metered
+where
+    K: Archive,
+    ArchivedHashSet<K::Archived>: Deserialize<HashSet<K, S>, D>,
+    D: Fallible + Metering + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedHashSet<K::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashSet<K, S>, D::Error> {
+        super::alloc::meter_array::<K, D>(field.len(), deserializer)?;
+        field.deserialize(deserializer)
+    }
+}
@@ -131,6 +131,107 @@ pub trait DeserializeWith<F: ?Sized, T, D: Fallible + ?Sized> {
         -> Result<T, D::Error>;
 }
 
+/// An archived type which can be resolved from a field of type `F`.
+///
+/// Implementing this trait lets a field be archived as `Self` with
+/// `#[archive(archived = "...", resolver = "...")]`, without writing a
+/// wrapper or implementing the traits for the whole struct manually. The
+/// resolver may be omitted from the attribute, in which case it defaults to
+/// [`ArchiveFrom::Resolver`]. The archived type must also implement
+/// [`SerializeFrom`] to be serialized and [`Deserialize`](crate::Deserialize)
+/// to be deserialized.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, deserialize,
+///     munge::munge,
+///     primitive::ArchivedI32,
+///     rancor::{Error, Fallible},
+///     to_bytes,
+///     with::{ArchiveFrom, SerializeFrom},
+///     Archive, Archived, Deserialize, Place, Portable, Serialize,
+/// };
+///
+/// // Archives a temperature as a whole number of hundredths of a degree.
+/// #[derive(Portable)]
+/// #[repr(transparent)]
+/// struct ArchivedCentis(ArchivedI32);
+///
+/// impl ArchiveFrom<f64> for ArchivedCentis {
+///     type Resolver = ();
+///
+///     fn resolve_from(field: &f64, _: (), out: Place<Self>) {
+///         munge!(let ArchivedCentis(centis) = out);
+///         centis.write(ArchivedI32::from_native((field * 100.0) as i32));
+///     }
+/// }
+///
+/// impl<S: Fallible + ?Sized> SerializeFrom<f64, S> for ArchivedCentis {
+///     fn serialize_from(_: &f64, _: &mut S) -> Result<(), S::Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl<D: Fallible + ?Sized> Deserialize<f64, D> for ArchivedCentis {
+///     fn deserialize(&self, _: &mut D) -> Result<f64, D::Error> {
+///         Ok(self.0.to_native() as f64 / 100.0)
+///     }
+/// }
+///
+/// #[derive(Archive, Deserialize, Serialize)]
+/// struct Reading {
+///     #[archive(archived = "ArchivedCentis", resolver = "()")]
+///     temperature: f64,
+/// }
+///
+/// let value = Reading { temperature: 21.5 };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+///
+/// let archived = unsafe { access_unchecked::<Archived<Reading>>(&bytes) };
+/// assert_eq!(archived.temperature.0.to_native(), 2150);
+///
+/// let deserialized =
+///     deserialize::<Reading, _, Error>(archived, &mut ()).unwrap();
+/// assert_eq!(deserialized.temperature, 21.5);
+/// ```
+pub trait ArchiveFrom<F: ?Sized>: Portable {
+    /// The resolver of `Self` when it is archived from `F`.
+    type Resolver;
+
+    /// Resolves `Self` using a reference to the field type `F`.
+    fn resolve_from(field: &F, resolver: Self::Resolver, out: Place<Self>);
+}
+
+/// A variant of `Serialize` for archived types which are resolved from a
+/// field.
+pub trait SerializeFrom<F: ?Sized, S: Fallible + ?Sized>:
+    ArchiveFrom<F>
+{
+    /// Serializes the field type `F` using the given serializer.
+    fn serialize_from(
+        field: &F,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error>;
+}
+
+/// A wrapper which archives a field as the archived type `A` with the
+/// resolver `R`.
+///
+/// This is the wrapper used for fields with
+/// `#[archive(archived = "...", resolver = "...")]`. See [`ArchiveFrom`] for
+/// more information.
+pub struct Substitute<A, R> {
+    _phantom: PhantomData<fn() -> (A, R)>,
+}
+
+impl<A, R> fmt::Debug for Substitute<A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Substitute").finish()
+    }
+}
+
 /// A wrapper to make a type immutable.
 #[derive(Debug, Portable)]
 #[archive(crate)]
@@ -356,6 +457,45 @@ impl fmt::Display for InvalidStr {
 #[cfg(feature = "std")]
 impl ::std::error::Error for InvalidStr {}
 
+/// A wrapper that archives bytes as a string with the given encoding.
+///
+/// The bytes are checked against the [`Encoding`] `E` when they are serialized
+/// and when the archive is validated. Any `Vec<u8>` can be archived with this
+/// wrapper. With the `std` feature enabled, `OsString`s can be archived with
+/// `Encoded<Bytes>` on Unix and `Encoded<Wtf8>` on Windows, which preserves
+/// strings that aren't valid UTF-8.
+///
+/// See [`encoded`](crate::string::encoded) for the available encodings.
+///
+/// [`Encoding`]: crate::string::encoded::Encoding
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     string::encoded::{Bytes, Utf8},
+///     with::Encoded,
+///     Archive,
+/// };
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(Encoded<Utf8>)]
+///     name: Vec<u8>,
+///     #[with(Encoded<Bytes>)]
+///     tag: Vec<u8>,
+/// }
+/// ```
+pub struct Encoded<E> {
+    _encoding: PhantomData<E>,
+}
+
+impl<E> fmt::Debug for Encoded<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encoded").finish()
+    }
+}
+
 /// A wrapper that locks a lock and serializes the value immutably.
 ///
 /// This wrapper can panic under very specific circumstances when:
@@ -447,6 +587,158 @@ pub struct AsOwned;
 #[derive(Debug)]
 pub struct AsVec;
 
+/// A wrapper that archives a `HashMap` or `HashSet` with the hasher `H` seeded
+/// with `SEED`.
+///
+/// The hash algorithm and seed are recorded in the archived collection, and
+/// lookups use them instead of the hasher type parameter of the archived
+/// collection. See [`HasherConfig`](crate::hash::HasherConfig) for more
+/// information.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::{HashMap, HashSet};
+///
+/// use rkyv::{
+///     hash::{FxHasher64, SipHasher13},
+///     with::WithHasher,
+///     Archive,
+/// };
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(WithHasher<SipHasher13, 0x5eed>)]
+///     untrusted: HashMap<String, u32>,
+///     #[with(WithHasher<FxHasher64>)]
+///     ids: HashSet<u64>,
+/// }
+/// ```
+pub struct WithHasher<H, const SEED: u64 = 0> {
+    _hasher: PhantomData<H>,
+}
+
+impl<H, const SEED: u64> fmt::Debug for WithHasher<H, SEED> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithHasher").field("seed", &SEED).finish()
+    }
+}
+
+/// A wrapper that archives a `Vec` or `BTreeSet` as an
+/// [`ArchivedSortedVec`](crate::collections::sorted_vec::ArchivedSortedVec).
+///
+/// Elements are sorted when they are serialized if they are not sorted
+/// already. The archived elements must be ordered the same way as the
+/// elements they were archived from.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use rkyv::{with::AsSortedSlice, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsSortedSlice)]
+///     ids: Vec<u64>,
+///     #[with(AsSortedSlice)]
+///     tags: BTreeSet<String>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsSortedSlice;
+
+/// A wrapper that deserializes byte buffers without copying when the archive
+/// is backed by a [`Bytes`](bytes::Bytes) buffer.
+///
+/// Deserializing with this wrapper requires a deserializer that implements
+/// [`BackingBytes`](crate::de::bytes::BackingBytes), such as
+/// [`BytesBacked`](crate::de::bytes::BytesBacked). The deserialized `Bytes`
+/// share the allocation of the backing buffer.
+///
+/// `Arc<str>` fields can use this wrapper too. Each archived string is copied
+/// into an `Arc<str>` once, and later deserializations of the same string with
+/// the same deserializer return clones of that `Arc`.
+///
+/// # Example
+///
+/// ```
+/// use bytes::Bytes;
+/// use rkyv::{with::ShareBytes, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(ShareBytes)]
+///     payload: Bytes,
+/// }
+/// ```
+#[cfg(feature = "bytes")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "bytes")))]
+#[derive(Debug)]
+pub struct ShareBytes;
+
+/// A wrapper that archives a field as an independent nested archive.
+///
+/// The field is serialized into its own archive with its own root, which is
+/// then embedded in the outer archive as an
+/// [`ArchivedNested`](crate::nested::ArchivedNested). The nested archive is
+/// validated separately when it is accessed with
+/// [`access_inner`](crate::nested::ArchivedNested::access_inner).
+///
+/// Validating the outer archive does not validate the nested archive, so
+/// deserializing a nested field always validates it first. This requires the
+/// `bytecheck` feature.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access_unchecked, rancor::Error, with::Nested, Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Inner {
+///     values: Vec<u32>,
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// struct Container {
+///     name: String,
+///     #[with(Nested)]
+///     inner: Inner,
+/// }
+///
+/// let value = Container {
+///     name: "container".to_string(),
+///     inner: Inner {
+///         values: vec![1, 2, 3],
+///     },
+/// };
+/// let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+/// let archived = unsafe { access_unchecked::<ArchivedContainer>(&bytes) };
+/// let inner = archived.inner.access_inner::<Error>().unwrap();
+/// assert_eq!(inner.values.len(), 3);
+/// assert_eq!(inner.values[2], 3);
+/// ```
+#[derive(Debug)]
+pub struct Nested;
+
+/// A wrapper that archives a field in its own segment.
+///
+/// The field is serialized as an independent archive which is added to the
+/// segment table of the serializer, and the field is archived as a
+/// [`FarPtr`](crate::segment::FarPtr) to it. The serializer must implement
+/// [`Segmenting`](crate::segment::Segmenting), and the deserializer must
+/// implement [`Segments`](crate::segment::Segments). See
+/// [`Segmented`](crate::segment::Segmented) for an example.
+///
+/// Segments may be loaded from a different source than the archive which
+/// points into them, so deserializing a far field always validates the
+/// segment first. This requires the `bytecheck` feature.
+#[derive(Debug)]
+pub struct Far;
+
 /// A wrapper that niches some type combinations.
 ///
 /// A common type combination is `Option<Box<T>>`. By using a null pointer, the
@@ -478,6 +770,25 @@ pub struct AsVec;
 #[derive(Debug)]
 pub struct Niche;
 
+/// A wrapper that archives integers and vecs in the given
+/// [`Format`](crate::format::Format).
+///
+/// Integers are archived as the primitives of the format, and vecs are
+/// archived as an [`ArchivedVec`](crate::vec::ArchivedVec) with the relative
+/// pointer and length of the format. The elements of a vec are still archived
+/// in their own archived types.
+///
+/// See [`format`](mod@crate::format) for an example.
+pub struct InFormat<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F> fmt::Debug for InFormat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InFormat").finish()
+    }
+}
+
 /// A wrapper that converts a [`SystemTime`](::std::time::SystemTime) to a
 /// [`Duration`](::std::time::Duration) since
 /// [`UNIX_EPOCH`](::std::time::UNIX_EPOCH).
@@ -577,3 +888,20 @@ pub struct Skip;
 /// A wrapper that clones the contents of `Arc` and `Rc` pointers.
 #[derive(Debug)]
 pub struct Cloned;
+
+/// A wrapper that meters the allocation made to deserialize a container.
+///
+/// Deserializing a field with this wrapper reports the allocation for the
+/// field to the deserializer through [`Metering`](crate::de::Metering) before
+/// it is made, so the deserializer must implement `Metering`. Fields without
+/// this wrapper are deserialized as usual and are not metered.
+///
+/// Only the allocation for the field itself is metered, and not allocations
+/// made by its elements. Use `Map<Metered>` to meter the elements of a vector
+/// of containers instead.
+///
+/// This wrapper supports `Vec`, `String`, `Box`, `BTreeMap`, `BTreeSet`,
+/// `HashMap`, and `HashSet`. See [`Quota`](crate::de::quota::Quota) for an
+/// example.
+#[derive(Debug)]
+pub struct Metered;
@@ -0,0 +1,112 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Error, Fields};
+
+use crate::{
+    attributes::Attributes,
+    util::{is_not_omitted, members},
+};
+
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let where_clause = input.generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_not_omitted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::analyze::Analyze
+                });
+            }
+        }
+        Data::Enum(data) => {
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_not_omitted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::analyze::Analyze
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Analyze cannot be derived for unions",
+            ))
+        }
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let records = members(&data.fields).map(|(member, _)| {
+                quote! {
+                    #rkyv_path::analyze::TypeSizeReport::record(
+                        report,
+                        &self.#member,
+                    );
+                }
+            });
+            quote! { #(#records)* }
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                let arms = data.variants.iter().map(|variant| {
+                    let ident = &variant.ident;
+                    let bindings = (0..variant.fields.len())
+                        .map(|i| format_ident!("__field{}", i))
+                        .collect::<Vec<_>>();
+                    let pattern = match &variant.fields {
+                        Fields::Unit => quote! { Self::#ident },
+                        fields => {
+                            let members =
+                                members(fields).map(|(member, _)| member);
+                            quote! {
+                                Self::#ident { #(#members: #bindings),* }
+                            }
+                        }
+                    };
+                    quote! {
+                        #pattern => {
+                            #(
+                                #rkyv_path::analyze::TypeSizeReport::record(
+                                    report,
+                                    #bindings,
+                                );
+                            )*
+                        }
+                    }
+                });
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::analyze::Analyze
+            for #name #ty_generics
+        #where_clause
+        {
+            #[allow(unused_variables)]
+            fn analyze(
+                &self,
+                report: &mut #rkyv_path::analyze::TypeSizeReport,
+            ) {
+                #body
+            }
+        }
+    })
+}
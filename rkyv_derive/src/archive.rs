@@ -1,12 +1,18 @@
+mod builder;
 mod r#enum;
+mod evolve;
+mod offsets;
 mod printing;
 mod r#struct;
+mod transparent;
 
 use core::fmt::Display;
 
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, Error, Field, Ident, Meta};
+use syn::{
+    parse_quote, Data, DeriveInput, Error, Field, Ident, Meta, Visibility,
+};
 
 use crate::attributes::Attributes;
 
@@ -84,9 +90,29 @@ fn derive_archive_impl(
         }
     }
 
+    if let Some(transparent) = &attributes.transparent {
+        return transparent::impl_transparent(input, attributes, transparent);
+    }
+
     let printing = printing::Printing::new(input, attributes)?;
 
+    let module = attributes.archived_module(&input.ident);
+    if module.is_some() {
+        // The archived types are generated one module deeper, so their
+        // visibilities have to reach one module further out.
+        nest_visibility(&mut input.vis);
+        if let Data::Struct(data) = &mut input.data {
+            for field in data.fields.iter_mut() {
+                nest_visibility(&mut field.vis);
+            }
+        }
+    }
+
     let (archive_types, archive_impls) = match input.data {
+        Data::Struct(_) | Data::Enum(_) if attributes.evolve.is_some() => {
+            let evolve = attributes.evolve.as_ref().unwrap();
+            evolve::impl_evolve(input, attributes, &printing, evolve)?
+        }
         Data::Struct(_) => r#struct::impl_struct(input, attributes, &printing)?,
         Data::Enum(_) => r#enum::impl_enum(input, attributes, &printing)?,
         Data::Union(_) => {
@@ -99,6 +125,36 @@ fn derive_archive_impl(
 
     let rkyv_path = &printing.rkyv_path;
 
+    let (archive_types, use_archive_types) = match module {
+        Some(module) => {
+            let archived_name = &printing.archived_name;
+            let archived_import = attributes
+                .archive_as
+                .is_none()
+                .then(|| quote! { #archived_name, });
+            let resolver_import = attributes.pod.is_none().then(|| {
+                let resolver_name = &printing.resolver_name;
+                quote! { #resolver_name }
+            });
+            (
+                quote! {
+                    #[doc(hidden)]
+                    #[allow(non_snake_case)]
+                    mod #module {
+                        #[allow(unused_imports)]
+                        use super::*;
+
+                        #archive_types
+                    }
+                },
+                Some(quote! {
+                    use #module::{#archived_import #resolver_import};
+                }),
+            )
+        }
+        None => (archive_types, None),
+    };
+
     Ok(quote! {
         #archive_types
 
@@ -106,8 +162,28 @@ fn derive_archive_impl(
         const _: () = {
             use core::marker::PhantomData;
             use #rkyv_path::{Archive, Archived};
+            #use_archive_types
 
             #archive_impls
         };
     })
 }
+
+fn nest_visibility(vis: &mut Visibility) {
+    match vis {
+        Visibility::Public(_) => (),
+        Visibility::Restricted(restricted) => {
+            let path = &restricted.path;
+            if path.is_ident("self") {
+                *vis = parse_quote! { pub(super) };
+            } else if path
+                .segments
+                .first()
+                .map_or(false, |segment| segment.ident == "super")
+            {
+                *vis = parse_quote! { pub(in super::#path) };
+            }
+        }
+        Visibility::Inherited => *vis = parse_quote! { pub(super) },
+    }
+}
@@ -0,0 +1,214 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Error, Fields};
+
+use crate::{
+    archive::printing::Printing, attributes::Attributes, util::archived,
+};
+
+pub fn generate_builder(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    fields: &Fields,
+) -> Result<TokenStream, Error> {
+    let builder = attributes.builder.as_ref().unwrap();
+
+    if attributes.archive_as.is_some() {
+        return Err(Error::new_spanned(
+            builder,
+            "builder may not be used with as = \"...\"",
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "builder is not supported for generic types",
+        ));
+    }
+    let fields = match fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+            return Err(Error::new_spanned(
+                builder,
+                "builder is only supported for structs with named fields",
+            ))
+        }
+    };
+
+    for name in fields.iter().map(|field| field.ident.as_ref().unwrap()) {
+        if name == "new" || name == "finish" {
+            return Err(Error::new_spanned(
+                name,
+                "builder does not support fields named `new` or `finish`",
+            ));
+        }
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let archived_name = &printing.archived_name;
+    let builder_name = format_ident!("{}Builder", archived_name);
+    let vis = &input.vis;
+
+    let names = fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let params = (0..fields.len())
+        .map(|i| format_ident!("__F{}", i))
+        .collect::<Vec<_>>();
+    let values = (0..fields.len())
+        .map(|i| format_ident!("__U{}", i))
+        .collect::<Vec<_>>();
+
+    let builder_doc = format!("A builder for an archived [`{}`]", input.ident);
+
+    let setters = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = names[i];
+            let field_ty = archived(rkyv_path, field)?;
+            let doc = format!(
+                "Serializes the given value and sets it as the `{}` field.",
+                name
+            );
+            let result_params = params.iter().enumerate().map(|(j, param)| {
+                if i == j {
+                    quote! { #rkyv_path::builder::Set<'a, __U> }
+                } else {
+                    quote! { #param }
+                }
+            });
+            let result_fields = names.iter().enumerate().map(|(j, other)| {
+                if i == j {
+                    quote! {
+                        #other: #rkyv_path::builder::Set::new(value, resolver)
+                    }
+                } else {
+                    quote! { #other: self.#other }
+                }
+            });
+
+            Ok(quote! {
+                #[doc = #doc]
+                #[inline]
+                pub fn #name<__U>(
+                    self,
+                    value: &'a __U,
+                ) -> ::core::result::Result<
+                    #builder_name<'a, __S, #(#result_params,)*>,
+                    <__S as #rkyv_path::rancor::Fallible>::Error,
+                >
+                where
+                    __U: #rkyv_path::Archive<Archived = #field_ty>
+                        + #rkyv_path::Serialize<__S>,
+                {
+                    let resolver = #rkyv_path::Serialize::serialize(
+                        value,
+                        self.serializer,
+                    )?;
+                    Ok(#builder_name {
+                        serializer: self.serializer,
+                        #(#result_fields,)*
+                    })
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let value_bounds = fields
+        .iter()
+        .zip(values.iter())
+        .map(|(field, value)| {
+            let field_ty = archived(rkyv_path, field)?;
+            Ok(quote! { #value: #rkyv_path::Archive<Archived = #field_ty> })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        #[doc = #builder_doc]
+        #[must_use]
+        #vis struct #builder_name<
+            'a,
+            __S: ?Sized,
+            #(#params = #rkyv_path::builder::Unset,)*
+        > {
+            serializer: &'a mut __S,
+            #(#names: #params,)*
+        }
+
+        #[automatically_derived]
+        impl<'a, __S> #builder_name<'a, __S>
+        where
+            __S: #rkyv_path::rancor::Fallible + ?Sized,
+        {
+            /// Creates a new builder which serializes into the given
+            /// serializer.
+            #[inline]
+            pub fn new(serializer: &'a mut __S) -> Self {
+                Self {
+                    serializer,
+                    #(#names: #rkyv_path::builder::Unset,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl<'a, __S, #(#params,)*> #builder_name<'a, __S, #(#params,)*>
+        where
+            __S: #rkyv_path::rancor::Fallible + ?Sized,
+        {
+            #(#setters)*
+        }
+
+        #[automatically_derived]
+        impl<'a, __S, #(#values,)*> #builder_name<
+            'a,
+            __S,
+            #(#rkyv_path::builder::Set<'a, #values>,)*
+        >
+        where
+            __S: #rkyv_path::rancor::Fallible
+                + #rkyv_path::ser::Writer
+                + ?Sized,
+            #(#value_bounds,)*
+        {
+            /// Writes the archived value and returns its position.
+            #[inline]
+            pub fn finish(
+                self,
+            ) -> ::core::result::Result<
+                usize,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                use #rkyv_path::ser::WriterExt as _;
+
+                let pos = self.serializer.align_for::<#archived_name>()?;
+                let mut resolved =
+                    ::core::mem::MaybeUninit::<#archived_name>::zeroed();
+                // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+                // `MaybeUninit`, and so is properly aligned, dereferenceable,
+                // and all of its bytes are initialized.
+                let out = unsafe {
+                    #rkyv_path::Place::new_unchecked(
+                        pos,
+                        resolved.as_mut_ptr(),
+                    )
+                };
+                #(
+                    let field_ptr = unsafe {
+                        ::core::ptr::addr_of_mut!((*out.ptr()).#names)
+                    };
+                    let out_field = unsafe {
+                        #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+                    };
+                    self.#names.resolve(out_field);
+                )*
+                self.serializer.write(out.as_slice())?;
+                Ok(pos)
+            }
+        }
+    })
+}
@@ -13,8 +13,9 @@ use crate::{
     },
     attributes::Attributes,
     util::{
-        archive_bound, archived, is_not_omitted, members_starting_at, resolve,
-        resolver, strip_raw,
+        archive_bound, archived, copy_optimization, dedup_predicates,
+        inline_attr, is_not_omitted, members_starting_at, needs_archive_bound,
+        resolve, resolve_field, resolver, strip_raw,
     },
 };
 
@@ -35,7 +36,36 @@ pub fn impl_enum(
         ));
     }
 
+    if let Some(builder) = &attributes.builder {
+        return Err(Error::new_spanned(
+            builder,
+            "builder is only supported for structs with named fields",
+        ));
+    }
+
+    if let Some(pod) = &attributes.pod {
+        return Err(Error::new_spanned(
+            pod,
+            "pod is only supported for structs",
+        ));
+    }
+
+    if let Some(offsets) = &attributes.offsets {
+        return Err(Error::new_spanned(
+            offsets,
+            "offsets is only supported for structs",
+        ));
+    }
+
+    if let Some(reorder) = &attributes.reorder {
+        return Err(Error::new_spanned(
+            reorder,
+            "reorder is only supported for structs with named fields",
+        ));
+    }
+
     let rkyv_path = &printing.rkyv_path;
+    let needs_archive_bound = needs_archive_bound(attributes, &input.generics);
 
     let where_clause = input.generics.make_where_clause();
 
@@ -43,12 +73,13 @@ pub fn impl_enum(
         .variants
         .iter()
         .flat_map(|v| v.fields.iter())
-        .filter(is_not_omitted)
+        .filter(needs_archive_bound)
     {
         where_clause
             .predicates
             .push(archive_bound(rkyv_path, field)?);
     }
+    dedup_predicates(attributes, where_clause);
 
     let (impl_generics, ty_generics, where_clause) =
         input.generics.split_for_impl();
@@ -61,7 +92,8 @@ pub fn impl_enum(
         .transpose()?;
 
     let resolver_def = generate_resolver_def(input, printing, data)?;
-    let resolve_arms = generate_resolve_arms(input, printing, data)?;
+    let resolve_arms =
+        generate_resolve_arms(input, attributes, printing, data)?;
 
     let archived_variant_tags = data.variants.iter().map(|v| {
         let variant = &v.ident;
@@ -95,9 +127,17 @@ pub fn impl_enum(
         }
     }
 
+    let try_from_tag_impls = attributes
+        .try_from_tag
+        .is_some()
+        .then(|| generate_try_from_tag_impls(input, data, printing))
+        .transpose()?;
+
     let name = &input.ident;
     let archived_type = &printing.archived_type;
     let resolver_name = &printing.resolver_name;
+    let inline = inline_attr(attributes);
+    let copy_optimization = copy_optimization(attributes, rkyv_path);
 
     Ok((
         quote! {
@@ -114,13 +154,15 @@ pub fn impl_enum(
             #(#archived_variant_structs)*
 
             impl #impl_generics Archive for #name #ty_generics #where_clause {
+                #copy_optimization
+
                 type Archived = #archived_type;
                 type Resolver = #resolver_name #ty_generics;
 
                 // Some resolvers will be (), this allow is to prevent clippy
                 // from complaining
                 #[allow(clippy::unit_arg)]
-                #[inline]
+                #inline
                 fn resolve(
                     &self,
                     resolver: <Self as Archive>::Resolver,
@@ -134,10 +176,96 @@ pub fn impl_enum(
 
             #partial_eq_impl
             #partial_ord_impl
+            #try_from_tag_impls
         },
     ))
 }
 
+fn generate_try_from_tag_impls(
+    input: &DeriveInput,
+    data: &DataEnum,
+    printing: &Printing,
+) -> Result<TokenStream, Error> {
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return Err(Error::new_spanned(
+            &variant.fields,
+            "try_from_tag is only supported for enums without fields",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let archived_type = &printing.archived_type;
+    let variants = data.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let try_from_body = quote! {
+        #(
+            if tag == ArchivedTag::#variants as u8 {
+                return ::core::result::Result::Ok(Self::#variants);
+            }
+        )*
+        ::core::result::Result::Err(
+            #rkyv_path::convert::InvalidTag::new(tag),
+        )
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::TryFrom<u8>
+            for #name #ty_generics
+        #where_clause
+        {
+            type Error = #rkyv_path::convert::InvalidTag;
+
+            #[inline]
+            fn try_from(
+                tag: u8,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                #try_from_body
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for u8
+        #where_clause
+        {
+            #[inline]
+            fn from(value: #name #ty_generics) -> u8 {
+                match value {
+                    #(#name::#variants => ArchivedTag::#variants as u8,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<u8> for #archived_type
+        #where_clause
+        {
+            type Error = #rkyv_path::convert::InvalidTag;
+
+            #[inline]
+            fn try_from(
+                tag: u8,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                #try_from_body
+            }
+        }
+
+        impl #impl_generics #archived_type #where_clause {
+            /// Returns the integer tag of this variant.
+            #[inline]
+            pub fn tag(&self) -> u8 {
+                match *self {
+                    #(Self::#variants => ArchivedTag::#variants as u8,)*
+                }
+            }
+        }
+    })
+}
+
 fn generate_archived_def(
     input: &DeriveInput,
     printing: &Printing,
@@ -339,6 +467,7 @@ fn generate_resolver_def(
 
 fn generate_resolve_arms(
     input: &DeriveInput,
+    attributes: &Attributes,
     printing: &Printing,
     data: &DataEnum,
 ) -> Result<Vec<TokenStream>, Error> {
@@ -372,10 +501,46 @@ fn generate_resolve_arms(
                 })
                 .unzip::<_, _, Vec<_>, Vec<_>>();
 
-            let resolves = v
+            let field_resolves = v
                 .fields
                 .iter()
-                .map(|f| resolve(rkyv_path, f))
+                .zip(members.iter())
+                .zip(self_bindings.iter().zip(resolver_bindings.iter()))
+                .map(|((field, member), (self_binding, resolver_binding))| {
+                    if attributes.compact.is_some() {
+                        let resolve_field = resolve_field(rkyv_path, field)?;
+                        return Ok(quote! {
+                            unsafe {
+                                #resolve_field(
+                                    #self_binding,
+                                    #resolver_binding,
+                                    out,
+                                    ::core::ptr::addr_of_mut!(
+                                        (*out.ptr()).#member
+                                    ),
+                                );
+                            }
+                        });
+                    }
+
+                    let resolves = resolve(rkyv_path, field)?;
+                    Ok(quote! {
+                        let field_ptr = unsafe {
+                            ::core::ptr::addr_of_mut!((*out.ptr()).#member)
+                        };
+                        let out_field = unsafe {
+                            #rkyv_path::Place::from_field_unchecked(
+                                out,
+                                field_ptr,
+                            )
+                        };
+                        #resolves(
+                            #self_binding,
+                            #resolver_binding,
+                            out_field,
+                        );
+                    })
+                })
                 .collect::<Result<Vec<_>, Error>>()?;
 
             match v.fields {
@@ -400,24 +565,7 @@ fn generate_resolve_arms(
                                 unsafe {
                                     tag_ptr.write(ArchivedTag::#variant);
                                 }
-                                #(
-                                    let field_ptr = unsafe {
-                                        ::core::ptr::addr_of_mut!(
-                                            (*out.ptr()).#members
-                                        )
-                                    };
-                                    let out_field = unsafe {
-                                        #rkyv_path::Place::from_field_unchecked(
-                                            out,
-                                            field_ptr,
-                                        )
-                                    };
-                                    #resolves(
-                                        #self_bindings,
-                                        #resolver_bindings,
-                                        out_field,
-                                    );
-                                )*
+                                #(#field_resolves)*
                             },
                             #[allow(unreachable_patterns)]
                             _ => unsafe {
@@ -441,24 +589,7 @@ fn generate_resolve_arms(
                                 unsafe {
                                     tag_ptr.write(ArchivedTag::#variant);
                                 }
-                                #(
-                                    let field_ptr = unsafe {
-                                        ::core::ptr::addr_of_mut!(
-                                            (*out.ptr()).#members
-                                        )
-                                    };
-                                    let out_field = unsafe {
-                                        #rkyv_path::Place::from_field_unchecked(
-                                            out,
-                                            field_ptr,
-                                        )
-                                    };
-                                    #resolves(
-                                        #self_bindings,
-                                        #resolver_bindings,
-                                        out_field,
-                                    );
-                                )*
+                                #(#field_resolves)*
                             },
                             #[allow(unreachable_patterns)]
                             _ => unsafe {
@@ -0,0 +1,179 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Ident, Path};
+
+use crate::{
+    archive::{archived_doc, printing::Printing, resolver_doc},
+    attributes::Attributes,
+    util::{
+        archive_bound, archived, evolve_fields, inline_attr, is_not_omitted,
+    },
+};
+
+fn accessor_doc(name: &Ident, field_name: &Ident) -> String {
+    format!(
+        "The archived counterpart of [`{}::{}`], or `None` if the archive \
+         doesn't have it",
+        name, field_name,
+    )
+}
+
+pub fn impl_evolve(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    evolve: &Path,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let fields = evolve_fields(input, evolve)?;
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let vis = &input.vis;
+    let archived_name = &printing.archived_name;
+    let archived_type = &printing.archived_type;
+    let resolver_name = &printing.resolver_name;
+    let inline = inline_attr(attributes);
+    let table = quote! { #rkyv_path::evolve::ArchivedFieldTable };
+
+    let mut where_clause = input.generics.where_clause.clone().unwrap();
+    for field in fields.named.iter().filter(is_not_omitted) {
+        where_clause
+            .predicates
+            .push(archive_bound(rkyv_path, field)?);
+    }
+
+    let accessors = fields
+        .named
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let field_name = field.ident.as_ref().unwrap();
+            let field_vis = &field.vis;
+            let field_ty = archived(rkyv_path, field)?;
+            let doc = accessor_doc(name, field_name);
+            Ok(quote! {
+                #[doc = #doc]
+                #inline
+                #field_vis fn #field_name(
+                    &self,
+                ) -> ::core::option::Option<&#field_ty> {
+                    // SAFETY: Each field is archived at the same index of the
+                    // field table in every version of the struct.
+                    unsafe { self.fields.get::<#field_ty>(#index) }
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let check_bytes_impl = (attributes.check_bytes.is_some()
+        && cfg!(feature = "bytecheck"))
+    .then(|| {
+        let field_tys = fields
+            .named
+            .iter()
+            .map(|field| archived(rkyv_path, field))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let bounds = fields
+            .named
+            .iter()
+            .zip(field_tys.iter())
+            .filter(|(field, _)| is_not_omitted(field))
+            .map(|(_, field_ty)| {
+                quote! { #field_ty: #rkyv_path::bytecheck::CheckBytes<__C> }
+            });
+
+        Ok::<_, Error>(quote! {
+            // SAFETY: `check_bytes_with` checks the field table and each of
+            // the fields it points to as their archived types.
+            unsafe impl<__C> #rkyv_path::bytecheck::CheckBytes<__C>
+                for #archived_name
+            where
+                __C: #rkyv_path::rancor::Fallible
+                    + #rkyv_path::validation::ArchiveContext
+                    + ?Sized,
+                <__C as #rkyv_path::rancor::Fallible>::Error:
+                    #rkyv_path::rancor::Source,
+                #(#bounds,)*
+            {
+                unsafe fn check_bytes(
+                    value: *const Self,
+                    context: &mut __C,
+                ) -> ::core::result::Result<
+                    (),
+                    <__C as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    // SAFETY: The archived struct is a transparent wrapper
+                    // around a field table.
+                    unsafe {
+                        #table::check_bytes_with(
+                            value.cast(),
+                            context,
+                            &[#(
+                                #rkyv_path::evolve::check_field::<
+                                    #field_tys,
+                                    __C,
+                                >,
+                            )*],
+                        )
+                    }
+                }
+            }
+        })
+    })
+    .transpose()?;
+
+    let archived_doc = archived_doc(name);
+    let resolver_doc = resolver_doc(name);
+    let archive_attrs = attributes.attrs.iter();
+    let len = fields.named.len();
+
+    let archive_types = quote! {
+        #[automatically_derived]
+        #[doc = #archived_doc]
+        #(#[#archive_attrs])*
+        #[repr(transparent)]
+        #vis struct #archived_name {
+            fields: #table,
+        }
+
+        // SAFETY: The archived struct is a transparent wrapper around a field
+        // table, which is `Portable`.
+        unsafe impl #rkyv_path::Portable for #archived_name {}
+
+        impl #archived_name {
+            #(#accessors)*
+        }
+
+        #check_bytes_impl
+
+        #[automatically_derived]
+        #[doc = #resolver_doc]
+        #vis struct #resolver_name {
+            fields: #rkyv_path::evolve::FieldTableResolver,
+        }
+    };
+
+    let archive_impls = quote! {
+        impl #rkyv_path::Archive for #name #where_clause {
+            type Archived = #archived_type;
+            type Resolver = #resolver_name;
+
+            #inline
+            fn resolve(
+                &self,
+                resolver: Self::Resolver,
+                out: #rkyv_path::Place<Self::Archived>,
+            ) {
+                let field_ptr = unsafe {
+                    ::core::ptr::addr_of_mut!((*out.ptr()).fields)
+                };
+                let out_field = unsafe {
+                    #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+                };
+                #table::resolve_from_len(#len, resolver.fields, out_field);
+            }
+        }
+    };
+
+    Ok((archive_types, archive_impls))
+}
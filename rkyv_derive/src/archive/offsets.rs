@@ -0,0 +1,53 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Error, Fields, Member};
+
+use crate::{
+    archive::printing::Printing, attributes::Attributes, util::members,
+};
+
+pub fn generate_offsets(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    fields: &Fields,
+) -> Result<TokenStream, Error> {
+    if attributes.archive_as.is_some() {
+        return Err(Error::new_spanned(
+            attributes.offsets.as_ref().unwrap(),
+            "offsets may not be used with as = \"...\"",
+        ));
+    }
+
+    let archived_name = &printing.archived_name;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let offsets = members(fields).map(|(member, _)| {
+        let field_name = match &member {
+            Member::Named(ident) => {
+                ident.to_string().trim_start_matches("r#").to_string()
+            }
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        let name = format_ident!("OFFSET_{}", field_name.to_uppercase());
+        let doc = format!(
+            "The offset of the `{}` field within an archived [`{}`], in bytes.",
+            field_name, input.ident,
+        );
+
+        quote! {
+            #[doc = #doc]
+            #vis const #name: usize =
+                ::core::mem::offset_of!(Self, #member);
+        }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #archived_name #ty_generics #where_clause {
+            #(#offsets)*
+        }
+    })
+}
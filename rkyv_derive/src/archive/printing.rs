@@ -4,7 +4,7 @@ use syn::{
     LitStr, Path, Type,
 };
 
-use crate::{attributes::Attributes, util::strip_raw};
+use crate::attributes::Attributes;
 
 pub struct Printing {
     pub rkyv_path: Path,
@@ -34,6 +34,13 @@ impl Printing {
                      because no type is generated",
                 ));
             }
+            if let Some(ref module) = attributes.module {
+                return Err(Error::new_spanned(
+                    module,
+                    "module = \"...\" may not be used with as = \"...\" \
+                     because no type is generated",
+                ));
+            }
             if let Some(first) = attributes.attrs.first() {
                 return Err(Error::new_spanned(
                     first,
@@ -48,15 +55,8 @@ impl Printing {
             }
         }
 
-        let archived_name = attributes.archived.as_ref().map_or_else(
-            || Ident::new(&format!("Archived{}", strip_raw(name)), name.span()),
-            |value| value.clone(),
-        );
-
-        let resolver_name = attributes.resolver.as_ref().map_or_else(
-            || Ident::new(&format!("{}Resolver", strip_raw(name)), name.span()),
-            |value| value.clone(),
-        );
+        let archived_name = attributes.archived_name(name)?;
+        let resolver_name = attributes.resolver_name(name)?;
 
         let archived_type = attributes.archive_as.as_ref().map_or_else(
             || {
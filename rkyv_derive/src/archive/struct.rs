@@ -1,18 +1,25 @@
+use core::cmp::Reverse;
+
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
-    parse_quote, punctuated::Punctuated, Data, DeriveInput, Error, Fields,
-    FieldsNamed, FieldsUnnamed,
+    parse_quote, punctuated::Punctuated, Data, DeriveInput, Error, Field,
+    Fields, FieldsNamed, FieldsUnnamed, Path, Type,
 };
 
 use crate::{
     archive::{
-        archived_doc, field_archive_attrs, printing::Printing, resolver_doc,
+        archived_doc, builder::generate_builder, field_archive_attrs,
+        offsets::generate_offsets, printing::Printing, resolver_doc,
         struct_field_doc,
     },
     attributes::Attributes,
+    repr::{Modifier, Repr},
     util::{
-        archive_bound, archived, is_not_omitted, members, resolve, resolver,
+        archive_bound, archived, copy_optimization, dedup_predicates, has_with,
+        inline_attr, is_not_omitted, is_with_attr, members,
+        needs_archive_bound, pod_archive_bound, resolve, resolve_field,
+        resolver,
     },
 };
 
@@ -27,14 +34,23 @@ pub fn impl_struct(
     };
 
     let rkyv_path = &printing.rkyv_path;
+    let needs_archive_bound = needs_archive_bound(attributes, &input.generics);
+
+    if let Some(pod) = &attributes.pod {
+        check_pod(input, pod, fields)?;
+    }
 
     let where_clause = input.generics.make_where_clause();
 
-    for field in fields.iter().filter(is_not_omitted) {
-        where_clause
-            .predicates
-            .push(archive_bound(rkyv_path, field)?);
+    for field in fields.iter().filter(needs_archive_bound) {
+        let bound = if attributes.pod.is_some() {
+            pod_archive_bound(rkyv_path, field)
+        } else {
+            archive_bound(rkyv_path, field)?
+        };
+        where_clause.predicates.push(bound);
     }
+    dedup_predicates(attributes, where_clause);
 
     let (impl_generics, ty_generics, where_clause) =
         input.generics.split_for_impl();
@@ -43,13 +59,43 @@ pub fn impl_struct(
     let archived_def = attributes
         .archive_as
         .is_none()
-        .then(|| generate_archived_def(input, printing, fields))
+        .then(|| generate_archived_def(input, attributes, printing, fields))
         .transpose()?;
 
-    let resolver_def = generate_resolver_def(input, printing, fields)?;
+    let resolver_def = attributes
+        .pod
+        .is_none()
+        .then(|| generate_resolver_def(input, printing, fields))
+        .transpose()?;
+
+    let builder_def = attributes
+        .builder
+        .is_some()
+        .then(|| generate_builder(input, attributes, printing, fields))
+        .transpose()?;
+
+    let offsets_def = attributes
+        .offsets
+        .is_some()
+        .then(|| generate_offsets(input, attributes, printing, fields))
+        .transpose()?;
 
     let resolve_statements = members(fields)
         .map(|(member, field)| {
+            if attributes.compact.is_some() {
+                let resolve_field = resolve_field(rkyv_path, field)?;
+                return Ok(quote! {
+                    unsafe {
+                        #resolve_field(
+                            &self.#member,
+                            resolver.#member,
+                            out,
+                            ::core::ptr::addr_of_mut!((*out.ptr()).#member),
+                        );
+                    }
+                });
+            }
+
             let resolves = resolve(rkyv_path, field)?;
             Ok(quote! {
                 let field_ptr = unsafe {
@@ -84,23 +130,43 @@ pub fn impl_struct(
     let name = &input.ident;
     let archived_type = &printing.archived_type;
     let resolver_name = &printing.resolver_name;
+    let inline = inline_attr(attributes);
+    let copy_optimization = copy_optimization(attributes, rkyv_path);
+
+    let archive_types = quote! {
+        #archived_def
+        #resolver_def
+        #builder_def
+        #offsets_def
+    };
+
+    if attributes.pod.is_some() {
+        let pod_impl = generate_pod_impl(input, attributes, printing, fields)?;
+        return Ok((
+            archive_types,
+            quote! {
+                #pod_impl
+                #partial_eq_impl
+                #partial_ord_impl
+            },
+        ));
+    }
 
     Ok((
-        quote! {
-            #archived_def
-            #resolver_def
-        },
+        archive_types,
         quote! {
             impl #impl_generics #rkyv_path::Archive for #name #ty_generics
             #where_clause
             {
+                #copy_optimization
+
                 type Archived = #archived_type;
                 type Resolver = #resolver_name #ty_generics;
 
                 // Some resolvers will be (), this allow is to prevent clippy
                 // from complaining.
                 #[allow(clippy::unit_arg)]
-                #[inline]
+                #inline
                 fn resolve(
                     &self,
                     resolver: Self::Resolver,
@@ -116,14 +182,146 @@ pub fn impl_struct(
     ))
 }
 
+fn check_pod(
+    input: &DeriveInput,
+    pod: &Path,
+    fields: &Fields,
+) -> Result<(), Error> {
+    let is_packed = |modifier: &Option<Modifier>| {
+        matches!(modifier, Some(Modifier::Packed(_)))
+    };
+    match Repr::from_attrs(&input.attrs)? {
+        Repr::Transparent => (),
+        Repr::C { modifier, .. } if !is_packed(&modifier) => (),
+        _ => {
+            return Err(Error::new_spanned(
+                pod,
+                "pod is only supported for structs which are `repr(C)` or \
+                 `repr(transparent)`",
+            ))
+        }
+    }
+
+    let with = fields
+        .iter()
+        .flat_map(|field| field.attrs.iter())
+        .find(|attr| is_with_attr(attr));
+    if let Some(with) = with {
+        let message = if with.path().is_ident("with") {
+            "with may not be used in pod types"
+        } else {
+            "archived types may not be substituted in pod types"
+        };
+        return Err(Error::new_spanned(with, message));
+    }
+
+    Ok(())
+}
+
+// Pod types have a unit resolver and serialize without doing anything. When
+// every field can be copied and neither the native nor the archived type has
+// any padding, they are archived by copying their bytes. This also enables
+// the copy optimization for slices and arrays of them.
+fn generate_pod_impl(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    fields: &Fields,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let archived_type = &printing.archived_type;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+    let inline = inline_attr(attributes);
+
+    let field_tys = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let resolve_statements = members(fields).map(|(member, _)| {
+        quote! {
+            unsafe {
+                #rkyv_path::derive::resolve_field(
+                    &self.#member,
+                    (),
+                    out,
+                    ::core::ptr::addr_of_mut!((*out.ptr()).#member),
+                );
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Archive for #name #ty_generics
+        #where_clause
+        {
+            // SAFETY: The native and archived types are both `repr(C)` with
+            // the same fields in the same order. When every field can be
+            // copied and neither type has padding, every field is at the same
+            // offset in both types, so the native bytes are the archived
+            // bytes.
+            const COPY_OPTIMIZATION: #rkyv_path::CopyOptimization<Self> =
+                unsafe {
+                    #rkyv_path::CopyOptimization::enable_if(
+                        true
+                        #(&& <#field_tys as #rkyv_path::Archive>
+                            ::COPY_OPTIMIZATION
+                            .is_enabled())*
+                        && ::core::mem::size_of::<Self>()
+                            == 0 #(+ ::core::mem::size_of::<#field_tys>())*
+                        && ::core::mem::size_of::<#archived_type>()
+                            == 0 #(+ ::core::mem::size_of::<
+                                #rkyv_path::Archived<#field_tys>
+                            >())*
+                        && ::core::mem::size_of::<Self>()
+                            == ::core::mem::size_of::<#archived_type>()
+                    )
+                };
+
+            type Archived = #archived_type;
+            type Resolver = ();
+
+            #[allow(clippy::unit_arg)]
+            #inline
+            fn resolve(
+                &self,
+                _: Self::Resolver,
+                out: #rkyv_path::Place<Self::Archived>,
+            ) {
+                if Self::COPY_OPTIMIZATION.is_enabled() {
+                    // SAFETY: The copy optimization is only enabled when the
+                    // native bytes are the archived bytes.
+                    unsafe {
+                        ::core::ptr::copy_nonoverlapping(
+                            (self as *const Self).cast::<u8>(),
+                            out.ptr().cast::<u8>(),
+                            ::core::mem::size_of::<Self>(),
+                        );
+                    }
+                } else {
+                    #(#resolve_statements)*
+                }
+            }
+        }
+    })
+}
+
 fn generate_archived_def(
     input: &DeriveInput,
+    attributes: &Attributes,
     printing: &Printing,
     fields: &Fields,
 ) -> Result<TokenStream, Error> {
+    if let (Some(reorder), Fields::Unnamed(_) | Fields::Unit) =
+        (&attributes.reorder, fields)
+    {
+        return Err(Error::new_spanned(
+            reorder,
+            "reorder is only supported for structs with named fields",
+        ));
+    }
+
     let archived_def = match fields {
         Fields::Named(fields) => {
-            generate_archived_def_named(input, printing, fields)?
+            generate_archived_def_named(input, attributes, printing, fields)?
         }
         Fields::Unnamed(fields) => {
             generate_archived_def_unnamed(input, printing, fields)?
@@ -150,14 +348,23 @@ fn generate_archived_def(
 
 fn generate_archived_def_named(
     input: &DeriveInput,
+    attributes: &Attributes,
     printing: &Printing,
     fields: &FieldsNamed,
 ) -> Result<TokenStream, Error> {
     let rkyv_path = &printing.rkyv_path;
 
-    let archived_fields = fields
-        .named
-        .iter()
+    let mut ordered_fields = fields.named.iter().collect::<Vec<_>>();
+    if attributes.reorder.is_some() {
+        // Fields whose size is unknown are placed first, which keeps them in
+        // declaration order ahead of the sorted primitive fields.
+        ordered_fields.sort_by_key(|field| {
+            Reverse(primitive_size(field).unwrap_or(usize::MAX))
+        });
+    }
+
+    let archived_fields = ordered_fields
+        .into_iter()
         .map(|field| {
             let field_ty = archived(rkyv_path, field)?;
             let vis = &field.vis;
@@ -191,6 +398,37 @@ fn generate_archived_def_named(
     })
 }
 
+// Returns the size of the primitive elements of the field's type, if the
+// field's type is a primitive or an array of primitives.
+fn primitive_size(field: &Field) -> Option<usize> {
+    if has_with(field) {
+        return None;
+    }
+
+    fn type_size(ty: &Type) -> Option<usize> {
+        match ty {
+            Type::Array(array) => type_size(&array.elem),
+            Type::Group(group) => type_size(&group.elem),
+            Type::Paren(paren) => type_size(&paren.elem),
+            Type::Path(path) if path.qself.is_none() => {
+                let ident = path.path.get_ident()?;
+                let size = match ident.to_string().as_str() {
+                    "u8" | "i8" | "bool" => 1,
+                    "u16" | "i16" => 2,
+                    "u32" | "i32" | "f32" | "char" => 4,
+                    "u64" | "i64" | "f64" => 8,
+                    "u128" | "i128" => 16,
+                    _ => return None,
+                };
+                Some(size)
+            }
+            _ => None,
+        }
+    }
+
+    type_size(&field.ty)
+}
+
 fn generate_archived_def_unnamed(
     input: &DeriveInput,
     printing: &Printing,
@@ -0,0 +1,51 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Path};
+
+use crate::{
+    attributes::Attributes,
+    util::{archive_bound, archived, resolve, resolver, transparent_field},
+};
+
+pub fn impl_transparent(
+    input: &mut DeriveInput,
+    attributes: &Attributes,
+    transparent: &Path,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+
+    let (member, field) = transparent_field(input, transparent)?;
+    let archive_bound = archive_bound(&rkyv_path, field)?;
+    let archived = archived(&rkyv_path, field)?;
+    let resolver = resolver(&rkyv_path, field)?;
+    let resolve = resolve(&rkyv_path, field)?;
+
+    input
+        .generics
+        .make_where_clause()
+        .predicates
+        .push(archive_bound);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #rkyv_path::Archive for #name #ty_generics
+        #where_clause
+        {
+            type Archived = #archived;
+            type Resolver = #resolver;
+
+            #[inline]
+            fn resolve(
+                &self,
+                resolver: Self::Resolver,
+                out: #rkyv_path::Place<Self::Archived>,
+            ) {
+                #resolve(&self.#member, resolver, out);
+            }
+        }
+    })
+}
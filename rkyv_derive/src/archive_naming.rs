@@ -0,0 +1,111 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens as _};
+use syn::{
+    meta::ParseNestedMeta, parse_quote, punctuated::Punctuated, Attribute,
+    DeriveInput, Error, Item, ItemMod, LitStr, Path, Token,
+};
+
+use crate::attributes::Attributes;
+
+#[derive(Default)]
+pub struct Naming {
+    archived: Option<LitStr>,
+    resolver: Option<LitStr>,
+}
+
+impl Naming {
+    pub fn parse_meta(
+        &mut self,
+        meta: ParseNestedMeta<'_>,
+    ) -> Result<(), Error> {
+        let slot = if meta.path.is_ident("archived") {
+            &mut self.archived
+        } else if meta.path.is_ident("resolver") {
+            &mut self.resolver
+        } else {
+            return Err(meta.error("unrecognized archive_naming argument"));
+        };
+        if slot.is_some() {
+            return Err(meta.error("argument already specified"));
+        }
+        *slot = Some(meta.value()?.parse()?);
+        Ok(())
+    }
+}
+
+fn derives_archive(attrs: &[Attribute]) -> Result<bool, Error> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let paths = attr
+            .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+        let archive = paths.iter().any(|path| {
+            path.segments
+                .last()
+                .map_or(false, |segment| segment.ident == "Archive")
+        });
+        if archive {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn apply(
+    naming: &Naming,
+    attrs: &mut Vec<Attribute>,
+    item: &Item,
+) -> Result<(), Error> {
+    if !derives_archive(attrs)? {
+        return Ok(());
+    }
+
+    let input = syn::parse2::<DeriveInput>(item.to_token_stream())?;
+    let attributes = Attributes::parse(&input)?;
+    // Transparent types don't generate any types to name.
+    if attributes.transparent.is_some() {
+        return Ok(());
+    }
+
+    if let Some(archived) = &naming.archived {
+        if attributes.archived.is_none() && attributes.archive_as.is_none() {
+            attrs.push(parse_quote! { #[archive(archived = #archived)] });
+        }
+    }
+    if let Some(resolver) = &naming.resolver {
+        if attributes.resolver.is_none() {
+            attrs.push(parse_quote! { #[archive(resolver = #resolver)] });
+        }
+    }
+    Ok(())
+}
+
+fn apply_all(naming: &Naming, items: &mut [Item]) -> Result<(), Error> {
+    for item in items.iter_mut() {
+        let snapshot = item.clone();
+        match item {
+            Item::Struct(item) => apply(naming, &mut item.attrs, &snapshot)?,
+            Item::Enum(item) => apply(naming, &mut item.attrs, &snapshot)?,
+            Item::Mod(ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => apply_all(naming, items)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+pub fn expand(
+    naming: Naming,
+    mut module: ItemMod,
+) -> Result<TokenStream, Error> {
+    match &mut module.content {
+        Some((_, items)) => apply_all(&naming, items)?,
+        None => {
+            return Err(Error::new_spanned(
+                &module,
+                "archive_naming must be applied to an inline module",
+            ))
+        }
+    }
+    Ok(quote! { #module })
+}
@@ -0,0 +1,52 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    braced,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Error, Ident, Token, Visibility,
+};
+
+use crate::util::archived_module_name;
+
+pub struct Input {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    types: Punctuated<Ident, Token![,]>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self, Error> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![mod]>()?;
+        let name = input.parse()?;
+        let types;
+        braced!(types in input);
+        let types = types.parse_terminated(Ident::parse, Token![,])?;
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            types,
+        })
+    }
+}
+
+pub fn expand(input: Input) -> TokenStream {
+    let Input {
+        attrs,
+        vis,
+        name,
+        types,
+    } = input;
+    let modules = types.iter().map(|ty| archived_module_name(&name, ty));
+
+    quote! {
+        #(#attrs)*
+        #vis mod #name {
+            #(pub use super::#modules::*;)*
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Error, Fields};
+
+use crate::{
+    attributes::Attributes,
+    util::{is_not_omitted, members},
+};
+
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let where_clause = input.generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_not_omitted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::ArchivedSize
+                });
+            }
+        }
+        Data::Enum(data) => {
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_not_omitted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::ArchivedSize
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "ArchivedSize cannot be derived for unions",
+            ))
+        }
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let sizes = members(&data.fields).map(|(member, _)| {
+                quote! {
+                    + #rkyv_path::ArchivedSize::out_of_line_size(
+                        &self.#member,
+                    )
+                }
+            });
+            quote! { 0 #(#sizes)* }
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                let arms = data.variants.iter().map(|variant| {
+                    let ident = &variant.ident;
+                    let bindings = (0..variant.fields.len())
+                        .map(|i| format_ident!("__field{}", i))
+                        .collect::<Vec<_>>();
+                    let pattern = match &variant.fields {
+                        Fields::Unit => quote! { Self::#ident },
+                        fields => {
+                            let members =
+                                members(fields).map(|(member, _)| member);
+                            quote! {
+                                Self::#ident { #(#members: #bindings),* }
+                            }
+                        }
+                    };
+                    quote! {
+                        #pattern => 0 #(
+                            + #rkyv_path::ArchivedSize::out_of_line_size(
+                                #bindings,
+                            )
+                        )*
+                    }
+                });
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::ArchivedSize for #name #ty_generics
+        #where_clause
+        {
+            #[inline]
+            fn out_of_line_size(&self) -> usize {
+                #body
+            }
+        }
+    })
+}
@@ -1,11 +1,13 @@
 use quote::ToTokens;
 use syn::{
     meta::ParseNestedMeta, parenthesized, parse::Parse, parse_quote,
-    punctuated::Punctuated, AttrStyle, DeriveInput, Error, Ident, LitStr, Meta,
-    Path, Token, WherePredicate,
+    punctuated::Punctuated, AttrStyle, Data, DeriveInput, Error, Ident, LitStr,
+    Meta, Path, Token, WherePredicate,
 };
 
-fn try_set_attribute<T: ToTokens>(
+use crate::util::{archived_module_name, strip_raw};
+
+pub fn try_set_attribute<T: ToTokens>(
     attribute: &mut Option<T>,
     value: T,
     name: &'static str,
@@ -21,17 +23,63 @@ fn try_set_attribute<T: ToTokens>(
     }
 }
 
+// Parses a type name, which is either an identifier or a string. Strings may
+// contain `{}`, which is replaced with the name of the type.
+fn parse_name(meta: &ParseNestedMeta<'_>) -> Result<LitStr, Error> {
+    let value = meta.value()?;
+    if value.peek(LitStr) {
+        value.parse()
+    } else {
+        let ident = value.parse::<Ident>()?;
+        Ok(LitStr::new(&ident.to_string(), ident.span()))
+    }
+}
+
+// Formats a type name for `name` from the given format, or from the default
+// format if none was given.
+fn format_name(
+    format: Option<&LitStr>,
+    default: &str,
+    name: &Ident,
+) -> Result<Ident, Error> {
+    let stripped = strip_raw(name);
+    let formatted = match format {
+        Some(format) => format.value().replace("{}", &stripped),
+        None => default.replace("{}", &stripped),
+    };
+    match syn::parse_str::<Ident>(&formatted) {
+        Ok(ident) => Ok(Ident::new(&ident.to_string(), name.span())),
+        Err(_) => Err(Error::new_spanned(
+            format,
+            format!("`{}` is not a valid type name", formatted),
+        )),
+    }
+}
+
 #[derive(Default)]
 pub struct Attributes {
     pub archive_as: Option<LitStr>,
-    pub archived: Option<Ident>,
-    pub resolver: Option<Ident>,
+    pub archived: Option<LitStr>,
+    pub resolver: Option<LitStr>,
     pub attrs: Vec<Meta>,
     pub compares: Option<Punctuated<Path, Token![,]>>,
     pub archive_bounds: Option<Punctuated<WherePredicate, Token![,]>>,
     pub serialize_bounds: Option<Punctuated<WherePredicate, Token![,]>>,
     pub deserialize_bounds: Option<Punctuated<WherePredicate, Token![,]>>,
     pub check_bytes: Option<Path>,
+    pub builder: Option<Path>,
+    pub offsets: Option<Path>,
+    pub evolve: Option<Path>,
+    pub preserve_order: Option<Path>,
+    pub reorder: Option<LitStr>,
+    pub transparent: Option<Path>,
+    pub compact: Option<Path>,
+    pub pod: Option<Path>,
+    pub bulk_copy: Option<Path>,
+    pub try_from_tag: Option<Path>,
+    pub pre_serialize: Option<Path>,
+    pub post_deserialize: Option<Path>,
+    pub module: Option<Ident>,
     pub crate_path: Option<Path>,
 }
 
@@ -43,6 +91,93 @@ impl Attributes {
             }
 
             try_set_attribute(&mut self.check_bytes, meta.path, "check_bytes")
+        } else if meta.path.is_ident("builder") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("builder does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.builder, meta.path, "builder")
+        } else if meta.path.is_ident("offsets") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("offsets does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.offsets, meta.path, "offsets")
+        } else if meta.path.is_ident("evolve") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("evolve does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.evolve, meta.path, "evolve")
+        } else if meta.path.is_ident("preserve_order") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(
+                    meta.error("preserve_order does not take any arguments")
+                );
+            }
+
+            try_set_attribute(
+                &mut self.preserve_order,
+                meta.path,
+                "preserve_order",
+            )
+        } else if meta.path.is_ident("transparent") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(
+                    meta.error("transparent does not take any arguments")
+                );
+            }
+
+            try_set_attribute(&mut self.transparent, meta.path, "transparent")
+        } else if meta.path.is_ident("compact") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("compact does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.compact, meta.path, "compact")
+        } else if meta.path.is_ident("pod") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("pod does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.pod, meta.path, "pod")
+        } else if meta.path.is_ident("bulk_copy") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("bulk_copy does not take any arguments"));
+            }
+
+            try_set_attribute(&mut self.bulk_copy, meta.path, "bulk_copy")
+        } else if meta.path.is_ident("try_from_tag") {
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(
+                    meta.error("try_from_tag does not take any arguments")
+                );
+            }
+
+            try_set_attribute(&mut self.try_from_tag, meta.path, "try_from_tag")
+        } else if meta.path.is_ident("reorder") {
+            let order = meta.value()?.parse::<LitStr>()?;
+            if order.value() != "size_desc" {
+                return Err(Error::new_spanned(
+                    order,
+                    "unrecognized reorder argument, the supported order is \
+                     \"size_desc\"",
+                ));
+            }
+            try_set_attribute(&mut self.reorder, order, "reorder")
+        } else if meta.path.is_ident("pre_serialize") {
+            let hook = meta.value()?.parse::<LitStr>()?.parse::<Path>()?;
+            try_set_attribute(&mut self.pre_serialize, hook, "pre_serialize")
+        } else if meta.path.is_ident("post_deserialize") {
+            let hook = meta.value()?.parse::<LitStr>()?.parse::<Path>()?;
+            try_set_attribute(
+                &mut self.post_deserialize,
+                hook,
+                "post_deserialize",
+            )
+        } else if meta.path.is_ident("module") {
+            let module = meta.value()?.parse::<LitStr>()?.parse::<Ident>()?;
+            try_set_attribute(&mut self.module, module, "module")
         } else if meta.path.is_ident("compare") {
             let traits;
             parenthesized!(traits in meta.input);
@@ -81,13 +216,13 @@ impl Attributes {
         } else if meta.path.is_ident("archived") {
             try_set_attribute(
                 &mut self.archived,
-                meta.value()?.parse()?,
+                parse_name(&meta)?,
                 "archived",
             )
         } else if meta.path.is_ident("resolver") {
             try_set_attribute(
                 &mut self.resolver,
-                meta.value()?.parse()?,
+                parse_name(&meta)?,
                 "resolver",
             )
         } else if meta.path.is_ident("as") {
@@ -133,9 +268,133 @@ impl Attributes {
             }
         }
 
+        if let (Some(_), Some(reorder)) =
+            (&result.preserve_order, &result.reorder)
+        {
+            return Err(Error::new_spanned(
+                reorder,
+                "reorder may not be used with preserve_order",
+            ));
+        }
+
+        if let (Some(_), Some(builder)) = (&result.pod, &result.builder) {
+            return Err(Error::new_spanned(
+                builder,
+                "builder may not be used with pod because pod types have no \
+                 resolver",
+            ));
+        }
+
+        if let (Some(_), Some(bulk_copy)) = (&result.pod, &result.bulk_copy) {
+            return Err(Error::new_spanned(
+                bulk_copy,
+                "bulk_copy may not be used with pod because pod types detect \
+                 whether they can be copied",
+            ));
+        }
+
+        if let (Some(_), Some(archive_as)) = (&result.pod, &result.archive_as) {
+            return Err(Error::new_spanned(
+                archive_as,
+                "as = \"...\" may not be used with pod because pod types must \
+                 generate their archived type",
+            ));
+        }
+
+        if let (Some(_), Some(reorder)) = (&result.pod, &result.reorder) {
+            return Err(Error::new_spanned(
+                reorder,
+                "reorder may not be used with pod because pod types must keep \
+                 the layout of the native type",
+            ));
+        }
+
+        if let Some(try_from_tag) = &result.try_from_tag {
+            if !matches!(input.data, Data::Enum(_)) {
+                return Err(Error::new_spanned(
+                    try_from_tag,
+                    "try_from_tag is only supported for enums",
+                ));
+            }
+            if let Some(archive_as) = &result.archive_as {
+                return Err(Error::new_spanned(
+                    archive_as,
+                    "as = \"...\" may not be used with try_from_tag because \
+                     the archived enum must be generated",
+                ));
+            }
+        }
+
+        if let Some(transparent) = &result.transparent {
+            let conflicts = [
+                ("as = \"...\"", result.archive_as.is_some()),
+                ("archived = \"...\"", result.archived.is_some()),
+                ("resolver = \"...\"", result.resolver.is_some()),
+                ("archive_attr(...)", !result.attrs.is_empty()),
+                ("compare(...)", result.compares.is_some()),
+                ("check_bytes", result.check_bytes.is_some()),
+                ("builder", result.builder.is_some()),
+                ("offsets", result.offsets.is_some()),
+                ("reorder = \"...\"", result.reorder.is_some()),
+                ("module = \"...\"", result.module.is_some()),
+                ("pod", result.pod.is_some()),
+                ("bulk_copy", result.bulk_copy.is_some()),
+                ("evolve", result.evolve.is_some()),
+            ];
+            if let Some((name, _)) = conflicts.iter().find(|(_, set)| *set) {
+                return Err(Error::new_spanned(
+                    transparent,
+                    format!(
+                        "{} may not be used with transparent because no \
+                         archived type is generated",
+                        name,
+                    ),
+                ));
+            }
+        }
+
+        if let Some(evolve) = &result.evolve {
+            let conflicts = [
+                ("as = \"...\"", result.archive_as.is_some()),
+                ("compare(...)", result.compares.is_some()),
+                ("builder", result.builder.is_some()),
+                ("offsets", result.offsets.is_some()),
+                ("reorder = \"...\"", result.reorder.is_some()),
+                ("pod", result.pod.is_some()),
+                ("bulk_copy", result.bulk_copy.is_some()),
+                ("module = \"...\"", result.module.is_some()),
+            ];
+            if let Some((name, _)) = conflicts.iter().find(|(_, set)| *set) {
+                return Err(Error::new_spanned(
+                    evolve,
+                    format!(
+                        "{} may not be used with evolve because evolving \
+                         structs are archived as a table of fields",
+                        name,
+                    ),
+                ));
+            }
+        }
+
         Ok(result)
     }
 
+    pub fn archived_name(&self, name: &Ident) -> Result<Ident, Error> {
+        format_name(self.archived.as_ref(), "Archived{}", name)
+    }
+
+    pub fn resolver_name(&self, name: &Ident) -> Result<Ident, Error> {
+        format_name(self.resolver.as_ref(), "{}Resolver", name)
+    }
+
+    // Returns the hidden module that the archived types of `name` are
+    // generated in, if `module = "..."` was specified.
+    pub fn archived_module(&self, name: &Ident) -> Option<Ident> {
+        self.module
+            .as_ref()
+            .map(|module| archived_module_name(module, name))
+    }
+
     pub fn crate_path(&self) -> Path {
         self.crate_path
             .clone()
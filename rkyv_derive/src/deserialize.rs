@@ -2,12 +2,16 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse_quote, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput,
-    Error, Fields, Generics, Ident, Index,
+    Error, Field, Fields, Generics, Ident, Index,
 };
 
 use crate::{
     attributes::Attributes,
-    util::{archive_bound, deserialize, deserialize_bound, is_not_omitted},
+    util::{
+        archive_bound, compact_deserialize, dedup_predicates, deserialize,
+        deserialize_bound, evolve_fields, inline_attr, is_not_omitted,
+        needs_archive_bound, pod_archive_bound, transparent_field,
+    },
 };
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -20,6 +24,7 @@ fn derive_deserialize_impl(
     attributes: &Attributes,
 ) -> Result<TokenStream, Error> {
     let rkyv_path = attributes.crate_path();
+    let needs_archive_bound = needs_archive_bound(attributes, &input.generics);
 
     let where_clause = input.generics.make_where_clause();
     if let Some(ref bounds) = attributes.archive_bounds {
@@ -51,30 +56,188 @@ fn derive_deserialize_impl(
     let (_, ty_generics, where_clause) = input.generics.split_for_impl();
     let where_clause = where_clause.unwrap();
 
+    let inline = inline_attr(attributes);
+    // Returns the deserialized value, after passing it to the post-deserialize
+    // hook if there is one.
+    let finish = |value: TokenStream| match &attributes.post_deserialize {
+        Some(hook) => quote! {
+            let mut value = #value;
+            #hook(&mut value, deserializer)?;
+            Ok(value)
+        },
+        None => quote! { Ok(#value) },
+    };
+    let struct_archive_bound = |field: &Field| {
+        if attributes.pod.is_some() {
+            Ok(pod_archive_bound(&rkyv_path, field))
+        } else {
+            archive_bound(&rkyv_path, field)
+        }
+    };
+    let deserialize_field = |field: &Field| {
+        if attributes.compact.is_some() {
+            compact_deserialize(&rkyv_path, field)
+        } else {
+            deserialize(&rkyv_path, field)
+        }
+    };
+
+    if let Some(transparent) = &attributes.transparent {
+        let (member, field) = transparent_field(&input, transparent)?;
+        let mut deserialize_where = where_clause.clone();
+        deserialize_where
+            .predicates
+            .push(archive_bound(&rkyv_path, field)?);
+        deserialize_where
+            .predicates
+            .push(deserialize_bound(&rkyv_path, field)?);
+        let deserialize = deserialize(&rkyv_path, field)?;
+        let finish = finish(quote! {
+            #name {
+                #member: #deserialize(self, deserializer)?,
+            }
+        });
+
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics
+                #rkyv_path::Deserialize<#name #ty_generics, __D>
+                for #rkyv_path::Archived<#name #ty_generics>
+            #deserialize_where
+            {
+                #[inline]
+                fn deserialize(
+                    &self,
+                    deserializer: &mut __D,
+                ) -> ::core::result::Result<
+                    #name #ty_generics,
+                    <__D as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #finish
+                }
+            }
+        });
+    }
+
+    if let Some(evolve) = &attributes.evolve {
+        let fields = evolve_fields(&input, evolve)?;
+        let mut deserialize_where = where_clause.clone();
+        for field in fields.named.iter() {
+            let ty = &field.ty;
+            deserialize_where
+                .predicates
+                .push(parse_quote! { #ty: ::core::default::Default });
+            if is_not_omitted(&field) {
+                deserialize_where
+                    .predicates
+                    .push(archive_bound(&rkyv_path, field)?);
+                deserialize_where
+                    .predicates
+                    .push(deserialize_bound(&rkyv_path, field)?);
+            }
+        }
+
+        // Fields which are missing from the archive were added after it was
+        // written, and get their default values.
+        let deserialize_fields = fields
+            .named
+            .iter()
+            .map(|field| {
+                let name = &field.ident;
+                let ty = &field.ty;
+                let deserialize = deserialize(&rkyv_path, field)?;
+                Ok(quote! {
+                    #name: match self.#name() {
+                        ::core::option::Option::Some(field) => {
+                            #deserialize(field, deserializer)?
+                        }
+                        ::core::option::Option::None => {
+                            <#ty as ::core::default::Default>::default()
+                        }
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let finish = finish(quote! {
+            #name {
+                #(#deserialize_fields,)*
+            }
+        });
+
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics
+                #rkyv_path::Deserialize<#name #ty_generics, __D>
+                for #rkyv_path::Archived<#name #ty_generics>
+            #deserialize_where
+            {
+                #inline
+                fn deserialize(
+                    &self,
+                    deserializer: &mut __D,
+                ) -> ::core::result::Result<
+                    #name #ty_generics,
+                    <__D as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #finish
+                }
+            }
+        });
+    }
+
+    // Pod types which are archived by copying their bytes are deserialized by
+    // copying them back.
+    let copy_deserialize = attributes.pod.is_some().then(|| {
+        // SAFETY: The copy optimization is only enabled when the archived
+        // bytes are the native bytes.
+        let finish = finish(quote! {
+            unsafe {
+                ::core::ptr::read_unaligned(
+                    (self as *const Self).cast::<#name #ty_generics>(),
+                )
+            }
+        });
+        quote! {
+            if <#name #ty_generics as #rkyv_path::Archive>::COPY_OPTIMIZATION
+                .is_enabled()
+            {
+                return { #finish };
+            }
+        }
+    });
+
     let deserialize_impl = match input.data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
                 let mut deserialize_where = where_clause.clone();
                 for field in fields.named.iter().filter(is_not_omitted) {
-                    deserialize_where
-                        .predicates
-                        .push(archive_bound(&rkyv_path, field)?);
+                    if needs_archive_bound(&field) {
+                        deserialize_where
+                            .predicates
+                            .push(struct_archive_bound(field)?);
+                    }
                     deserialize_where
                         .predicates
                         .push(deserialize_bound(&rkyv_path, field)?);
                 }
+                dedup_predicates(attributes, &mut deserialize_where);
 
                 let deserialize_fields = fields
                     .named
                     .iter()
                     .map(|field| {
                         let name = &field.ident;
-                        let deserialize = deserialize(&rkyv_path, field)?;
+                        let deserialize = deserialize_field(field)?;
                         Ok(quote! {
                             #name: #deserialize(&self.#name, deserializer)?
                         })
                     })
                     .collect::<Result<Vec<_>, Error>>()?;
+                let finish = finish(quote! {
+                    #name {
+                        #(#deserialize_fields,)*
+                    }
+                });
 
                 quote! {
                     impl #impl_generics
@@ -82,7 +245,7 @@ fn derive_deserialize_impl(
                         for #rkyv_path::Archived<#name #ty_generics>
                     #deserialize_where
                     {
-                        #[inline]
+                        #inline
                         fn deserialize(
                             &self,
                             deserializer: &mut __D,
@@ -90,9 +253,8 @@ fn derive_deserialize_impl(
                             #name #ty_generics,
                             <__D as #rkyv_path::rancor::Fallible>::Error,
                         > {
-                            Ok(#name {
-                                #(#deserialize_fields,)*
-                            })
+                            #copy_deserialize
+                            #finish
                         }
                     }
                 }
@@ -100,13 +262,16 @@ fn derive_deserialize_impl(
             Fields::Unnamed(ref fields) => {
                 let mut deserialize_where = where_clause.clone();
                 for field in fields.unnamed.iter().filter(is_not_omitted) {
-                    deserialize_where
-                        .predicates
-                        .push(archive_bound(&rkyv_path, field)?);
+                    if needs_archive_bound(&field) {
+                        deserialize_where
+                            .predicates
+                            .push(struct_archive_bound(field)?);
+                    }
                     deserialize_where
                         .predicates
                         .push(deserialize_bound(&rkyv_path, field)?);
                 }
+                dedup_predicates(attributes, &mut deserialize_where);
 
                 let deserialize_fields = fields
                     .unnamed
@@ -114,7 +279,7 @@ fn derive_deserialize_impl(
                     .enumerate()
                     .map(|(i, field)| {
                         let index = Index::from(i);
-                        let deserialize = deserialize(&rkyv_path, field)?;
+                        let deserialize = deserialize_field(field)?;
                         Ok(quote! {
                             #deserialize(
                                 &self.#index,
@@ -123,6 +288,9 @@ fn derive_deserialize_impl(
                         })
                     })
                     .collect::<Result<Vec<_>, Error>>()?;
+                let finish = finish(quote! {
+                    #name(#(#deserialize_fields,)*)
+                });
 
                 quote! {
                     impl #impl_generics
@@ -130,7 +298,7 @@ fn derive_deserialize_impl(
                         for #rkyv_path::Archived<#name #ty_generics>
                     #deserialize_where
                     {
-                        #[inline]
+                        #inline
                         fn deserialize(
                             &self,
                             deserializer: &mut __D,
@@ -138,31 +306,38 @@ fn derive_deserialize_impl(
                             #name #ty_generics,
                             <__D as #rkyv_path::rancor::Fallible>::Error,
                         > {
-                            Ok(#name(
-                                #(#deserialize_fields,)*
-                            ))
+                            #copy_deserialize
+                            #finish
                         }
                     }
                 }
             }
-            Fields::Unit => quote! {
-                impl #impl_generics
-                    #rkyv_path::Deserialize<#name #ty_generics, __D>
-                    for #rkyv_path::Archived<#name #ty_generics>
-                #where_clause
-                {
-                    #[inline]
-                    fn deserialize(
-                        &self,
-                        _: &mut __D,
-                    ) -> ::core::result::Result<
-                        #name #ty_generics,
-                        <__D as #rkyv_path::rancor::Fallible>::Error,
-                    > {
-                        Ok(#name)
+            Fields::Unit => {
+                let deserializer = match attributes.post_deserialize {
+                    Some(_) => quote! { deserializer },
+                    None => quote! { _ },
+                };
+                let finish = finish(quote! { #name });
+
+                quote! {
+                    impl #impl_generics
+                        #rkyv_path::Deserialize<#name #ty_generics, __D>
+                        for #rkyv_path::Archived<#name #ty_generics>
+                    #where_clause
+                    {
+                        #inline
+                        fn deserialize(
+                            &self,
+                            #deserializer: &mut __D,
+                        ) -> ::core::result::Result<
+                            #name #ty_generics,
+                            <__D as #rkyv_path::rancor::Fallible>::Error,
+                        > {
+                            #finish
+                        }
                     }
                 }
-            },
+            }
         },
         Data::Enum(ref data) => {
             let mut deserialize_where = where_clause.clone();
@@ -171,9 +346,11 @@ fn derive_deserialize_impl(
                     Fields::Named(ref fields) => {
                         for field in fields.named.iter().filter(is_not_omitted)
                         {
-                            deserialize_where
-                                .predicates
-                                .push(archive_bound(&rkyv_path, field)?);
+                            if needs_archive_bound(&field) {
+                                deserialize_where
+                                    .predicates
+                                    .push(archive_bound(&rkyv_path, field)?);
+                            }
                             deserialize_where
                                 .predicates
                                 .push(deserialize_bound(&rkyv_path, field)?);
@@ -183,9 +360,11 @@ fn derive_deserialize_impl(
                         for field in
                             fields.unnamed.iter().filter(is_not_omitted)
                         {
-                            deserialize_where
-                                .predicates
-                                .push(archive_bound(&rkyv_path, field)?);
+                            if needs_archive_bound(&field) {
+                                deserialize_where
+                                    .predicates
+                                    .push(archive_bound(&rkyv_path, field)?);
+                            }
                             deserialize_where
                                 .predicates
                                 .push(deserialize_bound(&rkyv_path, field)?);
@@ -194,6 +373,7 @@ fn derive_deserialize_impl(
                     Fields::Unit => (),
                 }
             }
+            dedup_predicates(attributes, &mut deserialize_where);
 
             let deserialize_variants = data
                 .variants
@@ -211,8 +391,7 @@ fn derive_deserialize_impl(
                                 .iter()
                                 .map(|field| {
                                     let name = &field.ident;
-                                    let deserialize =
-                                        deserialize(&rkyv_path, field)?;
+                                    let deserialize = deserialize_field(field)?;
                                     Ok(quote! {
                                         #name: #deserialize(
                                             #name,
@@ -243,8 +422,7 @@ fn derive_deserialize_impl(
                                         &format!("_{}", i),
                                         field.span(),
                                     );
-                                    let deserialize =
-                                        deserialize(&rkyv_path, field)?;
+                                    let deserialize = deserialize_field(field)?;
                                     Ok(quote! {
                                         #deserialize(
                                             #binding,
@@ -265,6 +443,11 @@ fn derive_deserialize_impl(
                     }
                 })
                 .collect::<Result<Vec<_>, Error>>()?;
+            let finish = finish(quote! {
+                match self {
+                    #(#deserialize_variants,)*
+                }
+            });
 
             quote! {
                 impl #impl_generics
@@ -272,7 +455,7 @@ fn derive_deserialize_impl(
                     for #rkyv_path::Archived<#name #ty_generics>
                 #deserialize_where
                 {
-                    #[inline]
+                    #inline
                     fn deserialize(
                         &self,
                         deserializer: &mut __D,
@@ -280,9 +463,7 @@ fn derive_deserialize_impl(
                         #name #ty_generics,
                         <__D as #rkyv_path::rancor::Fallible>::Error,
                     > {
-                        Ok(match self {
-                            #(#deserialize_variants,)*
-                        })
+                        #finish
                     }
                 }
             }
@@ -0,0 +1,256 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Ident, Member, Path};
+
+use crate::{
+    attributes::Attributes,
+    util::{is_not_omitted, members},
+};
+
+fn name_of(ident: &Ident) -> String {
+    ident.to_string().trim_start_matches("r#").to_string()
+}
+
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => name_of(ident),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+fn export_fields(
+    rkyv_path: &Path,
+    name: &str,
+    variant: Option<(u32, String)>,
+    fields: &Fields,
+    bindings: &[impl ToTokens],
+) -> TokenStream {
+    let serde = quote! { #rkyv_path::serde };
+    let len = fields.len();
+    let wrapped = bindings
+        .iter()
+        .map(|binding| quote! { &#rkyv_path::export::Serde(#binding) })
+        .collect::<Vec<_>>();
+
+    match (fields, variant) {
+        (Fields::Unit, None) => quote! {
+            #serde::Serializer::serialize_unit_struct(serializer, #name)
+        },
+        (Fields::Unit, Some((index, variant))) => quote! {
+            #serde::Serializer::serialize_unit_variant(
+                serializer,
+                #name,
+                #index,
+                #variant,
+            )
+        },
+        (Fields::Unnamed(_), None) if len == 1 => {
+            let value = &wrapped[0];
+            quote! {
+                #serde::Serializer::serialize_newtype_struct(
+                    serializer,
+                    #name,
+                    #value,
+                )
+            }
+        }
+        (Fields::Unnamed(_), Some((index, variant))) if len == 1 => {
+            let value = &wrapped[0];
+            quote! {
+                #serde::Serializer::serialize_newtype_variant(
+                    serializer,
+                    #name,
+                    #index,
+                    #variant,
+                    #value,
+                )
+            }
+        }
+        (Fields::Unnamed(_), None) => quote! {
+            let mut state = #serde::Serializer::serialize_tuple_struct(
+                serializer,
+                #name,
+                #len,
+            )?;
+            #(
+                #serde::ser::SerializeTupleStruct::serialize_field(
+                    &mut state,
+                    #wrapped,
+                )?;
+            )*
+            #serde::ser::SerializeTupleStruct::end(state)
+        },
+        (Fields::Unnamed(_), Some((index, variant))) => quote! {
+            let mut state = #serde::Serializer::serialize_tuple_variant(
+                serializer,
+                #name,
+                #index,
+                #variant,
+                #len,
+            )?;
+            #(
+                #serde::ser::SerializeTupleVariant::serialize_field(
+                    &mut state,
+                    #wrapped,
+                )?;
+            )*
+            #serde::ser::SerializeTupleVariant::end(state)
+        },
+        (Fields::Named(_), None) => {
+            let names = members(fields).map(|(member, _)| member_name(&member));
+            quote! {
+                let mut state = #serde::Serializer::serialize_struct(
+                    serializer,
+                    #name,
+                    #len,
+                )?;
+                #(
+                    #serde::ser::SerializeStruct::serialize_field(
+                        &mut state,
+                        #names,
+                        #wrapped,
+                    )?;
+                )*
+                #serde::ser::SerializeStruct::end(state)
+            }
+        }
+        (Fields::Named(_), Some((index, variant))) => {
+            let names = members(fields).map(|(member, _)| member_name(&member));
+            quote! {
+                let mut state = #serde::Serializer::serialize_struct_variant(
+                    serializer,
+                    #name,
+                    #index,
+                    #variant,
+                    #len,
+                )?;
+                #(
+                    #serde::ser::SerializeStructVariant::serialize_field(
+                        &mut state,
+                        #names,
+                        #wrapped,
+                    )?;
+                )*
+                #serde::ser::SerializeStructVariant::end(state)
+            }
+        }
+    }
+}
+
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let where_clause = input.generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_not_omitted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::export::Export
+                });
+            }
+        }
+        Data::Enum(data) => {
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_not_omitted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::export::Export
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Export cannot be derived for unions",
+            ))
+        }
+    }
+
+    let type_name = name_of(&input.ident);
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let bindings = members(&data.fields)
+                .map(|(member, _)| quote! { &self.#member })
+                .collect::<Vec<_>>();
+            export_fields(&rkyv_path, &type_name, None, &data.fields, &bindings)
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                let arms =
+                    data.variants.iter().enumerate().map(|(index, variant)| {
+                        let ident = &variant.ident;
+                        let bindings = (0..variant.fields.len())
+                            .map(|i| format_ident!("__field{}", i))
+                            .collect::<Vec<_>>();
+                        let pattern = match &variant.fields {
+                            Fields::Unit => quote! { Self::#ident },
+                            fields => {
+                                let members =
+                                    members(fields).map(|(member, _)| member);
+                                quote! {
+                                    Self::#ident { #(#members: #bindings),* }
+                                }
+                            }
+                        };
+                        let export = export_fields(
+                            &rkyv_path,
+                            &type_name,
+                            Some((index as u32, name_of(ident))),
+                            &variant.fields,
+                            &bindings,
+                        );
+                        quote! {
+                            #pattern => { #export }
+                        }
+                    });
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::export::Export for #name #ty_generics
+        #where_clause
+        {
+            fn export<__S: #rkyv_path::serde::Serializer>(
+                &self,
+                serializer: __S,
+            ) -> ::core::result::Result<
+                <__S as #rkyv_path::serde::Serializer>::Ok,
+                <__S as #rkyv_path::serde::Serializer>::Error,
+            > {
+                #body
+            }
+        }
+
+        impl #impl_generics #rkyv_path::serde::Serialize
+            for #name #ty_generics
+        #where_clause
+        {
+            #[inline]
+            fn serialize<__S: #rkyv_path::serde::Serializer>(
+                &self,
+                serializer: __S,
+            ) -> ::core::result::Result<
+                <__S as #rkyv_path::serde::Serializer>::Ok,
+                <__S as #rkyv_path::serde::Serializer>::Error,
+            > {
+                #rkyv_path::export::Export::export(self, serializer)
+            }
+        }
+    })
+}
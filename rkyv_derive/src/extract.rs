@@ -0,0 +1,228 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Index};
+
+use crate::{
+    attributes::Attributes,
+    repr::Repr,
+    util::{is_not_omitted, members},
+};
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let mut impl_input_generics = input.generics.clone();
+    impl_input_generics.params.push(parse_quote! {
+        __S: #rkyv_path::rancor::Fallible + ?Sized
+    });
+    let where_clause = impl_input_generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_not_omitted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::Extract<__S>
+                });
+            }
+        }
+        Data::Enum(data) => {
+            if !Repr::from_attrs(&input.attrs)?.is_enum_well_defined() {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "enum must be `repr(u8/i8)` or `repr(C, u8/i8)` to \
+                     implement `Extract`",
+                ));
+            }
+
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_not_omitted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::Extract<__S>
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Extract cannot be derived for unions",
+            ))
+        }
+    }
+
+    let resolve_field = quote! {
+        #rkyv_path::extract::resolve_field_unchecked
+    };
+    let resolver_ty = |fields: &Fields| {
+        let tys = fields.iter().map(|field| {
+            let ty = &field.ty;
+            quote! { <#ty as #rkyv_path::Extract<__S>>::Resolver }
+        });
+        quote! { (#(#tys,)*) }
+    };
+
+    let (resolver, extract, resolve) = match &input.data {
+        Data::Struct(data) => {
+            let resolver = resolver_ty(&data.fields);
+            let members = members(&data.fields)
+                .map(|(member, _)| member)
+                .collect::<Vec<_>>();
+            let resolvers = (0..members.len())
+                .map(|i| format_ident!("__resolver{}", i))
+                .collect::<Vec<_>>();
+            let tys = data.fields.iter().map(|field| &field.ty);
+
+            let extract = quote! {
+                ::core::result::Result::Ok((#(
+                    #rkyv_path::Extract::<__S>::extract(
+                        &self.#members,
+                        serializer,
+                    )?,
+                )*))
+            };
+            let resolve = quote! {
+                let (#(#resolvers,)*) = resolver;
+                #(
+                    unsafe {
+                        #resolve_field::<_, #tys, __S>(
+                            self,
+                            &self.#members,
+                            #resolvers,
+                            out,
+                        );
+                    }
+                )*
+            };
+            (resolver, extract, resolve)
+        }
+        Data::Enum(data) => {
+            let variant_resolvers =
+                data.variants.iter().map(|v| resolver_ty(&v.fields));
+            let resolver = quote! {
+                (#(::core::option::Option<#variant_resolvers>,)*)
+            };
+
+            let bindings = |fields: &Fields| {
+                (0..fields.len())
+                    .map(|i| format_ident!("__field{}", i))
+                    .collect::<Vec<_>>()
+            };
+            let pattern = |ident, fields: &Fields, bindings: &[_]| match fields
+            {
+                Fields::Unit => quote! { Self::#ident },
+                fields => {
+                    let members = members(fields).map(|(member, _)| member);
+                    quote! { Self::#ident { #(#members: #bindings),* } }
+                }
+            };
+
+            let variant_count = data.variants.len();
+            let extract_arms =
+                data.variants.iter().enumerate().map(|(i, variant)| {
+                    let bindings = bindings(&variant.fields);
+                    let pattern =
+                        pattern(&variant.ident, &variant.fields, &bindings);
+                    let options = (0..variant_count).map(|j| {
+                        if i == j {
+                            quote! {
+                                ::core::option::Option::Some((#(
+                                    #rkyv_path::Extract::<__S>::extract(
+                                        #bindings,
+                                        serializer,
+                                    )?,
+                                )*))
+                            }
+                        } else {
+                            quote! { ::core::option::Option::None }
+                        }
+                    });
+                    quote! {
+                        #pattern => ::core::result::Result::Ok((#(#options,)*))
+                    }
+                });
+            let resolve_arms =
+                data.variants.iter().enumerate().map(|(i, variant)| {
+                    let index = Index::from(i);
+                    let bindings = bindings(&variant.fields);
+                    let pattern =
+                        pattern(&variant.ident, &variant.fields, &bindings);
+                    let resolvers = (0..bindings.len())
+                        .map(|i| format_ident!("__resolver{}", i))
+                        .collect::<Vec<_>>();
+                    let tys = variant.fields.iter().map(|field| &field.ty);
+                    quote! {
+                        #pattern => {
+                            let (#(#resolvers,)*) = resolver.#index.unwrap();
+                            #(
+                                unsafe {
+                                    #resolve_field::<_, #tys, __S>(
+                                        self,
+                                        #bindings,
+                                        #resolvers,
+                                        out,
+                                    );
+                                }
+                            )*
+                        }
+                    }
+                });
+
+            let extract = if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! { match self { #(#extract_arms,)* } }
+            };
+            // The archived enum is well-defined, so its tag is the first byte
+            // and can be copied from the original value.
+            let resolve = if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    unsafe {
+                        let tag = *(self as *const Self).cast::<u8>();
+                        out.cast_unchecked::<u8>().write(tag);
+                    }
+                    match self { #(#resolve_arms,)* }
+                }
+            };
+            (resolver, extract, resolve)
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (impl_generics, _, where_clause) = impl_input_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Extract<__S> for #name #ty_generics
+        #where_clause
+        {
+            type Resolver = #resolver;
+
+            #[inline]
+            #[allow(unused_variables)]
+            fn extract(
+                &self,
+                serializer: &mut __S,
+            ) -> ::core::result::Result<
+                Self::Resolver,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #extract
+            }
+
+            #[inline]
+            #[allow(unused_variables)]
+            fn resolve_extracted(
+                &self,
+                resolver: Self::Resolver,
+                out: #rkyv_path::Place<Self>,
+            ) {
+                #resolve
+            }
+        }
+    })
+}
@@ -6,13 +6,22 @@
     rustdoc::missing_crate_level_docs
 )]
 
+mod analyze;
 mod archive;
+mod archive_naming;
+mod archived_module;
+mod archived_size;
 mod attributes;
 mod deserialize;
+mod export;
+mod extract;
 mod portable;
+mod redact;
 mod repr;
+mod schema_hash;
 mod serde;
 mod serialize;
+mod stable_hash;
 mod util;
 
 extern crate proc_macro;
@@ -47,10 +56,14 @@ pub fn derive_portable(
 ///
 /// - `archived = "..."`: Changes the name of the generated archived type to the
 ///   given value. By default, archived types are named "Archived" + `the name
-///   of the type`.
+///   of the type`. Any `{}` in the value is replaced with the name of the type,
+///   so `archived = "{}Archived"` names the archived type of `Foo`
+///   `FooArchived`. See [`macro@archive_naming`] to change the names of many
+///   types at once.
 /// - `resolver = "..."`: Changes the name of the generated resolver type to the
 ///   given value. By default, resolver types are named `the name of the type` +
-///   "Resolver".
+///   "Resolver". Like `archived`, any `{}` is replaced with the name of the
+///   type.
 /// - `repr(...)`: *Deprecated, use `#[archive_attr(repr(...))]` instead.* Sets
 ///   the representation for the archived type to the given representation.
 ///   Available representation options may vary depending on features and type
@@ -68,6 +81,74 @@ pub fn derive_portable(
 ///   enable safe deserialization. Requires `validation` feature. Not compatible
 ///   with `as = "..."`. In that case, use `#[derive(CheckBytes)]` on the
 ///   archived type, and include a `use rkyv::bytecheck` statement.
+/// - `builder`: Generates a builder for the archived type which serializes each
+///   field as it is set and then writes the archived value. The builder is
+///   named "Archived" + `the name of the type` + "Builder". Only supported for
+///   non-generic structs with named fields, and not compatible with `as =
+///   "..."`. See `rkyv::builder` for more information.
+/// - `offsets`: Generates constants on the archived type holding the offset of
+///   each field in bytes. The constants are named "OFFSET_" + `the name of the
+///   field in uppercase`, or "OFFSET_" + `the index of the field` for tuple
+///   structs. Only supported for structs, and not compatible with `as = "..."`.
+/// - `preserve_order`: Lays out the fields of the archived type in declaration
+///   order. This is the default, and may be used to document that a layout must
+///   match an external format.
+/// - `reorder = "size_desc"`: Sorts the fields of the archived type to reduce
+///   padding. Fields whose types are primitives or arrays of primitives are
+///   sorted by the size of those primitives in descending order. All other
+///   fields are placed first in declaration order, since their sizes are not
+///   known to the derive. Only supported for structs with named fields.
+/// - `module = "..."`: Generates the archived type and resolver in a hidden
+///   module instead of next to the type. Use [`archived_module!`] to gather the
+///   archived types of several types into one module with the given name. Not
+///   compatible with `as = "..."`.
+/// - `compact`: Generates less code for the derived impls, which reduces
+///   compile times for large schemas. Fields are resolved, serialized, and
+///   deserialized by calling shared helper functions, `Archive` bounds are only
+///   added for fields whose types mention a generic parameter, duplicate bounds
+///   are removed, and the generated methods are not marked `#[inline]`. The
+///   archived type is the same with or without `compact`.
+/// - `pod`: Archives a plain-old-data struct without generating a resolver. The
+///   struct must be `repr(C)` or `repr(transparent)`, and every field must have
+///   a unit resolver (like primitives and arrays of primitives). Pod types
+///   serialize without doing anything, and when none of their fields need
+///   converting and neither they nor their archived types have padding, they
+///   are archived and deserialized by copying their bytes. Slices of them are
+///   then serialized with a single copy. Not compatible with `as = "..."`,
+///   `builder`, `reorder`, or `#[with(...)]` fields.
+/// - `bulk_copy`: Enables the copy optimization for the type, so slices and
+///   arrays of it are serialized with a single copy. The type must also
+///   implement the unsafe `BulkCopy` trait. Generic types may need
+///   `archive_bounds(...)` to require `BulkCopy` for their parameters. Not
+///   compatible with `pod`, which detects whether it can be copied.
+/// - `try_from_tag`: Implements `TryFrom<u8>` for a fieldless enum and its
+///   archived enum, and `From` for converting the enum into its `u8` tag. The
+///   archived enum also gets a `tag` method. Conversions from integers fail
+///   with `convert::InvalidTag` if no variant has the tag, so dense integer
+///   tags read from elsewhere can be checked before they are used as enums. Not
+///   compatible with `as = "..."`.
+/// - `pre_serialize = "..."`: Calls the given function with the value and the
+///   serializer before the value is serialized, e.g. to check invariants. The
+///   function must have the signature `fn(&T, &mut S) -> Result<(), S::Error>`
+///   for every serializer `S` that the type is serialized with.
+/// - `post_deserialize = "..."`: Calls the given function with the deserialized
+///   value and the deserializer after the value is deserialized, e.g. to
+///   recompute caches or normalize it. The function must have the signature
+///   `fn(&mut T, &mut D) -> Result<(), D::Error>` for every deserializer `D`
+///   that the type is deserialized with.
+/// - `transparent`: Archives a struct with exactly one field as that field. No
+///   archived or resolver types are generated. Instead, the archived type and
+///   resolver are those of the field, so `Archived<Meters>` is `Archived<f64>`
+///   for `struct Meters(f64)`. Not compatible with arguments which customize
+///   the archived type.
+/// - `evolve`: Archives the struct as a table of pointers to its fields, so
+///   fields can be added to the end of the struct without breaking existing
+///   archives. The archived type has an accessor method for each field which
+///   returns `None` if the archive was written before the field was added, and
+///   deserializing fills those fields with their `Default` values. Only
+///   supported for non-generic structs with named fields, and not compatible
+///   with `as = "..."`, `compare(...)`, `builder`, `offsets`, `reorder`, `pod`,
+///   `bulk_copy`, or `module = "..."`. See `rkyv::evolve` for more information.
 /// - `as = "..."`: Instead of generating a separate archived type, this type
 ///   will archive as the named type. This is useful for types which are generic
 ///   over their parameters.
@@ -97,6 +178,24 @@ pub fn derive_portable(
 /// attribute. Multiple wrappers can be used, and they are applied in reverse
 /// order (i.e. `#[with(A, B, C)]` will archive `MyType` as
 /// `With<With<With<MyType, C>, B, A>`).
+///
+/// # Substituted archived types
+///
+/// A field's archived type can be replaced with a user-defined type by adding
+/// `#[archive(archived = "...", resolver = "...")]` to the field. The archived
+/// type must implement `ArchiveFrom` for the field's type, and its resolver
+/// must be the given resolver. The resolver may be omitted, in which case the
+/// one chosen by the `ArchiveFrom` impl is used. Substituted fields may not
+/// also use `#[with(...)]`.
+///
+/// # Trait objects
+///
+/// When the `rkyv_dyn` feature is enabled, fields of type `Box<dyn Trait>` or
+/// `Arc<dyn Trait>` without a `#[with(...)]` attribute are archived with
+/// `rkyv_dyn::with::AsDyn`. The trait must be annotated with
+/// `#[archive_dyn]`, and only impls which are also annotated with
+/// `#[archive_dyn]` can be serialized. Trait objects with additional bounds
+/// like `dyn Trait + Send` are not detected and still require a wrapper.
 #[proc_macro_derive(
     Archive,
     attributes(archive, archive_attr, omit_bounds, with)
@@ -113,6 +212,151 @@ pub fn derive_archive(
     }
 }
 
+/// Derives `Analyze` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(Analyze))]`. Every field must implement `Analyze`,
+/// and each field is recorded in the report as a separate value.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(Analyze, attributes(archive, omit_bounds))]
+pub fn derive_analyze(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match analyze::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `ArchivedSize` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(ArchivedSize))]`. Every field must implement
+/// `ArchivedSize`, and the out-of-line size of the labeled type is the sum of
+/// the out-of-line sizes of its fields.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(ArchivedSize, attributes(archive, omit_bounds))]
+pub fn derive_archived_size(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match archived_size::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `Export` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(Export))]`. Every field must implement `Export`. This
+/// also implements `serde::Serialize` for the labeled type, which has the same
+/// shape as the output of `#[derive(serde::Serialize)]`.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(Export, attributes(archive, omit_bounds))]
+pub fn derive_export(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match export::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `Extract` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(Extract))]`. Every field must implement `Extract`,
+/// and enums must have a well-defined representation.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(Extract, attributes(archive, omit_bounds))]
+pub fn derive_extract(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match extract::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `Redact` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(Redact))]`. Fields which are marked with `#[redact]`
+/// (typically added with `#[archive_attr(redact)]`) are redacted, and every
+/// marked field must implement `Redact`. Unmarked fields are left unchanged.
+///
+/// This macro also supports the `#[archive]` attribute.
+#[proc_macro_derive(Redact, attributes(archive, omit_bounds, redact))]
+pub fn derive_redact(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match redact::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `SchemaHash` for the labeled type.
+///
+/// This is typically applied to archived types with
+/// `#[archive_attr(derive(SchemaHash))]`. Every field must implement
+/// `SchemaHash`, and the schema hash covers the layout of the labeled type
+/// along with the name, offset, and schema hash of each of its fields.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(SchemaHash, attributes(archive, omit_bounds))]
+pub fn derive_schema_hash(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match schema_hash::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `StableHash` for the labeled type.
+///
+/// Unlike the other helper derives, this is applied to the unarchived type.
+/// Every field must implement `StableHash`, and the digest of the labeled type
+/// covers the digests of its fields in order. Enums also hash the index of the
+/// variant.
+///
+/// This macro also supports the `#[archive]` and `#[omit_bounds]` attributes.
+#[proc_macro_derive(StableHash, attributes(archive, omit_bounds))]
+pub fn derive_stable_hash(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut derive_input = parse_macro_input!(input as DeriveInput);
+    serde::receiver::replace_receiver(&mut derive_input);
+
+    match stable_hash::derive(derive_input) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
 /// Derives `Serialize` for the labeled type.
 ///
 /// This macro also supports the `#[archive]`, `#[omit_bounds]`, and `#[with]`
@@ -146,3 +390,97 @@ pub fn derive_deserialize(
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+/// Declares a module which re-exports the archived types and resolvers of
+/// types archived with `#[archive(module = "...")]`.
+///
+/// The module must have the same name as the `module` argument of the types,
+/// and be declared in the same module as them.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{archived_module, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(module = "archived")]
+/// pub struct Point {
+///     pub x: i32,
+///     pub y: i32,
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// #[archive(module = "archived")]
+/// pub struct Line {
+///     pub from: Point,
+///     pub to: Point,
+/// }
+///
+/// archived_module! {
+///     /// The archived counterparts of the geometry types.
+///     pub mod archived { Point, Line }
+/// }
+///
+/// fn length_x(line: &archived::ArchivedLine) -> i32 {
+///     line.to.x.to_native() - line.from.x.to_native()
+/// }
+/// ```
+#[proc_macro]
+pub fn archived_module(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as archived_module::Input);
+    archived_module::expand(input).into()
+}
+
+/// Changes the names of the archived types and resolvers generated for every
+/// type in a module.
+///
+/// This takes the same `archived = "..."` and `resolver = "..."` arguments as
+/// `#[archive(...)]`, and applies them to every type in the module which
+/// derives `Archive`, including types in nested inline modules. Types which
+/// specify their own names are left as they are. Applying this to the
+/// top-level module of a schema applies the names to the whole schema.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::archive_naming;
+///
+/// #[archive_naming(archived = "{}Archived", resolver = "{}Res")]
+/// mod schema {
+///     use rkyv::{Archive, Serialize};
+///
+///     #[derive(Archive, Serialize)]
+///     pub struct Point {
+///         pub x: i32,
+///         pub y: i32,
+///     }
+///
+///     #[derive(Archive, Serialize)]
+///     #[archive(archived = "ArchivedPath")]
+///     pub struct Path {
+///         pub points: Vec<Point>,
+///     }
+/// }
+///
+/// fn first_x(path: &schema::ArchivedPath) -> Option<i32> {
+///     let point: &schema::PointArchived = path.points.first()?;
+///     Some(point.x.to_native())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn archive_naming(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut naming = archive_naming::Naming::default();
+    let parser = syn::meta::parser(|meta| naming.parse_meta(meta));
+    parse_macro_input!(attr with parser);
+    let module = parse_macro_input!(item as syn::ItemMod);
+
+    match archive_naming::expand(naming, module) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,125 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Error, Field, Fields};
+
+use crate::{attributes::Attributes, util::members};
+
+fn is_redacted(field: &&Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("redact"))
+}
+
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let where_clause = input.generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_redacted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::redact::Redact
+                });
+            }
+        }
+        Data::Enum(data) => {
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_redacted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::redact::Redact
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Redact cannot be derived for unions",
+            ))
+        }
+    }
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let redactions = members(&data.fields)
+                .filter(|(_, field)| is_redacted(field))
+                .map(|(member, _)| {
+                    quote! {
+                        #rkyv_path::redact::Redact::redact(unsafe {
+                            ::core::pin::Pin::new_unchecked(
+                                &mut this.#member,
+                            )
+                        });
+                    }
+                });
+            quote! {
+                let this = unsafe { ::core::pin::Pin::get_unchecked_mut(self) };
+                #(#redactions)*
+            }
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                let arms = data.variants.iter().map(|variant| {
+                    let ident = &variant.ident;
+                    let bindings = (0..variant.fields.len())
+                        .map(|i| format_ident!("__field{}", i))
+                        .collect::<Vec<_>>();
+                    let pattern = match &variant.fields {
+                        Fields::Unit => quote! { Self::#ident },
+                        fields => {
+                            let members =
+                                members(fields).map(|(member, _)| member);
+                            quote! {
+                                Self::#ident { #(#members: #bindings),* }
+                            }
+                        }
+                    };
+                    let redactions = variant
+                        .fields
+                        .iter()
+                        .zip(bindings.iter())
+                        .filter(|(field, _)| is_redacted(field))
+                        .map(|(_, binding)| {
+                            quote! {
+                                #rkyv_path::redact::Redact::redact(unsafe {
+                                    ::core::pin::Pin::new_unchecked(#binding)
+                                });
+                            }
+                        });
+                    quote! {
+                        #pattern => {
+                            #(#redactions)*
+                        }
+                    }
+                });
+                quote! {
+                    match unsafe { ::core::pin::Pin::get_unchecked_mut(self) } {
+                        #(#arms,)*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::redact::Redact for #name #ty_generics
+        #where_clause
+        {
+            #[allow(unused_variables)]
+            fn redact(self: ::core::pin::Pin<&mut Self>) {
+                #body
+            }
+        }
+    })
+}
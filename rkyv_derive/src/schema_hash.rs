@@ -0,0 +1,121 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Member};
+
+use crate::{
+    attributes::Attributes,
+    util::{is_not_omitted, members},
+};
+
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => {
+            ident.to_string().trim_start_matches("r#").to_string()
+        }
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let attributes = Attributes::parse(&input)?;
+    let rkyv_path = attributes.crate_path();
+
+    let where_clause = input.generics.make_where_clause();
+    match &input.data {
+        Data::Struct(data) => {
+            for field in data.fields.iter().filter(is_not_omitted) {
+                let ty = &field.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: #rkyv_path::SchemaHash
+                });
+            }
+        }
+        Data::Enum(data) => {
+            for variant in data.variants.iter() {
+                for field in variant.fields.iter().filter(is_not_omitted) {
+                    let ty = &field.ty;
+                    where_clause.predicates.push(parse_quote! {
+                        #ty: #rkyv_path::SchemaHash
+                    });
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "SchemaHash cannot be derived for unions",
+            ))
+        }
+    }
+
+    // Struct fields are hashed with their offsets. The offsets of enum
+    // variant fields can't be computed, but they follow from the layouts of
+    // the fields because archived enums have a well-defined representation.
+    let writes = match &input.data {
+        Data::Struct(data) => {
+            let fields = members(&data.fields).map(|(member, field)| {
+                let name = member_name(&member);
+                let ty = &field.ty;
+                quote! {
+                    .write_str(#name)
+                    .write_usize(::core::mem::offset_of!(Self, #member))
+                    .write_u64(
+                        <#ty as #rkyv_path::SchemaHash>::SCHEMA_HASH,
+                    )
+                }
+            });
+            quote! {
+                .write_str("struct")
+                .write_layout::<Self>()
+                #(#fields)*
+            }
+        }
+        Data::Enum(data) => {
+            let variants = data.variants.iter().map(|variant| {
+                let variant_name = variant.ident.to_string();
+                let fields = match &variant.fields {
+                    Fields::Unit => Vec::new(),
+                    fields => members(fields)
+                        .map(|(member, field)| {
+                            let name = member_name(&member);
+                            let ty = &field.ty;
+                            quote! {
+                                .write_str(#name)
+                                .write_u64(
+                                    <#ty as #rkyv_path::SchemaHash>
+                                        ::SCHEMA_HASH,
+                                )
+                            }
+                        })
+                        .collect(),
+                };
+                let len = fields.len();
+                quote! {
+                    .write_str(#variant_name)
+                    .write_usize(#len)
+                    #(#fields)*
+                }
+            });
+            quote! {
+                .write_str("enum")
+                .write_layout::<Self>()
+                #(#variants)*
+            }
+        }
+        Data::Union(_) => unreachable!(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::SchemaHash for #name #ty_generics
+        #where_clause
+        {
+            const SCHEMA_HASH: u64 = #rkyv_path::schema::SchemaHasher::new()
+                #writes
+                .finish();
+        }
+    })
+}
@@ -2,12 +2,16 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse_quote, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput,
-    Error, Fields, Generics, Ident, Index,
+    Error, Field, Fields, Generics, Ident, Index,
 };
 
 use crate::{
     attributes::Attributes,
-    util::{is_not_omitted, serialize, serialize_bound, strip_raw},
+    util::{
+        compact_serialize, dedup_predicates, evolve_fields, evolve_serialize,
+        inline_attr, is_not_omitted, needs_archive_bound, pod_archive_bound,
+        serialize, serialize_bound, transparent_field,
+    },
 };
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -20,6 +24,7 @@ fn derive_serialize_impl(
     attributes: &Attributes,
 ) -> Result<TokenStream, Error> {
     let rkyv_path = attributes.crate_path();
+    let needs_archive_bound = needs_archive_bound(attributes, &input.generics);
 
     let where_clause = input.generics.make_where_clause();
     if let Some(ref bounds) = attributes.archive_bounds {
@@ -51,10 +56,139 @@ fn derive_serialize_impl(
     let (_, ty_generics, where_clause) = input.generics.split_for_impl();
     let where_clause = where_clause.unwrap();
 
-    let resolver = attributes.resolver.as_ref().map_or_else(
-        || Ident::new(&format!("{}Resolver", strip_raw(name)), name.span()),
-        |value| value.clone(),
-    );
+    let resolver = attributes.resolver_name(name)?;
+    let resolver = match attributes.archived_module(name) {
+        Some(module) => quote! { #module::#resolver },
+        None => quote! { #resolver },
+    };
+
+    let inline = inline_attr(attributes);
+    let pre_serialize = attributes.pre_serialize.as_ref().map(|hook| {
+        quote! { #hook(self, serializer)?; }
+    });
+    let serialize_field = |field: &Field| {
+        if attributes.compact.is_some() {
+            compact_serialize(&rkyv_path, field)
+        } else {
+            serialize(&rkyv_path, field)
+        }
+    };
+
+    if let Some(transparent) = &attributes.transparent {
+        let (member, field) = transparent_field(&input, transparent)?;
+        let mut serialize_where = where_clause.clone();
+        serialize_where
+            .predicates
+            .push(serialize_bound(&rkyv_path, field)?);
+        let serialize = serialize(&rkyv_path, field)?;
+
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #rkyv_path::Serialize<__S>
+                for #name #ty_generics
+            #serialize_where
+            {
+                #[inline]
+                fn serialize(
+                    &self,
+                    serializer: &mut __S,
+                ) -> ::core::result::Result<
+                    Self::Resolver,
+                    <__S as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #pre_serialize
+                    #serialize(&self.#member, serializer)
+                }
+            }
+        });
+    }
+
+    if attributes.pod.is_some() {
+        let mut serialize_where = where_clause.clone();
+        if let Data::Struct(ref data) = input.data {
+            for field in data.fields.iter().filter(needs_archive_bound) {
+                serialize_where
+                    .predicates
+                    .push(pod_archive_bound(&rkyv_path, field));
+            }
+        }
+        dedup_predicates(attributes, &mut serialize_where);
+        let serializer = match pre_serialize {
+            Some(_) => quote! { serializer },
+            None => quote! { _ },
+        };
+
+        // Pod types don't have anything to serialize.
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #rkyv_path::Serialize<__S>
+                for #name #ty_generics
+            #serialize_where
+            {
+                #inline
+                fn serialize(
+                    &self,
+                    #serializer: &mut __S,
+                ) -> ::core::result::Result<
+                    Self::Resolver,
+                    <__S as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #pre_serialize
+                    Ok(())
+                }
+            }
+        });
+    }
+
+    if let Some(evolve) = &attributes.evolve {
+        let fields = evolve_fields(&input, evolve)?;
+        let mut serialize_where = where_clause.clone();
+        serialize_where.predicates.push(parse_quote! {
+            __S: #rkyv_path::ser::Allocator + #rkyv_path::ser::Writer
+        });
+        for field in fields.named.iter().filter(is_not_omitted) {
+            serialize_where
+                .predicates
+                .push(serialize_bound(&rkyv_path, field)?);
+        }
+
+        let positions = fields
+            .named
+            .iter()
+            .map(|field| {
+                let name = &field.ident;
+                let serialize = evolve_serialize(&rkyv_path, field)?;
+                Ok(quote! { #serialize(&self.#name, serializer)? })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let len = positions.len();
+
+        // Each field is archived on its own, and then the table which points
+        // to them.
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #rkyv_path::Serialize<__S>
+                for #name #ty_generics
+            #serialize_where
+            {
+                #inline
+                fn serialize(
+                    &self,
+                    serializer: &mut __S,
+                ) -> ::core::result::Result<
+                    Self::Resolver,
+                    <__S as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #pre_serialize
+                    let positions: [usize; #len] = [#(#positions,)*];
+                    Ok(#resolver {
+                        fields: #rkyv_path::evolve::ArchivedFieldTable::
+                            serialize_from_positions(&positions, serializer)?,
+                    })
+                }
+            }
+        });
+    }
 
     let serialize_impl =
         match input.data {
@@ -66,10 +200,11 @@ fn derive_serialize_impl(
                             .predicates
                             .push(serialize_bound(&rkyv_path, field)?);
                     }
+                    dedup_predicates(attributes, &mut serialize_where);
 
                     let resolver_values = fields.named.iter().map(|field| {
                     let name = &field.ident;
-                    let serialize = serialize(&rkyv_path, field)?;
+                    let serialize = serialize_field(field)?;
                     Ok(quote! { #name: #serialize(&self.#name, serializer)? })
                 }).collect::<Result<Vec<_>, Error>>()?;
 
@@ -78,7 +213,7 @@ fn derive_serialize_impl(
                             for #name #ty_generics
                         #serialize_where
                         {
-                            #[inline]
+                            #inline
                             fn serialize(
                                 &self,
                                 serializer: &mut __S
@@ -86,6 +221,7 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
+                                #pre_serialize
                                 Ok(#resolver {
                                     #(#resolver_values,)*
                                 })
@@ -100,6 +236,7 @@ fn derive_serialize_impl(
                             .predicates
                             .push(serialize_bound(&rkyv_path, field)?);
                     }
+                    dedup_predicates(attributes, &mut serialize_where);
 
                     let resolver_values = fields
                         .unnamed
@@ -107,7 +244,7 @@ fn derive_serialize_impl(
                         .enumerate()
                         .map(|(i, field)| {
                             let index = Index::from(i);
-                            let serialize = serialize(&rkyv_path, field)?;
+                            let serialize = serialize_field(field)?;
                             Ok(quote! { #serialize(&self.#index, serializer)? })
                         })
                         .collect::<Result<Vec<_>, Error>>()?;
@@ -117,7 +254,7 @@ fn derive_serialize_impl(
                             for #name #ty_generics
                         #serialize_where
                         {
-                            #[inline]
+                            #inline
                             fn serialize(
                                 &self,
                                 serializer: &mut __S,
@@ -125,6 +262,7 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
+                                #pre_serialize
                                 Ok(#resolver(
                                     #(#resolver_values,)*
                                 ))
@@ -138,7 +276,7 @@ fn derive_serialize_impl(
                             for #name #ty_generics
                         #where_clause
                         {
-                            #[inline]
+                            #inline
                             fn serialize(
                                 &self,
                                 serializer: &mut __S,
@@ -146,6 +284,7 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
+                                #pre_serialize
                                 Ok(#resolver)
                             }
                         }
@@ -177,6 +316,7 @@ fn derive_serialize_impl(
                         Fields::Unit => (),
                     }
                 }
+                dedup_predicates(attributes, &mut serialize_where);
 
                 let serialize_arms = data.variants.iter().map(|v| {
                 let variant = &v.ident;
@@ -185,7 +325,7 @@ fn derive_serialize_impl(
                         let bindings = fields.named.iter().map(|f| &f.ident);
                         let fields = fields.named.iter().map(|field| {
                             let name = &field.ident;
-                            let serialize = serialize(&rkyv_path, field)?;
+                            let serialize = serialize_field(field)?;
                             Ok(quote! {
                                 #name: #serialize(#name, serializer)?
                             })
@@ -215,7 +355,7 @@ fn derive_serialize_impl(
                                     &format!("_{}", i),
                                     field.span(),
                                 );
-                                let serialize = serialize(&rkyv_path, field)?;
+                                let serialize = serialize_field(field)?;
                                 Ok(quote! {
                                     #serialize(#binding, serializer)?
                                 })
@@ -237,7 +377,7 @@ fn derive_serialize_impl(
                         for #name #ty_generics
                     #serialize_where
                     {
-                        #[inline]
+                        #inline
                         fn serialize(
                             &self,
                             serializer: &mut __S,
@@ -245,6 +385,7 @@ fn derive_serialize_impl(
                             <Self as #rkyv_path::Archive>::Resolver,
                             <__S as #rkyv_path::rancor::Fallible>::Error,
                         > {
+                            #pre_serialize
                             Ok(match self {
                                 #(#serialize_arms,)*
                             })
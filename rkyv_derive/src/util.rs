@@ -1,10 +1,14 @@
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens as _};
 use syn::{
-    parse_quote, Error, Field, Fields, Index, Member, Meta, Path, Type,
+    parse_quote, Attribute, Data, DeriveInput, Error, Field, Fields,
+    FieldsNamed, GenericArgument, GenericParam, Generics, Index, LitStr,
+    Member, Meta, Path, PathArguments, Type, TypeParamBound, WhereClause,
     WherePredicate,
 };
 
+use crate::attributes::{try_set_attribute, Attributes};
+
 pub fn strip_raw(ident: &Ident) -> String {
     let as_string = ident.to_string();
     as_string
@@ -13,6 +17,15 @@ pub fn strip_raw(ident: &Ident) -> String {
         .unwrap_or(as_string)
 }
 
+// The name of the hidden module that the archived types of `name` are
+// generated in with `module = "..."`.
+pub fn archived_module_name(module: &Ident, name: &Ident) -> Ident {
+    Ident::new(
+        &format!("__{}_{}", strip_raw(module), strip_raw(name)),
+        name.span(),
+    )
+}
+
 pub fn is_not_omitted(f: &&Field) -> bool {
     f.attrs.iter().all(|attr| {
         if let Meta::Path(path) = &attr.meta {
@@ -23,6 +36,98 @@ pub fn is_not_omitted(f: &&Field) -> bool {
     })
 }
 
+// Returns whether any of the given tokens name `Self` or one of the given
+// generic parameters.
+fn mentions_any(tokens: TokenStream, params: &[Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => ident == "Self" || params.contains(&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), params),
+        _ => false,
+    })
+}
+
+// Returns a filter for the fields which need `Archive` bounds.
+//
+// Fields with `#[omit_bounds]` never get bounds. Compact derives also skip
+// fields whose types don't mention any generic parameters: those bounds are
+// checked when the impl is type checked, so they only add tokens.
+pub fn needs_archive_bound(
+    attributes: &Attributes,
+    generics: &Generics,
+) -> impl Fn(&&Field) -> bool {
+    let compact = attributes.compact.is_some();
+    let params = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => param.ident.clone(),
+            GenericParam::Lifetime(param) => param.lifetime.ident.clone(),
+            GenericParam::Const(param) => param.ident.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    move |field| {
+        if !is_not_omitted(field) {
+            return false;
+        }
+        if !compact {
+            return true;
+        }
+        mentions_any(field.ty.to_token_stream(), &params)
+            || field
+                .attrs
+                .iter()
+                .filter(|attr| is_with_attr(attr))
+                .any(|attr| mentions_any(attr.to_token_stream(), &params))
+    }
+}
+
+// Removes duplicate predicates from a where clause. Compact derives use this
+// so that fields of the same type only add one bound.
+pub fn dedup_predicates(
+    attributes: &Attributes,
+    where_clause: &mut WhereClause,
+) {
+    if attributes.compact.is_none() {
+        return;
+    }
+
+    let mut seen = Vec::new();
+    where_clause.predicates = where_clause
+        .predicates
+        .iter()
+        .filter(|predicate| {
+            let tokens = predicate.to_token_stream().to_string();
+            if seen.contains(&tokens) {
+                false
+            } else {
+                seen.push(tokens);
+                true
+            }
+        })
+        .cloned()
+        .collect();
+}
+
+// Returns `#[inline]`, unless the derive is compact. Compact derives leave
+// inlining to the compiler so that each impl is only code generated once.
+pub fn inline_attr(attributes: &Attributes) -> Option<TokenStream> {
+    attributes.compact.is_none().then(|| quote! { #[inline] })
+}
+
+// Returns the copy optimization for types which derive with `bulk_copy`.
+pub fn copy_optimization(
+    attributes: &Attributes,
+    rkyv_path: &Path,
+) -> Option<TokenStream> {
+    attributes.bulk_copy.is_some().then(|| {
+        quote! {
+            const COPY_OPTIMIZATION: #rkyv_path::CopyOptimization<Self> =
+                #rkyv_path::CopyOptimization::bulk_copy();
+        }
+    })
+}
+
 pub fn members_starting_at(
     fields: &Fields,
     start: usize,
@@ -41,17 +146,173 @@ pub fn members(fields: &Fields) -> impl Iterator<Item = (Member, &Field)> {
     members_starting_at(fields, 0)
 }
 
+pub fn transparent_field<'a>(
+    input: &'a DeriveInput,
+    transparent: &Path,
+) -> Result<(Member, &'a Field), Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                transparent,
+                "transparent is only supported for structs",
+            ))
+        }
+    };
+
+    let mut members = members(fields);
+    match (members.next(), members.next()) {
+        (Some(member), None) => Ok(member),
+        _ => Err(Error::new_spanned(
+            transparent,
+            "transparent is only supported for structs with exactly one field",
+        )),
+    }
+}
+
+pub fn evolve_fields<'a>(
+    input: &'a DeriveInput,
+    evolve: &Path,
+) -> Result<&'a FieldsNamed, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            evolve,
+            "evolve is not supported for generic structs",
+        ));
+    }
+
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(Error::new_spanned(
+                evolve,
+                "evolve is only supported for structs with named fields",
+            )),
+        },
+        _ => Err(Error::new_spanned(
+            evolve,
+            "evolve is only supported for structs",
+        )),
+    }
+}
+
+// Returns the wrapper a field is archived with, if it has one.
+//
+// Fields with `#[archive(archived = "...")]` substitute their archived type,
+// and are archived with the `Substitute` wrapper. The resolver defaults to the
+// one chosen by the archived type's `ArchiveFrom` impl.
+pub fn with_type(
+    rkyv_path: &Path,
+    field: &Field,
+) -> Result<Option<Type>, Error> {
+    let mut with = None;
+    let mut archived = None;
+    let mut resolver = None;
+    for attr in field.attrs.iter() {
+        if attr.path().is_ident("with") {
+            if with.is_none() {
+                with = Some(attr);
+            }
+        } else if attr.path().is_ident("archive") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("archived") {
+                    let ty =
+                        meta.value()?.parse::<LitStr>()?.parse::<Type>()?;
+                    try_set_attribute(&mut archived, ty, "archived")
+                } else if meta.path.is_ident("resolver") {
+                    let ty =
+                        meta.value()?.parse::<LitStr>()?.parse::<Type>()?;
+                    try_set_attribute(&mut resolver, ty, "resolver")
+                } else {
+                    Err(meta.error("unrecognized archive field argument"))
+                }
+            })?;
+        }
+    }
+
+    let ty = &field.ty;
+    match (with, archived, resolver) {
+        (Some(with), None, None) => Ok(Some(with.parse_args::<Type>()?)),
+        (Some(with), ..) => Err(Error::new_spanned(
+            with,
+            "with may not be used with archived = \"...\" or resolver = \
+             \"...\"",
+        )),
+        (None, Some(archived), Some(resolver)) => Ok(Some(parse_quote! {
+            #rkyv_path::with::Substitute<#archived, #resolver>
+        })),
+        (None, Some(archived), None) => Ok(Some(parse_quote! {
+            #rkyv_path::with::Substitute<
+                #archived,
+                <#archived as #rkyv_path::with::ArchiveFrom<#ty>>::Resolver,
+            >
+        })),
+        (None, None, Some(resolver)) => Err(Error::new_spanned(
+            resolver,
+            "resolver = \"...\" may only be used with archived = \"...\"",
+        )),
+        (None, None, None) => Ok(dyn_with_type(ty)),
+    }
+}
+
+// Returns the wrapper for boxed and shared trait objects, which are archived
+// through the serialize traits generated by `rkyv_dyn`.
+fn dyn_with_type(ty: &Type) -> Option<Type> {
+    if !cfg!(feature = "rkyv_dyn") {
+        return None;
+    }
+
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            path.path.segments.last()?
+        }
+        _ => return None,
+    };
+    if segment.ident != "Box" && segment.ident != "Arc" {
+        return None;
+    }
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            &args.args
+        }
+        _ => return None,
+    };
+
+    match args.first()? {
+        GenericArgument::Type(Type::TraitObject(object))
+            if object.dyn_token.is_some()
+                && object
+                    .bounds
+                    .iter()
+                    .filter(|bound| matches!(bound, TypeParamBound::Trait(_)))
+                    .count()
+                    == 1 =>
+        {
+            Some(parse_quote! { ::rkyv_dyn::with::AsDyn })
+        }
+        _ => None,
+    }
+}
+
+// Returns whether an attribute gives a field a wrapper or a substituted
+// archived type.
+pub fn is_with_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("with") || attr.path().is_ident("archive")
+}
+
+// Returns whether a field has a wrapper or a substituted archived type.
+pub fn has_with(field: &Field) -> bool {
+    field.attrs.iter().any(is_with_attr)
+}
+
 pub fn map_with_or_else<T>(
+    rkyv_path: &Path,
     field: &Field,
     f: impl FnOnce(Type) -> T,
     d: impl FnOnce() -> T,
 ) -> Result<T, Error> {
-    let with_attr = field
-        .attrs
-        .iter()
-        .find(|attr| attr.meta.path().is_ident("with"));
-    if let Some(with) = with_attr {
-        Ok(f(with.parse_args::<Type>()?))
+    if let Some(with_ty) = with_type(rkyv_path, field)? {
+        Ok(f(with_ty))
     } else {
         Ok(d())
     }
@@ -64,6 +325,7 @@ pub fn archive_bound(
     let ty = &field.ty;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             parse_quote! {
@@ -78,6 +340,15 @@ pub fn archive_bound(
     )
 }
 
+// Pod types have a unit resolver, so each of their fields must also have a
+// unit resolver.
+pub fn pod_archive_bound(rkyv_path: &Path, field: &Field) -> WherePredicate {
+    let ty = &field.ty;
+    parse_quote! {
+        #ty: #rkyv_path::Archive<Resolver = ()>
+    }
+}
+
 pub fn serialize_bound(
     rkyv_path: &Path,
     field: &Field,
@@ -85,6 +356,7 @@ pub fn serialize_bound(
     let ty = &field.ty;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             parse_quote! {
@@ -108,6 +380,7 @@ pub fn deserialize_bound(
     let archived = archived(rkyv_path, field)?;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             parse_quote! {
@@ -131,6 +404,7 @@ fn archive_item(
     let ty = &field.ty;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             let ident = Ident::new(with_name, Span::call_site());
@@ -166,6 +440,7 @@ pub fn serialize(
     let ty = &field.ty;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             quote! {
@@ -191,6 +466,7 @@ pub fn deserialize(
     let archived = archived(rkyv_path, field)?;
 
     map_with_or_else(
+        rkyv_path,
         field,
         |with_ty| {
             quote! {
@@ -210,3 +486,78 @@ pub fn deserialize(
         },
     )
 }
+
+// Returns the function which serializes and resolves a field of an evolving
+// struct.
+pub fn evolve_serialize(
+    rkyv_path: &Path,
+    field: &Field,
+) -> Result<TokenStream, Error> {
+    let ty = &field.ty;
+
+    map_with_or_else(
+        rkyv_path,
+        field,
+        |with_ty| {
+            quote! {
+                #rkyv_path::evolve::serialize_field_with::<#with_ty, #ty, __S>
+            }
+        },
+        || quote! { #rkyv_path::evolve::serialize_field::<#ty, __S> },
+    )
+}
+
+// The functions below are used by compact derives. They call trait methods
+// without naming the field type, which is inferred from the arguments.
+
+pub fn resolve_field(
+    rkyv_path: &Path,
+    field: &Field,
+) -> Result<TokenStream, Error> {
+    map_with_or_else(
+        rkyv_path,
+        field,
+        |with_ty| {
+            quote! {
+                #rkyv_path::derive::resolve_field_with::<#with_ty, _, _>
+            }
+        },
+        || quote! { #rkyv_path::derive::resolve_field },
+    )
+}
+
+pub fn compact_serialize(
+    rkyv_path: &Path,
+    field: &Field,
+) -> Result<TokenStream, Error> {
+    map_with_or_else(
+        rkyv_path,
+        field,
+        |with_ty| {
+            quote! {
+                <
+                    #with_ty as #rkyv_path::with::SerializeWith<_, __S>
+                >::serialize_with
+            }
+        },
+        || quote! { #rkyv_path::Serialize::<__S>::serialize },
+    )
+}
+
+pub fn compact_deserialize(
+    rkyv_path: &Path,
+    field: &Field,
+) -> Result<TokenStream, Error> {
+    map_with_or_else(
+        rkyv_path,
+        field,
+        |with_ty| {
+            quote! {
+                <
+                    #with_ty as #rkyv_path::with::DeserializeWith<_, _, __D>
+                >::deserialize_with
+            }
+        },
+        || quote! { #rkyv_path::Deserialize::<_, __D>::deserialize },
+    )
+}
@@ -0,0 +1,292 @@
+//! Registry footers which record the trait impls that an archive may use.
+//!
+//! Archived trait objects refer to their implementations by impl ID. A reader
+//! which does not know an impl only finds out when it looks up the metadata of
+//! a trait object using it. A registry footer records the name and version of
+//! each impl alongside an archive, so a reader can check up front whether it
+//! knows every impl that the archive may refer to.
+//!
+//! A footer is appended after the archive with [`append_footer`], and split
+//! back off with [`split_footer`]. The archive bytes returned by
+//! [`split_footer`] can be accessed as usual.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{rancor::Error, to_bytes, Archived};
+//! use rkyv_dyn::footer::{
+//!     access_footer, append_footer, RegistryEntry, RegistryFooter,
+//! };
+//!
+//! let archive = to_bytes::<Error>(&42u32).unwrap();
+//! let footer = RegistryFooter {
+//!     entries: vec![RegistryEntry {
+//!         impl_id: 0,
+//!         name: "Unknown as dyn Trait".to_string(),
+//!         version: 1,
+//!     }],
+//! };
+//! let bytes = append_footer::<Error>(archive, &footer).unwrap();
+//!
+//! let (archive, footer) = access_footer::<Error>(&bytes).unwrap();
+//! assert!(footer.check_known::<Error>().is_err());
+//! assert_eq!(footer.unknown_impls().count(), 1);
+//!
+//! let value = rkyv::access::<Archived<u32>, Error>(archive).unwrap();
+//! assert_eq!(*value, 42);
+//! ```
+
+use core::fmt;
+
+use rancor::{fail, Source};
+use rkyv::{
+    ser::AllocSerializer, util::AlignedVec, Archive, Deserialize, Serialize,
+};
+
+use crate::{ImplId, TraitImpl, TRAIT_IMPLS};
+
+/// The name and version of a registered trait impl.
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct RegistryEntry {
+    /// The ID of the trait impl.
+    pub impl_id: ImplId,
+    /// The name of the trait impl.
+    pub name: String,
+    /// The version of the trait impl.
+    pub version: u32,
+}
+
+impl RegistryEntry {
+    fn from_trait_impl(impl_id: ImplId, trait_impl: &TraitImpl) -> Self {
+        Self {
+            impl_id,
+            name: trait_impl.name().to_string(),
+            version: trait_impl.version(),
+        }
+    }
+}
+
+impl ArchivedRegistryEntry {
+    /// Returns whether this entry matches a trait impl registered in
+    /// [`TRAIT_IMPLS`].
+    ///
+    /// An entry matches if a trait impl with the same name and version is
+    /// registered with the same impl ID.
+    pub fn is_known(&self) -> bool {
+        let registered = TRAIT_IMPLS
+            .get()
+            .and_then(|impls| impls.get(self.impl_id.to_native() as usize));
+        match registered {
+            Some(trait_impl) => {
+                trait_impl.name() == self.name.as_str()
+                    && trait_impl.version() == self.version.to_native()
+            }
+            None => false,
+        }
+    }
+}
+
+/// A footer recording the trait impls that an archive may use.
+#[derive(
+    Archive, Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize,
+)]
+#[cfg_attr(feature = "bytecheck", archive(check_bytes))]
+pub struct RegistryFooter {
+    /// The entries of the footer.
+    pub entries: Vec<RegistryEntry>,
+}
+
+impl RegistryFooter {
+    /// Returns a footer which records every trait impl registered in
+    /// [`TRAIT_IMPLS`].
+    ///
+    /// The footer is empty if no trait impls have been registered.
+    pub fn from_registry() -> Self {
+        let impls = TRAIT_IMPLS.get().copied().unwrap_or(&[]);
+        Self {
+            entries: impls
+                .iter()
+                .enumerate()
+                .map(|(id, trait_impl)| {
+                    RegistryEntry::from_trait_impl(id as ImplId, trait_impl)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns a footer which records the trait impls with the given IDs.
+    ///
+    /// IDs which are not registered in [`TRAIT_IMPLS`] are skipped.
+    pub fn for_impl_ids(ids: impl IntoIterator<Item = ImplId>) -> Self {
+        let impls = TRAIT_IMPLS.get().copied().unwrap_or(&[]);
+        Self {
+            entries: ids
+                .into_iter()
+                .filter_map(|id| {
+                    let trait_impl = impls.get(id as usize)?;
+                    Some(RegistryEntry::from_trait_impl(id, trait_impl))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct UnknownImpls {
+    impl_id: ImplId,
+    name: String,
+    version: u32,
+    count: usize,
+}
+
+impl fmt::Display for UnknownImpls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "archive uses {} unknown trait impl(s), including `{}` (impl ID \
+             {}, version {})",
+            self.count, self.name, self.impl_id, self.version,
+        )
+    }
+}
+
+impl std::error::Error for UnknownImpls {}
+
+impl ArchivedRegistryFooter {
+    /// Returns an iterator over the entries which do not match a registered
+    /// trait impl.
+    pub fn unknown_impls(
+        &self,
+    ) -> impl Iterator<Item = &ArchivedRegistryEntry> + '_ {
+        self.entries.iter().filter(|entry| !entry.is_known())
+    }
+
+    /// Checks that every entry matches a registered trait impl.
+    ///
+    /// Returns an error naming the first unknown impl otherwise.
+    pub fn check_known<E: Source>(&self) -> Result<(), E> {
+        let mut unknown = self.unknown_impls();
+        if let Some(first) = unknown.next() {
+            fail!(UnknownImpls {
+                impl_id: first.impl_id.to_native(),
+                name: first.name.as_str().to_string(),
+                version: first.version.to_native(),
+                count: 1 + unknown.count(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// The trailer following a footer: the archive length and footer length as
+// little-endian `u32`s, followed by the magic bytes.
+const MAGIC: [u8; 8] = *b"rkyv_dyn";
+const TRAILER_LEN: usize = 16;
+
+#[derive(Debug)]
+enum FooterError {
+    MissingTrailer,
+    InvalidLengths,
+    TooLarge(usize),
+}
+
+impl fmt::Display for FooterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTrailer => {
+                write!(f, "bytes do not end with a registry footer")
+            }
+            Self::InvalidLengths => write!(
+                f,
+                "registry footer lengths are out of bounds of the bytes",
+            ),
+            Self::TooLarge(len) => write!(
+                f,
+                "archive length {} is too large for a registry footer",
+                len,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FooterError {}
+
+fn footer_start(archive_len: usize) -> Option<usize> {
+    let align = <AlignedVec>::ALIGNMENT;
+    Some(archive_len.checked_add(align - 1)? / align * align)
+}
+
+/// Appends a registry footer to the bytes of an archive.
+///
+/// The footer is placed at the next aligned position after the archive, and
+/// is followed by a fixed-size trailer which locates the archive and footer.
+pub fn append_footer<E: Source>(
+    archive: AlignedVec,
+    footer: &RegistryFooter,
+) -> Result<AlignedVec, E> {
+    let archive_len = archive.len();
+    let start = match footer_start(archive_len) {
+        Some(start) => start,
+        None => fail!(FooterError::TooLarge(archive_len)),
+    };
+
+    let mut bytes = archive;
+    bytes.resize(start, 0);
+    let serializer =
+        AllocSerializer::new(bytes, Default::default(), Default::default());
+    let mut bytes =
+        rkyv::util::serialize_into(footer, serializer)?.into_writer();
+
+    let footer_len = bytes.len() - start;
+    let (archive_len, footer_len) =
+        match (u32::try_from(archive_len), u32::try_from(footer_len)) {
+            (Ok(archive_len), Ok(footer_len)) => (archive_len, footer_len),
+            _ => fail!(FooterError::TooLarge(archive_len)),
+        };
+    bytes.extend_from_slice(&archive_len.to_le_bytes());
+    bytes.extend_from_slice(&footer_len.to_le_bytes());
+    bytes.extend_from_slice(&MAGIC);
+
+    Ok(bytes)
+}
+
+/// Splits bytes written by [`append_footer`] into the archive bytes and the
+/// footer bytes.
+///
+/// The footer bytes contain an archived [`RegistryFooter`] at the root
+/// position.
+pub fn split_footer<E: Source>(bytes: &[u8]) -> Result<(&[u8], &[u8]), E> {
+    if bytes.len() < TRAILER_LEN || bytes[bytes.len() - 8..] != MAGIC {
+        fail!(FooterError::MissingTrailer);
+    }
+
+    let trailer = &bytes[bytes.len() - TRAILER_LEN..];
+    let mut len_bytes = [0; 4];
+    len_bytes.copy_from_slice(&trailer[0..4]);
+    let archive_len = u32::from_le_bytes(len_bytes) as usize;
+    len_bytes.copy_from_slice(&trailer[4..8]);
+    let footer_len = u32::from_le_bytes(len_bytes) as usize;
+
+    let body_len = bytes.len() - TRAILER_LEN;
+    let end = footer_start(archive_len)
+        .and_then(|start| Some((start, start.checked_add(footer_len)?)));
+    let (start, end) = match end {
+        Some((start, end)) if end == body_len => (start, end),
+        _ => fail!(FooterError::InvalidLengths),
+    };
+
+    Ok((&bytes[..archive_len], &bytes[start..end]))
+}
+
+/// Splits bytes written by [`append_footer`] and validates the footer.
+///
+/// Returns the archive bytes and the archived footer.
+#[cfg(feature = "bytecheck")]
+pub fn access_footer<E: Source>(
+    bytes: &[u8],
+) -> Result<(&[u8], &ArchivedRegistryFooter), E> {
+    let (archive, footer) = split_footer::<E>(bytes)?;
+    let footer = rkyv::access::<ArchivedRegistryFooter, E>(footer)?;
+    Ok((archive, footer))
+}
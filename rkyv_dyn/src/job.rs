@@ -0,0 +1,412 @@
+//! Archived jobs which call registered functions.
+//!
+//! A distributed job queue needs to ship "call this function with these
+//! arguments" between processes. Function pointers can't be archived, so jobs
+//! refer to their function by a stable [`JobId`] instead. The ID is derived
+//! from the name of the job, which defaults to the name of the function.
+//!
+//! 1. Add [`rkyv_job`](macro@crate::rkyv_job) to a function which takes a
+//!    reference to its archived arguments.
+//! 2. Register the jobs with [`register_jobs`](crate::register_jobs) in every
+//!    process which runs them.
+//! 3. Serialize a [`Job`] holding the arguments, and invoke the resulting
+//!    [`ArchivedJob`] on the reader side.
+//!
+//! The arguments are stored as a nested archive, so an archived job can be
+//! validated and queued without knowing which function it calls. The
+//! arguments are validated when the job is invoked.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::atomic::{AtomicU32, Ordering};
+//!
+//! use rkyv::{rancor::Error, to_bytes, Archive, Archived, Serialize};
+//! use rkyv_dyn::{
+//!     job::{ArchivedJob, Job},
+//!     register_jobs, rkyv_job,
+//! };
+//!
+//! static TOTAL: AtomicU32 = AtomicU32::new(0);
+//!
+//! #[derive(Archive, Serialize)]
+//! #[archive(check_bytes)]
+//! struct Add {
+//!     amount: u32,
+//! }
+//!
+//! #[rkyv_job]
+//! fn add(args: &Archived<Add>) {
+//!     TOTAL.fetch_add(args.amount.to_native(), Ordering::Relaxed);
+//! }
+//!
+//! register_jobs!(add);
+//!
+//! let job = Job::<add>::new(Add { amount: 3 });
+//! let bytes = to_bytes::<Error>(&job).unwrap();
+//!
+//! let job = rkyv::access::<ArchivedJob, Error>(&bytes).unwrap();
+//! assert_eq!(job.name(), Some("add"));
+//! job.invoke::<Error>().unwrap();
+//! assert_eq!(TOTAL.load(Ordering::Relaxed), 3);
+//! ```
+
+use core::{fmt, marker::PhantomData};
+
+#[cfg(feature = "bytecheck")]
+use bytecheck::CheckBytes;
+use munge::munge;
+#[cfg(feature = "bytecheck")]
+use rancor::ResultExt as _;
+use rancor::{fail, Fallible, Source, Strategy};
+#[cfg(feature = "bytecheck")]
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{
+    nested::{ArchivedNested, NestedResolver},
+    ser::{AllocSerializer, Writer},
+    Archive, Archived, Place, Portable, Serialize,
+};
+
+use crate::LazyStatic;
+
+/// The type of job IDs.
+pub type JobId = u64;
+
+/// Returns the job ID for the given job name.
+///
+/// IDs are a 64-bit FNV-1a hash of the name, so they stay the same across
+/// builds and platforms as long as the name does not change.
+pub const fn job_id(name: &str) -> JobId {
+    let bytes = name.as_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// A function which can be called by an archived job.
+///
+/// This is implemented by [`rkyv_job`](macro@crate::rkyv_job), which creates
+/// a type with the same name as the function.
+pub trait JobFn {
+    /// The arguments of the job.
+    type Args: Archive;
+
+    /// The stable name of the job.
+    const NAME: &'static str;
+
+    /// The ID of the job.
+    const ID: JobId = job_id(Self::NAME);
+
+    /// Calls the job function with its archived arguments.
+    fn call(args: &Archived<Self::Args>);
+}
+
+/// A registered job function.
+///
+/// Entries are created by [`register_jobs`](crate::register_jobs), and are
+/// looked up by ID when an [`ArchivedJob`] is invoked.
+#[derive(Clone, Copy)]
+pub struct JobEntry {
+    id: JobId,
+    name: &'static str,
+    call: unsafe fn(&[u8]),
+    #[cfg(feature = "bytecheck")]
+    check: fn(&[u8]) -> Result<(), rancor::Error>,
+}
+
+// The bytes must contain a valid archived `J::Args` at the root position.
+unsafe fn call_erased<J: JobFn>(bytes: &[u8]) {
+    // SAFETY: The caller has guaranteed that the bytes contain a valid
+    // archived `J::Args`.
+    let args = unsafe { rkyv::access_unchecked::<Archived<J::Args>>(bytes) };
+    J::call(args);
+}
+
+#[cfg(feature = "bytecheck")]
+fn check_erased<J>(bytes: &[u8]) -> Result<(), rancor::Error>
+where
+    J: JobFn,
+    Archived<J::Args>: CheckBytes<Strategy<DefaultValidator, rancor::Error>>,
+{
+    rkyv::access::<Archived<J::Args>, rancor::Error>(bytes)?;
+    Ok(())
+}
+
+impl JobEntry {
+    /// Returns the entry for the given job function.
+    #[cfg(feature = "bytecheck")]
+    pub fn new<J>() -> Self
+    where
+        J: JobFn,
+        Archived<J::Args>:
+            CheckBytes<Strategy<DefaultValidator, rancor::Error>>,
+    {
+        Self {
+            id: J::ID,
+            name: J::NAME,
+            call: call_erased::<J>,
+            check: check_erased::<J>,
+        }
+    }
+
+    /// Returns the entry for the given job function.
+    #[cfg(not(feature = "bytecheck"))]
+    pub fn new<J: JobFn>() -> Self {
+        Self {
+            id: J::ID,
+            name: J::NAME,
+            call: call_erased::<J>,
+        }
+    }
+
+    /// Returns the ID of the job.
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    /// Returns the name of the job.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl fmt::Debug for JobEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobEntry")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// All registered jobs, sorted by ID.
+///
+/// This is initialized with [`register_jobs`](crate::register_jobs).
+pub static JOBS: LazyStatic<&'static [JobEntry]> = LazyStatic::new();
+
+/// Returns the registered job with the given ID, if any.
+pub fn lookup_job(id: JobId) -> Option<&'static JobEntry> {
+    let jobs = JOBS.get()?;
+    let index = jobs.binary_search_by_key(&id, |job| job.id).ok()?;
+    Some(&jobs[index])
+}
+
+/// Sorts the given job entries by ID.
+///
+/// # Panics
+///
+/// Panics if two of the jobs have the same ID.
+#[doc(hidden)]
+pub fn sort_jobs(jobs: &mut [JobEntry]) {
+    jobs.sort_unstable_by_key(|job| job.id);
+    for pair in jobs.windows(2) {
+        assert!(
+            pair[0].id != pair[1].id,
+            "jobs `{}` and `{}` have the same job ID",
+            pair[0].name,
+            pair[1].name,
+        );
+    }
+}
+
+/// Globally registers the given job functions.
+///
+/// Each argument is a function with the [`rkyv_job`](macro@crate::rkyv_job)
+/// attribute. This initializes [`JOBS`](crate::job::JOBS), and panics if it is
+/// called more than once or if two jobs have the same ID.
+#[macro_export]
+macro_rules! register_jobs {
+    ($($job:ty),* $(,)?) => {
+        let _: () = {
+            const JOB_COUNT: usize = 0
+                $(+ { let _ = ::core::marker::PhantomData::<$job>; 1 })*;
+            static JOBS: $crate::LazyStatic<[
+                $crate::job::JobEntry;
+                JOB_COUNT
+            ]> = $crate::LazyStatic::new();
+            let mut jobs = [
+                $($crate::job::JobEntry::new::<$job>(),)*
+            ];
+            $crate::job::sort_jobs(&mut jobs);
+            let jobs = JOBS.init(jobs).unwrap();
+            $crate::job::JOBS.init(jobs).unwrap();
+        };
+    };
+}
+
+#[derive(Debug)]
+struct UnknownJob {
+    id: JobId,
+}
+
+impl fmt::Display for UnknownJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "job ID {:#018x} is not registered", self.id)
+    }
+}
+
+impl std::error::Error for UnknownJob {}
+
+/// A job which calls `J` with the given arguments.
+///
+/// Serializing a `Job` produces an [`ArchivedJob`].
+pub struct Job<J: JobFn> {
+    args: J::Args,
+    _phantom: PhantomData<fn() -> J>,
+}
+
+impl<J: JobFn> Job<J> {
+    /// Returns a new job which calls `J` with the given arguments.
+    pub fn new(args: J::Args) -> Self {
+        Self {
+            args,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the arguments of the job.
+    pub fn args(&self) -> &J::Args {
+        &self.args
+    }
+
+    /// Consumes the job and returns its arguments.
+    pub fn into_args(self) -> J::Args {
+        self.args
+    }
+}
+
+impl<J: JobFn> fmt::Debug for Job<J>
+where
+    J::Args: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Job")
+            .field("name", &J::NAME)
+            .field("args", &self.args)
+            .finish()
+    }
+}
+
+/// An archived [`Job`].
+///
+/// The type of the arguments is erased, and is recovered from the registered
+/// job when it is invoked.
+#[derive(Portable)]
+#[cfg_attr(feature = "bytecheck", derive(CheckBytes))]
+#[repr(C)]
+pub struct ArchivedJob {
+    id: Archived<JobId>,
+    args: ArchivedNested<()>,
+}
+
+impl ArchivedJob {
+    /// Returns the ID of the job.
+    pub fn id(&self) -> JobId {
+        self.id.to_native()
+    }
+
+    /// Returns the bytes of the nested archive holding the arguments.
+    pub fn args_bytes(&self) -> &[u8] {
+        self.args.as_bytes()
+    }
+
+    /// Returns the registered job which this job calls, if any.
+    pub fn entry(&self) -> Option<&'static JobEntry> {
+        lookup_job(self.id())
+    }
+
+    /// Returns the name of the registered job which this job calls, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        self.entry().map(JobEntry::name)
+    }
+
+    /// Returns whether this job calls `J`.
+    pub fn is<J: JobFn>(&self) -> bool {
+        self.id() == J::ID
+    }
+
+    fn registered<E: Source>(&self) -> Result<&'static JobEntry, E> {
+        match self.entry() {
+            Some(entry) => Ok(entry),
+            None => fail!(UnknownJob { id: self.id() }),
+        }
+    }
+
+    /// Validates the arguments of the job and calls the registered job
+    /// function.
+    ///
+    /// Returns an error if the job is not registered or its arguments are
+    /// invalid.
+    #[cfg(feature = "bytecheck")]
+    pub fn invoke<E: Source>(&self) -> Result<(), E> {
+        let entry = self.registered::<E>()?;
+        (entry.check)(self.args_bytes()).into_error()?;
+        // SAFETY: We just validated the arguments of the job.
+        unsafe { (entry.call)(self.args_bytes()) };
+        Ok(())
+    }
+
+    /// Calls the registered job function without validating its arguments.
+    ///
+    /// Returns an error if the job is not registered.
+    ///
+    /// # Safety
+    ///
+    /// The arguments of the job must be a valid archive of the arguments of
+    /// the registered job.
+    pub unsafe fn invoke_unchecked<E: Source>(&self) -> Result<(), E> {
+        let entry = self.registered::<E>()?;
+        // SAFETY: The caller has guaranteed that the arguments are valid.
+        unsafe { (entry.call)(self.args_bytes()) };
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ArchivedJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedJob")
+            .field("id", &self.id())
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// The resolver for [`Job`].
+pub struct JobResolver {
+    args: NestedResolver,
+}
+
+impl<J: JobFn> Archive for Job<J> {
+    type Archived = ArchivedJob;
+    type Resolver = JobResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedJob { id, args } = out);
+        J::ID.resolve((), id);
+        ArchivedNested::resolve_from_resolver(resolver.args, args);
+    }
+}
+
+impl<J, S> Serialize<S> for Job<J>
+where
+    J: JobFn,
+    J::Args: Serialize<Strategy<AllocSerializer, S::Error>>,
+    S: Fallible + Writer + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let bytes = rkyv::to_bytes::<S::Error>(&self.args)?;
+        Ok(JobResolver {
+            args: ArchivedNested::<()>::serialize_from_bytes(
+                &bytes, serializer,
+            )?,
+        })
+    }
+}
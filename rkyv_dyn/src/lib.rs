@@ -7,67 +7,44 @@
 //!
 //! ## Features
 //!
+//! - `std`: Enables registry footers through the `footer` module, archived jobs
+//!   through the `job` module, and support for `std` in dependencies.
 //! - `bytecheck`: Enables validation support through `bytecheck`.
+//! - `derive`: Makes the `Archive` derive archive `Box<dyn Trait>` and `Arc<dyn
+//!   Trait>` fields with [`with::AsDyn`] automatically.
 
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(missing_docs)]
 #![deny(rustdoc::missing_crate_level_docs)]
 
+#[cfg(feature = "std")]
+pub mod footer;
+#[cfg(feature = "std")]
+pub mod job;
 mod lazy_static;
+#[cfg(feature = "std")]
+pub mod with;
 // TODO: re-enable
 // #[cfg(feature = "bytecheck")]
 // mod bytecheck;
 
 use core::{hash, marker::PhantomData};
+use std::sync::Arc;
 
 pub use lazy_static::LazyStatic;
 use ptr_meta::{DynMetadata, Pointee};
 use rancor::Fallible;
+#[doc(inline)]
+pub use rkyv::ser::{AsDynSerializer, DynSerializer};
 use rkyv::{
-    de::Pooling,
-    place::Initialized,
-    primitive::FixedUsize,
-    ser::{Allocator, Sharing, Writer},
-    Archived, Portable, Serialize,
+    de::Pooling, place::Initialized, primitive::FixedUsize, Archived, Portable,
+    Serialize,
 };
-pub use rkyv_dyn_derive::archive_dyn;
+pub use rkyv_dyn_derive::{archive_dyn, rkyv_job};
 
 /// The type of trait impl IDs.
 pub type ImplId = FixedUsize;
 
-/// An object-safe version of `Serializer`.
-///
-/// Instead of an associated error type, `DynSerializer` returns the `E` type.
-/// If you have a serializer that already implements `Serializer`, then it will
-/// automatically implement `DynSerializer`.
-pub trait DynSerializer<E>: Writer<E> + Allocator<E> + Sharing<E> {}
-
-impl<E> Fallible for dyn DynSerializer<E> + '_ {
-    type Error = E;
-}
-
-impl<S: Writer<E> + Allocator<E> + Sharing<E>, E> DynSerializer<E> for S {}
-
-/// TODO
-pub trait AsDynSerializer<E> {
-    /// TODO
-    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E>;
-}
-
-impl<S: DynSerializer<E>, E> AsDynSerializer<E> for S {
-    #[inline]
-    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E> {
-        self as &mut dyn DynSerializer<E>
-    }
-}
-
-impl<E> AsDynSerializer<E> for dyn DynSerializer<E> {
-    #[inline]
-    fn as_dyn_serializer(&mut self) -> &mut dyn DynSerializer<E> {
-        self
-    }
-}
-
 /// A trait object that can be archived.
 ///
 /// To add archive support for a trait object:
@@ -184,6 +161,26 @@ impl<T: for<'a> Serialize<dyn DynSerializer<E> + 'a>, E> SerializeDyn<E> for T {
     }
 }
 
+/// A trait object which can be serialized through its serialize trait.
+///
+/// This is implemented by [`archive_dyn`](macro@archive_dyn) for `dyn Trait`
+/// and for the serialize trait of `Trait`. It's used by [`with::AsDyn`] to
+/// archive `Box<dyn Trait>` and `Arc<dyn Trait>` fields.
+pub trait AsSerializeDyn {
+    /// The serialize trait object for this trait object.
+    type Serialize: ?Sized;
+
+    /// Returns this value as its serialize trait object, or `None` if its
+    /// impl was not annotated with [`archive_dyn`](macro@archive_dyn).
+    fn as_serialize_dyn(&self) -> Option<&Self::Serialize>;
+
+    /// Converts a boxed serialize trait object into this trait object.
+    fn upcast_box(value: Box<Self::Serialize>) -> Box<Self>;
+
+    /// Converts a shared serialize trait object into this trait object.
+    fn upcast_arc(value: Arc<Self::Serialize>) -> Arc<Self>;
+}
+
 /// An object-safe version of `Deserializer`.
 pub trait DynDeserializer<E>: Pooling<E> {}
 
@@ -274,15 +271,57 @@ impl<T: ?Sized> ArchivedDynMetadata<T> {
 
     /// Returns the pointer metadata for the trait object this metadata refers
     /// to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the impl ID is not registered. Use
+    /// [`try_lookup_metadata`](ArchivedDynMetadata::try_lookup_metadata) or
+    /// [`lookup_metadata_or_fallback`] to handle unregistered impls instead.
+    ///
+    /// [`lookup_metadata_or_fallback`]:
+    /// ArchivedDynMetadata::lookup_metadata_or_fallback
     pub fn lookup_metadata(&self) -> DynMetadata<T> {
-        unsafe {
-            TRAIT_IMPLS
-                .get()
-                .expect("TRAIT_IMPLS was not initialized for rkyv_dyn")
-                [self.impl_id() as usize]
-                .downcast_metadata()
+        assert!(
+            TRAIT_IMPLS.get().is_some(),
+            "TRAIT_IMPLS was not initialized for rkyv_dyn",
+        );
+        match self.try_lookup_metadata() {
+            Some(metadata) => metadata,
+            None => panic!(
+                "impl ID {} is not registered for rkyv_dyn",
+                self.impl_id(),
+            ),
         }
     }
+
+    /// Returns the pointer metadata for the trait object this metadata refers
+    /// to, or `None` if the impl ID is not registered.
+    pub fn try_lookup_metadata(&self) -> Option<DynMetadata<T>> {
+        let trait_impl = TRAIT_IMPLS.get()?.get(self.impl_id() as usize)?;
+        // SAFETY: Registered impl IDs always refer to an impl of `T`.
+        Some(unsafe { trait_impl.downcast_metadata() })
+    }
+
+    /// Returns whether the impl ID is registered.
+    pub fn is_registered(&self) -> bool {
+        self.try_lookup_metadata().is_some()
+    }
+
+    /// Returns the pointer metadata for the trait object this metadata refers
+    /// to, or the metadata of the fallback impl if the impl ID is not
+    /// registered.
+    ///
+    /// This allows archives written with impls that the reader does not know
+    /// to be partially read. See [`FallbackImpl`] for more information.
+    pub fn lookup_metadata_or_fallback(&self) -> DynMetadata<T>
+    where
+        T: FallbackImpl,
+    {
+        self.try_lookup_metadata().unwrap_or_else(|| {
+            // SAFETY: Fallback impls are always impls of `T`.
+            unsafe { T::fallback_impl().downcast_metadata() }
+        })
+    }
 }
 
 impl<T: ?Sized> Clone for ArchivedDynMetadata<T> {
@@ -323,11 +362,17 @@ impl<T: ?Sized> Ord for ArchivedDynMetadata<T> {
 }
 
 /// The trait object metadata for a trait implementation.
+///
+/// Trait impls also carry a name and version, which are recorded in registry
+/// footers so that readers can check whether they know the impls used by an
+/// archive. See the `footer` module for more information.
 #[derive(Clone, Copy, Debug)]
 pub struct TraitImpl {
     // The type of this `DynMetadata` is erased. Whatever uses it will
     // transmute it to the correct `DynMetadata<T>`.
     metadata: DynMetadata<()>,
+    name: &'static str,
+    version: u32,
 }
 
 impl TraitImpl {
@@ -351,9 +396,39 @@ impl TraitImpl {
             // They all contain a single erased `&'static VTable` reference and
             // a `PhantomData<T>`.
             metadata: unsafe { core::mem::transmute(metadata) },
+            name: "",
+            version: 0,
         }
     }
 
+    /// Returns this trait impl with the given name.
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Returns this trait impl with the given version.
+    pub const fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns the name of this trait implementation.
+    ///
+    /// Trait impls created with [`trait_impl`] are named after the type and
+    /// trait they were created from.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the version of this trait implementation.
+    ///
+    /// The version defaults to zero, and should be incremented whenever the
+    /// archived form of the implementing type changes.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     /// Returns the trait object metadata of this trait implementation downcast
     /// to the given type.
     ///
@@ -372,6 +447,7 @@ impl TraitImpl {
 ///
 /// # Example
 /// ```
+/// # use rkyv_dyn::trait_impl;
 /// struct MyType;
 ///
 /// trait MyTrait {}
@@ -379,18 +455,25 @@ impl TraitImpl {
 /// impl MyTrait for MyType {}
 ///
 /// let trait_impl = trait_impl!(MyType as dyn MyTrait);
+/// assert_eq!(trait_impl.name(), "MyType as dyn MyTrait");
+///
+/// let trait_impl = trait_impl!(MyType as dyn MyTrait; version = 2);
+/// assert_eq!(trait_impl.version(), 2);
 /// ```
 #[macro_export]
 macro_rules! trait_impl {
-    ($type:ty as $trait:ty) => {
+    ($type:ty as $trait:ty $(; version = $version:expr)?) => {{
         // SAFETY: The given pointer is guaranteed to have valid metadata
         // because we just made them.
-        unsafe {
+        let trait_impl = unsafe {
             $crate::TraitImpl::from_pointer(
                 ::core::ptr::null::<$type>() as *const $trait
             )
-        }
-    };
+        };
+        trait_impl
+            .with_name(::core::stringify!($type as $trait))
+            $(.with_version($version))?
+    }};
 }
 
 /// All registered trait impls for `rkyv_dyn`.
@@ -407,9 +490,18 @@ pub static TRAIT_IMPLS: LazyStatic<&'static [TraitImpl]> = LazyStatic::new();
 ///    trait impl argument.
 /// 3. Initializing [`TRAIT_IMPLS`] with a reference to the array of
 ///    [`TraitImpl`]s.
+///
+/// Each trait impl may be followed by `; version = ...` to set the version
+/// recorded for it in registry footers.
 #[macro_export]
 macro_rules! register_trait_impls {
-    ($($type:ty as $trait:ty $(= $id:expr)?),* $(,)?) => {
+    (
+        $(
+            $type:ty as $trait:ty
+            $(= $id:expr)?
+            $(; version = $version:expr)?
+        ),* $(,)?
+    ) => {
         let _: () = {
             $crate::register_trait_impls!(
                 @register $($type as $trait $(= $id)?,)*
@@ -422,7 +514,9 @@ macro_rules! register_trait_impls {
             ]> = $crate::LazyStatic::new();
             let trait_impls = TRAIT_IMPLS.init([
                 $(
-                    $crate::trait_impl!($type as $trait),
+                    $crate::trait_impl!(
+                        $type as $trait $(; version = $version)?
+                    ),
                 )*
             ]).unwrap();
             $crate::TRAIT_IMPLS.init(trait_impls).unwrap();
@@ -479,6 +573,69 @@ macro_rules! register_trait_impls {
     (@choose_id $default:expr,) => { $default };
 }
 
+/// A placeholder trait impl which is used for archived trait objects with
+/// unregistered impls.
+///
+/// This is implemented for `dyn Trait` types with [`fallback_impl`]. When an
+/// archive refers to an impl that the reader has not registered,
+/// [`ArchivedDynMetadata::lookup_metadata_or_fallback`] returns the metadata
+/// of the placeholder instead. The methods of the placeholder should return
+/// error values so that the rest of the archive can still be read.
+///
+/// # Safety
+///
+/// `fallback_impl` must return an impl of `Self` for a type which has a size
+/// of zero and an alignment of one, so that it can be used with a pointer to
+/// the archived value of any other impl.
+pub unsafe trait FallbackImpl {
+    /// Returns the placeholder impl.
+    fn fallback_impl() -> TraitImpl;
+}
+
+/// Implements [`FallbackImpl`] for a dyn trait with the given placeholder
+/// type.
+///
+/// The placeholder type must have a size of zero and an alignment of one.
+///
+/// # Example
+///
+/// ```
+/// use rkyv_dyn::fallback_impl;
+///
+/// #[ptr_meta::pointee]
+/// trait Describe {
+///     fn describe(&self) -> Result<&str, &str>;
+/// }
+///
+/// struct MissingImpl;
+///
+/// impl Describe for MissingImpl {
+///     fn describe(&self) -> Result<&str, &str> {
+///         Err("this value was written with an unknown impl")
+///     }
+/// }
+///
+/// fallback_impl!(MissingImpl as dyn Describe);
+/// ```
+#[macro_export]
+macro_rules! fallback_impl {
+    ($type:ty as $trait:ty) => {
+        const _: () = assert!(
+            ::core::mem::size_of::<$type>() == 0
+                && ::core::mem::align_of::<$type>() == 1,
+            "fallback impls must have a size of zero and an alignment of one",
+        );
+
+        // SAFETY: We just checked that the placeholder type has a size of zero
+        // and an alignment of one.
+        unsafe impl $crate::FallbackImpl for $trait {
+            fn fallback_impl() -> $crate::TraitImpl {
+                $crate::trait_impl!($type as $trait)
+            }
+        }
+    };
+}
+
 /// A trait impl that has a globally-unique ID.
 ///
 /// # Safety
@@ -0,0 +1,177 @@
+//! Wrappers for archiving boxed and shared trait objects.
+//!
+//! Values of a trait annotated with [`archive_dyn`](macro@crate::archive_dyn)
+//! can only be serialized as its serialize trait. [`AsDyn`] serializes a
+//! `Box<dyn Trait>` or `Arc<dyn Trait>` through the serialize trait of the
+//! value, so fields don't have to be declared with the serialize trait.
+//!
+//! When `rkyv_derive` is built with its `rkyv_dyn` feature (enabled by this
+//! crate's `derive` feature), fields of type `Box<dyn Trait>` and
+//! `Arc<dyn Trait>` without a `#[with(...)]` attribute are archived with
+//! `AsDyn` automatically.
+
+use core::fmt;
+use std::sync::Arc;
+
+use rancor::{fail, Fallible, Source};
+use rkyv::{
+    boxed::{ArchivedBox, BoxResolver},
+    rc::{ArcFlavor, ArchivedRc, RcResolver},
+    ser::{Sharing, Writer},
+    with::{ArchiveWith, DeserializeWith, SerializeWith},
+    ArchiveUnsized, Deserialize, Place, SerializeUnsized,
+};
+
+use crate::AsSerializeDyn;
+
+/// A wrapper which archives boxed and shared trait objects through their
+/// serialize traits.
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug)]
+pub struct AsDyn;
+
+#[derive(Debug)]
+struct UnregisteredImpl;
+
+impl fmt::Display for UnregisteredImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trait object was not implemented with #[archive_dyn] and can't \
+             be serialized",
+        )
+    }
+}
+
+impl std::error::Error for UnregisteredImpl {}
+
+fn as_serialize_dyn<T, E>(value: &T) -> Result<&T::Serialize, E>
+where
+    T: AsSerializeDyn + ?Sized,
+    E: Source,
+{
+    match value.as_serialize_dyn() {
+        Some(value) => Ok(value),
+        None => fail!(UnregisteredImpl),
+    }
+}
+
+impl<T> ArchiveWith<Box<T>> for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: ArchiveUnsized,
+{
+    type Archived = ArchivedBox<<T::Serialize as ArchiveUnsized>::Archived>;
+    type Resolver = BoxResolver;
+
+    fn resolve_with(
+        field: &Box<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        // Serializing the field already checked that it has a serialize
+        // trait.
+        let value = field.as_serialize_dyn().unwrap();
+        ArchivedBox::resolve_from_ref(value, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Box<T>, S> for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: SerializeUnsized<S>,
+    S: Fallible + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Box<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedBox::serialize_from_ref(as_serialize_dyn(&**field)?, serializer)
+    }
+}
+
+impl<T, D>
+    DeserializeWith<
+        ArchivedBox<<T::Serialize as ArchiveUnsized>::Archived>,
+        Box<T>,
+        D,
+    > for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: ArchiveUnsized,
+    ArchivedBox<<T::Serialize as ArchiveUnsized>::Archived>:
+        Deserialize<Box<T::Serialize>, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedBox<<T::Serialize as ArchiveUnsized>::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Box<T>, D::Error> {
+        Ok(T::upcast_box(field.deserialize(deserializer)?))
+    }
+}
+
+impl<T> ArchiveWith<Arc<T>> for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: ArchiveUnsized,
+{
+    type Archived =
+        ArchivedRc<<T::Serialize as ArchiveUnsized>::Archived, ArcFlavor>;
+    type Resolver = RcResolver;
+
+    fn resolve_with(
+        field: &Arc<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        // Serializing the field already checked that it has a serialize
+        // trait.
+        let value = field.as_serialize_dyn().unwrap();
+        ArchivedRc::resolve_from_ref(value, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Arc<T>, S> for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: SerializeUnsized<S>,
+    S: Fallible + Writer + Sharing + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Arc<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedRc::<_, ArcFlavor>::serialize_from_ref(
+            as_serialize_dyn(&**field)?,
+            serializer,
+        )
+    }
+}
+
+impl<T, D>
+    DeserializeWith<
+        ArchivedRc<<T::Serialize as ArchiveUnsized>::Archived, ArcFlavor>,
+        Arc<T>,
+        D,
+    > for AsDyn
+where
+    T: AsSerializeDyn + ?Sized,
+    T::Serialize: ArchiveUnsized,
+    ArchivedRc<<T::Serialize as ArchiveUnsized>::Archived, ArcFlavor>:
+        Deserialize<Arc<T::Serialize>, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedRc<
+            <T::Serialize as ArchiveUnsized>::Archived,
+            ArcFlavor,
+        >,
+        deserializer: &mut D,
+    ) -> Result<Arc<T>, D::Error> {
+        Ok(T::upcast_arc(field.deserialize(deserializer)?))
+    }
+}
@@ -12,9 +12,10 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse::{Parse, ParseStream, Result},
-    parse_macro_input,
+    parse_macro_input, parse_quote,
     spanned::Spanned,
-    Attribute, Error, Ident, ItemImpl, ItemTrait, LitStr, Path, Token,
+    Attribute, Error, FnArg, GenericArgument, Ident, ItemFn, ItemImpl,
+    ItemTrait, LitStr, Path, PathArguments, ReturnType, Token, Type,
     Visibility,
 };
 
@@ -241,6 +242,18 @@ fn register_impl(
         (trait_.clone(), quote! {})
     };
 
+    // Impls annotated with `#[archive_dyn]` can be serialized through the
+    // serialize trait, which lets `AsDyn` serialize them as `dyn Trait`.
+    let mut input = input.clone();
+    input.items.push(parse_quote! {
+        #[doc(hidden)]
+        fn __rkyv_as_serialize_dyn(
+            &self,
+        ) -> ::core::option::Option<&(dyn #ser_trait + 'static)> {
+            ::core::option::Option::Some(self)
+        }
+    });
+
     Ok(quote! {
         #input
 
@@ -284,6 +297,20 @@ fn generate_traits(input: &ItemTrait, args: &Args) -> Result<TokenStream> {
             Ident::new(&format!("Serialize{}", name), name.span())
         });
 
+    // Impls which aren't annotated with `#[archive_dyn]` (like the impls for
+    // archived types) can't be serialized, and keep the default method.
+    let mut trait_input = input.clone();
+    trait_input.items.push(parse_quote! {
+        #[doc(hidden)]
+        fn __rkyv_as_serialize_dyn(
+            &self,
+        ) -> ::core::option::Option<
+            &(dyn #ser_trait<#generic_args> + 'static)
+        > {
+            ::core::option::Option::None
+        }
+    });
+
     let type_name_wheres = input.generics.type_params().map(|p| {
         let name = &p.ident;
         quote! { #name: TypeName }
@@ -439,7 +466,7 @@ fn generate_traits(input: &ItemTrait, args: &Args) -> Result<TokenStream> {
 
     Ok(quote! {
         #pointee_input
-        #input
+        #trait_input
 
         #[ptr_meta::pointee]
         #vis trait #ser_trait<#generic_params>:
@@ -479,6 +506,46 @@ fn generate_traits(input: &ItemTrait, args: &Args) -> Result<TokenStream> {
 
             #de_trait_impl
 
+            impl<#generic_params> rkyv_dyn::AsSerializeDyn
+                for dyn #name<#generic_args>
+            {
+                type Serialize = dyn #ser_trait<#generic_args>;
+
+                fn as_serialize_dyn(&self) -> Option<&Self::Serialize> {
+                    self.__rkyv_as_serialize_dyn()
+                }
+
+                fn upcast_box(value: Box<Self::Serialize>) -> Box<Self> {
+                    value
+                }
+
+                fn upcast_arc(
+                    value: std::sync::Arc<Self::Serialize>,
+                ) -> std::sync::Arc<Self> {
+                    value
+                }
+            }
+
+            impl<#generic_params> rkyv_dyn::AsSerializeDyn
+                for dyn #ser_trait<#generic_args>
+            {
+                type Serialize = Self;
+
+                fn as_serialize_dyn(&self) -> Option<&Self> {
+                    Some(self)
+                }
+
+                fn upcast_box(value: Box<Self>) -> Box<Self> {
+                    value
+                }
+
+                fn upcast_arc(
+                    value: std::sync::Arc<Self>,
+                ) -> std::sync::Arc<Self> {
+                    value
+                }
+            }
+
             impl<#generic_params> TypeName for dyn #de_trait<#generic_args> + '_
             where
                 #type_name_wheres
@@ -541,3 +608,178 @@ fn generate_traits(input: &ItemTrait, args: &Args) -> Result<TokenStream> {
         };
     })
 }
+
+struct JobArgs {
+    name: Option<LitStr>,
+    args: Option<Type>,
+}
+
+impl Parse for JobArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        mod kw {
+            syn::custom_keyword!(name);
+            syn::custom_keyword!(args);
+        }
+
+        let mut name = None;
+        let mut args = None;
+
+        let mut needs_punct = false;
+        while !input.is_empty() {
+            if needs_punct {
+                input.parse::<Token![,]>()?;
+            }
+
+            if input.peek(kw::name) {
+                if name.is_some() {
+                    return Err(input.error("duplicate name argument"));
+                }
+
+                input.parse::<kw::name>()?;
+                input.parse::<Token![=]>()?;
+                name = Some(input.parse::<LitStr>()?);
+            } else if input.peek(kw::args) {
+                if args.is_some() {
+                    return Err(input.error("duplicate args argument"));
+                }
+
+                input.parse::<kw::args>()?;
+                input.parse::<Token![=]>()?;
+                args = Some(input.parse::<Type>()?);
+            } else {
+                return Err(input.error(
+                    "expected name = \"...\" or args = ... parameters",
+                ));
+            }
+
+            needs_punct = true;
+        }
+
+        Ok(JobArgs { name, args })
+    }
+}
+
+/// Registers a function as a job which can be called by an archived job.
+///
+/// The function must take a single reference to its archived arguments and
+/// return nothing. This creates a type with the same name as the function,
+/// which implements `JobFn` and can be passed to `register_jobs!` and `Job`.
+///
+/// See the `job` module of `rkyv_dyn` for usage information and examples.
+///
+/// # Parameters
+///
+/// - `name = "..."`: Chooses the stable name of the job, which its job ID is
+///   derived from. By default, the job is named after the function. Set a name
+///   to keep the job ID the same when the function is renamed.
+/// - `args = ...`: Chooses the unarchived type of the arguments. By default,
+///   the argument must be written as `&Archived<T>` and the arguments are of
+///   type `T`.
+#[proc_macro_attribute]
+pub fn rkyv_job(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let args = parse_macro_input!(attr as JobArgs);
+
+    match apply_rkyv_job(&input, &args) {
+        Ok(result) => result.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+// Returns `T` if the given type is `&Archived<T>`.
+fn archived_arg(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Path(path) => &path.path,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Archived" {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn apply_rkyv_job(input: &ItemFn, args: &JobArgs) -> Result<TokenStream> {
+    let sig = &input.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &sig.generics,
+            "#[rkyv_job] can only be used on non-generic functions",
+        ));
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "#[rkyv_job] can't be used on async functions",
+        ));
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        return Err(Error::new_spanned(
+            ty,
+            "#[rkyv_job] functions must not return a value",
+        ));
+    }
+
+    let arg_ty = match (sig.inputs.len(), sig.inputs.first()) {
+        (1, Some(FnArg::Typed(arg))) => &*arg.ty,
+        _ => {
+            return Err(Error::new_spanned(
+                &sig.inputs,
+                "#[rkyv_job] functions must take a single reference to their \
+                 archived arguments",
+            ))
+        }
+    };
+
+    let args_ty = match &args.args {
+        Some(ty) => ty,
+        None => archived_arg(arg_ty).ok_or_else(|| {
+            Error::new_spanned(
+                arg_ty,
+                "expected the argument to be `&Archived<T>`; pass `args = T` \
+                 to choose the type of the arguments",
+            )
+        })?,
+    };
+
+    let vis = &input.vis;
+    let ident = &sig.ident;
+    let name = match &args.name {
+        Some(name) => name.clone(),
+        None => LitStr::new(&ident.to_string(), ident.span()),
+    };
+    let doc = format!("The job which calls [`{}()`].", ident);
+
+    Ok(quote! {
+        #input
+
+        #[doc = #doc]
+        #[allow(non_camel_case_types)]
+        #vis struct #ident {}
+
+        impl rkyv_dyn::job::JobFn for #ident {
+            type Args = #args_ty;
+
+            const NAME: &'static str = #name;
+
+            #[inline]
+            fn call(args: &rkyv::Archived<Self::Args>) {
+                #ident(args)
+            }
+        }
+    })
+}
@@ -208,6 +208,156 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(all(feature = "bytecheck", not(feature = "wasm")))]
+    fn registry_footer() {
+        use rkyv::{rancor::Error, to_bytes, Archived};
+        use rkyv_dyn::{
+            footer::{
+                access_footer, append_footer, split_footer, RegistryEntry,
+                RegistryFooter,
+            },
+            trait_impl,
+        };
+
+        trait Named {}
+        struct Unregistered;
+        impl Named for Unregistered {}
+
+        let trait_impl = trait_impl!(Unregistered as dyn Named; version = 3);
+        assert_eq!(trait_impl.name(), "Unregistered as dyn Named");
+        assert_eq!(trait_impl.version(), 3);
+
+        let footer = RegistryFooter {
+            entries: vec![RegistryEntry {
+                impl_id: 1000,
+                name: trait_impl.name().to_string(),
+                version: trait_impl.version(),
+            }],
+        };
+
+        let archive = to_bytes::<Error>(&0x1234u16).unwrap();
+        let bytes = append_footer::<Error>(archive, &footer).unwrap();
+        let (archive, archived_footer) =
+            access_footer::<Error>(&bytes).unwrap();
+        assert_eq!(
+            *rkyv::access::<Archived<u16>, Error>(archive).unwrap(),
+            0x1234
+        );
+        assert_eq!(archived_footer.entries.len(), 1);
+        assert_eq!(archived_footer.unknown_impls().count(), 1);
+        let error = archived_footer.check_known::<Error>().unwrap_err();
+        assert!(error.to_string().contains("Unregistered as dyn Named"));
+
+        let empty = RegistryFooter::default();
+        let archive = to_bytes::<Error>(&()).unwrap();
+        let bytes = append_footer::<Error>(archive, &empty).unwrap();
+        let (_, archived_footer) = access_footer::<Error>(&bytes).unwrap();
+        assert!(archived_footer.check_known::<Error>().is_ok());
+
+        assert!(split_footer::<Error>(&bytes[..bytes.len() - 1]).is_err());
+        assert!(split_footer::<Error>(&[0; 8]).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn fallback_impl() {
+        use rkyv_dyn::{fallback_impl, ArchivedDynMetadata};
+
+        #[ptr_meta::pointee]
+        trait Describe {
+            fn describe(&self) -> Result<&'static str, &'static str>;
+        }
+
+        struct MissingImpl;
+
+        impl Describe for MissingImpl {
+            fn describe(&self) -> Result<&'static str, &'static str> {
+                Err("unknown impl")
+            }
+        }
+
+        fallback_impl!(MissingImpl as dyn Describe);
+
+        let metadata = ArchivedDynMetadata::<dyn Describe>::new(1000);
+        assert!(!metadata.is_registered());
+        assert!(metadata.try_lookup_metadata().is_none());
+
+        let placeholder = ptr_meta::from_raw_parts::<dyn Describe>(
+            core::ptr::NonNull::<u8>::dangling().as_ptr().cast(),
+            metadata.lookup_metadata_or_fallback(),
+        );
+        let placeholder = unsafe { &*placeholder };
+        assert_eq!(placeholder.describe(), Err("unknown impl"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bytecheck", not(feature = "wasm")))]
+    fn archived_jobs() {
+        use std::sync::Mutex;
+
+        use rkyv::{rancor::Error, to_bytes, Archive, Archived, Serialize};
+        use rkyv_dyn::{
+            job::{job_id, lookup_job, ArchivedJob, Job, JobFn},
+            register_jobs, rkyv_job,
+        };
+
+        static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Greet {
+            name: String,
+            times: u32,
+        }
+
+        #[rkyv_job]
+        fn greet(args: &Archived<Greet>) {
+            for _ in 0..args.times.to_native() {
+                let line = format!("hello, {}", args.name);
+                LOG.lock().unwrap().push(line);
+            }
+        }
+
+        #[rkyv_job(name = "jobs::clear_log", args = ())]
+        fn clear(_: &()) {
+            LOG.lock().unwrap().clear();
+        }
+
+        assert_eq!(<greet as JobFn>::NAME, "greet");
+        assert_eq!(<clear as JobFn>::ID, job_id("jobs::clear_log"));
+
+        let job = Job::<greet>::new(Greet {
+            name: "a name which is stored out of line".to_string(),
+            times: 2,
+        });
+        let bytes = to_bytes::<Error>(&job).unwrap();
+        let archived = rkyv::access::<ArchivedJob, Error>(&bytes).unwrap();
+        assert!(archived.is::<greet>());
+        assert!(!archived.is::<clear>());
+
+        // Jobs can't be invoked before they are registered.
+        assert!(archived.name().is_none());
+        assert!(archived.invoke::<Error>().is_err());
+
+        register_jobs!(greet, clear);
+        assert_eq!(lookup_job(job_id("greet")).unwrap().name(), "greet");
+
+        assert_eq!(archived.name(), Some("greet"));
+        archived.invoke::<Error>().unwrap();
+        assert_eq!(LOG.lock().unwrap().len(), 2);
+        assert_eq!(
+            LOG.lock().unwrap()[1],
+            "hello, a name which is stored out of line"
+        );
+
+        let bytes = to_bytes::<Error>(&Job::<clear>::new(())).unwrap();
+        let archived = rkyv::access::<ArchivedJob, Error>(&bytes).unwrap();
+        assert_eq!(archived.name(), Some("jobs::clear_log"));
+        unsafe { archived.invoke_unchecked::<Error>().unwrap() };
+        assert!(LOG.lock().unwrap().is_empty());
+    }
+
     // TODO: uncomment and fix
     // #[test]
     // #[cfg(not(feature = "wasm"))]
@@ -153,4 +153,117 @@ mod tests {
         assert_eq!(ArchivedFoo::B as usize, 4);
         assert_eq!(ArchivedFoo::C as usize, 6);
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn format_descriptor() {
+        use rkyv::{
+            descriptor::{Endianness, FormatDescriptor},
+            stability::FORMAT_VERSION,
+        };
+
+        let local = FormatDescriptor::for_root::<(u32, u64)>();
+        assert_eq!(local.format_version, FORMAT_VERSION);
+        assert_ne!(local.root_layout_hash, 0);
+        assert_eq!(
+            local.root_layout_hash,
+            FormatDescriptor::for_root::<(u32, u64)>().root_layout_hash,
+        );
+
+        let bytes = local.to_bytes();
+        assert_eq!(bytes.len(), FormatDescriptor::ENCODED_LEN);
+        assert_eq!(FormatDescriptor::from_bytes(&bytes), Some(local));
+        assert_eq!(FormatDescriptor::from_bytes(&bytes[1..]), None);
+        let mut corrupt = bytes;
+        corrupt[0] = 0;
+        assert_eq!(FormatDescriptor::from_bytes(&corrupt), None);
+
+        assert!(local.check_compatible(&local).is_ok());
+        assert!(local.check_compatible(&FormatDescriptor::current()).is_ok());
+
+        let mut remote = local;
+        remote.pointer_width *= 2;
+        remote.endianness = match local.endianness {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        };
+        let mismatch = local.check_compatible(&remote).unwrap_err();
+        assert!(mismatch.pointer_width());
+        assert!(mismatch.endianness());
+        assert!(!mismatch.format_version());
+        assert!(!mismatch.flags());
+        assert!(!mismatch.root_layout_hash());
+        assert_eq!(mismatch.remote(), &remote);
+
+        let mismatch = local
+            .check_compatible(&FormatDescriptor::for_root::<u32>())
+            .unwrap_err();
+        assert!(mismatch.root_layout_hash());
+        assert!(!mismatch.pointer_width());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archived_field_offsets() {
+        use core::mem::align_of;
+
+        use rkyv::{Archive, Archived};
+
+        #[derive(Archive)]
+        #[archive(offsets)]
+        struct Header {
+            tag: u8,
+            len: u32,
+            r#type: u16,
+        }
+
+        #[derive(Archive)]
+        #[archive(offsets)]
+        struct Pair<T>(u8, T);
+
+        let len_offset = align_of::<Archived<u32>>();
+        assert_eq!(ArchivedHeader::OFFSET_TAG, 0);
+        assert_eq!(ArchivedHeader::OFFSET_LEN, len_offset);
+        assert_eq!(ArchivedHeader::OFFSET_TYPE, len_offset + 4);
+
+        assert_eq!(ArchivedPair::<u64>::OFFSET_0, 0);
+        assert_eq!(ArchivedPair::<u64>::OFFSET_1, align_of::<Archived<u64>>());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archived_field_order() {
+        use core::mem::size_of;
+
+        use rkyv::{Archive, Archived};
+
+        #[derive(Archive)]
+        #[archive(preserve_order, offsets)]
+        struct Declared {
+            a: u8,
+            b: u64,
+            c: u16,
+        }
+
+        #[derive(Archive)]
+        #[archive(reorder = "size_desc", offsets)]
+        struct Sorted {
+            a: u8,
+            b: u64,
+            c: u16,
+            d: [u32; 2],
+            e: (u8, u8),
+        }
+
+        assert_eq!(ArchivedDeclared::OFFSET_A, 0);
+        assert!(ArchivedDeclared::OFFSET_C > ArchivedDeclared::OFFSET_B);
+
+        // Fields of unknown size come first, then the primitives by size.
+        let e = size_of::<Archived<(u8, u8)>>();
+        assert_eq!(ArchivedSorted::OFFSET_E, 0);
+        assert!(ArchivedSorted::OFFSET_B >= e);
+        assert_eq!(ArchivedSorted::OFFSET_D, ArchivedSorted::OFFSET_B + 8);
+        assert_eq!(ArchivedSorted::OFFSET_C, ArchivedSorted::OFFSET_D + 8);
+        assert_eq!(ArchivedSorted::OFFSET_A, ArchivedSorted::OFFSET_C + 2);
+    }
 }
@@ -1081,6 +1081,34 @@ mod tests {
             .all(|&b| b == 0));
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn buffer_serializer_zeroes_overaligned_padding() {
+        use core::mem::size_of;
+
+        use rkyv::ser::Positional as _;
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(repr(C, align(64)))]
+        pub struct OverAligned {
+            a: u8,
+        }
+
+        // Writing the first vec leaves the second misaligned by one byte
+        let value =
+            (vec![0u8], vec![OverAligned { a: 0 }, OverAligned { a: 0 }]);
+        let mut buffer = AlignedBytes([0xccu8; 512]);
+        let serializer =
+            serialize_into::<_, Error>(&value, BufferWriter::new(&mut buffer))
+                .unwrap();
+        let len = serializer.pos();
+
+        crate::util::assert_initialized(&buffer[0..len]);
+        // Everything before the root is zero bytes, zero fields, or padding
+        let root = len - size_of::<Archived<(Vec<u8>, Vec<OverAligned>)>>();
+        assert!(buffer[0..root].iter().all(|&b| b == 0));
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn const_generics() {
@@ -1953,6 +1981,99 @@ mod tests {
         assert_eq!(value, deserialized);
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_btree_map_range() {
+        use core::ops::Bound;
+
+        let mut value = BTreeMap::new();
+        for i in 0..200 {
+            value.insert(i * 2, i);
+        }
+
+        let result = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe {
+            access_unchecked::<Archived<BTreeMap<i32, i32>>>(result.as_slice())
+        };
+
+        let native = |(k, v): (&Archived<i32>, &Archived<i32>)| {
+            (k.to_native(), v.to_native())
+        };
+        let expected = |(k, v): (&i32, &i32)| (*k, *v);
+
+        assert_eq!(archived.first_key_value().map(native), Some((0, 0)));
+        assert_eq!(archived.last_key_value().map(native), Some((398, 199)));
+        assert!(archived.iter().map(native).eq(value.iter().map(expected)));
+        assert!(archived
+            .iter()
+            .rev()
+            .map(native)
+            .eq(value.iter().rev().map(expected)));
+
+        let bounds = [
+            Bound::Included(-1),
+            Bound::Included(0),
+            Bound::Excluded(0),
+            Bound::Included(101),
+            Bound::Excluded(150),
+            Bound::Included(398),
+            Bound::Excluded(398),
+            Bound::Included(500),
+            Bound::Unbounded,
+        ];
+        for start in bounds {
+            for end in bounds {
+                let start_key = start.map(Archived::<i32>::from_native);
+                let end_key = end.map(Archived::<i32>::from_native);
+                let range =
+                    archived.range_bounds(start_key.as_ref(), end_key.as_ref());
+                let empty = match (start, end) {
+                    (Bound::Included(s), Bound::Included(e)) => s > e,
+                    (Bound::Included(s), Bound::Excluded(e))
+                    | (Bound::Excluded(s), Bound::Included(e))
+                    | (Bound::Excluded(s), Bound::Excluded(e)) => s >= e,
+                    _ => false,
+                };
+                if empty {
+                    assert_eq!(range.count(), 0);
+                    continue;
+                }
+
+                let expected_range = value.range((start, end));
+                assert!(range
+                    .clone()
+                    .map(native)
+                    .eq(expected_range.clone().map(expected)));
+                assert!(range
+                    .rev()
+                    .map(native)
+                    .eq(expected_range.rev().map(expected)));
+            }
+        }
+
+        // Iterating from both ends meets in the middle.
+        let mut range = archived.range(Archived::<i32>::from_native(10)..);
+        let mut expected_range = value.range(10..);
+        loop {
+            let front = range.next().map(native);
+            assert_eq!(front, expected_range.next().map(expected));
+            let back = range.next_back().map(native);
+            assert_eq!(back, expected_range.next_back().map(expected));
+            if front.is_none() {
+                break;
+            }
+        }
+
+        let empty = BTreeMap::<i32, i32>::new();
+        let result = to_bytes::<Error>(&empty).unwrap();
+        let archived = unsafe {
+            access_unchecked::<Archived<BTreeMap<i32, i32>>>(result.as_slice())
+        };
+        assert!(archived.first_key_value().is_none());
+        assert!(archived.last_key_value().is_none());
+        assert_eq!(archived.iter().count(), 0);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_zst_containers() {
@@ -2045,6 +2166,3623 @@ mod tests {
         assert_ne!(tracker.max_bytes_allocated(), 0);
         assert_eq!(tracker.max_allocations(), 1);
         assert_ne!(tracker.min_buffer_size(), 0);
+        assert_eq!(tracker.bytes_allocated(), 0);
+        assert_eq!(tracker.allocations(), 0);
+        assert_eq!(tracker.total_allocations(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn scratch_tracker_core_serializer() {
+        use rkyv::ser::CoreSerializer;
+
+        let value = vec![vec![1u32, 2], vec![3, 4], vec![5, 6]];
+        let serializer =
+            CoreSerializer::<256, 256>::default().with_allocation_tracker();
+        let serializer =
+            serialize_into::<_, Error>(&value, serializer).unwrap();
+        let tracker = serializer.allocation_tracker();
+        assert_eq!(tracker.total_allocations(), 1);
+        assert_eq!(tracker.max_allocations(), 1);
+
+        // A core serializer with the reported amount of scratch space succeeds.
+        assert!(tracker.min_buffer_size() <= 32);
+        let serializer = CoreSerializer::<256, 32>::default();
+        serialize_into::<_, Error>(&value, serializer).unwrap();
+
+        // One with too little fails.
+        let serializer = CoreSerializer::<256, 4>::default();
+        assert!(serialize_into::<_, Error>(&value, serializer).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn fallback_allocator() {
+        use rkyv::{
+            ser::{
+                allocator::{
+                    AllocationTracker, BufferAllocator, Fallback, Unavailable,
+                },
+                FallbackCoreSerializer,
+            },
+            util::AlignedBytes,
+        };
+
+        let value = vec![vec![1u32, 2], vec![3, 4], vec![5, 6]];
+
+        // Scratch space comes from the secondary allocator when the primary
+        // allocator runs out.
+        let serializer = FallbackCoreSerializer::<256, 8, _>::new(
+            Default::default(),
+            Fallback::new(
+                Default::default(),
+                AllocationTracker::new(BufferAllocator::new(
+                    AlignedBytes::<64>::default(),
+                )),
+            ),
+            Default::default(),
+        );
+        let serializer =
+            serialize_into::<_, Error>(&value, serializer).unwrap();
+        let secondary = serializer.allocator.secondary();
+        assert_eq!(secondary.total_allocations(), 1);
+        assert_eq!(secondary.bytes_allocated(), 0);
+
+        // The primary allocator is used when it has enough space.
+        let serializer = FallbackCoreSerializer::<256, 64, _>::new(
+            Default::default(),
+            Fallback::new(
+                Default::default(),
+                AllocationTracker::new(Unavailable),
+            ),
+            Default::default(),
+        );
+        let serializer =
+            serialize_into::<_, Error>(&value, serializer).unwrap();
+        assert_eq!(serializer.allocator.secondary().total_allocations(), 0);
+
+        // Running out of both reports how much space was needed.
+        let serializer = FallbackCoreSerializer::<256, 8, _>::new(
+            Default::default(),
+            Fallback::new(Default::default(), Unavailable),
+            Default::default(),
+        );
+        let error = serialize_into::<_, Error>(&value, serializer).unwrap_err();
+        assert!(error.to_string().contains("scratch space exhausted"));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serialization_stats() {
+        use rkyv::ser::stats::{serialize_tracked, Stats};
+
+        let shared = Rc::new(vec![1u32, 2, 3, 4, 5, 6, 7, 8]);
+        let value = vec![shared.clone(), shared.clone(), shared];
+
+        let mut serializer = Stats::new(DefaultSerializer::default());
+        serialize_tracked(&value, Strategy::<_, Error>::wrap(&mut serializer))
+            .unwrap();
+        let (serializer, stats) = serializer.into_raw_parts();
+
+        assert_eq!(stats.bytes_written(), serializer.writer.len());
+        assert_eq!(
+            stats.bytes_for_type::<Vec<Rc<Vec<u32>>>>(),
+            stats.bytes_written()
+        );
+        assert_eq!(stats.shared_hits(), 2);
+        assert!(stats.shared_bytes_saved() >= 2 * 8 * 4);
+        assert_ne!(stats.scratch_high_water(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archived_size() {
+        use core::mem::size_of;
+
+        use rkyv::{boxed::ArchivedBox, ArchivedSize};
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(ArchivedSize))]
+        struct Test {
+            short: String,
+            long: String,
+            values: Vec<u32>,
+            boxed: Option<Box<[u16]>>,
+            nothing: Option<Box<u64>>,
+        }
+
+        let value = Test {
+            short: "hi".to_string(),
+            long: "a string which is too long to be stored inline".to_string(),
+            values: vec![1, 2, 3],
+            boxed: Some(vec![4, 5].into_boxed_slice()),
+            nothing: None,
+        };
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedTest>(&bytes) };
+
+        assert_eq!(archived.short.out_of_line_size(), 0);
+        assert_eq!(archived.long.out_of_line_size(), value.long.len());
+        assert_eq!(
+            archived.boxed.as_ref().map(|b| b.archived_size()),
+            Some(size_of::<ArchivedBox<[Archived<u16>]>>() + 4),
+        );
+        assert_eq!(
+            archived.out_of_line_size(),
+            value.long.len() + 3 * 4 + 2 * 2,
+        );
+        assert_eq!(
+            archived.archived_size(),
+            size_of::<ArchivedTest>() + archived.out_of_line_size(),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn cow_archive_overlay() {
+        use rkyv::{cow::CowArchive, Extract};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive_attr(derive(Extract))]
+        struct Header {
+            id: u32,
+            tags: Vec<String>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive_attr(derive(Extract))]
+        struct Asset {
+            header: Header,
+            payload: Vec<u8>,
+            parent: Option<Box<str>>,
+        }
+
+        let value = Asset {
+            header: Header {
+                id: 1,
+                tags: vec!["a tag which will be replaced".to_string()],
+            },
+            payload: vec![3; 256],
+            parent: None,
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedAsset>(&bytes) };
+
+        let mut cow = CowArchive::new(archived);
+        assert!(!cow.is_patched());
+
+        cow.set::<u32, Error>(|a| &a.header.id, &2).unwrap();
+        assert_eq!(*cow.get(|a| &a.header.id), 2);
+        assert_eq!(archived.header.id, 1);
+
+        // Patching a containing field replaces the patch for the inner field
+        let header = Header {
+            id: 3,
+            tags: vec!["new".to_string()],
+        };
+        cow.set::<Header, Error>(|a| &a.header, &header).unwrap();
+        assert_eq!(cow.patch_count(), 1);
+        assert!(!cow.is_field_patched(|a| &a.header.id));
+        assert_eq!(cow.get(|a| &a.header).id, 3);
+
+        let parent = Some("root".to_string().into_boxed_str());
+        cow.set::<Option<Box<str>>, Error>(|a| &a.parent, &parent)
+            .unwrap();
+        assert!(cow.revert(|a| &a.parent));
+        cow.set::<Option<Box<str>>, Error>(|a| &a.parent, &parent)
+            .unwrap();
+
+        let frozen = cow.freeze::<Error>().unwrap();
+        let edited = unsafe { access_unchecked::<ArchivedAsset>(&frozen) };
+        let deserialized =
+            deserialize::<Asset, _, Error>(edited, &mut ()).unwrap();
+        assert_eq!(
+            deserialized,
+            Asset {
+                header,
+                payload: vec![3; 256],
+                parent,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn extract_subgraph() {
+        use rkyv::{extract, Extract};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive_attr(derive(Extract))]
+        enum Shape {
+            Empty,
+            Circle(u32),
+            Polygon {
+                name: String,
+                points: Vec<(i16, i16)>,
+            },
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive_attr(derive(Extract))]
+        struct Layer {
+            name: String,
+            shapes: Vec<Shape>,
+            shared: Rc<Box<[u8]>>,
+            also_shared: Rc<Box<[u8]>>,
+        }
+
+        let shared = Rc::new(vec![7u8; 64].into_boxed_slice());
+        let layers = (0..32)
+            .map(|i| Layer {
+                name: "a layer with a fairly long name".to_string(),
+                shapes: vec![
+                    Shape::Empty,
+                    Shape::Circle(i),
+                    Shape::Polygon {
+                        name: "a polygon with several points".to_string(),
+                        points: vec![(0, 0), (i as i16, 1), (2, -3)],
+                    },
+                ],
+                shared: shared.clone(),
+                also_shared: shared.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let bytes = to_bytes::<Error>(&layers).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Layer>>>(&bytes) };
+
+        let extracted = extract::<_, Error>(&archived[17]).unwrap();
+        assert!(extracted.len() < bytes.len() / 8);
+
+        let layer = unsafe { access_unchecked::<ArchivedLayer>(&extracted) };
+        assert!(core::ptr::eq(layer.shared.get(), layer.also_shared.get()));
+
+        let mut deserializer = DefaultDeserializer::default();
+        let deserialized =
+            deserialize::<Layer, _, Error>(layer, &mut deserializer).unwrap();
+        assert_eq!(deserialized, layers[17]);
+
+        // Extracting an extracted value produces an identical archive
+        let again = extract::<_, Error>(layer).unwrap();
+        assert_eq!(again.as_slice(), extracted.as_slice());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn compact_patched_archive() {
+        use rkyv::{extract::compact_unchecked, ser::AllocSerializer};
+
+        let old = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        let new = vec![
+            "short".to_string(),
+            "a string which is too long to be stored inline".to_string(),
+        ];
+
+        // Append a new root, which leaves the old value as dead data
+        let bytes = to_bytes::<Error>(&old).unwrap();
+        let old_len = bytes.len();
+        let serializer = AllocSerializer {
+            writer: bytes,
+            ..Default::default()
+        };
+        let bytes = serialize_into::<_, Error>(&new, serializer)
+            .unwrap()
+            .into_writer();
+
+        let compacted = unsafe {
+            compact_unchecked::<Archived<Vec<String>>, Error>(&bytes).unwrap()
+        };
+        assert_eq!(
+            compacted.as_bytes().len() + compacted.reclaimed(),
+            bytes.len()
+        );
+        assert!(compacted.reclaimed() >= old_len);
+
+        let archived = unsafe {
+            access_unchecked::<Archived<Vec<String>>>(compacted.as_bytes())
+        };
+        assert_eq!(archived.len(), new.len());
+        for (a, b) in archived.iter().zip(new.iter()) {
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn far_pointers() {
+        use rkyv::{
+            segment::{Segmented, WithSegments},
+            ser::AllocSerializer,
+            util::serialize,
+            with::Far,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Chunk {
+            id: u32,
+            data: Vec<u8>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Dataset {
+            name: String,
+            #[with(Far)]
+            first: Chunk,
+            #[with(Far)]
+            second: Chunk,
+        }
+
+        let value = Dataset {
+            name: "dataset".to_string(),
+            first: Chunk {
+                id: 1,
+                data: vec![1; 100],
+            },
+            second: Chunk {
+                id: 2,
+                data: vec![2; 200],
+            },
+        };
+
+        let mut serializer = Segmented::new(AllocSerializer::default());
+        serialize::<_, Error>(&value, &mut serializer).unwrap();
+        let (serializer, segments) = serializer.into_raw_parts();
+        let bytes = serializer.into_writer();
+        assert_eq!(segments.len(), 2);
+
+        let archived = unsafe { access_unchecked::<ArchivedDataset>(&bytes) };
+        assert_eq!(archived.first.segment(), 0);
+        assert_eq!(archived.second.segment(), 1);
+
+        // Only the second segment is loaded
+        let partial = [&[][..], segments[1].as_slice()];
+        let second = unsafe {
+            archived
+                .second
+                .get_unchecked::<_, Error>(&&partial[..])
+                .unwrap()
+        };
+        assert_eq!(second.id, 2);
+        assert_eq!(second.data.len(), 200);
+        assert!(unsafe {
+            archived
+                .first
+                .get_unchecked::<_, Error>(&&partial[..])
+                .is_err()
+        });
+
+        let mut deserializer = WithSegments::new(segments.as_slice(), ());
+        let deserialized =
+            deserialize::<Dataset, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert_eq!(deserialized, value);
+
+        // Segments are validated when they are deserialized
+        let mut corrupted = segments.clone();
+        let len = corrupted[1].len();
+        corrupted[1][len - 4..].copy_from_slice(&[0xff; 4]);
+        let mut deserializer = WithSegments::new(corrupted.as_slice(), ());
+        assert!(
+            deserialize::<Dataset, _, Error>(archived, &mut deserializer)
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn paged_writer() {
+        use rkyv::{
+            ser::{paged::PagedWriter, AllocSerializer},
+            util::serialize,
+        };
+
+        const PAGE_SIZE: usize = 64;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Entry {
+            key: u64,
+            name: String,
+            large: Vec<u8>,
+        }
+
+        let value = (0..16)
+            .map(|i| Entry {
+                key: i,
+                name: "x".repeat(i as usize * 5),
+                large: vec![i as u8; i as usize * 20],
+            })
+            .collect::<Vec<_>>();
+
+        let mut serializer =
+            PagedWriter::new(AllocSerializer::default(), PAGE_SIZE);
+        serialize::<_, Error>(&value, &mut serializer).unwrap();
+        assert!(serializer.objects_on_page(0).next().is_some());
+        let page_count = serializer.page_count();
+        let (serializer, objects) = serializer.into_raw_parts();
+        let bytes = serializer.into_writer();
+        assert_eq!(page_count, (bytes.len() + PAGE_SIZE - 1) / PAGE_SIZE);
+
+        let mut spans_pages = false;
+        for object in objects.iter() {
+            assert_eq!(object.first_page(), object.pos() / PAGE_SIZE);
+            if object.len() <= PAGE_SIZE {
+                let start = object.pos() % PAGE_SIZE;
+                assert!(start + object.len() <= PAGE_SIZE);
+                assert_eq!(object.first_page(), object.last_page());
+            } else {
+                spans_pages = true;
+                assert!(object.last_page() > object.first_page());
+            }
+        }
+        assert!(spans_pages);
+
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Entry>>>(&bytes) };
+        let deserialized =
+            deserialize::<Vec<Entry>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn analyze_type_sizes() {
+        use core::mem::size_of;
+
+        use rkyv::{analyze::analyze_unchecked, Analyze};
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(Analyze))]
+        struct Component {
+            name: String,
+            weights: Vec<u32>,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(Analyze))]
+        struct Model {
+            components: Vec<Component>,
+            shared: Rc<[u64]>,
+            other: Rc<[u64]>,
+            label: Option<Box<str>>,
+        }
+
+        fn build(weights: usize) -> Model {
+            let shared = Rc::<[u64]>::from(vec![7; 10]);
+            Model {
+                components: (0..4)
+                    .map(|i| Component {
+                        name: format!("component number {}", i),
+                        weights: vec![i; weights],
+                    })
+                    .collect(),
+                other: shared.clone(),
+                shared,
+                label: Some("label".into()),
+            }
+        }
+
+        let bytes = to_bytes::<Error>(&build(8)).unwrap();
+        let report = unsafe { analyze_unchecked::<ArchivedModel>(&bytes) };
+
+        let model = report.get::<ArchivedModel>().unwrap();
+        assert_eq!(model.count(), 1);
+        assert_eq!(model.bytes(), size_of::<ArchivedModel>());
+        assert_eq!(model.total_bytes(), report.total_bytes());
+        assert!(report.total_bytes() <= bytes.len());
+
+        let components = report.get::<ArchivedComponent>().unwrap();
+        assert_eq!(components.count(), 4);
+        assert_eq!(components.bytes(), 4 * size_of::<ArchivedComponent>());
+
+        let weights = report.get::<Archived<u32>>().unwrap();
+        assert_eq!(weights.count(), 4 * 8);
+
+        // The shared slice is only counted once
+        let shared = report.get::<[Archived<u64>]>().unwrap();
+        assert_eq!(shared.count(), 1);
+        assert_eq!(shared.bytes(), 10 * 8);
+        assert_eq!(report.get::<Archived<u64>>().unwrap().count(), 10);
+
+        assert_eq!(report.get::<str>().unwrap().count(), 5);
+
+        // Growing one component shows up in its total size
+        let bigger = to_bytes::<Error>(&build(64)).unwrap();
+        let bigger = unsafe { analyze_unchecked::<ArchivedModel>(&bigger) };
+        let before = components.total_bytes();
+        let after = bigger.get::<ArchivedComponent>().unwrap().total_bytes();
+        assert!(after >= 2 * before);
+        assert_eq!(
+            bigger.get::<ArchivedComponent>().unwrap().bytes(),
+            components.bytes(),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn search_archived_strings_and_bytes() {
+        #[derive(Archive, Serialize)]
+        struct Log {
+            line: String,
+            payload: Vec<u8>,
+        }
+
+        let value = Log {
+            line: "GET /index.html 200 héllo".to_string(),
+            payload: b"key=value;other=thing;last".to_vec(),
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedLog>(&bytes) };
+
+        assert!(archived.line.contains("index"));
+        assert!(archived.line.contains('é'));
+        assert!(!archived.line.contains("POST"));
+        assert_eq!(archived.line.find("200"), Some(16));
+        assert_eq!(archived.line.find('é'), Some(21));
+        assert!(archived.line.starts_with("GET "));
+        assert!(!archived.line.starts_with("index"));
+        assert_eq!(
+            archived.line.split(' ').collect::<Vec<_>>(),
+            ["GET", "/index.html", "200", "héllo"],
+        );
+        assert_eq!(
+            archived.line.split("é").collect::<Vec<_>>(),
+            ["GET /index.html 200 h", "llo"],
+        );
+
+        assert!(archived.payload.contains(b"other"));
+        assert!(archived.payload.contains(&b';'));
+        assert!(!archived.payload.contains("missing"));
+        assert_eq!(archived.payload.find(b'='), Some(3));
+        assert_eq!(archived.payload.find(&b"thing"[..]), Some(16));
+        assert!(archived.payload.starts_with("key="));
+        assert_eq!(
+            archived.payload.split(b';').collect::<Vec<_>>(),
+            [&b"key=value"[..], b"other=thing", b"last"],
+        );
+        assert_eq!(
+            archived.payload.split(b"").collect::<Vec<_>>(),
+            [archived.payload.as_slice()],
+        );
+        assert_eq!(archived.payload.split(b"last").last(), Some(&b""[..]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archived_vec_as_native_slice() {
+        use rkyv::{rend::u32_be, NativeLayout};
+
+        let value = (0..100u32).collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+        assert_eq!(
+            archived.as_native_slice().is_some(),
+            <Archived<u32> as NativeLayout>::LAYOUT_MATCHES,
+        );
+        if let Some(native) = archived.as_native_slice() {
+            assert_eq!(native, value.as_slice());
+        }
+
+        let bytes = to_bytes::<Error>(&vec![[1u8, 2, 3]; 4]).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<[u8; 3]>>>(&bytes) };
+        assert_eq!(archived.as_native_slice(), Some(&[[1, 2, 3]; 4][..]));
+
+        let value = (0..10u32).map(u32_be::from_native).collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32_be>>>(&bytes) };
+        assert_eq!(
+            archived.as_native_slice().is_some(),
+            cfg!(target_endian = "big"),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archived_vec_copy_to_native() {
+        use rkyv::rend::{u16_le, u32_be};
+
+        let value = (0..100u32).collect::<Vec<_>>();
+
+        let be = value.iter().copied().map(u32_be::from_native);
+        let bytes = to_bytes::<Error>(&be.collect::<Vec<_>>()).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32_be>>>(&bytes) };
+        let mut native = vec![0; 100];
+        archived.copy_to_native(&mut native);
+        assert_eq!(native, value);
+        assert_eq!(archived.to_native_vec(), value);
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+        let mut native = vec![0; 100];
+        archived.copy_to_native(&mut native);
+        assert_eq!(native, value);
+
+        let pairs = (0..37u16)
+            .map(|i| [u16_le::from_native(i), u16_le::from_native(i * 2)])
+            .collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&pairs).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<[u16_le; 2]>>>(&bytes) };
+        let native = archived.to_native_vec();
+        assert_eq!(native.len(), 37);
+        assert_eq!(native[36], [36, 72]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn unify_with_custom_hasher() {
+        use rkyv::{
+            de,
+            hash::FxBuildHasher,
+            ser::{self, allocator::GlobalAllocator, Composite},
+        };
+
+        let shared = Rc::new(42u32);
+        let value = vec![shared.clone(), Rc::new(7), shared.clone()];
+
+        let serializer = Composite::new(
+            AlignedVec::new(),
+            GlobalAllocator::new(),
+            ser::Unify::with_hasher(FxBuildHasher::default()),
+        );
+        let bytes = serialize_into::<_, Error>(&value, serializer)
+            .unwrap()
+            .into_writer();
+
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Rc<u32>>>>(&bytes) };
+        assert_eq!(
+            &*archived[0] as *const Archived<u32>,
+            &*archived[2] as *const Archived<u32>,
+        );
+
+        #[cfg(feature = "bytecheck")]
+        {
+            use rkyv::validation::{
+                util::access_with_context, validators::DefaultValidator,
+            };
+
+            let mut validator =
+                DefaultValidator::with_hasher(&bytes, FxBuildHasher::default());
+            access_with_context::<Archived<Vec<Rc<u32>>>, _, Error>(
+                &bytes,
+                &mut validator,
+            )
+            .unwrap();
+        }
+
+        let mut deserializer =
+            de::Unify::with_capacity_and_hasher(2, FxBuildHasher::default());
+        let deserialized =
+            deserialize::<Vec<Rc<u32>>, _, Error>(archived, &mut deserializer)
+                .unwrap();
+        assert_eq!(deserialized, value);
+        assert!(Rc::ptr_eq(&deserialized[0], &deserialized[2]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn aligned_vec_try_reserve() {
+        use rkyv::{
+            ser::AllocSerializer,
+            util::{serialize_with_size_hint, to_bytes_with_capacity},
+        };
+
+        let mut vec = AlignedVec::<16>::new();
+        vec.push(1);
+        vec.try_reserve_exact(15).unwrap();
+        assert_eq!(vec.capacity(), 16);
+        vec.try_reserve(17).unwrap();
+        assert_eq!(vec.capacity(), 32);
+
+        let err = vec.try_reserve(usize::MAX).unwrap_err();
+        assert!(err.layout().is_none());
+        let err = vec.try_reserve_exact(AlignedVec::<16>::MAX_CAPACITY);
+        assert!(err.is_err());
+        assert_eq!(vec.as_slice(), &[1]);
+        assert_eq!(vec.capacity(), 32);
+
+        let value = vec![42u32; 100];
+        let bytes = to_bytes_with_capacity::<Error>(&value, 1024).unwrap();
+        assert_eq!(bytes.capacity(), 1024);
+        assert_eq!(
+            bytes.as_slice(),
+            to_bytes::<Error>(&value).unwrap().as_slice()
+        );
+        assert!(to_bytes_with_capacity::<Error>(&value, usize::MAX).is_err());
+
+        let mut serializer = AllocSerializer::default();
+        serialize_with_size_hint::<_, Error>(&value, &mut serializer, 512)
+            .unwrap();
+        let writer = serializer.into_writer();
+        assert_eq!(writer.capacity(), 512);
+        assert_eq!(writer.as_slice(), bytes.as_slice());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn read_archived_in_const_context() {
+        use rkyv::{
+            primitive::{ArchivedU16, ArchivedU32},
+            util::{read_pos_unchecked, read_unchecked},
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(Clone, Copy))]
+        struct Config {
+            baud: u32,
+            retries: u16,
+        }
+
+        const BLOB: [u8; 6] = [9, 9, 9, 9, 1, 1];
+        const BAUD: ArchivedU32 =
+            unsafe { read_pos_unchecked::<ArchivedU32>(&BLOB, 0) };
+        const RETRIES: ArchivedU16 =
+            unsafe { read_pos_unchecked::<ArchivedU16>(&BLOB, 4) };
+        assert_eq!(BAUD, 0x09090909);
+        assert_eq!(RETRIES, 0x0101);
+
+        let value = Config {
+            baud: 115_200,
+            retries: 3,
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let config = unsafe { read_unchecked::<ArchivedConfig>(&bytes) };
+        assert_eq!(config.baud, 115_200);
+        assert_eq!(config.retries, 3);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn seal_iterators() {
+        let mut bytes = to_bytes::<Error>(&vec![1u32, 2, 3, 4]).unwrap();
+        let archived =
+            unsafe { access_unchecked_mut::<Archived<Vec<u32>>>(&mut bytes) };
+        let mut iter = archived.iter_seal();
+        assert_eq!(iter.len(), 4);
+        *iter.next_back().unwrap() = 40.into();
+        for mut value in iter {
+            *value = (value.to_native() * 10).into();
+        }
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+        assert_eq!(archived.as_slice(), &[10, 20, 30, 40]);
+
+        let map = (0..100u32).map(|i| (i, i * 2)).collect::<BTreeMap<_, _>>();
+        let mut bytes = to_bytes::<Error>(&map).unwrap();
+        let archived = unsafe {
+            access_unchecked_mut::<Archived<BTreeMap<u32, u32>>>(&mut bytes)
+        };
+        let mut values = archived.values_seal();
+        assert_eq!(values.len(), 100);
+        let mut expected = 0;
+        for mut value in values.by_ref() {
+            assert_eq!(*value, expected * 2);
+            *value = 0.into();
+            expected += 1;
+        }
+        assert_eq!(expected, 100);
+        assert!(values.next().is_none());
+
+        let archived =
+            unsafe { access_unchecked::<Archived<BTreeMap<u32, u32>>>(&bytes) };
+        assert_eq!(archived.len(), 100);
+        let mut count = 0;
+        archived.visit(|_, value| {
+            assert_eq!(*value, 0);
+            count += 1;
+            core::ops::ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(count, 100);
+
+        #[cfg(feature = "std")]
+        {
+            use std::collections::HashMap;
+
+            let map = (0..10u32).map(|i| (i, i)).collect::<HashMap<_, _>>();
+            let mut bytes = to_bytes::<Error>(&map).unwrap();
+            let archived = unsafe {
+                access_unchecked_mut::<Archived<HashMap<u32, u32>>>(&mut bytes)
+            };
+            for mut value in archived.values_seal() {
+                *value = 7.into();
+            }
+            let archived = unsafe {
+                access_unchecked::<Archived<HashMap<u32, u32>>>(&bytes)
+            };
+            assert!(archived.values().all(|value| *value == 7));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(feature = "bytecheck")]
+    fn redact_marked_fields() {
+        use rkyv::{redact, redact::redact_unchecked, Redact};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        #[archive_attr(derive(Redact))]
+        struct Contact {
+            name: String,
+            #[archive_attr(redact)]
+            email: String,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        #[archive_attr(derive(Redact))]
+        struct Account {
+            id: u32,
+            #[archive_attr(redact)]
+            balance: i64,
+            #[archive_attr(redact)]
+            contact: Contact,
+            #[archive_attr(redact)]
+            backups: Vec<Option<Contact>>,
+            note: String,
+        }
+
+        let contact = |name: &str| Contact {
+            name: name.to_string(),
+            email: format!("{}@example.com", name),
+        };
+        let value = Account {
+            id: 7,
+            balance: -1200,
+            contact: contact("alice"),
+            backups: vec![Some(contact("bob")), None],
+            note: "a note which is long enough to be out of line".to_string(),
+        };
+
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+        redact::<ArchivedAccount, Error>(&mut bytes).unwrap();
+
+        let archived = rkyv::access::<ArchivedAccount, Error>(&bytes).unwrap();
+        assert_eq!(archived.id, 7);
+        assert_eq!(archived.balance, 0);
+        assert_eq!(archived.contact.name, "alice");
+        assert_eq!(archived.contact.email.len(), "alice@example.com".len());
+        assert!(archived.contact.email.bytes().all(|b| b == 0));
+        let backup = archived.backups[0].as_ref().unwrap();
+        assert_eq!(backup.name, "bob");
+        assert!(backup.email.bytes().all(|b| b == 0));
+        assert!(archived.backups[1].is_none());
+        assert_eq!(
+            archived.note,
+            "a note which is long enough to be out of line"
+        );
+
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+        unsafe { redact_unchecked::<ArchivedAccount>(&mut bytes) };
+        let archived = unsafe { access_unchecked::<ArchivedAccount>(&bytes) };
+        assert_eq!(archived.balance, 0);
+        assert!(archived.contact.email.bytes().all(|b| b == 0));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(all(feature = "std", feature = "bytecheck"))]
+    fn memory_advice() {
+        use rkyv::{
+            util::{advise_random, advise_sequential, prefault, prefetch},
+            validation::util::access_prefaulted,
+        };
+
+        let value = (0..10_000u32).collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        advise_sequential(&bytes).unwrap();
+        advise_random(&bytes).unwrap();
+        prefetch(&bytes, 100..bytes.len()).unwrap();
+        prefetch(&bytes, 0..0).unwrap();
+        prefault(&bytes, 1..bytes.len() - 1);
+        prefault(&bytes, 0..0);
+
+        let archived =
+            access_prefaulted::<Archived<Vec<u32>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 10_000);
+        assert_eq!(archived[9_999], 9_999);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn aligned_vec_custom_allocator() {
+        use core::{alloc::Layout, cell::Cell};
+
+        use rkyv::util::{Global, RawAllocator};
+
+        #[derive(Default)]
+        struct Counting {
+            live: Cell<usize>,
+            total: Cell<usize>,
+        }
+
+        unsafe impl RawAllocator for Counting {
+            unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+                self.live.set(self.live.get() + 1);
+                self.total.set(self.total.get() + 1);
+                unsafe { Global.allocate(layout) }
+            }
+
+            unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+
+        let counting = Counting::default();
+        {
+            let value = (0..1000u32).collect::<Vec<_>>();
+            let bytes = serialize_into::<_, Error>(
+                &value,
+                AlignedVec::<16, _>::new_in(&counting),
+            )
+            .unwrap();
+            assert!(counting.total.get() > 1);
+            assert_eq!(counting.live.get(), 1);
+            assert_eq!(bytes.as_ptr() as usize % 16, 0);
+
+            let archived =
+                unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+            assert_eq!(archived.len(), 1000);
+            assert_eq!(archived[999], 999);
+
+            let cloned = bytes.clone();
+            assert_eq!(counting.live.get(), 2);
+            assert_eq!(cloned.as_slice(), bytes.as_slice());
+        }
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(not(feature = "pointer_width_64"))]
+    fn max_align_packs_small_archives() {
+        use rkyv::{
+            pack::{ArchivedPack, Packer},
+            util::to_bytes_with_max_align,
+        };
+
+        let values = ["a", "bc", "def"];
+        let archives = values
+            .iter()
+            .map(|value| {
+                to_bytes_with_max_align::<4, _, Error>(&value.to_string())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut small = Packer::<_, 4>::with_max_align(AlignedVec::<4>::new());
+        let mut default = Packer::new(AlignedVec::<16>::new());
+        for archive in archives.iter() {
+            small.push::<Error>(archive).unwrap();
+            default.push::<Error>(archive).unwrap();
+        }
+        let small = small.finish::<Error>().unwrap();
+        let default = default.finish::<Error>().unwrap();
+        assert!(small.len() < default.len());
+
+        let pack = unsafe { access_unchecked::<ArchivedPack>(&small) };
+        assert_eq!(pack.len(), 3);
+        for (i, value) in values.iter().enumerate() {
+            let archived =
+                unsafe { pack.access_unchecked::<Archived<String>>(i) };
+            assert_eq!(archived.unwrap().as_str(), *value);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_builder() {
+        use rkyv::ser::AllocSerializer;
+
+        #[derive(Archive, Serialize)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(builder)]
+        struct Shape {
+            id: u32,
+            name: String,
+            points: Vec<Point>,
+            label: Option<String>,
+        }
+
+        let mut serializer = AllocSerializer::default();
+        let points = (0..4).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>();
+        let name = "square".to_string();
+        let unused = "overwritten".to_string();
+        let label = Some("a label".to_string());
+
+        let builder = ArchivedShapeBuilder::new(Strategy::<_, Error>::wrap(
+            &mut serializer,
+        ))
+        .points(&points)
+        .unwrap()
+        .name(&unused)
+        .unwrap()
+        .name(&name)
+        .unwrap()
+        .label(&label)
+        .unwrap();
+        let pos = builder.id(&42u32).unwrap().finish().unwrap();
+
+        let bytes = serializer.into_writer();
+        assert_eq!(pos + core::mem::size_of::<ArchivedShape>(), bytes.len());
+        let archived = unsafe { access_unchecked::<ArchivedShape>(&bytes) };
+        assert_eq!(archived.id, 42);
+        assert_eq!(archived.name, "square");
+        assert_eq!(archived.points.len(), 4);
+        assert_eq!(archived.points[3].x, 3);
+        assert_eq!(archived.points[3].y, -3);
+        assert_eq!(archived.label.as_ref().unwrap(), "a label");
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn dynamic_builders() {
+        use rkyv::{
+            builder::{
+                ArchivedBTreeMapBuilder, ArchivedHashMapBuilder,
+                ArchivedStringBuilder, ArchivedVecBuilder,
+            },
+            collections::swiss_table::ArchivedHashMap,
+            ser::AllocSerializer,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(builder)]
+        struct Document {
+            title: String,
+            sections: Vec<String>,
+            counts: BTreeMap<String, u32>,
+            tags: Vec<Vec<u16>>,
+        }
+
+        let mut serializer = AllocSerializer::default();
+        let s = Strategy::<_, Error>::wrap(&mut serializer);
+
+        let mut title = ArchivedStringBuilder::new();
+        for part in ["a ", "title ", "long enough to be out of line"] {
+            title.push_str(part);
+        }
+        let title = title.finish(s).unwrap();
+
+        let mut sections = ArchivedVecBuilder::new();
+        for section in ["intro", "body", "conclusion"] {
+            let mut builder = ArchivedStringBuilder::new();
+            builder.push_str(section);
+            sections.push(builder.finish(s).unwrap(), s).unwrap();
+        }
+        let sections = sections.finish(s).unwrap();
+
+        let mut counts = ArchivedBTreeMapBuilder::new();
+        counts.insert("b".to_string(), 2u32);
+        counts.insert("a".to_string(), 0u32);
+        assert_eq!(counts.insert("a".to_string(), 1u32), Some(0));
+        let counts = counts.finish(s).unwrap();
+
+        let mut tags = ArchivedVecBuilder::new();
+        for len in 0..3u16 {
+            let mut inner = ArchivedVecBuilder::new();
+            for i in 0..len {
+                inner.push(i, s).unwrap();
+            }
+            tags.push(inner.finish(s).unwrap(), s).unwrap();
+        }
+        let tags = tags.finish(s).unwrap();
+
+        let pos = ArchivedDocumentBuilder::new(s)
+            .title(&title)
+            .unwrap()
+            .sections(&sections)
+            .unwrap()
+            .counts(&counts)
+            .unwrap()
+            .tags(&tags)
+            .unwrap()
+            .finish()
+            .unwrap();
+
+        let bytes = serializer.into_writer();
+        assert_eq!(pos + core::mem::size_of::<ArchivedDocument>(), bytes.len());
+        let archived = unsafe { access_unchecked::<ArchivedDocument>(&bytes) };
+        assert_eq!(archived.title, "a title long enough to be out of line");
+        assert_eq!(archived.sections.len(), 3);
+        assert_eq!(archived.sections[2], "conclusion");
+        assert_eq!(archived.counts.len(), 2);
+        assert_eq!(*archived.counts.get("a").unwrap(), 1);
+        assert_eq!(*archived.counts.get("b").unwrap(), 2);
+        assert_eq!(archived.tags.len(), 3);
+        assert!(archived.tags[0].is_empty());
+        assert_eq!(archived.tags[2].as_slice(), &[0, 1]);
+
+        let mut serializer = AllocSerializer::default();
+        let s = Strategy::<_, Error>::wrap(&mut serializer);
+        let mut map = ArchivedHashMapBuilder::new();
+        for (key, len) in [("empty", 0), ("one", 1), ("many", 16)] {
+            let mut values = ArchivedVecBuilder::new();
+            for i in 0..len {
+                values.push(i as u32, s).unwrap();
+            }
+            map.insert(key.to_string(), values.finish(s).unwrap());
+        }
+        map.finish(s).unwrap().serialize_and_resolve(s).unwrap();
+
+        let bytes = serializer.into_writer();
+        let archived = unsafe {
+            access_unchecked::<
+                ArchivedHashMap<Archived<String>, Archived<Vec<u32>>>,
+            >(&bytes)
+        };
+        assert_eq!(archived.len(), 3);
+        assert!(archived.get("empty").unwrap().is_empty());
+        assert_eq!(archived.get("one").unwrap().as_slice(), &[0]);
+        assert_eq!(archived.get("many").unwrap().len(), 16);
+        assert_eq!(archived.get("many").unwrap()[15], 15);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn untyped_value() {
+        use rkyv::value::{ArchivedValue, Value};
+
+        #[derive(Archive, Serialize)]
+        struct Event {
+            id: u32,
+            extra: Value,
+        }
+
+        let mut inner = BTreeMap::new();
+        inner.insert("a/b".to_string(), Value::from(-3));
+        inner.insert("m~n".to_string(), Value::from(1.5));
+        let extra = Value::from(vec![
+            Value::Null,
+            Value::from(true),
+            Value::Bytes(vec![1, 2, 3]),
+            Value::from(inner),
+            Value::from("a long enough string to be stored out of line"),
+        ]);
+        assert!(extra.get_index(0).unwrap().is_null());
+        assert_eq!(
+            extra.get_index(3).unwrap().get("a/b"),
+            Some(&Value::Int(-3))
+        );
+
+        let value = Event { id: 9, extra };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedEvent>(&bytes) };
+        assert_eq!(archived.id, 9);
+
+        let extra: &ArchivedValue = &archived.extra;
+        assert_eq!(extra.as_array().unwrap().len(), 5);
+        assert!(extra.pointer("/0").unwrap().is_null());
+        assert_eq!(extra.pointer("/1").unwrap().as_bool(), Some(true));
+        assert_eq!(
+            extra.pointer("/2").unwrap().as_bytes(),
+            Some(&[1, 2, 3][..])
+        );
+        assert_eq!(extra.pointer("/3/a~1b").unwrap().as_i64(), Some(-3));
+        assert_eq!(extra.pointer("/3/a~1b").unwrap().as_f64(), Some(-3.0));
+        assert_eq!(extra.pointer("/3/m~0n").unwrap().as_f64(), Some(1.5));
+        assert_eq!(extra.pointer("/3/m~0n").unwrap().as_i64(), None);
+        assert_eq!(
+            extra.get_index(4).unwrap().as_str(),
+            Some("a long enough string to be stored out of line"),
+        );
+        assert_eq!(extra.get_index(3).unwrap().as_map().unwrap().len(), 2);
+        assert!(extra.pointer("").unwrap().as_array().is_some());
+        assert!(extra.pointer("/5").is_none());
+        assert!(extra.pointer("/01").is_none());
+        assert!(extra.pointer("/3/missing").is_none());
+        assert!(extra.pointer("/0/0").is_none());
+        assert!(extra.pointer("3").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn envelope_round_trip() {
+        use rkyv::envelope::{
+            encode_error, encode_request, encode_response, Envelope,
+            EnvelopeHeader, EnvelopeKind,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Greet {
+            name: String,
+        }
+
+        const GREET: u32 = 3;
+
+        let request = Greet {
+            name: "a name which is stored out of line".to_string(),
+        };
+        let frame = encode_request::<_, Error>(GREET, 41, &request).unwrap();
+        let envelope = Envelope::decode::<Error>(&frame).unwrap();
+        assert_eq!(envelope.kind(), EnvelopeKind::Request);
+        assert_eq!(envelope.method(), GREET);
+        assert_eq!(envelope.correlation(), 41);
+        assert_eq!(envelope.frame_len(), frame.len());
+        assert_eq!(
+            envelope.payload().len(),
+            frame.len() - EnvelopeHeader::HEADER_LEN,
+        );
+        let archived = envelope.access::<ArchivedGreet, Error>().unwrap();
+        assert_eq!(archived.name, "a name which is stored out of line");
+
+        let mut greeting = "hello, ".to_string();
+        greeting.push_str(archived.name.as_str());
+        let response =
+            encode_response::<_, Error>(envelope.header(), &greeting).unwrap();
+        let failure =
+            encode_error::<_, Error>(envelope.header(), &"busy".to_string())
+                .unwrap();
+
+        // Frames can be concatenated in one stream.
+        let mut stream = AlignedVec::<16>::new();
+        stream.extend_from_slice(&response);
+        stream.extend_from_slice(&failure);
+
+        let first = Envelope::decode::<Error>(&stream).unwrap();
+        assert_eq!(first.kind(), EnvelopeKind::Response);
+        assert_eq!(first.correlation(), 41);
+        let archived = first.access::<Archived<String>, Error>().unwrap();
+        assert_eq!(archived, "hello, a name which is stored out of line");
+
+        let second =
+            Envelope::decode::<Error>(&stream[first.frame_len()..]).unwrap();
+        assert_eq!(second.kind(), EnvelopeKind::Error);
+        assert_eq!(second.method(), GREET);
+        let archived = second.access::<Archived<String>, Error>().unwrap();
+        assert_eq!(archived, "busy");
+
+        assert!(Envelope::decode::<Error>(&frame[..frame.len() - 1]).is_err());
+        assert!(Envelope::decode::<Error>(&frame[..8]).is_err());
+        let mut corrupt = frame.clone();
+        corrupt[1] = 9;
+        assert!(Envelope::decode::<Error>(&corrupt).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn access_leaked() {
+        use rkyv::validation::util::{access_leaked, access_leaked_vec};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Config {
+            name: String,
+            retries: u32,
+        }
+
+        let config = Config {
+            name: "production".to_string(),
+            retries: 3,
+        };
+
+        let bytes = to_bytes::<Error>(&config).unwrap();
+        let archived: &'static ArchivedConfig =
+            access_leaked_vec::<Config, Error>(bytes).unwrap();
+        assert_eq!(archived.name, "production");
+        assert_eq!(archived.retries, 3);
+
+        let bytes: &'static [u8] = to_bytes::<Error>(&config).unwrap().leak();
+        let archived = access_leaked::<Config, Error>(bytes).unwrap();
+        assert_eq!(archived.name, "production");
+
+        let mut invalid = to_bytes::<Error>(&config).unwrap();
+        invalid.clear();
+        assert!(access_leaked_vec::<Config, Error>(invalid).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_transparent() {
+        use core::mem::{align_of, size_of};
+
+        use rkyv::with::Inline;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transparent)]
+        struct Meters(f64);
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(transparent)]
+        struct Name {
+            value: String,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive(transparent)]
+        struct Borrowed<'a>(#[with(Inline)] &'a Option<u32>);
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Route {
+            name: Name,
+            length: Option<Meters>,
+        }
+
+        // The archived types are exactly those of the inner types.
+        let _: fn(&Archived<Meters>) -> &Archived<f64> = |m| m;
+        let _: fn(&Archived<Name>) -> &Archived<String> = |n| n;
+        assert_eq!(size_of::<Archived<Meters>>(), size_of::<Archived<f64>>());
+        assert_eq!(
+            align_of::<Archived<Option<Meters>>>(),
+            align_of::<Archived<Option<f64>>>(),
+        );
+
+        test_archive_with(&Meters(1.5), |v, a| v.0 == a.to_native());
+        test_archive_with(
+            &Name {
+                value: "a".to_string(),
+            },
+            |v, a| v.value == *a,
+        );
+
+        let route = Route {
+            name: Name {
+                value: "a name which is stored out of line".to_string(),
+            },
+            length: Some(Meters(42.0)),
+        };
+        test_archive_with(&route, |v, a| {
+            a.name == v.name.value
+                && a.length.as_ref().map(|l| l.to_native()) == Some(42.0)
+        });
+
+        let value = Some(7);
+        let bytes = to_bytes::<Error>(&Borrowed(&value)).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Option<u32>>>(&bytes) };
+        assert_eq!(archived.as_ref().map(|v| v.to_native()), Some(7));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn as_archived_ref() {
+        use rkyv::deref::AsArchivedRef;
+
+        #[derive(Archive, Serialize)]
+        struct Test {
+            inline: u32,
+            boxed: Box<u32>,
+            shared: Rc<u32>,
+        }
+
+        fn get(value: &impl AsArchivedRef<Archived<u32>>) -> u32 {
+            value.as_archived_ref().to_native()
+        }
+
+        let value = Test {
+            inline: 1,
+            boxed: Box::new(2),
+            shared: Rc::new(3),
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedTest>(&bytes) };
+
+        assert_eq!(get(&archived.inline), 1);
+        assert_eq!(get(&archived.boxed), 2);
+        assert_eq!(get(&archived.shared), 3);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn sorted_index() {
+        use rkyv::index::SortedIndex;
+
+        #[derive(Archive, Serialize)]
+        struct Record {
+            name: String,
+            age: u32,
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Table {
+            records: Vec<Record>,
+            by_name: SortedIndex<String>,
+            by_age: SortedIndex<u32>,
+        }
+
+        let records =
+            [("carol", 30), ("alice", 25), ("bob", 30), ("alice", 40)]
+                .into_iter()
+                .map(|(name, age)| Record {
+                    name: name.to_string(),
+                    age,
+                })
+                .collect::<Vec<_>>();
+        let by_name = SortedIndex::build(&records, |r| r.name.clone());
+        let by_age = SortedIndex::build(&records, |r| r.age);
+        assert_eq!(by_name.len(), 4);
+        assert_eq!(by_age.positions(&30), [0, 2]);
+
+        let value = Table {
+            records,
+            by_name,
+            by_age,
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedTable>(&bytes) };
+
+        assert_eq!(archived.by_name.len(), 4);
+        assert_eq!(
+            archived.by_name.positions("alice").collect::<Vec<_>>(),
+            [1, 3],
+        );
+        assert_eq!(archived.by_name.positions("dave").count(), 0);
+        assert_eq!(
+            archived
+                .by_age
+                .get_by(&archived.records, |age| age.to_native().cmp(&30))
+                .map(|r| r.name.as_str())
+                .collect::<Vec<_>>(),
+            ["carol", "bob"],
+        );
+        assert_eq!(
+            archived
+                .by_name
+                .get(&archived.records, "bob")
+                .map(|r| r.age.to_native())
+                .collect::<Vec<_>>(),
+            [30],
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn probabilistic_filters() {
+        use rkyv::filter::{BloomFilter, XorFilter};
+
+        #[derive(Archive, Serialize)]
+        struct Filters {
+            bloom: BloomFilter,
+            xor: XorFilter,
+        }
+
+        let keys = (0..1000u32).collect::<Vec<_>>();
+        let mut bloom = BloomFilter::with_false_positive_rate(1000, 0.01);
+        for key in keys.iter().take(500) {
+            bloom.insert(key);
+        }
+        assert!(keys.iter().take(500).all(|key| bloom.contains(key)));
+        bloom.insert(&999u32);
+        assert!(bloom.contains(&999u32));
+
+        let value = Filters {
+            bloom: BloomFilter::from_keys(&keys, 0.01),
+            xor: XorFilter::from_keys(keys.iter().chain(&keys[..10])),
+        };
+        assert!(keys.iter().all(|key| value.xor.contains(key)));
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedFilters>(&bytes) };
+
+        assert!(keys.iter().all(|key| archived.bloom.contains(key)));
+        assert!(keys.iter().all(|key| archived.xor.contains(key)));
+        let missing = 1000..11000u32;
+        let bloom_hits = missing
+            .clone()
+            .filter(|k| archived.bloom.contains(k))
+            .count();
+        let xor_hits = missing.filter(|k| archived.xor.contains(k)).count();
+        assert!(bloom_hits < 300, "{} bloom false positives", bloom_hits);
+        assert!(xor_hits < 100, "{} xor false positives", xor_hits);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn interval_map() {
+        use rkyv::collections::interval_map::IntervalMap;
+
+        let value = IntervalMap::from_ranges::<_, Error>([
+            (200u32..300, 2u8),
+            (0..100, 0),
+            (100..150, 1),
+            (400..500, 3),
+        ])
+        .unwrap();
+        assert_eq!(value.len(), 4);
+        assert_eq!(value.get(&120), Some(&1));
+        assert_eq!(value.get(&399), None);
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe {
+            access_unchecked::<Archived<IntervalMap<u32, u8>>>(&bytes)
+        };
+
+        assert_eq!(archived.len(), 4);
+        assert_eq!(archived.get(&0u32), Some(&0));
+        assert_eq!(archived.get(&99u32), Some(&0));
+        assert_eq!(archived.get(&100u32), Some(&1));
+        assert_eq!(archived.get(&150u32), None);
+        assert_eq!(archived.get(&299u32), Some(&2));
+        assert_eq!(archived.get(&1000u32), None);
+        assert_eq!(
+            archived
+                .overlapping(&120u32, &401u32)
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>(),
+            [1, 2, 3],
+        );
+        assert_eq!(archived.overlapping(&150u32, &200u32).count(), 0);
+        assert_eq!(
+            archived
+                .iter()
+                .map(|(range, _)| range.start.to_native())
+                .collect::<Vec<_>>(),
+            [0, 100, 200, 400],
+        );
+
+        assert!(IntervalMap::from_ranges::<_, Error>([
+            (0u32..10, ()),
+            (5..15, ()),
+        ])
+        .is_err());
+        assert!(IntervalMap::from_ranges::<_, Error>([(3u32..3, ())]).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn ring_buffer() {
+        use rkyv::{
+            primitive::ArchivedU32,
+            ring::{ArchivedRingBuffer, RingBuffer},
+        };
+
+        let mut value = RingBuffer::<u32>::with_capacity(3);
+        value.push(1).unwrap();
+        value.push(2).unwrap();
+        assert_eq!(value.pop(), Some(1));
+        value.push(3).unwrap();
+        value.push(4).unwrap();
+        assert!(value.is_full());
+        assert_eq!(value.push(5), Err(5));
+
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+        let ring = unsafe {
+            access_unchecked_mut::<ArchivedRingBuffer<ArchivedU32>>(&mut bytes)
+        }
+        .get_ref();
+        assert_eq!(ring.capacity(), 3);
+        assert_eq!(ring.len(), 3);
+        assert!(ring.is_full());
+
+        let (mut producer, mut consumer) =
+            unsafe { (ring.producer(), ring.consumer()) };
+        let pop = |consumer: &mut rkyv::ring::Consumer<'_, ArchivedU32>| {
+            consumer.pop().map(|v| v.to_native())
+        };
+        assert!(producer.push(ArchivedU32::from_native(5)).is_err());
+        assert_eq!(pop(&mut consumer), Some(2));
+        producer.push(ArchivedU32::from_native(5)).unwrap();
+
+        // Wrap the head and tail around several times.
+        let mut expected = vec![3, 4, 5];
+        for i in 6..20 {
+            assert_eq!(pop(&mut consumer), Some(expected.remove(0)));
+            producer.push(ArchivedU32::from_native(i)).unwrap();
+            expected.push(i);
+        }
+        for v in expected {
+            assert_eq!(pop(&mut consumer), Some(v));
+        }
+        assert!(ring.is_empty());
+        assert_eq!(pop(&mut consumer), None);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn nd_array() {
+        use rkyv::tensor::NdArray;
+
+        let value = NdArray::from_shape_vec::<Error>(
+            vec![2, 3, 2],
+            (0u32..12).collect(),
+        )
+        .unwrap();
+        assert_eq!(value.strides(), [6, 2, 1]);
+        assert_eq!(value.get(&[1, 2, 0]), Some(&10));
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<NdArray<u32>>>(&bytes) };
+        assert_eq!(archived.ndim(), 3);
+        assert_eq!(archived.len(), 12);
+        assert_eq!(archived.get(&[1, 2, 0]).map(|v| v.to_native()), Some(10));
+        assert!(archived.get(&[1, 3, 0]).is_none());
+        assert!(archived.get(&[1, 2]).is_none());
+        assert!(archived
+            .iter()
+            .map(|v| v.to_native())
+            .eq(value.iter().copied()));
+
+        let deserialized =
+            deserialize::<NdArray<u32>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+
+        // A transposed view of a 2x3 matrix and a row broadcast along an axis.
+        let transposed = NdArray::from_shape_strides_vec::<Error>(
+            vec![3, 2],
+            vec![1, 3],
+            vec![1u32, 2, 3, 4, 5, 6],
+        )
+        .unwrap();
+        assert_eq!(
+            transposed.iter().copied().collect::<Vec<_>>(),
+            [1, 4, 2, 5, 3, 6],
+        );
+        let broadcast = NdArray::from_shape_strides_vec::<Error>(
+            vec![4, 2],
+            vec![0, 1],
+            vec![7u32, 8],
+        )
+        .unwrap();
+        assert_eq!(broadcast.len(), 8);
+        assert_eq!(broadcast.get(&[3, 1]), Some(&8));
+
+        assert!(
+            NdArray::from_shape_vec::<Error>(vec![2, 2], vec![0u8; 3]).is_err()
+        );
+        assert!(NdArray::from_shape_strides_vec::<Error>(
+            vec![2, 2],
+            vec![2, 2],
+            vec![0u8; 4],
+        )
+        .is_err());
+        assert!(NdArray::from_shape_strides_vec::<Error>(
+            vec![2],
+            vec![1, 1],
+            vec![0u8; 4],
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn sorted_vec() {
+        use rkyv::with::AsSortedSlice;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Postings {
+            #[with(AsSortedSlice)]
+            left: Vec<u32>,
+            #[with(AsSortedSlice)]
+            right: BTreeSet<u32>,
+        }
+
+        let value = Postings {
+            left: vec![8, 2, 6, 4, 10],
+            right: [1, 2, 3, 4, 5].into_iter().collect(),
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedPostings>(&bytes) };
+
+        let native = |v: &Archived<u32>| v.to_native();
+        assert_eq!(
+            archived.left.iter().map(native).collect::<Vec<_>>(),
+            [2, 4, 6, 8, 10],
+        );
+        assert_eq!(archived.left.binary_search(&6u32), Ok(2));
+        assert_eq!(archived.left.binary_search(&7u32), Err(3));
+        assert!(archived.left.contains(&10u32));
+        assert!(!archived.left.contains(&3u32));
+        assert_eq!(archived.right.first().map(native), Some(1));
+        assert_eq!(archived.right.last().map(native), Some(5));
+
+        assert_eq!(
+            archived
+                .left
+                .union(&archived.right)
+                .map(native)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 8, 10],
+        );
+        assert_eq!(
+            archived
+                .left
+                .intersection(&archived.right)
+                .map(native)
+                .collect::<Vec<_>>(),
+            [2, 4],
+        );
+
+        let deserialized =
+            deserialize::<Postings, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized.left, [2, 4, 6, 8, 10]);
+        assert_eq!(deserialized.right, value.right);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn reuse_unchanged_subtrees() {
+        use rkyv::ser::{
+            reuse::{Reuse, Tracked},
+            AllocSerializer,
+        };
+
+        #[derive(Archive, Serialize, Deserialize)]
+        struct Document {
+            title: String,
+            sections: Vec<Tracked<Vec<String>>>,
+        }
+
+        fn save(value: &Document, previous: &[u8]) -> (AlignedVec, usize) {
+            let mut serializer =
+                Reuse::new(AllocSerializer::default(), previous);
+            rkyv::util::serialize::<_, Error>(value, &mut serializer).unwrap();
+            let reused = serializer.reused();
+            (serializer.into_inner().into_writer(), reused)
+        }
+
+        fn check(archived: &ArchivedDocument, value: &Document) {
+            assert_eq!(archived.title, value.title);
+            assert_eq!(archived.sections.len(), value.sections.len());
+            for (a, v) in archived.sections.iter().zip(value.sections.iter()) {
+                assert_eq!(a.get(), v.get());
+            }
+        }
+
+        let mut value = Document {
+            title: "notes".to_string(),
+            sections: (0..4)
+                .map(|i| {
+                    Tracked::new(
+                        (0..10).map(|j| format!("line {} {}", i, j)).collect(),
+                    )
+                })
+                .collect(),
+        };
+        assert!(value.sections[0].is_dirty());
+
+        let (first, reused) = save(&value, &[]);
+        assert_eq!(reused, 0);
+        assert!(!value.sections[0].is_dirty());
+
+        value.title = "more notes".to_string();
+        value.sections[2].get_mut().push("a new line".to_string());
+        assert!(value.sections[2].is_dirty());
+        let (second, reused) = save(&value, &first);
+        assert_eq!(reused, 3);
+        let archived = unsafe { access_unchecked::<ArchivedDocument>(&second) };
+        check(archived, &value);
+
+        // Values are copied from the archive they were last written to.
+        value.sections[0].get_mut().clear();
+        let (third, reused) = save(&value, &second);
+        assert_eq!(reused, 3);
+        let archived = unsafe { access_unchecked::<ArchivedDocument>(&third) };
+        check(archived, &value);
+
+        // Values which are not in the previous archive are serialized again.
+        let (fourth, reused) = save(&value, &[]);
+        assert_eq!(reused, 0);
+        let archived = unsafe { access_unchecked::<ArchivedDocument>(&fourth) };
+        check(archived, &value);
+
+        let deserialized =
+            deserialize::<Document, _, Error>(archived, &mut ()).unwrap();
+        assert!(deserialized.sections[1].is_dirty());
+        assert_eq!(deserialized.sections[1].get(), value.sections[1].get());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn canonical_shared_order() {
+        use rkyv::ser::canonical::to_bytes_canonical;
+
+        #[derive(Archive, Serialize)]
+        struct Node {
+            name: String,
+            tag: Rc<String>,
+        }
+
+        #[derive(Archive, Serialize)]
+        struct Test {
+            items: Vec<Rc<Vec<String>>>,
+            nodes: Vec<Rc<Node>>,
+        }
+
+        fn offset_of<T>(bytes: &[u8], value: &T) -> usize {
+            value as *const T as usize - bytes.as_ptr() as usize
+        }
+
+        let a = Rc::new(vec!["apples and pears".to_string()]);
+        let b = Rc::new(vec!["bananas".to_string(), "cherries".to_string()]);
+        let tag = Rc::new("a shared tag string".to_string());
+        let node = Rc::new(Node {
+            name: "node with a long name".to_string(),
+            tag: tag.clone(),
+        });
+
+        let forward = Test {
+            items: vec![a.clone(), b.clone(), a.clone()],
+            nodes: vec![node.clone()],
+        };
+        let backward = Test {
+            items: vec![b.clone(), a.clone(), b.clone()],
+            nodes: vec![node.clone()],
+        };
+
+        let forward_bytes = to_bytes_canonical::<Error>(&forward).unwrap();
+        let backward_bytes = to_bytes_canonical::<Error>(&backward).unwrap();
+        let forward_archived =
+            unsafe { access_unchecked::<ArchivedTest>(&forward_bytes) };
+        let backward_archived =
+            unsafe { access_unchecked::<ArchivedTest>(&backward_bytes) };
+
+        // Shared values are written to the same positions regardless of the
+        // order they are encountered in.
+        let fwd = &forward_archived.items;
+        let bwd = &backward_archived.items;
+        assert_eq!(
+            offset_of(&forward_bytes, fwd[0].get()),
+            offset_of(&backward_bytes, bwd[1].get()),
+        );
+        assert_eq!(
+            offset_of(&forward_bytes, fwd[1].get()),
+            offset_of(&backward_bytes, bwd[0].get()),
+        );
+        assert_eq!(
+            offset_of(&forward_bytes, forward_archived.nodes[0].get()),
+            offset_of(&backward_bytes, backward_archived.nodes[0].get()),
+        );
+
+        // Shared pointers are still unified.
+        assert!(core::ptr::eq(fwd[0].get(), fwd[2].get()));
+        assert!(core::ptr::eq(bwd[0].get(), bwd[2].get()));
+
+        assert_eq!(fwd[0].as_slice(), a.as_slice());
+        assert_eq!(fwd[1].as_slice(), b.as_slice());
+        let archived_node = &forward_archived.nodes[0];
+        assert_eq!(archived_node.name, node.name);
+        assert_eq!(archived_node.tag.as_str(), tag.as_str());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn composite_components() {
+        use rkyv::{
+            rancor::Fallible,
+            ser::{
+                component::ComponentPack, AllocSerializer, GetComponent, Writer,
+            },
+            util::serialize_into,
+        };
+
+        #[derive(Default)]
+        struct Counter(usize);
+
+        #[derive(Default)]
+        struct Trace(Vec<u32>);
+
+        #[derive(Default)]
+        struct Pack {
+            counter: Counter,
+            trace: Trace,
+        }
+
+        impl ComponentPack<Counter> for Pack {
+            fn get(&self) -> &Counter {
+                &self.counter
+            }
+
+            fn get_mut(&mut self) -> &mut Counter {
+                &mut self.counter
+            }
+        }
+
+        impl ComponentPack<Trace> for Pack {
+            fn get(&self) -> &Trace {
+                &self.trace
+            }
+
+            fn get_mut(&mut self) -> &mut Trace {
+                &mut self.trace
+            }
+        }
+
+        struct Traced(u32);
+
+        impl Archive for Traced {
+            type Archived = Archived<u32>;
+            type Resolver = ();
+
+            fn resolve(&self, _: (), out: Place<Self::Archived>) {
+                self.0.resolve((), out);
+            }
+        }
+
+        impl<S> Serialize<S> for Traced
+        where
+            S: Fallible
+                + Writer
+                + GetComponent<Counter>
+                + GetComponent<Trace>
+                + ?Sized,
+        {
+            fn serialize(&self, serializer: &mut S) -> Result<(), S::Error> {
+                GetComponent::<Counter>::component_mut(serializer).0 += 1;
+                GetComponent::<Trace>::component_mut(serializer)
+                    .0
+                    .push(self.0);
+                Ok(())
+            }
+        }
+
+        let value = vec![Traced(3), Traced(1), Traced(4)];
+        let serializer =
+            AllocSerializer::default().with_extension(Pack::default());
+        let serializer =
+            serialize_into::<_, Error>(&value, serializer).unwrap();
+        assert_eq!(serializer.extension.counter.0, 3);
+        assert_eq!(serializer.extension.trace.0, [3, 1, 4]);
+
+        let bytes = serializer.into_writer();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Traced>>>(&bytes) };
+        assert_eq!(
+            archived.iter().map(|v| v.to_native()).collect::<Vec<_>>(),
+            [3, 1, 4],
+        );
+    }
+
+    mod module_types {
+        use rkyv::{archived_module, Archive, Deserialize, Serialize};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(module = "archived", compare(PartialEq))]
+        #[archive_attr(derive(Debug))]
+        pub struct Point {
+            pub x: i32,
+            y: i32,
+        }
+
+        impl Point {
+            pub fn new(x: i32, y: i32) -> Self {
+                Self { x, y }
+            }
+        }
+
+        impl archived::ArchivedPoint {
+            pub fn y(&self) -> i32 {
+                self.y.to_native()
+            }
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(module = "archived")]
+        pub enum Shape {
+            Dot(Point),
+            Line { from: Point, to: Point },
+        }
+
+        archived_module!(pub mod archived { Point, Shape });
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_module() {
+        use module_types::{
+            archived::{ArchivedPoint, ArchivedShape},
+            Point, Shape,
+        };
+
+        let value = vec![
+            Shape::Dot(Point::new(1, 2)),
+            Shape::Line {
+                from: Point::new(3, 4),
+                to: Point::new(5, 6),
+            },
+        ];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Shape>>>(&bytes) };
+
+        match &archived[1] {
+            ArchivedShape::Line { from, to } => {
+                assert_eq!(from.x.to_native(), 3);
+                assert_eq!(to.y(), 6);
+            }
+            ArchivedShape::Dot(_) => panic!("expected a line"),
+        }
+        let point: &ArchivedPoint = match &archived[0] {
+            ArchivedShape::Dot(point) => point,
+            _ => panic!("expected a dot"),
+        };
+        assert_eq!(Point::new(1, 2), *point);
+
+        let deserialized =
+            deserialize::<Vec<Shape>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[rkyv::archive_naming(archived = "{}Archived", resolver = "{}Res")]
+    mod naming_types {
+        use rkyv::{Archive, Deserialize, Serialize};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        pub struct Point {
+            pub x: i32,
+            pub y: i32,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(archived = "Polyline")]
+        pub enum Path {
+            Empty,
+            Points(Vec<Point>),
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(archived = "Archived{}V2")]
+        pub struct Tagged(pub u32);
+
+        pub mod nested {
+            use rkyv::{Archive, Serialize};
+
+            #[derive(Archive, Serialize)]
+            pub struct Label {
+                pub text: String,
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_naming() {
+        use naming_types::{
+            nested::{Label, LabelArchived, LabelRes},
+            Path, Point, PointArchived, PointRes, Polyline, Tagged,
+            TaggedArchivedV2,
+        };
+
+        let value = Path::Points(vec![Point { x: 1, y: 2 }]);
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<Polyline>(&bytes) };
+        match archived {
+            Polyline::Points(points) => {
+                let point: &PointArchived = &points[0];
+                assert_eq!(point.y.to_native(), 2);
+            }
+            Polyline::Empty => panic!("expected points"),
+        }
+        let deserialized =
+            deserialize::<Path, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+
+        let bytes = to_bytes::<Error>(&Tagged(7)).unwrap();
+        let archived = unsafe { access_unchecked::<TaggedArchivedV2>(&bytes) };
+        assert_eq!(archived.0.to_native(), 7);
+
+        let label = Label {
+            text: "origin".to_string(),
+        };
+        let bytes = to_bytes::<Error>(&label).unwrap();
+        let archived = unsafe { access_unchecked::<LabelArchived>(&bytes) };
+        assert_eq!(archived.text, "origin");
+
+        fn check_resolver<T: Archive<Resolver = R>, R>() {}
+        check_resolver::<Point, PointRes>();
+        check_resolver::<Label, LabelRes>();
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_std_mirrors() {
+        use core::{cmp::Reverse, num::Saturating, ops::ControlFlow};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compare(PartialEq), check_bytes)]
+        #[archive_attr(derive(Debug))]
+        enum Event {
+            Tick(u32),
+            Never(Infallible),
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compare(PartialEq), check_bytes)]
+        #[archive_attr(derive(Debug))]
+        struct Test {
+            health: Saturating<u32>,
+            priority: Reverse<i32>,
+            flow: ControlFlow<String, u8>,
+            parsed: Result<u16, Infallible>,
+            event: Event,
+        }
+
+        let value = Test {
+            health: Saturating(100),
+            priority: Reverse(3),
+            flow: ControlFlow::Break("done".to_string()),
+            parsed: Ok(42),
+            event: Event::Tick(7),
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = rkyv::access::<ArchivedTest, Error>(&bytes).unwrap();
+        assert_eq!(archived, &value);
+        assert_eq!(archived.health.0.to_native(), 100);
+        assert!(archived.flow.is_break());
+        assert_eq!(archived.flow.break_value().unwrap(), "done");
+
+        let deserialized =
+            deserialize::<Test, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+
+        let mut priorities = [Reverse(1), Reverse(5), Reverse(3)]
+            .iter()
+            .map(|p| to_bytes::<Error>(p).unwrap())
+            .collect::<Vec<_>>();
+        priorities.sort_by(|a, b| {
+            let a = unsafe { access_unchecked::<Archived<Reverse<i32>>>(a) };
+            let b = unsafe { access_unchecked::<Archived<Reverse<i32>>>(b) };
+            a.cmp(b)
+        });
+        let first = unsafe {
+            access_unchecked::<Archived<Reverse<i32>>>(&priorities[0])
+        };
+        assert_eq!(first.0.to_native(), 5);
+
+        // An archived `Err` can't be accessed as an uninhabited error.
+        let bytes = to_bytes::<Error>(&Err::<(), ()>(())).unwrap();
+        rkyv::access::<Archived<Result<(), Infallible>>, Error>(&bytes)
+            .expect_err("an uninhabited error must fail validation");
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn owned_archive_backings() {
+        use rkyv::backing::{Backing, OwnedArchive};
+
+        let value = vec!["alpha".to_string(), "a longer string".to_string()];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        assert!(bytes.is_aligned_to(16));
+
+        let archive = OwnedArchive::<Archived<Vec<String>>, _>::new::<Error>(
+            bytes.as_slice(),
+        )
+        .unwrap();
+        assert_eq!(archive[1], "a longer string");
+
+        let archive = OwnedArchive::<Archived<Vec<String>>, _>::new::<Error>(
+            Rc::new(bytes.clone()),
+        )
+        .unwrap();
+        assert_eq!(archive.len(), 2);
+        let backing = archive.into_backing();
+        assert_eq!(backing.as_bytes(), bytes.as_slice());
+
+        let archive =
+            OwnedArchive::<Archived<Vec<String>>, _>::new::<Error>(bytes)
+                .unwrap();
+        assert_eq!(archive.get()[0], "alpha");
+
+        let archive = OwnedArchive::from_value::<_, Error>(&value).unwrap();
+        assert_eq!(archive[1], "a longer string");
+        assert!(archive.backing().is_aligned_to(16));
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn derive_compact() {
+        use rkyv::with::Boxed;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact, check_bytes)]
+        struct Record<T> {
+            id: u32,
+            name: String,
+            alias: String,
+            #[with(Boxed)]
+            count: i32,
+            value: T,
+            tags: Vec<T>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact, check_bytes)]
+        enum Shape {
+            Unit,
+            Point(i32, i32),
+            Named {
+                label: String,
+                #[with(Boxed)]
+                size: u64,
+            },
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(compact, check_bytes)]
+        struct Pair(Shape, Record<Shape>);
+
+        let value = Pair(
+            Shape::Point(1, -2),
+            Record {
+                id: 7,
+                name: "seven".to_string(),
+                alias: "sieben".to_string(),
+                count: -3,
+                value: Shape::Named {
+                    label: "box".to_string(),
+                    size: 42,
+                },
+                tags: vec![Shape::Unit, Shape::Point(3, 4)],
+            },
+        );
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = rkyv::access::<ArchivedPair, Error>(&bytes).unwrap();
+        assert_eq!(archived.1.id.to_native(), 7);
+        assert_eq!(archived.1.count.get().to_native(), -3);
+
+        let deserialized =
+            deserialize::<Pair, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn derive_pod() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(pod, check_bytes)]
+        #[repr(C)]
+        struct Point {
+            x: f32,
+            y: f32,
+            id: u32,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(pod, check_bytes)]
+        #[repr(C)]
+        struct Padded<T> {
+            tag: u8,
+            value: T,
+        }
+
+        let copyable = <u32 as Archive>::COPY_OPTIMIZATION.is_enabled();
+        assert_eq!(
+            <Point as Archive>::COPY_OPTIMIZATION.is_enabled(),
+            copyable
+        );
+        assert!(!<Padded<u32> as Archive>::COPY_OPTIMIZATION.is_enabled());
+
+        let points = (0..100)
+            .map(|i| Point {
+                x: i as f32,
+                y: -(i as f32),
+                id: i,
+            })
+            .collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&points).unwrap();
+        let archived =
+            rkyv::access::<Archived<Vec<Point>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 100);
+        assert_eq!(archived[42].id.to_native(), 42);
+        assert_eq!(archived[42].y.to_native(), -42.0);
+        let deserialized =
+            deserialize::<Vec<Point>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, points);
+
+        let padded = Padded {
+            tag: 1,
+            value: 7u32,
+        };
+        let bytes = to_bytes::<Error>(&padded).unwrap();
+        let archived =
+            rkyv::access::<ArchivedPadded<u32>, Error>(&bytes).unwrap();
+        assert_eq!(archived.tag, 1);
+        assert_eq!(archived.value.to_native(), 7);
+        let deserialized =
+            deserialize::<Padded<u32>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, padded);
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn derive_bulk_copy() {
+        use rkyv::BulkCopy;
+
+        #[derive(
+            Archive, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+        )]
+        #[archive(bulk_copy, check_bytes)]
+        #[repr(C)]
+        struct Rgb {
+            r: u8,
+            g: u8,
+            b: u8,
+        }
+
+        // SAFETY: `Rgb` is `repr(C)` with only `u8` fields, so it has no
+        // padding and is archived with the same bytes.
+        unsafe impl BulkCopy for Rgb {}
+
+        assert!(<Rgb as Archive>::COPY_OPTIMIZATION.is_enabled());
+        assert!(<[Rgb; 4] as Archive>::COPY_OPTIMIZATION.is_enabled());
+
+        let pixels = (0..64u8)
+            .map(|i| Rgb {
+                r: i,
+                g: i.wrapping_mul(3),
+                b: 255 - i,
+            })
+            .collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&pixels).unwrap();
+        let archived =
+            rkyv::access::<Archived<Vec<Rgb>>, Error>(&bytes).unwrap();
+        assert_eq!(archived[10].g, 30);
+        let deserialized =
+            deserialize::<Vec<Rgb>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, pixels);
+
+        let quad = [pixels[0], pixels[1], pixels[2], pixels[3]];
+        let bytes = to_bytes::<Error>(&quad).unwrap();
+        let archived =
+            rkyv::access::<Archived<[Rgb; 4]>, Error>(&bytes).unwrap();
+        assert_eq!(archived[3].b, 252);
+        let deserialized =
+            deserialize::<[Rgb; 4], _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, quad);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn substituted_archived_field() {
+        use rkyv::{
+            munge::munge,
+            string::{ArchivedString, StringResolver},
+            with::{ArchiveFrom, SerializeFrom},
+            SerializeUnsized,
+        };
+
+        // Archives a list of words as a single comma-separated string.
+        #[derive(Portable)]
+        #[repr(transparent)]
+        struct ArchivedCsv(ArchivedString);
+
+        impl ArchiveFrom<Vec<String>> for ArchivedCsv {
+            type Resolver = StringResolver;
+
+            fn resolve_from(
+                field: &Vec<String>,
+                resolver: StringResolver,
+                out: Place<Self>,
+            ) {
+                munge!(let ArchivedCsv(string) = out);
+                ArchivedString::resolve_from_str(
+                    &field.join(","),
+                    resolver,
+                    string,
+                );
+            }
+        }
+
+        impl<S> SerializeFrom<Vec<String>, S> for ArchivedCsv
+        where
+            S: Fallible + ?Sized,
+            str: SerializeUnsized<S>,
+        {
+            fn serialize_from(
+                field: &Vec<String>,
+                serializer: &mut S,
+            ) -> Result<StringResolver, S::Error> {
+                ArchivedString::serialize_from_str(&field.join(","), serializer)
+            }
+        }
+
+        impl<D: Fallible + ?Sized> Deserialize<Vec<String>, D> for ArchivedCsv {
+            fn deserialize(&self, _: &mut D) -> Result<Vec<String>, D::Error> {
+                Ok(self.0.split(',').map(ToString::to_string).collect())
+            }
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Post {
+            id: u32,
+            #[archive(archived = "ArchivedCsv", resolver = "StringResolver")]
+            tags: Vec<String>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        enum Entry {
+            Post(Post),
+            Draft {
+                #[archive(archived = "ArchivedCsv")]
+                tags: Vec<String>,
+            },
+        }
+
+        let value = vec![
+            Entry::Post(Post {
+                id: 42,
+                tags: vec!["rust".to_string(), "serialization".to_string()],
+            }),
+            Entry::Draft {
+                tags: vec!["zero".to_string(), "copy".to_string()],
+            },
+        ];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Entry>>>(&bytes) };
+
+        match &archived[0] {
+            ArchivedEntry::Post(post) => {
+                assert_eq!(post.id, 42);
+                assert_eq!(post.tags.0, "rust,serialization");
+            }
+            ArchivedEntry::Draft { .. } => panic!("expected a post"),
+        }
+        match &archived[1] {
+            ArchivedEntry::Draft { tags } => assert_eq!(tags.0, "zero,copy"),
+            ArchivedEntry::Post(_) => panic!("expected a draft"),
+        }
+
+        let deserialized =
+            deserialize::<Vec<Entry>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg(all(feature = "test-util", feature = "bytecheck"))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn test_util_round_trip() {
+        use rkyv::test_util::{round_trip, PoolingKind, SerializerKind};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Shared {
+            first: Rc<String>,
+            second: Rc<String>,
+            values: Vec<u32>,
+        }
+
+        let name = Rc::new("shared".to_string());
+        let value = Shared {
+            first: name.clone(),
+            second: name,
+            values: vec![1, 2, 3],
+        };
+
+        let report = round_trip(&value);
+        assert_eq!(report.runs().len(), 8);
+        assert!(report.runs().iter().any(|run| {
+            run.serializer == SerializerKind::Core
+                && run.validated
+                && run.pooling == PoolingKind::Duplicate
+        }));
+        assert_eq!(report.is_little_endian(), cfg!(feature = "little_endian"));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(feature = "bytecheck")]
+    fn encoded_strings() {
+        use rkyv::{
+            access,
+            string::encoded::{Latin1, Utf8, Wtf8},
+            with::Encoded,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Names {
+            #[with(Encoded<Utf8>)]
+            utf8: Vec<u8>,
+            #[with(Encoded<Wtf8>)]
+            wtf8: Vec<u8>,
+            #[with(Encoded<Latin1>)]
+            latin1: Vec<u8>,
+        }
+
+        // "a", an unpaired lead surrogate (U+D800), then U+1F600.
+        let wide: [u16; 4] = [0x61, 0xd800, 0xd83d, 0xde00];
+        let value = Names {
+            utf8: "héllo".as_bytes().to_vec(),
+            wtf8: rkyv::string::encoded::wtf8_from_wide(wide),
+            latin1: b"Caf\xe9".to_vec(),
+        };
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = access::<ArchivedNames, Error>(&bytes).unwrap();
+        assert_eq!(archived.utf8.as_str(), "héllo");
+        assert_eq!(archived.wtf8.encode_wide().collect::<Vec<_>>(), wide);
+        assert_eq!(archived.wtf8.to_str(), None);
+        assert_eq!(archived.wtf8.to_string_lossy(), "a\u{fffd}\u{1f600}");
+        assert_eq!(archived.latin1.chars().collect::<String>(), "Café");
+        let deserialized =
+            deserialize::<Names, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+
+        // Lone surrogates are only valid in WTF-8.
+        let invalid = Names {
+            utf8: value.wtf8.clone(),
+            ..value
+        };
+        assert!(to_bytes::<Error>(&invalid).is_err());
+
+        // A surrogate pair must be encoded as a single code point in WTF-8.
+        let paired = Names {
+            wtf8: vec![0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80],
+            ..deserialized
+        };
+        assert!(to_bytes::<Error>(&paired).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    #[cfg(feature = "bytecheck")]
+    fn evolving_struct() {
+        use rkyv::access;
+
+        mod v1 {
+            use rkyv::{Archive, Deserialize, Serialize};
+
+            #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+            #[archive(evolve, check_bytes)]
+            pub struct User {
+                pub id: u32,
+                pub name: String,
+            }
+        }
+
+        mod v2 {
+            use rkyv::{
+                string::encoded::Utf8, with::Encoded, Archive, Deserialize,
+                Serialize,
+            };
+
+            #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+            #[archive(evolve, check_bytes)]
+            pub struct User {
+                pub id: u32,
+                pub name: String,
+                #[with(Encoded<Utf8>)]
+                pub nickname: Vec<u8>,
+                pub active: bool,
+            }
+        }
+
+        // Newer readers fill in the fields that older writers didn't know
+        // about.
+        let old = v1::User {
+            id: 1,
+            name: "alice".to_string(),
+        };
+        let bytes = to_bytes::<Error>(&old).unwrap();
+        let archived = access::<v2::ArchivedUser, Error>(&bytes).unwrap();
+        assert_eq!(*archived.id().unwrap(), 1);
+        assert!(archived.nickname().is_none());
+        let upgraded =
+            deserialize::<v2::User, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(
+            upgraded,
+            v2::User {
+                id: 1,
+                name: "alice".to_string(),
+                nickname: Vec::new(),
+                active: false,
+            }
+        );
+
+        // Older readers skip the fields that newer writers added.
+        let new = v2::User {
+            id: 2,
+            name: "bob".to_string(),
+            nickname: b"bobby".to_vec(),
+            active: true,
+        };
+        let bytes = to_bytes::<Error>(&new).unwrap();
+        let archived = access::<v2::ArchivedUser, Error>(&bytes).unwrap();
+        assert_eq!(archived.nickname().unwrap().as_str(), "bobby");
+        assert_eq!(
+            deserialize::<v2::User, _, Error>(archived, &mut ()).unwrap(),
+            new,
+        );
+        let archived = access::<v1::ArchivedUser, Error>(&bytes).unwrap();
+        assert_eq!(archived.name().unwrap(), "bob");
+        let downgraded =
+            deserialize::<v1::User, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(
+            downgraded,
+            v1::User {
+                id: 2,
+                name: "bob".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn schema_hashes() {
+        use rkyv::{schema::SchemaHash, Archived};
+
+        mod v1 {
+            use rkyv::Archive;
+
+            #[derive(Archive)]
+            #[archive_attr(derive(rkyv::SchemaHash))]
+            pub struct Record {
+                pub id: u32,
+                pub tags: Vec<String>,
+                pub parent: Option<Box<u64>>,
+            }
+
+            #[derive(Archive)]
+            #[archive_attr(derive(rkyv::SchemaHash))]
+            pub enum Event {
+                Created(Record),
+                Deleted { id: u32 },
+            }
+        }
+
+        mod v2 {
+            use rkyv::Archive;
+
+            #[derive(Archive)]
+            #[archive_attr(derive(rkyv::SchemaHash))]
+            pub struct Record {
+                pub id: u32,
+                pub tags: Vec<String>,
+                pub parent: Option<Box<u32>>,
+            }
+
+            #[derive(Archive)]
+            #[archive_attr(derive(rkyv::SchemaHash))]
+            pub enum Event {
+                Created(Record),
+                Deleted { id: u32 },
+            }
+        }
+
+        mod v1_copy {
+            use rkyv::Archive;
+
+            #[derive(Archive)]
+            #[archive_attr(derive(rkyv::SchemaHash))]
+            pub struct Record {
+                pub id: u32,
+                pub tags: Vec<String>,
+                pub parent: Option<Box<u64>>,
+            }
+        }
+
+        const V1: u64 = <Archived<v1::Record> as SchemaHash>::SCHEMA_HASH;
+
+        assert_eq!(V1, Archived::<v1_copy::Record>::SCHEMA_HASH);
+        assert_ne!(V1, Archived::<v2::Record>::SCHEMA_HASH);
+        assert_ne!(
+            Archived::<v1::Event>::SCHEMA_HASH,
+            Archived::<v2::Event>::SCHEMA_HASH,
+        );
+        assert_ne!(
+            Archived::<[u32; 2]>::SCHEMA_HASH,
+            Archived::<[u32; 3]>::SCHEMA_HASH,
+        );
+        assert_ne!(Archived::<u32>::SCHEMA_HASH, Archived::<i32>::SCHEMA_HASH,);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn export_archived_to_json() {
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(rkyv::Export))]
+        enum Shape {
+            Point,
+            Circle(f32),
+            Line(i16, i16),
+            Rect { width: u32, height: u32 },
+        }
+
+        #[derive(Archive, Serialize)]
+        #[archive_attr(derive(rkyv::Export))]
+        struct Scene {
+            name: String,
+            shapes: Vec<Shape>,
+            origin: (i32, i32),
+            parent: Option<Box<char>>,
+            labels: BTreeMap<u8, String>,
+        }
+
+        let mut labels = BTreeMap::new();
+        labels.insert(1, "one".to_string());
+        let value = Scene {
+            name: "demo".to_string(),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(0.5),
+                Shape::Line(-1, 2),
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            ],
+            origin: (-7, 8),
+            parent: Some(Box::new('x')),
+            labels,
+        };
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedScene>(&bytes) };
+        assert_eq!(
+            serde_json::to_string(archived).unwrap(),
+            concat!(
+                r#"{"name":"demo","shapes":["Point",{"Circle":0.5},"#,
+                r#"{"Line":[-1,2]},{"Rect":{"width":3,"height":4}}],"#,
+                r#""origin":[-7,8],"parent":"x","labels":{"1":"one"}}"#,
+            ),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn limited_writer() {
+        use rkyv::{ser::writer::LimitedWriter, util::serialize};
+
+        let value = "a string which is stored out of line".to_string();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        let mut writer =
+            LimitedWriter::new(AlignedVec::<16>::new(), bytes.len());
+        serialize::<_, Error>(&value, &mut writer).unwrap();
+        assert_eq!(writer.into_inner().as_slice(), bytes.as_slice());
+
+        let mut writer =
+            LimitedWriter::new(AlignedVec::<16>::new(), bytes.len() - 1);
+        let error = serialize::<_, Error>(&value, &mut writer).unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "needed {} bytes, limit is {} bytes",
+            bytes.len(),
+            bytes.len() - 1,
+        )));
+        assert!(writer.inner().len() < bytes.len());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn try_from_tag() {
+        use rkyv::convert::InvalidTag;
+
+        #[derive(Archive, Serialize, Clone, Copy, Debug, PartialEq)]
+        #[archive(try_from_tag)]
+        #[archive_attr(derive(Debug, PartialEq))]
+        enum Level {
+            Low,
+            Mid = 5,
+            High,
+        }
+
+        assert_eq!(Level::try_from(0), Ok(Level::Low));
+        assert_eq!(Level::try_from(5), Ok(Level::Mid));
+        assert_eq!(Level::try_from(6), Ok(Level::High));
+        assert_eq!(Level::try_from(1), Err(InvalidTag::new(1)));
+        assert_eq!(u8::from(Level::High), 6);
+
+        assert_eq!(ArchivedLevel::try_from(5), Ok(ArchivedLevel::Mid));
+        assert_eq!(ArchivedLevel::try_from(7), Err(InvalidTag::new(7)));
+
+        for level in [Level::Low, Level::Mid, Level::High] {
+            let bytes = to_bytes::<Error>(&level).unwrap();
+            let archived = unsafe { access_unchecked::<ArchivedLevel>(&bytes) };
+            assert_eq!(archived.tag(), u8::from(level));
+            assert_eq!(Level::try_from(archived.tag()), Ok(level));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn stable_hash_values() {
+        use rkyv::{digest::stable_hash, StableHash};
+
+        #[derive(StableHash)]
+        enum Shape {
+            Point,
+            Circle(u32),
+            Rect { width: u32, height: u32 },
+        }
+
+        #[derive(StableHash)]
+        struct Scene {
+            name: String,
+            shapes: Vec<Shape>,
+            parent: Option<Box<Scene>>,
+        }
+
+        let scene = |name: &str, width| Scene {
+            name: name.to_string(),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(4),
+                Shape::Rect { width, height: 2 },
+            ],
+            parent: None,
+        };
+
+        assert_eq!(stable_hash(&scene("a", 1)), stable_hash(&scene("a", 1)));
+        assert_ne!(stable_hash(&scene("a", 1)), stable_hash(&scene("b", 1)));
+        assert_ne!(stable_hash(&scene("a", 1)), stable_hash(&scene("a", 2)));
+
+        let mut child = scene("a", 1);
+        child.parent = Some(Box::new(scene("root", 1)));
+        assert_ne!(stable_hash(&child), stable_hash(&scene("a", 1)));
+
+        // Variants with the same fields are distinguished by their index.
+        assert_ne!(
+            stable_hash(&Shape::Circle(1)),
+            stable_hash(&Shape::Rect {
+                width: 1,
+                height: 0
+            }),
+        );
+        assert_ne!(stable_hash(&Shape::Point), stable_hash(&()));
+
+        // Lengths are hashed, so moving elements between strings changes the
+        // digest.
+        assert_ne!(
+            stable_hash(&("ab".to_string(), "c".to_string())),
+            stable_hash(&("a".to_string(), "bc".to_string())),
+        );
+
+        // Pointers hash the values they point to.
+        assert_eq!(stable_hash(&Box::new(10u32)), stable_hash(&10u32));
+        assert_eq!(stable_hash("hello"), stable_hash(&"hello".to_string()));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serialize_vec_from_buffered_iter() {
+        use rkyv::{
+            ser::Allocator,
+            string::ArchivedString,
+            vec::{ArchivedVec, VecResolver},
+        };
+
+        // Archives the multiples of a number below a limit as strings, without
+        // collecting them first.
+        struct Multiples {
+            of: u32,
+            below: u32,
+        }
+
+        impl Archive for Multiples {
+            type Archived = ArchivedVec<ArchivedString>;
+            type Resolver = (VecResolver, usize);
+
+            fn resolve(
+                &self,
+                (resolver, len): Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                ArchivedVec::resolve_from_len(len, resolver, out);
+            }
+        }
+
+        impl<S> Serialize<S> for Multiples
+        where
+            S: Fallible + Allocator + Writer + ?Sized,
+            String: Serialize<S, Archived = ArchivedString>,
+        {
+            fn serialize(
+                &self,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                ArchivedVec::serialize_from_buffered_iter(
+                    (0..self.below)
+                        .filter(|i| i % self.of == 0)
+                        .map(|i| i.to_string()),
+                    serializer,
+                )
+            }
+        }
+
+        for below in [0, 1, 8, 9, 100, 1000] {
+            let value = Multiples { of: 3, below };
+            let result = to_bytes::<Error>(&value).unwrap();
+            let archived = unsafe {
+                access_unchecked::<ArchivedVec<ArchivedString>>(&result)
+            };
+
+            let expected = (0..below).filter(|i| i % 3 == 0);
+            assert_eq!(archived.len(), expected.clone().count());
+            for (archived, i) in archived.iter().zip(expected) {
+                assert_eq!(archived.as_str(), i.to_string());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serialization_hooks() {
+        use core::fmt;
+
+        use rkyv::rancor::fail;
+
+        #[derive(Debug)]
+        struct Unsorted;
+
+        impl fmt::Display for Unsorted {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "values are not sorted")
+            }
+        }
+
+        impl std::error::Error for Unsorted {}
+
+        fn check_sorted<S>(value: &Sorted, _: &mut S) -> Result<(), S::Error>
+        where
+            S: Fallible + ?Sized,
+            S::Error: Source,
+        {
+            if !value.values.windows(2).all(|w| w[0] <= w[1]) {
+                fail!(Unsorted);
+            }
+            Ok(())
+        }
+
+        fn compute_sum<D>(value: &mut Sorted, _: &mut D) -> Result<(), D::Error>
+        where
+            D: Fallible + ?Sized,
+        {
+            value.sum = value.values.iter().sum();
+            Ok(())
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(
+            pre_serialize = "check_sorted",
+            post_deserialize = "compute_sum",
+            serialize_bounds(__S::Error: Source),
+        )]
+        struct Sorted {
+            values: Vec<u32>,
+            #[with(rkyv::with::Skip)]
+            sum: u32,
+        }
+
+        let value = Sorted {
+            values: vec![1, 2, 3],
+            sum: 0,
+        };
+        let result = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedSorted>(&result) };
+        let deserialized =
+            deserialize::<Sorted, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(
+            deserialized,
+            Sorted {
+                values: vec![1, 2, 3],
+                sum: 6,
+            }
+        );
+
+        let value = Sorted {
+            values: vec![3, 1, 2],
+            sum: 0,
+        };
+        assert!(to_bytes::<Error>(&value).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn serialize_maps_from_pairs() {
+        use rkyv::{
+            collections::{
+                btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+                swiss_table::{ArchivedHashMap, HashMapResolver},
+                util::DuplicateKeys,
+            },
+            ser::Allocator,
+            string::ArchivedString,
+        };
+
+        type HashCounts = ArchivedHashMap<ArchivedString, Archived<u32>>;
+        type BTreeCounts = ArchivedBTreeMap<ArchivedString, Archived<u32>>;
+
+        // Archives word counts as maps without collecting them into native
+        // maps first.
+        struct Counts<const SORTED: bool> {
+            pairs: Vec<(&'static str, u32)>,
+            duplicates: DuplicateKeys,
+        }
+
+        impl Archive for Counts<false> {
+            type Archived = HashCounts;
+            type Resolver = (HashMapResolver, usize);
+
+            fn resolve(
+                &self,
+                (resolver, len): Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                ArchivedHashMap::resolve_from_len(len, (7, 8), resolver, out);
+            }
+        }
+
+        impl<S> Serialize<S> for Counts<false>
+        where
+            S: Fallible + Allocator + Writer + ?Sized,
+            S::Error: Source,
+        {
+            fn serialize(
+                &self,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                ArchivedHashMap::serialize_from_pairs(
+                    self.pairs
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), *value)),
+                    (7, 8),
+                    self.duplicates,
+                    serializer,
+                )
+            }
+        }
+
+        impl Archive for Counts<true> {
+            type Archived = BTreeCounts;
+            type Resolver = (BTreeMapResolver, usize);
+
+            fn resolve(
+                &self,
+                (resolver, len): Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                ArchivedBTreeMap::resolve_from_len(len, resolver, out);
+            }
+        }
+
+        impl<S> Serialize<S> for Counts<true>
+        where
+            S: Fallible + Allocator + Writer + ?Sized,
+            S::Error: Source,
+        {
+            fn serialize(
+                &self,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                ArchivedBTreeMap::serialize_from_sorted_iter(
+                    self.pairs
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), *value)),
+                    self.duplicates,
+                    serializer,
+                )
+            }
+        }
+
+        let pairs = vec![("a", 1), ("b", 2), ("b", 3), ("c", 4), ("c", 5)];
+        for (duplicates, b, c) in [
+            (DuplicateKeys::KeepFirst, 2, 4),
+            (DuplicateKeys::KeepLast, 3, 5),
+        ] {
+            let value = Counts::<false> {
+                pairs: pairs.clone(),
+                duplicates,
+            };
+            let bytes = to_bytes::<Error>(&value).unwrap();
+            let archived = unsafe { access_unchecked::<HashCounts>(&bytes) };
+            assert_eq!(archived.len(), 3);
+            assert_eq!(*archived.get("a").unwrap(), 1);
+            assert_eq!(*archived.get("b").unwrap(), b);
+            assert_eq!(*archived.get("c").unwrap(), c);
+
+            let value = Counts::<true> {
+                pairs: pairs.clone(),
+                duplicates,
+            };
+            let bytes = to_bytes::<Error>(&value).unwrap();
+            let archived = unsafe { access_unchecked::<BTreeCounts>(&bytes) };
+            assert_eq!(archived.len(), 3);
+            assert_eq!(*archived.get("a").unwrap(), 1);
+            assert_eq!(*archived.get("b").unwrap(), b);
+            assert_eq!(*archived.get("c").unwrap(), c);
+        }
+
+        let value = Counts::<false> {
+            pairs: pairs.clone(),
+            duplicates: DuplicateKeys::Error,
+        };
+        assert!(to_bytes::<Error>(&value).is_err());
+        let value = Counts::<true> {
+            pairs,
+            duplicates: DuplicateKeys::Error,
+        };
+        assert!(to_bytes::<Error>(&value).is_err());
+
+        let value = Counts::<true> {
+            pairs: vec![("b", 1), ("a", 2)],
+            duplicates: DuplicateKeys::Error,
+        };
+        assert!(to_bytes::<Error>(&value).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_in_format() {
+        use core::mem::size_of;
+
+        use rkyv::{
+            format::{BigEndian, LittleEndian, Width16},
+            rend::{u16_le, u32_be},
+            vec::ArchivedVec,
+            with::InFormat,
+        };
+
+        type Small = LittleEndian<Width16>;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Mixed {
+            #[with(InFormat<BigEndian<Width16>>)]
+            id: u32,
+            #[with(InFormat<Small>)]
+            len: usize,
+            #[with(InFormat<Small>)]
+            bytes: Vec<u8>,
+            names: Vec<String>,
+        }
+
+        assert_eq!(size_of::<ArchivedVec<u8, Small>>(), 4);
+
+        let value = Mixed {
+            id: 0x0102_0304,
+            len: 3,
+            bytes: vec![1, 2, 3],
+            names: vec!["a".to_string(), "b".to_string()],
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedMixed>(&bytes) };
+
+        let id: &u32_be = &archived.id;
+        assert_eq!(id.to_native(), 0x0102_0304);
+        let len: &u16_le = &archived.len;
+        assert_eq!(len.to_native(), 3);
+        assert_eq!(archived.bytes.as_slice(), &[1, 2, 3]);
+        // Fields without a wrapper are still archived in the default format
+        assert_eq!(archived.names.len(), 2);
+        assert_eq!(archived.names[1].as_str(), "b");
+
+        let deserialized =
+            deserialize::<Mixed, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_big_endian_format() {
+        use rkyv::{
+            format::{
+                to_bytes_in_format, ArchivedIn, BigEndian, LittleEndian,
+                Width32,
+            },
+            schema::SchemaHash,
+            vec::ArchivedVec,
+        };
+
+        type Big = BigEndian<Width32>;
+
+        // A big-endian archive of `vec![1u8, 2, 3, 4]`, followed by the
+        // relative pointer and length of the vec
+        let mut bytes = AlignedVec::<16>::new();
+        bytes.extend_from_slice(&[
+            1, 2, 3, 4, 0xff, 0xff, 0xff, 0xfc, 0, 0, 0, 4,
+        ]);
+
+        let archived =
+            unsafe { access_unchecked::<ArchivedIn<Vec<u8>, Big>>(&bytes) };
+        assert_eq!(archived.len(), 4);
+        assert_eq!(archived.as_slice(), &[1, 2, 3, 4]);
+        let deserialized =
+            deserialize::<Vec<u8>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, vec![1, 2, 3, 4]);
+
+        // Serializing in the big-endian format produces the same bytes
+        let value = vec![1u8, 2, 3, 4];
+        let serialized = to_bytes_in_format::<Big, _, Error>(&value).unwrap();
+        assert_eq!(serialized.as_slice(), bytes.as_slice());
+
+        // Integers are archived as the primitives of the format
+        let serialized =
+            to_bytes_in_format::<Big, _, Error>(&0x01020304u32).unwrap();
+        assert_eq!(serialized.as_slice(), &[1, 2, 3, 4]);
+        let archived =
+            unsafe { access_unchecked::<ArchivedIn<u32, Big>>(&serialized) };
+        assert_eq!(archived.to_native(), 0x01020304);
+
+        // The format is part of the schema hash
+        assert_ne!(
+            <ArchivedVec<u8, Big> as SchemaHash>::SCHEMA_HASH,
+            <ArchivedVec<u8, LittleEndian<Width32>> as SchemaHash>::SCHEMA_HASH,
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn incremental_vec_partial_flushes() {
+        use core::{mem::size_of, task::Poll};
+
+        use rkyv::ser::incremental::IncrementalVec;
+
+        let values = (0..100u64).collect::<Vec<_>>();
+
+        let mut writer = AlignedVec::new();
+        let mut incremental = IncrementalVec::new(&values);
+        let mut flushed = Vec::new();
+        let mut steps = 0;
+        let pos = loop {
+            let before = writer.len();
+            let serializer = Strategy::<_, Error>::wrap(&mut writer);
+            match incremental.poll_serialize(serializer, 64) {
+                Poll::Ready(result) => break result.unwrap(),
+                Poll::Pending => {
+                    steps += 1;
+                    assert!(!incremental.is_done());
+                    assert!(writer.len() - before >= 64);
+                    // Bytes written by earlier steps are never revisited, so
+                    // they can be flushed as soon as each step returns.
+                    flushed.extend_from_slice(&writer[flushed.len()..]);
+                }
+            }
+        };
+        flushed.extend_from_slice(&writer[flushed.len()..]);
+
+        assert!(incremental.is_done());
+        assert!(steps >= 10);
+        assert_eq!(flushed.as_slice(), writer.as_slice());
+        assert_eq!(pos + size_of::<Archived<Vec<u64>>>(), flushed.len());
+
+        let mut bytes = AlignedVec::<16>::new();
+        bytes.extend_from_slice(&flushed);
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u64>>>(&bytes) };
+        assert_eq!(archived, &values);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn incremental_vec_out_of_line() {
+        use core::task::Poll;
+
+        use rkyv::ser::{incremental::IncrementalVec, AllocSerializer};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Entry {
+            name: String,
+            tags: Vec<String>,
+            parent: Option<Box<u32>>,
+        }
+
+        let values = (0..50u32)
+            .map(|i| Entry {
+                name: i.to_string(),
+                tags: (0..i % 4).map(|j| j.to_string()).collect(),
+                parent: (i % 3 == 0).then(|| Box::new(i / 3)),
+            })
+            .collect::<Vec<_>>();
+
+        let mut serializer = AllocSerializer::default();
+        let mut incremental = IncrementalVec::new(&values);
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            let strategy = Strategy::<_, Error>::wrap(&mut serializer);
+            match incremental.poll_serialize(strategy, 128) {
+                Poll::Ready(result) => {
+                    result.unwrap();
+                    break;
+                }
+                Poll::Pending => continue,
+            }
+        }
+        assert!(steps > 1);
+
+        let bytes = serializer.into_writer();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Entry>>>(&bytes) };
+        assert_eq!(archived.len(), values.len());
+        assert_eq!(archived[7].name, "7");
+        assert_eq!(archived[7].tags.len(), 3);
+
+        let deserialized =
+            deserialize::<Vec<Entry>, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, values);
+    }
+
+    #[cfg(feature = "chacha20poly1305")]
+    fn encrypt_archive(
+        value: &[u32; 4],
+        key: &[u8; 32],
+        nonces: &mut rkyv::encrypted::NonceCounter<
+            rkyv::encrypted::ChaCha20Poly1305,
+        >,
+    ) -> Vec<u8> {
+        use rkyv::encrypted::EncryptedWriter;
+
+        let writer = EncryptedWriter::with_counter::<Error>(
+            Vec::new(),
+            key.into(),
+            nonces,
+        )
+        .unwrap()
+        .with_aad(b"header");
+        serialize_into::<_, Error>(value, writer)
+            .unwrap()
+            .into_inner()
+    }
+
+    #[test]
+    #[cfg(feature = "chacha20poly1305")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn encrypted_archive_rejects_tampering() {
+        use rkyv::encrypted::{
+            decrypt, open_encrypted_unchecked, ChaCha20Poly1305, NonceCounter,
+        };
+
+        let key = [7; 32];
+        let value = [1u32, 2, 3, 4];
+        let mut nonces = NonceCounter::new([0; 12].into());
+        let encrypted = encrypt_archive(&value, &key, &mut nonces);
+
+        let opened = unsafe {
+            open_encrypted_unchecked::<
+                Archived<[u32; 4]>,
+                ChaCha20Poly1305,
+                Error,
+            >(&encrypted, (&key).into(), b"header")
+        }
+        .unwrap();
+        assert_eq!(opened.get(), &value.map(Archived::<u32>::from_native));
+
+        // Flipping any bit of the nonce, ciphertext, or tag fails to
+        // authenticate
+        for i in 0..encrypted.len() {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 0x01;
+            assert!(decrypt::<ChaCha20Poly1305, Error>(
+                &tampered,
+                (&key).into(),
+                b"header",
+            )
+            .is_err());
+        }
+
+        // Truncated archives are rejected
+        for len in [0, 12, 12 + 16, encrypted.len() - 1] {
+            assert!(decrypt::<ChaCha20Poly1305, Error>(
+                &encrypted[..len],
+                (&key).into(),
+                b"header",
+            )
+            .is_err());
+        }
+
+        // So is the wrong associated data
+        assert!(decrypt::<ChaCha20Poly1305, Error>(
+            &encrypted,
+            (&key).into(),
+            b"other",
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "chacha20poly1305")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn encrypted_archive_rejects_wrong_key() {
+        use rkyv::encrypted::{decrypt, ChaCha20Poly1305, NonceCounter};
+
+        let key = [7; 32];
+        let value = [1u32, 2, 3, 4];
+        let mut nonces = NonceCounter::new([0; 12].into());
+        let encrypted = encrypt_archive(&value, &key, &mut nonces);
+
+        assert!(decrypt::<ChaCha20Poly1305, Error>(
+            &encrypted,
+            (&key).into(),
+            b"header",
+        )
+        .is_ok());
+        for i in 0..key.len() {
+            let mut wrong_key = key;
+            wrong_key[i] ^= 0x80;
+            assert!(decrypt::<ChaCha20Poly1305, Error>(
+                &encrypted,
+                (&wrong_key).into(),
+                b"header",
+            )
+            .is_err());
+        }
+
+        // Archives encrypted with consecutive nonces from a counter differ,
+        // and each only decrypts with its own nonce
+        let second = encrypt_archive(&value, &key, &mut nonces);
+        assert_ne!(encrypted[..12], second[..12]);
+        assert_ne!(encrypted[12..], second[12..]);
+        let mut swapped = second.clone();
+        swapped[..12].copy_from_slice(&encrypted[..12]);
+        assert!(decrypt::<ChaCha20Poly1305, Error>(
+            &swapped,
+            (&key).into(),
+            b"header",
+        )
+        .is_err());
+
+        // A counter fails once it runs out of nonces
+        let mut nonces =
+            NonceCounter::<ChaCha20Poly1305>::new([0xff; 12].into());
+        assert!(nonces.take::<Error>().is_ok());
+        assert!(nonces.peek().is_none());
+        assert!(nonces.take::<Error>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fallible-alloc")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn fallible_writer_allocation_failure() {
+        use core::{alloc::Layout, ptr};
+
+        use rkyv::{
+            ser::writer::FallibleWriter,
+            util::{Global, RawAllocator},
+        };
+
+        // Fails every allocation larger than `limit` bytes
+        struct Capped {
+            limit: usize,
+        }
+
+        unsafe impl RawAllocator for Capped {
+            unsafe fn allocate(&self, layout: Layout) -> *mut u8 {
+                if layout.size() > self.limit {
+                    ptr::null_mut()
+                } else {
+                    unsafe { Global.allocate(layout) }
+                }
+            }
+
+            unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+
+        let value = [7u8; 256];
+
+        let vec = AlignedVec::<16, _>::new_in(Capped { limit: 128 });
+        let writer = FallibleWriter::new(vec);
+        assert!(serialize_into::<_, Error>(&value, writer).is_err());
+
+        let vec = AlignedVec::<16, _>::new_in(Capped { limit: 1024 });
+        let writer = FallibleWriter::new(vec);
+        let writer = serialize_into::<_, Error>(&value, writer).unwrap();
+        assert_eq!(writer.into_inner().as_slice(), &value);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn deserialize_with_quota() {
+        use rkyv::{
+            de::quota::Quota,
+            with::{Map, Metered},
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Test {
+            #[with(Metered)]
+            ids: Vec<u32>,
+            #[with(Map<Metered>)]
+            names: Vec<String>,
+            #[with(Metered)]
+            lookup: BTreeMap<u32, String>,
+            shared: Rc<[u32]>,
+            other: Rc<[u32]>,
+        }
+
+        let shared = Rc::<[u32]>::from(vec![1, 2, 3, 4]);
+        let mut lookup = BTreeMap::new();
+        lookup.insert(1, "one".to_string());
+        let value = Test {
+            ids: vec![1, 2, 3],
+            names: vec!["a".to_string(), "b".to_string(), String::new()],
+            lookup,
+            shared: shared.clone(),
+            other: shared,
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedTest>(&bytes) };
+
+        // One allocation for the ids, two for the non-empty names, and one for
+        // the map. The names vector and the shared slices are not metered.
+        let mut quota = Quota::new(DefaultDeserializer::default());
+        let result = deserialize::<Test, _, Error>(archived, &mut quota);
+        assert_eq!(result.unwrap(), value);
+        assert_eq!(quota.allocations(), 4);
+        let used = quota.bytes();
+
+        let mut quota =
+            Quota::new(DefaultDeserializer::default()).with_max_bytes(used);
+        assert!(deserialize::<Test, _, Error>(archived, &mut quota).is_ok());
+
+        let mut quota =
+            Quota::new(DefaultDeserializer::default()).with_max_bytes(used - 1);
+        assert!(deserialize::<Test, _, Error>(archived, &mut quota).is_err());
+
+        let mut quota =
+            Quota::new(DefaultDeserializer::default()).with_max_allocations(3);
+        assert!(deserialize::<Test, _, Error>(archived, &mut quota).is_err());
+
+        // Quotas forward metering to the deserializer they wrap
+        let inner =
+            Quota::new(DefaultDeserializer::default()).with_max_allocations(3);
+        let mut quota = Quota::new(inner);
+        assert!(deserialize::<Test, _, Error>(archived, &mut quota).is_err());
+
+        let mut quota = Quota::new(Quota::new(DefaultDeserializer::default()));
+        assert!(deserialize::<Test, _, Error>(archived, &mut quota).is_ok());
+        assert_eq!(quota.into_inner().allocations(), 4);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn merkle_chunk_hashing() {
+        use rkyv::merkle::{MerkleTree, MerkleWriter, SipMerkleHasher};
+
+        let value = (0..1000u32).map(|i| i.to_string()).collect::<Vec<_>>();
+        let hasher = SipMerkleHasher::new_with_keys(3, 5);
+
+        let writer =
+            MerkleWriter::new(DefaultSerializer::default(), hasher, 100);
+        let (serializer, tree) = serialize_into::<_, Error>(&value, writer)
+            .unwrap()
+            .into_raw_parts();
+        let mut bytes = serializer.into_writer();
+
+        // The incremental tree matches one computed over the finished buffer
+        let expected = MerkleTree::from_bytes(&bytes, 100, &hasher);
+        assert_eq!(tree, expected);
+        tree.verify::<_, Error>(&bytes, &hasher).unwrap();
+
+        let range = tree.chunk_range(3).unwrap();
+        tree.verify_chunk::<_, Error>(3, &bytes[range.clone()], &hasher)
+            .unwrap();
+
+        bytes[range.start] ^= 1;
+        assert!(tree
+            .verify_chunk::<_, Error>(3, &bytes[range], &hasher)
+            .is_err());
+        assert!(tree.verify::<_, Error>(&bytes, &hasher).is_err());
+        assert_ne!(
+            MerkleTree::from_bytes(&bytes, 100, &hasher).root(),
+            tree.root()
+        );
+
+        // A tree whose leaves do not cover its length is rejected
+        let short = MerkleTree::from_leaves(
+            100,
+            bytes.len(),
+            tree.leaves()[1..].to_vec(),
+            &hasher,
+        );
+        assert!(short.verify::<_, Error>(&bytes, &hasher).is_err());
     }
 
     #[test]
@@ -3,9 +3,12 @@ mod tests {
     use std::collections::{HashMap, HashSet};
 
     use rkyv::{
-        access_unchecked, rancor::Error, ser::writer::IoWriter, serialize,
-        to_bytes, util::AlignedBytes, Archive, Archived, Deserialize,
-        Serialize,
+        access_unchecked,
+        rancor::Error,
+        ser::writer::IoWriter,
+        serialize, to_bytes,
+        util::{deserialize, AlignedBytes},
+        Archive, Archived, Deserialize, Serialize,
     };
     #[cfg(feature = "wasm")]
     use wasm_bindgen_test::*;
@@ -28,6 +31,109 @@ mod tests {
             .expect_err("serialized to an undersized buffer must fail");
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn write_serializer_finish_flushes() {
+        use std::io::BufWriter;
+
+        use rkyv::ser::Positional as _;
+
+        #[derive(Archive, Serialize)]
+        struct Example {
+            x: i32,
+            y: u64,
+        }
+
+        let value = Example { x: 1, y: 2 };
+        let mut ser = IoWriter::new(BufWriter::new(Vec::new()));
+        serialize::<_, Error>(&value, &mut ser).unwrap();
+
+        let pos = ser.pos();
+        let buf = ser.into_inner();
+        assert_eq!(buf.buffer().len(), 0);
+        assert_eq!(buf.get_ref().len(), pos);
+    }
+
+    #[test]
+    fn write_serializer_streams_to_file() {
+        use std::{fs::File, io::BufWriter};
+
+        use rkyv::{
+            ser::{allocator::GlobalAllocator, sharing::Unify, Composite},
+            util::{serialize_into, AlignedVec},
+        };
+
+        let value = (0..100_000u32).map(|i| i.to_string()).collect::<Vec<_>>();
+        let path = std::env::temp_dir()
+            .join(format!("rkyv_io_writer_{}.rkyv", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let serializer = Composite::new(
+            IoWriter::new(BufWriter::new(file)),
+            GlobalAllocator::default(),
+            Unify::default(),
+        );
+        serialize_into::<_, Error>(&value, serializer).unwrap();
+
+        let mut bytes = AlignedVec::new();
+        bytes
+            .extend_from_reader(&mut File::open(&path).unwrap())
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<String>>>(&bytes) };
+        assert_eq!(archived.len(), 100_000);
+        assert_eq!(archived[99_999], "99999");
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn chunked_writer_vectored_output() {
+        use std::io;
+
+        use rkyv::{ser::writer::ChunkedWriter, util::serialize_into};
+
+        // Accepts at most a few bytes per call to exercise partial writes
+        struct Trickle(Vec<u8>);
+
+        impl io::Write for Trickle {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let len = buf.len().min(7);
+                self.0.extend_from_slice(&buf[..len]);
+                Ok(len)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = (0..100)
+            .map(|i| (i, format!("value number {}", i)))
+            .collect::<HashMap<u32, String>>();
+        let writer =
+            serialize_into::<_, Error>(&value, ChunkedWriter::new(64)).unwrap();
+        assert!(writer.chunks().all(|chunk| chunk.len() <= 64));
+        assert_eq!(writer.io_slices().len(), writer.chunks().len());
+        assert_eq!(
+            writer.chunks().map(|chunk| chunk.len()).sum::<usize>(),
+            writer.len(),
+        );
+
+        let mut trickle = Trickle(Vec::new());
+        writer.write_to(&mut trickle).unwrap();
+        assert_eq!(trickle.0, writer.to_vec());
+
+        let mut bytes = rkyv::util::AlignedVec::<16>::new();
+        bytes.extend_from_slice(&trickle.0);
+        let archived = unsafe {
+            access_unchecked::<Archived<HashMap<u32, String>>>(&bytes)
+        };
+        assert_eq!(archived.len(), 100);
+        let key = rkyv::primitive::ArchivedU32::from_native(42);
+        assert_eq!(archived.get(&key).unwrap(), "value number 42");
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn archive_hash_map() {
@@ -230,6 +336,327 @@ mod tests {
         test_archive(&value);
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_system_time_error() {
+        use std::time::{Duration, SystemTime, SystemTimeError};
+
+        let later = SystemTime::UNIX_EPOCH + Duration::from_millis(1500);
+        let error = SystemTime::UNIX_EPOCH.duration_since(later).unwrap_err();
+
+        let bytes = to_bytes::<Error>(&error).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<SystemTimeError>>(&bytes) };
+        assert_eq!(*archived, Duration::from_millis(1500));
+
+        let deserialized =
+            deserialize::<SystemTimeError, _, Error>(archived, &mut ())
+                .unwrap();
+        assert_eq!(deserialized.duration(), error.duration());
+
+        let bytes = to_bytes::<Error>(&Duration::ZERO).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<SystemTimeError>>(&bytes) };
+        deserialize::<SystemTimeError, _, Error>(archived, &mut ())
+            .expect_err("a system time error can't have a zero duration");
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_errors() {
+        use std::{error::Error as StdError, fmt, io};
+
+        use rkyv::error::RestoredError;
+
+        #[derive(Debug)]
+        struct Outer(io::Error);
+
+        impl fmt::Display for Outer {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "job failed")
+            }
+        }
+
+        impl StdError for Outer {
+            fn source(&self) -> Option<&(dyn StdError + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        #[derive(Archive, Serialize, Deserialize)]
+        #[archive(check_bytes)]
+        struct Job {
+            result: Result<u32, Box<dyn StdError + Send + Sync>>,
+            kind: io::ErrorKind,
+        }
+
+        let inner =
+            io::Error::new(io::ErrorKind::NotFound, "input file is missing");
+        let value = Job {
+            result: Err(Box::new(Outer(inner))),
+            kind: io::ErrorKind::PermissionDenied,
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = rkyv::access::<ArchivedJob, Error>(&bytes).unwrap();
+        assert_eq!(archived.kind, io::ErrorKind::PermissionDenied);
+        let error = match &archived.result {
+            rkyv::result::ArchivedResult::Err(error) => error,
+            rkyv::result::ArchivedResult::Ok(_) => panic!("expected an error"),
+        };
+        assert_eq!(error.message(), "job failed");
+        assert_eq!(
+            error.sources().collect::<Vec<_>>(),
+            ["input file is missing"],
+        );
+
+        let deserialized =
+            deserialize::<Job, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized.kind, io::ErrorKind::PermissionDenied);
+        let error = deserialized.result.unwrap_err();
+        assert_eq!(error.to_string(), "job failed");
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "input file is missing",
+        );
+        assert!(error.source().unwrap().source().is_none());
+
+        let restored = error.downcast::<RestoredError>().unwrap();
+        let bytes = to_bytes::<Error>(&*restored).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<RestoredError>>(&bytes) };
+        assert_eq!(
+            deserialize::<RestoredError, _, Error>(archived, &mut ()).unwrap(),
+            *restored,
+        );
+
+        let error = io::Error::new(io::ErrorKind::TimedOut, "no response");
+        let bytes = to_bytes::<Error>(&error).unwrap();
+        let archived =
+            rkyv::access::<Archived<io::Error>, Error>(&bytes).unwrap();
+        assert_eq!(archived.kind, io::ErrorKind::TimedOut);
+        assert_eq!(archived.to_string(), "no response");
+        let deserialized =
+            deserialize::<io::Error, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(deserialized.to_string(), "no response");
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn write_atomic_file() {
+        use rkyv::fs::{open_checked, write_atomic, HEADER_LEN};
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Snapshot {
+            version: u32,
+            items: Vec<String>,
+        }
+
+        let path = std::env::temp_dir()
+            .join(format!("rkyv_atomic_{}.rkyv", std::process::id()));
+        let snapshot = |version: u32| Snapshot {
+            version,
+            items: (0..version).map(|i| format!("item {}", i)).collect(),
+        };
+
+        write_atomic::<_, Error>(&path, &snapshot(1)).unwrap();
+        write_atomic::<_, Error>(&path, &snapshot(3)).unwrap();
+        let file = open_checked::<ArchivedSnapshot, Error>(&path).unwrap();
+        assert_eq!(file.version.to_native(), 3);
+        assert_eq!(file.items[2], "item 2");
+
+        // No temporary files are left behind.
+        let dir = path.parent().unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let leftovers = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let entry_name = entry.file_name();
+                let entry_name = entry_name.to_string_lossy();
+                entry_name.starts_with(&format!(".{}.", name))
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+
+        // Flipping a byte of the archive fails the checksum.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_LEN] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        open_checked::<ArchivedSnapshot, Error>(&path)
+            .expect_err("a corrupted file must fail its checksum");
+
+        // A torn write is detected by the length in the header.
+        bytes[HEADER_LEN] ^= 1;
+        std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+        open_checked::<ArchivedSnapshot, Error>(&path)
+            .expect_err("a truncated file must fail to open");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn background_validation() {
+        use rkyv::validation::background::{
+            access_cancellable, validate_in_background, CancelToken,
+        };
+
+        let value = (0..100).map(|i| vec![i; 4]).collect::<Vec<Vec<u32>>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        let token = CancelToken::new();
+        let archived = access_cancellable::<Archived<Vec<Vec<u32>>>, Error>(
+            &bytes, &token,
+        )
+        .unwrap();
+        assert_eq!(archived.len(), 100);
+        token.cancel();
+        access_cancellable::<Archived<Vec<Vec<u32>>>, Error>(&bytes, &token)
+            .expect_err("a cancelled validation must fail");
+
+        let handle =
+            validate_in_background::<_, Archived<Vec<Vec<u32>>>, Error>(bytes);
+        assert!(!handle.token().is_cancelled());
+        let validated = handle.join().unwrap();
+        assert_eq!(validated.len(), 100);
+        assert_eq!(validated[42][3].to_native(), 42);
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn validation_cache() {
+        use rkyv::validation::cache::ValidationCache;
+
+        let mut bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+        let cache = ValidationCache::new();
+        assert!(cache.is_empty());
+
+        for _ in 0..3 {
+            let archived = unsafe {
+                cache
+                    .access::<Archived<Vec<u32>>, Error>(&bytes, 0)
+                    .unwrap()
+            };
+            assert_eq!(archived[1], 2);
+        }
+        assert_eq!(cache.len(), 1);
+
+        // The same position as a different type is validated separately.
+        let pos = bytes.len() - core::mem::size_of::<Archived<Vec<u32>>>();
+        unsafe {
+            cache
+                .check_pos::<Archived<u32>, Error>(&bytes, 0, pos)
+                .unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains::<Archived<u32>>(&bytes, 0, pos));
+        assert!(!cache.contains::<Archived<u32>>(&bytes, 1, pos));
+
+        // Corrupting the buffer and starting a new generation validates it
+        // again.
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&[0xff; 4]);
+        unsafe {
+            cache
+                .access::<Archived<Vec<u32>>, Error>(&bytes, 1)
+                .expect_err("corrupted buffer must fail validation");
+        }
+        assert!(!cache.contains::<Archived<Vec<u32>>>(&bytes, 1, pos));
+
+        cache.invalidate(&bytes);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn async_serialize_stream() {
+        use core::{
+            future::Future,
+            pin::{pin, Pin},
+            task::{Context, Poll, Waker},
+        };
+        use std::{sync::Arc, task::Wake};
+
+        use rkyv::ser::stream::{
+            to_async_writer, AsyncSerializer, AsyncWriter,
+        };
+
+        struct NoopWaker;
+
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut cx = Context::from_waker(&waker);
+            let mut future = pin!(future);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+        }
+
+        // Accepts at most 7 bytes per write and is only ready every other
+        // poll.
+        #[derive(Default)]
+        struct Trickle {
+            bytes: AlignedVec,
+            ready: bool,
+            largest_write: usize,
+        }
+
+        impl AsyncWriter<Error> for Trickle {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                bytes: &[u8],
+            ) -> Poll<Result<usize, Error>> {
+                self.ready = !self.ready;
+                if !self.ready {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                self.largest_write = self.largest_write.max(bytes.len());
+                let len = bytes.len().min(7);
+                self.bytes.extend_from_slice(&bytes[..len]);
+                Poll::Ready(Ok(len))
+            }
+
+            fn poll_flush(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<Result<(), Error>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let value = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        let mut writer = Trickle::default();
+        let len = block_on(to_async_writer::<_, _, Error>(&value, &mut writer))
+            .unwrap();
+        assert_eq!(len, writer.bytes.len());
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<String>>>(&writer.bytes) };
+        assert_eq!(archived, &value);
+
+        let mut serializer = AsyncSerializer::new(Trickle::default());
+        block_on(serializer.serialize_slice::<_, Error>(&value, 256)).unwrap();
+        block_on(serializer.flush::<Error>()).unwrap();
+        let writer = serializer.into_writer();
+        assert!(writer.largest_write < 1024);
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<String>>>(&writer.bytes) };
+        assert_eq!(archived, &value);
+    }
+
     #[test]
     #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
     fn c_string() {
@@ -407,4 +834,388 @@ mod tests {
             y: Some(ExampleEnum::Bar(0)),
         };
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn cooperative_deserialize() {
+        use core::{
+            future::Future,
+            num::NonZeroUsize,
+            pin::pin,
+            task::{Context, Poll, Waker},
+        };
+        use std::{sync::Arc, task::Wake};
+
+        use rkyv::{
+            de::{
+                coop::{deserialize_hash_map, deserialize_vec, Budget},
+                pooling::Unify,
+            },
+            rancor::Strategy,
+        };
+
+        struct NoopWaker;
+
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut cx = Context::from_waker(&waker);
+            let mut future = pin!(future);
+            let mut yields = 0;
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(output) => return (output, yields),
+                    Poll::Pending => yields += 1,
+                }
+            }
+        }
+
+        let value = (0..100).collect::<Vec<u32>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<u32>>>(&bytes) };
+
+        let mut deserializer = Unify::default();
+        let (result, yields) = block_on(deserialize_vec::<u32, _>(
+            archived,
+            Strategy::<_, Error>::wrap(&mut deserializer),
+            Budget::elements(NonZeroUsize::new(10).unwrap()),
+        ));
+        assert_eq!(result.unwrap(), value);
+        assert_eq!(yields, 10);
+
+        let (result, yields) = block_on(deserialize_vec::<u32, _>(
+            archived,
+            Strategy::<_, Error>::wrap(&mut deserializer),
+            Budget::inline_bytes(NonZeroUsize::new(200).unwrap()),
+        ));
+        assert_eq!(result.unwrap(), value);
+        assert_eq!(yields, 2);
+
+        let map = (0..10).map(|i| (i, i * 2)).collect::<HashMap<u32, u32>>();
+        let bytes = to_bytes::<Error>(&map).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<HashMap<u32, u32>>>(&bytes) };
+        let (result, yields) = block_on(deserialize_hash_map::<
+            u32,
+            u32,
+            std::collections::hash_map::RandomState,
+            _,
+        >(
+            archived,
+            Strategy::<_, Error>::wrap(&mut deserializer),
+            Budget::unlimited(),
+        ));
+        assert_eq!(result.unwrap(), map);
+        assert_eq!(yields, 0);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn envelope_read_frame() {
+        use std::io::Cursor;
+
+        use rkyv::{
+            envelope::{encode_request, read_frame, Envelope},
+            util::AlignedVec,
+        };
+
+        let mut stream = Vec::new();
+        for i in 0..3u32 {
+            let frame = encode_request::<_, Error>(i, i.into(), &i).unwrap();
+            stream.extend_from_slice(&frame);
+        }
+
+        let mut reader = Cursor::new(stream);
+        let mut buffer = AlignedVec::new();
+        for i in 0..3u32 {
+            read_frame(&mut reader, &mut buffer).unwrap();
+            let envelope = Envelope::decode::<Error>(&buffer).unwrap();
+            assert_eq!(envelope.method(), i);
+            assert_eq!(envelope.correlation(), u64::from(i));
+            assert_eq!(envelope.frame_len(), buffer.len());
+            let value = unsafe { envelope.access_unchecked::<Archived<u32>>() };
+            assert_eq!(*value, i);
+        }
+        assert!(read_frame(&mut reader, &mut buffer).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bytecheck")]
+    fn key_value_store() {
+        use rkyv::store::{Store, StoreBuilder};
+
+        let mut builder = StoreBuilder::new();
+        for i in 0..100u32 {
+            builder.insert(format!("key{}", i), vec![i; i as usize % 4]);
+        }
+        assert_eq!(builder.len(), 100);
+
+        let path = std::env::temp_dir()
+            .join(format!("rkyv_store_{}.rkyv", std::process::id()));
+        builder
+            .with_bloom_filter(10)
+            .write_to_file::<Error>(&path)
+            .unwrap();
+        let store = Store::<String, Vec<u32>>::open::<Error>(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(store.len(), 100);
+        assert!(store.bloom_filter().is_some());
+        let value = store.get("key7").unwrap();
+        assert_eq!(value.len(), 3);
+        assert!(value.iter().all(|v| v.to_native() == 7));
+        assert!(store.get("key100").is_none());
+        assert!(store.contains_key("key0"));
+        assert!((100..200).all(|i| !store.contains_key(&*format!("key{}", i))));
+
+        let mut builder = StoreBuilder::<String, u32>::new();
+        builder.insert("a".to_string(), 1);
+        let bytes = builder.to_bytes::<Error>().unwrap();
+        let store = Store::<String, u32>::from_bytes::<Error>(bytes).unwrap();
+        assert!(store.bloom_filter().is_none());
+        assert_eq!(store.get("a").map(|v| v.to_native()), Some(1));
+
+        assert!(Store::<String, u32>::from_bytes::<Error>(
+            rkyv::util::AlignedVec::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn guarded_archive() {
+        use rkyv::{
+            access_unchecked_mut,
+            guarded::{Guarded, GuardedArchive},
+            primitive::ArchivedU32,
+        };
+
+        let mut bytes = to_bytes::<Error>(&Guarded([0u32; 2])).unwrap();
+        let guarded = unsafe {
+            access_unchecked_mut::<GuardedArchive<[ArchivedU32; 2]>>(&mut bytes)
+        }
+        .get_ref();
+
+        let guard = guarded.write();
+        assert!(guarded.is_locked());
+        assert!(guarded.try_write().is_none());
+        drop(guard);
+        assert_eq!(guarded.generation(), 2);
+
+        // Readers must never observe a partially written pair.
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        let [a, b] = guarded.snapshot();
+                        assert_eq!(a, b);
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for i in 1..=1000 {
+                    let mut guard = guarded.write();
+                    let value = ArchivedU32::from_native(i);
+                    guard.get_mut().set([value, value]);
+                }
+            });
+        });
+
+        assert_eq!(guarded.snapshot()[0].to_native(), 1000);
+        assert_eq!(guarded.generation(), 2002);
+        let value =
+            deserialize::<Guarded<[u32; 2]>, _, Error>(guarded, &mut ())
+                .unwrap();
+        assert_eq!(value, Guarded([1000, 1000]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn archive_large_hash_map() {
+        let value = (0..100_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761), i))
+            .collect::<HashMap<u32, u32>>();
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<HashMap<u32, u32>>>(&bytes) };
+        assert_eq!(archived.len(), value.len());
+
+        // Lookups which probe past the first group of control bytes must
+        // terminate whether or not the key is present.
+        for (k, v) in value.iter() {
+            let key = Archived::<u32>::from_native(*k);
+            assert_eq!(archived.get(&key).map(|v| v.to_native()), Some(*v));
+        }
+        for k in 0..10_000u32 {
+            let k = k.wrapping_mul(2_654_435_761).wrapping_add(1);
+            if !value.contains_key(&k) {
+                let key = Archived::<u32>::from_native(k);
+                assert!(archived.get(&key).is_none());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn stable_hash_unordered() {
+        use rkyv::digest::stable_hash;
+
+        let mut a = HashMap::new();
+        let mut b = HashMap::new();
+        for i in 0..100u32 {
+            a.insert(i, i.to_string());
+            b.insert(99 - i, (99 - i).to_string());
+        }
+        assert_eq!(stable_hash(&a), stable_hash(&b));
+
+        b.insert(100, "100".to_string());
+        assert_ne!(stable_hash(&a), stable_hash(&b));
+
+        let a = (0..100u32).collect::<HashSet<_>>();
+        let b = (0..100u32).rev().collect::<HashSet<_>>();
+        assert_eq!(stable_hash(&a), stable_hash(&b));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn buffered_writer() {
+        use std::io;
+
+        use rkyv::{
+            ser::{writer::BufferedWriter, Positional as _, Writer},
+            util::serialize_into,
+        };
+
+        #[derive(Default)]
+        struct CountWrites {
+            bytes: Vec<u8>,
+            writes: usize,
+        }
+
+        impl io::Write for CountWrites {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.writes += 1;
+                self.bytes.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = BufferedWriter::with_capacity(
+            IoWriter::new(CountWrites::default()),
+            64,
+        );
+        for i in 0..100u8 {
+            Writer::<Error>::write(&mut writer, &[i; 3]).unwrap();
+        }
+        assert_eq!(writer.pos(), 300);
+        assert_eq!(writer.inner().pos(), 252);
+        assert_eq!(writer.buffer().len(), 48);
+
+        // Writes which don't fit in the buffer bypass it.
+        Writer::<Error>::write(&mut writer, &[100; 100]).unwrap();
+        assert_eq!(writer.pos(), 400);
+        assert!(writer.buffer().is_empty());
+
+        let inner = writer.into_inner::<Error>().unwrap().into_inner();
+        assert_eq!(inner.writes, 6);
+        assert_eq!(inner.bytes.len(), 400);
+        assert!(inner.bytes[..300]
+            .chunks(3)
+            .enumerate()
+            .all(|(i, chunk)| chunk == [i as u8; 3]));
+
+        // Serializing through the buffer produces the same bytes.
+        let value = [Box::new(1u32), Box::new(2), Box::new(3), Box::new(4)];
+        let expected = to_bytes::<Error>(&value).unwrap();
+        let writer = serialize_into::<_, Error>(
+            &value,
+            BufferedWriter::with_capacity(
+                IoWriter::new(CountWrites::default()),
+                8,
+            ),
+        )
+        .unwrap();
+        assert!(writer.buffer().is_empty());
+        let inner = writer.into_inner::<Error>().unwrap().into_inner();
+        assert_eq!(inner.bytes, expected.as_slice());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn hash_map_hasher_selection() {
+        use rkyv::{
+            hash::{FxHasher64, HashAlgorithm, SipHasher13},
+            with::WithHasher,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        struct Indexes {
+            default_map: HashMap<String, u32>,
+            #[with(WithHasher<FxHasher64, 0x1234>)]
+            fx_map: HashMap<String, u32>,
+            #[with(WithHasher<SipHasher13, 0x5678>)]
+            sip_map: HashMap<String, u32>,
+            #[with(WithHasher<FxHasher64, 0x1234>)]
+            fx_set: HashSet<u32>,
+            #[with(WithHasher<SipHasher13, 0x5678>)]
+            sip_set: HashSet<u32>,
+        }
+
+        let map = (0..100u32)
+            .map(|i| (i.to_string(), i))
+            .collect::<HashMap<_, _>>();
+        let set = (0..100u32).map(|i| i * 3).collect::<HashSet<_>>();
+        let value = Indexes {
+            default_map: map.clone(),
+            fx_map: map.clone(),
+            sip_map: map.clone(),
+            fx_set: set.clone(),
+            sip_set: set.clone(),
+        };
+
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedIndexes>(&bytes) };
+
+        let maps = [
+            (&archived.default_map, HashAlgorithm::Default, 0),
+            (&archived.fx_map, HashAlgorithm::FxHash64, 0x1234),
+            (&archived.sip_map, HashAlgorithm::SipHash13, 0x5678),
+        ];
+        for (archived_map, algorithm, seed) in maps {
+            assert_eq!(archived_map.hasher().algorithm(), algorithm);
+            if algorithm != HashAlgorithm::Default {
+                assert_eq!(archived_map.hasher().seed(), seed);
+            }
+            assert_eq!(archived_map.len(), map.len());
+            for (key, value) in map.iter() {
+                let found = archived_map.get(key.as_str());
+                assert_eq!(found.map(|v| v.to_native()), Some(*value));
+            }
+            assert!(archived_map.get("missing").is_none());
+        }
+
+        let sets = [
+            (&archived.fx_set, HashAlgorithm::FxHash64),
+            (&archived.sip_set, HashAlgorithm::SipHash13),
+        ];
+        for (archived_set, algorithm) in sets {
+            assert_eq!(archived_set.hasher().algorithm(), algorithm);
+            for i in 0..300u32 {
+                let key = Archived::<u32>::from_native(i);
+                assert_eq!(archived_set.contains(&key), set.contains(&i));
+            }
+        }
+
+        let deserialized =
+            deserialize::<Indexes, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+    }
 }
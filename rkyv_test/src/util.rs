@@ -1,6 +1,18 @@
 #[cfg(feature = "wasm")]
 wasm_bindgen_test::wasm_bindgen_test_configure!();
 
+/// Asserts that every byte of a serialized archive is initialized.
+///
+/// Reading an uninitialized byte is undefined behavior, so this only reliably
+/// catches uninitialized output (e.g. padding that was not zeroed) when tests
+/// are run under Miri.
+pub fn assert_initialized(bytes: &[u8]) {
+    let checksum = bytes
+        .iter()
+        .fold(0u8, |acc, &byte| acc ^ ::core::hint::black_box(byte));
+    ::core::hint::black_box(checksum);
+}
+
 pub mod core {
     use core::fmt::Debug;
 
@@ -31,6 +43,8 @@ pub mod core {
         let len = serializer.pos();
         let buffer = serializer.writer.inner();
 
+        super::assert_initialized(&buffer[0..len]);
+
         let archived_value =
             unsafe { access_unchecked::<T::Archived>(&buffer[0..len]) };
         assert!(cmp(value, archived_value));
@@ -80,6 +94,8 @@ pub mod alloc {
         let len = serializer.pos();
         let buffer = &serializer.writer;
 
+        super::assert_initialized(&buffer[0..len]);
+
         let archived_value =
             unsafe { access_unchecked::<T::Archived>(&buffer[0..len]) };
         assert!(cmp(value, archived_value));
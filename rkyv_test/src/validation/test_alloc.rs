@@ -490,6 +490,171 @@ mod tests {
         rkyv::from_bytes::<String, Error>(&data.0).unwrap_err();
     }
 
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn shallow_check() {
+        use rkyv::validation::{
+            util::{check_pos_with_context, check_shallow, ShallowCheck},
+            validators::DefaultValidator,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Upload {
+            name: String,
+            chunks: Vec<Vec<u8>>,
+        }
+
+        let value = Upload {
+            name: "a name which is too long to be stored inline".to_string(),
+            chunks: vec![vec![1, 2, 3], vec![4, 5, 6]],
+        };
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+        type T = ArchivedUpload;
+
+        assert!(matches!(
+            check_shallow::<T, Error>(&bytes, 0),
+            ShallowCheck::Incomplete,
+        ));
+        assert!(matches!(
+            check_shallow::<T, Error>(&bytes, 1),
+            ShallowCheck::Incomplete,
+        ));
+        assert!(check_shallow::<T, Error>(&bytes, 2).is_valid());
+
+        // Incomplete checks must not be accepted as valid
+        let mut validator = DefaultValidator::with_shallow_check(&bytes, 0);
+        let root = bytes.len() - core::mem::size_of::<T>();
+        check_pos_with_context::<T, _, Error>(&bytes, root, &mut validator)
+            .unwrap_err();
+        assert!(validator.is_incomplete());
+
+        // Corrupting data below the cutoff is not detected until it is checked
+        let name_pos = bytes.windows(6).position(|w| w == b"a name").unwrap();
+        bytes[name_pos] = 0xff;
+        assert!(matches!(
+            check_shallow::<T, Error>(&bytes, 0),
+            ShallowCheck::Incomplete,
+        ));
+        assert!(check_shallow::<T, Error>(&bytes, 1).is_invalid());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn validation_limits() {
+        use rkyv::validation::{
+            util::access_with_context,
+            validators::{DefaultValidator, ValidationLimits},
+        };
+
+        fn check<T>(bytes: &[u8], limits: ValidationLimits) -> bool
+        where
+            T: rkyv::Portable
+                + CheckBytes<rkyv::rancor::Strategy<DefaultValidator, Error>>,
+        {
+            let mut validator = DefaultValidator::with_limits(bytes, limits);
+            access_with_context::<T, _, Error>(bytes, &mut validator).is_ok()
+        }
+
+        // The root, the outer vec, and the inner vecs are three levels deep
+        let value = vec![vec![1u32, 2], vec![3, 4]];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        type Nested = Archived<Vec<Vec<u32>>>;
+        assert!(check::<Nested>(&bytes, ValidationLimits::new()));
+        assert!(!check::<Nested>(
+            &bytes,
+            ValidationLimits::new().with_max_depth(2),
+        ));
+        assert!(check::<Nested>(
+            &bytes,
+            ValidationLimits::new().with_max_depth(3),
+        ));
+
+        let mut validator = DefaultValidator::new(&bytes);
+        access_with_context::<Nested, _, Error>(&bytes, &mut validator)
+            .unwrap();
+        let claimed = validator.claimed_bytes();
+        assert!(claimed > 0 && claimed <= bytes.len());
+        assert!(check::<Nested>(
+            &bytes,
+            ValidationLimits::new().with_max_claimed_bytes(claimed),
+        ));
+        assert!(!check::<Nested>(
+            &bytes,
+            ValidationLimits::new().with_max_claimed_bytes(claimed - 1),
+        ));
+
+        // Each distinct shared pointer is only registered once
+        let shared = Rc::new(1u32);
+        let value = vec![shared.clone(), shared, Rc::new(2)];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        type Shared = Archived<Vec<Rc<u32>>>;
+        assert!(check::<Shared>(
+            &bytes,
+            ValidationLimits::new().with_max_shared_pointers(2),
+        ));
+        assert!(!check::<Shared>(
+            &bytes,
+            ValidationLimits::new().with_max_shared_pointers(1),
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn path_errors() {
+        use core::mem::size_of;
+
+        use rkyv::{
+            access_unchecked, primitive::ArchivedUsize,
+            validation::path::PathError, vec::ArchivedVec,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct User {
+            name: String,
+            active: bool,
+        }
+
+        let value = vec![
+            User {
+                name: "ferris".to_string(),
+                active: true,
+            },
+            User {
+                name: "crab".to_string(),
+                active: false,
+            },
+        ];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        type Users = ArchivedVec<ArchivedUser>;
+
+        // Invalid fields are reported with their path from the root
+        let mut corrupt = bytes.clone();
+        let users = unsafe { access_unchecked::<Users>(&corrupt) };
+        let active = &users[1].active as *const bool as usize
+            - corrupt.as_ptr() as usize;
+        corrupt[active] = 2;
+        let error = access::<Users, PathError>(&corrupt).unwrap_err();
+        assert_eq!(error.path().to_string(), "root[1].active");
+        assert_eq!(error.offset(), None);
+
+        // Out-of-bounds pointers are reported with their offset
+        let mut corrupt = bytes.clone();
+        let len = corrupt.len() - size_of::<ArchivedUsize>();
+        unsafe {
+            corrupt
+                .as_mut_ptr()
+                .add(len)
+                .cast::<ArchivedUsize>()
+                .write_unaligned(ArchivedUsize::from_native(1000));
+        }
+        let error = access::<Users, PathError>(&corrupt).unwrap_err();
+        assert_eq!(error.path().to_string(), "root");
+        assert!(error.offset().is_some());
+        assert!(error.to_string().starts_with("invalid data at `root`"));
+    }
+
     #[test]
     fn rc_btreemap() {
         use rkyv::{Archive, Deserialize, Serialize};
@@ -548,4 +713,172 @@ mod tests {
             let _ = access::<ArchivedJsonValue, Failure>(&buf).unwrap();
         }
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn static_archive() {
+        use rkyv::{include_bytes_aligned, util::StaticArchive};
+
+        static BYTES: &[u8] = include_bytes_aligned!("fixtures/bytes.rkyv");
+        static ARCHIVE: StaticArchive<[u8; 8]> = StaticArchive::new(BYTES);
+        static INVALID: StaticArchive<u64> = StaticArchive::new(&[0; 3]);
+
+        assert_eq!(BYTES.as_ptr() as usize % 16, 0);
+        assert!(!ARCHIVE.is_validated());
+        assert_eq!(*ARCHIVE, *b"rkyv!!!!");
+        assert!(ARCHIVE.is_validated());
+        assert_eq!(ARCHIVE.get::<Error>().unwrap(), b"rkyv!!!!");
+
+        assert!(INVALID.get::<Error>().is_err());
+        assert!(!INVALID.is_validated());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn include_bytes_aligned() {
+        use rkyv::include_bytes_aligned;
+
+        let default: &[u8] = include_bytes_aligned!("fixtures/bytes.rkyv");
+        let page: &[u8] = include_bytes_aligned!(4096, "fixtures/bytes.rkyv");
+        let small: &[u8] = include_bytes_aligned!(2, "fixtures/bytes.rkyv",);
+
+        assert_eq!(default.as_ptr() as usize % 16, 0);
+        assert_eq!(page.as_ptr() as usize % 4096, 0);
+        assert_eq!(small.as_ptr() as usize % 2, 0);
+        assert_eq!(default, b"rkyv!!!!");
+        assert_eq!(page, default);
+        assert_eq!(small, default);
+
+        let archived = access::<[u8; 8], Error>(page).unwrap();
+        assert_eq!(archived, b"rkyv!!!!");
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_sorted_vec() {
+        use rkyv::{
+            collections::sorted_vec::ArchivedSortedVec, primitive::ArchivedU32,
+            with::AsSortedSlice,
+        };
+
+        #[derive(Archive, Serialize)]
+        #[archive(check_bytes)]
+        struct Postings {
+            #[with(AsSortedSlice)]
+            ids: Vec<u32>,
+        }
+
+        let value = Postings {
+            ids: vec![5, 3, 9, 1],
+        };
+        let buf = to_bytes::<Error>(&value).unwrap();
+        let archived = access::<ArchivedPostings, Error>(&buf).unwrap();
+        assert_eq!(
+            archived
+                .ids
+                .iter()
+                .map(|v| v.to_native())
+                .collect::<Vec<_>>(),
+            [1, 3, 5, 9],
+        );
+
+        // A plain vec has the same layout as a sorted vec.
+        let sorted = to_bytes::<Error>(&vec![1u32, 3, 3, 9]).unwrap();
+        access::<ArchivedSortedVec<ArchivedU32>, Error>(&sorted).unwrap();
+        let unsorted = to_bytes::<Error>(&vec![1u32, 9, 3]).unwrap();
+        access::<ArchivedSortedVec<ArchivedU32>, Error>(&unsorted).unwrap_err();
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_nested_on_deserialize() {
+        use rkyv::{util::deserialize, with::Nested};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Inner {
+            values: Vec<u32>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[archive(check_bytes)]
+        struct Outer {
+            #[with(Nested)]
+            inner: Inner,
+        }
+
+        let value = Outer {
+            inner: Inner {
+                values: vec![1, 2, 3],
+            },
+        };
+        let mut buf = to_bytes::<Error>(&value).unwrap();
+        let archived = access::<ArchivedOuter, Error>(&buf).unwrap();
+        let deserialized =
+            deserialize::<Outer, _, Error>(archived, &mut ()).unwrap();
+        assert_eq!(deserialized, value);
+
+        // Corrupt the root of the nested archive so its vec points outside of
+        // the nested bytes.
+        let nested = to_bytes::<Error>(&value.inner).unwrap();
+        let start = buf
+            .windows(nested.len())
+            .position(|window| window == nested.as_slice())
+            .unwrap();
+        let end = start + nested.len();
+        buf[end - 4..end].copy_from_slice(&[0xff; 4]);
+
+        // The outer archive only checks the nested bytes, not their contents.
+        let archived = access::<ArchivedOuter, Error>(&buf).unwrap();
+        deserialize::<Outer, _, Error>(archived, &mut ()).unwrap_err();
+    }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn check_merkle_tree_shape() {
+        use rkyv::merkle::{ArchivedMerkleTree, MerkleTree, SipMerkleHasher};
+
+        let hasher = SipMerkleHasher::new_with_keys(3, 5);
+        let data = (0..=255u8).collect::<Vec<_>>();
+
+        let tree = MerkleTree::from_bytes(&data, 100, &hasher);
+        let buf = to_bytes::<Error>(&tree).unwrap();
+        access::<ArchivedMerkleTree<u64>, Error>(&buf).unwrap();
+
+        // The leaves must cover exactly the hashed length
+        let short = MerkleTree::from_leaves(
+            100,
+            data.len(),
+            tree.leaves()[1..].to_vec(),
+            &hasher,
+        );
+        let buf = to_bytes::<Error>(&short).unwrap();
+        access::<ArchivedMerkleTree<u64>, Error>(&buf).unwrap_err();
+    }
+
+    #[test]
+    #[cfg(feature = "static-errors")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn static_error_offset() {
+        use core::mem::size_of;
+
+        use rkyv::static_error::StaticError;
+
+        let bytes = to_bytes::<StaticError>(&vec![1u32, 2, 3]).unwrap();
+        access::<Archived<Vec<u32>>, StaticError>(&bytes).unwrap();
+
+        // Cutting off the elements leaves the root pointing outside of the
+        // buffer.
+        let root = bytes.len() - size_of::<Archived<Vec<u32>>>();
+        let truncated = &bytes[root - 4..];
+        let error = access_pos::<Archived<Vec<u32>>, StaticError>(truncated, 4)
+            .unwrap_err();
+        assert_eq!(error.offset(), Some(4));
+
+        let other = access::<Archived<Vec<u32>>, StaticError>(&bytes[root..])
+            .unwrap_err();
+        assert_eq!(other.offset(), Some(0));
+        assert_eq!(other.code(), error.code());
+        assert_eq!(other.name(), error.name());
+    }
 }
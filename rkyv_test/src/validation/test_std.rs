@@ -27,4 +27,62 @@ mod tests {
         set.insert("baz".to_string());
         serialize_and_check::<_, Error>(&set);
     }
+
+    #[test]
+    #[cfg_attr(feature = "wasm", wasm_bindgen_test)]
+    fn parallel_validation() {
+        use core::{mem::size_of, num::NonZeroUsize};
+
+        use rkyv::{
+            access, access_unchecked, string::ArchivedString, to_bytes,
+            validation::validators::access_vec_parallel, vec::ArchivedVec,
+            Archived, RelPtr,
+        };
+
+        let value = (0..100)
+            .map(|i| format!("a string long enough to be out of line {i}"))
+            .collect::<Vec<_>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        for threads in [1, 3, 8, 200] {
+            let threads = NonZeroUsize::new(threads).unwrap();
+            let archived =
+                access_vec_parallel::<ArchivedString, Error>(&bytes, threads)
+                    .unwrap();
+            assert_eq!(archived.len(), value.len());
+            for (archived, value) in archived.iter().zip(value.iter()) {
+                assert_eq!(archived, value);
+            }
+        }
+
+        let bytes = to_bytes::<Error>(&Vec::<String>::new()).unwrap();
+        let threads = NonZeroUsize::new(4).unwrap();
+        assert!(
+            access_vec_parallel::<ArchivedString, Error>(&bytes, threads)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Point the second vec at the middle of the first one. The chunks are
+        // valid on their own, but their subtrees overlap.
+        let value = vec![(0u32..8).collect::<Vec<_>>(), vec![8, 9]];
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+        let (first, second) = {
+            let archived =
+                unsafe { access_unchecked::<Archived<Vec<Vec<u32>>>>(&bytes) };
+            let base = bytes.as_ptr() as usize;
+            (
+                &archived[0] as *const ArchivedVec<_> as usize - base,
+                &archived[1] as *const ArchivedVec<_> as usize - base,
+            )
+        };
+        let len = size_of::<RelPtr<Archived<u32>>>();
+        bytes.copy_within(first..first + len, second);
+
+        assert!(access::<Archived<Vec<Vec<u32>>>, Error>(&bytes).is_err());
+        let threads = NonZeroUsize::new(2).unwrap();
+        assert!(access_vec_parallel::<ArchivedVec<Archived<u32>>, Error>(
+            &bytes, threads,
+        )
+        .is_err());
+    }
 }